@@ -0,0 +1,91 @@
+//! Client hot paths: key encoding, message wire format and account decoding
+//!
+//! Run with `cargo bench --bench client_hot_paths`; response parsing lives in
+//! the `json_parsing` bench.
+
+use base64::{engine::general_purpose, Engine as _};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use gloo_solana::application::flows::TransactionBuilder;
+use gloo_solana::domain::message::Transaction;
+use gloo_solana::domain::programs::system;
+use gloo_solana::infrastructure::rpc::AccountData;
+use gloo_solana::{Hash, Pubkey};
+use serde_json::json;
+
+/// The USDC mint compressed with zstd, as `base64+zstd` returns it
+const USDC_MINT_ZSTD: &str = "KLUv/SRSkQIAAQAAAJj+huiNm+Lqi8HMpIeLKYjCQPUrhCS/tA7Rot3LXhmbsDkznjXVHwAGAQEAAABicKqKWcWUBbRShshncubNEm6bil06OFNtN/e0FOi2Z0rZ8s8=";
+
+fn bench_base58(c: &mut Criterion) {
+    let pubkey = Pubkey::new([7; 32]);
+    let encoded = pubkey.to_base58();
+    let mut group = c.benchmark_group("base58");
+
+    group.bench_function("pubkey_encode", |b| {
+        b.iter(|| black_box(&pubkey).to_base58())
+    });
+    group.bench_function("pubkey_decode", |b| {
+        b.iter(|| Pubkey::from_base58(black_box(&encoded)).unwrap())
+    });
+    group.finish();
+}
+
+/// An unsigned transaction with `transfers` transfers to distinct recipients
+fn transfers(transfers: u8) -> Transaction {
+    let payer = Pubkey::new([1; 32]);
+    (0..transfers)
+        .fold(TransactionBuilder::new(payer), |builder, i| {
+            builder.instruction(system::transfer(payer, Pubkey::new([i + 2; 32]), 5))
+        })
+        .build(Hash::new([3; 32]))
+        .unwrap()
+}
+
+fn bench_messages(c: &mut Criterion) {
+    let mut group = c.benchmark_group("message");
+
+    for count in [1, 20] {
+        let transaction = transfers(count);
+        let bytes = transaction.serialize().unwrap();
+        group.throughput(Throughput::Bytes(bytes.len() as u64));
+
+        group.bench_with_input(BenchmarkId::new("compile", count), &count, |b, &count| {
+            b.iter(|| transfers(black_box(count)))
+        });
+        group.bench_with_input(
+            BenchmarkId::new("serialize", count),
+            &transaction,
+            |b, transaction| b.iter(|| transaction.serialize().unwrap()),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("deserialize", count),
+            &bytes,
+            |b, bytes| b.iter(|| Transaction::deserialize(bytes).unwrap()),
+        );
+    }
+    group.finish();
+}
+
+fn bench_account_data(c: &mut Criterion) {
+    let token_account = general_purpose::STANDARD.encode([9u8; 165]);
+    let encodings: [(&str, AccountData); 2] = [
+        (
+            "base64",
+            serde_json::from_value(json!([token_account, "base64"])).unwrap(),
+        ),
+        (
+            "base64_zstd",
+            serde_json::from_value(json!([USDC_MINT_ZSTD, "base64+zstd"])).unwrap(),
+        ),
+    ];
+    let mut group = c.benchmark_group("account_data");
+
+    for (name, data) in &encodings {
+        group.bench_with_input(BenchmarkId::new("decode", name), data, |b, data| {
+            b.iter(|| data.decode().unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_base58, bench_messages, bench_account_data);
+criterion_main!(benches);
@@ -0,0 +1,54 @@
+//! Response parsing throughput on a large `getProgramAccounts` body
+//!
+//! Run with `cargo bench --features simd-json` to compare simd-json against
+//! serde_json.
+
+use base64::{engine::general_purpose, Engine as _};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use gloo_solana::infrastructure::json::{JsonParser, SerdeJsonParser};
+use serde_json::json;
+
+/// A `getProgramAccounts` response with `count` 165-byte token accounts
+fn program_accounts_body(count: usize) -> String {
+    let accounts: Vec<_> = (0..count)
+        .map(|i| {
+            json!({
+                "pubkey": bs58::encode([(i % 256) as u8; 32]).into_string(),
+                "account": {
+                    "lamports": 2_039_280u64 + i as u64,
+                    "owner": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+                    "data": [general_purpose::STANDARD.encode([(i % 251) as u8; 165]), "base64"],
+                    "executable": false,
+                    "rentEpoch": u64::MAX,
+                    "space": 165,
+                }
+            })
+        })
+        .collect();
+    json!({ "jsonrpc": "2.0", "result": accounts, "id": 1 }).to_string()
+}
+
+fn bench_parsers(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse_program_accounts");
+    group.sample_size(20);
+
+    for count in [1_000, 10_000] {
+        let body = program_accounts_body(count);
+        group.throughput(Throughput::Bytes(body.len() as u64));
+
+        group.bench_with_input(BenchmarkId::new("serde_json", count), &body, |b, body| {
+            b.iter(|| SerdeJsonParser.parse(body.clone()).unwrap())
+        });
+
+        #[cfg(feature = "simd-json")]
+        group.bench_with_input(BenchmarkId::new("simd_json", count), &body, |b, body| {
+            use gloo_solana::infrastructure::json::SimdJsonParser;
+            b.iter(|| SimdJsonParser.parse(body.clone()).unwrap())
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_parsers);
+criterion_main!(benches);
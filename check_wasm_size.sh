@@ -0,0 +1,76 @@
+#!/bin/bash
+
+# WASM Bundle Size Check for gloo_solana
+# Builds the minimal browser configuration, optimizes it with wasm-opt and
+# fails if the result grows past the size budget. Uses twiggy (if installed)
+# to list the largest contributors.
+
+set -e  # Exit on any error
+
+echo "📦 gloo_solana WASM Size Check"
+echo "=============================="
+
+# Colors for output
+RED='\033[0;31m'
+GREEN='\033[0;32m'
+BLUE='\033[0;34m'
+NC='\033[0m' # No Color
+
+print_status() {
+    echo -e "${BLUE}[INFO]${NC} $1"
+}
+
+print_success() {
+    echo -e "${GREEN}[SUCCESS]${NC} $1"
+}
+
+print_error() {
+    echo -e "${RED}[ERROR]${NC} $1"
+}
+
+# Budget for the optimized minimal build, in KiB. Raise deliberately, in the
+# same commit as the change that needs it.
+BUDGET_KB="${WASM_SIZE_BUDGET_KB:-450}"
+FEATURES="${WASM_FEATURES:-}"
+TARGET_DIR="target/wasm32-unknown-unknown/release"
+ARTIFACT="$TARGET_DIR/gloo_solana.wasm"
+OPTIMIZED="$TARGET_DIR/gloo_solana.opt.wasm"
+
+if ! rustup target list --installed | grep -q wasm32-unknown-unknown; then
+    print_status "Installing wasm32-unknown-unknown target..."
+    rustup target add wasm32-unknown-unknown
+fi
+
+print_status "Building minimal browser configuration (features: '${FEATURES:-none}')..."
+cargo build --lib --release --target wasm32-unknown-unknown \
+    --no-default-features ${FEATURES:+--features "$FEATURES"}
+
+if command -v wasm-opt &> /dev/null; then
+    print_status "Optimizing with wasm-opt -Oz..."
+    wasm-opt -Oz --strip-debug "$ARTIFACT" -o "$OPTIMIZED"
+else
+    print_error "wasm-opt not found (install binaryen); checking the unoptimized artifact"
+    cp "$ARTIFACT" "$OPTIMIZED"
+fi
+
+SIZE_BYTES=$(wc -c < "$OPTIMIZED")
+SIZE_KB=$((SIZE_BYTES / 1024))
+
+if command -v twiggy &> /dev/null; then
+    print_status "Largest items:"
+    twiggy top -n 15 "$OPTIMIZED"
+fi
+
+# Native-only crates must never reach the browser build
+if cargo tree --target wasm32-unknown-unknown --no-default-features \
+    ${FEATURES:+--features "$FEATURES"} -e normal | grep -E "^.*(reqwest|tokio|hyper) v" ; then
+    print_error "Native-only dependencies found in the wasm32 dependency tree"
+    exit 1
+fi
+
+if [ "$SIZE_KB" -gt "$BUDGET_KB" ]; then
+    print_error "WASM size ${SIZE_KB} KiB exceeds budget of ${BUDGET_KB} KiB"
+    exit 1
+fi
+
+print_success "WASM size ${SIZE_KB} KiB is within budget of ${BUDGET_KB} KiB"
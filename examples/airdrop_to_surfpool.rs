@@ -18,7 +18,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
     // Create RPC client
     let client = RpcClientBuilder::new(surfpool_network().endpoint())
         .commitment(CommitmentLevel::Confirmed)
-        .build();
+        .build()?;
 
     println!("🔌 Connecting to surfpool at: {}", client.endpoint());
 
@@ -90,7 +90,7 @@ fn test_rpc_client_creation() -> Result<(), Box<dyn Error>> {
     for network in networks {
         let client = RpcClientBuilder::new(network.endpoint())
             .commitment(CommitmentLevel::Confirmed)
-            .build();
+            .build()?;
 
         println!("   Created client for: {}", network.endpoint());
         assert_eq!(client.endpoint(), network.endpoint());
@@ -161,7 +161,8 @@ mod tests {
     fn test_rpc_client_builder() {
         let client = RpcClientBuilder::new("http://localhost:8899")
             .commitment(CommitmentLevel::Finalized)
-            .build();
+            .build()
+            .unwrap();
 
         assert_eq!(client.endpoint(), "http://localhost:8899");
     }
@@ -7,7 +7,7 @@
 #[cfg(feature = "dioxus")]
 use dioxus::prelude::*;
 #[cfg(feature = "dioxus")]
-use gloo_solana::{constants::SYSTEM_PROGRAM_ID, surfpool_network, Network, Pubkey};
+use gloo_solana::{constants::SYSTEM_PROGRAM_ID, surfpool_network, validate_pubkey_input, Network};
 
 #[cfg(feature = "dioxus")]
 #[cfg(target_arch = "wasm32")]
@@ -95,7 +95,7 @@ fn App() -> Element {
             // Simulate network delay in web
             gloo_timers::future::sleep(std::time::Duration::from_millis(1000)).await;
 
-            match pubkey_str.parse::<Pubkey>() {
+            match validate_pubkey_input(&pubkey_str) {
                 Ok(_pubkey) => {
                     let mock_balance = 1_000_000_000u64;
                     let current_network = state.read().network.clone();
@@ -116,7 +116,7 @@ fn App() -> Element {
                         selected_pubkey: current_pubkey,
                         balance: None,
                         loading: false,
-                        error: Some(format!("Invalid pubkey: {}", e)),
+                        error: Some(e.to_string()),
                     });
                 }
             }
@@ -125,7 +125,7 @@ fn App() -> Element {
         #[cfg(not(target_arch = "wasm32"))]
         {
             // Desktop version - immediate mock response
-            match pubkey_str.parse::<Pubkey>() {
+            match validate_pubkey_input(&pubkey_str) {
                 Ok(_pubkey) => {
                     let mock_balance = 1_000_000_000u64;
                     let current_network = state.read().network.clone();
@@ -146,7 +146,7 @@ fn App() -> Element {
                         selected_pubkey: current_pubkey,
                         balance: None,
                         loading: false,
-                        error: Some(format!("Invalid pubkey: {}", e)),
+                        error: Some(e.to_string()),
                     });
                 }
             }
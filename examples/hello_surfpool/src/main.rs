@@ -29,10 +29,7 @@ impl HelloAccount {
         Self {
             name,
             message,
-            created_at: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
+            created_at: gloo_solana::infrastructure::time::unix_timestamp(),
         }
     }
 
@@ -55,7 +52,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
     // Create RPC client for surfpool
     let client = RpcClientBuilder::new(surfpool_network().endpoint())
         .commitment(CommitmentLevel::Confirmed)
-        .build();
+        .build()?;
 
     println!("✅ Connected to surfpool at: {}", client.endpoint());
 
@@ -190,11 +187,10 @@ async fn explore_clock_sysvar(client: &gloo_solana::SolanaRpcClient) -> Result<(
                 println!("   Current Unix Timestamp: {}", timestamp);
                 println!("   Current Slot: {}", slot);
 
-                // Convert timestamp to human-readable format
-                let datetime = std::time::UNIX_EPOCH + std::time::Duration::from_secs(timestamp);
-                if let Ok(datetime) = datetime.elapsed() {
-                    println!("   Time: {} seconds ago", datetime.as_secs());
-                }
+                println!(
+                    "   Time: {}",
+                    gloo_solana::format_timestamp_relative(timestamp as i64)
+                );
             }
         }
         None => {
@@ -273,7 +269,9 @@ mod tests {
 
     #[tokio::test]
     async fn test_surfpool_connectivity() {
-        let client = RpcClientBuilder::new(surfpool_network().endpoint()).build();
+        let client = RpcClientBuilder::new(surfpool_network().endpoint())
+            .build()
+            .unwrap();
 
         // This test requires surfpool to be running
         let result = client.get_block_height().await;
@@ -2,10 +2,14 @@
 //!
 //! This example demonstrates how to work with Solana account data using gloo_solana
 //! It simulates creating an account with "hello surf" message and shows data handling
-//! This version works without requiring actual network connections or WASM
+//! This version works without requiring actual network connections or WASM:
+//! accounts are created by running system instructions on an `AccountExecutor`
+//!
+//! Run with: cargo run --example hello_surfpool_demo --features svm-lite
 
 use gloo_solana::{
-    constants::SYSTEM_PROGRAM_ID, surfpool_network, CommitmentLevel, Pubkey, RpcClientBuilder,
+    constants::SYSTEM_PROGRAM_ID, domain::programs::system, surfpool_network, AccountExecutor,
+    CommitmentLevel, Pubkey, RpcClientBuilder,
 };
 use serde::{Deserialize, Serialize};
 use std::error::Error;
@@ -31,10 +35,7 @@ impl HelloAccount {
         Self {
             name,
             message,
-            created_at: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
+            created_at: gloo_solana::infrastructure::time::unix_timestamp(),
             owner,
             balance: 0,
         }
@@ -56,30 +57,31 @@ impl HelloAccount {
     }
 }
 
-/// Simulated account data for demonstration
-#[derive(Debug, Clone)]
-pub struct SimulatedAccount {
-    pub pubkey: Pubkey,
-    pub lamports: u64,
-    pub data: Vec<u8>,
-    pub owner: Pubkey,
-    pub executable: bool,
-    pub rent_epoch: u64,
-}
-
-impl SimulatedAccount {
-    /// Create a simulated account from HelloAccount
-    fn from_hello_account(hello: &HelloAccount) -> Result<Self, serde_json::Error> {
-        let data = hello.to_bytes()?;
-        Ok(Self {
-            pubkey: generate_random_pubkey(),
-            lamports: hello.balance,
-            data,
-            owner: hello.owner,
-            executable: false,
-            rent_epoch: 0,
-        })
-    }
+/// Payer funding the accounts created by the demo
+const PAYER: Pubkey = Pubkey::new([7; 32]);
+
+/// Create an account for `hello` on the executor and write its data
+///
+/// The executor runs the system program's `CreateAccount`; the data is then
+/// written directly, standing in for the program that would own it.
+fn create_hello_account(
+    executor: &mut AccountExecutor,
+    hello: &HelloAccount,
+) -> Result<gloo_solana::Account, Box<dyn Error>> {
+    let data = hello.to_bytes()?;
+    let pubkey = generate_random_pubkey();
+    executor.execute(&[system::create_account(
+        PAYER,
+        pubkey,
+        hello.balance,
+        data.len() as u64,
+        &hello.owner,
+    )])?;
+
+    let mut account = executor.account(&pubkey).cloned().ok_or("account missing")?;
+    account.data = data;
+    executor.set_account(account.clone());
+    Ok(account)
 }
 
 /// Generate a random-looking pubkey for demonstration
@@ -145,8 +147,9 @@ fn main() -> Result<(), Box<dyn Error>> {
     println!("   Base64: {}", base64_str);
     println!();
 
-    // Create simulated account
-    let simulated_account = SimulatedAccount::from_hello_account(&hello_account)?;
+    // Create the account on an in-memory executor
+    let mut executor = AccountExecutor::new().with_balance(PAYER, 1_000_000_000);
+    let simulated_account = create_hello_account(&mut executor, &hello_account)?;
     println!("🏦 Simulated Account:");
     println!("   Pubkey: {}", simulated_account.pubkey);
     println!("   Lamports: {}", simulated_account.lamports);
@@ -178,7 +181,7 @@ fn main() -> Result<(), Box<dyn Error>> {
         let account =
             HelloAccount::new(username.to_string(), message.to_string(), SYSTEM_PROGRAM_ID);
 
-        let simulated = SimulatedAccount::from_hello_account(&account)?;
+        let simulated = create_hello_account(&mut executor, &account)?;
         println!(
             "   {}: {} -> {} bytes",
             username,
@@ -224,7 +227,7 @@ fn main() -> Result<(), Box<dyn Error>> {
     println!("🔧 RPC Client Configuration:");
     let client = RpcClientBuilder::new(surfpool_network().endpoint())
         .commitment(CommitmentLevel::Confirmed)
-        .build();
+        .build()?;
     println!("   Client created for: {}", client.endpoint());
     println!("   Commitment: Confirmed");
     println!();
@@ -316,7 +319,8 @@ mod tests {
             SYSTEM_PROGRAM_ID,
         );
 
-        let simulated = SimulatedAccount::from_hello_account(&hello).unwrap();
+        let mut executor = AccountExecutor::new().with_balance(PAYER, 1_000);
+        let simulated = create_hello_account(&mut executor, &hello).unwrap();
 
         assert_eq!(simulated.owner, SYSTEM_PROGRAM_ID);
         assert_eq!(simulated.lamports, 0);
@@ -0,0 +1,138 @@
+//! Program Deployment on Surfpool
+//!
+//! Creates program-owned accounts and writes bytes into them with real
+//! signed transactions:
+//! 1. Funds a fresh payer with an airdrop
+//! 2. Creates a rent-exempt account owned by a program
+//! 3. Creates an upgradeable loader buffer and writes a program binary into
+//!    it chunk by chunk, the first half of a deployment
+//! 4. Reads the buffer back and checks every byte landed
+//!
+//! Run with: surfpool start, then
+//! cargo run --example program_deployment -- --program target/deploy/my_program.so
+
+use clap::{Arg, Command};
+use gloo_solana::{
+    surfpool_network, CommitmentLevel, ConfirmationConfig, DataAccountService, Keypair, Pubkey,
+    RpcClientBuilder, Signer, TransactionConfirmer,
+};
+use std::error::Error;
+
+/// Airdropped to the payer, enough for rent on a small program buffer
+const AIRDROP_LAMPORTS: u64 = 5_000_000_000;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    let matches = Command::new("program_deployment")
+        .about("Create program-owned accounts and write a program buffer on surfpool")
+        .arg(
+            Arg::new("program")
+                .short('p')
+                .long("program")
+                .value_name("FILE")
+                .help("Program binary to write into the buffer, a small stand-in if omitted"),
+        )
+        .arg(
+            Arg::new("owner")
+                .short('o')
+                .long("owner")
+                .value_name("PUBKEY")
+                .help("Program owning the data account")
+                .default_value("Memo1UhkJRfHyvLMcVucJwxXeuD728EqVDDwQDxFMNo"),
+        )
+        .arg(
+            Arg::new("space")
+                .short('s')
+                .long("space")
+                .value_name("BYTES")
+                .help("Size of the data account")
+                .default_value("64"),
+        )
+        .get_matches();
+
+    let owner = Pubkey::from_base58(matches.get_one::<String>("owner").unwrap())?;
+    let space: usize = matches.get_one::<String>("space").unwrap().parse()?;
+    let program_bytes = match matches.get_one::<String>("program") {
+        Some(path) => std::fs::read(path)?,
+        None => b"hello surf! ".repeat(200),
+    };
+
+    println!("🚀 Program Deployment on Surfpool");
+    println!("=================================");
+
+    let network = surfpool_network();
+    let client = RpcClientBuilder::new(network.endpoint())
+        .commitment(CommitmentLevel::Confirmed)
+        .build()?;
+    println!("🌐 Endpoint: {}", network.endpoint());
+    println!("📊 Block height: {}", client.get_block_height().await?);
+
+    // 1. Fund a fresh payer
+    let payer = Keypair::new()?;
+    println!("\n💸 Airdropping to payer {}", payer.pubkey());
+    let airdrop = client
+        .request_airdrop(&payer.pubkey(), AIRDROP_LAMPORTS)
+        .await?;
+    TransactionConfirmer::new(
+        client.clone(),
+        ConfirmationConfig::for_endpoint(network.endpoint()),
+    )
+    .confirm(&airdrop)
+    .await?;
+    println!(
+        "   ✅ Balance: {} lamports",
+        client.get_balance(&payer.pubkey()).await?
+    );
+
+    let accounts = DataAccountService::new(client.clone());
+
+    // 2. A rent-exempt account owned by a program
+    let data_account = Keypair::new()?;
+    println!("\n📦 Creating a {}-byte account owned by {}", space, owner);
+    let created = accounts
+        .create_account(&payer, &data_account, space, &owner)
+        .await?;
+    println!("   ✅ {} in slot {}", data_account.pubkey(), created.slot);
+    println!(
+        "   🔗 {}",
+        network.explorer_account_url(&data_account.pubkey())
+    );
+
+    // 3. A loader buffer holding the program binary
+    let buffer = Keypair::new()?;
+    println!(
+        "\n📝 Writing {} bytes into buffer {}",
+        program_bytes.len(),
+        buffer.pubkey()
+    );
+    let sent = accounts
+        .create_buffer(&payer, &buffer, &program_bytes)
+        .await?;
+    for (i, transaction) in sent.iter().enumerate() {
+        let step = if i == 0 {
+            "create + initialize"
+        } else {
+            "write"
+        };
+        println!("   ✅ {:<20} {}", step, transaction.signature);
+    }
+
+    // 4. Verify the written bytes
+    let written = accounts
+        .read_buffer(&buffer.pubkey(), program_bytes.len())
+        .await?;
+    if written != program_bytes {
+        return Err("buffer contents differ from the program binary".into());
+    }
+    println!(
+        "\n🎉 Buffer verified: {} bytes in {} transactions",
+        written.len(),
+        sent.len()
+    );
+    println!(
+        "   Deploy it with `solana program deploy --buffer {}`",
+        buffer.pubkey()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,91 @@
+//! Program Deployment Demo
+//!
+//! Shows the transactions the `program_deployment` example sends, without a
+//! network: the data account is created by running the system program's
+//! `CreateAccount` on an in-memory `AccountExecutor`, and the loader buffer
+//! writes are planned chunk by chunk.
+//!
+//! Run with: cargo run --example program_deployment_demo --features svm-lite
+
+use gloo_solana::domain::programs::loader::{self, BUFFER_METADATA_SIZE, MAX_WRITE_CHUNK};
+use gloo_solana::domain::programs::system;
+use gloo_solana::domain::rent::rent_exempt_minimum;
+use gloo_solana::{AccountExecutor, Hash, Keypair, Pubkey, Signer, TransactionBuilder};
+use std::error::Error;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    println!("🚀 Program Deployment Demo");
+    println!("==========================");
+
+    let payer = Keypair::from_seed(&[1; 32]);
+    let data_account = Keypair::from_seed(&[2; 32]);
+    let buffer = Keypair::from_seed(&[3; 32]);
+    let owner = Pubkey::from_base58("Memo1UhkJRfHyvLMcVucJwxXeuD728EqVDDwQDxFMNo")?;
+    let program_bytes = b"hello surf! ".repeat(200);
+
+    // A rent-exempt account owned by a program, created on the executor
+    let mut executor = AccountExecutor::new().with_balance(payer.pubkey(), 5_000_000_000);
+    let space = 64;
+    let create = system::create_account(
+        payer.pubkey(),
+        data_account.pubkey(),
+        rent_exempt_minimum(space),
+        space as u64,
+        &owner,
+    );
+    executor.execute(std::slice::from_ref(&create))?;
+    let account = executor
+        .account(&data_account.pubkey())
+        .ok_or("account missing")?;
+    println!("\n📦 Created {}", data_account.pubkey());
+    println!("   Owner: {}", account.owner);
+    println!(
+        "   Size: {} bytes, {} lamports",
+        account.data.len(),
+        account.lamports
+    );
+    println!(
+        "   Payer left with {} lamports",
+        executor.balance(&payer.pubkey())
+    );
+
+    // The loader buffer transactions, as DataAccountService::create_buffer sends them
+    let space = BUFFER_METADATA_SIZE + program_bytes.len();
+    let setup = TransactionBuilder::new(payer.pubkey())
+        .instruction(system::create_account(
+            payer.pubkey(),
+            buffer.pubkey(),
+            rent_exempt_minimum(space),
+            space as u64,
+            &loader::BPF_LOADER_UPGRADEABLE_ID,
+        ))
+        .instruction(loader::initialize_buffer(buffer.pubkey(), payer.pubkey()));
+    println!(
+        "\n📝 Buffer {} for {} bytes",
+        buffer.pubkey(),
+        program_bytes.len()
+    );
+    for signer in setup.signers() {
+        println!("   Setup transaction signer: {}", signer);
+    }
+
+    let writes = loader::write_chunks(buffer.pubkey(), payer.pubkey(), &program_bytes);
+    for (i, write) in writes.iter().enumerate() {
+        let transaction = TransactionBuilder::new(payer.pubkey())
+            .instruction(write.clone())
+            .build(Hash::new([0; 32]))?;
+        println!(
+            "   write {}: offset {:>5}, {} bytes on the wire",
+            i,
+            i * MAX_WRITE_CHUNK,
+            transaction.serialize()?.len()
+        );
+    }
+
+    println!(
+        "\n🎉 {} transactions would deploy the buffer",
+        writes.len() + 1
+    );
+    println!("   Run the program_deployment example against surfpool to send them");
+    Ok(())
+}
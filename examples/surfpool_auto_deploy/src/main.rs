@@ -8,8 +8,6 @@
 
 use clap::{Arg, Command};
 use std::error::Error;
-use std::fs;
-
 use std::path::PathBuf;
 use std::process::Command as ProcessCommand;
 use std::time::Duration;
@@ -17,7 +15,9 @@ use std::time::Duration;
 use tokio::time::sleep;
 
 // Import gloo_solana for program interaction
-use gloo_solana::{domain::types::Pubkey, surfpool_network, CommitmentLevel, RpcClientBuilder};
+use gloo_solana::{
+    domain::types::Pubkey, surfpool_network, AnchorWorkspace, CommitmentLevel, RpcClientBuilder,
+};
 use serde::{Deserialize, Serialize};
 
 #[tokio::main]
@@ -84,20 +84,18 @@ async fn main() -> Result<(), Box<dyn Error>> {
     println!("✅ Found Anchor.toml at: {:?}", anchor_toml);
 
     // Parse configuration
-    let config = parse_anchor_config(&anchor_toml)?;
+    let workspace = load_workspace(&anchor_toml)?;
 
     // Get program ID from config
-    let (program_name, program_id_str) = config
-        .programs
-        .localnet
-        .iter()
+    let program = workspace
+        .programs("localnet")?
+        .into_iter()
         .next()
         .ok_or("No program found in configuration")?;
+    let program_id = program.program_id;
 
-    let program_id = Pubkey::from_base58(program_id_str)
-        .map_err(|e| format!("Invalid program ID {}: {}", program_id_str, e))?;
-
-    println!("📦 Target program: {} ({})", program_name, program_id);
+    println!("📦 Target program: {} ({})", program.name, program_id);
+    println!("   Binary: {:?}", program.so_path);
 
     // Step 1: Check if surfpool is running, deploy if needed
     // Step 1: Deploy using automated surfpool start
@@ -137,22 +135,30 @@ async fn main() -> Result<(), Box<dyn Error>> {
 }
 
 /// Parse Anchor.toml configuration
-fn parse_anchor_config(config_path: &PathBuf) -> Result<AnchorConfig, Box<dyn Error>> {
+fn load_workspace(config_path: &PathBuf) -> Result<AnchorWorkspace, Box<dyn Error>> {
     println!("\n📋 Parsing Anchor.toml configuration...");
 
-    let content = fs::read_to_string(config_path)?;
-    let config: AnchorConfig = toml::from_str(&content)?;
-
-    if config.programs.localnet.is_empty() {
+    let workspace = AnchorWorkspace::load(config_path)?;
+    let programs = workspace.programs("localnet")?;
+    if programs.is_empty() {
         return Err("No programs found in [programs.localnet] section".into());
     }
 
     println!("✅ Configuration parsed successfully");
-    for (name, program_id) in &config.programs.localnet {
-        println!("   • {}: {}", name, program_id);
+    for program in &programs {
+        println!("   • {}: {}", program.name, program.program_id);
+    }
+    if let Some(auto_deploy) = workspace.section::<AutoDeploy>("auto_deploy")? {
+        println!(
+            "   Auto-deploy enabled: {}",
+            auto_deploy.enabled.unwrap_or(false)
+        );
+    }
+    if let Some(airdrop) = workspace.section::<Airdrop>("airdrop")? {
+        println!("   Airdrop users: {}", airdrop.users.len());
     }
 
-    Ok(config)
+    Ok(workspace)
 }
 
 /// Deploy program using automated surfpool start with proper configuration
@@ -360,7 +366,7 @@ async fn check_surfpool_running() -> Result<bool, Box<dyn Error>> {
 
     let client = RpcClientBuilder::new(surfpool_network().endpoint())
         .commitment(CommitmentLevel::Confirmed)
-        .build();
+        .build()?;
 
     // Try to get latest blockhash with a short timeout
     match tokio::time::timeout(Duration::from_secs(3), client.get_latest_blockhash()).await {
@@ -459,7 +465,7 @@ async fn wait_for_surfpool_ready() -> Result<(), Box<dyn Error>> {
 
     let client = RpcClientBuilder::new(surfpool_network().endpoint())
         .commitment(CommitmentLevel::Confirmed)
-        .build();
+        .build()?;
 
     println!("\n🔄 Testing connection to surfpool...");
     let mut retries = 30;
@@ -499,7 +505,7 @@ async fn call_program_loop(program_id: &Pubkey, count: usize) -> Result<(), Box<
 
     let client = RpcClientBuilder::new(surfpool_network().endpoint())
         .commitment(CommitmentLevel::Confirmed)
-        .build();
+        .build()?;
 
     // Create a mock authority (in real scenario, this would be from a keypair)
     let authority = Pubkey::new([1u8; 32]);
@@ -621,26 +627,7 @@ fn derive_counter_pda(authority: &Pubkey, program_id: &Pubkey) -> Pubkey {
     Pubkey::new(bytes)
 }
 
-// Configuration structures
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AnchorConfig {
-    pub features: Option<Features>,
-    pub programs: Programs,
-    pub auto_deploy: Option<AutoDeploy>,
-    pub airdrop: Option<Airdrop>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Features {
-    pub resolution: Option<bool>,
-    pub skip_lint: Option<bool>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Programs {
-    pub localnet: std::collections::HashMap<String, String>,
-}
-
+// Surfpool-specific Anchor.toml sections
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AutoDeploy {
     pub enabled: Option<bool>,
@@ -667,8 +654,6 @@ pub struct UserInfo {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::fs;
-    use tempfile::TempDir;
 
     #[test]
     fn test_config_parsing() {
@@ -679,14 +664,18 @@ resolution = true
 [programs.localnet]
 counter = "CounterProgram111111111111111111111111111111"
 
+[provider]
+cluster = "localnet"
+wallet = "~/.config/solana/id.json"
+
 [auto_deploy]
 enabled = true
 "#;
 
-        let config: AnchorConfig = toml::from_str(config_content).unwrap();
-        assert!(config.features.unwrap().resolution.unwrap());
-        assert_eq!(config.programs.localnet.len(), 1);
-        assert!(config.auto_deploy.unwrap().enabled.unwrap());
+        let workspace = AnchorWorkspace::parse(config_content, ".").unwrap();
+        assert_eq!(workspace.programs("localnet").unwrap().len(), 1);
+        let auto_deploy: AutoDeploy = workspace.section("auto_deploy").unwrap().unwrap();
+        assert!(auto_deploy.enabled.unwrap());
     }
 
     #[test]
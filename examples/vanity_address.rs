@@ -0,0 +1,44 @@
+//! Grind a vanity keypair
+//!
+//! Searches on every core for a keypair whose pubkey starts with the first
+//! argument and, if given, ends with the second, then saves it encrypted in
+//! the keystore under its pubkey with the password in `KEYSTORE_PASSWORD`.
+//!
+//! ```sh
+//! KEYSTORE_PASSWORD=... cargo run --release --features keystore --example vanity_address -- Sol
+//! ```
+
+use gloo_solana::infrastructure::storage::default_storage;
+use gloo_solana::{Keystore, Signer, VanityGrinder, VanityPattern};
+use std::error::Error;
+use std::time::Instant;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut args = std::env::args().skip(1);
+    let prefix = args
+        .next()
+        .ok_or("usage: vanity_address <prefix> [suffix]")?;
+    let suffix = args.next().unwrap_or_default();
+    // Checked before grinding so a long search is not wasted
+    let password = std::env::var("KEYSTORE_PASSWORD")
+        .map_err(|_| "set KEYSTORE_PASSWORD to encrypt the keypair")?;
+    let pattern = VanityPattern::new()
+        .prefix(prefix)
+        .suffix(suffix)
+        .ignore_case(true);
+
+    let started = Instant::now();
+    let found = VanityGrinder::new(pattern).grind()?;
+    let pubkey = found.keypair.pubkey();
+    println!(
+        "Found {} after {} attempts in {:.1?}",
+        pubkey,
+        found.attempts,
+        started.elapsed()
+    );
+
+    let keystore = Keystore::new(default_storage());
+    keystore.save(&pubkey.to_base58(), &found.keypair, &password)?;
+    println!("Keypair saved encrypted in the keystore as {}", pubkey);
+    Ok(())
+}
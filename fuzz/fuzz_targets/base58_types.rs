@@ -0,0 +1,28 @@
+#![no_main]
+
+use gloo_solana::{Hash, Pubkey, Signature};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    // Arbitrary strings must never panic, and anything accepted must round-trip
+    if let Ok(input) = std::str::from_utf8(data) {
+        if let Ok(pubkey) = Pubkey::from_base58(input) {
+            assert_eq!(Pubkey::from_base58(&pubkey.to_base58()).unwrap(), pubkey);
+        }
+        if let Ok(signature) = Signature::from_base58(input) {
+            assert_eq!(
+                Signature::from_base58(&signature.to_base58()).unwrap(),
+                signature
+            );
+        }
+        if let Ok(hash) = Hash::from_base58(input) {
+            assert_eq!(Hash::from_base58(&hash.to_base58()).unwrap(), hash);
+        }
+    }
+
+    // Raw bytes of the right length always round-trip through base58
+    if let Ok(bytes) = <[u8; 32]>::try_from(data) {
+        let pubkey = Pubkey::new(bytes);
+        assert_eq!(Pubkey::from_base58(&pubkey.to_base58()).unwrap(), pubkey);
+    }
+});
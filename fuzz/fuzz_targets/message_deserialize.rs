@@ -0,0 +1,12 @@
+#![no_main]
+
+use gloo_solana::domain::message::Message;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    // Decoding untrusted bytes must never panic; accepted input must re-encode
+    // to exactly the same bytes (the wire format has a single canonical form)
+    if let Ok(message) = Message::deserialize(data) {
+        assert_eq!(message.serialize().unwrap(), data);
+    }
+});
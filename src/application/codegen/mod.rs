@@ -0,0 +1,566 @@
+//! Typed program client generation
+//!
+//! [`generate_client`] turns an Anchor IDL into a Rust module with a struct
+//! per account and type, an accounts struct per instruction and a client whose
+//! methods build [`ProgramInstruction`](crate::domain::programs::ProgramInstruction)s
+//! and fetch decoded accounts through [`AssertionService`](crate::AssertionService).
+//! The `cargo gloo-solana gen-client` command writes its output to a file.
+
+use crate::domain::idl::{Idl, IdlDefinedFields, IdlField, IdlInstruction, IdlType, IdlTypeDefTy};
+use crate::domain::types::Pubkey;
+use std::collections::BTreeSet;
+use std::fmt::Write as _;
+use thiserror::Error;
+
+/// Errors raised while generating a client
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum CodegenError {
+    #[error("The IDL has no program name")]
+    MissingName,
+
+    #[error("Invalid program address in the IDL: {0}")]
+    InvalidAddress(String),
+
+    #[error("Type {0} is referenced but not defined in the IDL")]
+    UnknownType(String),
+}
+
+/// Generate a typed client module for the program described by `idl`
+pub fn generate_client(idl: &Idl) -> Result<String, CodegenError> {
+    let name = idl.name().ok_or(CodegenError::MissingName)?;
+    check_types(idl)?;
+    let program_id = idl
+        .address
+        .as_deref()
+        .map(|address| {
+            Pubkey::from_base58(address)
+                .map_err(|_| CodegenError::InvalidAddress(address.to_string()))
+        })
+        .transpose()?;
+
+    let mut out = String::new();
+    write_header(&mut out, name, program_id.as_ref());
+    write_types(&mut out, idl);
+    for instruction in &idl.instructions {
+        write_accounts_struct(&mut out, instruction);
+    }
+    write_client(&mut out, name, idl);
+    Ok(out)
+}
+
+/// Fail on `defined` references to types the IDL does not declare
+fn check_types(idl: &Idl) -> Result<(), CodegenError> {
+    let declared: BTreeSet<&str> = idl
+        .types
+        .iter()
+        .map(|ty| ty.name.as_str())
+        .chain(idl.accounts.iter().map(|account| account.name.as_str()))
+        .collect();
+
+    let mut used = Vec::new();
+    for ty in &idl.types {
+        collect_defined(&ty.ty, &mut used);
+    }
+    for account in &idl.accounts {
+        if let Some(ty) = &account.ty {
+            collect_defined(ty, &mut used);
+        }
+    }
+    for instruction in &idl.instructions {
+        for arg in &instruction.args {
+            defined_names(&arg.ty, &mut used);
+        }
+    }
+
+    match used
+        .into_iter()
+        .find(|name| !declared.contains(name.as_str()))
+    {
+        Some(name) => Err(CodegenError::UnknownType(name)),
+        None => Ok(()),
+    }
+}
+
+fn collect_defined(ty: &IdlTypeDefTy, used: &mut Vec<String>) {
+    let mut fields = |fields: &IdlDefinedFields| match fields {
+        IdlDefinedFields::Named(fields) => fields
+            .iter()
+            .for_each(|field| defined_names(&field.ty, used)),
+        IdlDefinedFields::Tuple(types) => types.iter().for_each(|ty| defined_names(ty, used)),
+    };
+    match ty {
+        IdlTypeDefTy::Struct { fields: defined } => fields(defined),
+        IdlTypeDefTy::Enum { variants } => variants
+            .iter()
+            .filter_map(|variant| variant.fields.as_ref())
+            .for_each(fields),
+    }
+}
+
+fn defined_names(ty: &IdlType, used: &mut Vec<String>) {
+    match ty {
+        IdlType::Vec(inner) | IdlType::Option(inner) | IdlType::Array(inner, _) => {
+            defined_names(inner, used)
+        }
+        IdlType::Defined(name) => used.push(name.clone()),
+        _ => {}
+    }
+}
+
+fn write_header(out: &mut String, name: &str, program_id: Option<&Pubkey>) {
+    let _ = writeln!(out, "//! Typed client for the `{}` program", name);
+    out.push_str(
+        "//!\n\
+         //! Generated by `cargo gloo-solana gen-client` from the program's Anchor IDL.\n\
+         //! Regenerate it rather than editing it by hand.\n\n",
+    );
+    out.push_str(
+        "use borsh::{BorshDeserialize, BorshSerialize};\n\
+         use gloo_solana::domain::programs::{InstructionAccount, ProgramInstruction};\n\
+         use gloo_solana::{AssertionError, AssertionService, Discriminator, Pubkey, RpcApi};\n\
+         use std::sync::Arc;\n",
+    );
+    if let Some(program_id) = program_id {
+        let _ = write!(
+            out,
+            "\n/// Program id declared in the IDL, `{}`\npub const PROGRAM_ID: Pubkey = Pubkey::new({});\n",
+            program_id,
+            byte_array(program_id.as_bytes())
+        );
+    }
+}
+
+fn write_types(out: &mut String, idl: &Idl) {
+    // Anchor 0.30+ IDLs describe account layouts in `types`
+    for account in &idl.accounts {
+        if let Some(ty) = &account.ty {
+            if idl.type_def(&account.name).is_none() {
+                write_type(out, &account.name, ty);
+            }
+        }
+    }
+    for ty in &idl.types {
+        write_type(out, &ty.name, &ty.ty);
+    }
+    for account in &idl.accounts {
+        let _ = write!(
+            out,
+            "\nimpl Discriminator for {} {{\n    const DISCRIMINATOR: [u8; 8] = {};\n}}\n",
+            type_name(&account.name),
+            byte_array(&account.discriminator())
+        );
+    }
+}
+
+fn write_type(out: &mut String, name: &str, ty: &IdlTypeDefTy) {
+    let name = type_name(name);
+    let derives = if type_def_has_float(ty) {
+        "Debug, Clone, PartialEq"
+    } else {
+        "Debug, Clone, PartialEq, Eq"
+    };
+    let _ = writeln!(
+        out,
+        "\n#[derive({}, BorshSerialize, BorshDeserialize)]",
+        derives
+    );
+    match ty {
+        IdlTypeDefTy::Struct {
+            fields: IdlDefinedFields::Named(fields),
+        } => {
+            let _ = writeln!(out, "pub struct {} {{", name);
+            for field in fields {
+                let _ = writeln!(
+                    out,
+                    "    pub {}: {},",
+                    field_name(&field.name),
+                    rust_type(&field.ty)
+                );
+            }
+            out.push_str("}\n");
+        }
+        IdlTypeDefTy::Struct {
+            fields: IdlDefinedFields::Tuple(types),
+        } => {
+            let _ = writeln!(out, "pub struct {}({});", name, tuple_fields(types, true));
+        }
+        IdlTypeDefTy::Enum { variants } => {
+            let _ = writeln!(out, "pub enum {} {{", name);
+            for variant in variants {
+                let variant_name = type_name(&variant.name);
+                match &variant.fields {
+                    None => {
+                        let _ = writeln!(out, "    {},", variant_name);
+                    }
+                    Some(IdlDefinedFields::Tuple(types)) => {
+                        let _ =
+                            writeln!(out, "    {}({}),", variant_name, tuple_fields(types, false));
+                    }
+                    Some(IdlDefinedFields::Named(fields)) => {
+                        let fields: Vec<String> = fields
+                            .iter()
+                            .map(|field| {
+                                format!("{}: {}", field_name(&field.name), rust_type(&field.ty))
+                            })
+                            .collect();
+                        let _ = writeln!(out, "    {} {{ {} }},", variant_name, fields.join(", "));
+                    }
+                }
+            }
+            out.push_str("}\n");
+        }
+    }
+}
+
+fn write_accounts_struct(out: &mut String, instruction: &IdlInstruction) {
+    let accounts: Vec<_> = instruction
+        .accounts
+        .iter()
+        .filter(|account| account.address.is_none())
+        .collect();
+    let _ = write!(
+        out,
+        "\n/// Accounts of the `{}` instruction\n#[derive(Debug, Clone, Copy, PartialEq, Eq)]\npub struct {}Accounts {{\n",
+        instruction.name,
+        type_name(&instruction.name)
+    );
+    for account in accounts {
+        let ty = if account.optional {
+            "Option<Pubkey>"
+        } else {
+            "Pubkey"
+        };
+        let _ = writeln!(out, "    pub {}: {},", field_name(&account.name), ty);
+    }
+    out.push_str("}\n");
+}
+
+fn write_client(out: &mut String, name: &str, idl: &Idl) {
+    let client = format!("{}Client", type_name(name));
+    let _ = write!(
+        out,
+        "\n/// Client for the `{name}` program\n\
+         pub struct {client} {{\n    rpc_client: Arc<dyn RpcApi>,\n    program_id: Pubkey,\n}}\n\n\
+         impl {client} {{\n    \
+         /// Create a client for the program deployed at `program_id`\n    \
+         pub fn new(rpc_client: impl RpcApi + 'static, program_id: Pubkey) -> Self {{\n        \
+         Self::from_api(Arc::new(rpc_client), program_id)\n    }}\n\n    \
+         /// Create a client from a shared RPC implementation\n    \
+         pub fn from_api(rpc_client: Arc<dyn RpcApi>, program_id: Pubkey) -> Self {{\n        \
+         Self {{\n            rpc_client,\n            program_id,\n        }}\n    }}\n\n    \
+         /// The program's id\n    \
+         pub fn program_id(&self) -> Pubkey {{\n        self.program_id\n    }}\n",
+    );
+
+    for (index, instruction) in idl.instructions.iter().enumerate() {
+        write_instruction_method(out, index, instruction);
+    }
+    for account in &idl.accounts {
+        let ty = type_name(&account.name);
+        let _ = write!(
+            out,
+            "\n    /// Fetch a `{ty}` account, checking that the program owns it\n    \
+             pub async fn fetch_{method}(&self, address: &Pubkey) -> Result<{ty}, AssertionError> {{\n        \
+             AssertionService::from_api(self.rpc_client.clone())\n            \
+             .assert_program_account(address, &self.program_id)\n            \
+             .await\n    }}\n",
+            method = method_name(&account.name),
+        );
+    }
+    out.push_str("}\n");
+}
+
+fn write_instruction_method(out: &mut String, index: usize, instruction: &IdlInstruction) {
+    let method = method_name(&instruction.name);
+    let mut params = format!("accounts: &{}Accounts", type_name(&instruction.name));
+    for arg in &instruction.args {
+        let _ = write!(
+            params,
+            ", {}: {}",
+            field_name(&arg.name),
+            param_type(&arg.ty)
+        );
+    }
+    let _ = write!(
+        out,
+        "\n    /// Build the `{}` instruction\n    pub fn {}(&self, {}) -> ProgramInstruction {{\n",
+        instruction.name, method, params
+    );
+    let _ = writeln!(
+        out,
+        "        let {}data = {}.to_vec();",
+        if instruction.args.is_empty() {
+            ""
+        } else {
+            "mut "
+        },
+        byte_array(&instruction.discriminator())
+    );
+    for arg in &instruction.args {
+        let _ = writeln!(
+            out,
+            "        BorshSerialize::serialize(&{}, &mut data).expect(\"writing to a Vec cannot fail\");",
+            field_name(&arg.name)
+        );
+    }
+    out.push_str("        let accounts = vec![\n");
+    for account in &instruction.accounts {
+        let pubkey = match &account.address {
+            Some(address) => match Pubkey::from_base58(address) {
+                Ok(pubkey) => format!("Pubkey::new({})", byte_array(pubkey.as_bytes())),
+                Err(_) => "self.program_id".to_string(),
+            },
+            // Anchor expects the program id in place of an omitted optional account
+            None if account.optional => format!(
+                "accounts.{}.unwrap_or(self.program_id)",
+                field_name(&account.name)
+            ),
+            None => format!("accounts.{}", field_name(&account.name)),
+        };
+        let constructor = if account.signer {
+            format!(
+                "InstructionAccount::signer({}, {})",
+                pubkey, account.writable
+            )
+        } else if account.writable {
+            format!("InstructionAccount::writable({})", pubkey)
+        } else {
+            format!("InstructionAccount::readonly({})", pubkey)
+        };
+        let _ = writeln!(out, "            {},", constructor);
+    }
+    let _ = writeln!(
+        out,
+        "        ];\n        ProgramInstruction::new(self.program_id, accounts, data, {})\n    }}",
+        index.min(u8::MAX as usize)
+    );
+}
+
+/// Rust type of an IDL field
+fn rust_type(ty: &IdlType) -> String {
+    match ty {
+        IdlType::Bool => "bool".to_string(),
+        IdlType::U8 => "u8".to_string(),
+        IdlType::I8 => "i8".to_string(),
+        IdlType::U16 => "u16".to_string(),
+        IdlType::I16 => "i16".to_string(),
+        IdlType::U32 => "u32".to_string(),
+        IdlType::I32 => "i32".to_string(),
+        IdlType::U64 => "u64".to_string(),
+        IdlType::I64 => "i64".to_string(),
+        IdlType::U128 => "u128".to_string(),
+        IdlType::I128 => "i128".to_string(),
+        IdlType::F32 => "f32".to_string(),
+        IdlType::F64 => "f64".to_string(),
+        IdlType::Bytes => "Vec<u8>".to_string(),
+        IdlType::String => "String".to_string(),
+        IdlType::Pubkey => "Pubkey".to_string(),
+        IdlType::Vec(inner) => format!("Vec<{}>", rust_type(inner)),
+        IdlType::Option(inner) => format!("Option<{}>", rust_type(inner)),
+        IdlType::Array(inner, len) => format!("[{}; {}]", rust_type(inner), len),
+        IdlType::Defined(name) => type_name(name),
+    }
+}
+
+/// Type of an instruction method parameter, borrowing unsized data
+fn param_type(ty: &IdlType) -> String {
+    match ty {
+        IdlType::String => "&str".to_string(),
+        IdlType::Bytes => "&[u8]".to_string(),
+        IdlType::Vec(inner) => format!("&[{}]", rust_type(inner)),
+        IdlType::Defined(name) => format!("&{}", type_name(name)),
+        other => rust_type(other),
+    }
+}
+
+fn tuple_fields(types: &[IdlType], public: bool) -> String {
+    types
+        .iter()
+        .map(|ty| {
+            if public {
+                format!("pub {}", rust_type(ty))
+            } else {
+                rust_type(ty)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn type_def_has_float(ty: &IdlTypeDefTy) -> bool {
+    let fields_have_float = |fields: &IdlDefinedFields| match fields {
+        IdlDefinedFields::Named(fields) => {
+            fields.iter().any(|field: &IdlField| has_float(&field.ty))
+        }
+        IdlDefinedFields::Tuple(types) => types.iter().any(has_float),
+    };
+    match ty {
+        IdlTypeDefTy::Struct { fields } => fields_have_float(fields),
+        IdlTypeDefTy::Enum { variants } => variants
+            .iter()
+            .filter_map(|variant| variant.fields.as_ref())
+            .any(fields_have_float),
+    }
+}
+
+/// Whether a type contains floats, which rule out `Eq`
+///
+/// Defined types are assumed to be float-free; a struct holding a float
+/// inside another defined type only loses `Eq` on the inner type.
+fn has_float(ty: &IdlType) -> bool {
+    match ty {
+        IdlType::F32 | IdlType::F64 => true,
+        IdlType::Vec(inner) | IdlType::Option(inner) | IdlType::Array(inner, _) => has_float(inner),
+        _ => false,
+    }
+}
+
+fn byte_array(bytes: &[u8]) -> String {
+    let bytes: Vec<String> = bytes.iter().map(u8::to_string).collect();
+    format!("[{}]", bytes.join(", "))
+}
+
+/// `snake_case` or `camelCase` to `PascalCase`
+fn type_name(name: &str) -> String {
+    name.split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// `camelCase` or `PascalCase` to `snake_case`
+fn method_name(name: &str) -> String {
+    let mut snake = String::with_capacity(name.len() + 4);
+    for (i, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i > 0 && !snake.ends_with('_') {
+                snake.push('_');
+            }
+            snake.push(c.to_ascii_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
+
+/// A field or parameter name, escaping Rust keywords
+fn field_name(name: &str) -> String {
+    const KEYWORDS: &[&str] = &[
+        "as", "async", "await", "box", "const", "crate", "dyn", "enum", "fn", "for", "gen", "impl",
+        "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "static",
+        "struct", "trait", "type", "unsafe", "use", "where", "while", "yield",
+    ];
+    let name = method_name(name);
+    if KEYWORDS.contains(&name.as_str()) {
+        format!("r#{}", name)
+    } else {
+        name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COUNTER_IDL: &str = r#"{
+        "address": "11111111111111111111111111111111",
+        "metadata": { "name": "counter", "version": "0.1.0", "spec": "0.1.0" },
+        "instructions": [{
+            "name": "initialize",
+            "discriminator": [175, 175, 109, 31, 13, 152, 155, 237],
+            "accounts": [
+                { "name": "counter", "writable": true, "signer": true },
+                { "name": "authority", "writable": true, "signer": true },
+                { "name": "system_program", "address": "11111111111111111111111111111111" }
+            ],
+            "args": [{ "name": "start", "type": "u64" }, { "name": "label", "type": "string" }]
+        }, {
+            "name": "setMode",
+            "accounts": [
+                { "name": "counter", "writable": true },
+                { "name": "delegate", "optional": true }
+            ],
+            "args": [{ "name": "type", "type": { "defined": { "name": "Mode" } } }]
+        }],
+        "accounts": [{ "name": "Counter", "discriminator": [255, 176, 4, 245, 188, 253, 124, 25] }],
+        "types": [{
+            "name": "Counter",
+            "type": { "kind": "struct", "fields": [
+                { "name": "authority", "type": "pubkey" },
+                { "name": "count", "type": "u64" },
+                { "name": "history", "type": { "vec": { "array": ["u8", 4] } } }
+            ] }
+        }, {
+            "name": "Mode",
+            "type": { "kind": "enum", "variants": [
+                { "name": "Off" },
+                { "name": "Step", "fields": ["u32"] },
+                { "name": "Scaled", "fields": [{ "name": "factor", "type": "f64" }] }
+            ] }
+        }]
+    }"#;
+
+    #[test]
+    fn test_generate_counter_client() {
+        let idl = Idl::from_json(COUNTER_IDL).unwrap();
+        let code = generate_client(&idl).unwrap();
+
+        assert!(code.starts_with("//! Typed client for the `counter` program"));
+        assert!(code.contains("pub const PROGRAM_ID: Pubkey = Pubkey::new([0, 0,"));
+        assert!(code.contains(
+            "#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]\npub struct Counter {\n    pub authority: Pubkey,\n    pub count: u64,\n    pub history: Vec<[u8; 4]>,\n}"
+        ));
+        assert!(code.contains(
+            "#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize)]\npub enum Mode {\n    Off,\n    Step(u32),\n    Scaled { factor: f64 },\n}"
+        ));
+        assert!(code.contains(
+            "impl Discriminator for Counter {\n    const DISCRIMINATOR: [u8; 8] = [255, 176, 4, 245, 188, 253, 124, 25];\n}"
+        ));
+
+        // Accounts with a fixed address are filled in by the client
+        assert!(code.contains(
+            "pub struct InitializeAccounts {\n    pub counter: Pubkey,\n    pub authority: Pubkey,\n}"
+        ));
+        assert!(code.contains(
+            "pub fn initialize(&self, accounts: &InitializeAccounts, start: u64, label: &str) -> ProgramInstruction {"
+        ));
+        assert!(code.contains("InstructionAccount::signer(accounts.counter, true),"));
+        assert!(code.contains("InstructionAccount::readonly(Pubkey::new([0, 0,"));
+        assert!(code.contains("pub delegate: Option<Pubkey>,"));
+        assert!(code.contains(
+            "pub fn set_mode(&self, accounts: &SetModeAccounts, r#type: &Mode) -> ProgramInstruction {"
+        ));
+        assert!(code.contains("accounts.delegate.unwrap_or(self.program_id)"));
+        assert!(code.contains("ProgramInstruction::new(self.program_id, accounts, data, 1)"));
+        assert!(code.contains(
+            "pub async fn fetch_counter(&self, address: &Pubkey) -> Result<Counter, AssertionError> {"
+        ));
+        assert!(code.contains("pub struct CounterClient {"));
+    }
+
+    #[test]
+    fn test_rejects_undefined_types() {
+        let idl = Idl::from_json(
+            r#"{
+                "metadata": { "name": "broken", "version": "0.1.0" },
+                "instructions": [{
+                    "name": "run",
+                    "accounts": [],
+                    "args": [{ "name": "config", "type": { "defined": "Config" } }]
+                }]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            generate_client(&idl),
+            Err(CodegenError::UnknownType("Config".to_string()))
+        );
+    }
+}
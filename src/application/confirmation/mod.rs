@@ -0,0 +1,385 @@
+//! Transaction confirmation strategies
+//!
+//! A [`TransactionConfirmer`] waits for a sent transaction to reach a
+//! commitment level, either through a `signatureSubscribe` websocket, by
+//! polling `getSignatureStatuses`, or both at once. The hybrid strategy keeps
+//! confirming through polling when a proxy or firewall breaks the websocket.
+//! [`TransactionConfirmer::track_signature`] streams each stage on the way.
+
+use crate::domain::errors::TransactionError;
+use crate::domain::types::Signature;
+use crate::infrastructure::cancel::CancelToken;
+use crate::infrastructure::pubsub::{websocket_url, PubsubClient, PubsubError};
+use crate::infrastructure::rpc::{CommitmentLevel, RpcApi, RpcError};
+use crate::infrastructure::time::sleep;
+use futures::future::{select, Either};
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+
+pub mod track;
+
+pub use track::SignatureProgress;
+
+/// Default delay between `getSignatureStatuses` polls
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Default time to wait for confirmation, about the lifetime of a blockhash
+pub const DEFAULT_CONFIRMATION_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How a transaction's confirmation is observed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfirmationStrategy {
+    /// Wait for a `signatureSubscribe` notification
+    WebSocket,
+    /// Poll `getSignatureStatuses` every `interval`
+    Polling { interval: Duration },
+    /// Subscribe and poll concurrently, falling back to polling alone if the
+    /// websocket fails
+    Hybrid { interval: Duration },
+}
+
+impl Default for ConfirmationStrategy {
+    fn default() -> Self {
+        Self::Hybrid {
+            interval: DEFAULT_POLL_INTERVAL,
+        }
+    }
+}
+
+/// Settings for confirming a transaction
+#[derive(Debug, Clone)]
+pub struct ConfirmationConfig {
+    pub strategy: ConfirmationStrategy,
+    pub commitment: CommitmentLevel,
+    pub timeout: Duration,
+    /// Websocket endpoint used by the websocket and hybrid strategies
+    pub websocket_url: Option<String>,
+    /// Stops waiting once cancelled
+    pub cancel_token: Option<CancelToken>,
+}
+
+impl ConfirmationConfig {
+    /// Create a hybrid configuration waiting for `confirmed`
+    pub fn new() -> Self {
+        Self {
+            strategy: ConfirmationStrategy::default(),
+            commitment: CommitmentLevel::Confirmed,
+            timeout: DEFAULT_CONFIRMATION_TIMEOUT,
+            websocket_url: None,
+            cancel_token: None,
+        }
+    }
+
+    /// Create a configuration using the websocket paired with an HTTP endpoint
+    pub fn for_endpoint(endpoint: &str) -> Self {
+        Self::new().websocket_url(websocket_url(endpoint))
+    }
+
+    /// Set the confirmation strategy
+    pub fn strategy(mut self, strategy: ConfirmationStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Set the commitment level to wait for
+    pub fn commitment(mut self, commitment: CommitmentLevel) -> Self {
+        self.commitment = commitment;
+        self
+    }
+
+    /// Set how long to wait before giving up
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set the websocket endpoint
+    pub fn websocket_url(mut self, url: impl Into<String>) -> Self {
+        self.websocket_url = Some(url.into());
+        self
+    }
+
+    /// Give up with [`ConfirmationError::Cancelled`] once `token` is cancelled
+    pub fn cancel_token(mut self, token: CancelToken) -> Self {
+        self.cancel_token = Some(token);
+        self
+    }
+}
+
+impl Default for ConfirmationConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Errors that can occur while confirming a transaction
+#[derive(Debug, Clone, Error)]
+pub enum ConfirmationError {
+    #[error("RPC error: {0}")]
+    Rpc(#[from] RpcError),
+
+    #[error("WebSocket error: {0}")]
+    Pubsub(#[from] PubsubError),
+
+    #[error("Transaction {signature} failed: {error}")]
+    Failed {
+        signature: Signature,
+        error: TransactionError,
+    },
+
+    #[error("Transaction {0} was not confirmed in time")]
+    Timeout(Signature),
+
+    #[error("Confirmation of transaction {0} was cancelled")]
+    Cancelled(Signature),
+
+    #[error("No websocket URL configured")]
+    NoWebsocketUrl,
+}
+
+/// Waits for transactions to be confirmed
+pub struct TransactionConfirmer {
+    rpc_client: Arc<dyn RpcApi>,
+    config: ConfirmationConfig,
+}
+
+impl TransactionConfirmer {
+    /// Create a confirmer
+    pub fn new(rpc_client: impl RpcApi + 'static, config: ConfirmationConfig) -> Self {
+        Self::from_api(Arc::new(rpc_client), config)
+    }
+
+    /// Create a confirmer from a shared RPC implementation
+    pub fn from_api(rpc_client: Arc<dyn RpcApi>, config: ConfirmationConfig) -> Self {
+        Self { rpc_client, config }
+    }
+
+    /// Get the configuration
+    pub fn config(&self) -> &ConfirmationConfig {
+        &self.config
+    }
+
+    /// Wait until `signature` reaches the configured commitment
+    pub async fn confirm(&self, signature: &Signature) -> Result<(), ConfirmationError> {
+        let confirmation = async {
+            let confirmation = Box::pin(self.run(signature));
+            let timeout = Box::pin(sleep(self.config.timeout));
+            match select(confirmation, timeout).await {
+                Either::Left((result, _)) => result,
+                Either::Right(_) => Err(ConfirmationError::Timeout(signature.clone())),
+            }
+        };
+        match &self.config.cancel_token {
+            Some(token) => token
+                .run(confirmation)
+                .await
+                .unwrap_or_else(|| Err(ConfirmationError::Cancelled(signature.clone()))),
+            None => confirmation.await,
+        }
+    }
+
+    async fn run(&self, signature: &Signature) -> Result<(), ConfirmationError> {
+        match self.config.strategy {
+            ConfirmationStrategy::WebSocket => self.subscribe(signature).await,
+            ConfirmationStrategy::Polling { interval } => self.poll(signature, interval).await,
+            ConfirmationStrategy::Hybrid { interval } => {
+                let websocket = Box::pin(self.subscribe(signature));
+                let polling = Box::pin(self.poll(signature, interval));
+                match select(websocket, polling).await {
+                    Either::Left((Err(error), polling))
+                        if !matches!(error, ConfirmationError::Failed { .. }) =>
+                    {
+                        polling.await
+                    }
+                    Either::Left((result, _)) | Either::Right((result, _)) => result,
+                }
+            }
+        }
+    }
+
+    async fn subscribe(&self, signature: &Signature) -> Result<(), ConfirmationError> {
+        let url = self
+            .config
+            .websocket_url
+            .as_deref()
+            .ok_or(ConfirmationError::NoWebsocketUrl)?;
+        let mut subscription = PubsubClient::new(url)
+            .signature_subscribe(signature, self.config.commitment)
+            .await?;
+
+        // The transaction may have landed before the subscription was opened,
+        // in which case no notification will ever arrive
+        if let Ok(Some(result)) = self.check(signature).await {
+            return result;
+        }
+
+        let notification = subscription.next().await.ok_or(PubsubError::Closed)??;
+        match notification.pointer("/value/err") {
+            None | Some(Value::Null) => Ok(()),
+            Some(error) => Err(ConfirmationError::Failed {
+                signature: signature.clone(),
+                error: TransactionError::from_value(error),
+            }),
+        }
+    }
+
+    async fn poll(
+        &self,
+        signature: &Signature,
+        interval: Duration,
+    ) -> Result<(), ConfirmationError> {
+        loop {
+            match self.check(signature).await {
+                Ok(Some(result)) => return result,
+                Err(RpcError::Cancelled) => {
+                    return Err(ConfirmationError::Cancelled(signature.clone()))
+                }
+                // Transient RPC failures are retried until the timeout
+                _ => {}
+            }
+            sleep(interval).await;
+        }
+    }
+
+    /// The outcome for `signature`, if it is already settled
+    async fn check(
+        &self,
+        signature: &Signature,
+    ) -> Result<Option<Result<(), ConfirmationError>>, RpcError> {
+        let statuses = self
+            .rpc_client
+            .get_signature_statuses(std::slice::from_ref(signature))
+            .await?;
+        let Some(status) = statuses.into_iter().next().flatten() else {
+            return Ok(None);
+        };
+
+        if let Some(error) = status.error() {
+            return Ok(Some(Err(ConfirmationError::Failed {
+                signature: signature.clone(),
+                error,
+            })));
+        }
+        Ok(status.satisfies(self.config.commitment).then_some(Ok(())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::errors::InstructionError;
+    use crate::infrastructure::rpc::mock::MockRpc;
+    use crate::infrastructure::rpc::SignatureStatus;
+    use serde_json::json;
+
+    const INTERVAL: Duration = Duration::from_millis(1);
+
+    fn status(confirmation_status: CommitmentLevel, err: Option<Value>) -> Option<SignatureStatus> {
+        Some(SignatureStatus {
+            slot: 5,
+            confirmations: Some(0),
+            err,
+            confirmation_status: Some(confirmation_status),
+        })
+    }
+
+    fn confirmer(rpc: MockRpc, strategy: ConfirmationStrategy) -> TransactionConfirmer {
+        let config = ConfirmationConfig::new()
+            .strategy(strategy)
+            .timeout(Duration::from_secs(5));
+        TransactionConfirmer::new(rpc, config)
+    }
+
+    #[tokio::test]
+    async fn test_polling_waits_for_commitment() {
+        let signature = Signature::new([9; 64]);
+        let rpc = MockRpc::new().with_statuses(
+            signature.clone(),
+            vec![
+                None,
+                status(CommitmentLevel::Processed, None),
+                status(CommitmentLevel::Confirmed, None),
+            ],
+        );
+        let confirmer = confirmer(rpc, ConfirmationStrategy::Polling { interval: INTERVAL });
+        assert!(confirmer.confirm(&signature).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_polling_reports_failure_and_timeout() {
+        let failed = Signature::new([9; 64]);
+        let rpc = MockRpc::new().with_statuses(
+            failed.clone(),
+            vec![status(
+                CommitmentLevel::Processed,
+                Some(json!({ "InstructionError": [0, { "Custom": 1 }] })),
+            )],
+        );
+        let confirmer = confirmer(rpc, ConfirmationStrategy::Polling { interval: INTERVAL });
+        match confirmer.confirm(&failed).await {
+            Err(ConfirmationError::Failed { error, .. }) => assert_eq!(
+                error,
+                TransactionError::InstructionError {
+                    index: 0,
+                    error: InstructionError::Custom(1),
+                }
+            ),
+            other => panic!("expected failure, got {:?}", other),
+        }
+
+        let confirmer = TransactionConfirmer::new(
+            MockRpc::new(),
+            ConfirmationConfig::new()
+                .strategy(ConfirmationStrategy::Polling { interval: INTERVAL })
+                .timeout(Duration::from_millis(20)),
+        );
+        let pending = Signature::new([8; 64]);
+        assert!(matches!(
+            confirmer.confirm(&pending).await,
+            Err(ConfirmationError::Timeout(signature)) if signature == pending
+        ));
+
+        // Cancelling stops the wait long before the timeout
+        let token = CancelToken::new();
+        let confirmer = TransactionConfirmer::new(
+            MockRpc::new(),
+            ConfirmationConfig::new()
+                .strategy(ConfirmationStrategy::Polling { interval: INTERVAL })
+                .cancel_token(token.clone()),
+        );
+        let remote = token.clone();
+        tokio::spawn(async move {
+            sleep(Duration::from_millis(10)).await;
+            remote.cancel();
+        });
+        assert!(matches!(
+            confirmer.confirm(&pending).await,
+            Err(ConfirmationError::Cancelled(signature)) if signature == pending
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_hybrid_falls_back_to_polling() {
+        let signature = Signature::new([9; 64]);
+        let rpc = MockRpc::new().with_statuses(
+            signature.clone(),
+            vec![None, status(CommitmentLevel::Finalized, None)],
+        );
+        let config = ConfirmationConfig::for_endpoint("http://127.0.0.1:0")
+            .strategy(ConfirmationStrategy::Hybrid { interval: INTERVAL })
+            .timeout(Duration::from_secs(5));
+        let confirmer = TransactionConfirmer::new(rpc, config);
+        assert!(confirmer.confirm(&signature).await.is_ok());
+
+        let websocket_only = TransactionConfirmer::new(
+            MockRpc::new(),
+            ConfirmationConfig::new().strategy(ConfirmationStrategy::WebSocket),
+        );
+        assert!(matches!(
+            websocket_only.confirm(&signature).await,
+            Err(ConfirmationError::NoWebsocketUrl)
+        ));
+    }
+}
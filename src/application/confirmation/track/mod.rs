@@ -0,0 +1,370 @@
+//! Signature progress streams
+//!
+//! [`TransactionConfirmer::track_signature`] reports each stage a transaction
+//! passes through so UIs can animate a progress bar instead of waiting for a
+//! single confirmation. A `signatureSubscribe` with received notifications
+//! reports the early stages as soon as the leader sees the transaction, while
+//! `getSignatureStatuses` polling follows it through to finalization and
+//! keeps the stream going if the websocket is unavailable.
+
+use super::{ConfirmationError, ConfirmationStrategy, TransactionConfirmer, DEFAULT_POLL_INTERVAL};
+use crate::domain::errors::TransactionError;
+use crate::domain::types::Signature;
+use crate::infrastructure::cancel::CancelToken;
+use crate::infrastructure::pubsub::{PubsubClient, Subscription};
+use crate::infrastructure::rpc::{CommitmentLevel, RpcApi, RpcError, SignatureStatus};
+use crate::infrastructure::time::{sleep, Instant};
+use futures::future::{select, Either};
+use futures::stream::{self, Stream};
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A stage reached by a tracked transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignatureProgress {
+    /// The leader received the transaction
+    Received,
+    Processed,
+    Confirmed,
+    Finalized,
+    /// The transaction landed with an error
+    Failed(TransactionError),
+}
+
+impl SignatureProgress {
+    /// Share of the way to finalization, for progress bars
+    pub fn fraction(&self) -> f64 {
+        match self {
+            Self::Received => 0.25,
+            Self::Processed => 0.5,
+            Self::Confirmed => 0.75,
+            Self::Finalized | Self::Failed(_) => 1.0,
+        }
+    }
+
+    /// Whether no further stages follow
+    pub fn is_final(&self) -> bool {
+        matches!(self, Self::Finalized | Self::Failed(_))
+    }
+
+    /// Stage reported by a status, if the transaction has landed
+    pub fn from_status(status: &SignatureStatus) -> Self {
+        if let Some(error) = status.error() {
+            return Self::Failed(error);
+        }
+        if status.satisfies(CommitmentLevel::Finalized) {
+            Self::Finalized
+        } else if status.satisfies(CommitmentLevel::Confirmed) {
+            Self::Confirmed
+        } else {
+            Self::Processed
+        }
+    }
+
+    fn rank(&self) -> u8 {
+        match self {
+            Self::Received => 0,
+            Self::Processed => 1,
+            Self::Confirmed => 2,
+            Self::Finalized | Self::Failed(_) => 3,
+        }
+    }
+}
+
+/// Successful stages in order
+const STAGES: [SignatureProgress; 4] = [
+    SignatureProgress::Received,
+    SignatureProgress::Processed,
+    SignatureProgress::Confirmed,
+    SignatureProgress::Finalized,
+];
+
+impl TransactionConfirmer {
+    /// Stream the stages `signature` reaches until it is finalized or fails
+    ///
+    /// Stages are yielded in order and never repeated; ones skipped between
+    /// polls are filled in. The websocket is used unless the strategy is
+    /// polling only. The stream ends with [`ConfirmationError::Timeout`] or
+    /// [`ConfirmationError::Cancelled`] if the configured timeout or cancel
+    /// token stops it first.
+    pub fn track_signature(
+        &self,
+        signature: &Signature,
+    ) -> impl Stream<Item = Result<SignatureProgress, ConfirmationError>> {
+        let (websocket_url, interval) = match self.config.strategy {
+            ConfirmationStrategy::Polling { interval } => (None, interval),
+            ConfirmationStrategy::Hybrid { interval } => {
+                (self.config.websocket_url.clone(), interval)
+            }
+            ConfirmationStrategy::WebSocket => {
+                (self.config.websocket_url.clone(), DEFAULT_POLL_INTERVAL)
+            }
+        };
+        let tracker = Tracker {
+            rpc_client: self.rpc_client.clone(),
+            signature: signature.clone(),
+            websocket_url,
+            interval,
+            deadline: Instant::now() + self.config.timeout,
+            cancel_token: self.config.cancel_token.clone(),
+            subscription: None,
+            connected: false,
+            polled: false,
+            reached: None,
+            pending: VecDeque::new(),
+            done: false,
+        };
+        stream::unfold(tracker, |mut tracker| async move {
+            let item = tracker.next().await?;
+            Some((item, tracker))
+        })
+    }
+}
+
+/// What ended a wait for the next stage
+enum Wake {
+    Notification(Value),
+    Closed,
+    Poll,
+}
+
+/// State of a [`TransactionConfirmer::track_signature`] stream
+struct Tracker {
+    rpc_client: Arc<dyn RpcApi>,
+    signature: Signature,
+    websocket_url: Option<String>,
+    interval: Duration,
+    deadline: Instant,
+    cancel_token: Option<CancelToken>,
+    subscription: Option<Box<Subscription>>,
+    connected: bool,
+    polled: bool,
+    /// Highest stage yielded so far
+    reached: Option<u8>,
+    pending: VecDeque<SignatureProgress>,
+    done: bool,
+}
+
+impl Tracker {
+    async fn next(&mut self) -> Option<Result<SignatureProgress, ConfirmationError>> {
+        loop {
+            if let Some(progress) = self.pending.pop_front() {
+                return Some(Ok(progress));
+            }
+            if self.done {
+                return None;
+            }
+            if let Some(error) = self.stopped() {
+                self.done = true;
+                return Some(Err(error));
+            }
+
+            if !self.connected {
+                self.connected = true;
+                self.subscription = self.subscribe().await;
+            }
+            match self.wake().await {
+                Wake::Notification(value) => {
+                    if let Some(progress) = parse_notification(&value) {
+                        self.advance(progress);
+                    }
+                }
+                Wake::Closed => self.subscription = None,
+                Wake::Poll => match self.poll().await {
+                    Ok(Some(progress)) => self.advance(progress),
+                    Err(RpcError::Cancelled) => {
+                        self.done = true;
+                        return Some(Err(ConfirmationError::Cancelled(self.signature.clone())));
+                    }
+                    // Not landed yet, or a transient failure retried next poll
+                    _ => {}
+                },
+            }
+        }
+    }
+
+    /// Why the stream should stop, if it should
+    fn stopped(&self) -> Option<ConfirmationError> {
+        if self
+            .cancel_token
+            .as_ref()
+            .is_some_and(CancelToken::is_cancelled)
+        {
+            Some(ConfirmationError::Cancelled(self.signature.clone()))
+        } else if Instant::now() >= self.deadline {
+            Some(ConfirmationError::Timeout(self.signature.clone()))
+        } else {
+            None
+        }
+    }
+
+    async fn subscribe(&self) -> Option<Box<Subscription>> {
+        let url = self.websocket_url.as_deref()?;
+        let params = vec![
+            json!(self.signature.to_base58()),
+            json!({ "commitment": CommitmentLevel::Processed, "enableReceivedNotification": true }),
+        ];
+        PubsubClient::new(url)
+            .subscribe("signatureSubscribe", params)
+            .await
+            .ok()
+            .map(Box::new)
+    }
+
+    /// Wait for a notification or until the next poll is due
+    async fn wake(&mut self) -> Wake {
+        // The first poll runs at once in case the transaction already landed
+        let delay = if self.polled {
+            self.interval
+        } else {
+            Duration::ZERO
+        };
+        self.polled = true;
+        let remaining = self.deadline.saturating_duration_since(Instant::now());
+        let tick = Box::pin(sleep(delay.min(remaining)));
+        let Some(subscription) = &mut self.subscription else {
+            tick.await;
+            return Wake::Poll;
+        };
+        if delay.is_zero() {
+            return Wake::Poll;
+        }
+        match select(Box::pin(subscription.next()), tick).await {
+            Either::Left((Some(Ok(value)), _)) => Wake::Notification(value),
+            Either::Left(_) => Wake::Closed,
+            Either::Right(_) => Wake::Poll,
+        }
+    }
+
+    async fn poll(&self) -> Result<Option<SignatureProgress>, RpcError> {
+        let statuses = self
+            .rpc_client
+            .get_signature_statuses(std::slice::from_ref(&self.signature))
+            .await?;
+        Ok(statuses
+            .into_iter()
+            .next()
+            .flatten()
+            .map(|status| SignatureProgress::from_status(&status)))
+    }
+
+    /// Queue `progress` and any stages skipped before it
+    fn advance(&mut self, progress: SignatureProgress) {
+        let rank = progress.rank();
+        if self.reached.is_some_and(|reached| rank <= reached) {
+            return;
+        }
+        let first = self.reached.map_or(0, |reached| reached + 1);
+        if let SignatureProgress::Failed(_) = progress {
+            self.pending.push_back(progress);
+        } else {
+            self.pending
+                .extend(STAGES[first as usize..=rank as usize].iter().cloned());
+        }
+        self.reached = Some(rank);
+        self.done = rank == SignatureProgress::Finalized.rank();
+    }
+}
+
+/// Decode a `signatureNotification` result
+fn parse_notification(value: &Value) -> Option<SignatureProgress> {
+    match &value["value"] {
+        Value::String(kind) if kind == "receivedSignature" => Some(SignatureProgress::Received),
+        Value::Object(result) => Some(match result.get("err") {
+            None | Some(Value::Null) => SignatureProgress::Processed,
+            Some(error) => SignatureProgress::Failed(TransactionError::from_value(error)),
+        }),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::confirmation::ConfirmationConfig;
+    use crate::infrastructure::rpc::mock::MockRpc;
+    use futures::StreamExt;
+
+    fn status(confirmation_status: CommitmentLevel, err: Option<Value>) -> Option<SignatureStatus> {
+        Some(SignatureStatus {
+            slot: 5,
+            confirmations: Some(0),
+            err,
+            confirmation_status: Some(confirmation_status),
+        })
+    }
+
+    fn confirmer(rpc: MockRpc, timeout: Duration) -> TransactionConfirmer {
+        let config = ConfirmationConfig::for_endpoint("http://127.0.0.1:0")
+            .strategy(ConfirmationStrategy::Hybrid {
+                interval: Duration::from_millis(1),
+            })
+            .timeout(timeout);
+        TransactionConfirmer::new(rpc, config)
+    }
+
+    #[tokio::test]
+    async fn test_tracks_every_stage_in_order() {
+        let signature = Signature::new([9; 64]);
+        let rpc = MockRpc::new().with_statuses(
+            signature.clone(),
+            vec![
+                None,
+                status(CommitmentLevel::Confirmed, None),
+                status(CommitmentLevel::Confirmed, None),
+                status(CommitmentLevel::Finalized, None),
+            ],
+        );
+        let stages: Vec<_> = confirmer(rpc, Duration::from_secs(5))
+            .track_signature(&signature)
+            .map(Result::unwrap)
+            .collect()
+            .await;
+        assert_eq!(stages, STAGES);
+        assert_eq!(stages[2].fraction(), 0.75);
+    }
+
+    #[tokio::test]
+    async fn test_failure_and_timeout_end_the_stream() {
+        let failed = Signature::new([9; 64]);
+        let rpc = MockRpc::new().with_statuses(
+            failed.clone(),
+            vec![status(
+                CommitmentLevel::Processed,
+                Some(json!({ "InstructionError": [0, { "Custom": 1 }] })),
+            )],
+        );
+        let stages: Vec<_> = confirmer(rpc, Duration::from_secs(5))
+            .track_signature(&failed)
+            .collect()
+            .await;
+        assert_eq!(stages.len(), 1);
+        assert!(matches!(&stages[0], Ok(progress) if progress.is_final()));
+
+        let pending = Signature::new([8; 64]);
+        let stages: Vec<_> = confirmer(MockRpc::new(), Duration::from_millis(20))
+            .track_signature(&pending)
+            .collect()
+            .await;
+        assert!(matches!(
+            stages.as_slice(),
+            [Err(ConfirmationError::Timeout(signature))] if *signature == pending
+        ));
+    }
+
+    #[test]
+    fn test_parse_notifications() {
+        let received = json!({ "context": { "slot": 5 }, "value": "receivedSignature" });
+        assert_eq!(
+            parse_notification(&received),
+            Some(SignatureProgress::Received)
+        );
+        let processed = json!({ "context": { "slot": 5 }, "value": { "err": null } });
+        assert_eq!(
+            parse_notification(&processed),
+            Some(SignatureProgress::Processed)
+        );
+    }
+}
@@ -0,0 +1,509 @@
+//! Program decoders for inspecting transactions and accounts
+//!
+//! The node only parses instructions and accounts of the programs it knows,
+//! so an in-house program shows up as opaque bytes in an explorer UI. A
+//! [`ProgramDecoder`] registered in a [`DecoderRegistry`] under the program
+//! id turns its instruction data and account data into a name and JSON
+//! fields. The registry then labels every instruction of a transaction and
+//! fills in parsed account data where the node returned none.
+
+use crate::domain::message::{CompiledInstruction, Transaction};
+#[cfg(feature = "oracles")]
+use crate::domain::oracles;
+use crate::domain::types::{constants::SYSTEM_PROGRAM_ID, Pubkey};
+use crate::infrastructure::rpc::{Account, ConfirmedTransaction, ParsedAccount, RpcError};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+/// An instruction decoded by its program's decoder
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DecodedInstruction {
+    /// Instruction name, e.g. `transfer`
+    pub name: String,
+    /// Arguments and named accounts
+    pub info: Value,
+}
+
+/// Account data decoded by its owner's decoder
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DecodedAccount {
+    /// Account type, e.g. `counter`
+    pub name: String,
+    pub info: Value,
+}
+
+/// Decodes the instructions and accounts of one program
+///
+/// Both methods return `None` for data the decoder does not recognize.
+pub trait ProgramDecoder: Send + Sync {
+    /// Display name of the program
+    fn program_name(&self) -> &str;
+
+    /// Decode an instruction given its data and account keys
+    fn decode_instruction(&self, data: &[u8], accounts: &[Pubkey]) -> Option<DecodedInstruction>;
+
+    /// Decode the data of an account owned by the program
+    fn decode_account(&self, _data: &[u8]) -> Option<DecodedAccount> {
+        None
+    }
+}
+
+type InstructionFn = dyn Fn(&[u8], &[Pubkey]) -> Option<DecodedInstruction> + Send + Sync;
+type AccountFn = dyn Fn(&[u8]) -> Option<DecodedAccount> + Send + Sync;
+
+/// A [`ProgramDecoder`] built from plain functions
+pub struct FnDecoder {
+    name: String,
+    instruction: Option<Box<InstructionFn>>,
+    account: Option<Box<AccountFn>>,
+}
+
+impl FnDecoder {
+    /// A decoder for the program called `name` that decodes nothing yet
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            instruction: None,
+            account: None,
+        }
+    }
+
+    /// Decode instructions with `decode`
+    pub fn instruction(
+        mut self,
+        decode: impl Fn(&[u8], &[Pubkey]) -> Option<DecodedInstruction> + Send + Sync + 'static,
+    ) -> Self {
+        self.instruction = Some(Box::new(decode));
+        self
+    }
+
+    /// Decode account data with `decode`
+    pub fn account(
+        mut self,
+        decode: impl Fn(&[u8]) -> Option<DecodedAccount> + Send + Sync + 'static,
+    ) -> Self {
+        self.account = Some(Box::new(decode));
+        self
+    }
+}
+
+impl ProgramDecoder for FnDecoder {
+    fn program_name(&self) -> &str {
+        &self.name
+    }
+
+    fn decode_instruction(&self, data: &[u8], accounts: &[Pubkey]) -> Option<DecodedInstruction> {
+        self.instruction.as_ref()?(data, accounts)
+    }
+
+    fn decode_account(&self, data: &[u8]) -> Option<DecodedAccount> {
+        self.account.as_ref()?(data)
+    }
+}
+
+/// Decoder for the System program's transfers and account creations
+pub struct SystemDecoder;
+
+impl ProgramDecoder for SystemDecoder {
+    fn program_name(&self) -> &str {
+        "system"
+    }
+
+    fn decode_instruction(&self, data: &[u8], accounts: &[Pubkey]) -> Option<DecodedInstruction> {
+        let tag = u32::from_le_bytes(data.get(..4)?.try_into().ok()?);
+        let u64_at = |offset: usize| {
+            Some(u64::from_le_bytes(
+                data.get(offset..offset + 8)?.try_into().ok()?,
+            ))
+        };
+        let (name, info) = match tag {
+            0 => (
+                "createAccount",
+                json!({
+                    "source": accounts.first()?,
+                    "newAccount": accounts.get(1)?,
+                    "lamports": u64_at(4)?,
+                    "space": u64_at(12)?,
+                    "owner": Pubkey::new(data.get(20..52)?.try_into().ok()?),
+                }),
+            ),
+            2 => (
+                "transfer",
+                json!({
+                    "source": accounts.first()?,
+                    "destination": accounts.get(1)?,
+                    "lamports": u64_at(4)?,
+                }),
+            ),
+            _ => return None,
+        };
+        Some(DecodedInstruction {
+            name: name.to_string(),
+            info,
+        })
+    }
+}
+
+/// Decoder for Pyth price update accounts
+#[cfg(feature = "oracles")]
+pub struct PythDecoder;
+
+#[cfg(feature = "oracles")]
+impl ProgramDecoder for PythDecoder {
+    fn program_name(&self) -> &str {
+        "pyth"
+    }
+
+    fn decode_instruction(&self, _data: &[u8], _accounts: &[Pubkey]) -> Option<DecodedInstruction> {
+        None
+    }
+
+    fn decode_account(&self, data: &[u8]) -> Option<DecodedAccount> {
+        let update = oracles::PythPriceUpdate::from_account_data(data).ok()?;
+        Some(DecodedAccount {
+            name: "priceUpdate".to_string(),
+            info: oracle_price_info(&update.price(), json!(hex(&update.price_message.feed_id))),
+        })
+    }
+}
+
+/// Decoder for Switchboard On-Demand pull feed accounts
+#[cfg(feature = "oracles")]
+pub struct SwitchboardDecoder;
+
+#[cfg(feature = "oracles")]
+impl ProgramDecoder for SwitchboardDecoder {
+    fn program_name(&self) -> &str {
+        "switchboard"
+    }
+
+    fn decode_instruction(&self, _data: &[u8], _accounts: &[Pubkey]) -> Option<DecodedInstruction> {
+        None
+    }
+
+    fn decode_account(&self, data: &[u8]) -> Option<DecodedAccount> {
+        let feed = oracles::SwitchboardFeed::from_account_data(data).ok()?;
+        Some(DecodedAccount {
+            name: "pullFeed".to_string(),
+            info: oracle_price_info(&feed.price(), json!(feed.name)),
+        })
+    }
+}
+
+/// The fields shown for an oracle price, with the feed identified by `feed`
+#[cfg(feature = "oracles")]
+fn oracle_price_info(price: &oracles::OraclePrice, feed: Value) -> Value {
+    json!({
+        "feed": feed,
+        "price": price.mantissa.to_string(),
+        "exponent": price.exponent,
+        "confidence": price.confidence.to_string(),
+        "uiPrice": price.value(),
+        "publishTime": price.publish_time,
+        "slot": price.slot,
+        "verified": price.verified,
+    })
+}
+
+#[cfg(feature = "oracles")]
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(feature = "anchor")]
+impl ProgramDecoder for crate::domain::idl::Idl {
+    fn program_name(&self) -> &str {
+        self.name().unwrap_or("anchor program")
+    }
+
+    /// Names the instruction and its accounts; arguments stay undecoded
+    fn decode_instruction(&self, data: &[u8], accounts: &[Pubkey]) -> Option<DecodedInstruction> {
+        let instruction = self.match_instruction(data)?;
+        let named: serde_json::Map<String, Value> = instruction
+            .accounts
+            .iter()
+            .zip(accounts)
+            .map(|(account, pubkey)| (account.name.clone(), json!(pubkey)))
+            .collect();
+        Some(DecodedInstruction {
+            name: instruction.name.clone(),
+            info: Value::Object(named),
+        })
+    }
+
+    fn decode_account(&self, data: &[u8]) -> Option<DecodedAccount> {
+        let prefix = data.get(..crate::domain::idl::DISCRIMINATOR_LEN)?;
+        let account = self
+            .accounts
+            .iter()
+            .find(|account| account.discriminator().as_slice() == prefix)?;
+        Some(DecodedAccount {
+            name: account.name.clone(),
+            info: Value::Null,
+        })
+    }
+}
+
+/// One instruction of a transaction, decoded when a decoder is registered
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InspectedInstruction {
+    pub program_id: Pubkey,
+    /// Name of the registered decoder's program
+    pub program: Option<String>,
+    pub accounts: Vec<Pubkey>,
+    pub data: Vec<u8>,
+    pub decoded: Option<DecodedInstruction>,
+}
+
+/// Program decoders keyed by program id
+///
+/// Clones share the same decoders.
+#[derive(Clone, Default)]
+pub struct DecoderRegistry {
+    decoders: Arc<RwLock<HashMap<Pubkey, Arc<dyn ProgramDecoder>>>>,
+}
+
+impl DecoderRegistry {
+    /// An empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A registry with decoders for the System program, and for Pyth and
+    /// Switchboard price accounts with the `oracles` feature
+    pub fn with_builtins() -> Self {
+        let registry = Self::new();
+        registry.register(SYSTEM_PROGRAM_ID, SystemDecoder);
+        #[cfg(feature = "oracles")]
+        {
+            registry.register(oracles::PYTH_RECEIVER_PROGRAM_ID, PythDecoder);
+            registry.register(oracles::PYTH_PUSH_ORACLE_PROGRAM_ID, PythDecoder);
+            registry.register(
+                oracles::SWITCHBOARD_ON_DEMAND_PROGRAM_ID,
+                SwitchboardDecoder,
+            );
+        }
+        registry
+    }
+
+    /// Decode `program_id`'s instructions and accounts with `decoder`,
+    /// replacing any previous one
+    pub fn register(&self, program_id: Pubkey, decoder: impl ProgramDecoder + 'static) {
+        if let Ok(mut decoders) = self.decoders.write() {
+            decoders.insert(program_id, Arc::new(decoder));
+        }
+    }
+
+    /// Stop decoding `program_id`
+    pub fn remove(&self, program_id: &Pubkey) {
+        if let Ok(mut decoders) = self.decoders.write() {
+            decoders.remove(program_id);
+        }
+    }
+
+    /// The decoder registered for `program_id`
+    pub fn get(&self, program_id: &Pubkey) -> Option<Arc<dyn ProgramDecoder>> {
+        self.decoders.read().ok()?.get(program_id).cloned()
+    }
+
+    /// Decode one instruction of `program_id`
+    pub fn decode_instruction(
+        &self,
+        program_id: &Pubkey,
+        data: &[u8],
+        accounts: &[Pubkey],
+    ) -> Option<DecodedInstruction> {
+        self.get(program_id)?.decode_instruction(data, accounts)
+    }
+
+    /// The account's data in `jsonParsed` form
+    ///
+    /// `parsed` holds `{"type", "info"}` as the node's own parsers produce.
+    pub fn parse_account(&self, account: &Account) -> Option<ParsedAccount> {
+        let decoder = self.get(&account.owner)?;
+        let decoded = decoder.decode_account(&account.data)?;
+        Some(ParsedAccount {
+            program: decoder.program_name().to_string(),
+            parsed: json!({ "type": decoded.name, "info": decoded.info }),
+            space: account.data.len() as u64,
+        })
+    }
+
+    /// The top-level instructions of a confirmed transaction
+    pub fn inspect(
+        &self,
+        transaction: &ConfirmedTransaction,
+    ) -> Result<Vec<InspectedInstruction>, RpcError> {
+        let keys = transaction.account_keys();
+        transaction
+            .transaction
+            .message
+            .instructions
+            .iter()
+            .map(|instruction| {
+                let data = bs58::decode(&instruction.data)
+                    .into_vec()
+                    .map_err(|e| RpcError::ParseError(e.to_string()))?;
+                self.inspect_compiled(
+                    &keys,
+                    &CompiledInstruction {
+                        program_id_index: instruction.program_id_index,
+                        accounts: instruction.accounts.clone(),
+                        data,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// The instructions of a transaction that has not been sent yet
+    pub fn inspect_transaction(
+        &self,
+        transaction: &Transaction,
+    ) -> Result<Vec<InspectedInstruction>, RpcError> {
+        let message = &transaction.message;
+        message
+            .instructions
+            .iter()
+            .map(|instruction| self.inspect_compiled(&message.account_keys, instruction))
+            .collect()
+    }
+
+    fn inspect_compiled(
+        &self,
+        keys: &[Pubkey],
+        instruction: &CompiledInstruction,
+    ) -> Result<InspectedInstruction, RpcError> {
+        let key = |index: u8| {
+            keys.get(index as usize).copied().ok_or_else(|| {
+                RpcError::ParseError(format!("Account index {} out of range", index))
+            })
+        };
+        let program_id = key(instruction.program_id_index)?;
+        let accounts = instruction
+            .accounts
+            .iter()
+            .map(|&index| key(index))
+            .collect::<Result<Vec<_>, _>>()?;
+        let decoder = self.get(&program_id);
+        Ok(InspectedInstruction {
+            program_id,
+            program: decoder
+                .as_ref()
+                .map(|decoder| decoder.program_name().to_string()),
+            decoded: decoder
+                .and_then(|decoder| decoder.decode_instruction(&instruction.data, &accounts)),
+            accounts,
+            data: instruction.data.clone(),
+        })
+    }
+}
+
+impl std::fmt::Debug for DecoderRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let programs: Vec<Pubkey> = self
+            .decoders
+            .read()
+            .map(|decoders| decoders.keys().copied().collect())
+            .unwrap_or_default();
+        f.debug_struct("DecoderRegistry")
+            .field("programs", &programs)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::flows::TransactionBuilder;
+    use crate::domain::programs::system;
+    use crate::domain::types::Hash;
+
+    /// An in-house counter program storing a `u64` after a one-byte tag
+    fn counter_decoder() -> FnDecoder {
+        FnDecoder::new("counter")
+            .instruction(|data, accounts| {
+                (data.first() == Some(&1)).then(|| DecodedInstruction {
+                    name: "increment".to_string(),
+                    info: json!({ "counter": accounts.first() }),
+                })
+            })
+            .account(|data| {
+                let count = u64::from_le_bytes(data.get(1..9)?.try_into().ok()?);
+                Some(DecodedAccount {
+                    name: "counter".to_string(),
+                    info: json!({ "count": count }),
+                })
+            })
+    }
+
+    #[test]
+    fn test_inspect_with_registered_decoders() {
+        let program_id = Pubkey::new([7; 32]);
+        let payer = Pubkey::new([1; 32]);
+        let counter = Pubkey::new([2; 32]);
+        let registry = DecoderRegistry::with_builtins();
+        registry.register(program_id, counter_decoder());
+
+        let mut increment = system::transfer(payer, counter, 0);
+        increment.program_id = program_id;
+        increment.accounts.truncate(1);
+        increment.accounts[0].pubkey = counter;
+        increment.data = vec![1];
+        let transaction = TransactionBuilder::new(payer)
+            .instruction(system::transfer(payer, counter, 5))
+            .instruction(increment)
+            .build(Hash::new([3; 32]))
+            .unwrap();
+
+        let inspected = registry.inspect_transaction(&transaction).unwrap();
+        let transfer = inspected[0].decoded.as_ref().unwrap();
+        assert_eq!(inspected[0].program.as_deref(), Some("system"));
+        assert_eq!(transfer.name, "transfer");
+        assert_eq!(transfer.info["lamports"], 5);
+        assert_eq!(transfer.info["destination"], json!(counter));
+        assert_eq!(inspected[1].program.as_deref(), Some("counter"));
+        assert_eq!(inspected[1].decoded.as_ref().unwrap().name, "increment");
+
+        registry.remove(&program_id);
+        let inspected = registry.inspect_transaction(&transaction).unwrap();
+        assert_eq!(
+            (inspected[1].program.as_ref(), inspected[1].decoded.as_ref()),
+            (None, None)
+        );
+    }
+
+    #[test]
+    fn test_parse_account() {
+        let program_id = Pubkey::new([7; 32]);
+        let registry = DecoderRegistry::new();
+        registry.register(program_id, counter_decoder());
+
+        let mut data = vec![0; 9];
+        data[1..].copy_from_slice(&42u64.to_le_bytes());
+        let account = Account {
+            pubkey: Pubkey::new([2; 32]),
+            lamports: 1,
+            data,
+            owner: program_id,
+            executable: false,
+            rent_epoch: 0,
+        };
+        let parsed = registry.parse_account(&account).unwrap();
+        assert_eq!(parsed.program, "counter");
+        assert_eq!(
+            parsed.parsed,
+            json!({ "type": "counter", "info": { "count": 42 } })
+        );
+        assert_eq!(parsed.space, 9);
+        assert!(registry
+            .parse_account(&Account {
+                owner: SYSTEM_PROGRAM_ID,
+                ..account
+            })
+            .is_none());
+    }
+}
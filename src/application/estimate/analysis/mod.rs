@@ -0,0 +1,288 @@
+//! Accounts a transaction will touch
+//!
+//! A message lists the accounts its instructions may use and which of them
+//! they may write, but not what the programs will do with them.
+//! [`analyze_transaction`] simulates the transaction and compares each
+//! writable account before and after it, so a signing prompt can say which
+//! accounts will actually be written, created or closed, and which programs
+//! will run, including those only reached through cross-program calls.
+
+use super::{simulate_writable, EstimateError, TransactionEstimate};
+use crate::domain::diff::AccountDiff;
+use crate::domain::message::Transaction;
+use crate::domain::types::Pubkey;
+use crate::infrastructure::rpc::{RpcApi, SimulationResult};
+use std::collections::HashMap;
+use std::fmt;
+
+/// What a transaction does to one of its accounts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountEffect {
+    /// The message only allows reading the account
+    ReadOnly,
+    /// Writable, but the simulation left it as it was or did not run
+    Unchanged,
+    Written,
+    Created,
+    Closed,
+}
+
+impl AccountEffect {
+    /// Whether the account's state changes
+    pub fn is_change(&self) -> bool {
+        matches!(self, Self::Written | Self::Created | Self::Closed)
+    }
+}
+
+impl fmt::Display for AccountEffect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::ReadOnly => "read",
+            Self::Unchanged => "unchanged",
+            Self::Written => "written",
+            Self::Created => "created",
+            Self::Closed => "closed",
+        })
+    }
+}
+
+/// An account of a transaction and what the transaction does to it
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccountAccess {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    /// Whether the message allows writing the account
+    pub is_writable: bool,
+    pub effect: AccountEffect,
+    /// The simulated change, empty unless the account changes
+    pub diff: AccountDiff,
+}
+
+/// What a transaction will do, as found by simulating it
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransactionAnalysis {
+    pub estimate: TransactionEstimate,
+    /// Every account of the message, in message order
+    pub accounts: Vec<AccountAccess>,
+    /// Programs invoked, directly or through cross-program calls, in order of
+    /// first invocation
+    pub programs: Vec<Pubkey>,
+    /// Programs only reached through cross-program calls
+    pub inner_programs: Vec<Pubkey>,
+    /// Whether the simulation ran and succeeded, so the effects are complete
+    pub simulated: bool,
+    /// Whether the node reported cross-program calls; without them `programs`
+    /// holds only the top-level ones
+    pub inner_instructions_reported: bool,
+}
+
+impl TransactionAnalysis {
+    /// Accounts whose state changes in any way
+    pub fn changed(&self) -> Vec<Pubkey> {
+        self.matching(AccountEffect::is_change)
+    }
+
+    /// Accounts the transaction creates
+    pub fn created(&self) -> Vec<Pubkey> {
+        self.matching(|effect| *effect == AccountEffect::Created)
+    }
+
+    /// Accounts the transaction closes
+    pub fn closed(&self) -> Vec<Pubkey> {
+        self.matching(|effect| *effect == AccountEffect::Closed)
+    }
+
+    fn matching(&self, predicate: impl Fn(&AccountEffect) -> bool) -> Vec<Pubkey> {
+        self.accounts
+            .iter()
+            .filter(|access| predicate(&access.effect))
+            .map(|access| access.pubkey)
+            .collect()
+    }
+}
+
+/// Simulate `transaction` and report what it does to each of its accounts
+///
+/// Like [`dry_run`](super::dry_run), signatures are not checked. When the
+/// simulation fails or is skipped, writable accounts are reported as
+/// [`AccountEffect::Unchanged`] and `simulated` is `false`.
+pub async fn analyze_transaction(
+    rpc_client: &dyn RpcApi,
+    transaction: &Transaction,
+) -> Result<TransactionAnalysis, EstimateError> {
+    let (estimate, simulation, changes) = simulate_writable(rpc_client, transaction).await?;
+    let simulated = estimate.error.is_none() && !changes.is_empty();
+    let mut changes: HashMap<Pubkey, AccountDiff> = changes.into_iter().collect();
+
+    let accounts = transaction
+        .message
+        .accounts()
+        .into_iter()
+        .map(|account| {
+            let diff = changes.remove(&account.pubkey).unwrap_or_default();
+            let effect = if !account.is_writable {
+                AccountEffect::ReadOnly
+            } else if diff.created {
+                AccountEffect::Created
+            } else if diff.closed {
+                AccountEffect::Closed
+            } else if !diff.is_empty() {
+                AccountEffect::Written
+            } else {
+                AccountEffect::Unchanged
+            };
+            AccountAccess {
+                pubkey: account.pubkey,
+                is_signer: account.is_signer,
+                is_writable: account.is_writable,
+                effect,
+                diff,
+            }
+        })
+        .collect();
+
+    let programs = invoked_programs(transaction, &simulation);
+    let message = &transaction.message;
+    let top_level: Vec<usize> = message
+        .instructions
+        .iter()
+        .map(|instruction| usize::from(instruction.program_id_index))
+        .collect();
+    let inner_programs = programs
+        .iter()
+        .filter(|program| {
+            !top_level
+                .iter()
+                .any(|index| message.account_keys.get(*index) == Some(*program))
+        })
+        .copied()
+        .collect();
+
+    Ok(TransactionAnalysis {
+        programs,
+        inner_programs,
+        inner_instructions_reported: simulation.inner_instructions.is_some(),
+        estimate,
+        accounts,
+        simulated,
+    })
+}
+
+/// Programs of the top-level instructions, each followed by the ones it calls
+fn invoked_programs(transaction: &Transaction, simulation: &SimulationResult) -> Vec<Pubkey> {
+    let keys = &transaction.message.account_keys;
+    let inner = simulation.inner_instructions.as_deref().unwrap_or_default();
+
+    let mut programs = Vec::new();
+    for (index, instruction) in transaction.message.instructions.iter().enumerate() {
+        let called = inner
+            .iter()
+            .filter(|calls| usize::from(calls.index) == index)
+            .flat_map(|calls| &calls.instructions)
+            .map(|call| call.program_id_index);
+        for program_index in std::iter::once(instruction.program_id_index).chain(called) {
+            if let Some(program) = keys.get(usize::from(program_index)) {
+                if !programs.contains(program) {
+                    programs.push(*program);
+                }
+            }
+        }
+    }
+    programs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::message::Message;
+    use crate::domain::programs::{InstructionAccount, ProgramInstruction};
+    use crate::domain::types::Hash;
+    use crate::infrastructure::rpc::mock::MockRpc;
+    use crate::infrastructure::rpc::{Account, InnerInstructions, UiCompiledInstruction};
+
+    #[tokio::test]
+    async fn test_analysis_reports_effects_and_inner_programs() {
+        let account = |pubkey: Pubkey, lamports: u64| Account {
+            pubkey,
+            lamports,
+            data: Vec::new(),
+            owner: Pubkey::new([9; 32]),
+            executable: false,
+            rent_epoch: 0,
+        };
+        let (payer, created, closed, untouched, oracle) = (
+            Pubkey::new([1; 32]),
+            Pubkey::new([2; 32]),
+            Pubkey::new([3; 32]),
+            Pubkey::new([4; 32]),
+            Pubkey::new([5; 32]),
+        );
+        let (program, token_program) = (Pubkey::new([9; 32]), Pubkey::new([8; 32]));
+        let instruction = ProgramInstruction::new(
+            program,
+            vec![
+                InstructionAccount::writable(created),
+                InstructionAccount::writable(closed),
+                InstructionAccount::writable(untouched),
+                InstructionAccount::readonly(oracle),
+                InstructionAccount::readonly(token_program),
+            ],
+            vec![1],
+            0,
+        );
+        let message = Message::compile(&[instruction], Some(&payer), Hash::new([7; 32])).unwrap();
+        let transaction = Transaction::new_unsigned(message);
+        let token_index = transaction
+            .message
+            .account_keys
+            .iter()
+            .position(|key| *key == token_program)
+            .unwrap() as u8;
+
+        let after = |pubkey: &Pubkey| match *pubkey {
+            key if key == payer => Some(account(payer, 7_000)),
+            key if key == created => Some(account(created, 1_000)),
+            key if key == untouched => Some(account(untouched, 500)),
+            _ => None,
+        };
+        let writable = transaction.message.accounts().into_iter();
+        let after = writable
+            .filter(|account| account.is_writable)
+            .map(|account| after(&account.pubkey))
+            .collect();
+
+        let rpc = MockRpc::new()
+            .with_account(account(payer, 10_000))
+            .with_account(account(closed, 2_000))
+            .with_account(account(untouched, 500))
+            .with_simulation(SimulationResult {
+                units_consumed: Some(9_000),
+                inner_instructions: Some(vec![InnerInstructions {
+                    index: 0,
+                    instructions: vec![UiCompiledInstruction {
+                        program_id_index: token_index,
+                        accounts: Vec::new(),
+                        data: String::new(),
+                        stack_height: Some(2),
+                    }],
+                }]),
+                accounts: after,
+                ..Default::default()
+            });
+
+        let analysis = analyze_transaction(&rpc, &transaction).await.unwrap();
+        assert!(analysis.simulated && analysis.inner_instructions_reported);
+        let effect = |pubkey: Pubkey| {
+            let access = analysis.accounts.iter().find(|a| a.pubkey == pubkey);
+            access.unwrap().effect
+        };
+        assert_eq!(effect(payer), AccountEffect::Written);
+        assert_eq!(effect(untouched), AccountEffect::Unchanged);
+        assert_eq!(effect(oracle), AccountEffect::ReadOnly);
+        assert_eq!(analysis.created(), [created]);
+        assert_eq!(analysis.closed(), [closed]);
+        assert_eq!(analysis.changed(), [payer, created, closed]);
+        assert_eq!(analysis.programs, [program, token_program]);
+        assert_eq!(analysis.inner_programs, [token_program]);
+    }
+}
@@ -0,0 +1,383 @@
+//! Transaction size and compute budget estimates
+//!
+//! A transaction must fit in a 1232-byte packet and its instructions within
+//! the compute unit limit, and a node only reports a violation once the
+//! transaction is sent. [`estimate_transaction`] serializes and simulates it
+//! beforehand, so a review screen can warn before either limit is exceeded.
+//! [`dry_run`] also reports how the simulation changed each writable account,
+//! and [`analyze_transaction`] what it does to every account of the message.
+
+use crate::domain::diff::AccountDiff;
+use crate::domain::errors::TransactionError;
+use crate::domain::message::{MessageError, Transaction};
+use crate::domain::types::constants::COMPUTE_BUDGET_PROGRAM_ID;
+use crate::domain::types::Pubkey;
+use crate::infrastructure::rpc::{RpcApi, RpcError, SimulationResult};
+use base64::{engine::general_purpose, Engine as _};
+use std::fmt;
+use thiserror::Error;
+
+pub mod analysis;
+
+pub use analysis::{analyze_transaction, AccountAccess, AccountEffect, TransactionAnalysis};
+
+/// Largest serialized transaction a node accepts
+pub const PACKET_DATA_SIZE: usize = 1232;
+
+/// Most compute units a transaction can request
+pub const MAX_COMPUTE_UNIT_LIMIT: u64 = 1_400_000;
+
+/// Compute units granted per instruction without a `SetComputeUnitLimit`
+pub const DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT: u64 = 200_000;
+
+/// Usage, in percent of a limit, from which an estimate warns
+const WARNING_PERCENT: u64 = 90;
+
+/// `SetComputeUnitLimit` instruction tag of the compute budget program
+const SET_COMPUTE_UNIT_LIMIT: u8 = 2;
+
+/// Errors that can occur while estimating a transaction
+#[derive(Debug, Clone, Error)]
+pub enum EstimateError {
+    #[error("Message encoding failed: {0}")]
+    Message(#[from] MessageError),
+
+    #[error("RPC error: {0}")]
+    Rpc(#[from] RpcError),
+}
+
+/// A limit the transaction exceeds or comes close to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EstimateWarning {
+    SizeExceeded { size: usize },
+    SizeNearLimit { size: usize },
+    ComputeExceeded { units: u64, limit: u64 },
+    ComputeNearLimit { units: u64, limit: u64 },
+    SimulationFailed(TransactionError),
+}
+
+impl EstimateWarning {
+    /// Whether the transaction cannot land as it is
+    pub fn is_fatal(&self) -> bool {
+        matches!(
+            self,
+            Self::SizeExceeded { .. } | Self::ComputeExceeded { .. } | Self::SimulationFailed(_)
+        )
+    }
+}
+
+impl fmt::Display for EstimateWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SizeExceeded { size } => write!(
+                f,
+                "Transaction is {} bytes, over the {}-byte limit",
+                size, PACKET_DATA_SIZE
+            ),
+            Self::SizeNearLimit { size } => write!(
+                f,
+                "Transaction is {} of {} bytes; little room for more accounts",
+                size, PACKET_DATA_SIZE
+            ),
+            Self::ComputeExceeded { units, limit } => write!(
+                f,
+                "Simulation used {} compute units, over the limit of {}",
+                units, limit
+            ),
+            Self::ComputeNearLimit { units, limit } => write!(
+                f,
+                "Simulation used {} of {} compute units; it may fail if state changes",
+                units, limit
+            ),
+            Self::SimulationFailed(error) => write!(f, "Simulation failed: {}", error),
+        }
+    }
+}
+
+/// Size and compute usage of a transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionEstimate {
+    /// Serialized size in bytes
+    pub size: usize,
+    /// Number of required signatures
+    pub signatures: usize,
+    /// Compute units the simulation consumed, `None` if not simulated
+    pub compute_units: Option<u64>,
+    /// Compute units the runtime grants the transaction
+    pub compute_unit_limit: u64,
+    /// Error the simulation failed with
+    pub error: Option<TransactionError>,
+}
+
+impl TransactionEstimate {
+    /// Measure a transaction without simulating it
+    pub fn from_transaction(transaction: &Transaction) -> Result<Self, MessageError> {
+        Ok(Self {
+            size: transaction.serialize()?.len(),
+            signatures: usize::from(transaction.message.header.num_required_signatures),
+            compute_units: None,
+            compute_unit_limit: compute_unit_limit(transaction),
+            error: None,
+        })
+    }
+
+    /// Limits the transaction exceeds or comes close to
+    pub fn warnings(&self) -> Vec<EstimateWarning> {
+        let mut warnings = Vec::new();
+        if self.size > PACKET_DATA_SIZE {
+            warnings.push(EstimateWarning::SizeExceeded { size: self.size });
+        } else if self.size * 100 >= PACKET_DATA_SIZE * WARNING_PERCENT as usize {
+            warnings.push(EstimateWarning::SizeNearLimit { size: self.size });
+        }
+
+        if let Some(units) = self.compute_units {
+            let limit = self.compute_unit_limit;
+            if units > limit {
+                warnings.push(EstimateWarning::ComputeExceeded { units, limit });
+            } else if units * 100 >= limit * WARNING_PERCENT {
+                warnings.push(EstimateWarning::ComputeNearLimit { units, limit });
+            }
+        }
+
+        if let Some(error) = &self.error {
+            warnings.push(EstimateWarning::SimulationFailed(error.clone()));
+        }
+        warnings
+    }
+
+    /// Whether nothing stops the transaction from landing
+    pub fn fits(&self) -> bool {
+        !self.warnings().iter().any(EstimateWarning::is_fatal)
+    }
+}
+
+/// Estimate of a transaction with the account changes its simulation made
+#[derive(Debug, Clone, PartialEq)]
+pub struct DryRun {
+    pub estimate: TransactionEstimate,
+    /// Writable accounts the transaction changes, empty if simulation failed
+    pub changes: Vec<(Pubkey, AccountDiff)>,
+}
+
+/// Measure `transaction` and simulate it for its compute usage
+///
+/// Signatures are not checked, so the transaction can be estimated before it
+/// is signed. Transactions over the size limit are not simulated, since the
+/// node would reject them.
+pub async fn estimate_transaction(
+    rpc_client: &dyn RpcApi,
+    transaction: &Transaction,
+) -> Result<TransactionEstimate, EstimateError> {
+    let (estimate, _) = simulate(rpc_client, transaction, &[]).await?;
+    Ok(estimate)
+}
+
+/// Estimate `transaction` and diff its writable accounts against their state
+/// after simulation
+pub async fn dry_run(
+    rpc_client: &dyn RpcApi,
+    transaction: &Transaction,
+) -> Result<DryRun, EstimateError> {
+    let (estimate, _, changes) = simulate_writable(rpc_client, transaction).await?;
+    let changes = changes
+        .into_iter()
+        .filter(|(_, diff)| !diff.is_empty())
+        .collect();
+    Ok(DryRun { estimate, changes })
+}
+
+/// Simulate `transaction` and diff each of its writable accounts
+///
+/// The diffs are empty when the simulation did not run or failed.
+async fn simulate_writable(
+    rpc_client: &dyn RpcApi,
+    transaction: &Transaction,
+) -> Result<
+    (
+        TransactionEstimate,
+        SimulationResult,
+        Vec<(Pubkey, AccountDiff)>,
+    ),
+    EstimateError,
+> {
+    let message = &transaction.message;
+    let writable: Vec<Pubkey> = (0..message.account_keys.len())
+        .filter(|i| message.is_writable(*i))
+        .map(|i| message.account_keys[i])
+        .collect();
+
+    let before = rpc_client.get_multiple_accounts(&writable).await?;
+    let (estimate, simulation) = simulate(rpc_client, transaction, &writable).await?;
+    let changes = writable
+        .into_iter()
+        .zip(before)
+        .zip(&simulation.accounts)
+        .map(|((pubkey, old), new)| (pubkey, AccountDiff::compare(old.as_ref(), new.as_ref())))
+        .collect();
+    Ok((estimate, simulation, changes))
+}
+
+/// Estimate and simulate `transaction`, requesting the state of `addresses`
+/// after it
+///
+/// Oversized transactions get an empty simulation result.
+async fn simulate(
+    rpc_client: &dyn RpcApi,
+    transaction: &Transaction,
+    addresses: &[Pubkey],
+) -> Result<(TransactionEstimate, SimulationResult), EstimateError> {
+    let mut estimate = TransactionEstimate::from_transaction(transaction)?;
+    if estimate.size > PACKET_DATA_SIZE {
+        return Ok((estimate, SimulationResult::default()));
+    }
+
+    let encoded = general_purpose::STANDARD.encode(transaction.serialize()?);
+    let simulation = rpc_client
+        .simulate_transaction_with_accounts(&encoded, addresses)
+        .await?
+        .value;
+    estimate.compute_units = simulation.units_consumed;
+    estimate.error = simulation.error();
+    Ok((estimate, simulation))
+}
+
+/// The limit set by a `SetComputeUnitLimit` instruction, or the default of
+/// 200k units per other instruction
+fn compute_unit_limit(transaction: &Transaction) -> u64 {
+    let message = &transaction.message;
+    let mut instructions = 0;
+    for instruction in &message.instructions {
+        let program = message
+            .account_keys
+            .get(usize::from(instruction.program_id_index));
+        if program != Some(&COMPUTE_BUDGET_PROGRAM_ID) {
+            instructions += 1;
+            continue;
+        }
+        if let [SET_COMPUTE_UNIT_LIMIT, a, b, c, d] = instruction.data[..] {
+            return u64::from(u32::from_le_bytes([a, b, c, d])).min(MAX_COMPUTE_UNIT_LIMIT);
+        }
+    }
+    (instructions * DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT).min(MAX_COMPUTE_UNIT_LIMIT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::message::Message;
+    use crate::domain::programs::{InstructionAccount, ProgramInstruction};
+    use crate::domain::types::{Hash, Pubkey};
+    use crate::infrastructure::rpc::mock::MockRpc;
+    use crate::infrastructure::rpc::{Account, SimulationResult};
+    use serde_json::json;
+
+    fn transaction(instructions: Vec<ProgramInstruction>) -> Transaction {
+        let payer = Pubkey::new([1; 32]);
+        let message = Message::compile(&instructions, Some(&payer), Hash::new([7; 32])).unwrap();
+        Transaction::new_unsigned(message)
+    }
+
+    fn memo(data: Vec<u8>) -> ProgramInstruction {
+        let program = Pubkey::new([9; 32]);
+        ProgramInstruction::new(
+            program,
+            vec![InstructionAccount::writable(Pubkey::new([5; 32]))],
+            data,
+            0,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_estimate_simulates_compute_usage() {
+        let set_limit = ProgramInstruction::new(
+            COMPUTE_BUDGET_PROGRAM_ID,
+            Vec::new(),
+            vec![SET_COMPUTE_UNIT_LIMIT, 0x50, 0xc3, 0, 0],
+            0,
+        );
+        let tx = transaction(vec![set_limit, memo(vec![1, 2, 3])]);
+        let rpc = MockRpc::new().with_simulation(SimulationResult {
+            units_consumed: Some(48_000),
+            ..Default::default()
+        });
+
+        let estimate = estimate_transaction(&rpc, &tx).await.unwrap();
+        assert_eq!(estimate.size, tx.serialize().unwrap().len());
+        assert_eq!(estimate.signatures, 1);
+        assert_eq!(estimate.compute_unit_limit, 50_000);
+        assert_eq!(
+            estimate.warnings(),
+            [EstimateWarning::ComputeNearLimit {
+                units: 48_000,
+                limit: 50_000
+            }]
+        );
+        assert!(estimate.fits());
+
+        let rpc = MockRpc::new().with_simulation(SimulationResult {
+            err: Some(json!({ "InstructionError": [0, { "Custom": 1 }] })),
+            units_consumed: Some(1_000),
+            ..Default::default()
+        });
+        let estimate = estimate_transaction(&rpc, &tx).await.unwrap();
+        assert!(matches!(
+            estimate.warnings()[..],
+            [EstimateWarning::SimulationFailed(_)]
+        ));
+        assert!(!estimate.fits());
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_diffs_writable_accounts() {
+        let account = |pubkey: Pubkey, lamports: u64, data: Vec<u8>| Account {
+            pubkey,
+            lamports,
+            data,
+            owner: Pubkey::new([9; 32]),
+            executable: false,
+            rent_epoch: 0,
+        };
+        let payer = Pubkey::new([1; 32]);
+        let target = Pubkey::new([5; 32]);
+        let rpc = MockRpc::new()
+            .with_account(account(payer, 10_000, Vec::new()))
+            .with_simulation(SimulationResult {
+                units_consumed: Some(3_000),
+                accounts: vec![
+                    Some(account(payer, 5_000, Vec::new())),
+                    Some(account(target, 890, vec![1, 2, 3])),
+                ],
+                ..Default::default()
+            });
+
+        let dry_run = dry_run(&rpc, &transaction(vec![memo(vec![1])]))
+            .await
+            .unwrap();
+        assert_eq!(dry_run.estimate.compute_units, Some(3_000));
+        let [(first, paid), (second, created)] = &dry_run.changes[..] else {
+            panic!("expected two changed accounts");
+        };
+        assert_eq!((*first, *second), (payer, target));
+        assert_eq!(paid.lamports.unwrap().new, 5_000);
+        assert!(paid.data.is_empty());
+        assert!(created.created);
+        assert_eq!(created.data[0].new, [1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_oversized_transaction_is_not_simulated() {
+        let tx = transaction(vec![memo(vec![0; 1100]), memo(vec![0; 100])]);
+        let estimate = estimate_transaction(&MockRpc::new().offline(), &tx)
+            .await
+            .unwrap();
+        assert_eq!(estimate.compute_units, None);
+        assert_eq!(estimate.compute_unit_limit, 400_000);
+        assert_eq!(
+            estimate.warnings(),
+            [EstimateWarning::SizeExceeded {
+                size: estimate.size
+            }]
+        );
+        assert!(!estimate.fits());
+    }
+}
@@ -0,0 +1,521 @@
+//! Transaction history export
+//!
+//! Walks `getSignaturesForAddress` and `getTransaction` for a wallet and writes
+//! one record per transaction as CSV or JSON lines. Records are written as soon
+//! as each transaction is fetched, so exporting a long history never holds more
+//! than one page of signatures in memory.
+//!
+//! Each record carries a readable UTC time. Transactions the node returns
+//! without a block time get one interpolated from their neighbours' by a
+//! [`SlotTimeEstimator`].
+
+use crate::application::services::clock::SlotTimeEstimator;
+use crate::domain::types::{Pubkey, Signature};
+use crate::infrastructure::rpc::{
+    ConfirmedTransaction, RpcApi, RpcError, SignaturesForAddressConfig, TokenBalance,
+    MAX_SIGNATURES_PER_REQUEST,
+};
+use crate::infrastructure::time::format_utc;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::sync::Arc;
+use thiserror::Error;
+
+/// Output format of an export
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Comma-separated values with a header row
+    Csv,
+    /// One JSON object per line
+    JsonLines,
+}
+
+/// Slot and time bounds of an export (all inclusive, all optional)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExportRange {
+    pub min_slot: Option<u64>,
+    pub max_slot: Option<u64>,
+    /// Earliest block time as a unix timestamp
+    pub start_time: Option<i64>,
+    /// Latest block time as a unix timestamp
+    pub end_time: Option<i64>,
+}
+
+impl ExportRange {
+    /// Range covering the whole history
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    /// Range between two slots
+    pub fn slots(min_slot: u64, max_slot: u64) -> Self {
+        Self {
+            min_slot: Some(min_slot),
+            max_slot: Some(max_slot),
+            ..Self::default()
+        }
+    }
+
+    /// Range between two unix timestamps
+    pub fn time(start_time: i64, end_time: i64) -> Self {
+        Self {
+            start_time: Some(start_time),
+            end_time: Some(end_time),
+            ..Self::default()
+        }
+    }
+
+    /// Whether a transaction is newer than the range
+    fn is_after(&self, slot: u64, block_time: Option<i64>) -> bool {
+        self.max_slot.is_some_and(|max| slot > max)
+            || matches!((self.end_time, block_time), (Some(end), Some(time)) if time > end)
+    }
+
+    /// Whether a transaction is older than the range
+    fn is_before(&self, slot: u64, block_time: Option<i64>) -> bool {
+        self.min_slot.is_some_and(|min| slot < min)
+            || matches!((self.start_time, block_time), (Some(start), Some(time)) if time < start)
+    }
+}
+
+/// One exported transaction, seen from the wallet's point of view
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HistoryRecord {
+    pub signature: Signature,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    /// Whether `block_time` was estimated from neighbouring blocks
+    pub block_time_estimated: bool,
+    /// `block_time` as `YYYY-MM-DD HH:MM:SS UTC`
+    pub time: Option<String>,
+    pub success: bool,
+    /// Fee in lamports (paid by the fee payer, not necessarily the wallet)
+    pub fee: u64,
+    /// Change of the wallet's SOL balance in lamports, fee included
+    pub sol_delta: i64,
+    /// Other accounts whose SOL or token balance changed
+    pub counterparties: Vec<Pubkey>,
+    /// Changes of token accounts owned by the wallet
+    pub token_deltas: Vec<TokenDelta>,
+}
+
+/// Change of a token balance
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TokenDelta {
+    pub mint: Pubkey,
+    /// Change in raw base units
+    pub amount: i128,
+    pub decimals: u8,
+}
+
+/// Errors that can occur while exporting history
+#[derive(Debug, Error)]
+pub enum ExportError {
+    #[error("RPC error: {0}")]
+    Rpc(#[from] RpcError),
+
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Serialization error: {0}")]
+    Serialization(String),
+}
+
+impl HistoryRecord {
+    /// Build a record for `wallet` from a fetched transaction
+    pub fn from_transaction(
+        wallet: &Pubkey,
+        signature: Signature,
+        transaction: &ConfirmedTransaction,
+    ) -> Self {
+        let keys = transaction.account_keys();
+        let meta = transaction.meta.as_ref();
+        let mut counterparties = Vec::new();
+        let mut sol_delta = 0i64;
+
+        if let Some(meta) = meta {
+            for (index, key) in keys.iter().enumerate() {
+                let pre = meta.pre_balances.get(index).copied().unwrap_or(0) as i64;
+                let post = meta.post_balances.get(index).copied().unwrap_or(0) as i64;
+                if key == wallet {
+                    sol_delta += post - pre;
+                } else if post != pre {
+                    push_unique(&mut counterparties, *key);
+                }
+            }
+        }
+
+        let pre_tokens = meta
+            .and_then(|meta| meta.pre_token_balances.as_deref())
+            .unwrap_or_default();
+        let post_tokens = meta
+            .and_then(|meta| meta.post_token_balances.as_deref())
+            .unwrap_or_default();
+
+        // Net change per (account, mint), so closed and newly created accounts count too
+        let mut changes: BTreeMap<(usize, String), (TokenDelta, Option<Pubkey>)> = BTreeMap::new();
+        for (balances, sign) in [(pre_tokens, -1i128), (post_tokens, 1i128)] {
+            for balance in balances {
+                let entry = changes
+                    .entry((balance.account_index, balance.mint.to_base58()))
+                    .or_insert_with(|| {
+                        (
+                            TokenDelta {
+                                mint: balance.mint,
+                                amount: 0,
+                                decimals: balance.ui_token_amount.decimals,
+                            },
+                            balance.owner,
+                        )
+                    });
+                entry.0.amount += sign * raw_amount(balance);
+            }
+        }
+
+        let mut token_deltas: Vec<TokenDelta> = Vec::new();
+        for (delta, owner) in changes.into_values().filter(|(delta, _)| delta.amount != 0) {
+            if owner.as_ref() == Some(wallet) {
+                match token_deltas.iter_mut().find(|d| d.mint == delta.mint) {
+                    Some(existing) => existing.amount += delta.amount,
+                    None => token_deltas.push(delta),
+                }
+            } else if let Some(owner) = owner {
+                push_unique(&mut counterparties, owner);
+            }
+        }
+
+        Self {
+            signature,
+            slot: transaction.slot,
+            block_time: transaction.block_time,
+            block_time_estimated: false,
+            time: transaction.block_time.map(format_utc),
+            success: meta.is_some() && transaction.error().is_none(),
+            fee: meta.map_or(0, |meta| meta.fee),
+            sol_delta,
+            counterparties,
+            token_deltas,
+        }
+    }
+
+    /// Fill in a missing block time from `estimator`
+    pub fn estimate_time(&mut self, estimator: &SlotTimeEstimator) {
+        if self.block_time.is_some() {
+            return;
+        }
+        self.block_time = estimator.estimate(self.slot);
+        self.block_time_estimated = self.block_time.is_some();
+        self.time = self.block_time.map(|time| format!("~{}", format_utc(time)));
+    }
+
+    /// CSV header row matching [`HistoryRecord::to_csv_row`]
+    pub fn csv_header() -> &'static str {
+        "signature,slot,block_time,status,fee,sol_delta,counterparties,token_deltas"
+    }
+
+    /// Render the record as a CSV row (without trailing newline)
+    pub fn to_csv_row(&self) -> String {
+        let counterparties = self
+            .counterparties
+            .iter()
+            .map(Pubkey::to_base58)
+            .collect::<Vec<_>>()
+            .join(";");
+        let token_deltas = self
+            .token_deltas
+            .iter()
+            .map(|delta| {
+                format!(
+                    "{}={}",
+                    delta.mint,
+                    format_units(delta.amount, delta.decimals)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(";");
+
+        [
+            self.signature.to_base58(),
+            self.slot.to_string(),
+            self.block_time.map(|t| t.to_string()).unwrap_or_default(),
+            if self.success { "success" } else { "failed" }.to_string(),
+            self.fee.to_string(),
+            self.sol_delta.to_string(),
+            counterparties,
+            token_deltas,
+        ]
+        .iter()
+        .map(|field| csv_escape(field))
+        .collect::<Vec<_>>()
+        .join(",")
+    }
+}
+
+/// Exports a wallet's transaction history
+pub struct TransactionHistoryExporter {
+    rpc_client: Arc<dyn RpcApi>,
+    page_size: usize,
+}
+
+impl TransactionHistoryExporter {
+    /// Create a new exporter
+    pub fn new(rpc_client: impl RpcApi + 'static) -> Self {
+        Self::from_api(Arc::new(rpc_client))
+    }
+
+    /// Create a new exporter from a shared RPC implementation
+    pub fn from_api(rpc_client: Arc<dyn RpcApi>) -> Self {
+        Self {
+            rpc_client,
+            page_size: MAX_SIGNATURES_PER_REQUEST,
+        }
+    }
+
+    /// Set how many signatures are requested per page
+    pub fn page_size(mut self, page_size: usize) -> Self {
+        self.page_size = page_size.clamp(1, MAX_SIGNATURES_PER_REQUEST);
+        self
+    }
+
+    /// Visit every transaction of `wallet` within `range`, newest first
+    ///
+    /// Returns the number of records visited.
+    pub async fn for_each_record<F>(
+        &self,
+        wallet: &Pubkey,
+        range: ExportRange,
+        mut visit: F,
+    ) -> Result<usize, ExportError>
+    where
+        F: FnMut(HistoryRecord) -> Result<(), ExportError>,
+    {
+        let mut before = None;
+        let mut count = 0;
+        let mut times = SlotTimeEstimator::new();
+
+        loop {
+            let page = self
+                .rpc_client
+                .get_signatures_for_address(
+                    wallet,
+                    SignaturesForAddressConfig {
+                        before: before.clone(),
+                        until: None,
+                        limit: Some(self.page_size),
+                    },
+                )
+                .await?;
+            let page_len = page.len();
+
+            for info in &page {
+                if let Some(time) = info.block_time {
+                    times.record(info.slot, time);
+                }
+            }
+
+            for info in page {
+                before = Some(info.signature.clone());
+
+                if range.is_after(info.slot, info.block_time) {
+                    continue;
+                }
+                if range.is_before(info.slot, info.block_time) {
+                    return Ok(count);
+                }
+
+                if let Some(transaction) = self.rpc_client.get_transaction(&info.signature).await? {
+                    let mut record =
+                        HistoryRecord::from_transaction(wallet, info.signature, &transaction);
+                    record.estimate_time(&times);
+                    visit(record)?;
+                    count += 1;
+                }
+            }
+
+            if page_len < self.page_size {
+                return Ok(count);
+            }
+        }
+    }
+
+    /// Write the history of `wallet` within `range` to `writer`
+    ///
+    /// Returns the number of records written.
+    pub async fn export<W: Write>(
+        &self,
+        wallet: &Pubkey,
+        range: ExportRange,
+        format: ExportFormat,
+        writer: &mut W,
+    ) -> Result<usize, ExportError> {
+        if format == ExportFormat::Csv {
+            writeln!(writer, "{}", HistoryRecord::csv_header())?;
+        }
+
+        self.for_each_record(wallet, range, |record| {
+            match format {
+                ExportFormat::Csv => writeln!(writer, "{}", record.to_csv_row())?,
+                ExportFormat::JsonLines => {
+                    serde_json::to_writer(&mut *writer, &record)
+                        .map_err(|e| ExportError::Serialization(e.to_string()))?;
+                    writeln!(writer)?;
+                }
+            }
+            Ok(())
+        })
+        .await
+    }
+}
+
+fn raw_amount(balance: &TokenBalance) -> i128 {
+    balance.ui_token_amount.amount.parse().unwrap_or(0)
+}
+
+fn push_unique(keys: &mut Vec<Pubkey>, key: Pubkey) {
+    if !keys.contains(&key) {
+        keys.push(key);
+    }
+}
+
+/// Format raw base units as a decimal string
+fn format_units(amount: i128, decimals: u8) -> String {
+    let sign = if amount < 0 { "-" } else { "" };
+    let digits = amount.unsigned_abs().to_string();
+    let decimals = usize::from(decimals);
+    if decimals == 0 {
+        return format!("{}{}", sign, digits);
+    }
+
+    let padded = format!("{:0>width$}", digits, width = decimals + 1);
+    let (whole, fraction) = padded.split_at(padded.len() - decimals);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        format!("{}{}", sign, whole)
+    } else {
+        format!("{}{}.{}", sign, whole, fraction)
+    }
+}
+
+fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn key(byte: u8) -> Pubkey {
+        Pubkey::new([byte; 32])
+    }
+
+    fn fixture() -> ConfirmedTransaction {
+        let mint = key(9);
+        serde_json::from_value(json!({
+            "slot": 42,
+            "blockTime": 1_700_000_000,
+            "meta": {
+                "err": null,
+                "fee": 5000,
+                "preBalances": [1_000_000, 0, 1],
+                "postBalances": [894_000, 100_000, 1],
+                "preTokenBalances": [{
+                    "accountIndex": 1, "mint": mint.to_base58(), "owner": key(2).to_base58(),
+                    "uiTokenAmount": { "amount": "0", "decimals": 6 }
+                }, {
+                    "accountIndex": 3, "mint": mint.to_base58(), "owner": key(1).to_base58(),
+                    "uiTokenAmount": { "amount": "2500000", "decimals": 6 }
+                }],
+                "postTokenBalances": [{
+                    "accountIndex": 1, "mint": mint.to_base58(), "owner": key(2).to_base58(),
+                    "uiTokenAmount": { "amount": "1500000", "decimals": 6 }
+                }, {
+                    "accountIndex": 3, "mint": mint.to_base58(), "owner": key(1).to_base58(),
+                    "uiTokenAmount": { "amount": "1000000", "decimals": 6 }
+                }],
+                "loadedAddresses": { "writable": [key(4).to_base58()], "readonly": [] }
+            },
+            "transaction": {
+                "signatures": [Signature::new([5; 64]).to_base58()],
+                "message": {
+                    "accountKeys": [key(1).to_base58(), key(2).to_base58(), key(3).to_base58()],
+                    "recentBlockhash": "11111111111111111111111111111111",
+                    "instructions": []
+                }
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_record_from_transaction() {
+        let transaction = fixture();
+        assert_eq!(transaction.account_keys().len(), 4);
+
+        let record =
+            HistoryRecord::from_transaction(&key(1), Signature::new([5; 64]), &transaction);
+        assert!(record.success);
+        assert_eq!(record.fee, 5000);
+        assert_eq!(record.sol_delta, -106_000);
+        assert_eq!(record.counterparties, vec![key(2)]);
+        assert_eq!(
+            record.token_deltas,
+            vec![TokenDelta {
+                mint: key(9),
+                amount: -1_500_000,
+                decimals: 6
+            }]
+        );
+
+        assert_eq!(record.time.as_deref(), Some("2023-11-14 22:13:20 UTC"));
+
+        let row = record.to_csv_row();
+        assert!(row.contains(",42,1700000000,success,5000,-106000,"));
+        assert!(row.ends_with(&format!("{}=-1.5", key(9))));
+    }
+
+    #[test]
+    fn test_estimated_time() {
+        let mut transaction = fixture();
+        transaction.block_time = None;
+        let mut record =
+            HistoryRecord::from_transaction(&key(1), Signature::new([5; 64]), &transaction);
+        assert_eq!(record.time, None);
+
+        let mut times = SlotTimeEstimator::new();
+        times.record(40, 1_700_000_000);
+        times.record(50, 1_700_000_010);
+        record.estimate_time(&times);
+        assert_eq!(record.block_time, Some(1_700_000_002));
+        assert!(record.block_time_estimated);
+        assert_eq!(record.time.as_deref(), Some("~2023-11-14 22:13:22 UTC"));
+    }
+
+    #[test]
+    fn test_range_bounds() {
+        let range = ExportRange::slots(10, 20);
+        assert!(range.is_after(21, None));
+        assert!(range.is_before(9, None));
+        assert!(!range.is_after(20, None) && !range.is_before(10, None));
+
+        let range = ExportRange::time(100, 200);
+        assert!(range.is_after(0, Some(201)));
+        assert!(range.is_before(0, Some(99)));
+        assert!(!range.is_before(0, None));
+    }
+
+    #[test]
+    fn test_formatting_helpers() {
+        assert_eq!(format_units(1_500_000, 6), "1.5");
+        assert_eq!(format_units(-5, 2), "-0.05");
+        assert_eq!(format_units(300, 0), "300");
+        assert_eq!(format_units(1_000_000, 6), "1");
+        assert_eq!(csv_escape("a,b"), "\"a,b\"");
+        assert_eq!(csv_escape("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+}
@@ -0,0 +1,238 @@
+//! Transaction templates for common flows
+//!
+//! Most dApp transactions are one of a handful of instruction sequences:
+//! paying a wallet in a token it may not hold yet, wrapping SOL around a swap,
+//! or creating an account for an Anchor program to initialize. The functions
+//! here compose those sequences into a [`TransactionBuilder`] that can take
+//! more instructions and then be compiled for signing.
+
+use crate::domain::discriminator::instruction_discriminator;
+use crate::domain::message::{Message, MessageError, Transaction};
+use crate::domain::programs::{system, InstructionAccount, ProgramInstruction};
+use crate::domain::types::{Hash, Pubkey};
+#[cfg(feature = "token")]
+use crate::domain::{
+    programs::token,
+    types::constants::{NATIVE_MINT, TOKEN_PROGRAM_ID},
+};
+
+/// Instructions of a transaction that has not been compiled yet
+#[derive(Debug, Clone)]
+pub struct TransactionBuilder {
+    payer: Pubkey,
+    instructions: Vec<ProgramInstruction>,
+}
+
+impl TransactionBuilder {
+    /// Start a transaction whose fees are paid by `payer`
+    pub fn new(payer: Pubkey) -> Self {
+        Self {
+            payer,
+            instructions: Vec::new(),
+        }
+    }
+
+    /// Append an instruction
+    pub fn instruction(mut self, instruction: ProgramInstruction) -> Self {
+        self.instructions.push(instruction);
+        self
+    }
+
+    /// The fee payer
+    pub fn payer(&self) -> &Pubkey {
+        &self.payer
+    }
+
+    /// The instructions, in execution order
+    pub fn instructions(&self) -> &[ProgramInstruction] {
+        &self.instructions
+    }
+
+    /// Accounts that must sign, the payer first
+    pub fn signers(&self) -> Vec<Pubkey> {
+        let mut signers = vec![self.payer];
+        let accounts = self.instructions.iter().flat_map(|ix| &ix.accounts);
+        for account in accounts.filter(|account| account.is_signer) {
+            if !signers.contains(&account.pubkey) {
+                signers.push(account.pubkey);
+            }
+        }
+        signers
+    }
+
+    /// Compile into an unsigned transaction using `recent_blockhash`
+    pub fn build(&self, recent_blockhash: Hash) -> Result<Transaction, MessageError> {
+        let message = Message::compile(&self.instructions, Some(&self.payer), recent_blockhash)?;
+        Ok(Transaction::new_unsigned(message))
+    }
+}
+
+/// An Anchor instruction: the discriminator of `name` followed by `args`
+///
+/// `args` are the instruction's arguments, already Borsh-encoded.
+pub fn anchor_instruction(
+    program_id: Pubkey,
+    name: &str,
+    accounts: Vec<InstructionAccount>,
+    args: &[u8],
+) -> ProgramInstruction {
+    let mut data = instruction_discriminator(name).to_vec();
+    data.extend_from_slice(args);
+    ProgramInstruction::new(program_id, accounts, data, 0)
+}
+
+/// Send `amount` of `mint` to `recipient`, creating their token account if needed
+///
+/// Tokens move between the associated token accounts of `sender` and
+/// `recipient`; `sender` pays and signs.
+#[cfg(feature = "token")]
+pub fn create_ata_and_transfer(
+    sender: Pubkey,
+    recipient: Pubkey,
+    mint: Pubkey,
+    token_program: Pubkey,
+    amount: u64,
+    decimals: u8,
+) -> TransactionBuilder {
+    let source = token::associated_token_address(&sender, &mint, &token_program);
+    let destination = token::associated_token_address(&recipient, &mint, &token_program);
+    TransactionBuilder::new(sender)
+        .instruction(token::create_associated_token_account_idempotent(
+            sender,
+            recipient,
+            mint,
+            token_program,
+        ))
+        .instruction(token::transfer_checked(
+            token_program,
+            source,
+            mint,
+            destination,
+            sender,
+            amount,
+            decimals,
+        ))
+}
+
+/// Wrap `lamports` of SOL, run `swap`, then unwrap whatever SOL is left
+///
+/// `swap` is built by the caller against `owner`'s wrapped SOL account, the
+/// associated token account of [`NATIVE_MINT`]. Closing that account at the
+/// end returns its remaining balance and rent to `owner`.
+#[cfg(feature = "token")]
+pub fn wrap_sol_and_swap(
+    owner: Pubkey,
+    lamports: u64,
+    swap: ProgramInstruction,
+) -> TransactionBuilder {
+    let wrapped = token::associated_token_address(&owner, &NATIVE_MINT, &TOKEN_PROGRAM_ID);
+    TransactionBuilder::new(owner)
+        .instruction(token::create_associated_token_account_idempotent(
+            owner,
+            owner,
+            NATIVE_MINT,
+            TOKEN_PROGRAM_ID,
+        ))
+        .instruction(system::transfer(owner, wrapped, lamports))
+        .instruction(token::sync_native(TOKEN_PROGRAM_ID, wrapped))
+        .instruction(swap)
+        .instruction(token::close_account(
+            TOKEN_PROGRAM_ID,
+            wrapped,
+            owner,
+            owner,
+        ))
+}
+
+/// Create `new_account` for `initialize`'s program and run `initialize`
+///
+/// The account gets `space` bytes and `lamports`, normally the rent-exempt
+/// minimum, and is owned by the program so it can write the account's
+/// discriminator. `initialize` must list `new_account` itself, see
+/// [`anchor_instruction`].
+pub fn create_and_initialize_anchor_account(
+    payer: Pubkey,
+    new_account: Pubkey,
+    space: u64,
+    lamports: u64,
+    initialize: ProgramInstruction,
+) -> TransactionBuilder {
+    let create =
+        system::create_account(payer, new_account, lamports, space, &initialize.program_id);
+    TransactionBuilder::new(payer)
+        .instruction(create)
+        .instruction(initialize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::types::constants::SYSTEM_PROGRAM_ID;
+
+    #[test]
+    fn test_create_and_initialize_anchor_account() {
+        let payer = Pubkey::new([1; 32]);
+        let counter = Pubkey::new([2; 32]);
+        let program_id = Pubkey::new([3; 32]);
+        let initialize = anchor_instruction(
+            program_id,
+            "initialize",
+            vec![
+                InstructionAccount::writable(counter),
+                InstructionAccount::signer(payer, true),
+                InstructionAccount::program(SYSTEM_PROGRAM_ID),
+            ],
+            &7u64.to_le_bytes(),
+        );
+
+        let flow = create_and_initialize_anchor_account(payer, counter, 16, 1_002_240, initialize);
+        let instructions = flow.instructions();
+        assert_eq!(instructions[0].program_id, SYSTEM_PROGRAM_ID);
+        assert_eq!(&instructions[0].data[20..], program_id.as_bytes());
+        assert_eq!(
+            &instructions[1].data[..8],
+            &instruction_discriminator("initialize")
+        );
+        assert_eq!(&instructions[1].data[8..], &7u64.to_le_bytes());
+        assert_eq!(flow.signers(), [payer, counter]);
+
+        let transaction = flow.build(Hash::new([5; 32])).unwrap();
+        assert_eq!(transaction.signatures.len(), 2);
+        assert_eq!(transaction.message.account_keys[0], payer);
+    }
+
+    #[cfg(feature = "token")]
+    #[test]
+    fn test_token_flows() {
+        let sender = Pubkey::new([1; 32]);
+        let recipient = Pubkey::new([2; 32]);
+        let mint = Pubkey::new([3; 32]);
+
+        let flow = create_ata_and_transfer(sender, recipient, mint, TOKEN_PROGRAM_ID, 250, 6);
+        let [create, transfer] = flow.instructions() else {
+            panic!("expected two instructions");
+        };
+        let destination = token::associated_token_address(&recipient, &mint, &TOKEN_PROGRAM_ID);
+        assert_eq!(create.accounts[1].pubkey, destination);
+        assert_eq!(transfer.accounts[2].pubkey, destination);
+        assert_eq!(flow.signers(), [sender]);
+        assert_eq!(flow.build(Hash::new([5; 32])).unwrap().signatures.len(), 1);
+
+        let swap_program = Pubkey::new([4; 32]);
+        let swap = ProgramInstruction::new(swap_program, Vec::new(), vec![1], 1);
+        let flow = wrap_sol_and_swap(sender, 1_000_000, swap);
+        let programs: Vec<Pubkey> = flow.instructions().iter().map(|ix| ix.program_id).collect();
+        assert_eq!(
+            programs,
+            [
+                crate::domain::types::constants::ASSOCIATED_TOKEN_PROGRAM_ID,
+                SYSTEM_PROGRAM_ID,
+                TOKEN_PROGRAM_ID,
+                swap_program,
+                TOKEN_PROGRAM_ID,
+            ]
+        );
+        let wrapped = token::associated_token_address(&sender, &NATIVE_MINT, &TOKEN_PROGRAM_ID);
+        assert_eq!(flow.instructions()[1].accounts[1].pubkey, wrapped);
+    }
+}
@@ -0,0 +1,500 @@
+//! Token swaps through the Jupiter aggregator
+//!
+//! [`JupiterClient`] asks the Jupiter Swap API for the best route between two
+//! mints, then for a transaction executing that route for a given wallet. The
+//! transaction comes back unsigned as a [`Transaction`], so it goes through
+//! the same signing and sending path as one built locally. Versioned messages
+//! are not supported yet, so quotes are requested as legacy transactions,
+//! which limits routes to those that fit without address lookup tables.
+
+use crate::application::sender::{SendError, SentTransaction, TransactionSender};
+use crate::domain::message::{MessageError, Transaction};
+use crate::domain::types::Pubkey;
+use crate::infrastructure::http::{HttpClient, HttpError};
+use crate::infrastructure::signer::{Signer, SignerError};
+use base64::{engine::general_purpose, Engine as _};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt::Write as _;
+use thiserror::Error;
+
+/// Base URL of the public Jupiter Swap API
+pub const JUPITER_API_URL: &str = "https://lite-api.jup.ag/swap/v1";
+
+/// Slippage allowed when a [`QuoteRequest`] does not set one, 0.5%
+pub const DEFAULT_SLIPPAGE_BPS: u16 = 50;
+
+/// Errors that can occur while swapping through Jupiter
+#[derive(Debug, Clone, Error)]
+pub enum JupiterError {
+    #[error("HTTP error: {0}")]
+    Http(#[from] HttpError),
+
+    #[error("Invalid Jupiter response: {0}")]
+    InvalidResponse(String),
+
+    #[error("Invalid swap transaction: {0}")]
+    Message(#[from] MessageError),
+
+    #[error("Signing failed: {0}")]
+    Signer(#[from] SignerError),
+
+    #[error("Send error: {0}")]
+    Send(#[from] SendError),
+}
+
+/// Which side of a swap is fixed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SwapMode {
+    /// Sell exactly the amount; the output varies within the slippage
+    #[default]
+    ExactIn,
+    /// Buy exactly the amount; the input varies within the slippage
+    ExactOut,
+}
+
+impl SwapMode {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::ExactIn => "ExactIn",
+            Self::ExactOut => "ExactOut",
+        }
+    }
+}
+
+/// What to quote: an amount of one mint to swap for another
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuoteRequest {
+    pub input_mint: Pubkey,
+    pub output_mint: Pubkey,
+    /// Amount in base units of the fixed side's mint
+    pub amount: u64,
+    pub slippage_bps: u16,
+    pub swap_mode: SwapMode,
+    /// Only route through a single market
+    pub only_direct_routes: bool,
+    /// Most accounts the route may use, to leave room for other instructions
+    pub max_accounts: Option<usize>,
+}
+
+impl QuoteRequest {
+    /// Quote selling `amount` of `input_mint` for `output_mint`
+    pub fn new(input_mint: Pubkey, output_mint: Pubkey, amount: u64) -> Self {
+        Self {
+            input_mint,
+            output_mint,
+            amount,
+            slippage_bps: DEFAULT_SLIPPAGE_BPS,
+            swap_mode: SwapMode::ExactIn,
+            only_direct_routes: false,
+            max_accounts: None,
+        }
+    }
+
+    /// Set the slippage allowed, in basis points
+    pub fn slippage_bps(mut self, bps: u16) -> Self {
+        self.slippage_bps = bps;
+        self
+    }
+
+    /// Set which side of the swap `amount` fixes
+    pub fn swap_mode(mut self, mode: SwapMode) -> Self {
+        self.swap_mode = mode;
+        self
+    }
+
+    /// Whether to only route through a single market
+    pub fn only_direct_routes(mut self, direct: bool) -> Self {
+        self.only_direct_routes = direct;
+        self
+    }
+
+    /// Limit the accounts the route may use
+    pub fn max_accounts(mut self, accounts: usize) -> Self {
+        self.max_accounts = Some(accounts);
+        self
+    }
+
+    /// The `/quote` URL under `base_url`
+    fn url(&self, base_url: &str) -> String {
+        let mut url = format!(
+            "{}/quote?inputMint={}&outputMint={}&amount={}&slippageBps={}&swapMode={}\
+             &onlyDirectRoutes={}&asLegacyTransaction=true",
+            base_url,
+            self.input_mint,
+            self.output_mint,
+            self.amount,
+            self.slippage_bps,
+            self.swap_mode.as_str(),
+            self.only_direct_routes,
+        );
+        if let Some(max) = self.max_accounts {
+            let _ = write!(url, "&maxAccounts={}", max);
+        }
+        url
+    }
+}
+
+/// One market a quote routes through
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteStep {
+    /// Name of the market's protocol, e.g. `Whirlpool`
+    pub label: Option<String>,
+    /// The market's account
+    pub amm_key: Pubkey,
+    pub input_mint: Pubkey,
+    pub output_mint: Pubkey,
+    pub in_amount: u64,
+    pub out_amount: u64,
+    /// Share of the swap routed through this step
+    pub percent: u8,
+}
+
+/// Jupiter's best route for a [`QuoteRequest`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Quote {
+    pub input_mint: Pubkey,
+    pub output_mint: Pubkey,
+    pub in_amount: u64,
+    pub out_amount: u64,
+    /// Least output, or most input for [`SwapMode::ExactOut`], after slippage
+    pub other_amount_threshold: u64,
+    pub swap_mode: SwapMode,
+    pub slippage_bps: u16,
+    /// Price impact as a fraction, 0.01 being 1%
+    pub price_impact: f64,
+    pub route: Vec<RouteStep>,
+    /// The response as received, which the swap request sends back
+    response: Value,
+}
+
+impl Quote {
+    /// Decode a `/quote` response
+    pub fn from_response(response: Value) -> Result<Self, JupiterError> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct SwapInfo {
+            amm_key: Pubkey,
+            label: Option<String>,
+            input_mint: Pubkey,
+            output_mint: Pubkey,
+            in_amount: String,
+            out_amount: String,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Step {
+            swap_info: SwapInfo,
+            percent: u8,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Raw {
+            input_mint: Pubkey,
+            output_mint: Pubkey,
+            in_amount: String,
+            out_amount: String,
+            other_amount_threshold: String,
+            swap_mode: SwapMode,
+            slippage_bps: u16,
+            #[serde(default)]
+            price_impact_pct: Option<String>,
+            route_plan: Vec<Step>,
+        }
+
+        let raw: Raw = serde_json::from_value(response.clone())
+            .map_err(|e| JupiterError::InvalidResponse(e.to_string()))?;
+        let route = raw
+            .route_plan
+            .into_iter()
+            .map(|step| {
+                let info = step.swap_info;
+                Ok(RouteStep {
+                    label: info.label,
+                    amm_key: info.amm_key,
+                    input_mint: info.input_mint,
+                    output_mint: info.output_mint,
+                    in_amount: parse_amount(&info.in_amount)?,
+                    out_amount: parse_amount(&info.out_amount)?,
+                    percent: step.percent,
+                })
+            })
+            .collect::<Result<_, JupiterError>>()?;
+        let price_impact = match raw.price_impact_pct {
+            Some(impact) => impact
+                .parse()
+                .map_err(|_| JupiterError::InvalidResponse(format!("price impact {}", impact)))?,
+            None => 0.0,
+        };
+
+        Ok(Self {
+            input_mint: raw.input_mint,
+            output_mint: raw.output_mint,
+            in_amount: parse_amount(&raw.in_amount)?,
+            out_amount: parse_amount(&raw.out_amount)?,
+            other_amount_threshold: parse_amount(&raw.other_amount_threshold)?,
+            swap_mode: raw.swap_mode,
+            slippage_bps: raw.slippage_bps,
+            price_impact,
+            route,
+            response,
+        })
+    }
+}
+
+/// Body of a `/swap` request
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SwapRequest<'a> {
+    user_public_key: Pubkey,
+    quote_response: &'a Value,
+    wrap_and_unwrap_sol: bool,
+    dynamic_compute_unit_limit: bool,
+    as_legacy_transaction: bool,
+}
+
+/// An unsigned transaction executing a [`Quote`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SwapTransaction {
+    pub transaction: Transaction,
+    /// Block height after which the transaction's blockhash expires
+    pub last_valid_block_height: u64,
+    /// Priority fee Jupiter added, in lamports
+    pub prioritization_fee_lamports: u64,
+}
+
+impl SwapTransaction {
+    /// Sign as `signer`, returning the base64 transaction to send
+    pub async fn sign(&mut self, signer: &dyn Signer) -> Result<String, JupiterError> {
+        let signature = signer.sign(&self.transaction.message).await?;
+        self.transaction
+            .add_signature(&signer.pubkey(), signature)?;
+        Ok(general_purpose::STANDARD.encode(self.transaction.serialize()?))
+    }
+}
+
+/// Client of the Jupiter Swap API
+pub struct JupiterClient<H: HttpClient> {
+    http_client: H,
+    base_url: String,
+}
+
+impl<H: HttpClient> JupiterClient<H> {
+    /// Create a client of the public API using `http_client`
+    pub fn new(http_client: H) -> Self {
+        Self {
+            http_client,
+            base_url: JUPITER_API_URL.to_string(),
+        }
+    }
+
+    /// Use the API at `base_url` instead, e.g. a paid endpoint
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Find the best route for `request`
+    pub async fn quote(&self, request: &QuoteRequest) -> Result<Quote, JupiterError> {
+        let response: Value = self.http_client.get(&request.url(&self.base_url)).await?;
+        Quote::from_response(response)
+    }
+
+    /// Build the transaction executing `quote` for `user`
+    ///
+    /// SOL is wrapped and unwrapped as needed. `user` pays the fees and is
+    /// the only signer.
+    pub async fn swap_transaction(
+        &self,
+        quote: &Quote,
+        user: &Pubkey,
+    ) -> Result<SwapTransaction, JupiterError> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct SwapResponse {
+            swap_transaction: String,
+            last_valid_block_height: u64,
+            #[serde(default)]
+            prioritization_fee_lamports: u64,
+        }
+
+        let body = SwapRequest {
+            user_public_key: *user,
+            quote_response: &quote.response,
+            wrap_and_unwrap_sol: true,
+            dynamic_compute_unit_limit: true,
+            as_legacy_transaction: true,
+        };
+        let url = format!("{}/swap", self.base_url);
+        let response: SwapResponse = self.http_client.post_json(&url, &body).await?;
+        let bytes = general_purpose::STANDARD
+            .decode(&response.swap_transaction)
+            .map_err(|e| JupiterError::InvalidResponse(e.to_string()))?;
+        let transaction = Transaction::deserialize(&bytes)?;
+        if transaction.message.account_keys.first() != Some(user) {
+            return Err(MessageError::NotASigner(*user).into());
+        }
+
+        Ok(SwapTransaction {
+            transaction,
+            last_valid_block_height: response.last_valid_block_height,
+            prioritization_fee_lamports: response.prioritization_fee_lamports,
+        })
+    }
+
+    /// Build, sign and send the swap of `quote`, waiting until it lands
+    pub async fn swap(
+        &self,
+        quote: &Quote,
+        signer: &dyn Signer,
+        sender: &TransactionSender,
+    ) -> Result<SentTransaction, JupiterError> {
+        let mut swap = self.swap_transaction(quote, &signer.pubkey()).await?;
+        let encoded = swap.sign(signer).await?;
+        Ok(sender.send(&encoded, swap.last_valid_block_height).await?)
+    }
+}
+
+/// Parse an amount Jupiter sends as a decimal string
+fn parse_amount(amount: &str) -> Result<u64, JupiterError> {
+    amount
+        .parse()
+        .map_err(|_| JupiterError::InvalidResponse(format!("amount {}", amount)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::flows::TransactionBuilder;
+    use crate::domain::programs::system;
+    use crate::domain::types::Hash;
+    use crate::infrastructure::signer::Keypair;
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    /// Answers `/quote` with `quote` and `/swap` with `swap`, keeping requests
+    struct FakeJupiter {
+        quote: Value,
+        swap: Value,
+        requests: Mutex<Vec<(String, Value)>>,
+    }
+
+    impl HttpClient for FakeJupiter {
+        async fn post_json<'a, Req, Resp>(
+            &'a self,
+            url: &'a str,
+            body: &'a Req,
+        ) -> Result<Resp, HttpError>
+        where
+            Req: Serialize + Send + Sync,
+            Resp: for<'de> Deserialize<'de> + 'static,
+        {
+            let body = serde_json::to_value(body).unwrap();
+            self.requests.lock().unwrap().push((url.to_string(), body));
+            Ok(serde_json::from_value(self.swap.clone()).unwrap())
+        }
+
+        async fn post_json_text<'a, Req>(
+            &'a self,
+            _url: &'a str,
+            _body: &'a Req,
+        ) -> Result<String, HttpError>
+        where
+            Req: Serialize + Send + Sync,
+        {
+            Ok(self.swap.to_string())
+        }
+
+        async fn get<'a, Resp>(&'a self, url: &'a str) -> Result<Resp, HttpError>
+        where
+            Resp: for<'de> Deserialize<'de> + 'static,
+        {
+            self.requests
+                .lock()
+                .unwrap()
+                .push((url.to_string(), Value::Null));
+            Ok(serde_json::from_value(self.quote.clone()).unwrap())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_quote_then_sign_swap() {
+        let user = Keypair::from_seed(&[1; 32]);
+        let (sol, usdc, pool) = (
+            Pubkey::new([2; 32]),
+            Pubkey::new([3; 32]),
+            Pubkey::new([4; 32]),
+        );
+        let quote = json!({
+            "inputMint": sol.to_string(),
+            "inAmount": "1000000000",
+            "outputMint": usdc.to_string(),
+            "outAmount": "152300000",
+            "otherAmountThreshold": "151538500",
+            "swapMode": "ExactIn",
+            "slippageBps": 50,
+            "priceImpactPct": "0.0012",
+            "routePlan": [{
+                "swapInfo": {
+                    "ammKey": pool.to_string(),
+                    "label": "Whirlpool",
+                    "inputMint": sol.to_string(),
+                    "outputMint": usdc.to_string(),
+                    "inAmount": "1000000000",
+                    "outAmount": "152300000",
+                    "feeAmount": "300000",
+                    "feeMint": sol.to_string()
+                },
+                "percent": 100
+            }],
+            "contextSlot": 299_000_000u64,
+            "timeTaken": 0.01
+        });
+        let unsigned = TransactionBuilder::new(user.pubkey())
+            .instruction(system::transfer(user.pubkey(), pool, 1))
+            .build(Hash::new([5; 32]))
+            .unwrap();
+        let jupiter = JupiterClient::new(FakeJupiter {
+            quote: quote.clone(),
+            swap: json!({
+                "swapTransaction": general_purpose::STANDARD.encode(unsigned.serialize().unwrap()),
+                "lastValidBlockHeight": 279_000_000u64,
+                "prioritizationFeeLamports": 1_000
+            }),
+            requests: Mutex::new(Vec::new()),
+        })
+        .with_base_url("https://jupiter.example/");
+
+        let request = QuoteRequest::new(sol, usdc, 1_000_000_000).max_accounts(20);
+        let found = jupiter.quote(&request).await.unwrap();
+        assert_eq!(found.out_amount, 152_300_000);
+        assert_eq!(found.other_amount_threshold, 151_538_500);
+        assert_eq!(found.route[0].label.as_deref(), Some("Whirlpool"));
+        assert_eq!(found.route[0].amm_key, pool);
+
+        let mut swap = jupiter
+            .swap_transaction(&found, &user.pubkey())
+            .await
+            .unwrap();
+        assert_eq!(swap.last_valid_block_height, 279_000_000);
+        assert_eq!(swap.transaction.missing_signers(), [user.pubkey()]);
+        swap.sign(&user).await.unwrap();
+        assert!(swap.transaction.missing_signers().is_empty());
+
+        let other = Pubkey::new([6; 32]);
+        assert!(jupiter.swap_transaction(&found, &other).await.is_err());
+
+        let requests = jupiter.http_client.requests.lock().unwrap();
+        assert!(requests[0]
+            .0
+            .starts_with("https://jupiter.example/quote?inputMint="));
+        assert!(requests[0]
+            .0
+            .ends_with("&asLegacyTransaction=true&maxAccounts=20"));
+        assert_eq!(requests[1].0, "https://jupiter.example/swap");
+        assert_eq!(requests[1].1["quoteResponse"], quote);
+        assert_eq!(requests[1].1["userPublicKey"], json!(user.pubkey()));
+    }
+}
@@ -0,0 +1,11 @@
+//! Third-party protocol integrations
+//!
+//! Clients for services outside the Solana RPC that apps commonly build on.
+//! Each one speaks plain HTTP through an [`HttpClient`], so it runs in the
+//! browser and natively alike, turns the service's responses into this
+//! crate's types, and sits behind its own feature flag.
+//!
+//! [`HttpClient`]: crate::infrastructure::http::HttpClient
+
+#[cfg(feature = "jupiter")]
+pub mod jupiter;
@@ -4,7 +4,22 @@
 //! the domain objects and infrastructure components to implement business
 //! functionality.
 
+#[cfg(feature = "anchor")]
+pub mod codegen;
+pub mod confirmation;
+pub mod decoders;
+pub mod estimate;
+pub mod export;
+pub mod flows;
+pub mod integrations;
+pub mod offline;
+pub mod overlay;
+pub mod sender;
 pub mod services;
+pub mod solana_pay;
+#[cfg(feature = "svm-lite")]
+pub mod svm;
+pub mod watch;
 
 // Re-export commonly used application services
-pub use services::*;
+pub use services::{AccountService, NetworkService, NetworkStatus, TransactionService};
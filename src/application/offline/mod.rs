@@ -0,0 +1,251 @@
+//! Offline signing envelopes
+//!
+//! Cold-wallet flows build a transaction on an online device, carry it to an
+//! offline one for signing and bring the signatures back to send. A
+//! [`TransactionEnvelope`] is the portable form of that transaction: its wire
+//! bytes with any signatures collected so far, the signers still missing and
+//! the block height its blockhash expires at, as JSON or a single URL-safe
+//! base64 string for QR codes and links. Without a durable nonce the round
+//! trip has to finish before that height, about a minute after the blockhash
+//! was fetched.
+
+use crate::domain::message::{MessageError, Transaction};
+use crate::domain::types::Pubkey;
+use crate::infrastructure::signer::{Signer, SignerError};
+use base64::{engine::general_purpose, Engine as _};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Envelope format written by this version of the crate
+pub const ENVELOPE_VERSION: u8 = 1;
+
+/// Errors that can occur while reading or signing an envelope
+#[derive(Debug, Clone, Error)]
+pub enum EnvelopeError {
+    #[error("Unsupported envelope version {0}")]
+    UnsupportedVersion(u8),
+
+    #[error("Invalid envelope: {0}")]
+    Format(String),
+
+    #[error(transparent)]
+    Message(#[from] MessageError),
+
+    #[error("The envelope's signer list does not match its transaction")]
+    SignerMismatch,
+
+    #[error("The signature by {0} does not match the transaction")]
+    InvalidSignature(Pubkey),
+
+    #[error("{0} has no signature left to add to this transaction")]
+    NotRequired(Pubkey),
+
+    #[error("Signing failed: {0}")]
+    Signer(#[from] SignerError),
+}
+
+/// A transaction in transit between devices for signing
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionEnvelope {
+    pub version: u8,
+    /// Base64 wire transaction, with empty slots for missing signatures
+    pub transaction: String,
+    /// Signers that have not signed yet, in signature order
+    pub required_signers: Vec<Pubkey>,
+    /// Block height after which the transaction's blockhash is rejected
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_valid_block_height: Option<u64>,
+    /// What the transaction does, shown to whoever signs it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+impl TransactionEnvelope {
+    /// Wrap `transaction` as it stands
+    pub fn new(transaction: &Transaction) -> Result<Self, EnvelopeError> {
+        Ok(Self {
+            version: ENVELOPE_VERSION,
+            transaction: general_purpose::STANDARD.encode(transaction.serialize()?),
+            required_signers: transaction.missing_signers(),
+            last_valid_block_height: None,
+            description: None,
+        })
+    }
+
+    /// Record the block height the blockhash expires at
+    pub fn last_valid_block_height(mut self, height: u64) -> Self {
+        self.last_valid_block_height = Some(height);
+        self
+    }
+
+    /// Describe the transaction for the signer
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// The transaction, after checking it against the envelope
+    ///
+    /// Every signature already present must be valid and the signer list
+    /// must name exactly the signatures still missing, so an envelope edited
+    /// in transit is rejected.
+    pub fn transaction(&self) -> Result<Transaction, EnvelopeError> {
+        if self.version != ENVELOPE_VERSION {
+            return Err(EnvelopeError::UnsupportedVersion(self.version));
+        }
+        let bytes = general_purpose::STANDARD
+            .decode(&self.transaction)
+            .map_err(|e| EnvelopeError::Format(e.to_string()))?;
+        let transaction = Transaction::deserialize(&bytes)?;
+        if transaction.missing_signers() != self.required_signers {
+            return Err(EnvelopeError::SignerMismatch);
+        }
+
+        let message = transaction.message.serialize()?;
+        let signed = transaction
+            .signatures
+            .iter()
+            .zip(&transaction.message.account_keys)
+            .filter(|(signature, _)| signature.as_bytes() != &[0; 64]);
+        for (signature, signer) in signed {
+            if !signature.verify(signer, &message) {
+                return Err(EnvelopeError::InvalidSignature(*signer));
+            }
+        }
+        Ok(transaction)
+    }
+
+    /// Add `signer`'s signature, returning the updated envelope
+    pub async fn sign<S: Signer>(&self, signer: &S) -> Result<Self, EnvelopeError> {
+        let mut transaction = self.transaction()?;
+        let pubkey = signer.pubkey();
+        if !self.required_signers.contains(&pubkey) {
+            return Err(EnvelopeError::NotRequired(pubkey));
+        }
+        let signature = signer.sign(&transaction.message).await?;
+        transaction.add_signature(&pubkey, signature)?;
+        Ok(Self {
+            last_valid_block_height: self.last_valid_block_height,
+            description: self.description.clone(),
+            ..Self::new(&transaction)?
+        })
+    }
+
+    /// Whether every required signature is present
+    pub fn is_complete(&self) -> bool {
+        self.required_signers.is_empty()
+    }
+
+    /// Whether the blockhash has expired at `block_height`
+    ///
+    /// Envelopes without a recorded height never report expiry.
+    pub fn is_expired(&self, block_height: u64) -> bool {
+        self.last_valid_block_height
+            .is_some_and(|last_valid| block_height > last_valid)
+    }
+
+    /// The envelope as pretty-printed JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// The envelope as one URL-safe base64 string
+    pub fn encode(&self) -> String {
+        general_purpose::URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    /// Read an envelope written by [`to_json`](Self::to_json) or
+    /// [`encode`](Self::encode)
+    pub fn decode(input: &str) -> Result<Self, EnvelopeError> {
+        let input = input.trim();
+        let json = if input.starts_with('{') {
+            input.as_bytes().to_vec()
+        } else {
+            general_purpose::URL_SAFE_NO_PAD
+                .decode(input)
+                .map_err(|e| EnvelopeError::Format(e.to_string()))?
+        };
+        serde_json::from_slice(&json).map_err(|e| EnvelopeError::Format(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::flows::TransactionBuilder;
+    use crate::domain::programs::system;
+    use crate::domain::types::Hash;
+    use crate::infrastructure::signer::Keypair;
+
+    #[tokio::test]
+    async fn test_envelope_round_trip() {
+        let payer = Keypair::from_seed(&[1; 32]);
+        let cold = Keypair::from_seed(&[2; 32]);
+        let transaction = TransactionBuilder::new(payer.pubkey())
+            .instruction(system::transfer(cold.pubkey(), payer.pubkey(), 5))
+            .build(Hash::new([3; 32]))
+            .unwrap();
+
+        let envelope = TransactionEnvelope::new(&transaction)
+            .unwrap()
+            .last_valid_block_height(150)
+            .description("Sweep the cold wallet");
+        assert_eq!(envelope.required_signers, [payer.pubkey(), cold.pubkey()]);
+        assert!(envelope.is_expired(151) && !envelope.is_expired(150));
+
+        // Carried to the cold device as a string, signed there and brought back
+        let carried = TransactionEnvelope::decode(&envelope.encode()).unwrap();
+        let signed = carried.sign(&cold).await.unwrap();
+        assert_eq!(signed.required_signers, [payer.pubkey()]);
+        assert_eq!(signed.description.as_deref(), Some("Sweep the cold wallet"));
+
+        let returned = TransactionEnvelope::decode(&signed.to_json()).unwrap();
+        let complete = returned.sign(&payer).await.unwrap();
+        assert!(complete.is_complete());
+        assert!(complete.transaction().unwrap().missing_signers().is_empty());
+        assert!(matches!(
+            complete.sign(&payer).await,
+            Err(EnvelopeError::NotRequired(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_tampered_envelopes_are_rejected() {
+        let payer = Keypair::from_seed(&[1; 32]);
+        let transaction = TransactionBuilder::new(payer.pubkey())
+            .instruction(system::transfer(payer.pubkey(), Pubkey::new([9; 32]), 5))
+            .build(Hash::new([3; 32]))
+            .unwrap();
+        let signed = TransactionEnvelope::new(&transaction)
+            .unwrap()
+            .sign(&payer)
+            .await
+            .unwrap();
+
+        let mut unsigned = TransactionEnvelope::new(&transaction).unwrap();
+        unsigned.required_signers.clear();
+        assert!(matches!(
+            unsigned.transaction(),
+            Err(EnvelopeError::SignerMismatch)
+        ));
+
+        // Redirect the transfer after it was signed
+        let mut bytes = general_purpose::STANDARD
+            .decode(&signed.transaction)
+            .unwrap();
+        let recipient = bytes
+            .windows(32)
+            .position(|window| window == [9; 32])
+            .unwrap();
+        bytes[recipient] = 8;
+        let redirected = TransactionEnvelope {
+            transaction: general_purpose::STANDARD.encode(bytes),
+            ..signed
+        };
+        assert!(matches!(
+            redirected.transaction(),
+            Err(EnvelopeError::InvalidSignature(signer)) if signer == payer.pubkey()
+        ));
+    }
+}
@@ -0,0 +1,262 @@
+//! Optimistic account updates
+//!
+//! Waiting for confirmation before showing the effect of a transaction makes
+//! a UI feel slow. A [`LocalOverlay`] records the writes a sent transaction
+//! is expected to make, such as "the counter will be 5", and merges them over
+//! fetched account data until the transaction confirms. If it fails instead,
+//! [`LocalOverlay::settle`] rolls the writes back and reads show the chain's
+//! data again.
+
+use crate::application::confirmation::{ConfirmationError, TransactionConfirmer};
+use crate::domain::layout::AccountLayout;
+use crate::domain::types::{Pubkey, Signature};
+use crate::infrastructure::rpc::Account;
+use std::sync::{Arc, Mutex};
+
+/// A change expected to land on an account
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccountPatch {
+    /// Replace data bytes starting at `offset`
+    Data { offset: usize, bytes: Vec<u8> },
+    /// Set the lamport balance
+    Lamports(u64),
+}
+
+impl AccountPatch {
+    /// Set `field` of layout `L` to the Borsh encoding of `value`
+    ///
+    /// `None` if the field has no fixed offset.
+    pub fn field<L: AccountLayout, V: borsh::BorshSerialize>(
+        field: &str,
+        value: &V,
+    ) -> Option<Self> {
+        Some(Self::Data {
+            offset: L::field_offset(field)?,
+            bytes: borsh::to_vec(value).ok()?,
+        })
+    }
+
+    fn apply(&self, account: &mut Account) {
+        match self {
+            Self::Data { offset, bytes } => {
+                let end = offset + bytes.len();
+                if account.data.len() < end {
+                    account.data.resize(end, 0);
+                }
+                account.data[*offset..end].copy_from_slice(bytes);
+            }
+            Self::Lamports(lamports) => account.lamports = *lamports,
+        }
+    }
+}
+
+/// Writes of one pending transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PendingWrite {
+    signature: Signature,
+    account: Pubkey,
+    patch: AccountPatch,
+}
+
+/// Pending writes merged over fetched accounts
+///
+/// Clones share the same writes, so a sender and the views reading accounts
+/// can each hold one.
+#[derive(Debug, Clone, Default)]
+pub struct LocalOverlay {
+    writes: Arc<Mutex<Vec<PendingWrite>>>,
+}
+
+impl LocalOverlay {
+    /// Create an overlay with no pending writes
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Expect transaction `signature` to apply `patch` to `account`
+    ///
+    /// Patches apply in the order they were added, so a later transaction's
+    /// write wins over an earlier one.
+    pub fn apply(&self, signature: &Signature, account: Pubkey, patch: AccountPatch) {
+        self.lock().push(PendingWrite {
+            signature: signature.clone(),
+            account,
+            patch,
+        });
+    }
+
+    /// Drop the writes of a transaction that confirmed
+    ///
+    /// Fetched data includes them from now on.
+    pub fn confirm(&self, signature: &Signature) {
+        self.remove(signature);
+    }
+
+    /// Drop the writes of a transaction that failed, returning the accounts they touched
+    pub fn rollback(&self, signature: &Signature) -> Vec<Pubkey> {
+        let mut accounts: Vec<_> = self
+            .remove(signature)
+            .into_iter()
+            .map(|write| write.account)
+            .collect();
+        accounts.dedup();
+        accounts
+    }
+
+    /// Wait for `signature` to confirm, rolling its writes back if it does not
+    pub async fn settle(
+        &self,
+        signature: &Signature,
+        confirmer: &TransactionConfirmer,
+    ) -> Result<(), ConfirmationError> {
+        let result = confirmer.confirm(signature).await;
+        match result {
+            Ok(()) => self.confirm(signature),
+            Err(_) => {
+                self.rollback(signature);
+            }
+        }
+        result
+    }
+
+    /// Whether any write is pending for `account`
+    pub fn is_pending(&self, account: &Pubkey) -> bool {
+        self.lock().iter().any(|write| write.account == *account)
+    }
+
+    /// Signatures with pending writes, oldest first
+    pub fn pending_signatures(&self) -> Vec<Signature> {
+        let mut signatures: Vec<Signature> = Vec::new();
+        for write in self.lock().iter() {
+            if !signatures.contains(&write.signature) {
+                signatures.push(write.signature.clone());
+            }
+        }
+        signatures
+    }
+
+    /// `account` with the pending writes for it applied
+    pub fn view(&self, mut account: Account) -> Account {
+        for write in self.lock().iter() {
+            if write.account == account.pubkey {
+                write.patch.apply(&mut account);
+            }
+        }
+        account
+    }
+
+    /// A fetched balance of `account` with pending lamport writes applied
+    pub fn view_lamports(&self, account: &Pubkey, lamports: u64) -> u64 {
+        self.lock()
+            .iter()
+            .filter(|write| write.account == *account)
+            .fold(lamports, |lamports, write| match write.patch {
+                AccountPatch::Lamports(set) => set,
+                AccountPatch::Data { .. } => lamports,
+            })
+    }
+
+    /// A fetched account with pending writes applied, `None` if it does not exist
+    ///
+    /// Writes to an account that does not exist yet wait for it to be fetched.
+    pub fn merge(&self, account: Option<Account>) -> Option<Account> {
+        account.map(|account| self.view(account))
+    }
+
+    fn remove(&self, signature: &Signature) -> Vec<PendingWrite> {
+        let mut writes = self.lock();
+        let (removed, kept) = writes
+            .drain(..)
+            .partition(|write| write.signature == *signature);
+        *writes = kept;
+        removed
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<PendingWrite>> {
+        self.writes.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::confirmation::{ConfirmationConfig, ConfirmationStrategy};
+    use crate::infrastructure::rpc::mock::MockRpc;
+    use crate::infrastructure::rpc::{CommitmentLevel, SignatureStatus};
+    use serde_json::json;
+    use std::time::Duration;
+
+    struct Counter;
+
+    impl AccountLayout for Counter {
+        const FIELDS: &'static [(&'static str, usize)] = &[("authority", 8), ("count", 40)];
+    }
+
+    fn counter(count: u64) -> Account {
+        let mut data = vec![0; 48];
+        data[40..].copy_from_slice(&count.to_le_bytes());
+        Account {
+            pubkey: Pubkey::new([1; 32]),
+            lamports: 1_000,
+            data,
+            owner: Pubkey::new([2; 32]),
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    #[test]
+    fn test_writes_merge_until_confirmed() {
+        let overlay = LocalOverlay::new();
+        let first = Signature::new([1; 64]);
+        let second = Signature::new([2; 64]);
+        let key = Pubkey::new([1; 32]);
+        overlay.apply(
+            &first,
+            key,
+            AccountPatch::field::<Counter, _>("count", &5u64).unwrap(),
+        );
+        overlay.apply(&second, key, AccountPatch::Lamports(900));
+        assert!(overlay.is_pending(&key));
+        assert_eq!(
+            overlay.pending_signatures(),
+            [first.clone(), second.clone()]
+        );
+
+        let view = overlay.merge(Some(counter(4))).unwrap();
+        assert_eq!(view.data[40..], 5u64.to_le_bytes());
+        assert_eq!(view.lamports, 900);
+        assert_eq!(overlay.view_lamports(&key, 1_000), 900);
+        assert_eq!(overlay.merge(None), None);
+
+        overlay.confirm(&first);
+        assert_eq!(overlay.view(counter(4)).data[40..], 4u64.to_le_bytes());
+        assert_eq!(overlay.rollback(&second), [key]);
+        assert!(!overlay.is_pending(&key));
+        assert_eq!(overlay.view(counter(4)), counter(4));
+    }
+
+    #[tokio::test]
+    async fn test_settle_rolls_back_failed_transactions() {
+        let signature = Signature::new([3; 64]);
+        let failed = SignatureStatus {
+            slot: 5,
+            confirmations: None,
+            err: Some(json!({ "InstructionError": [0, { "Custom": 1 }] })),
+            confirmation_status: Some(CommitmentLevel::Confirmed),
+        };
+        let rpc = MockRpc::new().with_statuses(signature.clone(), vec![Some(failed)]);
+        let confirmer = TransactionConfirmer::new(
+            rpc,
+            ConfirmationConfig::new().strategy(ConfirmationStrategy::Polling {
+                interval: Duration::from_millis(1),
+            }),
+        );
+
+        let overlay = LocalOverlay::new();
+        overlay.apply(&signature, Pubkey::new([1; 32]), AccountPatch::Lamports(0));
+        let result = overlay.settle(&signature, &confirmer).await;
+        assert!(matches!(result, Err(ConfirmationError::Failed { .. })));
+        assert!(overlay.pending_signatures().is_empty());
+    }
+}
@@ -0,0 +1,461 @@
+//! Transaction resubmission
+//!
+//! RPC nodes forward a transaction to the leader once and drop it if the
+//! leader is congested. [`TransactionSender`] keeps re-broadcasting the same
+//! signed transaction until it lands or its blockhash expires, the loop
+//! `solana-cli` runs, which lands far more transactions under load than a
+//! single `sendTransaction`.
+
+use crate::domain::errors::TransactionError;
+use crate::domain::message::Transaction;
+use crate::domain::types::Signature;
+use crate::infrastructure::rpc::{CommitmentLevel, RpcApi, RpcError};
+use crate::infrastructure::storage::StorageError;
+use crate::infrastructure::time::{sleep, Instant};
+use base64::{engine::general_purpose, Engine as _};
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+
+pub mod pending;
+
+pub use pending::{idempotency_key, PendingTransaction, PendingTransactions};
+
+/// Default delay between broadcasts of an unconfirmed transaction
+pub const DEFAULT_REBROADCAST_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Default delay between status checks
+pub const DEFAULT_STATUS_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Settings of the send loop
+#[derive(Debug, Clone)]
+pub struct SendConfig {
+    pub commitment: CommitmentLevel,
+    /// Delay between broadcasts while the transaction is unconfirmed
+    pub rebroadcast_interval: Duration,
+    /// Most re-broadcasts after the first send, unlimited if `None`
+    pub max_rebroadcasts: Option<usize>,
+    /// Delay between status checks
+    pub status_interval: Duration,
+}
+
+impl SendConfig {
+    /// Rebroadcast every 2 seconds until `confirmed` or blockhash expiry
+    pub fn new() -> Self {
+        Self {
+            commitment: CommitmentLevel::Confirmed,
+            rebroadcast_interval: DEFAULT_REBROADCAST_INTERVAL,
+            max_rebroadcasts: None,
+            status_interval: DEFAULT_STATUS_INTERVAL,
+        }
+    }
+
+    /// Set the commitment level to wait for
+    pub fn commitment(mut self, commitment: CommitmentLevel) -> Self {
+        self.commitment = commitment;
+        self
+    }
+
+    /// Set the delay between broadcasts
+    pub fn rebroadcast_interval(mut self, interval: Duration) -> Self {
+        self.rebroadcast_interval = interval;
+        self
+    }
+
+    /// Limit the number of re-broadcasts
+    pub fn max_rebroadcasts(mut self, max: usize) -> Self {
+        self.max_rebroadcasts = Some(max);
+        self
+    }
+
+    /// Set the delay between status checks
+    pub fn status_interval(mut self, interval: Duration) -> Self {
+        self.status_interval = interval;
+        self
+    }
+}
+
+impl Default for SendConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Errors that can occur while sending a transaction
+#[derive(Debug, Clone, Error)]
+pub enum SendError {
+    #[error("RPC error: {0}")]
+    Rpc(#[from] RpcError),
+
+    #[error("Transaction {signature} failed: {error}")]
+    Failed {
+        signature: Signature,
+        error: TransactionError,
+    },
+
+    #[error("Transaction {signature} expired at block height {last_valid_block_height}")]
+    BlockhashExpired {
+        signature: Signature,
+        last_valid_block_height: u64,
+    },
+
+    #[error("Invalid transaction: {0}")]
+    InvalidTransaction(String),
+
+    #[error("Pending transaction storage error: {0}")]
+    Storage(#[from] StorageError),
+}
+
+/// A transaction that reached the requested commitment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SentTransaction {
+    pub signature: Signature,
+    /// Slot the transaction landed in
+    pub slot: u64,
+    /// Broadcasts after the first one
+    pub rebroadcasts: usize,
+}
+
+/// Sends transactions and re-broadcasts them until they land
+pub struct TransactionSender {
+    rpc_client: Arc<dyn RpcApi>,
+    config: SendConfig,
+    pending: PendingTransactions,
+}
+
+impl TransactionSender {
+    /// Create a sender
+    pub fn new(rpc_client: impl RpcApi + 'static, config: SendConfig) -> Self {
+        Self::from_api(Arc::new(rpc_client), config)
+    }
+
+    /// Create a sender from a shared RPC implementation
+    pub fn from_api(rpc_client: Arc<dyn RpcApi>, config: SendConfig) -> Self {
+        Self {
+            rpc_client,
+            config,
+            pending: PendingTransactions::in_memory(),
+        }
+    }
+
+    /// Record idempotent sends in `pending` instead of in memory
+    ///
+    /// Backed by persistent storage, pending sends survive a page refresh
+    /// and can be picked up with [`resume`](Self::resume).
+    pub fn with_pending(mut self, pending: PendingTransactions) -> Self {
+        self.pending = pending;
+        self
+    }
+
+    /// Get the configuration
+    pub fn config(&self) -> &SendConfig {
+        &self.config
+    }
+
+    /// Transactions sent idempotently that have not settled yet
+    pub fn pending(&self) -> &PendingTransactions {
+        &self.pending
+    }
+
+    /// Send a base64-encoded signed transaction and wait for it to land
+    ///
+    /// `last_valid_block_height` comes from the [`LatestBlockhash`] the
+    /// transaction was built with; once the chain passes it the transaction
+    /// can no longer land and the loop gives up.
+    ///
+    /// [`LatestBlockhash`]: crate::infrastructure::rpc::LatestBlockhash
+    pub async fn send(
+        &self,
+        transaction: &str,
+        last_valid_block_height: u64,
+    ) -> Result<SentTransaction, SendError> {
+        let signature = self.rpc_client.send_transaction(transaction).await?;
+        let mut rebroadcasts = 0;
+        let mut last_broadcast = Instant::now();
+
+        loop {
+            sleep(self.config.status_interval).await;
+
+            if let Some(slot) = self.check(&signature).await? {
+                return Ok(SentTransaction {
+                    signature,
+                    slot,
+                    rebroadcasts,
+                });
+            }
+
+            if self.rpc_client.get_block_height().await? > last_valid_block_height {
+                // It may have landed in the last valid block
+                if let Some(slot) = self.check(&signature).await? {
+                    return Ok(SentTransaction {
+                        signature,
+                        slot,
+                        rebroadcasts,
+                    });
+                }
+                return Err(SendError::BlockhashExpired {
+                    signature,
+                    last_valid_block_height,
+                });
+            }
+
+            let may_rebroadcast = self
+                .config
+                .max_rebroadcasts
+                .is_none_or(|max| rebroadcasts < max);
+            if may_rebroadcast && last_broadcast.elapsed() >= self.config.rebroadcast_interval {
+                // A resend the node rejects, e.g. as already processed, is
+                // settled by the next status check
+                let _ = self.rpc_client.send_transaction(transaction).await;
+                rebroadcasts += 1;
+                last_broadcast = Instant::now();
+            }
+        }
+    }
+
+    /// Send a transaction for the intent identified by `key`, at most once
+    ///
+    /// The signature is computed locally and recorded under `key` before
+    /// anything is sent. While a transaction is pending for `key`, calling
+    /// again follows that transaction, even if `transaction` is a rebuilt one
+    /// with a new blockhash; the new one is only sent once the pending one
+    /// has expired without landing. The entry is kept when the outcome is
+    /// unknown, such as after an RPC error, so a retry resumes it.
+    pub async fn send_idempotent(
+        &self,
+        key: &str,
+        transaction: &str,
+        last_valid_block_height: u64,
+    ) -> Result<SentTransaction, SendError> {
+        if let Some(pending) = self.pending.get(key)? {
+            match self.follow(&pending).await {
+                Err(SendError::BlockhashExpired { .. }) => self.pending.remove(key)?,
+                result => return self.settle(key, result),
+            }
+        }
+
+        let pending = PendingTransaction {
+            key: key.to_string(),
+            signature: local_signature(transaction)?,
+            transaction: transaction.to_string(),
+            last_valid_block_height,
+        };
+        self.pending.insert(&pending)?;
+        let result = self.send(transaction, last_valid_block_height).await;
+        self.settle(key, result)
+    }
+
+    /// Pick up tracking of the transaction pending for `key`
+    ///
+    /// Returns `None` when nothing is pending. The transaction is
+    /// re-broadcast as is, so resuming never submits a second one.
+    pub async fn resume(&self, key: &str) -> Result<Option<SentTransaction>, SendError> {
+        let Some(pending) = self.pending.get(key)? else {
+            return Ok(None);
+        };
+        let result = self.follow(&pending).await;
+        self.settle(key, result).map(Some)
+    }
+
+    /// Re-broadcast a pending transaction unless it already landed
+    async fn follow(&self, pending: &PendingTransaction) -> Result<SentTransaction, SendError> {
+        if let Some(slot) = self.check(&pending.signature).await? {
+            return Ok(SentTransaction {
+                signature: pending.signature.clone(),
+                slot,
+                rebroadcasts: 0,
+            });
+        }
+        self.send(&pending.transaction, pending.last_valid_block_height)
+            .await
+    }
+
+    /// Forget `key` once the outcome of its transaction is known
+    fn settle(
+        &self,
+        key: &str,
+        result: Result<SentTransaction, SendError>,
+    ) -> Result<SentTransaction, SendError> {
+        if matches!(
+            result,
+            Ok(_) | Err(SendError::Failed { .. } | SendError::BlockhashExpired { .. })
+        ) {
+            self.pending.remove(key)?;
+        }
+        result
+    }
+
+    /// The slot `signature` landed in, once it reaches the commitment
+    async fn check(&self, signature: &Signature) -> Result<Option<u64>, SendError> {
+        let statuses = match self
+            .rpc_client
+            .get_signature_statuses(std::slice::from_ref(signature))
+            .await
+        {
+            Ok(statuses) => statuses,
+            // Transient failures are retried on the next check
+            Err(_) => return Ok(None),
+        };
+        let Some(status) = statuses.into_iter().next().flatten() else {
+            return Ok(None);
+        };
+
+        if let Some(error) = status.error() {
+            return Err(SendError::Failed {
+                signature: signature.clone(),
+                error,
+            });
+        }
+        Ok(status
+            .satisfies(self.config.commitment)
+            .then_some(status.slot))
+    }
+}
+
+/// Signature of a base64-encoded signed transaction, its fee payer's
+fn local_signature(transaction: &str) -> Result<Signature, SendError> {
+    let bytes = general_purpose::STANDARD
+        .decode(transaction)
+        .map_err(|e| SendError::InvalidTransaction(e.to_string()))?;
+    let transaction = Transaction::deserialize(&bytes)
+        .map_err(|e| SendError::InvalidTransaction(e.to_string()))?;
+    match transaction.signatures.first() {
+        Some(signature) if signature.as_bytes() != &[0; 64] => Ok(signature.clone()),
+        _ => Err(SendError::InvalidTransaction(
+            "the fee payer has not signed".to_string(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::rpc::mock::{MockRpc, SENT_SIGNATURE};
+    use crate::infrastructure::rpc::SignatureStatus;
+    use serde_json::{json, Value};
+
+    fn status(commitment: CommitmentLevel, err: Option<Value>) -> Option<SignatureStatus> {
+        Some(SignatureStatus {
+            slot: 7,
+            confirmations: Some(0),
+            err,
+            confirmation_status: Some(commitment),
+        })
+    }
+
+    fn config() -> SendConfig {
+        SendConfig::new()
+            .rebroadcast_interval(Duration::ZERO)
+            .status_interval(Duration::from_millis(1))
+    }
+
+    #[tokio::test]
+    async fn test_rebroadcasts_until_confirmed() {
+        let rpc = Arc::new(MockRpc::new().with_statuses(
+            SENT_SIGNATURE,
+            vec![
+                None,
+                None,
+                status(CommitmentLevel::Processed, None),
+                status(CommitmentLevel::Confirmed, None),
+            ],
+        ));
+        let sender = TransactionSender::from_api(rpc.clone(), config());
+
+        let sent = sender.send("AQ==", 150).await.unwrap();
+        assert_eq!(sent.signature, SENT_SIGNATURE);
+        assert_eq!(sent.slot, 7);
+        assert_eq!(sent.rebroadcasts, 3);
+        assert_eq!(rpc.sent.lock().unwrap().len(), 4);
+
+        let rpc = Arc::new(MockRpc::new().with_statuses(
+            SENT_SIGNATURE,
+            vec![None, None, None, status(CommitmentLevel::Confirmed, None)],
+        ));
+        let sender = TransactionSender::from_api(rpc.clone(), config().max_rebroadcasts(1));
+        assert_eq!(sender.send("AQ==", 150).await.unwrap().rebroadcasts, 1);
+        assert_eq!(rpc.sent.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_stops_at_failure_or_expiry() {
+        let rpc = MockRpc::new().with_statuses(
+            SENT_SIGNATURE,
+            vec![status(
+                CommitmentLevel::Processed,
+                Some(json!({ "InstructionError": [0, { "Custom": 1 }] })),
+            )],
+        );
+        let sender = TransactionSender::new(rpc, config());
+        assert!(matches!(
+            sender.send("AQ==", 150).await,
+            Err(SendError::Failed { .. })
+        ));
+
+        // The mock chain is at block height 100
+        let sender = TransactionSender::new(MockRpc::new(), config());
+        assert!(matches!(
+            sender.send("AQ==", 99).await,
+            Err(SendError::BlockhashExpired {
+                last_valid_block_height: 99,
+                ..
+            })
+        ));
+    }
+
+    /// A base64 transfer signed with `signature`
+    fn signed_transfer(signature: Signature, blockhash: u8) -> String {
+        use crate::domain::message::Message;
+        use crate::domain::programs::system;
+        use crate::domain::types::{Hash, Pubkey};
+
+        let payer = Pubkey::new([1; 32]);
+        let transfer = system::transfer(payer, Pubkey::new([2; 32]), 5);
+        let message = Message::compile(&[transfer], Some(&payer), Hash::new([blockhash; 32]));
+        let mut transaction = Transaction::new_unsigned(message.unwrap());
+        transaction.signatures[0] = signature;
+        general_purpose::STANDARD.encode(transaction.serialize().unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_idempotent_send_follows_pending_transaction() {
+        let rpc = Arc::new(MockRpc::new().with_statuses(
+            SENT_SIGNATURE,
+            vec![status(CommitmentLevel::Confirmed, None)],
+        ));
+        let pending = PendingTransactions::in_memory();
+        let sender =
+            TransactionSender::from_api(rpc.clone(), config()).with_pending(pending.clone());
+        let key = idempotency_key(&[b"checkout", b"order-17"]);
+
+        // Sent before a page refresh, then rebuilt with a new blockhash
+        pending
+            .insert(&PendingTransaction {
+                key: key.clone(),
+                signature: SENT_SIGNATURE,
+                transaction: signed_transfer(SENT_SIGNATURE, 1),
+                last_valid_block_height: 150,
+            })
+            .unwrap();
+        let rebuilt = signed_transfer(Signature::new([3; 64]), 2);
+        let sent = sender.send_idempotent(&key, &rebuilt, 150).await.unwrap();
+        assert_eq!(sent.signature, SENT_SIGNATURE);
+        assert!(rpc.sent.lock().unwrap().is_empty());
+        assert!(pending.list().unwrap().is_empty());
+        assert!(sender.resume(&key).await.unwrap().is_none());
+
+        let sent = sender
+            .send_idempotent(&key, &signed_transfer(SENT_SIGNATURE, 3), 150)
+            .await
+            .unwrap();
+        assert_eq!(sent.slot, 7);
+        assert_eq!(rpc.sent.lock().unwrap().len(), 1);
+
+        let unsigned = signed_transfer(Signature::new([0; 64]), 4);
+        assert!(matches!(
+            sender.send_idempotent("other", &unsigned, 150).await,
+            Err(SendError::InvalidTransaction(_))
+        ));
+        assert!(pending.get("other").unwrap().is_none());
+    }
+}
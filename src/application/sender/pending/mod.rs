@@ -0,0 +1,140 @@
+//! Pending transactions keyed by user intent
+//!
+//! A retry after a timeout or a page refresh usually rebuilds the transaction
+//! with a fresh blockhash, which gives it a new signature: if the first one
+//! did reach the leader, the user pays twice. [`PendingTransactions`] records
+//! the signed transaction under an idempotency key before it is sent, so
+//! [`TransactionSender::send_idempotent`] keeps following that transaction
+//! until it lands or expires instead of submitting another.
+//!
+//! [`TransactionSender::send_idempotent`]: super::TransactionSender::send_idempotent
+
+use crate::domain::types::Signature;
+use crate::infrastructure::storage::{MemoryStorage, StorageAdapter, StorageError};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+
+/// Storage key prefix for pending transactions
+const STORAGE_PREFIX: &str = "gloo_solana:pending:";
+
+/// Idempotency key derived from the parts describing a user intent
+///
+/// The same parts, such as a form id, recipient and amount, always give the
+/// same key; parts are length-prefixed so `["ab", "c"]` and `["a", "bc"]`
+/// differ.
+pub fn idempotency_key(parts: &[&[u8]]) -> String {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update((part.len() as u64).to_le_bytes());
+        hasher.update(part);
+    }
+    bs58::encode(hasher.finalize()).into_string()
+}
+
+/// A signed transaction sent, or about to be sent, for an intent
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingTransaction {
+    /// Idempotency key of the intent
+    pub key: String,
+    /// Signature computed locally before sending
+    pub signature: Signature,
+    /// Base64-encoded signed transaction
+    pub transaction: String,
+    pub last_valid_block_height: u64,
+}
+
+/// Pending transactions persisted through a [`StorageAdapter`]
+///
+/// Clones share the same storage.
+#[derive(Clone)]
+pub struct PendingTransactions {
+    storage: Arc<dyn StorageAdapter>,
+}
+
+impl PendingTransactions {
+    /// Persist pending transactions in `storage`
+    pub fn new(storage: Arc<dyn StorageAdapter>) -> Self {
+        Self { storage }
+    }
+
+    /// Keep pending transactions in memory only
+    pub fn in_memory() -> Self {
+        Self::new(Arc::new(MemoryStorage::new()))
+    }
+
+    /// The transaction pending for `key`
+    ///
+    /// Unreadable entries are treated as absent.
+    pub fn get(&self, key: &str) -> Result<Option<PendingTransaction>, StorageError> {
+        Ok(self
+            .storage
+            .get(&storage_key(key))?
+            .and_then(|raw| serde_json::from_str(&raw).ok()))
+    }
+
+    /// Every pending transaction, to resume tracking after a restart
+    pub fn list(&self) -> Result<Vec<PendingTransaction>, StorageError> {
+        let mut pending = Vec::new();
+        for key in self.storage.keys(STORAGE_PREFIX)? {
+            if let Some(key) = key.strip_prefix(STORAGE_PREFIX) {
+                pending.extend(self.get(key)?);
+            }
+        }
+        Ok(pending)
+    }
+
+    /// Record `pending`, replacing any entry for its key
+    pub fn insert(&self, pending: &PendingTransaction) -> Result<(), StorageError> {
+        let raw = serde_json::to_string(pending).map_err(|e| StorageError::Io(e.to_string()))?;
+        self.storage.set(&storage_key(&pending.key), &raw)
+    }
+
+    /// Forget the transaction pending for `key`
+    pub fn remove(&self, key: &str) -> Result<(), StorageError> {
+        self.storage.remove(&storage_key(key))
+    }
+}
+
+impl std::fmt::Debug for PendingTransactions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PendingTransactions")
+            .finish_non_exhaustive()
+    }
+}
+
+fn storage_key(key: &str) -> String {
+    format!("{}{}", STORAGE_PREFIX, key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pending_round_trip() {
+        let key = idempotency_key(&[b"transfer", b"alice", &5u64.to_le_bytes()]);
+        assert_eq!(
+            key,
+            idempotency_key(&[b"transfer", b"alice", &5u64.to_le_bytes()])
+        );
+        assert_ne!(
+            idempotency_key(&[b"ab", b"c"]),
+            idempotency_key(&[b"a", b"bc"])
+        );
+
+        let pending = PendingTransactions::in_memory();
+        let entry = PendingTransaction {
+            key: key.clone(),
+            signature: Signature::new([2; 64]),
+            transaction: "AQ==".to_string(),
+            last_valid_block_height: 150,
+        };
+        pending.insert(&entry).unwrap();
+        assert_eq!(pending.get(&key).unwrap(), Some(entry.clone()));
+        assert_eq!(pending.clone().list().unwrap(), [entry]);
+        pending.remove(&key).unwrap();
+        assert!(pending.list().unwrap().is_empty());
+    }
+}
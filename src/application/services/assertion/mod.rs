@@ -0,0 +1,230 @@
+//! Account assertion guards
+//!
+//! [`AssertionService`] fetches accounts and checks what a dApp assumes about
+//! them at startup: that they exist, which program owns them, that their
+//! discriminator matches the expected Anchor type and that PDAs derive from
+//! the expected seeds. Each failure names the account and what was found.
+
+use crate::domain::discriminator::{Discriminator, DiscriminatorError};
+use crate::domain::types::Pubkey;
+use crate::infrastructure::rpc::{Account, RpcApi, RpcError};
+use borsh::BorshDeserialize;
+use std::sync::Arc;
+use thiserror::Error;
+
+/// A failed account assertion
+#[derive(Debug, Clone, Error)]
+pub enum AssertionError {
+    #[error("RPC error: {0}")]
+    Rpc(#[from] RpcError),
+
+    #[error("Account {0} does not exist")]
+    AccountNotFound(Pubkey),
+
+    #[error("Account {account} is owned by {actual}, expected {expected}")]
+    WrongOwner {
+        account: Pubkey,
+        expected: Pubkey,
+        actual: Pubkey,
+    },
+
+    #[error("Account {account} is not a {type_name}: {source}")]
+    WrongType {
+        account: Pubkey,
+        type_name: &'static str,
+        source: DiscriminatorError,
+    },
+
+    #[error("Account {0} is not an executable program")]
+    NotExecutable(Pubkey),
+
+    #[error("{account} is not the program address of these seeds, expected {expected}")]
+    WrongAddress { account: Pubkey, expected: Pubkey },
+}
+
+/// Service checking accounts against a dApp's expectations
+pub struct AssertionService {
+    rpc_client: Arc<dyn RpcApi>,
+}
+
+impl AssertionService {
+    /// Create an assertion service
+    pub fn new(rpc_client: impl RpcApi + 'static) -> Self {
+        Self::from_api(Arc::new(rpc_client))
+    }
+
+    /// Create an assertion service from a shared RPC implementation
+    pub fn from_api(rpc_client: Arc<dyn RpcApi>) -> Self {
+        Self { rpc_client }
+    }
+
+    /// Fetch `pubkey`, failing if it does not exist
+    pub async fn assert_exists(&self, pubkey: &Pubkey) -> Result<Account, AssertionError> {
+        self.rpc_client
+            .get_account_info(pubkey)
+            .await?
+            .ok_or(AssertionError::AccountNotFound(*pubkey))
+    }
+
+    /// Fetch `pubkey` and check that `program_id` owns it
+    pub async fn assert_owned_by(
+        &self,
+        pubkey: &Pubkey,
+        program_id: &Pubkey,
+    ) -> Result<Account, AssertionError> {
+        let account = self.assert_exists(pubkey).await?;
+        check_owner(&account, program_id)?;
+        Ok(account)
+    }
+
+    /// Fetch `pubkey` and decode it as Anchor account type `T`
+    pub async fn assert_account_type<T>(&self, pubkey: &Pubkey) -> Result<T, AssertionError>
+    where
+        T: Discriminator + BorshDeserialize,
+    {
+        let account = self.assert_exists(pubkey).await?;
+        decode(&account)
+    }
+
+    /// Fetch `pubkey`, check that `program_id` owns it and decode it as `T`
+    ///
+    /// This is the usual startup check for a program's state account: it
+    /// proves the account was initialized by the expected program.
+    pub async fn assert_program_account<T>(
+        &self,
+        pubkey: &Pubkey,
+        program_id: &Pubkey,
+    ) -> Result<T, AssertionError>
+    where
+        T: Discriminator + BorshDeserialize,
+    {
+        let account = self.assert_owned_by(pubkey, program_id).await?;
+        decode(&account)
+    }
+
+    /// Check that `program_id` is deployed and executable
+    pub async fn assert_executable(&self, program_id: &Pubkey) -> Result<Account, AssertionError> {
+        let account = self.assert_exists(program_id).await?;
+        if !account.executable {
+            return Err(AssertionError::NotExecutable(*program_id));
+        }
+        Ok(account)
+    }
+
+    /// Check that `pubkey` is the canonical PDA of `seeds`, returning its bump
+    ///
+    /// This needs no RPC call; it catches seeds that drifted from the program's.
+    pub fn assert_pda(
+        &self,
+        pubkey: &Pubkey,
+        seeds: &[&[u8]],
+        program_id: &Pubkey,
+    ) -> Result<u8, AssertionError> {
+        let (expected, bump) = Pubkey::find_program_address(seeds, program_id);
+        if expected != *pubkey {
+            return Err(AssertionError::WrongAddress {
+                account: *pubkey,
+                expected,
+            });
+        }
+        Ok(bump)
+    }
+}
+
+fn check_owner(account: &Account, program_id: &Pubkey) -> Result<(), AssertionError> {
+    if account.owner != *program_id {
+        return Err(AssertionError::WrongOwner {
+            account: account.pubkey,
+            expected: *program_id,
+            actual: account.owner,
+        });
+    }
+    Ok(())
+}
+
+fn decode<T>(account: &Account) -> Result<T, AssertionError>
+where
+    T: Discriminator + BorshDeserialize,
+{
+    account
+        .decode()
+        .map_err(|source| AssertionError::WrongType {
+            account: account.pubkey,
+            type_name: short_type_name::<T>(),
+            source,
+        })
+}
+
+/// `T`'s name without its module path
+fn short_type_name<T>() -> &'static str {
+    let name = std::any::type_name::<T>();
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::discriminator::account_discriminator;
+    use crate::infrastructure::rpc::mock::MockRpc;
+    use borsh::BorshSerialize;
+
+    #[derive(Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+    struct Counter {
+        count: u64,
+    }
+
+    impl Discriminator for Counter {
+        const DISCRIMINATOR: [u8; 8] = account_discriminator("Counter");
+    }
+
+    #[tokio::test]
+    async fn test_program_account_assertions() {
+        let program_id = Pubkey::new([9; 32]);
+        let (counter, _) = Pubkey::find_program_address(&[b"counter"], &program_id);
+        let mut data = Counter::DISCRIMINATOR.to_vec();
+        data.extend(borsh::to_vec(&Counter { count: 3 }).unwrap());
+        let stranger = Pubkey::new([4; 32]);
+        let rpc = MockRpc::new()
+            .with_account(Account {
+                pubkey: counter,
+                lamports: 1_000_000,
+                data,
+                owner: program_id,
+                executable: false,
+                rent_epoch: 0,
+            })
+            .with_balance(stranger, 5);
+        let assertions = AssertionService::new(rpc);
+
+        let decoded: Counter = assertions
+            .assert_program_account(&counter, &program_id)
+            .await
+            .unwrap();
+        assert_eq!(decoded, Counter { count: 3 });
+        assert!(assertions
+            .assert_pda(&counter, &[b"counter"], &program_id)
+            .is_ok());
+        assert!(matches!(
+            assertions.assert_pda(&counter, &[b"other"], &program_id),
+            Err(AssertionError::WrongAddress { .. })
+        ));
+
+        assert!(matches!(
+            assertions.assert_owned_by(&stranger, &program_id).await,
+            Err(AssertionError::WrongOwner { actual, .. }) if actual == Pubkey::new([0; 32])
+        ));
+        let error = assertions
+            .assert_account_type::<Counter>(&stranger)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("is not a Counter"));
+        assert!(matches!(
+            assertions.assert_exists(&Pubkey::new([5; 32])).await,
+            Err(AssertionError::AccountNotFound(_))
+        ));
+        assert!(matches!(
+            assertions.assert_executable(&counter).await,
+            Err(AssertionError::NotExecutable(_))
+        ));
+    }
+}
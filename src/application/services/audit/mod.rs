@@ -0,0 +1,310 @@
+//! Rent and reclaim audit of an owner's accounts
+//!
+//! Wallets accumulate token accounts that were emptied but never closed, and
+//! programs leave zeroed accounts behind; each still holds its rent deposit.
+//! [`AccountAuditService`] scans the token accounts of an owner, plus any
+//! program accounts registered with [`AccountAuditService::with_program`],
+//! flags the ones that can be closed or are below the rent-exempt minimum,
+//! and builds the close instructions that recover the lamports.
+
+use crate::domain::programs::token::{self, TOKEN_ACCOUNT_LEN};
+use crate::domain::programs::ProgramInstruction;
+use crate::domain::rent::rent_exempt_minimum;
+use crate::domain::types::constants::{TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID};
+use crate::domain::types::Pubkey;
+use crate::infrastructure::rpc::{Account, Filter, RpcApi, RpcError};
+use std::sync::Arc;
+
+/// Offset of the owner in a token account
+const TOKEN_OWNER_OFFSET: usize = 32;
+
+/// Token-2022 account type byte marking a token account, after the base layout
+const TOKEN_2022_ACCOUNT_TYPE: u8 = 2;
+
+/// Why an account was flagged
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuditFinding {
+    /// A token account holding no tokens, closable for its rent
+    EmptyTokenAccount { mint: Pubkey },
+    /// A program account whose data is all zeros
+    StaleProgramAccount,
+    /// An account holding less than the rent-exempt minimum for its size
+    NotRentExempt { shortfall: u64 },
+}
+
+/// A flagged account
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditedAccount {
+    pub address: Pubkey,
+    /// Program owning the account
+    pub program: Pubkey,
+    pub lamports: u64,
+    pub finding: AuditFinding,
+}
+
+impl AuditedAccount {
+    /// Lamports returned by closing the account, zero if it is not closable
+    pub fn recoverable_lamports(&self) -> u64 {
+        match self.finding {
+            AuditFinding::EmptyTokenAccount { .. } | AuditFinding::StaleProgramAccount => {
+                self.lamports
+            }
+            AuditFinding::NotRentExempt { .. } => 0,
+        }
+    }
+}
+
+/// Flagged accounts of one owner
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditReport {
+    pub owner: Pubkey,
+    /// Accounts scanned, flagged or not
+    pub scanned: usize,
+    /// Flagged accounts, most recoverable lamports first
+    pub accounts: Vec<AuditedAccount>,
+}
+
+impl AuditReport {
+    /// Lamports closing every reclaimable account would recover
+    pub fn recoverable_lamports(&self) -> u64 {
+        self.accounts
+            .iter()
+            .map(AuditedAccount::recoverable_lamports)
+            .sum()
+    }
+
+    /// Accounts below the rent-exempt minimum
+    pub fn not_rent_exempt(&self) -> impl Iterator<Item = &AuditedAccount> {
+        self.accounts
+            .iter()
+            .filter(|account| matches!(account.finding, AuditFinding::NotRentExempt { .. }))
+    }
+
+    /// Instructions closing the empty token accounts, paying `destination`
+    ///
+    /// The owner signs each instruction. Stale program accounts are closed
+    /// through their program's own instructions, so none are built for them.
+    pub fn close_instructions(&self, destination: Pubkey) -> Vec<ProgramInstruction> {
+        self.accounts
+            .iter()
+            .filter(|account| matches!(account.finding, AuditFinding::EmptyTokenAccount { .. }))
+            .map(|account| {
+                token::close_account(account.program, account.address, destination, self.owner)
+            })
+            .collect()
+    }
+}
+
+/// Program accounts to scan for an owner
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ProgramScan {
+    program: Pubkey,
+    /// Where the owner's key sits in the account data
+    owner_offset: usize,
+}
+
+/// Service finding reclaimable and under-funded accounts
+pub struct AccountAuditService {
+    rpc_client: Arc<dyn RpcApi>,
+    programs: Vec<ProgramScan>,
+}
+
+impl AccountAuditService {
+    /// Create an audit service scanning token accounts
+    pub fn new(rpc_client: impl RpcApi + 'static) -> Self {
+        Self::from_api(Arc::new(rpc_client))
+    }
+
+    /// Create an audit service from a shared RPC implementation
+    pub fn from_api(rpc_client: Arc<dyn RpcApi>) -> Self {
+        Self {
+            rpc_client,
+            programs: Vec::new(),
+        }
+    }
+
+    /// Also scan accounts of `program` storing the owner at `owner_offset`
+    pub fn with_program(mut self, program: Pubkey, owner_offset: usize) -> Self {
+        self.programs.push(ProgramScan {
+            program,
+            owner_offset,
+        });
+        self
+    }
+
+    /// Scan the accounts of `owner`
+    pub async fn audit(&self, owner: &Pubkey) -> Result<AuditReport, RpcError> {
+        let mut scanned = 0;
+        let mut accounts = Vec::new();
+
+        for program in [TOKEN_PROGRAM_ID, TOKEN_2022_PROGRAM_ID] {
+            for account in self.token_accounts(&program, owner).await? {
+                scanned += 1;
+                accounts.extend(audit_token_account(&account, owner));
+            }
+        }
+        for scan in &self.programs {
+            let filters = [Filter::memcmp_pubkey(scan.owner_offset, owner)];
+            for account in self
+                .rpc_client
+                .get_program_accounts(&scan.program, &filters)
+                .await?
+            {
+                scanned += 1;
+                accounts.extend(audit_program_account(&account, scan.owner_offset));
+            }
+        }
+
+        accounts.sort_by_key(|account| std::cmp::Reverse(account.recoverable_lamports()));
+        Ok(AuditReport {
+            owner: *owner,
+            scanned,
+            accounts,
+        })
+    }
+
+    async fn token_accounts(
+        &self,
+        program: &Pubkey,
+        owner: &Pubkey,
+    ) -> Result<Vec<Account>, RpcError> {
+        let mut filters = vec![Filter::memcmp_pubkey(TOKEN_OWNER_OFFSET, owner)];
+        if *program == TOKEN_PROGRAM_ID {
+            filters.push(Filter::data_size(TOKEN_ACCOUNT_LEN as u64));
+        }
+        let accounts = self
+            .rpc_client
+            .get_program_accounts(program, &filters)
+            .await?;
+        // Token-2022 mints are longer than 165 bytes too; keep token accounts
+        Ok(accounts
+            .into_iter()
+            .filter(|account| {
+                account.data.len() == TOKEN_ACCOUNT_LEN
+                    || account.data.get(TOKEN_ACCOUNT_LEN) == Some(&TOKEN_2022_ACCOUNT_TYPE)
+            })
+            .collect())
+    }
+}
+
+fn audit_token_account(account: &Account, owner: &Pubkey) -> Option<AuditedAccount> {
+    let data = &account.data;
+    let amount = u64::from_le_bytes(data.get(64..72)?.try_into().ok()?);
+    // Frozen accounts cannot be closed
+    let frozen = data.get(108) == Some(&2);
+    let close_authority = match data.get(129..133)? {
+        [0, 0, 0, 0] => None,
+        _ => Some(Pubkey::new(data.get(133..165)?.try_into().ok()?)),
+    };
+    let closable = amount == 0 && !frozen && close_authority.is_none_or(|key| key == *owner);
+
+    let finding = if closable {
+        AuditFinding::EmptyTokenAccount {
+            mint: Pubkey::new(data.get(..32)?.try_into().ok()?),
+        }
+    } else {
+        rent_shortfall(account)?
+    };
+    Some(flagged(account, finding))
+}
+
+fn audit_program_account(account: &Account, owner_offset: usize) -> Option<AuditedAccount> {
+    // The owner key itself matched the filter, so look at everything else
+    let stale = account
+        .data
+        .iter()
+        .enumerate()
+        .all(|(i, byte)| *byte == 0 || (owner_offset..owner_offset + 32).contains(&i));
+    let finding = if stale {
+        AuditFinding::StaleProgramAccount
+    } else {
+        rent_shortfall(account)?
+    };
+    Some(flagged(account, finding))
+}
+
+fn rent_shortfall(account: &Account) -> Option<AuditFinding> {
+    let minimum = rent_exempt_minimum(account.data.len());
+    (account.lamports < minimum).then(|| AuditFinding::NotRentExempt {
+        shortfall: minimum - account.lamports,
+    })
+}
+
+fn flagged(account: &Account, finding: AuditFinding) -> AuditedAccount {
+    AuditedAccount {
+        address: account.pubkey,
+        program: account.owner,
+        lamports: account.lamports,
+        finding,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::rpc::mock::MockRpc;
+
+    fn token_account(address: u8, owner: Pubkey, amount: u64, lamports: u64) -> Account {
+        let mut data = vec![0; TOKEN_ACCOUNT_LEN];
+        data[..32].copy_from_slice(&[address + 100; 32]);
+        data[32..64].copy_from_slice(owner.as_bytes());
+        data[64..72].copy_from_slice(&amount.to_le_bytes());
+        data[108] = 1;
+        Account {
+            pubkey: Pubkey::new([address; 32]),
+            lamports,
+            data,
+            owner: TOKEN_PROGRAM_ID,
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_audit_flags_reclaimable_accounts() {
+        let owner = Pubkey::new([9; 32]);
+        let program = Pubkey::new([50; 32]);
+        let mut stale = token_account(4, owner, 0, 1_500_000);
+        stale.owner = program;
+        stale.data = vec![0; 40];
+        stale.data[8..40].copy_from_slice(owner.as_bytes());
+        let rpc = MockRpc::new()
+            .with_account(token_account(1, owner, 0, 2_039_280))
+            .with_account(token_account(2, owner, 5, 2_039_280))
+            .with_account(token_account(3, owner, 5, 1_000_000))
+            .with_account(token_account(5, Pubkey::new([8; 32]), 0, 2_039_280))
+            .with_account(stale);
+        let service = AccountAuditService::new(rpc).with_program(program, 8);
+
+        let report = service.audit(&owner).await.unwrap();
+        assert_eq!(report.scanned, 4);
+        assert_eq!(report.accounts.len(), 3);
+        assert_eq!(report.accounts[0].address, Pubkey::new([1; 32]));
+        assert_eq!(
+            report.accounts[0].finding,
+            AuditFinding::EmptyTokenAccount {
+                mint: Pubkey::new([101; 32])
+            }
+        );
+        assert_eq!(
+            report.accounts[1].finding,
+            AuditFinding::StaleProgramAccount
+        );
+        assert_eq!(report.recoverable_lamports(), 3_539_280);
+
+        let underfunded: Vec<_> = report.not_rent_exempt().collect();
+        assert_eq!(
+            underfunded[0].finding,
+            AuditFinding::NotRentExempt {
+                shortfall: 1_039_280
+            }
+        );
+
+        let destination = Pubkey::new([7; 32]);
+        let instructions = report.close_instructions(destination);
+        assert_eq!(instructions.len(), 1);
+        assert_eq!(instructions[0].program_id, TOKEN_PROGRAM_ID);
+        assert_eq!(instructions[0].accounts[0].pubkey, Pubkey::new([1; 32]));
+        assert_eq!(instructions[0].accounts[1].pubkey, destination);
+    }
+}
@@ -0,0 +1,248 @@
+//! SOL balance over time
+//!
+//! Every transaction's metadata records the balances of its accounts before
+//! and after it ran, so walking an address's history yields its exact balance
+//! after each transaction. [`BalanceHistoryService`] turns that history into
+//! [`BalancePoint`]s ready to chart. Changes outside transactions, such as
+//! inflation rewards, only show up at the next transaction, which makes the
+//! curve between points approximate.
+
+use crate::application::services::clock::SlotTimeEstimator;
+use crate::domain::types::{Pubkey, Signature};
+use crate::infrastructure::rpc::{
+    ConfirmedTransaction, RpcApi, RpcError, SignatureInfo, SignaturesForAddressConfig,
+    MAX_SIGNATURES_PER_REQUEST,
+};
+use futures::StreamExt;
+use std::sync::Arc;
+
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+
+/// Balance of an address right after one of its transactions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BalancePoint {
+    pub signature: Signature,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    /// Whether `block_time` was estimated from neighbouring blocks
+    pub block_time_estimated: bool,
+    /// Balance after the transaction
+    pub lamports: u64,
+    /// Change made by the transaction, fees included
+    pub delta: i64,
+}
+
+impl BalancePoint {
+    /// Balance after the transaction in SOL
+    pub fn sol(&self) -> f64 {
+        self.lamports as f64 / LAMPORTS_PER_SOL
+    }
+}
+
+/// Balances of an address, oldest first
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BalanceHistory {
+    pub address: Pubkey,
+    pub points: Vec<BalancePoint>,
+}
+
+impl BalanceHistory {
+    /// The most recent point, if any
+    pub fn latest(&self) -> Option<&BalancePoint> {
+        self.points.last()
+    }
+
+    /// Balance before the oldest transaction covered
+    pub fn starting_lamports(&self) -> Option<u64> {
+        let first = self.points.first()?;
+        u64::try_from(first.lamports as i64 - first.delta).ok()
+    }
+
+    /// `(unix timestamp, SOL)` pairs for a chart, skipping untimed points
+    pub fn series(&self) -> Vec<(i64, f64)> {
+        self.points
+            .iter()
+            .filter_map(|point| Some((point.block_time?, point.sol())))
+            .collect()
+    }
+}
+
+/// Service reconstructing balance histories from transaction metadata
+pub struct BalanceHistoryService {
+    rpc_client: Arc<dyn RpcApi>,
+    max_transactions: usize,
+    concurrency: usize,
+}
+
+impl BalanceHistoryService {
+    /// Create a balance history service
+    pub fn new(rpc_client: impl RpcApi + 'static) -> Self {
+        Self::from_api(Arc::new(rpc_client))
+    }
+
+    /// Create a balance history service from a shared RPC implementation
+    pub fn from_api(rpc_client: Arc<dyn RpcApi>) -> Self {
+        Self {
+            rpc_client,
+            max_transactions: MAX_SIGNATURES_PER_REQUEST,
+            concurrency: 8,
+        }
+    }
+
+    /// Cover at most the `max` most recent transactions
+    pub fn max_transactions(mut self, max: usize) -> Self {
+        self.max_transactions = max.max(1);
+        self
+    }
+
+    /// Fetch up to `requests` transactions at once
+    pub fn concurrency(mut self, requests: usize) -> Self {
+        self.concurrency = requests.max(1);
+        self
+    }
+
+    /// Balance of `address` after each of its recent transactions
+    ///
+    /// Transactions the node no longer has are skipped.
+    pub async fn balance_history(&self, address: &Pubkey) -> Result<BalanceHistory, RpcError> {
+        let infos = self.signatures(address).await?;
+        let mut times = SlotTimeEstimator::new();
+        for info in &infos {
+            if let Some(time) = info.block_time {
+                times.record(info.slot, time);
+            }
+        }
+
+        let transactions: Vec<_> = futures::stream::iter(&infos)
+            .map(|info| self.rpc_client.get_transaction(&info.signature))
+            .buffered(self.concurrency)
+            .collect()
+            .await;
+
+        let mut points = Vec::with_capacity(infos.len());
+        for (info, transaction) in infos.iter().zip(transactions).rev() {
+            let Some(mut point) = transaction?
+                .as_ref()
+                .and_then(|transaction| balance_point(address, info, transaction))
+            else {
+                continue;
+            };
+            if point.block_time.is_none() {
+                point.block_time = times.estimate(point.slot);
+                point.block_time_estimated = point.block_time.is_some();
+            }
+            points.push(point);
+        }
+        Ok(BalanceHistory {
+            address: *address,
+            points,
+        })
+    }
+
+    /// Signatures of the most recent transactions, newest first
+    async fn signatures(&self, address: &Pubkey) -> Result<Vec<SignatureInfo>, RpcError> {
+        let mut infos: Vec<SignatureInfo> = Vec::new();
+        while infos.len() < self.max_transactions {
+            let limit = (self.max_transactions - infos.len()).min(MAX_SIGNATURES_PER_REQUEST);
+            let page = self
+                .rpc_client
+                .get_signatures_for_address(
+                    address,
+                    SignaturesForAddressConfig {
+                        before: infos.last().map(|info| info.signature.clone()),
+                        until: None,
+                        limit: Some(limit),
+                    },
+                )
+                .await?;
+            let done = page.len() < limit;
+            infos.extend(page.into_iter().take(limit));
+            if done {
+                break;
+            }
+        }
+        Ok(infos)
+    }
+}
+
+/// The balance of `address` after `transaction`, `None` if it is not an account of it
+fn balance_point(
+    address: &Pubkey,
+    info: &SignatureInfo,
+    transaction: &ConfirmedTransaction,
+) -> Option<BalancePoint> {
+    let meta = transaction.meta.as_ref()?;
+    let index = transaction
+        .account_keys()
+        .iter()
+        .position(|key| key == address)?;
+    let lamports = *meta.post_balances.get(index)?;
+    let before = meta.pre_balances.get(index).copied().unwrap_or(lamports);
+    Some(BalancePoint {
+        signature: info.signature.clone(),
+        slot: transaction.slot,
+        block_time: transaction.block_time,
+        block_time_estimated: false,
+        lamports,
+        delta: lamports as i64 - before as i64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::rpc::mock::MockRpc;
+    use serde_json::json;
+
+    fn transfer(slot: u64, block_time: Option<i64>, pre: u64, post: u64) -> ConfirmedTransaction {
+        serde_json::from_value(json!({
+            "slot": slot,
+            "blockTime": block_time,
+            "meta": {
+                "err": null,
+                "fee": 5000,
+                "preBalances": [10, pre],
+                "postBalances": [10, post]
+            },
+            "transaction": {
+                "signatures": [],
+                "message": {
+                    "accountKeys": [Pubkey::new([9; 32]).to_base58(), Pubkey::new([1; 32]).to_base58()],
+                    "recentBlockhash": "11111111111111111111111111111111"
+                }
+            }
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_balance_history_follows_post_balances() {
+        let wallet = Pubkey::new([1; 32]);
+        let rpc = MockRpc::new()
+            .with_transaction(
+                Signature::new([1; 64]),
+                transfer(100, Some(1_000), 0, 2_000_000_000),
+            )
+            .with_transaction(
+                Signature::new([2; 64]),
+                transfer(150, None, 2_000_000_000, 1_499_995_000),
+            )
+            .with_transaction(
+                Signature::new([3; 64]),
+                transfer(200, Some(1_040), 1_500_000_000, 1_750_000_000),
+            );
+
+        let history = BalanceHistoryService::new(rpc)
+            .balance_history(&wallet)
+            .await
+            .unwrap();
+        let slots: Vec<u64> = history.points.iter().map(|point| point.slot).collect();
+        assert_eq!(slots, [100, 150, 200]);
+        assert_eq!(history.starting_lamports(), Some(0));
+        assert_eq!(history.points[1].delta, -500_005_000);
+        assert_eq!(history.points[1].block_time, Some(1_020));
+        assert!(history.points[1].block_time_estimated);
+        assert_eq!(history.latest().unwrap().lamports, 1_750_000_000);
+        assert_eq!(history.series()[0], (1_000, 2.0));
+    }
+}
@@ -0,0 +1,446 @@
+//! Local cluster time estimation
+//!
+//! [`ClockService`] samples the Clock sysvar and extrapolates the current slot
+//! and cluster time locally between samples, so countdowns such as "auction
+//! ends in N slots" can tick every frame without an RPC call each time. The
+//! slot duration is measured from successive samples, which corrects for the
+//! cluster running slower or faster than the nominal 400ms.
+//!
+//! [`SlotTimeEstimator`] goes the other way for past slots: from the block
+//! times it has seen it interpolates the time of any nearby slot, so history
+//! views can show a time for transactions the node returned without one.
+//!
+//! [`ClockService::epoch_status`] combines `getEpochInfo` with the measured
+//! slot duration to estimate when the current epoch ends.
+
+use crate::domain::types::constants::SYSVAR_CLOCK_ID;
+use crate::infrastructure::rpc::{EpochInfo, RpcApi, RpcError};
+use crate::infrastructure::time::{Instant, SLOT_DURATION};
+use borsh::BorshDeserialize;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use thiserror::Error;
+
+/// Nominal slot duration, used until two samples have been taken
+pub const DEFAULT_SLOT_DURATION: Duration = SLOT_DURATION;
+
+/// Bounds of a measured slot duration; samples outside them are discarded
+const MIN_SLOT_DURATION: Duration = Duration::from_millis(100);
+const MAX_SLOT_DURATION: Duration = Duration::from_secs(2);
+
+/// Weight of the newest measurement in the smoothed slot duration
+const SMOOTHING: f64 = 0.3;
+
+/// Block times a [`SlotTimeEstimator`] keeps; the oldest slots are dropped first
+pub const MAX_TIME_ANCHORS: usize = 512;
+
+/// Errors raised while sampling the cluster clock
+#[derive(Debug, Clone, Error)]
+pub enum ClockError {
+    #[error("RPC error: {0}")]
+    Rpc(#[from] RpcError),
+
+    #[error("The Clock sysvar account does not exist")]
+    MissingSysvar,
+
+    #[error("Invalid Clock sysvar data: {0}")]
+    InvalidSysvar(String),
+}
+
+/// Contents of the Clock sysvar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshDeserialize)]
+pub struct Clock {
+    pub slot: u64,
+    /// Timestamp of the first slot of the epoch
+    pub epoch_start_timestamp: i64,
+    pub epoch: u64,
+    pub leader_schedule_epoch: u64,
+    /// Stake-weighted median of the validators' reported times, in seconds
+    pub unix_timestamp: i64,
+}
+
+impl Clock {
+    /// Decode the sysvar's account data
+    pub fn from_account_data(data: &[u8]) -> Result<Self, ClockError> {
+        Self::deserialize(&mut &data[..]).map_err(|e| ClockError::InvalidSysvar(e.to_string()))
+    }
+}
+
+/// Estimated cluster clock at one instant
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockEstimate {
+    pub slot: u64,
+    pub epoch: u64,
+    /// Estimated cluster time in seconds
+    pub unix_timestamp: i64,
+    /// Slot duration the estimate was extrapolated with
+    pub slot_duration: Duration,
+}
+
+impl ClockEstimate {
+    /// Estimated time until `slot`, zero once it has passed
+    pub fn time_until_slot(&self, slot: u64) -> Duration {
+        let slots = slot.saturating_sub(self.slot);
+        self.slot_duration
+            .saturating_mul(u32::try_from(slots).unwrap_or(u32::MAX))
+    }
+
+    /// Estimated slot at cluster time `unix_timestamp`
+    pub fn slot_at(&self, unix_timestamp: i64) -> u64 {
+        let seconds = unix_timestamp - self.unix_timestamp;
+        let slots = seconds as f64 / self.slot_duration.as_secs_f64();
+        (self.slot as f64 + slots).max(0.0) as u64
+    }
+}
+
+/// The current epoch with the slot duration its end is estimated with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EpochStatus {
+    pub info: EpochInfo,
+    pub slot_duration: Duration,
+}
+
+impl EpochStatus {
+    /// Estimated time until the next epoch starts
+    pub fn time_remaining(&self) -> Duration {
+        self.slot_duration
+            .saturating_mul(u32::try_from(self.info.slots_remaining()).unwrap_or(u32::MAX))
+    }
+}
+
+/// The latest sample and the slot duration measured so far
+#[derive(Debug, Clone, Copy)]
+struct ClockState {
+    clock: Clock,
+    sampled_at: Instant,
+    slot_duration: Duration,
+}
+
+impl ClockState {
+    fn estimate_at(&self, now: Instant) -> ClockEstimate {
+        let elapsed = now.saturating_duration_since(self.sampled_at);
+        let slots = elapsed.as_secs_f64() / self.slot_duration.as_secs_f64();
+        ClockEstimate {
+            slot: self.clock.slot + slots as u64,
+            epoch: self.clock.epoch,
+            unix_timestamp: self.clock.unix_timestamp + elapsed.as_secs() as i64,
+            slot_duration: self.slot_duration,
+        }
+    }
+
+    /// Take a new sample, folding the slot rate it implies into the estimate
+    fn resample(&mut self, clock: Clock, now: Instant) {
+        let slots = clock.slot.saturating_sub(self.clock.slot);
+        let elapsed = now.saturating_duration_since(self.sampled_at);
+        if slots > 0 {
+            let measured = elapsed / u32::try_from(slots).unwrap_or(u32::MAX);
+            if (MIN_SLOT_DURATION..=MAX_SLOT_DURATION).contains(&measured) {
+                self.slot_duration =
+                    self.slot_duration.mul_f64(1.0 - SMOOTHING) + measured.mul_f64(SMOOTHING);
+            }
+        }
+        self.clock = clock;
+        self.sampled_at = now;
+    }
+}
+
+/// Service estimating the cluster's current slot and time
+pub struct ClockService {
+    rpc_client: Arc<dyn RpcApi>,
+    resync_interval: Duration,
+    state: Mutex<Option<ClockState>>,
+}
+
+impl ClockService {
+    /// Create a clock service that resamples every 30 seconds
+    pub fn new(rpc_client: impl RpcApi + 'static) -> Self {
+        Self::from_api(Arc::new(rpc_client))
+    }
+
+    /// Create a clock service from a shared RPC implementation
+    pub fn from_api(rpc_client: Arc<dyn RpcApi>) -> Self {
+        Self {
+            rpc_client,
+            resync_interval: Duration::from_secs(30),
+            state: Mutex::new(None),
+        }
+    }
+
+    /// Set how old a sample may get before [`estimate`](Self::estimate) resamples
+    pub fn resync_interval(mut self, interval: Duration) -> Self {
+        self.resync_interval = interval;
+        self
+    }
+
+    /// Read the Clock sysvar and record it as the latest sample
+    pub async fn sync(&self) -> Result<Clock, ClockError> {
+        Ok(self.sample().await?.clock)
+    }
+
+    /// Estimate the cluster clock, resampling if the last sample is stale
+    pub async fn estimate(&self) -> Result<ClockEstimate, ClockError> {
+        let now = Instant::now();
+        let fresh = self
+            .state()
+            .filter(|state| now.saturating_duration_since(state.sampled_at) < self.resync_interval);
+        match fresh {
+            Some(state) => Ok(state.estimate_at(now)),
+            None => {
+                let state = self.sample().await?;
+                Ok(state.estimate_at(Instant::now()))
+            }
+        }
+    }
+
+    /// Estimate the cluster clock from the last sample without any RPC call
+    ///
+    /// Returns `None` until the first sample has been taken.
+    pub fn estimate_now(&self) -> Option<ClockEstimate> {
+        self.state().map(|state| state.estimate_at(Instant::now()))
+    }
+
+    /// The current epoch, with the end estimated from the measured slot duration
+    ///
+    /// Resamples the clock when the last sample is stale, so calling it on an
+    /// interval keeps refining the slot duration.
+    pub async fn epoch_status(&self) -> Result<EpochStatus, ClockError> {
+        let estimate = self.estimate().await?;
+        let info = self.rpc_client.get_epoch_info().await?;
+        Ok(EpochStatus {
+            info,
+            slot_duration: estimate.slot_duration,
+        })
+    }
+
+    /// The measured slot duration, or the nominal one before two samples
+    pub fn slot_duration(&self) -> Duration {
+        self.state()
+            .map_or(DEFAULT_SLOT_DURATION, |state| state.slot_duration)
+    }
+
+    fn state(&self) -> Option<ClockState> {
+        *self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Read the Clock sysvar and record it, returning the updated state
+    async fn sample(&self) -> Result<ClockState, ClockError> {
+        let account = self
+            .rpc_client
+            .get_account_info(&SYSVAR_CLOCK_ID)
+            .await?
+            .ok_or(ClockError::MissingSysvar)?;
+        let clock = Clock::from_account_data(&account.data)?;
+        Ok(self.record(clock, Instant::now()))
+    }
+
+    fn record(&self, clock: Clock, now: Instant) -> ClockState {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        match state.as_mut() {
+            Some(state) => {
+                state.resample(clock, now);
+                *state
+            }
+            None => *state.insert(ClockState {
+                clock,
+                sampled_at: now,
+                slot_duration: DEFAULT_SLOT_DURATION,
+            }),
+        }
+    }
+}
+
+/// Interpolates block times for slots between known ones
+#[derive(Debug, Clone)]
+pub struct SlotTimeEstimator {
+    anchors: BTreeMap<u64, i64>,
+    slot_duration: Duration,
+}
+
+impl Default for SlotTimeEstimator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SlotTimeEstimator {
+    /// Create an estimator extrapolating with the nominal slot duration
+    pub fn new() -> Self {
+        Self {
+            anchors: BTreeMap::new(),
+            slot_duration: DEFAULT_SLOT_DURATION,
+        }
+    }
+
+    /// Set the slot duration used beyond the known slots, e.g. a
+    /// [`ClockEstimate::slot_duration`]
+    pub fn slot_duration(mut self, slot_duration: Duration) -> Self {
+        self.slot_duration = slot_duration;
+        self
+    }
+
+    /// Record the block time of `slot`
+    pub fn record(&mut self, slot: u64, timestamp: i64) {
+        self.anchors.insert(slot, timestamp);
+        while self.anchors.len() > MAX_TIME_ANCHORS {
+            self.anchors.pop_first();
+        }
+    }
+
+    /// Fetch and record the block time of `slot`
+    pub async fn fetch(
+        &mut self,
+        rpc_client: &dyn RpcApi,
+        slot: u64,
+    ) -> Result<Option<i64>, RpcError> {
+        let timestamp = rpc_client.get_block_time(slot).await?;
+        if let Some(timestamp) = timestamp {
+            self.record(slot, timestamp);
+        }
+        Ok(timestamp)
+    }
+
+    /// Estimated block time of `slot`, `None` before any time is recorded
+    ///
+    /// Slots between two known ones are interpolated; slots outside them are
+    /// extrapolated from the nearest one with the slot duration.
+    pub fn estimate(&self, slot: u64) -> Option<i64> {
+        let before = self.anchors.range(..=slot).next_back();
+        let after = self.anchors.range(slot..).next();
+        match (before, after) {
+            (Some((&start, &start_time)), Some((&end, &end_time))) if start != end => {
+                let fraction = (slot - start) as f64 / (end - start) as f64;
+                Some(start_time + ((end_time - start_time) as f64 * fraction).round() as i64)
+            }
+            (Some((&known, &time)), _) | (None, Some((&known, &time))) => {
+                let slots = slot as f64 - known as f64;
+                Some(time + (slots * self.slot_duration.as_secs_f64()).round() as i64)
+            }
+            (None, None) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::types::Pubkey;
+    use crate::infrastructure::rpc::mock::MockRpc;
+    use crate::infrastructure::rpc::Account;
+
+    fn clock(slot: u64, unix_timestamp: i64) -> Clock {
+        Clock {
+            slot,
+            epoch_start_timestamp: 1_700_000_000,
+            epoch: 600,
+            leader_schedule_epoch: 601,
+            unix_timestamp,
+        }
+    }
+
+    fn clock_account(clock: &Clock) -> Account {
+        let mut data = Vec::new();
+        for field in [
+            clock.slot,
+            clock.epoch_start_timestamp as u64,
+            clock.epoch,
+            clock.leader_schedule_epoch,
+            clock.unix_timestamp as u64,
+        ] {
+            data.extend_from_slice(&field.to_le_bytes());
+        }
+        Account {
+            pubkey: SYSVAR_CLOCK_ID,
+            lamports: 1_169_280,
+            data,
+            owner: Pubkey::from_base58("Sysvar1111111111111111111111111111111111111").unwrap(),
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sync_reads_the_sysvar() {
+        let sampled = clock(250_000_000, 1_700_100_000);
+        let service = ClockService::new(MockRpc::new().with_account(clock_account(&sampled)));
+        assert!(service.estimate_now().is_none());
+
+        assert_eq!(service.sync().await.unwrap(), sampled);
+        let estimate = service.estimate().await.unwrap();
+        assert_eq!(estimate.slot, 250_000_000);
+        assert_eq!(estimate.epoch, 600);
+        assert_eq!(estimate.slot_duration, DEFAULT_SLOT_DURATION);
+
+        let missing = ClockService::new(MockRpc::new());
+        assert!(matches!(
+            missing.sync().await,
+            Err(ClockError::MissingSysvar)
+        ));
+
+        // The mock node is 20 slots into a 100-slot epoch
+        let status = service.epoch_status().await.unwrap();
+        assert_eq!(status.info.epoch, 1);
+        assert_eq!(status.info.slots_remaining(), 80);
+        assert!((status.info.progress() - 0.2).abs() < 1e-9);
+        assert_eq!(status.time_remaining(), Duration::from_secs(32));
+    }
+
+    #[tokio::test]
+    async fn test_slot_time_estimation() {
+        use crate::infrastructure::rpc::ConfirmedBlock;
+
+        let mut estimator = SlotTimeEstimator::new();
+        assert_eq!(estimator.estimate(100), None);
+
+        let block: ConfirmedBlock = serde_json::from_value(serde_json::json!({
+            "blockhash": "11111111111111111111111111111111",
+            "previousBlockhash": "11111111111111111111111111111111",
+            "parentSlot": 999,
+            "blockTime": 1_700_000_000
+        }))
+        .unwrap();
+        let rpc = MockRpc::new().with_block(1_000, block);
+        assert_eq!(
+            estimator.fetch(&rpc, 1_000).await.unwrap(),
+            Some(1_700_000_000)
+        );
+        assert_eq!(estimator.fetch(&rpc, 1_001).await.unwrap(), None);
+        estimator.record(1_100, 1_700_000_050);
+
+        assert_eq!(estimator.estimate(1_000), Some(1_700_000_000));
+        assert_eq!(estimator.estimate(1_040), Some(1_700_000_020));
+        assert_eq!(estimator.estimate(1_110), Some(1_700_000_054));
+        assert_eq!(estimator.estimate(990), Some(1_699_999_996));
+    }
+
+    #[test]
+    fn test_drift_correction() {
+        let start = Instant::now();
+        let mut state = ClockState {
+            clock: clock(1_000, 1_700_000_000),
+            sampled_at: start,
+            slot_duration: DEFAULT_SLOT_DURATION,
+        };
+        let estimate = state.estimate_at(start + Duration::from_secs(4));
+        assert_eq!(estimate.slot, 1_010);
+        assert_eq!(estimate.unix_timestamp, 1_700_000_004);
+
+        // The cluster produced 20 slots in 10s, i.e. 500ms slots
+        state.resample(clock(1_020, 1_700_000_010), start + Duration::from_secs(10));
+        assert_eq!(state.slot_duration, Duration::from_millis(430));
+        let estimate = state.estimate_at(start + Duration::from_millis(10_860));
+        assert_eq!(estimate.slot, 1_022);
+        assert_eq!(
+            estimate.time_until_slot(1_032),
+            Duration::from_millis(4_300)
+        );
+        assert_eq!(estimate.time_until_slot(1_000), Duration::ZERO);
+
+        // A sample after a long stall does not skew the estimate
+        state.resample(
+            clock(1_021, 1_700_000_100),
+            start + Duration::from_secs(100),
+        );
+        assert_eq!(state.slot_duration, Duration::from_millis(430));
+    }
+}
@@ -0,0 +1,478 @@
+//! Program-owned data accounts
+//!
+//! Creating an account for a program and filling it with bytes takes a
+//! `CreateAccount` transaction followed by one write transaction per chunk,
+//! each built, signed and sent until it lands. [`DataAccountService`] runs
+//! those transactions through a [`TransactionSender`]. Writes go through the
+//! owning program's own write instruction; the upgradeable loader's buffer
+//! accounts are supported out of the box since every cluster can write them.
+//!
+//! Like `solana program deploy`, chunks are written in parallel. A
+//! [`WriteWindow`] bounds how many write transactions are in flight: the
+//! window grows by one with each chunk that lands and halves when one expires
+//! or the node fails it, and failed chunks are sent again.
+
+use crate::application::flows::TransactionBuilder;
+use crate::application::sender::{SendConfig, SendError, SentTransaction, TransactionSender};
+use crate::domain::message::MessageError;
+use crate::domain::programs::loader::{self, BPF_LOADER_UPGRADEABLE_ID, BUFFER_METADATA_SIZE};
+use crate::domain::programs::{system, ProgramInstruction};
+use crate::domain::rent::rent_exempt_minimum;
+use crate::domain::types::Pubkey;
+use crate::infrastructure::rpc::{LatestBlockhash, RpcApi, RpcError};
+use crate::infrastructure::signer::{Signer, SignerError};
+use base64::{engine::general_purpose, Engine as _};
+use futures::stream::{FuturesUnordered, StreamExt};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use thiserror::Error;
+
+pub use crate::domain::programs::loader::MAX_WRITE_CHUNK;
+
+/// Errors that can occur while creating or writing data accounts
+#[derive(Debug, Clone, Error)]
+pub enum DataAccountError {
+    #[error("RPC error: {0}")]
+    Rpc(#[from] RpcError),
+
+    #[error("Send error: {0}")]
+    Send(#[from] SendError),
+
+    #[error("Signing failed: {0}")]
+    Signer(#[from] SignerError),
+
+    #[error("Transaction encoding failed: {0}")]
+    Message(#[from] MessageError),
+
+    #[error("A transaction needs at least one signer")]
+    NoSigners,
+
+    #[error("Account {0} not found")]
+    AccountNotFound(Pubkey),
+
+    #[error("{len} bytes at offset {offset} do not fit the account")]
+    OutOfBounds { offset: usize, len: usize },
+}
+
+/// How many chunk writes are in flight at once, and how often one is retried
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WriteWindow {
+    /// Write transactions in flight when writing starts
+    pub initial: usize,
+    /// Most write transactions in flight
+    pub max: usize,
+    /// Times a chunk is sent again after expiring or an RPC error
+    pub max_retries: usize,
+}
+
+impl WriteWindow {
+    /// Start with 4 writes in flight, growing to 32, retrying a chunk 5 times
+    pub fn new() -> Self {
+        Self {
+            initial: 4,
+            max: 32,
+            max_retries: 5,
+        }
+    }
+
+    /// Write one chunk at a time
+    pub fn sequential() -> Self {
+        Self {
+            initial: 1,
+            max: 1,
+            ..Self::new()
+        }
+    }
+
+    /// Set the writes in flight at the start
+    pub fn initial(mut self, writes: usize) -> Self {
+        self.initial = writes;
+        self
+    }
+
+    /// Set the most writes in flight
+    pub fn max(mut self, writes: usize) -> Self {
+        self.max = writes;
+        self
+    }
+
+    /// Set how many times a chunk is sent again
+    pub fn max_retries(mut self, retries: usize) -> Self {
+        self.max_retries = retries;
+        self
+    }
+}
+
+impl Default for WriteWindow {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A chunk that landed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WrittenChunk {
+    pub offset: usize,
+    pub len: usize,
+    /// Transactions sent for the chunk, 1 unless it was retried
+    pub attempts: usize,
+    pub transaction: SentTransaction,
+}
+
+/// Creates program-owned accounts and writes their data
+pub struct DataAccountService {
+    rpc_client: Arc<dyn RpcApi>,
+    sender: TransactionSender,
+    window: WriteWindow,
+}
+
+impl DataAccountService {
+    /// Create a data account service
+    pub fn new(rpc_client: impl RpcApi + 'static) -> Self {
+        Self::from_api(Arc::new(rpc_client))
+    }
+
+    /// Create a data account service from a shared RPC implementation
+    pub fn from_api(rpc_client: Arc<dyn RpcApi>) -> Self {
+        Self {
+            sender: TransactionSender::from_api(rpc_client.clone(), SendConfig::new()),
+            rpc_client,
+            window: WriteWindow::new(),
+        }
+    }
+
+    /// Send transactions with `config` instead of the default send loop
+    pub fn with_send_config(mut self, config: SendConfig) -> Self {
+        self.sender = TransactionSender::from_api(self.rpc_client.clone(), config);
+        self
+    }
+
+    /// Write chunks within `window` instead of the default
+    pub fn with_write_window(mut self, window: WriteWindow) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Create `account` with `space` bytes owned by `program`, rent-exempt
+    pub async fn create_account(
+        &self,
+        payer: &dyn Signer,
+        account: &dyn Signer,
+        space: usize,
+        program: &Pubkey,
+    ) -> Result<SentTransaction, DataAccountError> {
+        let create = system::create_account(
+            payer.pubkey(),
+            account.pubkey(),
+            rent_exempt_minimum(space),
+            space as u64,
+            program,
+        );
+        self.send(&[payer, account], vec![create]).await
+    }
+
+    /// Write `bytes` at `offset` in chunks, one transaction per chunk
+    ///
+    /// `write` encodes the owning program's instruction writing a chunk at
+    /// an offset. Chunks must be small enough for the instruction to fit in
+    /// a transaction; `signers` are the payer first, then any authority the
+    /// program checks. The transactions are returned in chunk order.
+    pub async fn write(
+        &self,
+        signers: &[&dyn Signer],
+        offset: usize,
+        bytes: &[u8],
+        chunk_size: usize,
+        write: impl Fn(u32, &[u8]) -> ProgramInstruction,
+    ) -> Result<Vec<SentTransaction>, DataAccountError> {
+        let chunks = self
+            .write_chunks(signers, offset, bytes, chunk_size, write)
+            .await?;
+        Ok(chunks.into_iter().map(|chunk| chunk.transaction).collect())
+    }
+
+    /// [`write`](Self::write), reporting each chunk and its attempts
+    ///
+    /// Chunks are sent within the service's [`WriteWindow`]. One that expires
+    /// or hits an RPC error is sent again with a fresh blockhash; one that
+    /// fails on chain stops the write, as sending it again would fail too.
+    pub async fn write_chunks(
+        &self,
+        signers: &[&dyn Signer],
+        offset: usize,
+        bytes: &[u8],
+        chunk_size: usize,
+        write: impl Fn(u32, &[u8]) -> ProgramInstruction,
+    ) -> Result<Vec<WrittenChunk>, DataAccountError> {
+        let chunk_size = chunk_size.max(1);
+        let mut queue = VecDeque::new();
+        for (index, chunk) in bytes.chunks(chunk_size).enumerate() {
+            let at = offset + index * chunk_size;
+            let at = u32::try_from(at).map_err(|_| DataAccountError::OutOfBounds {
+                offset: at,
+                len: chunk.len(),
+            })?;
+            queue.push_back(PendingChunk {
+                index,
+                offset: at,
+                bytes: chunk,
+                attempts: 0,
+            });
+        }
+        if queue.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut written: Vec<Option<WrittenChunk>> = vec![None; queue.len()];
+        let max = self.window.max.max(1);
+        let mut window = self.window.initial.clamp(1, max);
+        let mut latest = self.rpc_client.get_latest_blockhash().await?;
+        let mut in_flight = FuturesUnordered::new();
+        loop {
+            while in_flight.len() < window {
+                let Some(chunk) = queue.pop_front() else {
+                    break;
+                };
+                let instruction = write(chunk.offset, chunk.bytes);
+                let latest = latest.clone();
+                in_flight.push(async move {
+                    let result = self.send_with(signers, vec![instruction], &latest).await;
+                    (chunk, result)
+                });
+            }
+
+            let Some((mut chunk, result)) = in_flight.next().await else {
+                break;
+            };
+            chunk.attempts += 1;
+            match result {
+                Ok(transaction) => {
+                    window = (window + 1).min(max);
+                    written[chunk.index] = Some(WrittenChunk {
+                        offset: chunk.offset as usize,
+                        len: chunk.bytes.len(),
+                        attempts: chunk.attempts,
+                        transaction,
+                    });
+                }
+                Err(error) if is_retryable(&error) && chunk.attempts <= self.window.max_retries => {
+                    window = (window / 2).max(1);
+                    if matches!(
+                        error,
+                        DataAccountError::Send(SendError::BlockhashExpired { .. })
+                    ) {
+                        latest = self.rpc_client.get_latest_blockhash().await?;
+                    }
+                    queue.push_front(chunk);
+                }
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(written.into_iter().flatten().collect())
+    }
+
+    /// Create an upgradeable loader buffer holding `bytes`
+    ///
+    /// `buffer` is created and initialized with `payer` as its authority,
+    /// then written in parallel [`MAX_WRITE_CHUNK`]-byte chunks.
+    pub async fn create_buffer(
+        &self,
+        payer: &dyn Signer,
+        buffer: &dyn Signer,
+        bytes: &[u8],
+    ) -> Result<Vec<SentTransaction>, DataAccountError> {
+        let space = BUFFER_METADATA_SIZE + bytes.len();
+        let (buffer_key, authority) = (buffer.pubkey(), payer.pubkey());
+        let create = system::create_account(
+            authority,
+            buffer_key,
+            rent_exempt_minimum(space),
+            space as u64,
+            &BPF_LOADER_UPGRADEABLE_ID,
+        );
+        let initialize = loader::initialize_buffer(buffer_key, authority);
+        let mut sent = vec![
+            self.send(&[payer, buffer], vec![create, initialize])
+                .await?,
+        ];
+        sent.extend(
+            self.write(&[payer], 0, bytes, MAX_WRITE_CHUNK, |offset, chunk| {
+                loader::write(buffer_key, authority, offset, chunk)
+            })
+            .await?,
+        );
+        Ok(sent)
+    }
+
+    /// Read `len` bytes at `offset` of an account's data
+    pub async fn read(
+        &self,
+        account: &Pubkey,
+        offset: usize,
+        len: usize,
+    ) -> Result<Vec<u8>, DataAccountError> {
+        let data = self
+            .rpc_client
+            .get_account_info(account)
+            .await?
+            .ok_or(DataAccountError::AccountNotFound(*account))?
+            .data;
+        data.get(offset..offset + len)
+            .map(<[u8]>::to_vec)
+            .ok_or(DataAccountError::OutOfBounds { offset, len })
+    }
+
+    /// Read the bytes written to an upgradeable loader buffer
+    pub async fn read_buffer(
+        &self,
+        buffer: &Pubkey,
+        len: usize,
+    ) -> Result<Vec<u8>, DataAccountError> {
+        self.read(buffer, BUFFER_METADATA_SIZE, len).await
+    }
+
+    /// Build, sign and send one transaction paid by the first signer
+    async fn send(
+        &self,
+        signers: &[&dyn Signer],
+        instructions: Vec<ProgramInstruction>,
+    ) -> Result<SentTransaction, DataAccountError> {
+        let latest = self.rpc_client.get_latest_blockhash().await?;
+        self.send_with(signers, instructions, &latest).await
+    }
+
+    /// [`send`](Self::send) with an already fetched blockhash
+    async fn send_with(
+        &self,
+        signers: &[&dyn Signer],
+        instructions: Vec<ProgramInstruction>,
+        latest: &LatestBlockhash,
+    ) -> Result<SentTransaction, DataAccountError> {
+        let payer = signers.first().ok_or(DataAccountError::NoSigners)?.pubkey();
+        let builder = instructions.into_iter().fold(
+            TransactionBuilder::new(payer),
+            TransactionBuilder::instruction,
+        );
+        let mut transaction = builder.build(latest.blockhash)?;
+        for signer in signers {
+            let signature = signer.sign(&transaction.message).await?;
+            transaction.add_signature(&signer.pubkey(), signature)?;
+        }
+
+        let encoded = general_purpose::STANDARD.encode(transaction.serialize()?);
+        Ok(self
+            .sender
+            .send(&encoded, latest.last_valid_block_height)
+            .await?)
+    }
+}
+
+/// A chunk waiting to be written
+struct PendingChunk<'a> {
+    index: usize,
+    offset: u32,
+    bytes: &'a [u8],
+    attempts: usize,
+}
+
+/// Whether sending the chunk again may succeed
+fn is_retryable(error: &DataAccountError) -> bool {
+    match error {
+        DataAccountError::Rpc(error) | DataAccountError::Send(SendError::Rpc(error)) => {
+            !matches!(error, RpcError::Cancelled)
+        }
+        DataAccountError::Send(SendError::BlockhashExpired { .. }) => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::message::Transaction;
+    use crate::infrastructure::rpc::mock::{MockRpc, SENT_SIGNATURE};
+    use crate::infrastructure::rpc::{CommitmentLevel, SignatureStatus};
+    use crate::infrastructure::signer::Keypair;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_create_buffer_sends_create_and_chunked_writes() {
+        let rpc = Arc::new(MockRpc::new().with_statuses(
+            SENT_SIGNATURE,
+            vec![Some(SignatureStatus {
+                slot: 5,
+                confirmations: None,
+                err: None,
+                confirmation_status: Some(CommitmentLevel::Finalized),
+            })],
+        ));
+        let service = DataAccountService::from_api(rpc.clone())
+            .with_send_config(SendConfig::new().status_interval(Duration::from_millis(1)));
+        let payer = Keypair::from_seed(&[1; 32]);
+        let buffer = Keypair::from_seed(&[2; 32]);
+
+        let bytes = vec![7u8; MAX_WRITE_CHUNK + 10];
+        let landed = service
+            .create_buffer(&payer, &buffer, &bytes)
+            .await
+            .unwrap();
+        assert_eq!(landed.len(), 3);
+
+        let sent: Vec<Vec<u8>> = rpc
+            .sent
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|encoded| general_purpose::STANDARD.decode(encoded).unwrap())
+            .collect();
+        let create = Transaction::deserialize(&sent[0]).unwrap();
+        assert!(create.missing_signers().is_empty());
+        assert_eq!(create.message.instructions.len(), 2);
+        let last = Transaction::deserialize(&sent[2]).unwrap();
+        let write = &last.message.instructions[0].data;
+        assert_eq!(&write[4..8], &(MAX_WRITE_CHUNK as u32).to_le_bytes());
+        assert_eq!(write.len(), 16 + 10);
+    }
+
+    #[tokio::test]
+    async fn test_write_chunks_retries_failed_sends_in_order() {
+        let rpc = MockRpc::new().failing_sends(2).with_statuses(
+            SENT_SIGNATURE,
+            vec![Some(SignatureStatus {
+                slot: 5,
+                confirmations: None,
+                err: None,
+                confirmation_status: Some(CommitmentLevel::Finalized),
+            })],
+        );
+        let service = DataAccountService::new(rpc)
+            .with_send_config(SendConfig::new().status_interval(Duration::from_millis(1)))
+            .with_write_window(WriteWindow::new().initial(3));
+        let payer = Keypair::from_seed(&[1; 32]);
+        let buffer = Pubkey::new([2; 32]);
+
+        let chunks = service
+            .write_chunks(&[&payer], 8, &[1u8; 50], 10, |offset, chunk| {
+                loader::write(buffer, payer.pubkey(), offset, chunk)
+            })
+            .await
+            .unwrap();
+        let offsets: Vec<usize> = chunks.iter().map(|chunk| chunk.offset).collect();
+        assert_eq!(offsets, [8, 18, 28, 38, 48]);
+        let attempts: usize = chunks.iter().map(|chunk| chunk.attempts).sum();
+        assert_eq!(attempts, 7);
+
+        let failing = MockRpc::new().failing_sends(10);
+        let service = DataAccountService::new(failing)
+            .with_write_window(WriteWindow::sequential().max_retries(1));
+        let result = service
+            .write(&[&payer], 0, &[1u8; 10], 10, |offset, chunk| {
+                loader::write(buffer, payer.pubkey(), offset, chunk)
+            })
+            .await;
+        assert!(matches!(
+            result,
+            Err(DataAccountError::Send(SendError::Rpc(_)))
+        ));
+    }
+}
@@ -0,0 +1,400 @@
+//! Local development fixtures
+//!
+//! [`DevFixtures`] seeds a surfpool simnet with everything a dApp needs
+//! before its first test: funded user wallets, SPL token mints and token
+//! balances. Accounts are written with surfpool cheatcodes instead of
+//! transactions, so a whole [`FixtureConfig`] is provisioned in one call
+//! without a faucet or a mint authority's signature. With the `anchor`
+//! feature, the users can be read from the `[airdrop]` table of `Anchor.toml`.
+
+use crate::domain::rent::rent_exempt_minimum;
+use crate::domain::types::constants::TOKEN_PROGRAM_ID;
+use crate::domain::types::Pubkey;
+use crate::infrastructure::rpc::{AccountUpdate, Cheatcodes, RpcError};
+use std::sync::Arc;
+use thiserror::Error;
+
+/// Size of an SPL Token mint account
+pub const MINT_ACCOUNT_LEN: usize = 82;
+
+/// Errors raised while loading or provisioning fixtures
+#[derive(Debug, Clone, Error)]
+pub enum FixtureError {
+    #[error("RPC error: {0}")]
+    Rpc(#[from] RpcError),
+
+    #[error("Invalid fixture config: {0}")]
+    Config(String),
+
+    #[cfg(feature = "anchor")]
+    #[error("Workspace error: {0}")]
+    Workspace(#[from] crate::infrastructure::anchor_workspace::WorkspaceError),
+}
+
+/// A wallet to fund with SOL
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixtureUser {
+    pub pubkey: Pubkey,
+    pub lamports: u64,
+}
+
+/// An SPL token mint and the balances to hand out
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixtureMint {
+    pub mint: Pubkey,
+    pub decimals: u8,
+    pub mint_authority: Option<Pubkey>,
+    pub freeze_authority: Option<Pubkey>,
+    /// Token balances by owner, in base units
+    pub balances: Vec<(Pubkey, u64)>,
+}
+
+impl FixtureMint {
+    /// Create a mint without authorities or balances
+    pub fn new(mint: Pubkey, decimals: u8) -> Self {
+        Self {
+            mint,
+            decimals,
+            mint_authority: None,
+            freeze_authority: None,
+            balances: Vec::new(),
+        }
+    }
+
+    /// Set the mint authority
+    pub fn mint_authority(mut self, authority: Pubkey) -> Self {
+        self.mint_authority = Some(authority);
+        self
+    }
+
+    /// Set the freeze authority
+    pub fn freeze_authority(mut self, authority: Pubkey) -> Self {
+        self.freeze_authority = Some(authority);
+        self
+    }
+
+    /// Give `owner` a balance of `amount` base units
+    pub fn balance(mut self, owner: Pubkey, amount: u64) -> Self {
+        self.balances.push((owner, amount));
+        self
+    }
+
+    /// Total supply, the sum of every balance
+    pub fn supply(&self) -> u64 {
+        self.balances
+            .iter()
+            .fold(0u64, |total, (_, amount)| total.saturating_add(*amount))
+    }
+
+    /// The mint account in the SPL Token layout
+    pub fn account_data(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(MINT_ACCOUNT_LEN);
+        push_option_pubkey(&mut data, self.mint_authority.as_ref());
+        data.extend_from_slice(&self.supply().to_le_bytes());
+        data.push(self.decimals);
+        data.push(1); // is_initialized
+        push_option_pubkey(&mut data, self.freeze_authority.as_ref());
+        data
+    }
+}
+
+/// Wallets, mints and balances to seed a local cluster with
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FixtureConfig {
+    pub users: Vec<FixtureUser>,
+    pub mints: Vec<FixtureMint>,
+}
+
+impl FixtureConfig {
+    /// Create an empty configuration
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fund `pubkey` with `lamports`
+    pub fn user(mut self, pubkey: Pubkey, lamports: u64) -> Self {
+        self.users.push(FixtureUser { pubkey, lamports });
+        self
+    }
+
+    /// Create a token mint and its balances
+    pub fn mint(mut self, mint: FixtureMint) -> Self {
+        self.mints.push(mint);
+        self
+    }
+
+    /// Read the users of an `Anchor.toml` `[airdrop]` table
+    ///
+    /// Each user's keypair file is read for its public key; a user's own
+    /// `balance` overrides `amount_per_user` when non-zero. A missing or
+    /// disabled table yields an empty configuration.
+    #[cfg(all(feature = "anchor", not(target_arch = "wasm32")))]
+    pub fn from_workspace(
+        workspace: &crate::infrastructure::anchor_workspace::AnchorWorkspace,
+    ) -> Result<Self, FixtureError> {
+        let Some(airdrop) = workspace.section::<workspace::AirdropSection>("airdrop")? else {
+            return Ok(Self::new());
+        };
+        if !airdrop.enabled {
+            return Ok(Self::new());
+        }
+
+        airdrop.users.iter().try_fold(Self::new(), |config, user| {
+            let path = workspace.resolve_path(&user.keypair);
+            let pubkey = workspace::read_pubkey(&path)?;
+            let lamports = match user.balance {
+                0 => airdrop.amount_per_user,
+                balance => balance,
+            };
+            Ok(config.user(pubkey, lamports))
+        })
+    }
+}
+
+/// What [`DevFixtures::provision`] wrote
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FixtureReport {
+    pub funded_users: usize,
+    pub mints: usize,
+    pub token_accounts: usize,
+}
+
+/// Service seeding a surfpool simnet with fixtures
+pub struct DevFixtures {
+    cheatcodes: Arc<dyn Cheatcodes>,
+}
+
+impl DevFixtures {
+    /// Create a fixtures service
+    pub fn new(cheatcodes: impl Cheatcodes + 'static) -> Self {
+        Self::from_api(Arc::new(cheatcodes))
+    }
+
+    /// Create a fixtures service from a shared cheatcode implementation
+    pub fn from_api(cheatcodes: Arc<dyn Cheatcodes>) -> Self {
+        Self { cheatcodes }
+    }
+
+    /// Write every account in `config`
+    ///
+    /// Users are funded first, then mints are created, then token balances
+    /// are set. Provisioning is idempotent: running it again resets the same
+    /// accounts to the configured state.
+    pub async fn provision(&self, config: &FixtureConfig) -> Result<FixtureReport, FixtureError> {
+        let mut report = FixtureReport::default();
+
+        for user in &config.users {
+            self.cheatcodes
+                .set_account(&user.pubkey, &AccountUpdate::lamports(user.lamports))
+                .await?;
+            report.funded_users += 1;
+        }
+
+        for mint in &config.mints {
+            let update = AccountUpdate {
+                lamports: Some(rent_exempt_minimum(MINT_ACCOUNT_LEN)),
+                data: Some(mint.account_data()),
+                owner: Some(TOKEN_PROGRAM_ID),
+                executable: Some(false),
+            };
+            self.cheatcodes.set_account(&mint.mint, &update).await?;
+            report.mints += 1;
+
+            for (owner, amount) in &mint.balances {
+                self.cheatcodes
+                    .set_token_balance(owner, &mint.mint, *amount, &TOKEN_PROGRAM_ID)
+                    .await?;
+                report.token_accounts += 1;
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+/// Append a `COption<Pubkey>`: a 4-byte tag followed by 32 bytes
+fn push_option_pubkey(data: &mut Vec<u8>, pubkey: Option<&Pubkey>) {
+    match pubkey {
+        Some(pubkey) => {
+            data.extend_from_slice(&1u32.to_le_bytes());
+            data.extend_from_slice(pubkey.as_bytes());
+        }
+        None => data.extend_from_slice(&[0; 36]),
+    }
+}
+
+#[cfg(all(feature = "anchor", not(target_arch = "wasm32")))]
+mod workspace {
+    use super::FixtureError;
+    use crate::domain::types::Pubkey;
+    use crate::infrastructure::signer::{Keypair, Signer};
+    use serde::Deserialize;
+    use std::path::Path;
+
+    /// The `[airdrop]` table of the surfpool Anchor template
+    #[derive(Debug, Deserialize)]
+    pub(super) struct AirdropSection {
+        #[serde(default = "enabled")]
+        pub enabled: bool,
+        #[serde(default)]
+        pub amount_per_user: u64,
+        #[serde(default)]
+        pub users: Vec<AirdropUser>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    pub(super) struct AirdropUser {
+        pub keypair: String,
+        #[serde(default)]
+        pub balance: u64,
+    }
+
+    fn enabled() -> bool {
+        true
+    }
+
+    /// Public key of a `solana-keygen` JSON keypair file
+    pub(super) fn read_pubkey(path: &Path) -> Result<Pubkey, FixtureError> {
+        let invalid = |message: String| {
+            FixtureError::Config(format!("keypair {}: {}", path.display(), message))
+        };
+        let json = std::fs::read_to_string(path).map_err(|e| invalid(e.to_string()))?;
+        let bytes: Vec<u8> = serde_json::from_str(&json).map_err(|e| invalid(e.to_string()))?;
+        let keypair = Keypair::from_bytes(&bytes).map_err(|e| invalid(e.to_string()))?;
+        Ok(keypair.pubkey())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct RecordingCheatcodes {
+        accounts: Mutex<Vec<(Pubkey, AccountUpdate)>>,
+        token_balances: Mutex<Vec<(Pubkey, Pubkey, u64)>>,
+    }
+
+    #[async_trait::async_trait]
+    impl Cheatcodes for RecordingCheatcodes {
+        async fn set_account(
+            &self,
+            pubkey: &Pubkey,
+            update: &AccountUpdate,
+        ) -> Result<(), RpcError> {
+            self.accounts
+                .lock()
+                .unwrap()
+                .push((*pubkey, update.clone()));
+            Ok(())
+        }
+
+        async fn set_token_balance(
+            &self,
+            owner: &Pubkey,
+            mint: &Pubkey,
+            amount: u64,
+            token_program: &Pubkey,
+        ) -> Result<(), RpcError> {
+            assert_eq!(*token_program, TOKEN_PROGRAM_ID);
+            self.token_balances
+                .lock()
+                .unwrap()
+                .push((*owner, *mint, amount));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_mint_layout() {
+        let authority = Pubkey::new([3; 32]);
+        let mint = FixtureMint::new(Pubkey::new([1; 32]), 6)
+            .mint_authority(authority)
+            .balance(Pubkey::new([4; 32]), 700)
+            .balance(Pubkey::new([5; 32]), 300);
+
+        let data = mint.account_data();
+        assert_eq!(data.len(), MINT_ACCOUNT_LEN);
+        assert_eq!(&data[..4], &[1, 0, 0, 0]);
+        assert_eq!(&data[4..36], authority.as_bytes());
+        assert_eq!(u64::from_le_bytes(data[36..44].try_into().unwrap()), 1000);
+        assert_eq!(data[44], 6);
+        assert_eq!(data[45], 1);
+        assert_eq!(&data[46..], &[0; 36]);
+    }
+
+    #[tokio::test]
+    async fn test_provision() {
+        let alice = Pubkey::new([4; 32]);
+        let bob = Pubkey::new([5; 32]);
+        let usdc = Pubkey::new([1; 32]);
+        let config = FixtureConfig::new()
+            .user(alice, 10_000_000_000)
+            .user(bob, 1_000_000_000)
+            .mint(FixtureMint::new(usdc, 6).balance(alice, 5_000_000));
+
+        let cheatcodes = Arc::new(RecordingCheatcodes::default());
+        let fixtures = DevFixtures::from_api(cheatcodes.clone());
+        let report = fixtures.provision(&config).await.unwrap();
+        assert_eq!(
+            report,
+            FixtureReport {
+                funded_users: 2,
+                mints: 1,
+                token_accounts: 1
+            }
+        );
+
+        let accounts = cheatcodes.accounts.lock().unwrap();
+        assert_eq!(
+            accounts[0],
+            (alice, AccountUpdate::lamports(10_000_000_000))
+        );
+        assert_eq!(accounts[2].0, usdc);
+        assert_eq!(accounts[2].1.owner, Some(TOKEN_PROGRAM_ID));
+        assert_eq!(
+            *cheatcodes.token_balances.lock().unwrap(),
+            vec![(alice, usdc, 5_000_000)]
+        );
+    }
+
+    #[cfg(all(feature = "anchor", not(target_arch = "wasm32")))]
+    #[test]
+    fn test_from_workspace() {
+        use crate::infrastructure::anchor_workspace::AnchorWorkspace;
+        use crate::infrastructure::signer::{Keypair, Signer};
+
+        let root = std::env::temp_dir().join(format!("fixtures-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        let keypair = Keypair::from_seed(&[9; 32]);
+        let bytes = serde_json::to_string(&keypair.to_bytes().expose_secret().to_vec()).unwrap();
+        std::fs::write(root.join("user.json"), bytes).unwrap();
+
+        let workspace = AnchorWorkspace::parse(
+            r#"
+            [provider]
+            cluster = "localnet"
+            wallet = "user.json"
+
+            [airdrop]
+            enabled = true
+            amount_per_user = 10000000000
+
+            [[airdrop.users]]
+            keypair = "user.json"
+            name = "Local Developer"
+            balance = 0
+            "#,
+            &root,
+        )
+        .unwrap();
+        let config = FixtureConfig::from_workspace(&workspace).unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(
+            config,
+            FixtureConfig::new().user(keypair.pubkey(), 10_000_000_000)
+        );
+    }
+}
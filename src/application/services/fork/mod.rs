@@ -0,0 +1,235 @@
+//! Mainnet state in a local surfpool
+//!
+//! [`SurfpoolForkService`] reads accounts from one cluster, usually mainnet,
+//! and writes them into a surfpool simnet with the `surfnet_setAccount`
+//! cheatcode. Tests can then run against real token mints, pools or programs
+//! without waiting for surfpool to fetch them lazily, or while offline.
+
+use crate::domain::programs::loader::{
+    LoaderStateError, UpgradeableLoaderState, BPF_LOADER_UPGRADEABLE_ID,
+};
+use crate::domain::types::Pubkey;
+use crate::infrastructure::rpc::{
+    Account, AccountUpdate, Cheatcodes, Filter, RpcApi, RpcError, MAX_ACCOUNTS_PER_REQUEST,
+};
+use std::sync::Arc;
+use thiserror::Error;
+
+/// Errors that can occur while cloning accounts
+#[derive(Debug, Clone, Error)]
+pub enum ForkError {
+    #[error("RPC error: {0}")]
+    Rpc(#[from] RpcError),
+
+    #[error("Program {program}: {source}")]
+    Loader {
+        program: Pubkey,
+        source: LoaderStateError,
+    },
+}
+
+/// What a clone wrote
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ForkReport {
+    /// Accounts written to the simnet
+    pub cloned: Vec<Pubkey>,
+    /// Requested accounts that do not exist on the source cluster
+    pub missing: Vec<Pubkey>,
+}
+
+/// Service copying accounts from a source cluster into a surfpool simnet
+pub struct SurfpoolForkService {
+    source: Arc<dyn RpcApi>,
+    cheatcodes: Arc<dyn Cheatcodes>,
+}
+
+impl SurfpoolForkService {
+    /// Create a fork service reading from `source` and writing to `surfpool`
+    pub fn new(source: impl RpcApi + 'static, surfpool: impl Cheatcodes + 'static) -> Self {
+        Self::from_api(Arc::new(source), Arc::new(surfpool))
+    }
+
+    /// Create a fork service from shared implementations
+    pub fn from_api(source: Arc<dyn RpcApi>, surfpool: Arc<dyn Cheatcodes>) -> Self {
+        Self {
+            source,
+            cheatcodes: surfpool,
+        }
+    }
+
+    /// Copy `pubkeys` as they are now on the source cluster
+    pub async fn clone_accounts(&self, pubkeys: &[Pubkey]) -> Result<ForkReport, ForkError> {
+        let mut report = ForkReport::default();
+        for chunk in pubkeys.chunks(MAX_ACCOUNTS_PER_REQUEST) {
+            let accounts = self.source.get_multiple_accounts(chunk).await?;
+            for (pubkey, account) in chunk.iter().zip(accounts) {
+                match account {
+                    Some(account) => {
+                        self.write(&account).await?;
+                        report.cloned.push(*pubkey);
+                    }
+                    None => report.missing.push(*pubkey),
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    /// Copy a program so it can be invoked on the simnet
+    ///
+    /// Programs of the upgradeable loader are copied with their programdata
+    /// account, which holds the executable bytes.
+    pub async fn clone_program(&self, program_id: &Pubkey) -> Result<ForkReport, ForkError> {
+        let Some(program) = self.source.get_account_info(program_id).await? else {
+            return Ok(ForkReport {
+                missing: vec![*program_id],
+                ..ForkReport::default()
+            });
+        };
+
+        let mut pubkeys = vec![*program_id];
+        if program.owner == BPF_LOADER_UPGRADEABLE_ID {
+            let state =
+                UpgradeableLoaderState::from_account_data(&program.data).map_err(|source| {
+                    ForkError::Loader {
+                        program: *program_id,
+                        source,
+                    }
+                })?;
+            if let UpgradeableLoaderState::Program {
+                programdata_address,
+            } = state
+            {
+                pubkeys.push(programdata_address);
+            }
+        }
+        self.clone_accounts(&pubkeys).await
+    }
+
+    /// Copy every account owned by `program_id` that matches `filters`
+    ///
+    /// Without filters this scans the whole program, which public mainnet
+    /// endpoints refuse for large programs.
+    pub async fn clone_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        filters: &[Filter],
+    ) -> Result<ForkReport, ForkError> {
+        let accounts = self
+            .source
+            .get_program_accounts(program_id, filters)
+            .await?;
+        let mut report = ForkReport::default();
+        for account in accounts {
+            self.write(&account).await?;
+            report.cloned.push(account.pubkey);
+        }
+        Ok(report)
+    }
+
+    async fn write(&self, account: &Account) -> Result<(), RpcError> {
+        let update = AccountUpdate {
+            lamports: Some(account.lamports),
+            data: Some(account.data.clone()),
+            owner: Some(account.owner),
+            executable: Some(account.executable),
+        };
+        self.cheatcodes.set_account(&account.pubkey, &update).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::rpc::mock::MockRpc;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct RecordingCheatcodes {
+        accounts: Mutex<Vec<(Pubkey, AccountUpdate)>>,
+    }
+
+    #[async_trait::async_trait]
+    impl Cheatcodes for RecordingCheatcodes {
+        async fn set_account(
+            &self,
+            pubkey: &Pubkey,
+            update: &AccountUpdate,
+        ) -> Result<(), RpcError> {
+            self.accounts
+                .lock()
+                .unwrap()
+                .push((*pubkey, update.clone()));
+            Ok(())
+        }
+
+        async fn set_token_balance(
+            &self,
+            _owner: &Pubkey,
+            _mint: &Pubkey,
+            _amount: u64,
+            _token_program: &Pubkey,
+        ) -> Result<(), RpcError> {
+            unreachable!("forking only writes whole accounts")
+        }
+    }
+
+    fn account(pubkey: Pubkey, owner: Pubkey, data: Vec<u8>, executable: bool) -> Account {
+        Account {
+            pubkey,
+            lamports: 1_141_440,
+            data,
+            owner,
+            executable,
+            rent_epoch: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_clone_accounts() {
+        let mint = Pubkey::new([1; 32]);
+        let missing = Pubkey::new([2; 32]);
+        let source =
+            MockRpc::new().with_account(account(mint, Pubkey::new([6; 32]), vec![7; 82], false));
+
+        let surfpool = Arc::new(RecordingCheatcodes::default());
+        let fork = SurfpoolForkService::from_api(Arc::new(source), surfpool.clone());
+        let report = fork.clone_accounts(&[mint, missing]).await.unwrap();
+        assert_eq!(report.cloned, [mint]);
+        assert_eq!(report.missing, [missing]);
+
+        let written = surfpool.accounts.lock().unwrap();
+        assert_eq!(written[0].0, mint);
+        assert_eq!(written[0].1.data.as_deref(), Some(&[7; 82][..]));
+        assert_eq!(written[0].1.lamports, Some(1_141_440));
+    }
+
+    #[tokio::test]
+    async fn test_clone_upgradeable_program() {
+        let program_id = Pubkey::new([3; 32]);
+        let programdata = Pubkey::new([4; 32]);
+        // UpgradeableLoaderState::Program { programdata_address }
+        let mut data = 2u32.to_le_bytes().to_vec();
+        data.extend_from_slice(programdata.as_bytes());
+        let source = MockRpc::new()
+            .with_account(account(program_id, BPF_LOADER_UPGRADEABLE_ID, data, true))
+            .with_account(account(
+                programdata,
+                BPF_LOADER_UPGRADEABLE_ID,
+                vec![3; 45],
+                false,
+            ));
+
+        let surfpool = Arc::new(RecordingCheatcodes::default());
+        let fork = SurfpoolForkService::from_api(Arc::new(source), surfpool.clone());
+        let report = fork.clone_program(&program_id).await.unwrap();
+        assert_eq!(report.cloned, [program_id, programdata]);
+        assert_eq!(
+            surfpool.accounts.lock().unwrap()[0].1.executable,
+            Some(true)
+        );
+
+        let report = fork.clone_program(&Pubkey::new([5; 32])).await.unwrap();
+        assert_eq!(report.missing, [Pubkey::new([5; 32])]);
+    }
+}
@@ -0,0 +1,234 @@
+//! IDL registry service
+//!
+//! Keeps Anchor IDLs keyed by program id so logs, instruction data and custom
+//! program errors can be decoded into names. IDLs are registered directly or
+//! fetched from the program's on-chain IDL account, and persisted through a
+//! [`StorageAdapter`] so they survive restarts.
+
+use crate::domain::errors::{CustomErrorResolver, ResolvedError, TransactionError};
+use crate::domain::idl::{idl_address, Idl, IdlError, IdlEvent};
+use crate::domain::types::Pubkey;
+use crate::infrastructure::rpc::{RpcApi, RpcError};
+use crate::infrastructure::storage::{StorageAdapter, StorageError};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use thiserror::Error;
+
+/// Storage key prefix for persisted IDLs
+const STORAGE_PREFIX: &str = "gloo_solana:idl:";
+
+/// Registry of Anchor IDLs keyed by program id
+#[derive(Clone)]
+pub struct IdlRegistry {
+    rpc_client: Arc<dyn RpcApi>,
+    storage: Arc<dyn StorageAdapter>,
+    idls: Arc<RwLock<HashMap<Pubkey, Arc<Idl>>>>,
+}
+
+/// Errors that can occur in the IDL registry
+#[derive(Debug, Clone, Error)]
+pub enum IdlRegistryError {
+    #[error("RPC error: {0}")]
+    Rpc(#[from] RpcError),
+
+    #[error("Storage error: {0}")]
+    Storage(#[from] StorageError),
+
+    #[error("Failed to decode IDL: {0}")]
+    Decode(#[from] IdlError),
+
+    #[error("No IDL account found for program {0}")]
+    AccountNotFound(Pubkey),
+}
+
+impl IdlRegistry {
+    /// Create a new registry persisting IDLs to `storage`
+    pub fn new(rpc_client: impl RpcApi + 'static, storage: Arc<dyn StorageAdapter>) -> Self {
+        Self::from_api(Arc::new(rpc_client), storage)
+    }
+
+    /// Create a new registry from a shared RPC implementation
+    pub fn from_api(rpc_client: Arc<dyn RpcApi>, storage: Arc<dyn StorageAdapter>) -> Self {
+        Self {
+            rpc_client,
+            storage,
+            idls: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Register an IDL for a program, replacing any previous one
+    pub fn register(&self, program_id: Pubkey, idl: Idl) -> Result<Arc<Idl>, IdlRegistryError> {
+        let json = serde_json::to_string(&idl).map_err(|e| IdlError::Json(e.to_string()))?;
+        self.storage.set(&storage_key(&program_id), &json)?;
+
+        let idl = Arc::new(idl);
+        if let Ok(mut idls) = self.idls.write() {
+            idls.insert(program_id, idl.clone());
+        }
+        Ok(idl)
+    }
+
+    /// Look up an IDL in memory, falling back to storage
+    pub fn get(&self, program_id: &Pubkey) -> Option<Arc<Idl>> {
+        if let Some(idl) = self
+            .idls
+            .read()
+            .ok()
+            .and_then(|idls| idls.get(program_id).cloned())
+        {
+            return Some(idl);
+        }
+
+        let json = self.storage.get(&storage_key(program_id)).ok()??;
+        let idl = Arc::new(Idl::from_json(&json).ok()?);
+        if let Ok(mut idls) = self.idls.write() {
+            idls.insert(*program_id, idl.clone());
+        }
+        Some(idl)
+    }
+
+    /// Fetch a program's IDL from its on-chain IDL account and register it
+    pub async fn fetch_onchain(&self, program_id: &Pubkey) -> Result<Arc<Idl>, IdlRegistryError> {
+        let address = idl_address(program_id)?;
+        let account = self
+            .rpc_client
+            .get_account_info(&address)
+            .await?
+            .ok_or(IdlRegistryError::AccountNotFound(*program_id))?;
+
+        let idl = Idl::from_account_data(&account.data)?;
+        self.register(*program_id, idl)
+    }
+
+    /// Get a registered IDL, fetching it on-chain if unknown
+    pub async fn get_or_fetch(&self, program_id: &Pubkey) -> Result<Arc<Idl>, IdlRegistryError> {
+        match self.get(program_id) {
+            Some(idl) => Ok(idl),
+            None => self.fetch_onchain(program_id).await,
+        }
+    }
+
+    /// Remove a program's IDL from memory and storage
+    pub fn remove(&self, program_id: &Pubkey) -> Result<(), IdlRegistryError> {
+        if let Ok(mut idls) = self.idls.write() {
+            idls.remove(program_id);
+        }
+        self.storage.remove(&storage_key(program_id))?;
+        Ok(())
+    }
+
+    /// Program ids with a registered or persisted IDL
+    pub fn program_ids(&self) -> Result<Vec<Pubkey>, IdlRegistryError> {
+        let mut ids: Vec<Pubkey> = self
+            .storage
+            .keys(STORAGE_PREFIX)?
+            .iter()
+            .filter_map(|key| key.strip_prefix(STORAGE_PREFIX))
+            .filter_map(|id| Pubkey::from_base58(id).ok())
+            .collect();
+        if let Ok(idls) = self.idls.read() {
+            ids.extend(idls.keys().copied());
+        }
+        ids.sort_by_key(|id| id.to_base58());
+        ids.dedup();
+        Ok(ids)
+    }
+
+    /// Decode Anchor events emitted by `program_id` from transaction logs
+    pub fn parse_events(&self, program_id: &Pubkey, logs: &[String]) -> Vec<IdlEvent> {
+        self.get(program_id)
+            .map(|idl| idl.parse_events(logs))
+            .unwrap_or_default()
+    }
+
+    /// Name of the instruction encoded in `data`, if the program's IDL is known
+    pub fn instruction_name(&self, program_id: &Pubkey, data: &[u8]) -> Option<String> {
+        let idl = self.get(program_id)?;
+        idl.match_instruction(data).map(|ix| ix.name.clone())
+    }
+
+    /// Describe a transaction error, resolving custom codes through registered IDLs
+    pub fn describe_error(&self, error: &TransactionError, program_ids: &[Pubkey]) -> String {
+        error.describe(program_ids, Some(self))
+    }
+}
+
+impl CustomErrorResolver for IdlRegistry {
+    fn resolve_custom_error(&self, program_id: &Pubkey, code: u32) -> Option<ResolvedError> {
+        let idl = self.get(program_id)?;
+        let error = idl.error(code)?;
+        Some(ResolvedError {
+            code,
+            name: error.name.clone(),
+            message: error.msg.clone().unwrap_or_else(|| error.name.clone()),
+        })
+    }
+}
+
+fn storage_key(program_id: &Pubkey) -> String {
+    format!("{}{}", STORAGE_PREFIX, program_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::errors::InstructionError;
+    use crate::infrastructure::storage::MemoryStorage;
+
+    const IDL: &str = r#"{
+        "address": "Counter111111111111111111111111111111111111",
+        "metadata": { "name": "counter", "version": "0.1.0" },
+        "instructions": [{ "name": "increment", "discriminator": [1, 1, 1, 1, 1, 1, 1, 1] }],
+        "errors": [{ "code": 6000, "name": "Overflow", "msg": "Counter overflowed" }]
+    }"#;
+
+    fn registry(storage: Arc<dyn StorageAdapter>) -> IdlRegistry {
+        IdlRegistry::new(
+            crate::infrastructure::rpc::SolanaRpcClient::with_endpoint("http://localhost:8899")
+                .unwrap(),
+            storage,
+        )
+    }
+
+    #[test]
+    fn test_register_and_resolve() {
+        let registry = registry(Arc::new(MemoryStorage::new()));
+        let program_id = Pubkey::new([7; 32]);
+        registry
+            .register(program_id, Idl::from_json(IDL).unwrap())
+            .unwrap();
+
+        assert_eq!(
+            registry.instruction_name(&program_id, &[1; 9]).as_deref(),
+            Some("increment")
+        );
+
+        let resolved = registry.resolve_custom_error(&program_id, 6000).unwrap();
+        assert_eq!(resolved.name, "Overflow");
+        assert!(registry.resolve_custom_error(&program_id, 6001).is_none());
+
+        let error = TransactionError::InstructionError {
+            index: 0,
+            error: InstructionError::Custom(6000),
+        };
+        assert!(registry
+            .describe_error(&error, &[program_id])
+            .contains("Overflow"));
+    }
+
+    #[test]
+    fn test_persists_through_storage() {
+        let storage = Arc::new(MemoryStorage::new());
+        let program_id = Pubkey::new([9; 32]);
+        registry(storage.clone())
+            .register(program_id, Idl::from_json(IDL).unwrap())
+            .unwrap();
+
+        let fresh = registry(storage);
+        assert_eq!(fresh.get(&program_id).unwrap().name(), Some("counter"));
+        assert_eq!(fresh.program_ids().unwrap(), vec![program_id]);
+
+        fresh.remove(&program_id).unwrap();
+        assert!(fresh.get(&program_id).is_none());
+    }
+}
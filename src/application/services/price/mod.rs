@@ -0,0 +1,202 @@
+//! USD prices from on-chain oracles
+//!
+//! [`PriceService`] reads Pyth and Switchboard price accounts and refuses
+//! prices that are too old to show, so a dashboard can value balances in USD
+//! without a price API of its own. A price is stale once it was published
+//! longer ago than the service's maximum age, a minute by default, which
+//! catches feeds that stopped updating as well as a lagging RPC node.
+
+use crate::domain::oracles::{decode_price, OracleError, OraclePrice};
+use crate::domain::types::Pubkey;
+use crate::infrastructure::rpc::{Account, RpcApi, RpcError};
+use crate::infrastructure::time::unix_timestamp;
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+
+/// Age after which a price is stale unless configured otherwise
+pub const DEFAULT_MAX_PRICE_AGE: Duration = Duration::from_secs(60);
+
+/// Errors that can occur while reading a price
+#[derive(Debug, Clone, Error)]
+pub enum PriceError {
+    #[error("RPC error: {0}")]
+    Rpc(#[from] RpcError),
+
+    #[error("Oracle error: {0}")]
+    Oracle(#[from] OracleError),
+
+    #[error("Price account {0} not found")]
+    AccountNotFound(Pubkey),
+
+    #[error("Price is {age} s old, more than the {max_age} s allowed")]
+    Stale { age: u64, max_age: u64 },
+
+    #[error("Price has not been fully verified by its oracle")]
+    Unverified,
+}
+
+/// Reads oracle prices and checks they are fresh
+pub struct PriceService {
+    rpc_client: Arc<dyn RpcApi>,
+    max_age: Duration,
+    allow_unverified: bool,
+}
+
+impl PriceService {
+    /// Create a price service
+    pub fn new(rpc_client: impl RpcApi + 'static) -> Self {
+        Self::from_api(Arc::new(rpc_client))
+    }
+
+    /// Create a price service from a shared RPC implementation
+    pub fn from_api(rpc_client: Arc<dyn RpcApi>) -> Self {
+        Self {
+            rpc_client,
+            max_age: DEFAULT_MAX_PRICE_AGE,
+            allow_unverified: false,
+        }
+    }
+
+    /// Refuse prices published longer than `max_age` ago
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    /// Accept partially verified Pyth updates and Switchboard results from
+    /// fewer responses than the feed requires
+    pub fn allow_unverified(mut self, allow: bool) -> Self {
+        self.allow_unverified = allow;
+        self
+    }
+
+    /// The fresh price in the oracle account `feed`
+    pub async fn price(&self, feed: &Pubkey) -> Result<OraclePrice, PriceError> {
+        let account = self.rpc_client.get_account_info(feed).await?;
+        self.check(feed, account.as_ref(), unix_timestamp())
+    }
+
+    /// The prices in `feeds`, read in one request and checked one by one
+    pub async fn prices(
+        &self,
+        feeds: &[Pubkey],
+    ) -> Result<Vec<Result<OraclePrice, PriceError>>, PriceError> {
+        let accounts = self.rpc_client.get_multiple_accounts(feeds).await?;
+        let now = unix_timestamp();
+        Ok(feeds
+            .iter()
+            .zip(accounts)
+            .map(|(feed, account)| self.check(feed, account.as_ref(), now))
+            .collect())
+    }
+
+    /// USD worth of `amount` base units of a token with `decimals` decimals,
+    /// priced by `feed`
+    pub async fn usd_value(
+        &self,
+        amount: u64,
+        decimals: u8,
+        feed: &Pubkey,
+    ) -> Result<f64, PriceError> {
+        Ok(self.price(feed).await?.value_of(amount, decimals))
+    }
+
+    /// Decode `account` and check the price it holds as of `now`
+    fn check(
+        &self,
+        feed: &Pubkey,
+        account: Option<&Account>,
+        now: u64,
+    ) -> Result<OraclePrice, PriceError> {
+        let account = account.ok_or(PriceError::AccountNotFound(*feed))?;
+        let price = decode_price(&account.owner, &account.data)?;
+        let age = price.age(i64::try_from(now).unwrap_or(i64::MAX));
+        let max_age = self.max_age.as_secs();
+        if age > max_age {
+            return Err(PriceError::Stale { age, max_age });
+        }
+        if !price.verified && !self.allow_unverified {
+            return Err(PriceError::Unverified);
+        }
+        Ok(price)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::discriminator::Discriminator;
+    use crate::domain::oracles::{
+        PriceFeedMessage, PythPriceUpdate, VerificationLevel, PYTH_RECEIVER_PROGRAM_ID,
+    };
+    use crate::infrastructure::rpc::mock::MockRpc;
+
+    fn pyth_account(seed: u8, publish_time: i64, level: VerificationLevel) -> Account {
+        let update = PythPriceUpdate {
+            write_authority: Pubkey::new([1; 32]),
+            verification_level: level,
+            price_message: PriceFeedMessage {
+                feed_id: [seed; 32],
+                price: 15_230_000_000,
+                conf: 5_000_000,
+                exponent: -8,
+                publish_time,
+                prev_publish_time: publish_time - 1,
+                ema_price: 15_230_000_000,
+                ema_conf: 5_000_000,
+            },
+            posted_slot: 300,
+        };
+        let mut data = PythPriceUpdate::DISCRIMINATOR.to_vec();
+        data.extend(borsh::to_vec(&update).unwrap());
+        Account {
+            pubkey: Pubkey::new([seed; 32]),
+            lamports: 1_000_000,
+            data,
+            owner: PYTH_RECEIVER_PROGRAM_ID,
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_prices_are_checked_for_staleness() {
+        let now = unix_timestamp() as i64;
+        let rpc = MockRpc::new()
+            .with_account(pyth_account(1, now - 5, VerificationLevel::Full))
+            .with_account(pyth_account(2, now - 600, VerificationLevel::Full))
+            .with_account(pyth_account(
+                3,
+                now,
+                VerificationLevel::Partial { num_signatures: 5 },
+            ));
+        let service = PriceService::new(rpc);
+        let (fresh, stale, partial, missing) = (
+            Pubkey::new([1; 32]),
+            Pubkey::new([2; 32]),
+            Pubkey::new([3; 32]),
+            Pubkey::new([4; 32]),
+        );
+
+        let value = service.usd_value(2_000_000_000, 9, &fresh).await.unwrap();
+        assert!((value - 304.6).abs() < 1e-9);
+        let prices = service
+            .prices(&[fresh, stale, partial, missing])
+            .await
+            .unwrap();
+        assert!(prices[0].is_ok());
+        assert!(matches!(
+            prices[1],
+            Err(PriceError::Stale { max_age: 60, .. })
+        ));
+        assert!(matches!(prices[2], Err(PriceError::Unverified)));
+        assert!(matches!(prices[3], Err(PriceError::AccountNotFound(_))));
+
+        let lenient = service
+            .with_max_age(Duration::from_secs(3_600))
+            .allow_unverified(true);
+        assert!(lenient.price(&stale).await.is_ok());
+        assert!(lenient.price(&partial).await.is_ok());
+    }
+}
@@ -3,30 +3,76 @@
 //! This module provides high-level services for program deployment,
 //! account management, and program interaction using the gloo_solana library.
 
+use crate::domain::programs::loader::{
+    self, LoaderStateError, UpgradeableLoaderState, BPF_LOADER_UPGRADEABLE_ID,
+    BUFFER_METADATA_SIZE, MAX_WRITE_CHUNK, PROGRAMDATA_METADATA_SIZE,
+};
 use crate::domain::programs::{
     AccountMetadata, CreateAccountParams, CreateAccountRequest, InstructionAccount, Program,
     ProgramAccount, ProgramDeployment, ProgramInstruction, ProgramStatus,
 };
 use crate::domain::types::Pubkey;
-use crate::infrastructure::rpc::SolanaRpcClient;
+use crate::infrastructure::rpc::{Account, RpcApi, RpcError};
+use crate::infrastructure::time::unix_timestamp;
+use futures::channel::mpsc;
+use futures::Stream;
 use serde::{Deserialize, Serialize};
 use sha2::Digest;
 use std::collections::HashMap;
 use std::error::Error;
+use std::sync::Arc;
+use thiserror::Error;
+
+pub mod progress;
+
+pub use progress::{ProgressCallback, ProgressEvent};
 
 /// High-level service for managing Solana programs
 #[derive(Clone)]
 pub struct ProgramService {
-    rpc_client: SolanaRpcClient,
+    rpc_client: Arc<dyn RpcApi>,
     deployed_programs: HashMap<Pubkey, Program>,
+    progress: Option<ProgressCallback>,
 }
 
 impl ProgramService {
     /// Create a new program service
-    pub fn new(rpc_client: SolanaRpcClient) -> Self {
+    pub fn new(rpc_client: impl RpcApi + 'static) -> Self {
+        Self::from_api(Arc::new(rpc_client))
+    }
+
+    /// Create a new program service from a shared RPC implementation
+    pub fn from_api(rpc_client: Arc<dyn RpcApi>) -> Self {
         Self {
             rpc_client,
             deployed_programs: HashMap::new(),
+            progress: None,
+        }
+    }
+
+    /// Report the steps of each operation to `callback`
+    pub fn on_progress(
+        mut self,
+        callback: impl Fn(&ProgressEvent) + Send + Sync + 'static,
+    ) -> Self {
+        self.progress = Some(Arc::new(callback));
+        self
+    }
+
+    /// Report the steps of each operation to the returned stream
+    ///
+    /// Replaces any callback set with [`on_progress`](Self::on_progress).
+    pub fn progress_stream(&mut self) -> impl Stream<Item = ProgressEvent> {
+        let (sender, receiver) = mpsc::unbounded();
+        self.progress = Some(Arc::new(move |event: &ProgressEvent| {
+            let _ = sender.unbounded_send(event.clone());
+        }));
+        receiver
+    }
+
+    fn report(&self, event: ProgressEvent) {
+        if let Some(progress) = &self.progress {
+            progress(&event);
         }
     }
 
@@ -36,12 +82,13 @@ impl ProgramService {
         deployment: ProgramDeployment,
     ) -> Result<Pubkey, Box<dyn Error>> {
         let program_id = deployment.program.program_id;
-
-        // Simulate deployment process
-        println!("🚀 Deploying program: {}", deployment.program.name);
-        println!("   Program ID: {}", program_id);
-        println!("   Version: {}", deployment.program.version);
-        println!("   Data size: {} bytes", deployment.program.data.len());
+        let total = deployment.program.data.len();
+        self.report(ProgressEvent::DeployStarted {
+            program_id,
+            name: deployment.program.name.clone(),
+            version: deployment.program.version.clone(),
+            size: total,
+        });
 
         // In a real implementation, this would:
         // 1. Create deployment transaction
@@ -50,28 +97,57 @@ impl ProgramService {
         // 4. Send transaction
         // 5. Wait for confirmation
 
-        // For now, we simulate successful deployment
+        // For now, we simulate writing the buffer in loader-sized chunks
+        for offset in (0..total).step_by(MAX_WRITE_CHUNK) {
+            self.report(ProgressEvent::ChunkWritten {
+                program_id,
+                offset,
+                len: MAX_WRITE_CHUNK.min(total - offset),
+                total,
+            });
+        }
         let mut program = deployment.program;
         program.mark_deployed();
 
         self.deployed_programs.insert(program_id, program.clone());
 
-        println!("✅ Program deployed successfully!");
+        self.report(ProgressEvent::Deployed { program_id });
         Ok(program_id)
     }
 
+    /// Load a workspace program's compiled binary for deployment
+    #[cfg(all(feature = "anchor", not(target_arch = "wasm32")))]
+    pub fn load_workspace_program(
+        program: &crate::infrastructure::anchor_workspace::WorkspaceProgram,
+    ) -> Result<Program, crate::infrastructure::anchor_workspace::WorkspaceError> {
+        let data = std::fs::read(&program.so_path).map_err(|e| {
+            crate::infrastructure::anchor_workspace::WorkspaceError::Io {
+                path: program.so_path.clone(),
+                message: e.to_string(),
+            }
+        })?;
+        Ok(Program::new(
+            program.program_id,
+            program.name.clone(),
+            String::new(),
+            format!("Anchor workspace program {}", program.name),
+            data,
+            None,
+        ))
+    }
+
     /// Create a program account
     pub async fn create_account(
         &mut self,
         request: CreateAccountRequest,
     ) -> Result<Pubkey, Box<dyn Error>> {
         let account_pubkey = request.account.pubkey;
-
-        println!("📝 Creating program account:");
-        println!("   Account: {}", account_pubkey);
-        println!("   Owner: {}", request.program_id);
-        println!("   Size: {} bytes", request.params.space);
-        println!("   Lamports: {}", request.params.lamports);
+        self.report(ProgressEvent::AccountCreationStarted {
+            account: account_pubkey,
+            owner: request.program_id,
+            space: request.params.space,
+            lamports: request.params.lamports,
+        });
 
         // Simulate account creation
         // In a real implementation, this would create and send a transaction
@@ -81,7 +157,9 @@ impl ProgramService {
             program.add_account(request.account.clone());
         }
 
-        println!("✅ Account created successfully!");
+        self.report(ProgressEvent::AccountCreated {
+            account: account_pubkey,
+        });
         Ok(account_pubkey)
     }
 
@@ -90,26 +168,193 @@ impl ProgramService {
         &self,
         instruction: ProgramInstruction,
     ) -> Result<(), Box<dyn Error>> {
-        println!("⚡ Executing program instruction:");
-        println!("   Program: {}", instruction.program_id);
-        println!("   Instruction ID: {}", instruction.instruction_id);
-        println!("   Data: {} bytes", instruction.data.len());
-        println!("   Accounts: {}", instruction.accounts.len());
-
-        for (i, account) in instruction.accounts.iter().enumerate() {
-            println!(
-                "     {}: {} ({:?}, signer: {}, writable: {})",
-                i, account.pubkey, account.role, account.is_signer, account.is_writable
-            );
-        }
+        self.report(ProgressEvent::InstructionStarted {
+            program_id: instruction.program_id,
+            instruction_id: instruction.instruction_id,
+            accounts: instruction.accounts.len(),
+            data_len: instruction.data.len(),
+        });
 
         // Simulate instruction execution
         // In a real implementation, this would create and send a transaction
 
-        println!("✅ Instruction executed successfully!");
+        self.report(ProgressEvent::InstructionExecuted {
+            program_id: instruction.program_id,
+            instruction_id: instruction.instruction_id,
+        });
         Ok(())
     }
 
+    /// Build the instructions that upgrade a deployed program from a buffer
+    ///
+    /// Checks that the upgrade authority matches, that the program was not
+    /// deployed in the current slot and that both the buffer and programdata
+    /// accounts can hold the new bytes. The returned instructions write the
+    /// bytes, hand the buffer to the upgrade authority if needed and upgrade.
+    /// The tracked program is left alone until [`Self::complete_upgrade`].
+    pub async fn upgrade_program(
+        &self,
+        upgrade: &ProgramUpgrade,
+    ) -> Result<Vec<ProgramInstruction>, ProgramLifecycleError> {
+        let (programdata, programdata_account) = self
+            .upgradeable_program(&upgrade.program_id, &upgrade.upgrade_authority)
+            .await?;
+
+        let capacity = programdata_account
+            .data
+            .len()
+            .saturating_sub(PROGRAMDATA_METADATA_SIZE);
+        if capacity < upgrade.data.len() {
+            return Err(ProgramLifecycleError::ProgramDataTooSmall {
+                capacity,
+                required: upgrade.data.len(),
+            });
+        }
+
+        let (buffer_account, buffer_state) = self.loader_account(&upgrade.buffer).await?;
+        if !matches!(buffer_state, UpgradeableLoaderState::Buffer { .. }) {
+            return Err(ProgramLifecycleError::UnexpectedState(upgrade.buffer));
+        }
+        check_authority(&buffer_state, &upgrade.buffer, &upgrade.buffer_authority)?;
+
+        let capacity = buffer_account
+            .data
+            .len()
+            .saturating_sub(BUFFER_METADATA_SIZE);
+        if capacity < upgrade.data.len() {
+            return Err(ProgramLifecycleError::BufferTooSmall {
+                capacity,
+                required: upgrade.data.len(),
+            });
+        }
+        check_recipient(&upgrade.spill, &upgrade.buffer)?;
+
+        let mut instructions =
+            loader::write_chunks(upgrade.buffer, upgrade.buffer_authority, &upgrade.data);
+        if upgrade.buffer_authority != upgrade.upgrade_authority {
+            instructions.push(loader::set_buffer_authority(
+                upgrade.buffer,
+                upgrade.buffer_authority,
+                upgrade.upgrade_authority,
+            ));
+        }
+        instructions.push(loader::upgrade(
+            upgrade.program_id,
+            programdata,
+            upgrade.buffer,
+            upgrade.upgrade_authority,
+            upgrade.spill,
+        ));
+
+        Ok(instructions)
+    }
+
+    /// Record the new bytes of a tracked program once its upgrade is confirmed
+    pub fn complete_upgrade(&mut self, upgrade: ProgramUpgrade) -> bool {
+        match self.deployed_programs.get_mut(&upgrade.program_id) {
+            Some(program) if program.status != ProgramStatus::Closed => {
+                program.data = upgrade.data;
+                program.mark_deployed();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Build the instruction that closes a program and reclaims its rent
+    ///
+    /// A closed program can never be redeployed at the same address. The
+    /// tracked program is left alone until [`Self::complete_close`].
+    pub async fn close_program(
+        &self,
+        program_id: &Pubkey,
+        authority: &Pubkey,
+        recipient: &Pubkey,
+    ) -> Result<ProgramInstruction, ProgramLifecycleError> {
+        let (programdata, _) = self.upgradeable_program(program_id, authority).await?;
+        check_recipient(recipient, &programdata)?;
+
+        Ok(loader::close_program(
+            *program_id,
+            programdata,
+            *authority,
+            *recipient,
+        ))
+    }
+
+    /// Mark a tracked program as closed once its close is confirmed
+    pub fn complete_close(&mut self, program_id: &Pubkey) -> bool {
+        match self.deployed_programs.get_mut(program_id) {
+            Some(program) if program.status != ProgramStatus::Closed => {
+                program.mark_closed();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Build the instruction that closes a buffer and reclaims its rent
+    pub async fn close_buffer(
+        &self,
+        buffer: &Pubkey,
+        authority: &Pubkey,
+        recipient: &Pubkey,
+    ) -> Result<ProgramInstruction, ProgramLifecycleError> {
+        let (_, state) = self.loader_account(buffer).await?;
+        if !matches!(state, UpgradeableLoaderState::Buffer { .. }) {
+            return Err(ProgramLifecycleError::UnexpectedState(*buffer));
+        }
+        check_authority(&state, buffer, authority)?;
+        check_recipient(recipient, buffer)?;
+
+        Ok(loader::close_buffer(*buffer, *authority, *recipient))
+    }
+
+    /// Resolve a program's programdata account and verify it may be modified
+    async fn upgradeable_program(
+        &self,
+        program_id: &Pubkey,
+        authority: &Pubkey,
+    ) -> Result<(Pubkey, Account), ProgramLifecycleError> {
+        let programdata = match self.loader_account(program_id).await?.1 {
+            UpgradeableLoaderState::Program {
+                programdata_address,
+            } => programdata_address,
+            _ => return Err(ProgramLifecycleError::UnexpectedState(*program_id)),
+        };
+
+        let (account, state) = self.loader_account(&programdata).await?;
+        let slot = match state {
+            UpgradeableLoaderState::ProgramData { slot, .. } => slot,
+            _ => return Err(ProgramLifecycleError::UnexpectedState(programdata)),
+        };
+        check_authority(&state, program_id, authority)?;
+
+        let current_slot = self.rpc_client.get_slot().await?;
+        if slot >= current_slot {
+            return Err(ProgramLifecycleError::DeployedThisSlot { slot });
+        }
+
+        Ok((programdata, account))
+    }
+
+    /// Fetch an account owned by the upgradeable loader and decode its state
+    async fn loader_account(
+        &self,
+        pubkey: &Pubkey,
+    ) -> Result<(Account, UpgradeableLoaderState), ProgramLifecycleError> {
+        let account = self
+            .rpc_client
+            .get_account_info(pubkey)
+            .await?
+            .ok_or(ProgramLifecycleError::AccountNotFound(*pubkey))?;
+        if account.owner != BPF_LOADER_UPGRADEABLE_ID {
+            return Err(ProgramLifecycleError::NotUpgradeable(*pubkey));
+        }
+        let state = UpgradeableLoaderState::from_account_data(&account.data)?;
+        Ok((account, state))
+    }
+
     /// Get program information
     pub fn get_program(&self, program_id: &Pubkey) -> Option<&Program> {
         self.deployed_programs.get(program_id)
@@ -148,6 +393,90 @@ impl ProgramService {
     }
 }
 
+/// Request to upgrade a deployed program from a buffer account
+#[derive(Debug, Clone)]
+pub struct ProgramUpgrade {
+    /// Program to upgrade
+    pub program_id: Pubkey,
+    /// Initialized buffer that receives the new bytes
+    pub buffer: Pubkey,
+    /// Current authority of the buffer
+    pub buffer_authority: Pubkey,
+    /// Upgrade authority of the program
+    pub upgrade_authority: Pubkey,
+    /// Account that receives the buffer's leftover lamports
+    pub spill: Pubkey,
+    /// New program bytes
+    pub data: Vec<u8>,
+}
+
+/// Errors that can occur while upgrading or closing programs
+#[derive(Debug, Error)]
+pub enum ProgramLifecycleError {
+    #[error("RPC error: {0}")]
+    Rpc(#[from] RpcError),
+
+    #[error("Account {0} not found")]
+    AccountNotFound(Pubkey),
+
+    #[error("Account {0} is not owned by the upgradeable loader")]
+    NotUpgradeable(Pubkey),
+
+    #[error("Account {0} is not in the expected loader state")]
+    UnexpectedState(Pubkey),
+
+    #[error("Invalid loader account: {0}")]
+    InvalidState(#[from] LoaderStateError),
+
+    #[error("Account {0} is immutable")]
+    Immutable(Pubkey),
+
+    #[error("Authority mismatch for {account}: expected {expected}, got {actual}")]
+    AuthorityMismatch {
+        account: Pubkey,
+        expected: Pubkey,
+        actual: Pubkey,
+    },
+
+    #[error("Program was deployed in slot {slot}; wait for a later slot")]
+    DeployedThisSlot { slot: u64 },
+
+    #[error("Buffer holds {capacity} bytes but {required} are required")]
+    BufferTooSmall { capacity: usize, required: usize },
+
+    #[error(
+        "Program data holds {capacity} bytes but {required} are required; extend the program first"
+    )]
+    ProgramDataTooSmall { capacity: usize, required: usize },
+
+    #[error("Recipient {0} cannot be the account being closed")]
+    InvalidRecipient(Pubkey),
+}
+
+fn check_authority(
+    state: &UpgradeableLoaderState,
+    account: &Pubkey,
+    authority: &Pubkey,
+) -> Result<(), ProgramLifecycleError> {
+    match state.authority() {
+        None => Err(ProgramLifecycleError::Immutable(*account)),
+        Some(expected) if expected != *authority => Err(ProgramLifecycleError::AuthorityMismatch {
+            account: *account,
+            expected,
+            actual: *authority,
+        }),
+        Some(_) => Ok(()),
+    }
+}
+
+fn check_recipient(recipient: &Pubkey, closed: &Pubkey) -> Result<(), ProgramLifecycleError> {
+    if recipient == closed {
+        Err(ProgramLifecycleError::InvalidRecipient(*recipient))
+    } else {
+        Ok(())
+    }
+}
+
 /// Statistics for a deployed program
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProgramStats {
@@ -172,6 +501,11 @@ impl AccountCreationService {
         Self { program_service }
     }
 
+    /// The program service accounts are created through
+    pub fn program_service(&self) -> &ProgramService {
+        &self.program_service
+    }
+
     /// Create a hello world program account
     pub fn create_hello_account(
         &self,
@@ -179,18 +513,15 @@ impl AccountCreationService {
         user_name: String,
         message: String,
         owner: Pubkey,
-    ) -> CreateAccountRequest {
+    ) -> Result<CreateAccountRequest, serde_json::Error> {
         let account_data = HelloAccountData {
             name: user_name.clone(),
             message,
             greeting_count: 0,
-            created_at: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
+            created_at: unix_timestamp(),
         };
 
-        let serialized_data = serde_json::to_vec(&account_data).unwrap();
+        let serialized_data = serde_json::to_vec(&account_data)?;
         let account_pubkey = self.generate_pda(&program_id, &user_name);
 
         let account = ProgramAccount::new(
@@ -215,7 +546,9 @@ impl AccountCreationService {
             seeds: Some(vec![user_name.as_bytes().to_vec()]),
         };
 
-        CreateAccountRequest::new(account, program_id, owner, params)
+        Ok(CreateAccountRequest::new(
+            account, program_id, owner, params,
+        ))
     }
 
     /// Create a counter program account
@@ -225,17 +558,14 @@ impl AccountCreationService {
         counter_name: String,
         initial_value: u64,
         owner: Pubkey,
-    ) -> CreateAccountRequest {
+    ) -> Result<CreateAccountRequest, serde_json::Error> {
         let account_data = CounterAccountData {
             name: counter_name.clone(),
             value: initial_value,
-            last_updated: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
+            last_updated: unix_timestamp(),
         };
 
-        let serialized_data = serde_json::to_vec(&account_data).unwrap();
+        let serialized_data = serde_json::to_vec(&account_data)?;
         let account_pubkey = self.generate_pda(&program_id, &counter_name);
 
         let account = ProgramAccount::new(
@@ -260,7 +590,9 @@ impl AccountCreationService {
             seeds: Some(vec![counter_name.as_bytes().to_vec()]),
         };
 
-        CreateAccountRequest::new(account, program_id, owner, params)
+        Ok(CreateAccountRequest::new(
+            account, program_id, owner, params,
+        ))
     }
 
     /// Generate a Program Derived Address (PDA)
@@ -308,9 +640,13 @@ impl InstructionBuilder {
     }
 
     /// Build a hello world instruction
-    pub fn hello_world(&self, user_account: Pubkey, payer: Pubkey) -> ProgramInstruction {
+    pub fn hello_world(
+        &self,
+        user_account: Pubkey,
+        payer: Pubkey,
+    ) -> Result<ProgramInstruction, serde_json::Error> {
         let instruction_data = HelloInstruction::Greet;
-        let serialized = serde_json::to_vec(&instruction_data).unwrap();
+        let serialized = serde_json::to_vec(&instruction_data)?;
 
         let accounts = vec![
             InstructionAccount::program(self.program_id),
@@ -318,13 +654,22 @@ impl InstructionBuilder {
             InstructionAccount::signer(payer, true),
         ];
 
-        ProgramInstruction::new(self.program_id, accounts, serialized, 0)
+        Ok(ProgramInstruction::new(
+            self.program_id,
+            accounts,
+            serialized,
+            0,
+        ))
     }
 
     /// Build a counter increment instruction
-    pub fn increment_counter(&self, counter_account: Pubkey, payer: Pubkey) -> ProgramInstruction {
+    pub fn increment_counter(
+        &self,
+        counter_account: Pubkey,
+        payer: Pubkey,
+    ) -> Result<ProgramInstruction, serde_json::Error> {
         let instruction_data = CounterInstruction::Increment;
-        let serialized = serde_json::to_vec(&instruction_data).unwrap();
+        let serialized = serde_json::to_vec(&instruction_data)?;
 
         let accounts = vec![
             InstructionAccount::program(self.program_id),
@@ -332,7 +677,12 @@ impl InstructionBuilder {
             InstructionAccount::signer(payer, true),
         ];
 
-        ProgramInstruction::new(self.program_id, accounts, serialized, 1)
+        Ok(ProgramInstruction::new(
+            self.program_id,
+            accounts,
+            serialized,
+            1,
+        ))
     }
 
     /// Build a counter set instruction
@@ -341,9 +691,9 @@ impl InstructionBuilder {
         counter_account: Pubkey,
         value: u64,
         payer: Pubkey,
-    ) -> ProgramInstruction {
+    ) -> Result<ProgramInstruction, serde_json::Error> {
         let instruction_data = CounterInstruction::Set { value };
-        let serialized = serde_json::to_vec(&instruction_data).unwrap();
+        let serialized = serde_json::to_vec(&instruction_data)?;
 
         let accounts = vec![
             InstructionAccount::program(self.program_id),
@@ -351,7 +701,12 @@ impl InstructionBuilder {
             InstructionAccount::signer(payer, true),
         ];
 
-        ProgramInstruction::new(self.program_id, accounts, serialized, 2)
+        Ok(ProgramInstruction::new(
+            self.program_id,
+            accounts,
+            serialized,
+            2,
+        ))
     }
 }
 
@@ -373,33 +728,164 @@ pub enum CounterInstruction {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::types::constants::SYSTEM_PROGRAM_ID;
+    use crate::domain::programs::DeploymentConfig;
+    use crate::infrastructure::rpc::mock::MockRpc;
+
+    const PROGRAM: Pubkey = Pubkey::new([1; 32]);
+    const PROGRAMDATA: Pubkey = Pubkey::new([2; 32]);
+    const BUFFER: Pubkey = Pubkey::new([3; 32]);
+    const AUTHORITY: Pubkey = Pubkey::new([4; 32]);
+    const DEPLOYER: Pubkey = Pubkey::new([5; 32]);
+
+    /// A node at `slot` with a program deployed at slot 10 with
+    /// `upgrade_authority`, and a 2 KiB buffer owned by `DEPLOYER`
+    fn loader_rpc(upgrade_authority: Option<Pubkey>, slot: u64) -> MockRpc {
+        let mut program = vec![2, 0, 0, 0];
+        program.extend_from_slice(PROGRAMDATA.as_bytes());
+
+        let mut programdata = vec![3, 0, 0, 0];
+        programdata.extend_from_slice(&10u64.to_le_bytes());
+        match upgrade_authority {
+            Some(authority) => {
+                programdata.push(1);
+                programdata.extend_from_slice(authority.as_bytes());
+            }
+            None => programdata.extend_from_slice(&[0; 33]),
+        }
+        programdata.resize(PROGRAMDATA_METADATA_SIZE + 4096, 0);
+
+        let mut buffer = vec![1, 0, 0, 0, 1];
+        buffer.extend_from_slice(DEPLOYER.as_bytes());
+        buffer.resize(BUFFER_METADATA_SIZE + 2048, 0);
+
+        [
+            (PROGRAM, program),
+            (PROGRAMDATA, programdata),
+            (BUFFER, buffer),
+        ]
+        .into_iter()
+        .fold(MockRpc::new().with_slot(slot), |rpc, (pubkey, data)| {
+            rpc.with_account(Account {
+                pubkey,
+                lamports: 1_000_000,
+                data,
+                owner: BPF_LOADER_UPGRADEABLE_ID,
+                executable: pubkey == PROGRAM,
+                rent_epoch: 0,
+            })
+        })
+    }
+
+    fn upgrade_request(data_len: usize) -> ProgramUpgrade {
+        ProgramUpgrade {
+            program_id: PROGRAM,
+            buffer: BUFFER,
+            buffer_authority: DEPLOYER,
+            upgrade_authority: AUTHORITY,
+            spill: DEPLOYER,
+            data: vec![7; data_len],
+        }
+    }
 
     #[test]
     fn test_program_service_creation() {
-        let rpc_client =
-            crate::infrastructure::rpc::RpcClientBuilder::new("http://localhost:8899").build();
+        let rpc_client = crate::infrastructure::rpc::RpcClientBuilder::new("http://localhost:8899")
+            .build()
+            .unwrap();
         let service = ProgramService::new(rpc_client);
 
         assert_eq!(service.deployed_programs.len(), 0);
     }
 
+    #[tokio::test]
+    async fn test_deploy_reports_progress() {
+        use futures::StreamExt;
+        use std::sync::Mutex;
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let recorded = events.clone();
+        let mut service = ProgramService::new(MockRpc::new())
+            .on_progress(move |event| recorded.lock().unwrap().push(event.clone()));
+
+        let program = Program::new(
+            PROGRAM,
+            "counter".to_string(),
+            "1.0.0".to_string(),
+            String::new(),
+            vec![0; MAX_WRITE_CHUNK + 100],
+            None,
+        );
+        let config = DeploymentConfig {
+            skip_preflight: false,
+            max_compute_units: None,
+            priority_fee: None,
+            commitment: "confirmed".to_string(),
+        };
+        let deployment = ProgramDeployment::new(program, config);
+        service.deploy_program(deployment).await.unwrap();
+
+        let events = events.lock().unwrap().clone();
+        assert_eq!(events.len(), 4);
+        assert!(
+            matches!(events[0], ProgressEvent::DeployStarted { size, .. } if size == MAX_WRITE_CHUNK + 100)
+        );
+        assert_eq!(
+            events[2],
+            ProgressEvent::ChunkWritten {
+                program_id: PROGRAM,
+                offset: MAX_WRITE_CHUNK,
+                len: 100,
+                total: MAX_WRITE_CHUNK + 100
+            }
+        );
+        assert_eq!(
+            events[2].to_string(),
+            format!(
+                "Wrote {} of {} bytes",
+                MAX_WRITE_CHUNK + 100,
+                MAX_WRITE_CHUNK + 100
+            )
+        );
+        assert_eq!(
+            events[3],
+            ProgressEvent::Deployed {
+                program_id: PROGRAM
+            }
+        );
+
+        let mut progress = service.progress_stream();
+        let instruction = InstructionBuilder::new(PROGRAM)
+            .hello_world(AUTHORITY, DEPLOYER)
+            .unwrap();
+        service.execute_instruction(instruction).await.unwrap();
+        assert!(matches!(
+            progress.next().await,
+            Some(ProgressEvent::InstructionStarted {
+                program_id: PROGRAM,
+                ..
+            })
+        ));
+    }
+
     #[test]
     fn test_hello_account_creation() {
-        let rpc_client =
-            crate::infrastructure::rpc::RpcClientBuilder::new("http://localhost:8899").build();
+        let rpc_client = crate::infrastructure::rpc::RpcClientBuilder::new("http://localhost:8899")
+            .build()
+            .unwrap();
         let program_service = ProgramService::new(rpc_client);
         let account_service = AccountCreationService::new(program_service);
 
         let program_id = Pubkey::new([1; 32]);
         let owner = Pubkey::new([2; 32]);
 
-        let request = account_service.create_hello_account(
-            program_id,
-            "test_user".to_string(),
-            "Hello World!".to_string(),
-            owner,
-        );
+        let request = account_service
+            .create_hello_account(
+                program_id,
+                "test_user".to_string(),
+                "Hello World!".to_string(),
+                owner,
+            )
+            .unwrap();
 
         assert_eq!(request.account.owner, program_id);
         assert_eq!(request.payer, owner);
@@ -413,7 +899,7 @@ mod tests {
         let payer = Pubkey::new([3; 32]);
 
         let builder = InstructionBuilder::new(program_id);
-        let instruction = builder.hello_world(user_account, payer);
+        let instruction = builder.hello_world(user_account, payer).unwrap();
 
         assert_eq!(instruction.program_id, program_id);
         assert_eq!(instruction.accounts.len(), 3);
@@ -427,10 +913,134 @@ mod tests {
         let payer = Pubkey::new([3; 32]);
 
         let builder = InstructionBuilder::new(program_id);
-        let instruction = builder.increment_counter(counter_account, payer);
+        let instruction = builder.increment_counter(counter_account, payer).unwrap();
 
         assert_eq!(instruction.program_id, program_id);
         assert_eq!(instruction.accounts.len(), 3);
         assert_eq!(instruction.instruction_id, 1);
     }
+
+    #[tokio::test]
+    async fn test_upgrade_program_builds_lifecycle_instructions() {
+        let mut service = ProgramService::new(loader_rpc(Some(AUTHORITY), 20));
+        let mut program = Program::new(
+            PROGRAM,
+            "Upgradeable".to_string(),
+            "1.0.0".to_string(),
+            String::new(),
+            vec![1],
+            Some(AUTHORITY),
+        );
+        program.mark_deployed();
+        service.deployed_programs.insert(PROGRAM, program);
+
+        let upgrade = upgrade_request(1500);
+        let instructions = service.upgrade_program(&upgrade).await.unwrap();
+
+        // Two writes, a buffer authority handover, then the upgrade itself
+        assert_eq!(instructions.len(), 4);
+        assert_eq!(instructions[0].instruction_id, 1);
+        assert_eq!(instructions[2].instruction_id, 4);
+        assert_eq!(instructions[2].accounts[2].pubkey, AUTHORITY);
+        assert_eq!(instructions[3].instruction_id, 3);
+        assert_eq!(instructions[3].accounts[0].pubkey, PROGRAMDATA);
+
+        // Nothing changes until the upgrade is confirmed
+        assert_eq!(service.get_program(&PROGRAM).unwrap().data, vec![1]);
+        assert!(service.complete_upgrade(upgrade));
+        let program = service.get_program(&PROGRAM).unwrap();
+        assert_eq!(program.status, ProgramStatus::Deployed);
+        assert_eq!(program.data.len(), 1500);
+    }
+
+    #[tokio::test]
+    async fn test_upgrade_program_safety_checks() {
+        let service = ProgramService::new(loader_rpc(Some(AUTHORITY), 20));
+        let mut request = upgrade_request(16);
+        request.upgrade_authority = DEPLOYER;
+        assert!(matches!(
+            service.upgrade_program(&request).await,
+            Err(ProgramLifecycleError::AuthorityMismatch { expected, .. }) if expected == AUTHORITY
+        ));
+
+        assert!(matches!(
+            service.upgrade_program(&upgrade_request(4096 + 1)).await,
+            Err(ProgramLifecycleError::ProgramDataTooSmall { .. })
+        ));
+        assert!(matches!(
+            service.upgrade_program(&upgrade_request(2048 + 1)).await,
+            Err(ProgramLifecycleError::BufferTooSmall { .. })
+        ));
+
+        let service = ProgramService::new(loader_rpc(Some(AUTHORITY), 10));
+        assert!(matches!(
+            service.upgrade_program(&upgrade_request(16)).await,
+            Err(ProgramLifecycleError::DeployedThisSlot { slot: 10 })
+        ));
+
+        let service = ProgramService::new(loader_rpc(None, 20));
+        assert!(matches!(
+            service.upgrade_program(&upgrade_request(16)).await,
+            Err(ProgramLifecycleError::Immutable(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_close_program_and_buffer() {
+        let mut service = ProgramService::new(loader_rpc(Some(AUTHORITY), 20));
+        let mut program = Program::new(
+            PROGRAM,
+            "Closable".to_string(),
+            "1.0.0".to_string(),
+            String::new(),
+            vec![1],
+            Some(AUTHORITY),
+        );
+        program.mark_deployed();
+        service.deployed_programs.insert(PROGRAM, program);
+
+        let instruction = service
+            .close_program(&PROGRAM, &AUTHORITY, &DEPLOYER)
+            .await
+            .unwrap();
+        assert_eq!(instruction.instruction_id, 5);
+        assert_eq!(instruction.accounts.len(), 4);
+        assert_eq!(
+            service.get_program(&PROGRAM).unwrap().status,
+            ProgramStatus::Deployed
+        );
+        assert!(service.complete_close(&PROGRAM));
+        assert!(!service.complete_close(&PROGRAM));
+        assert_eq!(
+            service.get_program(&PROGRAM).unwrap().status,
+            ProgramStatus::Closed
+        );
+
+        assert!(matches!(
+            service
+                .close_program(&PROGRAM, &AUTHORITY, &PROGRAMDATA)
+                .await,
+            Err(ProgramLifecycleError::InvalidRecipient(_))
+        ));
+        assert!(matches!(
+            service.close_program(&BUFFER, &AUTHORITY, &DEPLOYER).await,
+            Err(ProgramLifecycleError::UnexpectedState(_))
+        ));
+
+        let instruction = service
+            .close_buffer(&BUFFER, &DEPLOYER, &AUTHORITY)
+            .await
+            .unwrap();
+        assert_eq!(instruction.accounts.len(), 3);
+        assert!(matches!(
+            service.close_buffer(&BUFFER, &AUTHORITY, &DEPLOYER).await,
+            Err(ProgramLifecycleError::AuthorityMismatch { .. })
+        ));
+        assert!(matches!(
+            service
+                .close_buffer(&Pubkey::new([9; 32]), &DEPLOYER, &AUTHORITY)
+                .await,
+            Err(ProgramLifecycleError::AccountNotFound(_))
+        ));
+    }
 }
@@ -0,0 +1,103 @@
+//! Progress reporting for program services
+//!
+//! [`ProgramService`](super::ProgramService) reports each step of a
+//! deployment, account creation or instruction as a [`ProgressEvent`] instead
+//! of printing it, so browser UIs can render a progress bar and CLIs can print
+//! the event's `Display` form, while libraries stay silent.
+
+use crate::domain::types::Pubkey;
+use std::fmt;
+use std::sync::Arc;
+
+/// Receives the progress events of a service
+pub type ProgressCallback = Arc<dyn Fn(&ProgressEvent) + Send + Sync>;
+
+/// A step of a program service operation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProgressEvent {
+    DeployStarted {
+        program_id: Pubkey,
+        name: String,
+        version: String,
+        size: usize,
+    },
+    /// Program bytes from `offset` were written; `total` is the program size
+    ChunkWritten {
+        program_id: Pubkey,
+        offset: usize,
+        len: usize,
+        total: usize,
+    },
+    Deployed {
+        program_id: Pubkey,
+    },
+    AccountCreationStarted {
+        account: Pubkey,
+        owner: Pubkey,
+        space: u64,
+        lamports: u64,
+    },
+    AccountCreated {
+        account: Pubkey,
+    },
+    InstructionStarted {
+        program_id: Pubkey,
+        instruction_id: u8,
+        accounts: usize,
+        data_len: usize,
+    },
+    InstructionExecuted {
+        program_id: Pubkey,
+        instruction_id: u8,
+    },
+}
+
+impl fmt::Display for ProgressEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DeployStarted {
+                program_id,
+                name,
+                version,
+                size,
+            } => write!(
+                f,
+                "Deploying {} {} ({} bytes) to {}",
+                name, version, size, program_id
+            ),
+            Self::ChunkWritten {
+                offset, len, total, ..
+            } => write!(f, "Wrote {} of {} bytes", offset + len, total),
+            Self::Deployed { program_id } => write!(f, "Deployed {}", program_id),
+            Self::AccountCreationStarted {
+                account,
+                owner,
+                space,
+                lamports,
+            } => write!(
+                f,
+                "Creating account {} owned by {} ({} bytes, {} lamports)",
+                account, owner, space, lamports
+            ),
+            Self::AccountCreated { account } => write!(f, "Created account {}", account),
+            Self::InstructionStarted {
+                program_id,
+                instruction_id,
+                accounts,
+                data_len,
+            } => write!(
+                f,
+                "Executing instruction {} of {} ({} accounts, {} bytes of data)",
+                instruction_id, program_id, accounts, data_len
+            ),
+            Self::InstructionExecuted {
+                program_id,
+                instruction_id,
+            } => write!(
+                f,
+                "Executed instruction {} of {}",
+                instruction_id, program_id
+            ),
+        }
+    }
+}
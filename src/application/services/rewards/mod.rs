@@ -0,0 +1,163 @@
+//! Staking rewards history
+//!
+//! [`RewardsService`] collects the inflation rewards of stake accounts over a
+//! range of epochs with one `getInflationReward` request per epoch, however
+//! many accounts are tracked, and sums them into the per-account histories
+//! staking dashboards chart.
+
+use crate::domain::types::Pubkey;
+use crate::infrastructure::rpc::{InflationReward, RpcApi, RpcError};
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+
+/// Rewards of one stake account over a range of epochs
+#[derive(Debug, Clone, PartialEq)]
+pub struct RewardsHistory {
+    pub address: Pubkey,
+    /// Rewards in epoch order
+    pub rewards: Vec<InflationReward>,
+    /// Epochs in the range that credited nothing, such as while activating
+    pub missing_epochs: Vec<u64>,
+}
+
+impl RewardsHistory {
+    /// Sum of the rewards in lamports
+    pub fn total(&self) -> u64 {
+        self.rewards.iter().map(|reward| reward.amount).sum()
+    }
+
+    /// Mean reward rate per rewarded epoch, 0 without rewards
+    pub fn average_rate(&self) -> f64 {
+        if self.rewards.is_empty() {
+            return 0.0;
+        }
+        let sum: f64 = self.rewards.iter().map(InflationReward::rate).sum();
+        sum / self.rewards.len() as f64
+    }
+
+    /// The latest reward, if any
+    pub fn latest(&self) -> Option<&InflationReward> {
+        self.rewards.last()
+    }
+}
+
+/// Service aggregating staking rewards over epochs
+pub struct RewardsService {
+    rpc_client: Arc<dyn RpcApi>,
+}
+
+impl RewardsService {
+    /// Create a rewards service
+    pub fn new(rpc_client: impl RpcApi + 'static) -> Self {
+        Self::from_api(Arc::new(rpc_client))
+    }
+
+    /// Create a rewards service from a shared RPC implementation
+    pub fn from_api(rpc_client: Arc<dyn RpcApi>) -> Self {
+        Self { rpc_client }
+    }
+
+    /// Rewards of `stake_account` for every epoch in `epochs`
+    pub async fn history(
+        &self,
+        stake_account: &Pubkey,
+        epochs: RangeInclusive<u64>,
+    ) -> Result<RewardsHistory, RpcError> {
+        let mut histories = self.histories(&[*stake_account], epochs).await?;
+        Ok(histories.remove(0))
+    }
+
+    /// Rewards of each of `stake_accounts` for every epoch in `epochs`
+    ///
+    /// Histories are returned in the order of `stake_accounts`. Nodes only
+    /// answer for completed epochs still in their ledger, and fail otherwise.
+    pub async fn histories(
+        &self,
+        stake_accounts: &[Pubkey],
+        epochs: RangeInclusive<u64>,
+    ) -> Result<Vec<RewardsHistory>, RpcError> {
+        let mut histories: Vec<RewardsHistory> = stake_accounts
+            .iter()
+            .map(|address| RewardsHistory {
+                address: *address,
+                rewards: Vec::new(),
+                missing_epochs: Vec::new(),
+            })
+            .collect();
+        if stake_accounts.is_empty() {
+            return Ok(histories);
+        }
+
+        for epoch in epochs {
+            let rewards = self
+                .rpc_client
+                .get_inflation_reward(stake_accounts, Some(epoch))
+                .await?;
+            for (history, reward) in histories.iter_mut().zip(rewards) {
+                match reward {
+                    Some(reward) => history.rewards.push(reward),
+                    None => history.missing_epochs.push(epoch),
+                }
+            }
+        }
+        Ok(histories)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::rpc::mock::MockRpc;
+    use serde_json::json;
+
+    fn reward(epoch: u64, amount: u64, post_balance: u64) -> InflationReward {
+        InflationReward {
+            epoch,
+            effective_slot: epoch * 432_000,
+            amount,
+            post_balance,
+            commission: Some(5),
+        }
+    }
+
+    #[test]
+    fn test_parse_inflation_reward() {
+        let parsed: Vec<Option<InflationReward>> = serde_json::from_value(json!([
+            {
+                "amount": 2500,
+                "effectiveSlot": 224,
+                "epoch": 2,
+                "postBalance": 499999442500u64
+            },
+            null
+        ]))
+        .unwrap();
+        assert_eq!(parsed[0].as_ref().unwrap().effective_slot, 224);
+        assert_eq!(parsed[0].as_ref().unwrap().commission, None);
+        assert_eq!(parsed[1], None);
+    }
+
+    #[tokio::test]
+    async fn test_rewards_history() {
+        let stake = Pubkey::new([1; 32]);
+        let other = Pubkey::new([2; 32]);
+        let rpc = MockRpc::new()
+            .with_inflation_reward(stake, reward(500, 1_000, 1_001_000))
+            .with_inflation_reward(stake, reward(502, 2_000, 1_003_000))
+            .with_inflation_reward(other, reward(501, 50, 10_050));
+
+        let service = RewardsService::new(rpc);
+        let history = service.history(&stake, 500..=502).await.unwrap();
+        assert_eq!(history.total(), 3_000);
+        assert_eq!(history.missing_epochs, [501]);
+        assert_eq!(history.latest().unwrap().epoch, 502);
+        assert_eq!(history.rewards[0].pre_balance(), 1_000_000);
+        assert!((history.rewards[0].rate() - 0.001).abs() < 1e-12);
+
+        let histories = service.histories(&[other, stake], 501..=501).await.unwrap();
+        assert_eq!(histories[0].total(), 50);
+        assert!((histories[0].average_rate() - 0.005).abs() < 1e-12);
+        assert_eq!(histories[1].missing_epochs, [501]);
+        assert_eq!(histories[1].average_rate(), 0.0);
+    }
+}
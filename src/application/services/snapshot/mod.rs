@@ -0,0 +1,341 @@
+//! Account state snapshots for regression tests
+//!
+//! [`SnapshotService::capture`] reads a set of accounts at one slot and keeps
+//! their decoded state. A later snapshot, or one saved as JSON from an earlier
+//! run, can then be compared field by field with [`StateSnapshot::assert_matches`],
+//! ignoring fields such as timestamps that are expected to move. On surfpool a
+//! captured snapshot can also be written back, rewinding the accounts.
+
+use crate::domain::diff::{diff_fields, FieldChange};
+use crate::domain::discriminator::Discriminator;
+use crate::domain::types::constants::SYSTEM_PROGRAM_ID;
+use crate::domain::types::Pubkey;
+use crate::infrastructure::rpc::{
+    Account, AccountUpdate, Cheatcodes, RpcApi, RpcError, MAX_ACCOUNTS_PER_REQUEST,
+};
+use borsh::BorshDeserialize;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use std::sync::Arc;
+use thiserror::Error;
+
+/// Errors that can occur while taking or comparing snapshots
+#[derive(Debug, Clone, Error)]
+pub enum SnapshotError {
+    #[error("RPC error: {0}")]
+    Rpc(#[from] RpcError),
+
+    #[error("Failed to decode account {account}: {message}")]
+    Decode { account: Pubkey, message: String },
+
+    #[error("Snapshot mismatch:\n{}", describe_changes(.0))]
+    Mismatch(Vec<(Pubkey, FieldChange)>),
+}
+
+/// Decoded state of a set of accounts at one slot
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StateSnapshot {
+    /// Slot the accounts were read at
+    pub slot: u64,
+    /// Decoded state of each account in capture order, `None` where it did
+    /// not exist
+    pub states: Vec<(Pubkey, Option<Value>)>,
+    /// Raw accounts for [`SnapshotService::restore`], not saved with the rest
+    #[serde(skip)]
+    accounts: Vec<(Pubkey, Option<Account>)>,
+}
+
+impl StateSnapshot {
+    /// Fields that differ in `later`, for every account in either snapshot
+    ///
+    /// An account that does not exist compares as `null`.
+    pub fn changes(&self, later: &StateSnapshot) -> Vec<(Pubkey, FieldChange)> {
+        let mut pubkeys: Vec<&Pubkey> = self.states.iter().map(|(pubkey, _)| pubkey).collect();
+        for (pubkey, _) in &later.states {
+            if !pubkeys.contains(&pubkey) {
+                pubkeys.push(pubkey);
+            }
+        }
+
+        let mut changes = Vec::new();
+        for pubkey in pubkeys {
+            let state =
+                |snapshot: &StateSnapshot| snapshot.state(pubkey).cloned().unwrap_or(Value::Null);
+            // Values always serialize to JSON
+            let fields = diff_fields(&state(self), &state(later)).unwrap_or_default();
+            changes.extend(fields.into_iter().map(|change| (*pubkey, change)));
+        }
+        changes
+    }
+
+    /// Decoded state of `pubkey`, `None` if it did not exist or was not captured
+    pub fn state(&self, pubkey: &Pubkey) -> Option<&Value> {
+        self.states
+            .iter()
+            .find(|(key, _)| key == pubkey)
+            .and_then(|(_, state)| state.as_ref())
+    }
+
+    /// Check that `later` holds the same state, apart from fields in `except`
+    ///
+    /// `except` lists field paths as reported by [`FieldChange`], such as
+    /// `"last_updated"` or `"history[0]"`; a path also covers the fields
+    /// nested under it.
+    pub fn assert_matches(
+        &self,
+        later: &StateSnapshot,
+        except: &[&str],
+    ) -> Result<(), SnapshotError> {
+        let changes: Vec<_> = self
+            .changes(later)
+            .into_iter()
+            .filter(|(_, change)| !except.iter().any(|path| covers(path, &change.path)))
+            .collect();
+        if changes.is_empty() {
+            Ok(())
+        } else {
+            Err(SnapshotError::Mismatch(changes))
+        }
+    }
+}
+
+/// Service capturing and restoring account snapshots
+pub struct SnapshotService {
+    rpc_client: Arc<dyn RpcApi>,
+}
+
+impl SnapshotService {
+    /// Create a snapshot service
+    pub fn new(rpc_client: impl RpcApi + 'static) -> Self {
+        Self::from_api(Arc::new(rpc_client))
+    }
+
+    /// Create a snapshot service from a shared RPC implementation
+    pub fn from_api(rpc_client: Arc<dyn RpcApi>) -> Self {
+        Self { rpc_client }
+    }
+
+    /// Capture `pubkeys` decoded as Anchor account type `T`
+    pub async fn capture<T>(&self, pubkeys: &[Pubkey]) -> Result<StateSnapshot, SnapshotError>
+    where
+        T: Discriminator + BorshDeserialize + Serialize,
+    {
+        self.capture_with(pubkeys, Account::decode::<T>).await
+    }
+
+    /// Capture `pubkeys` decoded with `decode`
+    ///
+    /// Every account is read at the slot of the first request, however many
+    /// requests the set needs.
+    pub async fn capture_with<T, E>(
+        &self,
+        pubkeys: &[Pubkey],
+        decode: impl Fn(&Account) -> Result<T, E>,
+    ) -> Result<StateSnapshot, SnapshotError>
+    where
+        T: Serialize,
+        E: fmt::Display,
+    {
+        let mut snapshot = StateSnapshot::default();
+        for chunk in pubkeys.chunks(MAX_ACCOUNTS_PER_REQUEST) {
+            let min_context_slot = (snapshot.slot > 0).then_some(snapshot.slot);
+            let read = self
+                .rpc_client
+                .get_multiple_accounts_with_context(chunk, min_context_slot)
+                .await?;
+            if snapshot.slot == 0 {
+                snapshot.slot = read.context.slot;
+            }
+            for (pubkey, account) in chunk.iter().zip(read.value) {
+                let state = account
+                    .as_ref()
+                    .map(|account| {
+                        let failed = |message: String| SnapshotError::Decode {
+                            account: *pubkey,
+                            message,
+                        };
+                        let decoded = decode(account).map_err(|e| failed(e.to_string()))?;
+                        serde_json::to_value(decoded).map_err(|e| failed(e.to_string()))
+                    })
+                    .transpose()?;
+                snapshot.states.push((*pubkey, state));
+                snapshot.accounts.push((*pubkey, account));
+            }
+        }
+        Ok(snapshot)
+    }
+
+    /// Write the captured accounts back through surfpool cheatcodes
+    ///
+    /// Accounts that did not exist are emptied and handed to the system
+    /// program. Snapshots loaded from JSON carry no raw accounts and restore
+    /// nothing.
+    pub async fn restore(
+        &self,
+        snapshot: &StateSnapshot,
+        cheatcodes: &dyn Cheatcodes,
+    ) -> Result<(), SnapshotError> {
+        for (pubkey, account) in &snapshot.accounts {
+            let update = match account {
+                Some(account) => AccountUpdate {
+                    lamports: Some(account.lamports),
+                    data: Some(account.data.clone()),
+                    owner: Some(account.owner),
+                    executable: Some(account.executable),
+                },
+                None => AccountUpdate {
+                    lamports: Some(0),
+                    data: Some(Vec::new()),
+                    owner: Some(SYSTEM_PROGRAM_ID),
+                    executable: Some(false),
+                },
+            };
+            cheatcodes.set_account(pubkey, &update).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether the exception `path` covers the field at `field`
+fn covers(path: &str, field: &str) -> bool {
+    field
+        .strip_prefix(path)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('.') || rest.starts_with('['))
+}
+
+fn describe_changes(changes: &[(Pubkey, FieldChange)]) -> String {
+    let show = |value: &Option<Value>| {
+        value
+            .as_ref()
+            .map_or("(none)".to_string(), Value::to_string)
+    };
+    changes
+        .iter()
+        .map(|(pubkey, change)| {
+            format!(
+                "{} {}: {} -> {}",
+                pubkey,
+                change.path,
+                show(&change.old),
+                show(&change.new)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::discriminator::account_discriminator;
+    use crate::infrastructure::rpc::mock::MockRpc;
+    use borsh::BorshSerialize;
+    use std::sync::Mutex;
+
+    #[derive(BorshSerialize, BorshDeserialize, Serialize)]
+    struct Counter {
+        count: u64,
+        updated_at: i64,
+    }
+
+    impl Discriminator for Counter {
+        const DISCRIMINATOR: [u8; 8] = account_discriminator("Counter");
+    }
+
+    fn counter(pubkey: Pubkey, count: u64, updated_at: i64) -> Account {
+        let mut data = Counter::DISCRIMINATOR.to_vec();
+        BorshSerialize::serialize(&Counter { count, updated_at }, &mut data).unwrap();
+        Account {
+            pubkey,
+            lamports: 1_002_240,
+            data,
+            owner: Pubkey::new([9; 32]),
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_capture_and_compare() {
+        let first = Pubkey::new([1; 32]);
+        let second = Pubkey::new([2; 32]);
+        let before = SnapshotService::new(MockRpc::new().with_account(counter(first, 1, 100)))
+            .capture::<Counter>(&[first, second])
+            .await
+            .unwrap();
+        assert_eq!(before.slot, 120);
+        assert_eq!(before.state(&second), None);
+        assert_eq!(before.state(&first).unwrap()["count"], 1);
+
+        let after = SnapshotService::new(
+            MockRpc::new()
+                .with_account(counter(first, 1, 160))
+                .with_account(counter(second, 0, 160)),
+        )
+        .capture::<Counter>(&[first, second])
+        .await
+        .unwrap();
+
+        // Only the new account remains once timestamps are ignored
+        let error = before.assert_matches(&after, &["updated_at"]).unwrap_err();
+        let SnapshotError::Mismatch(changes) = &error else {
+            panic!("expected a mismatch, got {error}");
+        };
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].0, second);
+        assert_eq!(changes[0].1.path, "");
+
+        // Saved snapshots compare the same way
+        let saved: StateSnapshot =
+            serde_json::from_str(&serde_json::to_string(&after).unwrap()).unwrap();
+        assert!(after.assert_matches(&saved, &[]).is_ok());
+        assert!(covers("updated_at", "updated_at"));
+        assert!(!covers("updated", "updated_at"));
+    }
+
+    #[derive(Default)]
+    struct RecordingCheatcodes {
+        accounts: Mutex<Vec<(Pubkey, AccountUpdate)>>,
+    }
+
+    #[async_trait::async_trait]
+    impl Cheatcodes for RecordingCheatcodes {
+        async fn set_account(
+            &self,
+            pubkey: &Pubkey,
+            update: &AccountUpdate,
+        ) -> Result<(), RpcError> {
+            self.accounts
+                .lock()
+                .unwrap()
+                .push((*pubkey, update.clone()));
+            Ok(())
+        }
+
+        async fn set_token_balance(
+            &self,
+            _owner: &Pubkey,
+            _mint: &Pubkey,
+            _amount: u64,
+            _token_program: &Pubkey,
+        ) -> Result<(), RpcError> {
+            unreachable!("restoring only writes whole accounts")
+        }
+    }
+
+    #[tokio::test]
+    async fn test_restore() {
+        let first = Pubkey::new([1; 32]);
+        let second = Pubkey::new([2; 32]);
+        let service = SnapshotService::new(MockRpc::new().with_account(counter(first, 3, 100)));
+        let snapshot = service.capture::<Counter>(&[first, second]).await.unwrap();
+
+        let surfpool = RecordingCheatcodes::default();
+        service.restore(&snapshot, &surfpool).await.unwrap();
+        let written = surfpool.accounts.lock().unwrap();
+        assert_eq!(written[0].1.data, Some(counter(first, 3, 100).data));
+        assert_eq!(written[1].0, second);
+        assert_eq!(written[1].1.lamports, Some(0));
+    }
+}
@@ -0,0 +1,140 @@
+//! Stake account listing
+//!
+//! Wallets show a "my stake accounts" view by asking the stake program for
+//! every account whose stake or withdraw authority is the user's key.
+//! [`StakeService`] runs that `getProgramAccounts` query with memcmp filters
+//! on the authority offsets, decodes each account's state and reports the
+//! cluster's minimum delegation for new stakes.
+
+use crate::domain::programs::stake::{
+    StakeState, STAKER_OFFSET, STAKE_ACCOUNT_SIZE, STAKE_PROGRAM_ID, WITHDRAWER_OFFSET,
+};
+use crate::domain::types::Pubkey;
+use crate::infrastructure::rpc::{Filter, RpcApi, RpcError};
+use std::sync::Arc;
+
+/// A stake account and its decoded state
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StakeAccount {
+    pub address: Pubkey,
+    pub lamports: u64,
+    pub state: StakeState,
+}
+
+impl StakeAccount {
+    /// Delegated lamports, zero while undelegated
+    pub fn delegated_stake(&self) -> u64 {
+        self.state
+            .delegation()
+            .map_or(0, |delegation| delegation.stake)
+    }
+}
+
+/// Service listing stake accounts by authority
+pub struct StakeService {
+    rpc_client: Arc<dyn RpcApi>,
+}
+
+impl StakeService {
+    /// Create a stake service
+    pub fn new(rpc_client: impl RpcApi + 'static) -> Self {
+        Self::from_api(Arc::new(rpc_client))
+    }
+
+    /// Create a stake service from a shared RPC implementation
+    pub fn from_api(rpc_client: Arc<dyn RpcApi>) -> Self {
+        Self { rpc_client }
+    }
+
+    /// Smallest delegation, in lamports, the cluster accepts
+    pub async fn minimum_delegation(&self) -> Result<u64, RpcError> {
+        self.rpc_client.get_stake_minimum_delegation().await
+    }
+
+    /// Stake accounts `withdrawer` can withdraw from
+    pub async fn accounts_by_withdrawer(
+        &self,
+        withdrawer: &Pubkey,
+    ) -> Result<Vec<StakeAccount>, RpcError> {
+        self.accounts_by_authority(WITHDRAWER_OFFSET, withdrawer)
+            .await
+    }
+
+    /// Stake accounts `staker` can delegate and deactivate
+    pub async fn accounts_by_staker(&self, staker: &Pubkey) -> Result<Vec<StakeAccount>, RpcError> {
+        self.accounts_by_authority(STAKER_OFFSET, staker).await
+    }
+
+    /// Accounts with `authority` at `offset`, biggest delegation first
+    async fn accounts_by_authority(
+        &self,
+        offset: usize,
+        authority: &Pubkey,
+    ) -> Result<Vec<StakeAccount>, RpcError> {
+        let filters = [
+            Filter::data_size(STAKE_ACCOUNT_SIZE as u64),
+            Filter::memcmp_pubkey(offset, authority),
+        ];
+        let mut accounts = self
+            .rpc_client
+            .get_program_accounts(&STAKE_PROGRAM_ID, &filters)
+            .await?
+            .into_iter()
+            .map(|account| {
+                let state = StakeState::from_account_data(&account.data)
+                    .map_err(|e| RpcError::ParseError(e.to_string()))?;
+                Ok(StakeAccount {
+                    address: account.pubkey,
+                    lamports: account.lamports,
+                    state,
+                })
+            })
+            .collect::<Result<Vec<_>, RpcError>>()?;
+        accounts.sort_by_key(|account| std::cmp::Reverse(account.delegated_stake()));
+        Ok(accounts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::rpc::mock::MockRpc;
+    use crate::infrastructure::rpc::Account;
+
+    fn stake_account(address: u8, withdrawer: Pubkey, stake: Option<u64>) -> Account {
+        let mut data = vec![0; STAKE_ACCOUNT_SIZE];
+        data[0] = if stake.is_some() { 2 } else { 1 };
+        data[WITHDRAWER_OFFSET..WITHDRAWER_OFFSET + 32].copy_from_slice(withdrawer.as_bytes());
+        data[156..164].copy_from_slice(&stake.unwrap_or_default().to_le_bytes());
+        Account {
+            pubkey: Pubkey::new([address; 32]),
+            lamports: 2_282_880 + stake.unwrap_or_default(),
+            data,
+            owner: STAKE_PROGRAM_ID,
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_accounts_by_withdrawer() {
+        let wallet = Pubkey::new([9; 32]);
+        let rpc = MockRpc::new()
+            .with_account(stake_account(1, wallet, None))
+            .with_account(stake_account(2, wallet, Some(5_000)))
+            .with_account(stake_account(3, Pubkey::new([8; 32]), Some(7_000)));
+        let service = StakeService::new(rpc);
+
+        let accounts = service.accounts_by_withdrawer(&wallet).await.unwrap();
+        let addresses: Vec<Pubkey> = accounts.iter().map(|account| account.address).collect();
+        assert_eq!(addresses, [Pubkey::new([2; 32]), Pubkey::new([1; 32])]);
+        assert_eq!(accounts[0].delegated_stake(), 5_000);
+        assert!(matches!(accounts[1].state, StakeState::Initialized(_)));
+        assert!(service
+            .accounts_by_staker(&wallet)
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(service.minimum_delegation().await.unwrap(), 1_000_000_000);
+    }
+}
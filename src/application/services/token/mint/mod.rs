@@ -0,0 +1,280 @@
+//! Mint metadata with a persistent cache
+//!
+//! Showing a token balance needs its mint's decimals, and a UI that cannot
+//! find them falls back to raw base units. [`MintInfoService`] decodes mint
+//! accounts into [`MintInfo`] and keeps them in memory and in a
+//! [`StorageAdapter`], such as browser local storage, so each mint is fetched
+//! once. Decimals never change; supply and authorities are as of the last
+//! fetch until [`MintInfoService::refresh`].
+
+use crate::domain::programs::token::Mint;
+use crate::domain::types::constants::{TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID};
+use crate::domain::types::Pubkey;
+use crate::domain::validation::format_units;
+use crate::infrastructure::rpc::{Account, RpcApi, RpcError};
+use crate::infrastructure::storage::{MemoryStorage, StorageAdapter};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+
+/// Storage key prefix for cached mints
+const STORAGE_PREFIX: &str = "gloo_solana:mint:";
+
+/// Mints fetched per `getMultipleAccounts` request
+const MINTS_PER_REQUEST: usize = 100;
+
+/// Errors that can occur while loading mint information
+#[derive(Debug, Clone, Error)]
+pub enum MintInfoError {
+    #[error("RPC error: {0}")]
+    Rpc(#[from] RpcError),
+
+    #[error("{0} is not a token mint")]
+    NotAMint(Pubkey),
+}
+
+/// A mint's decoded state and the token program owning it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MintInfo {
+    pub address: Pubkey,
+    /// Token or Token-2022 program
+    pub token_program: Pubkey,
+    /// Supply in base units when fetched
+    pub supply: u64,
+    pub decimals: u8,
+    pub mint_authority: Option<Pubkey>,
+    pub freeze_authority: Option<Pubkey>,
+}
+
+impl MintInfo {
+    /// Decode a fetched mint account
+    pub fn from_account(account: &Account) -> Option<Self> {
+        if account.owner != TOKEN_PROGRAM_ID && account.owner != TOKEN_2022_PROGRAM_ID {
+            return None;
+        }
+        let mint = Mint::decode(&account.data)?;
+        Some(Self {
+            address: account.pubkey,
+            token_program: account.owner,
+            supply: mint.supply,
+            decimals: mint.decimals,
+            mint_authority: mint.mint_authority,
+            freeze_authority: mint.freeze_authority,
+        })
+    }
+
+    /// `amount` base units as a decimal amount, e.g. `"1.5"`
+    pub fn format_amount(&self, amount: u64) -> String {
+        format_units(amount, self.decimals)
+    }
+}
+
+/// Loads mints once and serves them from a persistent cache
+///
+/// Clones share the cache. Storage failures are ignored: the cache only
+/// saves requests, so an unavailable storage means fetching again.
+#[derive(Clone)]
+pub struct MintInfoService {
+    rpc_client: Arc<dyn RpcApi>,
+    storage: Arc<dyn StorageAdapter>,
+    memory: Arc<Mutex<HashMap<Pubkey, MintInfo>>>,
+}
+
+impl MintInfoService {
+    /// Create a mint service caching in memory
+    pub fn new(rpc_client: impl RpcApi + 'static) -> Self {
+        Self::from_api(Arc::new(rpc_client))
+    }
+
+    /// Create a mint service from a shared RPC implementation
+    pub fn from_api(rpc_client: Arc<dyn RpcApi>) -> Self {
+        Self {
+            rpc_client,
+            storage: Arc::new(MemoryStorage::new()),
+            memory: Arc::default(),
+        }
+    }
+
+    /// Persist mints in `storage`
+    pub fn with_storage(mut self, storage: Arc<dyn StorageAdapter>) -> Self {
+        self.storage = storage;
+        self
+    }
+
+    /// The mint at `address`, fetched on first use
+    pub async fn mint_info(&self, address: &Pubkey) -> Result<MintInfo, MintInfoError> {
+        match self.cached(address) {
+            Some(info) => Ok(info),
+            None => self.refresh(address).await,
+        }
+    }
+
+    /// Several mints, fetching the uncached ones in batches
+    ///
+    /// Results follow `addresses`; accounts that are not mints fail alone.
+    pub async fn mint_infos(
+        &self,
+        addresses: &[Pubkey],
+    ) -> Result<Vec<Result<MintInfo, MintInfoError>>, RpcError> {
+        let mut missing: Vec<Pubkey> = addresses
+            .iter()
+            .filter(|address| self.cached(address).is_none())
+            .copied()
+            .collect();
+        missing.sort();
+        missing.dedup();
+        for chunk in missing.chunks(MINTS_PER_REQUEST) {
+            let accounts = self.rpc_client.get_multiple_accounts(chunk).await?;
+            for account in accounts.iter().flatten() {
+                if let Some(info) = MintInfo::from_account(account) {
+                    self.store(info);
+                }
+            }
+        }
+        Ok(addresses
+            .iter()
+            .map(|address| {
+                self.cached(address)
+                    .ok_or(MintInfoError::NotAMint(*address))
+            })
+            .collect())
+    }
+
+    /// The decimals of the mint at `address`
+    pub async fn decimals(&self, address: &Pubkey) -> Result<u8, MintInfoError> {
+        Ok(self.mint_info(address).await?.decimals)
+    }
+
+    /// `amount` base units of the mint at `address` as a decimal amount
+    pub async fn format_amount(
+        &self,
+        address: &Pubkey,
+        amount: u64,
+    ) -> Result<String, MintInfoError> {
+        Ok(self.mint_info(address).await?.format_amount(amount))
+    }
+
+    /// Fetch the mint again, updating its supply and authorities
+    pub async fn refresh(&self, address: &Pubkey) -> Result<MintInfo, MintInfoError> {
+        let info = self
+            .rpc_client
+            .get_account_info(address)
+            .await?
+            .as_ref()
+            .and_then(MintInfo::from_account)
+            .ok_or(MintInfoError::NotAMint(*address))?;
+        self.store(info);
+        Ok(info)
+    }
+
+    /// Drop the cached mint at `address`
+    pub fn invalidate(&self, address: &Pubkey) {
+        self.lock().remove(address);
+        let _ = self.storage.remove(&storage_key(address));
+    }
+
+    fn cached(&self, address: &Pubkey) -> Option<MintInfo> {
+        if let Some(info) = self.lock().get(address) {
+            return Some(*info);
+        }
+        let stored: MintInfo = self
+            .storage
+            .get(&storage_key(address))
+            .ok()
+            .flatten()
+            .and_then(|raw| serde_json::from_str(&raw).ok())?;
+        self.lock().insert(*address, stored);
+        Some(stored)
+    }
+
+    fn store(&self, info: MintInfo) {
+        self.lock().insert(info.address, info);
+        if let Ok(raw) = serde_json::to_string(&info) {
+            let _ = self.storage.set(&storage_key(&info.address), &raw);
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<Pubkey, MintInfo>> {
+        self.memory.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl std::fmt::Debug for MintInfoService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MintInfoService").finish_non_exhaustive()
+    }
+}
+
+fn storage_key(address: &Pubkey) -> String {
+    format!("{}{}", STORAGE_PREFIX, address)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::services::fixtures::FixtureMint;
+    use crate::infrastructure::rpc::mock::MockRpc;
+
+    fn mint_account(address: Pubkey, decimals: u8) -> Account {
+        Account {
+            pubkey: address,
+            lamports: 1_461_600,
+            data: FixtureMint::new(address, decimals)
+                .balance(Pubkey::new([9; 32]), 2_500_000)
+                .account_data(),
+            owner: TOKEN_PROGRAM_ID,
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mint_info_is_cached_persistently() {
+        let usdc = Pubkey::new([1; 32]);
+        let storage: Arc<dyn StorageAdapter> = Arc::new(MemoryStorage::new());
+        let service = MintInfoService::new(MockRpc::new().with_account(mint_account(usdc, 6)))
+            .with_storage(storage.clone());
+
+        let info = service.mint_info(&usdc).await.unwrap();
+        assert_eq!(info.decimals, 6);
+        assert_eq!(info.supply, 2_500_000);
+        assert_eq!(info.format_amount(info.supply), "2.5");
+        assert_eq!(
+            service.format_amount(&usdc, 1_000_001).await.unwrap(),
+            "1.000001"
+        );
+
+        // A new service with no account on chain still knows the mint
+        let offline = MintInfoService::new(MockRpc::new()).with_storage(storage);
+        assert_eq!(offline.decimals(&usdc).await.unwrap(), 6);
+        offline.invalidate(&usdc);
+        assert!(matches!(
+            offline.decimals(&usdc).await,
+            Err(MintInfoError::NotAMint(address)) if address == usdc
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_mint_infos_batches_lookups() {
+        let (first, second, wallet) = (
+            Pubkey::new([1; 32]),
+            Pubkey::new([2; 32]),
+            Pubkey::new([3; 32]),
+        );
+        let mut not_a_mint = mint_account(wallet, 0);
+        not_a_mint.owner = Pubkey::new([0; 32]);
+        let service = MintInfoService::new(
+            MockRpc::new()
+                .with_account(mint_account(first, 6))
+                .with_account(mint_account(second, 9))
+                .with_account(not_a_mint),
+        );
+
+        let infos = service.mint_infos(&[second, wallet, first]).await.unwrap();
+        assert_eq!(infos[0].as_ref().unwrap().decimals, 9);
+        assert!(matches!(infos[1], Err(MintInfoError::NotAMint(_))));
+        assert_eq!(infos[2].as_ref().unwrap().decimals, 6);
+    }
+}
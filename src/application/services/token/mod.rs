@@ -0,0 +1,142 @@
+//! SPL token analytics
+//!
+//! [`TokenAnalytics`] combines `getTokenSupply` and `getTokenLargestAccounts`
+//! into holder concentration figures for a mint, the numbers token dashboards
+//! usually chart: how much of the supply the top holders control and how
+//! concentrated ownership is overall.
+
+use crate::domain::types::Pubkey;
+use crate::infrastructure::rpc::{RpcApi, RpcError};
+use std::sync::Arc;
+
+pub mod mint;
+pub mod transfer;
+
+pub use mint::{MintInfo, MintInfoError, MintInfoService};
+pub use transfer::{
+    PlannedStep, TokenTransferError, TokenTransferService, TransferConfig, TransferPlan,
+};
+
+/// A token account and its share of the supply
+#[derive(Debug, Clone, PartialEq)]
+pub struct HolderShare {
+    pub address: Pubkey,
+    /// Balance in base units
+    pub amount: u64,
+    /// Fraction of the total supply, from 0 to 1
+    pub share: f64,
+}
+
+/// How concentrated a mint's supply is among its largest accounts
+#[derive(Debug, Clone, PartialEq)]
+pub struct HolderConcentration {
+    pub mint: Pubkey,
+    /// Total supply in base units
+    pub supply: u64,
+    pub decimals: u8,
+    /// Largest accounts, biggest first (at most 20)
+    pub holders: Vec<HolderShare>,
+}
+
+impl HolderConcentration {
+    /// Fraction of the supply held by the `n` largest accounts
+    pub fn top_share(&self, n: usize) -> f64 {
+        self.holders.iter().take(n).map(|holder| holder.share).sum()
+    }
+
+    /// Herfindahl-Hirschman index over the largest accounts, from 0 to 1
+    ///
+    /// Accounts outside the top 20 are too small to move the index much, so
+    /// this is a close lower bound of the index over every holder.
+    pub fn herfindahl_index(&self) -> f64 {
+        self.holders.iter().map(|holder| holder.share.powi(2)).sum()
+    }
+
+    /// Total supply scaled by the mint's decimals
+    pub fn ui_supply(&self) -> f64 {
+        self.supply as f64 / 10f64.powi(self.decimals as i32)
+    }
+}
+
+/// Service computing supply statistics for token mints
+pub struct TokenAnalytics {
+    rpc_client: Arc<dyn RpcApi>,
+}
+
+impl TokenAnalytics {
+    /// Create a token analytics service
+    pub fn new(rpc_client: impl RpcApi + 'static) -> Self {
+        Self::from_api(Arc::new(rpc_client))
+    }
+
+    /// Create a token analytics service from a shared RPC implementation
+    pub fn from_api(rpc_client: Arc<dyn RpcApi>) -> Self {
+        Self { rpc_client }
+    }
+
+    /// Compute how the supply of `mint` is spread over its largest accounts
+    pub async fn holder_concentration(
+        &self,
+        mint: &Pubkey,
+    ) -> Result<HolderConcentration, RpcError> {
+        let supply = self.rpc_client.get_token_supply(mint).await?;
+        let largest = self.rpc_client.get_token_largest_accounts(mint).await?;
+
+        let total = supply.raw_amount()?;
+        let mut holders = largest
+            .iter()
+            .map(|account| {
+                let amount = account.amount.raw_amount()?;
+                Ok(HolderShare {
+                    address: account.address,
+                    amount,
+                    share: if total == 0 {
+                        0.0
+                    } else {
+                        amount as f64 / total as f64
+                    },
+                })
+            })
+            .collect::<Result<Vec<_>, RpcError>>()?;
+        holders.sort_by_key(|holder| std::cmp::Reverse(holder.amount));
+
+        Ok(HolderConcentration {
+            mint: *mint,
+            supply: total,
+            decimals: supply.decimals,
+            holders,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::rpc::mock::MockRpc;
+
+    #[tokio::test]
+    async fn test_holder_concentration() {
+        let mint = Pubkey::new([1; 32]);
+        let whale = Pubkey::new([2; 32]);
+        let rpc = MockRpc::new().with_mint(
+            mint,
+            1_000_000_000,
+            6,
+            vec![(Pubkey::new([3; 32]), 100_000_000), (whale, 500_000_000)],
+        );
+        let analytics = TokenAnalytics::new(rpc);
+
+        let concentration = analytics.holder_concentration(&mint).await.unwrap();
+        assert_eq!(concentration.holders[0].address, whale);
+        assert_eq!(concentration.holders[0].share, 0.5);
+        assert_eq!(concentration.top_share(1), 0.5);
+        assert!((concentration.top_share(10) - 0.6).abs() < 1e-12);
+        assert!((concentration.herfindahl_index() - 0.26).abs() < 1e-12);
+        assert_eq!(concentration.ui_supply(), 1000.0);
+
+        assert!(analytics
+            .holder_concentration(&Pubkey::new([9; 32]))
+            .await
+            .is_err());
+    }
+}
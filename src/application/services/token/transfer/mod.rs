@@ -0,0 +1,399 @@
+//! Token transfers planned against on-chain state
+//!
+//! A transfer to a wallet that never held the mint fails unless its
+//! associated token account is created first, and that creation costs the
+//! sender rent. [`TokenTransferService::build_transfer`] reads the accounts
+//! involved, adds the instructions the transfer needs and returns them as a
+//! [`TransferPlan`] listing what was added and what it costs, fee included,
+//! so the UI can ask the user before signing.
+
+use crate::application::flows::TransactionBuilder;
+use crate::application::services::token::mint::{MintInfoError, MintInfoService};
+use crate::domain::message::MessageError;
+use crate::domain::programs::system;
+use crate::domain::programs::token::{self, TOKEN_2022_ACCOUNT_LEN, TOKEN_ACCOUNT_LEN};
+use crate::domain::types::constants::TOKEN_2022_PROGRAM_ID;
+use crate::domain::types::Pubkey;
+use crate::domain::validation::format_units;
+use crate::infrastructure::rpc::{RpcApi, RpcError};
+use base64::{engine::general_purpose, Engine as _};
+use std::sync::Arc;
+use thiserror::Error;
+
+/// Errors that can occur while planning a token transfer
+#[derive(Debug, Clone, Error)]
+pub enum TokenTransferError {
+    #[error("RPC error: {0}")]
+    Rpc(#[from] RpcError),
+
+    #[error("{0} is not a token mint")]
+    InvalidMint(Pubkey),
+
+    #[error("The sender has no token account {0}")]
+    SourceNotFound(Pubkey),
+
+    #[error("The recipient has no token account {0}")]
+    DestinationNotFound(Pubkey),
+
+    #[error("The sender needs {needed} lamports but has {available}")]
+    InsufficientLamports { needed: u64, available: u64 },
+
+    #[error("Transaction encoding failed: {0}")]
+    Message(#[from] MessageError),
+
+    #[error("The node no longer knows the blockhash, so it cannot quote the fee")]
+    FeeUnavailable,
+}
+
+/// Which missing pieces [`TokenTransferService::build_transfer`] may add
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransferConfig {
+    /// Create the recipient's associated token account if it does not exist
+    pub create_destination: bool,
+    /// Fund the recipient's token account up to the rent-exempt minimum
+    pub top_up_rent: bool,
+}
+
+impl Default for TransferConfig {
+    fn default() -> Self {
+        Self {
+            create_destination: true,
+            top_up_rent: true,
+        }
+    }
+}
+
+impl TransferConfig {
+    /// Add every missing piece
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether to create a missing destination account
+    pub fn create_destination(mut self, create: bool) -> Self {
+        self.create_destination = create;
+        self
+    }
+
+    /// Whether to top up a destination account below the rent-exempt minimum
+    pub fn top_up_rent(mut self, top_up: bool) -> Self {
+        self.top_up_rent = top_up;
+        self
+    }
+}
+
+/// An instruction added to a transfer beyond the transfer itself
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlannedStep {
+    /// Create the recipient's associated token account, paying its rent
+    CreateDestinationAccount { address: Pubkey, rent: u64 },
+    /// Send lamports so the destination account is rent exempt
+    TopUpRent { address: Pubkey, lamports: u64 },
+}
+
+impl PlannedStep {
+    /// Lamports the sender pays for the step, on top of fees
+    pub fn lamports(&self) -> u64 {
+        match self {
+            Self::CreateDestinationAccount { rent, .. } => *rent,
+            Self::TopUpRent { lamports, .. } => *lamports,
+        }
+    }
+}
+
+/// A token transfer ready to build, with the steps added to make it succeed
+#[derive(Debug, Clone)]
+pub struct TransferPlan {
+    /// Every instruction, added steps first
+    pub builder: TransactionBuilder,
+    pub source: Pubkey,
+    pub destination: Pubkey,
+    pub token_program: Pubkey,
+    pub amount: u64,
+    pub decimals: u8,
+    /// Added steps, in execution order
+    pub added: Vec<PlannedStep>,
+    /// Transaction fee quoted by the node
+    pub fee: u64,
+}
+
+impl TransferPlan {
+    /// Lamports the added steps cost the sender
+    pub fn extra_lamports(&self) -> u64 {
+        self.added.iter().map(PlannedStep::lamports).sum()
+    }
+
+    /// The amount scaled by the mint's decimals, e.g. `"1.5"`
+    pub fn ui_amount(&self) -> String {
+        format_units(self.amount, self.decimals)
+    }
+}
+
+impl From<MintInfoError> for TokenTransferError {
+    fn from(error: MintInfoError) -> Self {
+        match error {
+            MintInfoError::Rpc(error) => Self::Rpc(error),
+            MintInfoError::NotAMint(mint) => Self::InvalidMint(mint),
+        }
+    }
+}
+
+/// Service planning token transfers between wallets
+pub struct TokenTransferService {
+    rpc_client: Arc<dyn RpcApi>,
+    mints: MintInfoService,
+    config: TransferConfig,
+}
+
+impl TokenTransferService {
+    /// Create a token transfer service
+    pub fn new(rpc_client: impl RpcApi + 'static) -> Self {
+        Self::from_api(Arc::new(rpc_client))
+    }
+
+    /// Create a token transfer service from a shared RPC implementation
+    pub fn from_api(rpc_client: Arc<dyn RpcApi>) -> Self {
+        Self {
+            mints: MintInfoService::from_api(rpc_client.clone()),
+            rpc_client,
+            config: TransferConfig::default(),
+        }
+    }
+
+    /// Read mints through `mints`, sharing its cache
+    pub fn with_mint_info(mut self, mints: MintInfoService) -> Self {
+        self.mints = mints;
+        self
+    }
+
+    /// Choose which missing pieces are added
+    pub fn with_config(mut self, config: TransferConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Plan sending `amount` of `mint` from `sender`'s to `recipient`'s
+    /// associated token account
+    ///
+    /// Fails if a missing piece is needed but disabled in the config, or if
+    /// the sender cannot pay for the added steps and the fee.
+    pub async fn build_transfer(
+        &self,
+        sender: Pubkey,
+        recipient: Pubkey,
+        mint: Pubkey,
+        amount: u64,
+    ) -> Result<TransferPlan, TokenTransferError> {
+        let mint_info = self.mints.mint_info(&mint).await?;
+        let (token_program, decimals) = (mint_info.token_program, mint_info.decimals);
+
+        let source = token::associated_token_address(&sender, &mint, &token_program);
+        let destination = token::associated_token_address(&recipient, &mint, &token_program);
+        let accounts = self
+            .rpc_client
+            .get_multiple_accounts(&[source, destination, sender])
+            .await?;
+        let (source_account, destination_account, sender_account) = match accounts.as_slice() {
+            [source, destination, sender] => (source, destination, sender),
+            _ => {
+                return Err(TokenTransferError::Rpc(RpcError::ParseError(format!(
+                    "Expected 3 accounts, got {}",
+                    accounts.len()
+                ))))
+            }
+        };
+        if source_account.is_none() {
+            return Err(TokenTransferError::SourceNotFound(source));
+        }
+
+        let account_len = if token_program == TOKEN_2022_PROGRAM_ID {
+            TOKEN_2022_ACCOUNT_LEN
+        } else {
+            TOKEN_ACCOUNT_LEN
+        };
+        let rent = self
+            .rpc_client
+            .get_minimum_balance_for_rent_exemption(account_len)
+            .await?;
+        let mut builder = TransactionBuilder::new(sender);
+        let mut added = Vec::new();
+        match destination_account {
+            None if self.config.create_destination => {
+                builder = builder.instruction(token::create_associated_token_account_idempotent(
+                    sender,
+                    recipient,
+                    mint,
+                    token_program,
+                ));
+                added.push(PlannedStep::CreateDestinationAccount {
+                    address: destination,
+                    rent,
+                });
+            }
+            None => return Err(TokenTransferError::DestinationNotFound(destination)),
+            Some(account) if self.config.top_up_rent && account.lamports < rent => {
+                let lamports = rent - account.lamports;
+                builder = builder.instruction(system::transfer(sender, destination, lamports));
+                added.push(PlannedStep::TopUpRent {
+                    address: destination,
+                    lamports,
+                });
+            }
+            Some(_) => {}
+        }
+
+        let builder = builder.instruction(token::transfer_checked(
+            token_program,
+            source,
+            mint,
+            destination,
+            sender,
+            amount,
+            decimals,
+        ));
+        let fee = self.quote_fee(&builder).await?;
+
+        let needed = added
+            .iter()
+            .map(PlannedStep::lamports)
+            .fold(fee, u64::saturating_add);
+        let available = sender_account
+            .as_ref()
+            .map_or(0, |account| account.lamports);
+        if needed > available {
+            return Err(TokenTransferError::InsufficientLamports { needed, available });
+        }
+
+        Ok(TransferPlan {
+            builder,
+            source,
+            destination,
+            token_program,
+            amount,
+            decimals,
+            added,
+            fee,
+        })
+    }
+
+    /// Fee the node charges for the transaction `builder` makes
+    async fn quote_fee(&self, builder: &TransactionBuilder) -> Result<u64, TokenTransferError> {
+        let latest = self.rpc_client.get_latest_blockhash().await?;
+        let message = builder.build(latest.blockhash)?.message.serialize()?;
+        self.rpc_client
+            .get_fee_for_message(&general_purpose::STANDARD.encode(message))
+            .await?
+            .ok_or(TokenTransferError::FeeUnavailable)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::services::fixtures::FixtureMint;
+    use crate::domain::rent::rent_exempt_minimum;
+    use crate::domain::types::constants::TOKEN_PROGRAM_ID;
+    use crate::infrastructure::rpc::mock::{MockRpc, MOCK_LAMPORTS_PER_SIGNATURE};
+    use crate::infrastructure::rpc::Account;
+
+    fn account(pubkey: Pubkey, lamports: u64, data: Vec<u8>, owner: Pubkey) -> Account {
+        Account {
+            pubkey,
+            lamports,
+            data,
+            owner,
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_build_transfer_adds_missing_steps() {
+        let sender = Pubkey::new([1; 32]);
+        let recipient = Pubkey::new([2; 32]);
+        let mint = Pubkey::new([3; 32]);
+        let mint_data = FixtureMint::new(mint, 6).account_data();
+        let source = token::associated_token_address(&sender, &mint, &TOKEN_PROGRAM_ID);
+        let destination = token::associated_token_address(&recipient, &mint, &TOKEN_PROGRAM_ID);
+        let rent = rent_exempt_minimum(TOKEN_ACCOUNT_LEN);
+        let rpc = MockRpc::new()
+            .with_account(account(mint, 1, mint_data, TOKEN_PROGRAM_ID))
+            .with_account(account(source, rent, vec![0; 165], TOKEN_PROGRAM_ID))
+            .with_balance(sender, rent + MOCK_LAMPORTS_PER_SIGNATURE);
+        let service = TokenTransferService::new(rpc);
+
+        let plan = service
+            .build_transfer(sender, recipient, mint, 5)
+            .await
+            .unwrap();
+        assert_eq!(
+            plan.added,
+            [PlannedStep::CreateDestinationAccount {
+                address: destination,
+                rent
+            }]
+        );
+        assert_eq!(plan.extra_lamports(), rent);
+        assert_eq!(plan.fee, MOCK_LAMPORTS_PER_SIGNATURE);
+        assert_eq!(plan.decimals, 6);
+        assert_eq!(plan.ui_amount(), "0.000005");
+        assert_eq!(plan.builder.instructions().len(), 2);
+
+        let strict = service.with_config(TransferConfig::new().create_destination(false));
+        assert!(matches!(
+            strict.build_transfer(sender, recipient, mint, 5).await,
+            Err(TokenTransferError::DestinationNotFound(address)) if address == destination
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_token_2022_destination_pays_extended_rent() {
+        let sender = Pubkey::new([1; 32]);
+        let recipient = Pubkey::new([2; 32]);
+        let mint = Pubkey::new([3; 32]);
+        let mint_data = FixtureMint::new(mint, 6).account_data();
+        let source = token::associated_token_address(&sender, &mint, &TOKEN_2022_PROGRAM_ID);
+        let rent = rent_exempt_minimum(TOKEN_2022_ACCOUNT_LEN);
+        let rpc = MockRpc::new()
+            .with_account(account(mint, 1, mint_data, TOKEN_2022_PROGRAM_ID))
+            .with_account(account(source, rent, vec![0; 170], TOKEN_2022_PROGRAM_ID))
+            .with_balance(sender, rent + MOCK_LAMPORTS_PER_SIGNATURE);
+
+        let plan = TokenTransferService::new(rpc)
+            .build_transfer(sender, recipient, mint, 5)
+            .await
+            .unwrap();
+        assert_eq!(plan.token_program, TOKEN_2022_PROGRAM_ID);
+        assert_eq!(plan.extra_lamports(), rent);
+    }
+
+    #[tokio::test]
+    async fn test_rent_top_up_must_be_affordable() {
+        let sender = Pubkey::new([1; 32]);
+        let recipient = Pubkey::new([2; 32]);
+        let mint = Pubkey::new([3; 32]);
+        let source = token::associated_token_address(&sender, &mint, &TOKEN_PROGRAM_ID);
+        let destination = token::associated_token_address(&recipient, &mint, &TOKEN_PROGRAM_ID);
+        let rent = rent_exempt_minimum(TOKEN_ACCOUNT_LEN);
+        let rpc = MockRpc::new()
+            .with_account(account(mint, 1, vec![0; 82], TOKEN_PROGRAM_ID))
+            .with_account(account(source, rent, vec![0; 165], TOKEN_PROGRAM_ID))
+            .with_account(account(
+                destination,
+                rent - 10,
+                vec![0; 165],
+                TOKEN_PROGRAM_ID,
+            ))
+            .with_balance(sender, 5);
+
+        assert!(matches!(
+            TokenTransferService::new(rpc)
+                .build_transfer(sender, recipient, mint, 5)
+                .await,
+            Err(TokenTransferError::InsufficientLamports {
+                needed,
+                available: 5
+            }) if needed == 10 + MOCK_LAMPORTS_PER_SIGNATURE
+        ));
+    }
+}
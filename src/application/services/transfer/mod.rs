@@ -0,0 +1,270 @@
+//! Sending SOL with safety checks
+//!
+//! A bare system transfer fails on chain, after the user has signed and paid
+//! the fee, when the sender cannot cover it, when it would leave the sender
+//! with less than the rent-exempt minimum, or when it funds a new account
+//! below that minimum. [`AccountService::transfer_sol`] checks all three and
+//! simulates the transfer before asking for a signature, then sends it and
+//! waits until it lands. A dry run stops after the checks. The fee and the
+//! rent-exempt minimums are quoted by the node rather than assumed.
+
+use super::AccountService;
+use crate::application::estimate::{estimate_transaction, EstimateError, TransactionEstimate};
+use crate::application::flows::TransactionBuilder;
+use crate::application::sender::{SendError, SentTransaction};
+use crate::domain::message::MessageError;
+use crate::domain::programs::system;
+use crate::domain::types::Pubkey;
+use crate::infrastructure::rpc::RpcError;
+use crate::infrastructure::signer::{Signer, SignerError};
+use base64::{engine::general_purpose, Engine as _};
+use thiserror::Error;
+
+/// Errors that can occur while transferring SOL
+#[derive(Debug, Clone, Error)]
+pub enum SolTransferError {
+    #[error("RPC error: {0}")]
+    Rpc(#[from] RpcError),
+
+    #[error("Send error: {0}")]
+    Send(#[from] SendError),
+
+    #[error("Signing failed: {0}")]
+    Signer(#[from] SignerError),
+
+    #[error("Transaction encoding failed: {0}")]
+    Message(#[from] MessageError),
+
+    #[error("Estimate failed: {0}")]
+    Estimate(#[from] EstimateError),
+
+    #[error("The node no longer knows the blockhash, so it cannot quote the fee")]
+    FeeUnavailable,
+
+    #[error("Cannot transfer 0 lamports")]
+    ZeroAmount,
+
+    #[error("The sender needs {needed} lamports but has {available}")]
+    InsufficientLamports { needed: u64, available: u64 },
+
+    #[error("The sender would keep {remaining} lamports, below the rent-exempt {minimum}")]
+    SenderBelowRent { remaining: u64, minimum: u64 },
+
+    #[error("A new account needs at least {minimum} lamports, not {lamports}")]
+    RecipientBelowRent { lamports: u64, minimum: u64 },
+
+    #[error("The transfer would not land: {0}")]
+    Rejected(String),
+}
+
+/// How [`AccountService::transfer_sol_with`] runs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SolTransferConfig {
+    /// Stop after the checks and simulation, without signing or sending
+    pub dry_run: bool,
+}
+
+impl SolTransferConfig {
+    /// Check, sign and send
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether to only check and simulate the transfer
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+}
+
+/// A checked SOL transfer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolTransfer {
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub lamports: u64,
+    /// Fee the sender pays on top of `lamports`
+    pub fee: u64,
+    /// Lamports the sender keeps
+    pub remaining: u64,
+    pub estimate: TransactionEstimate,
+    /// The landed transaction, `None` for a dry run
+    pub transaction: Option<SentTransaction>,
+}
+
+impl AccountService {
+    /// Transfer `lamports` from `from` to `to` and wait until it lands
+    ///
+    /// Sending the whole balance, fee included, is allowed; leaving a
+    /// remainder below the rent-exempt minimum is not.
+    pub async fn transfer_sol(
+        &self,
+        from: &dyn Signer,
+        to: &Pubkey,
+        lamports: u64,
+    ) -> Result<SolTransfer, SolTransferError> {
+        self.transfer_sol_with(from, to, lamports, SolTransferConfig::new())
+            .await
+    }
+
+    /// [`transfer_sol`](Self::transfer_sol) run as `config` says
+    pub async fn transfer_sol_with(
+        &self,
+        from: &dyn Signer,
+        to: &Pubkey,
+        lamports: u64,
+        config: SolTransferConfig,
+    ) -> Result<SolTransfer, SolTransferError> {
+        if lamports == 0 {
+            return Err(SolTransferError::ZeroAmount);
+        }
+        let sender = from.pubkey();
+        let latest = self.rpc_client.get_latest_blockhash().await?;
+        let mut transaction = TransactionBuilder::new(sender)
+            .instruction(system::transfer(sender, *to, lamports))
+            .build(latest.blockhash)?;
+
+        let message = general_purpose::STANDARD.encode(transaction.message.serialize()?);
+        let fee = self
+            .rpc_client
+            .get_fee_for_message(&message)
+            .await?
+            .ok_or(SolTransferError::FeeUnavailable)?;
+        let remaining = self.check_transfer(&sender, to, lamports, fee).await?;
+        let estimate = estimate_transaction(self.rpc_client.as_ref(), &transaction).await?;
+        if let Some(warning) = estimate.warnings().into_iter().find(|w| w.is_fatal()) {
+            return Err(SolTransferError::Rejected(warning.to_string()));
+        }
+
+        let mut transfer = SolTransfer {
+            from: sender,
+            to: *to,
+            lamports,
+            fee,
+            remaining,
+            estimate,
+            transaction: None,
+        };
+        if config.dry_run {
+            return Ok(transfer);
+        }
+
+        let signature = from.sign(&transaction.message).await?;
+        transaction.add_signature(&sender, signature)?;
+        let encoded = general_purpose::STANDARD.encode(transaction.serialize()?);
+        let sent = self
+            .sender
+            .send(&encoded, latest.last_valid_block_height)
+            .await?;
+        transfer.transaction = Some(sent);
+        Ok(transfer)
+    }
+
+    /// Check both balances, returning what the sender keeps
+    async fn check_transfer(
+        &self,
+        from: &Pubkey,
+        to: &Pubkey,
+        lamports: u64,
+        fee: u64,
+    ) -> Result<u64, SolTransferError> {
+        let accounts = self.rpc_client.get_multiple_accounts(&[*from, *to]).await?;
+        let (sender, recipient) = match accounts.as_slice() {
+            [sender, recipient] => (sender.as_ref(), recipient.as_ref()),
+            _ => (None, None),
+        };
+
+        let available = sender.map_or(0, |account| account.lamports);
+        let needed = lamports.saturating_add(fee);
+        let remaining = available
+            .checked_sub(needed)
+            .ok_or(SolTransferError::InsufficientLamports { needed, available })?;
+        let minimum = self
+            .rpc_client
+            .get_minimum_balance_for_rent_exemption(sender.map_or(0, |account| account.data.len()))
+            .await?;
+        if remaining > 0 && remaining < minimum {
+            return Err(SolTransferError::SenderBelowRent { remaining, minimum });
+        }
+
+        let minimum = self
+            .rpc_client
+            .get_minimum_balance_for_rent_exemption(0)
+            .await?;
+        if recipient.is_none() && lamports < minimum {
+            return Err(SolTransferError::RecipientBelowRent { lamports, minimum });
+        }
+        Ok(remaining)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::sender::SendConfig;
+    use crate::infrastructure::rpc::mock::{MockRpc, MOCK_LAMPORTS_PER_SIGNATURE, SENT_SIGNATURE};
+    use crate::infrastructure::rpc::{CommitmentLevel, SignatureStatus};
+    use crate::infrastructure::signer::Keypair;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    const SOL: u64 = 1_000_000_000;
+
+    #[tokio::test]
+    async fn test_transfer_sol_checks_then_sends() {
+        let payer = Keypair::from_seed(&[1; 32]);
+        let (funded, fresh) = (Pubkey::new([7; 32]), Pubkey::new([8; 32]));
+        let rpc = Arc::new(
+            MockRpc::new()
+                .with_balance(payer.pubkey(), SOL)
+                .with_balance(funded, 1)
+                .with_statuses(
+                    SENT_SIGNATURE,
+                    vec![Some(SignatureStatus {
+                        slot: 5,
+                        confirmations: None,
+                        err: None,
+                        confirmation_status: Some(CommitmentLevel::Finalized),
+                    })],
+                ),
+        );
+        let service = AccountService::from_api(rpc.clone())
+            .with_send_config(SendConfig::new().status_interval(Duration::from_millis(1)));
+
+        let dry_run = SolTransferConfig::new().dry_run(true);
+        let planned = service
+            .transfer_sol_with(&payer, &funded, SOL / 2, dry_run)
+            .await
+            .unwrap();
+        assert_eq!(planned.fee, MOCK_LAMPORTS_PER_SIGNATURE);
+        assert_eq!(planned.remaining, SOL / 2 - MOCK_LAMPORTS_PER_SIGNATURE);
+        assert_eq!(planned.transaction, None);
+        assert!(rpc.sent.lock().unwrap().is_empty());
+
+        let sent = service.transfer_sol(&payer, &funded, SOL / 2).await;
+        assert_eq!(sent.unwrap().transaction.unwrap().signature, SENT_SIGNATURE);
+        assert_eq!(rpc.sent.lock().unwrap().len(), 1);
+
+        // Emptying the sender is fine, leaving dust behind is not
+        let all = SOL - MOCK_LAMPORTS_PER_SIGNATURE;
+        let emptied = service
+            .transfer_sol_with(&payer, &funded, all, dry_run)
+            .await;
+        assert_eq!(emptied.unwrap().remaining, 0);
+        let dust = service.transfer_sol(&payer, &funded, all - 10).await;
+        assert!(matches!(
+            dust,
+            Err(SolTransferError::SenderBelowRent { remaining: 10, .. })
+        ));
+        let too_much = service.transfer_sol(&payer, &funded, SOL).await;
+        assert!(matches!(
+            too_much,
+            Err(SolTransferError::InsufficientLamports { .. })
+        ));
+        let too_little = service.transfer_sol(&payer, &fresh, 1_000).await;
+        assert!(matches!(
+            too_little,
+            Err(SolTransferError::RecipientBelowRent { .. })
+        ));
+    }
+}
@@ -0,0 +1,777 @@
+//! Solana Pay transfer and transaction requests
+//!
+//! Builds and parses `solana:` payment URLs as defined by the Solana Pay
+//! specification, and finds and validates the payment transaction through the
+//! request's reference keys, which is how a point-of-sale app learns that a
+//! customer has paid.
+//!
+//! Transaction requests point the wallet at an HTTPS endpoint instead:
+//! [`TransactionRequestClient`] fetches the merchant's label and icon, then
+//! posts the payer's account and decodes the transaction it gets back.
+
+use crate::domain::message::{MessageError, Transaction};
+use crate::domain::types::{Pubkey, PubkeyError, Signature};
+use crate::infrastructure::http::{HttpClient, HttpError};
+use crate::infrastructure::rpc::{
+    RpcApi, RpcError, SignatureInfo, SignaturesForAddressConfig, TokenBalance,
+    MAX_SIGNATURES_PER_REQUEST,
+};
+use crate::infrastructure::url::{percent_decode, percent_encode};
+use base64::{engine::general_purpose, Engine as _};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::sync::Arc;
+use thiserror::Error;
+
+/// URL scheme of Solana Pay links
+pub const SOLANA_PAY_SCHEME: &str = "solana";
+
+/// Decimals of native SOL amounts
+const SOL_DECIMALS: u8 = 9;
+
+/// Errors that can occur with Solana Pay requests
+#[derive(Debug, Clone, Error)]
+pub enum SolanaPayError {
+    #[error("Invalid Solana Pay URL: {0}")]
+    InvalidUrl(String),
+
+    #[error("Invalid {field}: {source}")]
+    InvalidPubkey {
+        field: &'static str,
+        source: PubkeyError,
+    },
+
+    #[error("Invalid amount: {0}")]
+    InvalidAmount(String),
+
+    #[error("RPC error: {0}")]
+    Rpc(#[from] RpcError),
+
+    #[error("No transaction found for reference {0}")]
+    ReferenceNotFound(Pubkey),
+
+    #[error("Transaction {signature} does not satisfy the request: {reason}")]
+    ValidationFailed {
+        signature: Signature,
+        reason: String,
+    },
+
+    #[error("HTTP error: {0}")]
+    Http(#[from] HttpError),
+
+    #[error("Invalid transaction from request endpoint: {0}")]
+    InvalidTransaction(String),
+}
+
+impl From<MessageError> for SolanaPayError {
+    fn from(error: MessageError) -> Self {
+        Self::InvalidTransaction(error.to_string())
+    }
+}
+
+/// A parsed `solana:` URL of either request kind
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolanaPayUrl {
+    Transfer(TransferRequest),
+    Transaction(TransactionRequest),
+}
+
+impl SolanaPayUrl {
+    /// Parse a `solana:` URL, telling the request kinds apart by their path
+    pub fn parse(url: &str) -> Result<Self, SolanaPayError> {
+        if TransactionRequest::is_link(strip_scheme(url)?) {
+            TransactionRequest::parse(url).map(Self::Transaction)
+        } else {
+            TransferRequest::parse(url).map(Self::Transfer)
+        }
+    }
+
+    /// Encode as a `solana:` URL
+    pub fn to_url(&self) -> String {
+        match self {
+            Self::Transfer(request) => request.to_url(),
+            Self::Transaction(request) => request.to_url(),
+        }
+    }
+}
+
+/// A Solana Pay transfer request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransferRequest {
+    pub recipient: Pubkey,
+    /// Decimal amount in SOL or in units of `spl_token`
+    pub amount: Option<String>,
+    /// Mint of the token to transfer, SOL if absent
+    pub spl_token: Option<Pubkey>,
+    /// Keys added to the transaction so it can be found afterwards
+    pub references: Vec<Pubkey>,
+    pub label: Option<String>,
+    pub message: Option<String>,
+    pub memo: Option<String>,
+}
+
+impl TransferRequest {
+    /// Create a request paying `recipient`
+    pub fn new(recipient: Pubkey) -> Self {
+        Self {
+            recipient,
+            amount: None,
+            spl_token: None,
+            references: Vec::new(),
+            label: None,
+            message: None,
+            memo: None,
+        }
+    }
+
+    /// Set the decimal amount, e.g. `"1.5"`
+    pub fn amount(mut self, amount: impl Into<String>) -> Self {
+        self.amount = Some(amount.into());
+        self
+    }
+
+    /// Request an SPL token instead of SOL
+    pub fn spl_token(mut self, mint: Pubkey) -> Self {
+        self.spl_token = Some(mint);
+        self
+    }
+
+    /// Add a reference key
+    pub fn reference(mut self, reference: Pubkey) -> Self {
+        self.references.push(reference);
+        self
+    }
+
+    /// Set the merchant label
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// Set the message shown to the payer
+    pub fn message(mut self, message: impl Into<String>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+
+    /// Set the memo recorded on chain
+    pub fn memo(mut self, memo: impl Into<String>) -> Self {
+        self.memo = Some(memo.into());
+        self
+    }
+
+    /// Parse a `solana:<recipient>?...` URL
+    pub fn parse(url: &str) -> Result<Self, SolanaPayError> {
+        let rest = strip_scheme(url)?;
+        let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let recipient =
+            Pubkey::from_base58(path).map_err(|source| SolanaPayError::InvalidPubkey {
+                field: "recipient",
+                source,
+            })?;
+
+        let mut request = Self::new(recipient);
+        for (name, value) in query_pairs(query)? {
+            match name.as_str() {
+                "amount" => {
+                    check_amount(&value)?;
+                    request.amount = Some(value);
+                }
+                "spl-token" => request.spl_token = Some(parse_pubkey("spl-token", &value)?),
+                "reference" => request.references.push(parse_pubkey("reference", &value)?),
+                "label" => request.label = Some(value),
+                "message" => request.message = Some(value),
+                "memo" => request.memo = Some(value),
+                // Unknown parameters are ignored for forward compatibility
+                _ => {}
+            }
+        }
+        Ok(request)
+    }
+
+    /// Encode as a `solana:` URL
+    pub fn to_url(&self) -> String {
+        let mut params = Vec::new();
+        if let Some(amount) = &self.amount {
+            params.push(("amount", amount.clone()));
+        }
+        if let Some(mint) = &self.spl_token {
+            params.push(("spl-token", mint.to_base58()));
+        }
+        for reference in &self.references {
+            params.push(("reference", reference.to_base58()));
+        }
+        for (name, value) in [
+            ("label", &self.label),
+            ("message", &self.message),
+            ("memo", &self.memo),
+        ] {
+            if let Some(value) = value {
+                params.push((name, value.clone()));
+            }
+        }
+
+        let mut url = format!("{}:{}", SOLANA_PAY_SCHEME, self.recipient);
+        for (i, (name, value)) in params.iter().enumerate() {
+            let separator = if i == 0 { '?' } else { '&' };
+            let _ = write!(url, "{}{}={}", separator, name, percent_encode(value));
+        }
+        url
+    }
+
+    /// The requested amount in base units of a mint with `decimals`
+    pub fn amount_in_base_units(&self, decimals: u8) -> Result<Option<u64>, SolanaPayError> {
+        self.amount
+            .as_deref()
+            .map(|amount| parse_amount(amount, decimals))
+            .transpose()
+    }
+}
+
+/// A Solana Pay transaction request, `solana:<https link>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionRequest {
+    /// Endpoint serving the request metadata and transaction
+    pub link: String,
+}
+
+impl TransactionRequest {
+    /// Create a request for `link`, which must be an HTTPS URL
+    pub fn new(link: impl Into<String>) -> Result<Self, SolanaPayError> {
+        let link = link.into();
+        if !link.starts_with("https://") {
+            return Err(SolanaPayError::InvalidUrl(format!(
+                "transaction request link must use https: {}",
+                link
+            )));
+        }
+        Ok(Self { link })
+    }
+
+    /// Parse a `solana:<link>` URL
+    pub fn parse(url: &str) -> Result<Self, SolanaPayError> {
+        Self::new(decode(strip_scheme(url)?)?)
+    }
+
+    /// Encode as a `solana:` URL, escaping the link if it has a query
+    pub fn to_url(&self) -> String {
+        if self.link.contains('?') {
+            format!("{}:{}", SOLANA_PAY_SCHEME, percent_encode(&self.link))
+        } else {
+            format!("{}:{}", SOLANA_PAY_SCHEME, self.link)
+        }
+    }
+
+    /// Whether the path of a `solana:` URL is a link rather than a recipient
+    fn is_link(path: &str) -> bool {
+        let prefix = path.get(..6).unwrap_or_default();
+        prefix.eq_ignore_ascii_case("https:") || prefix.eq_ignore_ascii_case("https%")
+    }
+}
+
+/// Response to the wallet's GET request
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionRequestMetadata {
+    pub label: String,
+    /// Absolute URL of an SVG, PNG or WebP image
+    pub icon: String,
+}
+
+/// Body of the wallet's POST request
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionRequestBody {
+    /// Account that will sign and, usually, pay for the transaction
+    pub account: Pubkey,
+}
+
+/// Response to the wallet's POST request
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionResponse {
+    /// Base64-encoded serialized transaction
+    pub transaction: String,
+    /// Message to show the payer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl TransactionResponse {
+    /// Encode `transaction` as a response, for merchant servers
+    pub fn new(transaction: &Transaction) -> Result<Self, SolanaPayError> {
+        Ok(Self {
+            transaction: general_purpose::STANDARD.encode(transaction.serialize()?),
+            message: None,
+        })
+    }
+
+    /// Set the message shown to the payer
+    pub fn message(mut self, message: impl Into<String>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+
+    /// Decode the transaction for `account` to sign
+    ///
+    /// `account` must be a required signer. Signatures already present, from
+    /// the merchant for example, are kept; the slots of `account` and any
+    /// other missing signers stay empty.
+    pub fn decode(&self, account: &Pubkey) -> Result<Transaction, SolanaPayError> {
+        let bytes = general_purpose::STANDARD
+            .decode(&self.transaction)
+            .map_err(|e| SolanaPayError::InvalidTransaction(e.to_string()))?;
+        let transaction = Transaction::deserialize(&bytes)?;
+        let is_signer = transaction
+            .message
+            .account_keys
+            .iter()
+            .position(|key| key == account)
+            .is_some_and(|index| transaction.message.is_signer(index));
+        if !is_signer {
+            return Err(MessageError::NotASigner(*account).into());
+        }
+        Ok(transaction)
+    }
+}
+
+/// A transaction received from a request endpoint, ready for signing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestedTransaction {
+    pub transaction: Transaction,
+    /// Message the merchant wants shown to the payer
+    pub message: Option<String>,
+}
+
+/// Wallet side of the transaction request flow
+pub struct TransactionRequestClient<H: HttpClient> {
+    http_client: H,
+}
+
+impl<H: HttpClient> TransactionRequestClient<H> {
+    /// Create a client using `http_client`
+    pub fn new(http_client: H) -> Self {
+        Self { http_client }
+    }
+
+    /// Fetch the merchant label and icon to show before the payer approves
+    pub async fn fetch_metadata(
+        &self,
+        request: &TransactionRequest,
+    ) -> Result<TransactionRequestMetadata, SolanaPayError> {
+        Ok(self.http_client.get(&request.link).await?)
+    }
+
+    /// Post `account` to the endpoint and decode the transaction it returns
+    pub async fn fetch_transaction(
+        &self,
+        request: &TransactionRequest,
+        account: &Pubkey,
+    ) -> Result<RequestedTransaction, SolanaPayError> {
+        let body = TransactionRequestBody { account: *account };
+        let response: TransactionResponse =
+            self.http_client.post_json(&request.link, &body).await?;
+        Ok(RequestedTransaction {
+            transaction: response.decode(account)?,
+            message: response.message,
+        })
+    }
+}
+
+/// Finds and validates the transactions paying Solana Pay requests
+pub struct SolanaPayService {
+    rpc_client: Arc<dyn RpcApi>,
+}
+
+impl SolanaPayService {
+    /// Create a Solana Pay service
+    pub fn new(rpc_client: impl RpcApi + 'static) -> Self {
+        Self::from_api(Arc::new(rpc_client))
+    }
+
+    /// Create a Solana Pay service from a shared RPC implementation
+    pub fn from_api(rpc_client: Arc<dyn RpcApi>) -> Self {
+        Self { rpc_client }
+    }
+
+    /// Find the oldest transaction that includes `reference`
+    pub async fn find_reference(
+        &self,
+        reference: &Pubkey,
+    ) -> Result<SignatureInfo, SolanaPayError> {
+        let mut config = SignaturesForAddressConfig {
+            limit: Some(MAX_SIGNATURES_PER_REQUEST),
+            ..SignaturesForAddressConfig::default()
+        };
+        let mut oldest = None;
+        loop {
+            let page = self
+                .rpc_client
+                .get_signatures_for_address(reference, config.clone())
+                .await?;
+            let full = page.len() >= MAX_SIGNATURES_PER_REQUEST;
+            match page.into_iter().last() {
+                Some(last) => {
+                    config.before = Some(last.signature.clone());
+                    oldest = Some(last);
+                }
+                None => break,
+            }
+            if !full {
+                break;
+            }
+        }
+        oldest.ok_or(SolanaPayError::ReferenceNotFound(*reference))
+    }
+
+    /// Check that the transaction `signature` pays `request` in full
+    ///
+    /// Verifies success, the recipient's balance increase and the presence of
+    /// every reference key. The memo is not checked.
+    pub async fn validate_transfer(
+        &self,
+        signature: &Signature,
+        request: &TransferRequest,
+    ) -> Result<(), SolanaPayError> {
+        let fail = |reason: String| SolanaPayError::ValidationFailed {
+            signature: signature.clone(),
+            reason,
+        };
+
+        let transaction = self
+            .rpc_client
+            .get_transaction(signature)
+            .await?
+            .ok_or_else(|| fail("transaction not found".to_string()))?;
+        if let Some(error) = transaction.error() {
+            return Err(fail(format!("transaction failed: {}", error)));
+        }
+        let meta = transaction
+            .meta
+            .as_ref()
+            .ok_or_else(|| fail("missing transaction metadata".to_string()))?;
+
+        let keys = transaction.account_keys();
+        if let Some(missing) = request.references.iter().find(|r| !keys.contains(r)) {
+            return Err(fail(format!("reference {} not found", missing)));
+        }
+
+        let received = match &request.spl_token {
+            None => {
+                let index = keys
+                    .iter()
+                    .position(|key| *key == request.recipient)
+                    .ok_or_else(|| fail("recipient not found".to_string()))?;
+                let pre = meta.pre_balances.get(index).copied().unwrap_or(0);
+                let post = meta.post_balances.get(index).copied().unwrap_or(0);
+                let expected = request.amount_in_base_units(SOL_DECIMALS)?.unwrap_or(0);
+                (post.saturating_sub(pre), expected)
+            }
+            Some(mint) => {
+                let owner = &request.recipient;
+                let (pre, _) = token_balance(meta.pre_token_balances.as_deref(), mint, owner)?;
+                let (post, decimals) =
+                    token_balance(meta.post_token_balances.as_deref(), mint, owner)?;
+                let decimals = decimals
+                    .ok_or_else(|| fail("recipient token account not found".to_string()))?;
+                let expected = request.amount_in_base_units(decimals)?.unwrap_or(0);
+                (post.saturating_sub(pre), expected)
+            }
+        };
+
+        match received {
+            (received, expected) if received < expected => Err(fail(format!(
+                "recipient received {} base units, expected {}",
+                received, expected
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Total `mint` balance of `owner`'s token accounts, with the mint's decimals
+fn token_balance(
+    balances: Option<&[TokenBalance]>,
+    mint: &Pubkey,
+    owner: &Pubkey,
+) -> Result<(u64, Option<u8>), RpcError> {
+    let mut total = 0;
+    let mut decimals = None;
+    for balance in balances
+        .unwrap_or_default()
+        .iter()
+        .filter(|balance| balance.mint == *mint && balance.owner.as_ref() == Some(owner))
+    {
+        total += balance.ui_token_amount.raw_amount()?;
+        decimals = Some(balance.ui_token_amount.decimals);
+    }
+    Ok((total, decimals))
+}
+
+fn strip_scheme(url: &str) -> Result<&str, SolanaPayError> {
+    url.split_once(':')
+        .filter(|(scheme, _)| scheme.eq_ignore_ascii_case(SOLANA_PAY_SCHEME))
+        .map(|(_, rest)| rest)
+        .ok_or_else(|| {
+            SolanaPayError::InvalidUrl(format!("expected {}: scheme", SOLANA_PAY_SCHEME))
+        })
+}
+
+fn parse_pubkey(field: &'static str, value: &str) -> Result<Pubkey, SolanaPayError> {
+    Pubkey::from_base58(value).map_err(|source| SolanaPayError::InvalidPubkey { field, source })
+}
+
+/// Decoded `name=value` pairs of a query string
+fn query_pairs(query: &str) -> Result<Vec<(String, String)>, SolanaPayError> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            Ok((decode(name)?, decode(value)?))
+        })
+        .collect()
+}
+
+/// Split a non-negative decimal amount into its whole and fraction digits
+fn check_amount(amount: &str) -> Result<(&str, &str), SolanaPayError> {
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    let digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if whole.is_empty() || !digits(whole) || !digits(fraction) || amount.ends_with('.') {
+        return Err(SolanaPayError::InvalidAmount(amount.to_string()));
+    }
+    Ok((whole, fraction))
+}
+
+/// Parse a non-negative decimal amount into base units
+fn parse_amount(amount: &str, decimals: u8) -> Result<u64, SolanaPayError> {
+    let invalid = || SolanaPayError::InvalidAmount(amount.to_string());
+    let (whole, fraction) = check_amount(amount)?;
+    if fraction.len() > decimals as usize {
+        return Err(SolanaPayError::InvalidAmount(format!(
+            "{} has more than {} decimals",
+            amount, decimals
+        )));
+    }
+
+    let scale = 10u64.checked_pow(decimals as u32).ok_or_else(invalid)?;
+    let fraction_scale = 10u64.pow((decimals as usize - fraction.len()) as u32);
+    let whole: u64 = whole.parse().map_err(|_| invalid())?;
+    let fraction: u64 = if fraction.is_empty() {
+        0
+    } else {
+        fraction.parse().map_err(|_| invalid())?
+    };
+    whole
+        .checked_mul(scale)
+        .and_then(|units| units.checked_add(fraction * fraction_scale))
+        .ok_or_else(invalid)
+}
+
+/// Percent-decode a query value, mapping a bad escape to an invalid URL
+fn decode(value: &str) -> Result<String, SolanaPayError> {
+    percent_decode(value)
+        .ok_or_else(|| SolanaPayError::InvalidUrl(format!("bad escape in {}", value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::message::{Message, MessageHeader};
+    use crate::domain::types::Hash;
+    use crate::infrastructure::rpc::mock::MockRpc;
+    use crate::infrastructure::rpc::ConfirmedTransaction;
+    use serde_json::{json, Value};
+
+    const RECIPIENT: &str = "mvines9iiHiQTysrwkJjGf2gb9Ex9jXJX8ns3qwf2kN";
+
+    #[test]
+    fn test_url_round_trip() {
+        let url = "solana:mvines9iiHiQTysrwkJjGf2gb9Ex9jXJX8ns3qwf2kN?amount=1&label=Michael&message=Thanks%20for%20all%20the%20fish&memo=OrderId12345";
+        let request = TransferRequest::parse(url).unwrap();
+        assert_eq!(request.recipient.to_base58(), RECIPIENT);
+        assert_eq!(request.amount.as_deref(), Some("1"));
+        assert_eq!(request.message.as_deref(), Some("Thanks for all the fish"));
+        assert_eq!(request.to_url(), url);
+
+        let reference = Pubkey::new([3; 32]);
+        let token = TransferRequest::new(Pubkey::new([1; 32]))
+            .amount("0.01")
+            .spl_token(Pubkey::new([2; 32]))
+            .reference(reference)
+            .label("Café & Co");
+        let parsed = TransferRequest::parse(&token.to_url()).unwrap();
+        assert_eq!(parsed, token);
+        assert_eq!(parsed.amount_in_base_units(6).unwrap(), Some(10_000));
+    }
+
+    #[test]
+    fn test_rejects_invalid_requests() {
+        assert!(TransferRequest::parse("bitcoin:abc").is_err());
+        assert!(TransferRequest::parse("solana:notakey").is_err());
+        for amount in ["-1", "1.", ".5", "1e3", "1,5"] {
+            let url = format!("solana:{}?amount={}", RECIPIENT, amount);
+            assert!(TransferRequest::parse(&url).is_err(), "{}", amount);
+        }
+        assert!(TransferRequest::new(Pubkey::new([1; 32]))
+            .amount("0.0000000001")
+            .amount_in_base_units(9)
+            .is_err());
+    }
+
+    /// Endpoint answering GET with `metadata` and POST with `response`
+    struct FakeEndpoint {
+        metadata: Value,
+        response: Value,
+    }
+
+    impl HttpClient for FakeEndpoint {
+        async fn post_json<'a, Req, Resp>(
+            &'a self,
+            _url: &'a str,
+            body: &'a Req,
+        ) -> Result<Resp, HttpError>
+        where
+            Req: Serialize + Send + Sync,
+            Resp: for<'de> Deserialize<'de> + 'static,
+        {
+            let body = serde_json::to_value(body).unwrap();
+            assert!(body["account"].is_string());
+            Ok(serde_json::from_value(self.response.clone()).unwrap())
+        }
+
+        async fn post_json_text<'a, Req>(
+            &'a self,
+            _url: &'a str,
+            _body: &'a Req,
+        ) -> Result<String, HttpError>
+        where
+            Req: Serialize + Send + Sync,
+        {
+            Ok(self.response.to_string())
+        }
+
+        async fn get<'a, Resp>(&'a self, _url: &'a str) -> Result<Resp, HttpError>
+        where
+            Resp: for<'de> Deserialize<'de> + 'static,
+        {
+            Ok(serde_json::from_value(self.metadata.clone()).unwrap())
+        }
+    }
+
+    #[test]
+    fn test_transaction_request_urls() {
+        let plain = SolanaPayUrl::parse("solana:https://example.com/solana-pay").unwrap();
+        assert_eq!(
+            plain,
+            SolanaPayUrl::Transaction(TransactionRequest {
+                link: "https://example.com/solana-pay".to_string()
+            })
+        );
+        assert_eq!(plain.to_url(), "solana:https://example.com/solana-pay");
+
+        let encoded = "solana:https%3A%2F%2Fexample.com%2Fsolana-pay%3Forder%3D12345";
+        let request = TransactionRequest::parse(encoded).unwrap();
+        assert_eq!(request.link, "https://example.com/solana-pay?order=12345");
+        assert_eq!(request.to_url(), encoded);
+
+        assert!(matches!(
+            SolanaPayUrl::parse(&format!("solana:{}", RECIPIENT)),
+            Ok(SolanaPayUrl::Transfer(_))
+        ));
+        assert!(TransactionRequest::parse("solana:http://example.com").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_transaction() {
+        let payer = Pubkey::new([1; 32]);
+        let merchant = Pubkey::new([2; 32]);
+        let mut transaction = Transaction::new_unsigned(Message {
+            header: MessageHeader {
+                num_required_signatures: 2,
+                num_readonly_signed_accounts: 1,
+                num_readonly_unsigned_accounts: 1,
+            },
+            account_keys: vec![payer, merchant, Pubkey::new([0; 32])],
+            recent_blockhash: Hash::new([4; 32]),
+            instructions: Vec::new(),
+        });
+        transaction
+            .add_signature(&merchant, Signature::new([7; 64]))
+            .unwrap();
+        let response = TransactionResponse::new(&transaction)
+            .unwrap()
+            .message("Thanks!");
+
+        let client = TransactionRequestClient::new(FakeEndpoint {
+            metadata: json!({ "label": "Shop", "icon": "https://example.com/icon.svg" }),
+            response: serde_json::to_value(&response).unwrap(),
+        });
+        let request = TransactionRequest::new("https://example.com/pay").unwrap();
+
+        let metadata = client.fetch_metadata(&request).await.unwrap();
+        assert_eq!(metadata.label, "Shop");
+
+        let fetched = client.fetch_transaction(&request, &payer).await.unwrap();
+        assert_eq!(fetched.transaction, transaction);
+        assert_eq!(fetched.transaction.missing_signers(), vec![payer]);
+        assert_eq!(fetched.message.as_deref(), Some("Thanks!"));
+
+        assert!(matches!(
+            client
+                .fetch_transaction(&request, &Pubkey::new([0; 32]))
+                .await,
+            Err(SolanaPayError::InvalidTransaction(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_find_and_validate_transfer() {
+        let recipient = Pubkey::new([1; 32]);
+        let reference = Pubkey::new([7; 32]);
+        let signature = Signature::new([5; 64]);
+        let transaction: ConfirmedTransaction = serde_json::from_value(json!({
+            "slot": 42,
+            "meta": {
+                "err": null,
+                "fee": 5000,
+                "preBalances": [3_000_000_000u64, 0, 0],
+                "postBalances": [1_499_995_000u64, 1_500_000_000u64, 0]
+            },
+            "transaction": {
+                "signatures": [signature.to_base58()],
+                "message": {
+                    "accountKeys": [Pubkey::new([9; 32]).to_base58(), recipient.to_base58(), reference.to_base58()],
+                    "recentBlockhash": "11111111111111111111111111111111",
+                    "instructions": []
+                }
+            }
+        }))
+        .unwrap();
+        let service =
+            SolanaPayService::new(MockRpc::new().with_transaction(signature.clone(), transaction));
+
+        let found = service.find_reference(&reference).await.unwrap();
+        assert_eq!(found.signature, signature);
+        assert!(matches!(
+            service.find_reference(&Pubkey::new([8; 32])).await,
+            Err(SolanaPayError::ReferenceNotFound(_))
+        ));
+
+        let request = TransferRequest::new(recipient)
+            .amount("1.5")
+            .reference(reference);
+        service
+            .validate_transfer(&signature, &request)
+            .await
+            .unwrap();
+
+        let too_much = request.clone().amount("2");
+        assert!(matches!(
+            service.validate_transfer(&signature, &too_much).await,
+            Err(SolanaPayError::ValidationFailed { .. })
+        ));
+        let wrong_reference = request.reference(Pubkey::new([8; 32]));
+        assert!(service
+            .validate_transfer(&signature, &wrong_reference)
+            .await
+            .is_err());
+    }
+}
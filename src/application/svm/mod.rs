@@ -0,0 +1,607 @@
+//! In-memory account executor for unit tests of flows
+//!
+//! [`AccountExecutor`] applies the effects of System, SPL Token and
+//! Associated Token Account instructions to a map of accounts, so the
+//! instruction builders and flows can be tested without a validator. It
+//! checks what a builder can get wrong: signer and writable flags, balances,
+//! token account owners and mint decimals. Fees, compute and programs other
+//! than those three are out of scope; anything else fails as unsupported.
+
+use crate::domain::programs::token::{MINT_LEN, TOKEN_ACCOUNT_LEN};
+use crate::domain::programs::{InstructionAccount, ProgramInstruction};
+use crate::domain::rent::rent_exempt_minimum;
+use crate::domain::types::constants::{
+    ASSOCIATED_TOKEN_PROGRAM_ID, NATIVE_MINT, SYSTEM_PROGRAM_ID, TOKEN_2022_PROGRAM_ID,
+    TOKEN_PROGRAM_ID,
+};
+use crate::domain::types::Pubkey;
+use crate::infrastructure::rpc::Account;
+use std::collections::HashMap;
+use thiserror::Error;
+
+const AMOUNT: std::ops::Range<usize> = 64..72;
+const STATE: usize = 108;
+const IS_NATIVE: std::ops::Range<usize> = 109..121;
+const MINT_DECIMALS: usize = 44;
+
+/// Why an instruction failed
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ExecutorError {
+    #[error("Account {0} does not exist")]
+    AccountNotFound(Pubkey),
+
+    #[error("Instruction has no account at position {0}")]
+    MissingAccount(usize),
+
+    #[error("Account {0} must sign")]
+    MissingSignature(Pubkey),
+
+    #[error("Account {0} is modified but not writable")]
+    ReadonlyModified(Pubkey),
+
+    #[error("Account {0} already exists")]
+    AccountInUse(Pubkey),
+
+    #[error("Account {account} holds {available}, {needed} needed")]
+    InsufficientFunds {
+        account: Pubkey,
+        needed: u64,
+        available: u64,
+    },
+
+    #[error("Account {0} does not have the expected owner or layout")]
+    InvalidAccount(Pubkey),
+
+    #[error("Mint decimals are {expected}, the instruction says {found}")]
+    DecimalsMismatch { expected: u8, found: u8 },
+
+    #[error("Token account {0} still holds tokens")]
+    NonZeroBalance(Pubkey),
+
+    #[error("Invalid instruction data")]
+    InvalidData,
+
+    #[error("Program {0} is not supported by the executor")]
+    UnsupportedProgram(Pubkey),
+
+    #[error("Instruction {tag} of program {program} is not supported by the executor")]
+    UnsupportedInstruction { program: Pubkey, tag: u8 },
+}
+
+/// An instruction failed; no instruction of the batch took effect
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("Instruction {index} failed: {error}")]
+pub struct ExecutionError {
+    pub index: usize,
+    pub error: ExecutorError,
+}
+
+/// Accounts that System and Token instructions are applied to
+#[derive(Debug, Clone)]
+pub struct AccountExecutor {
+    accounts: HashMap<Pubkey, Account>,
+}
+
+impl Default for AccountExecutor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AccountExecutor {
+    /// An executor holding only the wrapped SOL mint
+    pub fn new() -> Self {
+        Self {
+            accounts: HashMap::new(),
+        }
+        .with_mint(NATIVE_MINT, 9, 0)
+    }
+
+    /// Add an account
+    pub fn with_account(mut self, account: Account) -> Self {
+        self.set_account(account);
+        self
+    }
+
+    /// Add an empty system account holding `lamports`
+    pub fn with_balance(self, pubkey: Pubkey, lamports: u64) -> Self {
+        self.with_account(Account {
+            pubkey,
+            lamports,
+            data: Vec::new(),
+            owner: SYSTEM_PROGRAM_ID,
+            executable: false,
+            rent_epoch: 0,
+        })
+    }
+
+    /// Add an SPL Token mint without authorities
+    pub fn with_mint(self, mint: Pubkey, decimals: u8, supply: u64) -> Self {
+        let mut data = vec![0; MINT_LEN];
+        data[36..44].copy_from_slice(&supply.to_le_bytes());
+        data[MINT_DECIMALS] = decimals;
+        data[45] = 1;
+        self.with_account(Account {
+            pubkey: mint,
+            lamports: rent_exempt_minimum(MINT_LEN),
+            data,
+            owner: TOKEN_PROGRAM_ID,
+            executable: false,
+            rent_epoch: 0,
+        })
+    }
+
+    /// Add `owner`'s associated token account for `mint` holding `amount`
+    pub fn with_token_balance(mut self, owner: Pubkey, mint: Pubkey, amount: u64) -> Self {
+        let address = token_address(&owner, &mint, &TOKEN_PROGRAM_ID);
+        let mut account = token_account(address, mint, owner, TOKEN_PROGRAM_ID);
+        write_amount(&mut account, amount);
+        self.set_account(account);
+        self
+    }
+
+    /// Insert or replace an account, as a program writing its data would
+    pub fn set_account(&mut self, account: Account) {
+        self.accounts.insert(account.pubkey, account);
+    }
+
+    /// The account at `pubkey`
+    pub fn account(&self, pubkey: &Pubkey) -> Option<&Account> {
+        self.accounts.get(pubkey)
+    }
+
+    /// Lamports at `pubkey`, zero for missing accounts
+    pub fn balance(&self, pubkey: &Pubkey) -> u64 {
+        self.accounts
+            .get(pubkey)
+            .map_or(0, |account| account.lamports)
+    }
+
+    /// Token amount of the token account at `pubkey`
+    pub fn token_balance(&self, pubkey: &Pubkey) -> Option<u64> {
+        self.accounts
+            .get(pubkey)
+            .filter(|account| is_token_account(account))
+            .map(read_amount)
+    }
+
+    /// Apply `instructions` in order, all or none
+    ///
+    /// Accounts flagged as signers are taken to have signed.
+    pub fn execute(&mut self, instructions: &[ProgramInstruction]) -> Result<(), ExecutionError> {
+        let mut accounts = self.accounts.clone();
+        for (index, instruction) in instructions.iter().enumerate() {
+            let mut context = Context {
+                accounts: &mut accounts,
+                instruction,
+            };
+            context
+                .execute()
+                .map_err(|error| ExecutionError { index, error })?;
+        }
+        self.accounts = accounts;
+        Ok(())
+    }
+}
+
+/// One instruction being applied
+struct Context<'a> {
+    accounts: &'a mut HashMap<Pubkey, Account>,
+    instruction: &'a ProgramInstruction,
+}
+
+impl Context<'_> {
+    fn execute(&mut self) -> Result<(), ExecutorError> {
+        let program = self.instruction.program_id;
+        if program == SYSTEM_PROGRAM_ID {
+            self.system()
+        } else if program == TOKEN_PROGRAM_ID || program == TOKEN_2022_PROGRAM_ID {
+            self.token()
+        } else if program == ASSOCIATED_TOKEN_PROGRAM_ID {
+            self.create_associated_token_account()
+        } else {
+            Err(ExecutorError::UnsupportedProgram(program))
+        }
+    }
+
+    fn system(&mut self) -> Result<(), ExecutorError> {
+        let data = &self.instruction.data;
+        let tag = u32::from_le_bytes(read_array(data, 0)?);
+        match tag {
+            0 => {
+                let lamports = u64::from_le_bytes(read_array(data, 4)?);
+                let space = u64::from_le_bytes(read_array(data, 12)?);
+                let owner = Pubkey::new(read_array(data, 20)?);
+                let payer = self.signer(0)?;
+                let new_account = self.signer(1)?;
+                if self
+                    .accounts
+                    .get(&new_account)
+                    .is_some_and(|account| account.lamports > 0 || !account.data.is_empty())
+                {
+                    return Err(ExecutorError::AccountInUse(new_account));
+                }
+                self.debit_system_account(payer, lamports)?;
+                self.accounts.insert(
+                    new_account,
+                    Account {
+                        pubkey: new_account,
+                        lamports,
+                        data: vec![0; space as usize],
+                        owner,
+                        executable: false,
+                        rent_epoch: 0,
+                    },
+                );
+                Ok(())
+            }
+            2 => {
+                let lamports = u64::from_le_bytes(read_array(data, 4)?);
+                let from = self.signer(0)?;
+                let to = self.writable(1)?;
+                self.debit_system_account(from, lamports)?;
+                self.credit(to, lamports);
+                Ok(())
+            }
+            tag => Err(ExecutorError::UnsupportedInstruction {
+                program: SYSTEM_PROGRAM_ID,
+                tag: tag as u8,
+            }),
+        }
+    }
+
+    fn token(&mut self) -> Result<(), ExecutorError> {
+        let program = self.instruction.program_id;
+        let data = &self.instruction.data;
+        match data.first().copied() {
+            // CloseAccount
+            Some(9) => {
+                let address = self.writable(0)?;
+                let destination = self.writable(1)?;
+                let owner = self.signer(2)?;
+                let account = self.token_account(address, owner)?;
+                if read_native_reserve(account).is_none() && read_amount(account) > 0 {
+                    return Err(ExecutorError::NonZeroBalance(address));
+                }
+                let lamports = account.lamports;
+                self.accounts.remove(&address);
+                self.credit(destination, lamports);
+                Ok(())
+            }
+            // TransferChecked
+            Some(12) => {
+                let amount = u64::from_le_bytes(read_array(data, 1)?);
+                let decimals = *data.get(9).ok_or(ExecutorError::InvalidData)?;
+                let source = self.writable(0)?;
+                let mint = self.account_key(1)?;
+                let destination = self.writable(2)?;
+                let authority = self.signer(3)?;
+
+                let mint_account = self.existing(mint)?;
+                if mint_account.owner != program || mint_account.data.len() != MINT_LEN {
+                    return Err(ExecutorError::InvalidAccount(mint));
+                }
+                let expected = mint_account.data[MINT_DECIMALS];
+                if expected != decimals {
+                    return Err(ExecutorError::DecimalsMismatch {
+                        expected,
+                        found: decimals,
+                    });
+                }
+
+                let source_account = self.token_account(source, authority)?;
+                let available = read_amount(source_account);
+                if source_account.data[..32] != *mint.as_bytes() {
+                    return Err(ExecutorError::InvalidAccount(source));
+                }
+                let destination_account = self.existing(destination)?;
+                if !is_token_account(destination_account)
+                    || destination_account.owner != program
+                    || destination_account.data[..32] != *mint.as_bytes()
+                {
+                    return Err(ExecutorError::InvalidAccount(destination));
+                }
+                if available < amount {
+                    return Err(ExecutorError::InsufficientFunds {
+                        account: source,
+                        needed: amount,
+                        available,
+                    });
+                }
+                self.update_amount(source, |balance| balance - amount);
+                self.update_amount(destination, |balance| balance.saturating_add(amount));
+                Ok(())
+            }
+            // SyncNative
+            Some(17) => {
+                let address = self.writable(0)?;
+                let account = self.existing(address)?;
+                let reserve = read_native_reserve(account)
+                    .filter(|_| account.owner == program)
+                    .ok_or(ExecutorError::InvalidAccount(address))?;
+                let lamports = account.lamports;
+                self.update_amount(address, |_| lamports.saturating_sub(reserve));
+                Ok(())
+            }
+            Some(tag) => Err(ExecutorError::UnsupportedInstruction { program, tag }),
+            None => Err(ExecutorError::InvalidData),
+        }
+    }
+
+    /// `CreateIdempotent` of the associated token account program
+    fn create_associated_token_account(&mut self) -> Result<(), ExecutorError> {
+        if self.instruction.data != [1] {
+            return Err(ExecutorError::UnsupportedInstruction {
+                program: ASSOCIATED_TOKEN_PROGRAM_ID,
+                tag: self.instruction.data.first().copied().unwrap_or_default(),
+            });
+        }
+        let payer = self.signer(0)?;
+        let address = self.writable(1)?;
+        let owner = self.account_key(2)?;
+        let mint = self.account_key(3)?;
+        let token_program = self.account_key(5)?;
+        if token_address(&owner, &mint, &token_program) != address {
+            return Err(ExecutorError::InvalidAccount(address));
+        }
+
+        if let Some(existing) = self.accounts.get(&address) {
+            let matches = is_token_account(existing)
+                && existing.owner == token_program
+                && existing.data[..32] == *mint.as_bytes()
+                && existing.data[32..64] == *owner.as_bytes();
+            return if matches {
+                Ok(())
+            } else {
+                Err(ExecutorError::AccountInUse(address))
+            };
+        }
+        if self
+            .accounts
+            .get(&mint)
+            .is_none_or(|account| account.owner != token_program)
+        {
+            return Err(ExecutorError::InvalidAccount(mint));
+        }
+
+        let rent = rent_exempt_minimum(TOKEN_ACCOUNT_LEN);
+        self.debit_system_account(payer, rent)?;
+        let mut account = token_account(address, mint, owner, token_program);
+        if mint == NATIVE_MINT {
+            account.data[IS_NATIVE.start] = 1;
+            account.data[IS_NATIVE.start + 4..IS_NATIVE.end].copy_from_slice(&rent.to_le_bytes());
+        }
+        self.accounts.insert(address, account);
+        Ok(())
+    }
+
+    fn account_meta(&self, position: usize) -> Result<&InstructionAccount, ExecutorError> {
+        self.instruction
+            .accounts
+            .get(position)
+            .ok_or(ExecutorError::MissingAccount(position))
+    }
+
+    fn account_key(&self, position: usize) -> Result<Pubkey, ExecutorError> {
+        Ok(self.account_meta(position)?.pubkey)
+    }
+
+    fn writable(&self, position: usize) -> Result<Pubkey, ExecutorError> {
+        let meta = self.account_meta(position)?;
+        if meta.is_writable {
+            Ok(meta.pubkey)
+        } else {
+            Err(ExecutorError::ReadonlyModified(meta.pubkey))
+        }
+    }
+
+    /// The account at `position`, which must sign
+    fn signer(&self, position: usize) -> Result<Pubkey, ExecutorError> {
+        let meta = self.account_meta(position)?;
+        if meta.is_signer {
+            Ok(meta.pubkey)
+        } else {
+            Err(ExecutorError::MissingSignature(meta.pubkey))
+        }
+    }
+
+    fn existing(&self, pubkey: Pubkey) -> Result<&Account, ExecutorError> {
+        self.accounts
+            .get(&pubkey)
+            .ok_or(ExecutorError::AccountNotFound(pubkey))
+    }
+
+    /// A token account of the instruction's program owned by `owner`
+    fn token_account(&self, address: Pubkey, owner: Pubkey) -> Result<&Account, ExecutorError> {
+        let account = self.existing(address)?;
+        if !is_token_account(account)
+            || account.owner != self.instruction.program_id
+            || account.data[32..64] != *owner.as_bytes()
+        {
+            return Err(ExecutorError::InvalidAccount(address));
+        }
+        Ok(account)
+    }
+
+    /// Take `lamports` from a system account without data
+    fn debit_system_account(&mut self, pubkey: Pubkey, lamports: u64) -> Result<(), ExecutorError> {
+        let account = self.existing(pubkey)?;
+        if account.owner != SYSTEM_PROGRAM_ID || !account.data.is_empty() {
+            return Err(ExecutorError::InvalidAccount(pubkey));
+        }
+        if account.lamports < lamports {
+            return Err(ExecutorError::InsufficientFunds {
+                account: pubkey,
+                needed: lamports,
+                available: account.lamports,
+            });
+        }
+        if let Some(account) = self.accounts.get_mut(&pubkey) {
+            account.lamports -= lamports;
+        }
+        Ok(())
+    }
+
+    /// Add `lamports`, creating a system account if needed
+    fn credit(&mut self, pubkey: Pubkey, lamports: u64) {
+        let account = self.accounts.entry(pubkey).or_insert_with(|| Account {
+            pubkey,
+            lamports: 0,
+            data: Vec::new(),
+            owner: SYSTEM_PROGRAM_ID,
+            executable: false,
+            rent_epoch: 0,
+        });
+        account.lamports = account.lamports.saturating_add(lamports);
+    }
+
+    fn update_amount(&mut self, pubkey: Pubkey, update: impl FnOnce(u64) -> u64) {
+        if let Some(account) = self.accounts.get_mut(&pubkey) {
+            let amount = update(read_amount(account));
+            write_amount(account, amount);
+        }
+    }
+}
+
+fn read_array<const N: usize>(data: &[u8], offset: usize) -> Result<[u8; N], ExecutorError> {
+    data.get(offset..offset + N)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(ExecutorError::InvalidData)
+}
+
+fn token_address(owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    crate::domain::programs::token::associated_token_address(owner, mint, token_program)
+}
+
+/// An initialized, empty token account
+fn token_account(address: Pubkey, mint: Pubkey, owner: Pubkey, program: Pubkey) -> Account {
+    let mut data = vec![0; TOKEN_ACCOUNT_LEN];
+    data[..32].copy_from_slice(mint.as_bytes());
+    data[32..64].copy_from_slice(owner.as_bytes());
+    data[STATE] = 1;
+    Account {
+        pubkey: address,
+        lamports: rent_exempt_minimum(TOKEN_ACCOUNT_LEN),
+        data,
+        owner: program,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn is_token_account(account: &Account) -> bool {
+    (account.owner == TOKEN_PROGRAM_ID || account.owner == TOKEN_2022_PROGRAM_ID)
+        && account.data.len() >= TOKEN_ACCOUNT_LEN
+        && account.data[STATE] != 0
+}
+
+fn read_amount(account: &Account) -> u64 {
+    u64::from_le_bytes(account.data[AMOUNT].try_into().unwrap_or_default())
+}
+
+fn write_amount(account: &mut Account, amount: u64) {
+    account.data[AMOUNT].copy_from_slice(&amount.to_le_bytes());
+}
+
+/// Rent reserve of a wrapped SOL account, `None` for other token accounts
+fn read_native_reserve(account: &Account) -> Option<u64> {
+    let is_native = &account.data[IS_NATIVE];
+    (is_native[0] == 1).then(|| u64::from_le_bytes(is_native[4..].try_into().unwrap_or_default()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::flows::{create_ata_and_transfer, wrap_sol_and_swap};
+    use crate::domain::programs::system;
+
+    #[test]
+    fn test_system_instructions() {
+        let payer = Pubkey::new([1; 32]);
+        let new_account = Pubkey::new([2; 32]);
+        let program = Pubkey::new([3; 32]);
+        let mut executor = AccountExecutor::new().with_balance(payer, 10_000);
+
+        executor
+            .execute(&[
+                system::create_account(payer, new_account, 2_000, 16, &program),
+                system::transfer(payer, Pubkey::new([4; 32]), 500),
+            ])
+            .unwrap();
+        assert_eq!(executor.balance(&payer), 7_500);
+        assert_eq!(executor.balance(&Pubkey::new([4; 32])), 500);
+        let created = executor.account(&new_account).unwrap();
+        assert_eq!((created.owner, created.data.len()), (program, 16));
+
+        // The second transfer overdraws, so the first one is rolled back
+        let error = executor
+            .execute(&[
+                system::transfer(payer, new_account, 5_000),
+                system::transfer(payer, new_account, 5_000),
+            ])
+            .unwrap_err();
+        assert_eq!(error.index, 1);
+        assert!(matches!(
+            error.error,
+            ExecutorError::InsufficientFunds {
+                needed: 5_000,
+                available: 2_500,
+                ..
+            }
+        ));
+        assert_eq!(executor.balance(&payer), 7_500);
+
+        let mut unsigned = system::transfer(payer, new_account, 1);
+        unsigned.accounts[0].is_signer = false;
+        assert_eq!(
+            executor.execute(&[unsigned]).unwrap_err().error,
+            ExecutorError::MissingSignature(payer)
+        );
+    }
+
+    #[test]
+    fn test_token_flows() {
+        let sender = Pubkey::new([1; 32]);
+        let recipient = Pubkey::new([2; 32]);
+        let mint = Pubkey::new([3; 32]);
+        let mut executor = AccountExecutor::new()
+            .with_balance(sender, 1_000_000_000)
+            .with_mint(mint, 6, 1_000)
+            .with_token_balance(sender, mint, 1_000);
+
+        let flow = create_ata_and_transfer(sender, recipient, mint, TOKEN_PROGRAM_ID, 250, 6);
+        executor.execute(flow.instructions()).unwrap();
+        // Running it again reuses the recipient's account
+        executor.execute(flow.instructions()).unwrap();
+        let source = token_address(&sender, &mint, &TOKEN_PROGRAM_ID);
+        let destination = token_address(&recipient, &mint, &TOKEN_PROGRAM_ID);
+        assert_eq!(executor.token_balance(&source), Some(500));
+        assert_eq!(executor.token_balance(&destination), Some(500));
+        assert_eq!(
+            executor.balance(&sender),
+            1_000_000_000 - rent_exempt_minimum(TOKEN_ACCOUNT_LEN)
+        );
+
+        let scaled_wrong = create_ata_and_transfer(sender, recipient, mint, TOKEN_PROGRAM_ID, 1, 9);
+        assert_eq!(
+            executor
+                .execute(scaled_wrong.instructions())
+                .unwrap_err()
+                .error,
+            ExecutorError::DecimalsMismatch {
+                expected: 6,
+                found: 9
+            }
+        );
+
+        // Wrapping SOL around a no-op swap hands every lamport back
+        let before = executor.balance(&sender);
+        let swap = system::transfer(sender, sender, 0);
+        executor
+            .execute(wrap_sol_and_swap(sender, 40_000, swap).instructions())
+            .unwrap();
+        assert_eq!(executor.balance(&sender), before);
+        let wrapped = token_address(&sender, &NATIVE_MINT, &TOKEN_PROGRAM_ID);
+        assert!(executor.account(&wrapped).is_none());
+    }
+}
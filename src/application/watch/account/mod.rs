@@ -0,0 +1,199 @@
+//! Live account changes
+//!
+//! [`ChainWatcher::watch_account`] streams each change to one account with an
+//! [`AccountDiff`] against its previous state, so a UI can highlight what just
+//! changed instead of re-rendering the whole account. It subscribes with
+//! `accountSubscribe` and falls back to polling `getAccountInfo`.
+
+use super::{ChainWatcher, Feed, WatchError};
+use crate::domain::diff::AccountDiff;
+use crate::domain::types::Pubkey;
+use crate::infrastructure::rpc::{parse_account, Account};
+use futures::stream::{self, Stream};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+/// New state of a watched account and what changed
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccountChange {
+    pub slot: u64,
+    /// The account, `None` once it is closed
+    pub account: Option<Account>,
+    pub diff: AccountDiff,
+}
+
+impl ChainWatcher {
+    /// Stream every change to the account at `pubkey`
+    ///
+    /// The account is read when the stream is first polled, and each update
+    /// is diffed against the state before it. The stream never ends; drop it
+    /// to unsubscribe.
+    pub fn watch_account(
+        &self,
+        pubkey: Pubkey,
+    ) -> impl Stream<Item = Result<AccountChange, WatchError>> {
+        let params = vec![
+            json!(pubkey.to_base58()),
+            json!({ "encoding": "base64", "commitment": self.config.commitment }),
+        ];
+        let feed = AccountFeed {
+            feed: self.feed("accountSubscribe", params),
+            pubkey,
+            current: None,
+        };
+        stream::unfold(feed, |mut feed| async move {
+            let update = feed.next_update().await;
+            Some((update, feed))
+        })
+    }
+}
+
+struct AccountFeed {
+    feed: Feed,
+    pubkey: Pubkey,
+    /// Last state seen, `None` until the account is first read
+    current: Option<Option<Account>>,
+}
+
+impl AccountFeed {
+    async fn next_update(&mut self) -> Result<AccountChange, WatchError> {
+        if self.current.is_none() {
+            let account = self.feed.rpc_client.get_account_info(&self.pubkey).await?;
+            self.current = Some(account);
+        }
+
+        loop {
+            if let Some(value) = self.feed.notification().await {
+                let (slot, account) = parse_notification(self.pubkey, value)?;
+                if let Some(update) = self.update(slot, account) {
+                    return Ok(update);
+                }
+                continue;
+            }
+
+            self.feed.wait().await;
+            let read = self
+                .feed
+                .rpc_client
+                .get_account_info_with_context(&self.pubkey, None)
+                .await?;
+            if let Some(update) = self.update(read.context.slot, read.value) {
+                return Ok(update);
+            }
+        }
+    }
+
+    /// Record the state read at `slot`, returning the update if it changed
+    fn update(&mut self, slot: u64, account: Option<Account>) -> Option<AccountChange> {
+        let previous = self.current.as_ref().and_then(Option::as_ref);
+        let diff = AccountDiff::compare(previous, account.as_ref());
+        if diff.is_empty() {
+            return None;
+        }
+        self.current = Some(account.clone());
+        Some(AccountChange {
+            slot,
+            account,
+            diff,
+        })
+    }
+}
+
+/// Decode an `accountNotification` result
+fn parse_notification(pubkey: Pubkey, value: Value) -> Result<(u64, Option<Account>), WatchError> {
+    #[derive(Deserialize)]
+    struct Context {
+        slot: u64,
+    }
+
+    #[derive(Deserialize)]
+    struct Notification {
+        context: Context,
+        value: Value,
+    }
+
+    let notification: Notification =
+        serde_json::from_value(value).map_err(|e| WatchError::Parse(e.to_string()))?;
+    let account =
+        parse_account(pubkey, notification.value).map_err(|e| WatchError::Parse(e.to_string()))?;
+    Ok((notification.context.slot, account))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::watch::WatchConfig;
+    use crate::domain::diff::Change;
+    use crate::infrastructure::rpc::mock::MockRpc;
+    use futures::{pin_mut, FutureExt, StreamExt};
+
+    fn account(pubkey: Pubkey, lamports: u64, data: Vec<u8>) -> Account {
+        Account {
+            pubkey,
+            lamports,
+            data,
+            owner: Pubkey::new([9; 32]),
+            executable: false,
+            rent_epoch: 361,
+        }
+    }
+
+    #[test]
+    fn test_parse_notification() {
+        let pubkey = Pubkey::new([4; 32]);
+        let (slot, parsed) = parse_notification(
+            pubkey,
+            json!({
+                "context": { "slot": 5199307 },
+                "value": {
+                    "data": ["AQID", "base64"],
+                    "executable": false,
+                    "lamports": 33594,
+                    "owner": Pubkey::new([9; 32]).to_base58(),
+                    "rentEpoch": 361,
+                    "space": 3
+                }
+            }),
+        )
+        .unwrap();
+        assert_eq!(slot, 5199307);
+        assert_eq!(parsed, Some(account(pubkey, 33594, vec![1, 2, 3])));
+
+        let closed = parse_notification(pubkey, json!({ "context": { "slot": 1 }, "value": null }));
+        assert_eq!(closed.unwrap(), (1, None));
+    }
+
+    #[tokio::test]
+    async fn test_updates_carry_diffs() {
+        let pubkey = Pubkey::new([4; 32]);
+        let rpc = MockRpc::new().with_account(account(pubkey, 1_000, vec![0; 8]));
+        let watcher = ChainWatcher::new(rpc, WatchConfig::new());
+
+        // An unchanged account yields nothing
+        let updates = watcher.watch_account(pubkey);
+        pin_mut!(updates);
+        assert!(updates.next().now_or_never().is_none());
+
+        let mut feed = AccountFeed {
+            feed: watcher.feed("accountSubscribe", Vec::new()),
+            pubkey,
+            current: Some(Some(account(pubkey, 1_000, vec![0; 8]))),
+        };
+        assert!(feed
+            .update(5, Some(account(pubkey, 1_000, vec![0; 8])))
+            .is_none());
+
+        let update = feed.update(
+            6,
+            Some(account(pubkey, 1_000, vec![0, 0, 7, 0, 0, 0, 0, 0])),
+        );
+        let diff = update.unwrap().diff;
+        assert_eq!(diff.data.len(), 1);
+        assert_eq!(diff.data[0].offset, 2);
+
+        let closed = feed.update(7, None).unwrap();
+        assert!(closed.diff.closed);
+        assert_eq!(closed.diff.lamports, Some(Change { old: 1_000, new: 0 }));
+        assert_eq!(feed.current, Some(None));
+    }
+}
@@ -0,0 +1,349 @@
+//! Client-side index of a program's accounts
+//!
+//! [`ChainWatcher::index_program`] loads every account of a program with
+//! `getProgramAccounts`, then keeps the set current from `programSubscribe`
+//! notifications, falling back to rescanning when the websocket is not
+//! available. Queries run against memory, so small account sets such as a
+//! game's leaderboard can be filtered and sorted by [`AccountLayout`] field
+//! without another request. Nodes only notify accounts that match the
+//! filters, so an account edited to stop matching them stays in the index
+//! until it is closed or the next rescan.
+
+use super::{ChainWatcher, Feed, WatchError};
+use crate::domain::diff::AccountDiff;
+use crate::domain::layout::AccountLayout;
+use crate::domain::types::Pubkey;
+use crate::infrastructure::rpc::filters::unknown_field;
+use crate::infrastructure::rpc::{parse_account, Account, Filter, FilterError};
+use borsh::BorshDeserialize;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, VecDeque};
+
+/// A change to the accounts of a [`ProgramIndex`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexUpdate {
+    pub slot: u64,
+    pub pubkey: Pubkey,
+    /// The account, `None` once it left the index
+    pub account: Option<Account>,
+    pub diff: AccountDiff,
+}
+
+/// The accounts of a program matching some filters, kept current
+pub struct ProgramIndex {
+    feed: Feed,
+    program_id: Pubkey,
+    filters: Vec<Filter>,
+    accounts: BTreeMap<Pubkey, Account>,
+    slot: u64,
+    pending: VecDeque<IndexUpdate>,
+}
+
+impl ChainWatcher {
+    /// Index the accounts owned by `program_id` that match every filter
+    ///
+    /// The accounts are read before this returns; call
+    /// [`ProgramIndex::next_update`] to keep the index current.
+    pub async fn index_program(
+        &self,
+        program_id: Pubkey,
+        filters: Vec<Filter>,
+    ) -> Result<ProgramIndex, WatchError> {
+        let params = vec![
+            json!(program_id.to_base58()),
+            json!({
+                "encoding": "base64",
+                "commitment": self.config.commitment,
+                "filters": filters,
+            }),
+        ];
+        let mut index = ProgramIndex {
+            feed: self.feed("programSubscribe", params),
+            program_id,
+            filters,
+            accounts: BTreeMap::new(),
+            slot: 0,
+            pending: VecDeque::new(),
+        };
+        index.rescan().await?;
+        index.pending.clear();
+        Ok(index)
+    }
+}
+
+impl ProgramIndex {
+    pub fn program_id(&self) -> &Pubkey {
+        &self.program_id
+    }
+
+    /// The filters accounts must match
+    pub fn filters(&self) -> &[Filter] {
+        &self.filters
+    }
+
+    /// Slot of the latest change applied
+    pub fn slot(&self) -> u64 {
+        self.slot
+    }
+
+    pub fn len(&self) -> usize {
+        self.accounts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+    }
+
+    /// The indexed account at `pubkey`
+    pub fn get(&self, pubkey: &Pubkey) -> Option<&Account> {
+        self.accounts.get(pubkey)
+    }
+
+    /// Every indexed account, by pubkey
+    pub fn accounts(&self) -> impl Iterator<Item = &Account> {
+        self.accounts.values()
+    }
+
+    /// Indexed accounts matching every filter
+    pub fn filter(&self, filters: &[Filter]) -> Vec<&Account> {
+        self.accounts()
+            .filter(|account| filters.iter().all(|filter| filter.matches(&account.data)))
+            .collect()
+    }
+
+    /// Indexed accounts whose `field` of layout `L` is `value`
+    pub fn field_eq<L: AccountLayout, V: borsh::BorshSerialize>(
+        &self,
+        field: &str,
+        value: V,
+    ) -> Result<Vec<&Account>, FilterError> {
+        Ok(self.filter(&[Filter::field_eq::<L, V>(field, value)?]))
+    }
+
+    /// The `limit` accounts with the largest `field` of layout `L`, largest first
+    ///
+    /// The field must have a fixed offset and size; accounts whose data does
+    /// not decode are skipped.
+    pub fn top_by_field<L: AccountLayout, V: BorshDeserialize + Ord>(
+        &self,
+        field: &str,
+        limit: usize,
+    ) -> Result<Vec<(&Account, V)>, FilterError> {
+        let range = L::field_range(field).ok_or_else(|| unknown_field::<L>(field))?;
+        let mut ranked: Vec<(&Account, V)> = self
+            .accounts()
+            .filter_map(|account| {
+                let bytes = account.data.get(range.clone())?;
+                Some((account, V::try_from_slice(bytes).ok()?))
+            })
+            .collect();
+        ranked.sort_by(|(_, a), (_, b)| b.cmp(a));
+        ranked.truncate(limit);
+        Ok(ranked)
+    }
+
+    /// Wait for the next change to the index and apply it
+    ///
+    /// While polling, the program's accounts are read again every poll
+    /// interval and compared with the index.
+    pub async fn next_update(&mut self) -> Result<IndexUpdate, WatchError> {
+        loop {
+            if let Some(update) = self.pending.pop_front() {
+                return Ok(update);
+            }
+            if let Some(value) = self.feed.notification().await {
+                let (slot, pubkey, account) = parse_notification(value)?;
+                if let Some(update) = self.apply(slot, pubkey, account) {
+                    return Ok(update);
+                }
+                continue;
+            }
+
+            self.feed.wait().await;
+            self.rescan().await?;
+        }
+    }
+
+    /// Read the program's accounts and queue the differences with the index
+    async fn rescan(&mut self) -> Result<(), WatchError> {
+        let slot = self.feed.rpc_client.get_slot().await?;
+        let scanned = self
+            .feed
+            .rpc_client
+            .get_program_accounts(&self.program_id, &self.filters)
+            .await?;
+        let mut scanned: BTreeMap<Pubkey, Account> = scanned
+            .into_iter()
+            .map(|account| (account.pubkey, account))
+            .collect();
+
+        let gone: Vec<Pubkey> = self
+            .accounts
+            .keys()
+            .filter(|pubkey| !scanned.contains_key(*pubkey))
+            .copied()
+            .collect();
+        for pubkey in gone {
+            if let Some(update) = self.apply(slot, pubkey, None) {
+                self.pending.push_back(update);
+            }
+        }
+        while let Some((pubkey, account)) = scanned.pop_first() {
+            if let Some(update) = self.apply(slot, pubkey, Some(account)) {
+                self.pending.push_back(update);
+            }
+        }
+        self.slot = self.slot.max(slot);
+        Ok(())
+    }
+
+    /// Record the state of `pubkey` at `slot`, returning the update if the
+    /// index changed
+    fn apply(
+        &mut self,
+        slot: u64,
+        pubkey: Pubkey,
+        account: Option<Account>,
+    ) -> Option<IndexUpdate> {
+        let account = account.filter(|account| {
+            account.owner == self.program_id
+                && self
+                    .filters
+                    .iter()
+                    .all(|filter| filter.matches(&account.data))
+        });
+        let diff = AccountDiff::compare(self.accounts.get(&pubkey), account.as_ref());
+        if diff.is_empty() {
+            return None;
+        }
+        match &account {
+            Some(account) => self.accounts.insert(pubkey, account.clone()),
+            None => self.accounts.remove(&pubkey),
+        };
+        self.slot = self.slot.max(slot);
+        Some(IndexUpdate {
+            slot,
+            pubkey,
+            account,
+            diff,
+        })
+    }
+}
+
+/// Decode a `programNotification` result
+fn parse_notification(value: Value) -> Result<(u64, Pubkey, Option<Account>), WatchError> {
+    #[derive(Deserialize)]
+    struct Context {
+        slot: u64,
+    }
+
+    #[derive(Deserialize)]
+    struct KeyedAccount {
+        pubkey: String,
+        account: Value,
+    }
+
+    #[derive(Deserialize)]
+    struct Notification {
+        context: Context,
+        value: KeyedAccount,
+    }
+
+    let notification: Notification =
+        serde_json::from_value(value).map_err(|e| WatchError::Parse(e.to_string()))?;
+    let pubkey = Pubkey::from_base58(&notification.value.pubkey)
+        .map_err(|e| WatchError::Parse(e.to_string()))?;
+    let account = parse_account(pubkey, notification.value.account)
+        .map_err(|e| WatchError::Parse(e.to_string()))?;
+    Ok((notification.context.slot, pubkey, account))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::watch::WatchConfig;
+    use crate::infrastructure::rpc::mock::MockRpc;
+
+    crate::account_layout! {
+        #[derive(borsh::BorshSerialize)]
+        struct Player {
+            authority: Pubkey,
+            score: u64,
+        }
+    }
+
+    fn player(seed: u8, score: u64, owner: Pubkey) -> Account {
+        let player = Player {
+            authority: Pubkey::new([seed; 32]),
+            score,
+        };
+        Account {
+            pubkey: Pubkey::new([seed; 32]),
+            lamports: 1_000,
+            data: borsh::to_vec(&player).unwrap(),
+            owner,
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    #[test]
+    fn test_parse_notification() {
+        let program = Pubkey::new([9; 32]);
+        let (slot, pubkey, account) = parse_notification(json!({
+            "context": { "slot": 5208469 },
+            "value": {
+                "pubkey": Pubkey::new([1; 32]).to_base58(),
+                "account": {
+                    "data": ["AQID", "base64"],
+                    "executable": false,
+                    "lamports": 33594,
+                    "owner": program.to_base58(),
+                    "rentEpoch": 636,
+                    "space": 3
+                }
+            }
+        }))
+        .unwrap();
+        assert_eq!(slot, 5208469);
+        assert_eq!(pubkey, Pubkey::new([1; 32]));
+        assert_eq!(account.unwrap().data, [1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_index_scans_then_applies_changes() {
+        let program = Pubkey::new([9; 32]);
+        let rpc = MockRpc::new()
+            .with_account(player(1, 50, program))
+            .with_account(player(2, 80, program))
+            .with_account(player(3, 99, Pubkey::new([8; 32])));
+        let watcher = ChainWatcher::new(rpc, WatchConfig::new());
+        let size = Filter::data_size(40);
+        let mut index = watcher.index_program(program, vec![size]).await.unwrap();
+        assert_eq!(index.len(), 2);
+        assert_eq!(index.slot(), 120);
+
+        let top = index.top_by_field::<Player, u64>("score", 1).unwrap();
+        assert_eq!(top[0].1, 80);
+        let mine = index
+            .field_eq::<Player, Pubkey>("authority", Pubkey::new([1; 32]))
+            .unwrap();
+        assert_eq!(mine[0].pubkey, Pubkey::new([1; 32]));
+
+        let update = index.apply(121, Pubkey::new([1; 32]), Some(player(1, 120, program)));
+        assert!(update.unwrap().diff.data.len() == 1);
+        let top = index.top_by_field::<Player, u64>("score", 2).unwrap();
+        let scores: Vec<u64> = top.into_iter().map(|(_, score)| score).collect();
+        assert_eq!(scores, [120, 80]);
+
+        // Closing hands the account back to the system program
+        let closed = player(2, 0, Pubkey::new([0; 32]));
+        let update = index
+            .apply(122, Pubkey::new([2; 32]), Some(closed))
+            .unwrap();
+        assert_eq!(update.account, None);
+        assert_eq!(index.len(), 1);
+        assert_eq!(index.slot(), 122);
+    }
+}
@@ -0,0 +1,351 @@
+//! Live program logs
+//!
+//! [`ChainWatcher::watch_logs`] streams the logs of transactions mentioning
+//! an address, parsed into [`LogLine`]s, for console panels that show a
+//! program's `msg!` output while the app is used. It subscribes with
+//! `logsSubscribe` and, since there is no `getLogs` method, falls back to
+//! polling `getSignaturesForAddress` and reading each new transaction's logs.
+
+use super::{ChainWatcher, Feed, WatchError};
+use crate::domain::errors::TransactionError;
+use crate::domain::logs::{parse_logs, LogLevel, LogLine};
+use crate::domain::types::{Pubkey, Signature};
+use crate::infrastructure::rpc::SignaturesForAddressConfig;
+use futures::stream::{self, Stream};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+
+/// Most transactions read per poll when the stream falls behind
+pub const MAX_CATCH_UP_TRANSACTIONS: usize = 50;
+
+/// Which transactions to stream logs of and which lines to keep
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogFilter {
+    /// Address the transactions must mention
+    pub mentions: Pubkey,
+    /// Keep only lines written while this program was executing
+    pub program: Option<Pubkey>,
+    /// Keep only lines at this level or above
+    pub min_level: LogLevel,
+}
+
+impl LogFilter {
+    /// Lines written by `program_id` in transactions that invoke it
+    pub fn program(program_id: Pubkey) -> Self {
+        Self {
+            mentions: program_id,
+            program: Some(program_id),
+            min_level: LogLevel::Debug,
+        }
+    }
+
+    /// Every line of transactions mentioning `address`
+    pub fn mentions(address: Pubkey) -> Self {
+        Self {
+            mentions: address,
+            program: None,
+            min_level: LogLevel::Debug,
+        }
+    }
+
+    /// Drop lines below `level`, e.g. [`LogLevel::Info`] for `msg!` output only
+    pub fn min_level(mut self, level: LogLevel) -> Self {
+        self.min_level = level;
+        self
+    }
+
+    /// Whether `line` passes the filter
+    pub fn matches(&self, line: &LogLine) -> bool {
+        line.level() >= self.min_level
+            && self
+                .program
+                .is_none_or(|program| line.program == Some(program))
+    }
+}
+
+/// Filtered logs of one transaction
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogEvent {
+    pub signature: Signature,
+    pub slot: u64,
+    /// Why the transaction failed, if it did
+    pub err: Option<TransactionError>,
+    pub lines: Vec<LogLine>,
+}
+
+impl LogEvent {
+    /// Parse `logs` and keep the lines `filter` matches, or `None` if none do
+    fn filtered(
+        signature: Signature,
+        slot: u64,
+        err: Option<&Value>,
+        logs: &[String],
+        filter: &LogFilter,
+    ) -> Option<Self> {
+        let lines: Vec<LogLine> = parse_logs(logs)
+            .into_iter()
+            .filter(|line| filter.matches(line))
+            .collect();
+        if lines.is_empty() {
+            return None;
+        }
+        Some(Self {
+            signature,
+            slot,
+            err: err
+                .filter(|err| !err.is_null())
+                .map(TransactionError::from_value),
+            lines,
+        })
+    }
+}
+
+impl ChainWatcher {
+    /// Stream the logs of new transactions matching `filter`
+    ///
+    /// Transactions without a matching line are skipped. The stream never
+    /// ends; drop it to unsubscribe.
+    pub fn watch_logs(
+        &self,
+        filter: LogFilter,
+    ) -> impl Stream<Item = Result<LogEvent, WatchError>> {
+        let params = vec![
+            json!({ "mentions": [filter.mentions.to_base58()] }),
+            json!({ "commitment": self.config.commitment }),
+        ];
+        let feed = LogFeed {
+            feed: self.feed("logsSubscribe", params),
+            filter,
+            cursor: None,
+            pending: VecDeque::new(),
+        };
+        stream::unfold(feed, |mut feed| async move {
+            let event = feed.next_event().await;
+            Some((event, feed))
+        })
+    }
+}
+
+struct LogFeed {
+    feed: Feed,
+    filter: LogFilter,
+    /// Newest transaction already seen
+    cursor: Option<Signature>,
+    pending: VecDeque<LogEvent>,
+}
+
+impl LogFeed {
+    async fn next_event(&mut self) -> Result<LogEvent, WatchError> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Ok(event);
+            }
+            if let Some(value) = self.feed.notification().await {
+                let notification = parse_notification(value)?;
+                self.cursor = Some(notification.signature.clone());
+                if let Some(event) = LogEvent::filtered(
+                    notification.signature,
+                    notification.slot,
+                    notification.err.as_ref(),
+                    &notification.logs,
+                    &self.filter,
+                ) {
+                    return Ok(event);
+                }
+                continue;
+            }
+
+            self.feed.wait().await;
+            self.poll().await?;
+        }
+    }
+
+    /// Queue the logs of transactions newer than the cursor
+    ///
+    /// The first poll only records the newest transaction, so polling streams
+    /// live activity like the subscription does.
+    async fn poll(&mut self) -> Result<(), WatchError> {
+        let config = SignaturesForAddressConfig {
+            until: self.cursor.clone(),
+            limit: Some(MAX_CATCH_UP_TRANSACTIONS),
+            ..SignaturesForAddressConfig::default()
+        };
+        let infos = self
+            .feed
+            .rpc_client
+            .get_signatures_for_address(&self.filter.mentions, config)
+            .await?;
+        if self.cursor.is_none() {
+            self.cursor = infos.first().map(|info| info.signature.clone());
+            return Ok(());
+        }
+
+        let new = infos
+            .iter()
+            .take_while(|info| Some(&info.signature) != self.cursor.as_ref());
+        for info in new.collect::<Vec<_>>().into_iter().rev() {
+            let transaction = self
+                .feed
+                .rpc_client
+                .get_transaction(&info.signature)
+                .await?;
+            let meta = transaction.as_ref().and_then(|tx| tx.meta.as_ref());
+            let logs = meta
+                .and_then(|meta| meta.log_messages.as_deref())
+                .unwrap_or_default();
+            if let Some(event) = LogEvent::filtered(
+                info.signature.clone(),
+                info.slot,
+                info.err.as_ref(),
+                logs,
+                &self.filter,
+            ) {
+                self.pending.push_back(event);
+            }
+            self.cursor = Some(info.signature.clone());
+        }
+        Ok(())
+    }
+}
+
+/// A `logsNotification` result
+struct LogsNotification {
+    signature: Signature,
+    slot: u64,
+    err: Option<Value>,
+    logs: Vec<String>,
+}
+
+fn parse_notification(value: Value) -> Result<LogsNotification, WatchError> {
+    #[derive(Deserialize)]
+    struct Context {
+        slot: u64,
+    }
+
+    #[derive(Deserialize)]
+    struct Logs {
+        signature: Signature,
+        #[serde(default)]
+        err: Option<Value>,
+        #[serde(default)]
+        logs: Vec<String>,
+    }
+
+    #[derive(Deserialize)]
+    struct Notification {
+        context: Context,
+        value: Logs,
+    }
+
+    let notification: Notification =
+        serde_json::from_value(value).map_err(|e| WatchError::Parse(e.to_string()))?;
+    Ok(LogsNotification {
+        signature: notification.value.signature,
+        slot: notification.context.slot,
+        err: notification.value.err,
+        logs: notification.value.logs,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::watch::WatchConfig;
+    use crate::domain::logs::LogKind;
+    use crate::infrastructure::rpc::mock::MockRpc;
+    use crate::infrastructure::rpc::ConfirmedTransaction;
+    use std::sync::Arc;
+
+    fn transaction(slot: u64, program: Pubkey, logs: Vec<String>) -> ConfirmedTransaction {
+        serde_json::from_value(json!({
+            "slot": slot,
+            "meta": {
+                "err": null,
+                "fee": 5000,
+                "preBalances": [],
+                "postBalances": [],
+                "logMessages": logs
+            },
+            "transaction": {
+                "signatures": [],
+                "message": {
+                    "accountKeys": [program.to_base58()],
+                    "recentBlockhash": "11111111111111111111111111111111",
+                    "instructions": []
+                }
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_filter_notification() {
+        let program = Pubkey::new([1; 32]);
+        let notification = parse_notification(json!({
+            "context": { "slot": 5208469 },
+            "value": {
+                "signature": Signature::new([3; 64]).to_base58(),
+                "err": { "InstructionError": [0, { "Custom": 6000 }] },
+                "logs": [
+                    "Program 11111111111111111111111111111111 invoke [1]",
+                    "Program 11111111111111111111111111111111 success",
+                    format!("Program {} invoke [1]", program),
+                    "Program log: hello",
+                    format!("Program {} failed: custom program error: 0x1770", program)
+                ]
+            }
+        }))
+        .unwrap();
+
+        let filter = LogFilter::program(program).min_level(LogLevel::Info);
+        let event = LogEvent::filtered(
+            notification.signature,
+            notification.slot,
+            notification.err.as_ref(),
+            &notification.logs,
+            &filter,
+        )
+        .unwrap();
+        assert_eq!(event.slot, 5208469);
+        assert!(event.err.is_some());
+        assert_eq!(event.lines.len(), 2);
+        assert_eq!(event.lines[0].message, "hello");
+        assert_eq!(event.lines[1].kind, LogKind::Failure);
+    }
+
+    #[tokio::test]
+    async fn test_polls_new_transactions() {
+        let program = Pubkey::new([1; 32]);
+        let seen = Signature::new([7; 64]);
+        let fresh = Signature::new([8; 64]);
+        let rpc = MockRpc::new()
+            .with_transaction(seen.clone(), transaction(10, program, vec![]))
+            .with_transaction(
+                fresh.clone(),
+                transaction(
+                    11,
+                    program,
+                    vec![
+                        format!("Program {} invoke [1]", program),
+                        "Program log: Instruction: Increment".to_string(),
+                        format!("Program {} success", program),
+                    ],
+                ),
+            );
+        let watcher = ChainWatcher::from_api(Arc::new(rpc), WatchConfig::new());
+        let mut feed = LogFeed {
+            feed: watcher.feed("logsSubscribe", Vec::new()),
+            filter: LogFilter::program(program).min_level(LogLevel::Info),
+            cursor: Some(seen),
+            pending: VecDeque::new(),
+        };
+
+        let event = feed.next_event().await.unwrap();
+        assert_eq!(event.signature, fresh);
+        assert_eq!(event.slot, 11);
+        assert_eq!(event.lines.len(), 1);
+        assert_eq!(event.lines[0].message, "Instruction: Increment");
+        assert_eq!(feed.cursor, Some(fresh));
+    }
+}
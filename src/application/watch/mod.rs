@@ -0,0 +1,418 @@
+//! Live slot and block streams
+//!
+//! [`ChainWatcher`] turns `slotSubscribe` and `blockSubscribe` notifications
+//! into typed [`SlotUpdate`]s and [`BlockUpdate`]s for explorer views. Many
+//! public endpoints disable `blockSubscribe`, so when the websocket cannot be
+//! opened or drops, the streams carry on by polling `getSlot` and `getBlock`.
+//! Program logs and account changes are streamed the same way by
+//! [`ChainWatcher::watch_logs`] and [`ChainWatcher::watch_account`], and
+//! [`ChainWatcher::index_program`] keeps a program's accounts in memory.
+
+use crate::domain::types::Hash;
+use crate::infrastructure::pubsub::{websocket_url, PubsubClient, Subscription};
+use crate::infrastructure::rpc::{CommitmentLevel, ConfirmedBlock, RpcApi, RpcError};
+use crate::infrastructure::time::sleep;
+use crate::infrastructure::visibility::sleep_visible;
+use futures::stream::{self, Stream};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+
+pub mod account;
+pub mod index;
+pub mod logs;
+
+pub use account::AccountChange;
+pub use index::{IndexUpdate, ProgramIndex};
+pub use logs::{LogEvent, LogFilter};
+
+/// Default delay between polls, about one slot
+pub const DEFAULT_WATCH_INTERVAL: Duration = Duration::from_millis(400);
+
+/// Most blocks fetched per poll when the stream falls behind
+pub const MAX_CATCH_UP_BLOCKS: u64 = 16;
+
+/// Errors yielded by slot and block streams
+///
+/// Errors do not end a stream; the next item retries.
+#[derive(Debug, Clone, Error)]
+pub enum WatchError {
+    #[error("RPC error: {0}")]
+    Rpc(#[from] RpcError),
+
+    #[error("Invalid notification: {0}")]
+    Parse(String),
+}
+
+/// A slot the node has started processing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct SlotUpdate {
+    pub slot: u64,
+    /// Parent slot, only known from websocket notifications
+    #[serde(default)]
+    pub parent: Option<u64>,
+    /// Latest rooted slot, only known from websocket notifications
+    #[serde(default)]
+    pub root: Option<u64>,
+}
+
+/// Summary of a produced block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockUpdate {
+    pub slot: u64,
+    pub blockhash: Hash,
+    pub previous_blockhash: Hash,
+    pub parent_slot: u64,
+    pub block_time: Option<i64>,
+    pub block_height: Option<u64>,
+    pub transaction_count: usize,
+}
+
+impl BlockUpdate {
+    /// Summarize the block at `slot`
+    pub fn new(slot: u64, block: &ConfirmedBlock) -> Self {
+        Self {
+            slot,
+            blockhash: block.blockhash,
+            previous_blockhash: block.previous_blockhash,
+            parent_slot: block.parent_slot,
+            block_time: block.block_time,
+            block_height: block.block_height,
+            transaction_count: block.signatures.len(),
+        }
+    }
+}
+
+/// Settings for slot and block streams
+#[derive(Debug, Clone)]
+pub struct WatchConfig {
+    /// Websocket endpoint, polling only if absent
+    pub websocket_url: Option<String>,
+    /// Commitment of streamed blocks
+    pub commitment: CommitmentLevel,
+    pub poll_interval: Duration,
+    /// Stop polling while the browser tab is hidden
+    pub pause_when_hidden: bool,
+}
+
+impl WatchConfig {
+    /// Create a polling-only configuration for confirmed blocks
+    pub fn new() -> Self {
+        Self {
+            websocket_url: None,
+            commitment: CommitmentLevel::Confirmed,
+            poll_interval: DEFAULT_WATCH_INTERVAL,
+            pause_when_hidden: true,
+        }
+    }
+
+    /// Create a configuration using the websocket paired with an HTTP endpoint
+    pub fn for_endpoint(endpoint: &str) -> Self {
+        Self::new().websocket_url(websocket_url(endpoint))
+    }
+
+    /// Set the websocket endpoint
+    pub fn websocket_url(mut self, url: impl Into<String>) -> Self {
+        self.websocket_url = Some(url.into());
+        self
+    }
+
+    /// Set the commitment of streamed blocks
+    pub fn commitment(mut self, commitment: CommitmentLevel) -> Self {
+        self.commitment = commitment;
+        self
+    }
+
+    /// Set the delay between polls
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Keep polling while the browser tab is hidden, or not
+    ///
+    /// Paused streams poll again as soon as the tab is shown.
+    pub fn pause_when_hidden(mut self, pause: bool) -> Self {
+        self.pause_when_hidden = pause;
+        self
+    }
+}
+
+impl Default for WatchConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Streams new slots and blocks
+pub struct ChainWatcher {
+    rpc_client: Arc<dyn RpcApi>,
+    config: WatchConfig,
+}
+
+impl ChainWatcher {
+    /// Create a watcher
+    pub fn new(rpc_client: impl RpcApi + 'static, config: WatchConfig) -> Self {
+        Self::from_api(Arc::new(rpc_client), config)
+    }
+
+    /// Create a watcher from a shared RPC implementation
+    pub fn from_api(rpc_client: Arc<dyn RpcApi>, config: WatchConfig) -> Self {
+        Self { rpc_client, config }
+    }
+
+    /// Get the configuration
+    pub fn config(&self) -> &WatchConfig {
+        &self.config
+    }
+
+    /// Stream every new slot
+    ///
+    /// The stream never ends; drop it to unsubscribe.
+    pub fn watch_slots(&self) -> impl Stream<Item = Result<SlotUpdate, WatchError>> {
+        let feed = self.feed("slotSubscribe", Vec::new());
+        stream::unfold(feed, |mut feed| async move {
+            let update = feed.next_slot().await;
+            Some((update, feed))
+        })
+    }
+
+    /// Stream every new block at the configured commitment
+    ///
+    /// Polling skips ahead to the newest [`MAX_CATCH_UP_BLOCKS`] blocks when
+    /// it falls behind. The stream never ends; drop it to unsubscribe.
+    pub fn watch_blocks(&self) -> impl Stream<Item = Result<BlockUpdate, WatchError>> {
+        let params = vec![
+            json!("all"),
+            json!({
+                "commitment": self.config.commitment,
+                "encoding": "json",
+                "transactionDetails": "signatures",
+                "showRewards": false,
+                "maxSupportedTransactionVersion": 0
+            }),
+        ];
+        let feed = self.feed("blockSubscribe", params);
+        stream::unfold(feed, |mut feed| async move {
+            let update = feed.next_block().await;
+            Some((update, feed))
+        })
+    }
+
+    fn feed(&self, method: &'static str, params: Vec<Value>) -> Feed {
+        Feed {
+            rpc_client: self.rpc_client.clone(),
+            config: self.config.clone(),
+            method,
+            params,
+            state: FeedState::Connecting,
+            last_slot: None,
+            polled: false,
+            pending: VecDeque::new(),
+        }
+    }
+}
+
+enum FeedState {
+    Connecting,
+    Subscribed(Box<Subscription>),
+    Polling,
+}
+
+/// State shared by the slot and block streams
+struct Feed {
+    rpc_client: Arc<dyn RpcApi>,
+    config: WatchConfig,
+    method: &'static str,
+    params: Vec<Value>,
+    state: FeedState,
+    last_slot: Option<u64>,
+    polled: bool,
+    pending: VecDeque<BlockUpdate>,
+}
+
+impl Feed {
+    /// Next websocket notification, or `None` once the feed is polling
+    async fn notification(&mut self) -> Option<Value> {
+        if let FeedState::Connecting = self.state {
+            self.state = match &self.config.websocket_url {
+                Some(url) => match PubsubClient::new(url)
+                    .subscribe(self.method, self.params.clone())
+                    .await
+                {
+                    Ok(subscription) => FeedState::Subscribed(Box::new(subscription)),
+                    Err(_) => FeedState::Polling,
+                },
+                None => FeedState::Polling,
+            };
+        }
+
+        if let FeedState::Subscribed(subscription) = &mut self.state {
+            match subscription.next().await {
+                Some(Ok(value)) => return Some(value),
+                _ => self.state = FeedState::Polling,
+            }
+        }
+        None
+    }
+
+    /// Wait out the poll interval, except before the first poll
+    async fn wait(&mut self) {
+        if self.polled && self.config.pause_when_hidden {
+            sleep_visible(self.config.poll_interval).await;
+        } else if self.polled {
+            sleep(self.config.poll_interval).await;
+        }
+        self.polled = true;
+    }
+
+    async fn next_slot(&mut self) -> Result<SlotUpdate, WatchError> {
+        loop {
+            if let Some(value) = self.notification().await {
+                let update = parse_slot(value)?;
+                self.last_slot = Some(update.slot);
+                return Ok(update);
+            }
+
+            self.wait().await;
+            let slot = self.rpc_client.get_slot().await?;
+            if self.last_slot.is_none_or(|last| slot > last) {
+                self.last_slot = Some(slot);
+                return Ok(SlotUpdate {
+                    slot,
+                    parent: None,
+                    root: None,
+                });
+            }
+        }
+    }
+
+    async fn next_block(&mut self) -> Result<BlockUpdate, WatchError> {
+        loop {
+            if let Some(update) = self.pending.pop_front() {
+                return Ok(update);
+            }
+            if let Some(value) = self.notification().await {
+                // Notifications without a block report a failure to load it
+                if let Some(update) = parse_block(value)? {
+                    self.last_slot = Some(update.slot);
+                    return Ok(update);
+                }
+                continue;
+            }
+
+            self.wait().await;
+            let tip = self.rpc_client.get_slot().await?;
+            let first = match self.last_slot {
+                Some(last) => (last + 1).max(tip.saturating_sub(MAX_CATCH_UP_BLOCKS - 1)),
+                None => tip,
+            };
+            for slot in first..=tip {
+                if let Some(block) = self.rpc_client.get_block(slot).await? {
+                    self.pending.push_back(BlockUpdate::new(slot, &block));
+                }
+                self.last_slot = Some(slot);
+            }
+        }
+    }
+}
+
+fn parse_slot(value: Value) -> Result<SlotUpdate, WatchError> {
+    serde_json::from_value(value).map_err(|e| WatchError::Parse(e.to_string()))
+}
+
+/// Decode a `blockNotification` result
+fn parse_block(mut value: Value) -> Result<Option<BlockUpdate>, WatchError> {
+    #[derive(Deserialize)]
+    struct BlockNotification {
+        slot: u64,
+        #[serde(default)]
+        block: Option<ConfirmedBlock>,
+    }
+
+    let notification: BlockNotification = serde_json::from_value(value["value"].take())
+        .map_err(|e| WatchError::Parse(e.to_string()))?;
+    Ok(notification
+        .block
+        .map(|block| BlockUpdate::new(notification.slot, &block)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::types::Signature;
+    use crate::infrastructure::rpc::mock::MockRpc;
+    use futures::{pin_mut, StreamExt};
+
+    fn block(parent_slot: u64, transactions: usize) -> ConfirmedBlock {
+        ConfirmedBlock {
+            blockhash: Hash::new([1; 32]),
+            previous_blockhash: Hash::new([2; 32]),
+            parent_slot,
+            block_time: Some(1_700_000_000),
+            block_height: Some(100),
+            signatures: vec![Signature::new([3; 64]); transactions],
+        }
+    }
+
+    #[test]
+    fn test_parse_notifications() {
+        let slot = parse_slot(json!({ "parent": 75, "root": 44, "slot": 76 })).unwrap();
+        assert_eq!(
+            slot,
+            SlotUpdate {
+                slot: 76,
+                parent: Some(75),
+                root: Some(44)
+            }
+        );
+
+        let update = parse_block(json!({
+            "context": { "slot": 112301554 },
+            "value": {
+                "slot": 112301554,
+                "err": null,
+                "block": {
+                    "blockhash": Hash::new([1; 32]).to_base58(),
+                    "previousBlockhash": Hash::new([2; 32]).to_base58(),
+                    "parentSlot": 112301553,
+                    "blockTime": 1639926816,
+                    "blockHeight": 101210751,
+                    "signatures": [Signature::new([3; 64]).to_base58()]
+                }
+            }
+        }))
+        .unwrap()
+        .unwrap();
+        assert_eq!(update.slot, 112301554);
+        assert_eq!(update.parent_slot, 112301553);
+        assert_eq!(update.transaction_count, 1);
+
+        let missing = json!({ "value": { "slot": 5, "err": "BlockStoreError", "block": null } });
+        assert_eq!(parse_block(missing).unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_falls_back_to_polling() {
+        let rpc = MockRpc::new().with_block(120, block(119, 2));
+        let config = WatchConfig::new()
+            .websocket_url("ws://127.0.0.1:1")
+            .poll_interval(Duration::from_millis(1));
+        let watcher = ChainWatcher::new(rpc, config);
+
+        let slots = watcher.watch_slots();
+        pin_mut!(slots);
+        let slot = slots.next().await.unwrap().unwrap();
+        assert_eq!(slot.slot, 120);
+        assert_eq!(slot.parent, None);
+
+        let blocks = watcher.watch_blocks();
+        pin_mut!(blocks);
+        let update = blocks.next().await.unwrap().unwrap();
+        assert_eq!(update, BlockUpdate::new(120, &block(119, 2)));
+        assert_eq!(update.transaction_count, 2);
+    }
+}
@@ -0,0 +1,91 @@
+//! `cargo gloo-solana` developer commands
+//!
+//! `cargo gloo-solana gen-client --idl counter.json [--out src/counter.rs]`
+//! generates a typed client module for an Anchor program from its IDL.
+
+use gloo_solana::application::codegen::generate_client;
+use gloo_solana::domain::idl::Idl;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+const USAGE: &str = "Usage: cargo gloo-solana gen-client --idl <IDL.json> [--out <FILE.rs>]
+
+Generate a typed client module for an Anchor program.
+
+Options:
+    --idl <PATH>    Anchor IDL JSON file
+    --out <PATH>    Write the module to a file instead of stdout";
+
+struct GenClientArgs {
+    idl: PathBuf,
+    out: Option<PathBuf>,
+}
+
+fn main() -> ExitCode {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    // Cargo passes the subcommand name through when run as `cargo gloo-solana`
+    if args.first().map(String::as_str) == Some("gloo-solana") {
+        args.remove(0);
+    }
+
+    let result = match args.split_first() {
+        Some((command, rest)) if command == "gen-client" => {
+            parse_gen_client(rest).and_then(|args| gen_client(&args))
+        }
+        Some((flag, _)) if flag == "-h" || flag == "--help" => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Some((command, _)) => Err(format!("unknown command `{}`\n\n{}", command, USAGE)),
+        None => Err(USAGE.to_string()),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("error: {}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn parse_gen_client(args: &[String]) -> Result<GenClientArgs, String> {
+    let mut idl = None;
+    let mut out = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .map(PathBuf::from)
+                .ok_or_else(|| format!("{} needs a path", name))
+        };
+        match arg.as_str() {
+            "--idl" => idl = Some(value("--idl")?),
+            "--out" | "-o" => out = Some(value("--out")?),
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            other => return Err(format!("unexpected argument `{}`\n\n{}", other, USAGE)),
+        }
+    }
+    Ok(GenClientArgs {
+        idl: idl.ok_or_else(|| format!("--idl is required\n\n{}", USAGE))?,
+        out,
+    })
+}
+
+fn gen_client(args: &GenClientArgs) -> Result<(), String> {
+    let json = fs::read_to_string(&args.idl)
+        .map_err(|e| format!("cannot read {}: {}", args.idl.display(), e))?;
+    let idl = Idl::from_json(&json).map_err(|e| format!("{}: {}", args.idl.display(), e))?;
+    let code = generate_client(&idl).map_err(|e| e.to_string())?;
+
+    match &args.out {
+        Some(path) => {
+            fs::write(path, code).map_err(|e| format!("cannot write {}: {}", path.display(), e))?;
+            eprintln!("Wrote {}", path.display());
+        }
+        None => print!("{}", code),
+    }
+    Ok(())
+}
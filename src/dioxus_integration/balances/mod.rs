@@ -0,0 +1,88 @@
+//! Batched balance hooks for portfolio views
+//!
+//! [`use_balances`] loads the lamports of many accounts with one
+//! `getMultipleAccounts` request per hundred keys, instead of a `getBalance`
+//! per key, and refreshes them on an interval, paused while the tab is
+//! hidden. Each key has its own [`Query`] state, so rows render as soon as
+//! their batch lands.
+
+use super::store::Query;
+use crate::domain::types::Pubkey;
+use crate::infrastructure::rpc::{AccountReadConfig, MAX_ACCOUNTS_PER_REQUEST};
+use crate::infrastructure::visibility::sleep_visible;
+use dioxus::prelude::*;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// How often [`use_balances`] refetches
+pub const DEFAULT_BALANCE_REFRESH: Duration = Duration::from_secs(30);
+
+/// Balances of `pubkeys`, refreshed every [`DEFAULT_BALANCE_REFRESH`]
+pub fn use_balances(pubkeys: Vec<Pubkey>) -> Signal<HashMap<Pubkey, Query<u64>>> {
+    use_balances_with_refresh(pubkeys, Some(DEFAULT_BALANCE_REFRESH))
+}
+
+/// Balances of `pubkeys`, refreshed every `refresh` or fetched once with `None`
+///
+/// Keys start as [`Query::Loading`]. A failed refresh only marks keys that
+/// have not loaded yet as failed; loaded keys keep their last balance.
+/// Passing a different list restarts the fetch loop.
+pub fn use_balances_with_refresh(
+    pubkeys: Vec<Pubkey>,
+    refresh: Option<Duration>,
+) -> Signal<HashMap<Pubkey, Query<u64>>> {
+    let context = use_context::<super::SolanaContext>();
+    let mut balances = use_signal(HashMap::new);
+
+    let _ = use_resource(use_reactive!(|(pubkeys, refresh)| {
+        let client = context.client.clone();
+        async move {
+            balances.set(
+                pubkeys
+                    .iter()
+                    .map(|pubkey| (*pubkey, Query::Loading))
+                    .collect(),
+            );
+            // Only lamports are shown, so skip the account data
+            let config = AccountReadConfig::new().data_slice(0, 0);
+            loop {
+                for chunk in pubkeys.chunks(MAX_ACCOUNTS_PER_REQUEST) {
+                    let result = client
+                        .get_multiple_accounts_with_config(chunk, &config)
+                        .await
+                        .map(|response| response.value);
+                    let mut states = balances.write();
+                    match result {
+                        Ok(accounts) => {
+                            for (pubkey, account) in chunk.iter().zip(accounts) {
+                                let lamports = account.map_or(0, |account| account.lamports);
+                                states.insert(*pubkey, Query::Ready(lamports));
+                            }
+                        }
+                        Err(e) => {
+                            for pubkey in chunk {
+                                let state = states.entry(*pubkey).or_insert(Query::Loading);
+                                if state.value().is_none() {
+                                    *state = Query::Failed(e.to_string());
+                                }
+                            }
+                        }
+                    }
+                }
+                match refresh {
+                    Some(interval) => sleep_visible(interval).await,
+                    None => break,
+                }
+            }
+        }
+    }));
+
+    balances
+}
+
+/// Sum of the loaded balances, with whether any key is still loading
+pub fn total_balance(balances: &HashMap<Pubkey, Query<u64>>) -> (u64, bool) {
+    let total = balances.values().filter_map(Query::value).sum();
+    let loading = balances.values().any(Query::is_loading);
+    (total, loading)
+}
@@ -0,0 +1,44 @@
+//! Epoch progress hook
+//!
+//! [`use_epoch_info`] polls `getEpochInfo` through a [`ClockService`] that
+//! lives as long as the component, so the time left in the epoch is estimated
+//! with the slot duration measured while the page is open rather than the
+//! nominal 400ms. Polling pauses while the tab is hidden.
+
+use super::store::Query;
+use crate::application::services::clock::{ClockService, EpochStatus};
+use crate::infrastructure::visibility::sleep_visible;
+use dioxus::prelude::*;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How often [`use_epoch_info`] refetches
+pub const DEFAULT_EPOCH_REFRESH: Duration = Duration::from_secs(20);
+
+/// The current epoch, refreshed every [`DEFAULT_EPOCH_REFRESH`]
+///
+/// A failed refresh keeps the last status; only a failed first fetch shows
+/// as [`Query::Failed`].
+pub fn use_epoch_info() -> Signal<Query<EpochStatus>> {
+    let context = use_context::<super::SolanaContext>();
+    let mut status = use_signal(|| Query::Loading);
+
+    use_hook(move || {
+        let clock = ClockService::from_api(Arc::new((*context.client).clone()))
+            .resync_interval(DEFAULT_EPOCH_REFRESH);
+        spawn(async move {
+            loop {
+                match clock.epoch_status().await {
+                    Ok(epoch) => status.set(Query::Ready(epoch)),
+                    Err(e) if status.peek().value().is_none() => {
+                        status.set(Query::Failed(e.to_string()))
+                    }
+                    Err(_) => {}
+                }
+                sleep_visible(DEFAULT_EPOCH_REFRESH).await;
+            }
+        })
+    });
+
+    status
+}
@@ -2,12 +2,97 @@
 //!
 //! This module provides basic Dioxus components for integrating Solana
 //! functionality into Dioxus applications.
+//!
+//! Components are safe to render on a server: data is loaded through
+//! [`use_solana_resource`], which runs during SSR and hydrates on the client
+//! when the `fullstack` feature is enabled, and browser-only UI (wallets,
+//! storage) belongs inside [`ClientOnly`].
+
+#[cfg(feature = "dioxus")]
+pub mod balances;
+#[cfg(feature = "dioxus")]
+pub mod epoch;
+#[cfg(feature = "dioxus")]
+pub mod store;
 
 #[cfg(feature = "dioxus")]
 use dioxus::prelude::*;
 #[cfg(feature = "dioxus")]
+use serde::{de::DeserializeOwned, Serialize};
+#[cfg(feature = "dioxus")]
+use std::future::Future;
+#[cfg(feature = "dioxus")]
 use std::rc::Rc;
 
+#[cfg(feature = "dioxus")]
+/// Whether this build renders in a browser rather than on a server or desktop
+pub const fn is_browser() -> bool {
+    cfg!(target_arch = "wasm32")
+}
+
+#[cfg(feature = "dioxus")]
+/// Load component data in a way that survives server-side rendering
+///
+/// With the `fullstack` feature the future is awaited during SSR and its
+/// result is serialized into the page, so the client hydrates without
+/// refetching. Otherwise it runs as an ordinary client-side resource.
+pub fn use_solana_resource<T, F>(
+    fetch: impl FnMut() -> F + 'static,
+) -> Result<Resource<T>, RenderError>
+where
+    T: Serialize + DeserializeOwned + 'static,
+    F: Future<Output = T> + 'static,
+{
+    #[cfg(feature = "fullstack")]
+    {
+        use_server_future(fetch)
+    }
+    #[cfg(not(feature = "fullstack"))]
+    {
+        Ok(use_resource(fetch))
+    }
+}
+
+#[cfg(feature = "dioxus")]
+/// Whether the component has mounted on the client
+///
+/// Always `false` while rendering on the server, so markup produced during
+/// SSR matches the first client render and hydration does not diverge.
+pub fn use_client_mounted() -> bool {
+    let mut mounted = use_signal(|| false);
+    use_effect(move || mounted.set(true));
+    mounted()
+}
+
+#[cfg(feature = "dioxus")]
+/// A cancel token that is cancelled when the component unmounts
+///
+/// Hand it to [`SolanaRpcClient::with_cancel_token`] or a confirmation config
+/// so scans and confirmation loops started by the component stop with it.
+///
+/// [`SolanaRpcClient::with_cancel_token`]: crate::SolanaRpcClient::with_cancel_token
+pub fn use_cancel_token() -> crate::CancelToken {
+    let token = use_hook(crate::CancelToken::new);
+    let unmounted = token.clone();
+    use_drop(move || unmounted.cancel());
+    token
+}
+
+#[cfg(feature = "dioxus")]
+/// Render children only once mounted in the client
+///
+/// Wrap anything touching browser-only APIs (wallet adapters, `window`,
+/// `localStorage`) so server rendering shows `fallback` instead of panicking.
+#[allow(non_snake_case)]
+#[component]
+pub fn ClientOnly(fallback: Option<Element>, children: Element) -> Element {
+    if use_client_mounted() {
+        children
+    } else {
+        fallback.unwrap_or_else(|| rsx! {})
+    }
+}
+
 #[cfg(feature = "dioxus")]
 /// Solana connection context for Dioxus applications
 #[derive(Clone)]
@@ -15,34 +100,58 @@ pub struct SolanaContext {
     pub client: Rc<crate::SolanaRpcClient>,
     pub network: crate::Network,
     pub authority: crate::Pubkey,
+    /// Clients for every network the app reads, including `network`
+    pub networks: crate::NetworkRegistry,
 }
 
 #[cfg(feature = "dioxus")]
 /// Simple Solana provider component
+///
+/// `networks` holds clients for other clusters the app reads from, such as
+/// mainnet prices in a devnet app; the provider adds its own client for
+/// `network` unless one is given. If that client cannot be built, the error
+/// goes to the nearest error boundary instead of rendering the children.
 #[allow(non_snake_case)]
 #[component]
 pub fn SolanaProvider(
     network: crate::Network,
     authority: crate::Pubkey,
+    #[props(default)] networks: crate::NetworkRegistry,
     children: Element,
 ) -> Element {
     let client = use_signal(|| {
-        Rc::new(
-            crate::RpcClientBuilder::new(network.endpoint())
-                .commitment(crate::CommitmentLevel::Confirmed)
-                .build(),
-        )
+        crate::RpcClientBuilder::new(network.endpoint())
+            .commitment(crate::CommitmentLevel::Confirmed)
+            .build()
+            .map(Rc::new)
     });
+    let client = client.read().clone()?;
 
-    use_context_provider(move || SolanaContext {
-        client: client.read().clone(),
-        network,
-        authority,
+    use_context_provider(move || {
+        let mut networks = networks;
+        if !networks.contains(&network) {
+            networks.insert(network.clone(), std::sync::Arc::new((*client).clone()));
+        }
+        SolanaContext {
+            client,
+            network,
+            authority,
+            networks,
+        }
     });
 
     children
 }
 
+#[cfg(feature = "dioxus")]
+/// The client the enclosing [`SolanaProvider`] holds for `network`
+///
+/// Lets a component read another cluster than the app's, e.g. mainnet price
+/// data while operating on devnet. `None` if the provider has no client for it.
+pub fn use_network_client(network: &crate::Network) -> Option<std::sync::Arc<dyn crate::RpcApi>> {
+    use_context::<SolanaContext>().networks.get(network)
+}
+
 #[cfg(feature = "dioxus")]
 /// Component to display account balance
 #[allow(non_snake_case)]
@@ -50,10 +159,10 @@ pub fn BalanceDisplay(pubkey: crate::Pubkey) -> Element {
     let solana_context = use_context::<SolanaContext>();
 
     let client = solana_context.client.clone();
-    let balance = use_resource(move || {
+    let balance = use_solana_resource(move || {
         let client = client.clone();
-        async move { client.get_balance(&pubkey).await }
-    });
+        async move { client.get_balance(&pubkey).await.map_err(|e| e.to_string()) }
+    })?;
 
     rsx! {
         div { class: "balance-display",
@@ -86,10 +195,15 @@ pub fn NetworkInfo() -> Element {
     let solana_context = use_context::<SolanaContext>();
 
     let client = solana_context.client.clone();
-    let blockhash = use_resource(move || {
+    let blockhash = use_solana_resource(move || {
         let client = client.clone();
-        async move { client.get_latest_blockhash().await }
-    });
+        async move {
+            client
+                .get_latest_blockhash()
+                .await
+                .map_err(|e| e.to_string())
+        }
+    })?;
 
     rsx! {
         div { class: "network-info",
@@ -164,6 +278,706 @@ pub fn NetworkSelector(on_network_change: EventHandler<crate::Network>) -> Eleme
     }
 }
 
+#[cfg(feature = "dioxus")]
+/// Wallets announced through the Wallet Standard, updated as they register
+///
+/// Discovery runs in the browser only; elsewhere the list stays empty. It
+/// stops when the calling component unmounts.
+pub fn use_wallet_standard() -> Signal<Vec<crate::infrastructure::wallet_standard::WalletInfo>> {
+    #[allow(unused_mut)]
+    let mut wallets = use_signal(Vec::new);
+
+    #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+    use_hook(move || {
+        crate::infrastructure::wallet_standard::discover(move |info| {
+            wallets.write().push(info.clone());
+        })
+        .ok()
+        .map(Rc::new)
+    });
+
+    wallets
+}
+
+#[cfg(feature = "dioxus")]
+/// List the Solana wallets found through the Wallet Standard
+///
+/// Each wallet is shown with its icon; `on_select` receives the chosen one.
+/// Render it inside [`ClientOnly`] when using server-side rendering.
+#[allow(non_snake_case)]
+#[component]
+pub fn WalletPicker(
+    on_select: EventHandler<crate::infrastructure::wallet_standard::WalletInfo>,
+) -> Element {
+    let wallets = use_wallet_standard();
+
+    let options = wallets
+        .read()
+        .iter()
+        .filter(|wallet| wallet.is_solana_wallet())
+        .cloned()
+        .map(|wallet| {
+            let name = wallet.name.clone();
+            let icon = wallet.icon.clone();
+            rsx! {
+                button {
+                    key: "{name}",
+                    class: "wallet-option",
+                    onclick: move |_| on_select.call(wallet.clone()),
+                    img { class: "wallet-icon", src: "{icon}", alt: "" }
+                    span { class: "wallet-name", "{name}" }
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+
+    rsx! {
+        div { class: "wallet-picker",
+            h3 { "Select a Wallet" }
+            if options.is_empty() {
+                div { class: "wallet-picker-empty", "No wallets detected" }
+            }
+            {options.into_iter()}
+        }
+    }
+}
+
+#[cfg(feature = "dioxus")]
+/// How long a remembered wallet has to register before restoring gives up
+pub const WALLET_RESTORE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(3);
+
+#[cfg(feature = "dioxus")]
+/// The user's wallet connection, from [`use_wallet_connection`]
+#[derive(Clone)]
+pub struct WalletConnection {
+    state: Signal<crate::infrastructure::wallet_standard::WalletConnectionState>,
+    wallets: Signal<Vec<crate::infrastructure::wallet_standard::WalletInfo>>,
+    store: crate::infrastructure::wallet_standard::WalletSessionStore,
+    #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+    discovery: Option<Rc<crate::infrastructure::wallet_standard::WalletDiscovery>>,
+}
+
+#[cfg(feature = "dioxus")]
+impl WalletConnection {
+    /// The connection state, for connect buttons and account badges
+    pub fn state(&self) -> Signal<crate::infrastructure::wallet_standard::WalletConnectionState> {
+        self.state
+    }
+
+    /// Wallets announced through the Wallet Standard so far
+    pub fn wallets(&self) -> Signal<Vec<crate::infrastructure::wallet_standard::WalletInfo>> {
+        self.wallets
+    }
+
+    /// Connect to the wallet called `wallet`, prompting the user
+    pub fn connect(&self, wallet: &str) {
+        self.request(wallet.to_string(), false);
+    }
+
+    /// Disconnect and forget the session, so the next start does not restore it
+    pub fn disconnect(&self) {
+        use crate::infrastructure::wallet_standard::WalletConnectionState;
+
+        let mut state = self.state;
+        #[allow(unused_variables)]
+        let previous = state.replace(WalletConnectionState::Disconnected);
+        let _ = self.store.clear();
+
+        #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+        if let WalletConnectionState::Connected(session) = previous {
+            if let Some(object) = self.wallet_object(&session.wallet) {
+                spawn(async move {
+                    let _ = crate::infrastructure::wallet_standard::disconnect(&object).await;
+                });
+            }
+        }
+    }
+
+    /// Have the connected account sign `message`, prompting the user
+    ///
+    /// Bytes that decode as a transaction are refused, see
+    /// [`check_message`](crate::infrastructure::wallet_standard::check_message).
+    pub async fn sign_message(
+        &self,
+        message: &[u8],
+    ) -> Result<crate::Signature, crate::infrastructure::wallet_standard::WalletStandardError> {
+        crate::infrastructure::wallet_standard::check_message(message)?;
+        let session = self.session()?;
+        #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+        {
+            use crate::infrastructure::wallet_standard::{sign_message, WalletStandardError};
+
+            let object = self
+                .wallet_object(&session.wallet)
+                .ok_or(WalletStandardError::NotConnected)?;
+            sign_message(&object, &session.public_key, message).await
+        }
+        #[cfg(not(all(target_arch = "wasm32", feature = "wasm")))]
+        {
+            let _ = session;
+            Err(crate::infrastructure::wallet_standard::WalletStandardError::NoWindow)
+        }
+    }
+
+    /// Have the connected account sign `transaction`, prompting the user
+    ///
+    /// The wallet may only add signatures: a transaction whose message it
+    /// changed, e.g. by adding a priority fee, is refused rather than sent
+    /// unreviewed.
+    pub async fn sign_transaction(
+        &self,
+        transaction: &crate::domain::message::Transaction,
+    ) -> Result<
+        crate::domain::message::Transaction,
+        crate::infrastructure::wallet_standard::WalletStandardError,
+    > {
+        let session = self.session()?;
+        let bytes = transaction.serialize()?;
+        #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+        {
+            use crate::domain::message::Transaction;
+            use crate::infrastructure::wallet_standard::{sign_transaction, WalletStandardError};
+
+            let object = self
+                .wallet_object(&session.wallet)
+                .ok_or(WalletStandardError::NotConnected)?;
+            let signed = sign_transaction(&object, &session.public_key, &bytes).await?;
+            let signed = Transaction::deserialize(&signed)?;
+            if signed.message != transaction.message {
+                return Err(WalletStandardError::TransactionChanged);
+            }
+            Ok(signed)
+        }
+        #[cfg(not(all(target_arch = "wasm32", feature = "wasm")))]
+        {
+            let _ = (session, bytes);
+            Err(crate::infrastructure::wallet_standard::WalletStandardError::NoWindow)
+        }
+    }
+
+    /// The connected session, which signing requires
+    fn session(
+        &self,
+    ) -> Result<
+        crate::infrastructure::wallet_standard::WalletSession,
+        crate::infrastructure::wallet_standard::WalletStandardError,
+    > {
+        use crate::infrastructure::wallet_standard::{WalletConnectionState, WalletStandardError};
+
+        match &*self.state.peek() {
+            WalletConnectionState::Connected(session) => Ok(session.clone()),
+            _ => Err(WalletStandardError::NotConnected),
+        }
+    }
+
+    #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+    fn wallet_object(&self, wallet: &str) -> Option<wasm_bindgen::JsValue> {
+        self.discovery.as_ref()?.wallet_object(wallet)
+    }
+
+    /// Ask `wallet` for its account, silently when restoring a session
+    fn request(&self, wallet: String, silent: bool) {
+        use crate::infrastructure::wallet_standard::WalletConnectionState;
+
+        let mut state = self.state;
+        #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+        {
+            use crate::infrastructure::wallet_standard::WalletSession;
+
+            let Some(object) = self.wallet_object(&wallet) else {
+                state.set(WalletConnectionState::Disconnected);
+                return;
+            };
+            if !silent {
+                state.set(WalletConnectionState::Connecting {
+                    wallet: wallet.clone(),
+                });
+            }
+            let store = self.store.clone();
+            spawn(async move {
+                let accounts =
+                    crate::infrastructure::wallet_standard::connect(&object, silent).await;
+                match accounts.ok().and_then(|accounts| accounts.first().copied()) {
+                    Some(public_key) => {
+                        let session = WalletSession { wallet, public_key };
+                        let _ = store.save(&session);
+                        state.set(WalletConnectionState::Connected(session));
+                    }
+                    None => state.set(WalletConnectionState::Disconnected),
+                }
+            });
+        }
+        #[cfg(not(all(target_arch = "wasm32", feature = "wasm")))]
+        {
+            // Only browsers have Wallet Standard wallets
+            let _ = (wallet, silent);
+            state.set(WalletConnectionState::Disconnected);
+        }
+    }
+}
+
+#[cfg(feature = "dioxus")]
+/// The user's wallet connection, restored without a prompt after a reload
+///
+/// The last connected wallet is remembered in
+/// [`default_storage`](crate::infrastructure::storage::default_storage).
+/// When there is one, the state starts as `Restoring` rather than
+/// `Disconnected`, and that wallet is asked to reconnect silently as soon as
+/// it registers. If it no longer trusts the app, or has not registered within
+/// [`WALLET_RESTORE_TIMEOUT`], the state becomes `Disconnected`. Call it once
+/// near the root, inside [`ClientOnly`], and share the result as context.
+pub fn use_wallet_connection() -> WalletConnection {
+    use crate::infrastructure::wallet_standard::{WalletConnectionState, WalletSessionStore};
+
+    let store =
+        use_hook(|| WalletSessionStore::new(crate::infrastructure::storage::default_storage()));
+    let mut state = use_signal(|| {
+        let remembered = if is_browser() {
+            store.load().ok().flatten()
+        } else {
+            None
+        };
+        WalletConnectionState::initial(remembered)
+    });
+    #[allow(unused_mut)]
+    let mut wallets = use_signal(Vec::new);
+
+    #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+    let discovery = use_hook(move || {
+        crate::infrastructure::wallet_standard::discover(move |info| {
+            wallets.write().push(info.clone());
+        })
+        .ok()
+        .map(Rc::new)
+    });
+
+    let connection = WalletConnection {
+        state,
+        wallets,
+        store,
+        #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+        discovery,
+    };
+
+    // Restore the remembered session once its wallet has registered
+    let mut restoring = use_signal(|| false);
+    let restore = connection.clone();
+    use_effect(move || {
+        let WalletConnectionState::Restoring(session) = state.read().clone() else {
+            return;
+        };
+        let registered = wallets
+            .read()
+            .iter()
+            .any(|info| info.name == session.wallet);
+        if registered && !*restoring.peek() {
+            restoring.set(true);
+            restore.request(session.wallet, true);
+        }
+    });
+    use_hook(move || {
+        spawn(async move {
+            crate::infrastructure::time::sleep(WALLET_RESTORE_TIMEOUT).await;
+            let waiting = matches!(*state.peek(), WalletConnectionState::Restoring(_));
+            if waiting && !*restoring.peek() {
+                state.set(WalletConnectionState::Disconnected);
+            }
+        })
+    });
+
+    connection
+}
+
+#[cfg(feature = "dioxus")]
+/// Size and compute usage of a transaction under review, with any warnings
+///
+/// Show it next to the approve button so users see a transaction that cannot
+/// land before signing it.
+#[allow(non_snake_case)]
+#[component]
+pub fn TransactionEstimateSummary(
+    estimate: crate::application::estimate::TransactionEstimate,
+) -> Element {
+    use crate::application::estimate::PACKET_DATA_SIZE;
+
+    let size = estimate.size;
+    let signatures = estimate.signatures;
+    let limit = estimate.compute_unit_limit;
+    let compute = match estimate.compute_units {
+        Some(units) => format!("{} / {} CU", units, limit),
+        None => format!("not simulated (limit {} CU)", limit),
+    };
+    let warnings = estimate.warnings().into_iter().map(|warning| {
+        let class = if warning.is_fatal() {
+            "estimate-warning estimate-error"
+        } else {
+            "estimate-warning"
+        };
+        rsx! {
+            li { class: "{class}", "{warning}" }
+        }
+    });
+
+    rsx! {
+        div { class: "transaction-estimate",
+            div { "Size: {size} / {PACKET_DATA_SIZE} bytes" }
+            div { "Signatures: {signatures}" }
+            div { "Compute: {compute}" }
+            ul { {warnings} }
+        }
+    }
+}
+
+#[cfg(feature = "dioxus")]
+/// The accounts a transaction under review writes, creates or closes
+///
+/// Render an [`analyze_transaction`] result in the confirmation dialog so
+/// users see which accounts change, and which programs run behind the ones
+/// they called, before approving.
+///
+/// [`analyze_transaction`]: crate::application::estimate::analyze_transaction
+#[allow(non_snake_case)]
+#[component]
+pub fn TransactionAccountsSummary(
+    analysis: crate::application::estimate::TransactionAnalysis,
+) -> Element {
+    let accounts = analysis
+        .accounts
+        .iter()
+        .filter(|access| access.effect.is_change() || access.is_signer)
+        .map(|access| {
+            let pubkey = access.pubkey;
+            let effect = access.effect;
+            let signer = if access.is_signer { " (signer)" } else { "" };
+            rsx! {
+                li { class: "account-access account-{effect}",
+                    AddressLink { pubkey }
+                    " {effect}{signer}"
+                }
+            }
+        });
+    let inner_programs = analysis.inner_programs.iter().map(|pubkey| {
+        let pubkey = *pubkey;
+        rsx! {
+            li { AddressLink { pubkey } }
+        }
+    });
+
+    rsx! {
+        div { class: "transaction-accounts",
+            if !analysis.simulated {
+                div { class: "estimate-warning",
+                    "Simulation did not succeed; account changes are unknown"
+                }
+            }
+            ul { {accounts} }
+            if !analysis.inner_programs.is_empty() {
+                div { "Also runs:" }
+                ul { {inner_programs} }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "dioxus")]
+/// Button asking the connected wallet to sign `message` as text
+///
+/// The exact message is shown above the button, which stays disabled until a
+/// [`WalletConnection`] provided as context is connected. Messages that
+/// decode as a transaction are refused instead of being signed blindly.
+#[allow(non_snake_case)]
+#[component]
+pub fn SignMessageButton(
+    message: String,
+    on_signed: EventHandler<crate::Signature>,
+    on_error: Option<EventHandler<crate::infrastructure::wallet_standard::WalletStandardError>>,
+    #[props(default = "Sign message".to_string())] label: String,
+) -> Element {
+    use crate::infrastructure::wallet_standard::check_message;
+
+    let wallet = try_use_context::<WalletConnection>();
+    let mut busy = use_signal(|| false);
+    let connected = wallet
+        .as_ref()
+        .is_some_and(|wallet| wallet.state().read().is_connected());
+    let refused = check_message(message.as_bytes()).err();
+    let disabled = !connected || busy() || refused.is_some();
+
+    let text = message.clone();
+    let sign = move |_| {
+        let Some(wallet) = wallet.clone() else {
+            return;
+        };
+        let message = message.clone();
+        busy.set(true);
+        spawn(async move {
+            match wallet.sign_message(message.as_bytes()).await {
+                Ok(signature) => on_signed.call(signature),
+                Err(error) => {
+                    if let Some(on_error) = on_error {
+                        on_error.call(error);
+                    }
+                }
+            }
+            busy.set(false);
+        });
+    };
+
+    rsx! {
+        div { class: "sign-message",
+            pre { class: "sign-message-text", "{text}" }
+            if let Some(error) = refused {
+                div { class: "estimate-warning estimate-error", "{error}" }
+            }
+            button { disabled, onclick: sign, "{label}" }
+        }
+    }
+}
+
+#[cfg(feature = "dioxus")]
+/// Dialog reviewing a transaction before the connected wallet signs it
+///
+/// Lists each instruction, decoded through the [`DecoderRegistry`] provided as
+/// context or the built-in decoders, then simulates the transaction and shows
+/// [`TransactionEstimateSummary`] and [`TransactionAccountsSummary`]. Approve
+/// stays disabled while simulating and when the transaction cannot land, and a
+/// fee payer other than the connected account is flagged.
+///
+/// Approving signs through the [`WalletConnection`] in context and hands the
+/// signed transaction to `on_approve`; without one, the unsigned transaction
+/// is handed over for the app to sign. The review runs once, so give the
+/// modal a `key` when the transaction can change.
+///
+/// [`DecoderRegistry`]: crate::application::decoders::DecoderRegistry
+#[allow(non_snake_case)]
+#[component]
+pub fn TransactionApprovalModal(
+    transaction: crate::domain::message::Transaction,
+    on_approve: EventHandler<crate::domain::message::Transaction>,
+    on_reject: EventHandler<()>,
+    on_error: Option<EventHandler<crate::infrastructure::wallet_standard::WalletStandardError>>,
+) -> Element {
+    use crate::application::decoders::DecoderRegistry;
+    use crate::application::estimate::analyze_transaction;
+
+    let solana_context = use_context::<SolanaContext>();
+    let wallet = try_use_context::<WalletConnection>();
+    let provided = try_use_context::<DecoderRegistry>();
+    let registry = use_hook(move || provided.unwrap_or_else(DecoderRegistry::with_builtins));
+    let mut busy = use_signal(|| false);
+
+    let client = solana_context.client.clone();
+    let reviewed = transaction.clone();
+    let analysis = use_resource(move || {
+        let client = client.clone();
+        let transaction = reviewed.clone();
+        async move {
+            analyze_transaction(client.as_ref(), &transaction)
+                .await
+                .map_err(|e| e.to_string())
+        }
+    });
+
+    let instructions = match registry.inspect_transaction(&transaction) {
+        Ok(instructions) => instructions,
+        Err(e) => {
+            return rsx! {
+                div { class: "error", "Cannot read the transaction: {e}" }
+            }
+        }
+    };
+    let instructions = instructions.into_iter().map(|instruction| {
+        let program_id = instruction.program_id;
+        let program = instruction.program.unwrap_or_default();
+        match instruction.decoded {
+            Some(decoded) => rsx! {
+                li { class: "approval-instruction",
+                    "{program} {decoded.name} "
+                    AddressLink { pubkey: program_id }
+                }
+            },
+            None => rsx! {
+                li { class: "approval-instruction estimate-warning",
+                    "Unknown instruction for "
+                    AddressLink { pubkey: program_id }
+                }
+            },
+        }
+    });
+
+    let fee_payer = transaction.message.account_keys.first().copied();
+    let signer = wallet
+        .as_ref()
+        .and_then(|wallet| wallet.state().read().public_key());
+    let foreign_payer = match (fee_payer, signer) {
+        (Some(fee_payer), Some(signer)) => fee_payer != signer,
+        _ => false,
+    };
+    let fits = matches!(&*analysis.read(), Some(Ok(analysis)) if analysis.estimate.fits());
+
+    let approve = move |_| {
+        let transaction = transaction.clone();
+        let Some(wallet) = wallet.clone() else {
+            on_approve.call(transaction);
+            return;
+        };
+        busy.set(true);
+        spawn(async move {
+            match wallet.sign_transaction(&transaction).await {
+                Ok(signed) => on_approve.call(signed),
+                Err(error) => {
+                    if let Some(on_error) = on_error {
+                        on_error.call(error);
+                    }
+                }
+            }
+            busy.set(false);
+        });
+    };
+
+    rsx! {
+        div { class: "transaction-approval", role: "dialog", "aria-modal": "true",
+            h3 { "Review Transaction" }
+            ul { {instructions} }
+            if foreign_payer {
+                if let Some(fee_payer) = fee_payer {
+                    div { class: "estimate-warning",
+                        "Fees are paid by "
+                        AddressLink { pubkey: fee_payer }
+                        ", not the connected account"
+                    }
+                }
+            }
+            match &*analysis.read() {
+                Some(Ok(analysis)) => rsx! {
+                    TransactionEstimateSummary { estimate: analysis.estimate.clone() }
+                    TransactionAccountsSummary { analysis: analysis.clone() }
+                },
+                Some(Err(e)) => rsx! {
+                    div { class: "error", "Simulation failed: {e}" }
+                },
+                None => rsx! {
+                    div { class: "loading", "Simulating..." }
+                }
+            }
+            div { class: "approval-actions",
+                button { onclick: move |_| on_reject.call(()), "Reject" }
+                button { disabled: !fits || busy(), onclick: approve, "Approve" }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "dioxus")]
+/// Badge showing the state of a websocket connection
+///
+/// Feed it the states from [`PubsubEvent::State`] so users can tell live data
+/// from a feed that is reconnecting.
+///
+/// [`PubsubEvent::State`]: crate::infrastructure::pubsub::PubsubEvent::State
+#[allow(non_snake_case)]
+#[component]
+pub fn ConnectionStatus(state: crate::infrastructure::pubsub::ConnectionState) -> Element {
+    use crate::infrastructure::pubsub::ConnectionState;
+
+    let class = match state {
+        ConnectionState::Connected => "connection-status connected",
+        ConnectionState::Reconnecting { .. } => "connection-status reconnecting",
+        ConnectionState::Disconnected => "connection-status disconnected",
+    };
+
+    rsx! {
+        span { class: "{class}", "{state}" }
+    }
+}
+
+#[cfg(feature = "dioxus")]
+/// Abbreviated pubkey linking to the account in a block explorer
+///
+/// Links use the network of the enclosing [`SolanaProvider`]; the full key
+/// shows as a tooltip. Without a link for that network, only the text renders.
+#[allow(non_snake_case)]
+#[component]
+pub fn AddressLink(
+    pubkey: crate::Pubkey,
+    #[props(default)] explorer: crate::infrastructure::explorer::Explorer,
+) -> Element {
+    use crate::infrastructure::explorer::abbreviate_pubkey;
+
+    let solana_context = use_context::<SolanaContext>();
+    let short = abbreviate_pubkey(&pubkey);
+
+    match explorer.account_url(&solana_context.network, &pubkey) {
+        Some(url) => rsx! {
+            a {
+                class: "address-link",
+                href: "{url}",
+                target: "_blank",
+                rel: "noopener noreferrer",
+                title: "{pubkey}",
+                "{short}"
+            }
+        },
+        None => rsx! {
+            span { class: "address-link", title: "{pubkey}", "{short}" }
+        },
+    }
+}
+
+#[cfg(feature = "dioxus")]
+/// The current epoch, how much of it is done and roughly how long is left
+///
+/// Driven by [`use_epoch_info`](epoch::use_epoch_info), so it refreshes on
+/// its own and the estimate improves as the slot duration is measured.
+#[allow(non_snake_case)]
+#[component]
+pub fn EpochProgress() -> Element {
+    use crate::infrastructure::time::format_duration;
+    use store::Query;
+
+    let status = epoch::use_epoch_info();
+
+    let content = match &*status.read() {
+        Query::Ready(status) => {
+            let epoch = status.info.epoch;
+            let percent = status.info.progress() * 100.0;
+            let remaining = format_duration(status.time_remaining());
+            rsx! {
+                div { class: "epoch-number", "Epoch {epoch}" }
+                progress { max: 100.0, value: percent }
+                div { class: "epoch-remaining", "{percent:.1}% complete, about {remaining} left" }
+            }
+        }
+        Query::Failed(e) => rsx! {
+            div { class: "error", "Error fetching epoch: {e}" }
+        },
+        Query::Loading => rsx! {
+            div { class: "loading", "Loading epoch..." }
+        },
+    };
+
+    rsx! {
+        div { class: "epoch-progress", {content} }
+    }
+}
+
+#[cfg(feature = "dioxus")]
+/// A unix timestamp such as a block time, shown as `5 minutes ago`
+///
+/// The exact UTC time shows as a tooltip. The text is computed when the
+/// component renders and does not tick on its own.
+#[allow(non_snake_case)]
+#[component]
+pub fn RelativeTime(timestamp: i64) -> Element {
+    use crate::infrastructure::time::{format_timestamp_relative, format_utc};
+
+    let relative = format_timestamp_relative(timestamp);
+    let exact = format_utc(timestamp);
+    rsx! {
+        time { class: "relative-time", title: "{exact}", "{relative}" }
+    }
+}
+
 // Empty exports when dioxus feature is not enabled
 #[cfg(not(feature = "dioxus"))]
 pub struct SolanaContext;
@@ -0,0 +1,366 @@
+//! Shared account cache for Dioxus apps
+//!
+//! [`SolanaStore`] keeps one signal per account, balance and token account
+//! list, keyed by pubkey. Components reading the same key share a single
+//! fetch and a single signal, and every cached account is followed over an
+//! `accountSubscribe` feed, so a change on chain re-renders each component
+//! showing it. Optimistic writes recorded with [`SolanaStore::apply_optimistic`]
+//! show over the fetched data until their transaction settles. Wrap the app
+//! in [`SolanaStoreProvider`] and read through the `use_*` hooks.
+
+use crate::application::confirmation::{ConfirmationError, TransactionConfirmer};
+use crate::application::overlay::{AccountPatch, LocalOverlay};
+use crate::application::watch::{ChainWatcher, WatchConfig};
+use crate::domain::programs::token::TOKEN_ACCOUNT_LEN;
+use crate::domain::types::constants::TOKEN_PROGRAM_ID;
+use crate::domain::types::{Pubkey, Signature};
+use crate::infrastructure::rpc::{Account, Filter, RpcApi};
+use dioxus::prelude::*;
+use futures::{pin_mut, StreamExt};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// Offset of the owner in an SPL Token account
+const TOKEN_ACCOUNT_OWNER_OFFSET: usize = 32;
+
+/// A cached value in the store
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StoreKey {
+    /// An account, `None` while it does not exist
+    Account(Pubkey),
+    /// An account's lamports
+    Balance(Pubkey),
+    /// The SPL Token accounts owned by a wallet
+    TokenAccounts(Pubkey),
+}
+
+/// State of a cached value
+#[derive(Debug, Clone, PartialEq)]
+pub enum Query<T> {
+    /// First fetch still in flight
+    Loading,
+    Ready(T),
+    /// The fetch failed, with the error message
+    Failed(String),
+}
+
+impl<T> Query<T> {
+    /// The value, if it has loaded
+    pub fn value(&self) -> Option<&T> {
+        match self {
+            Query::Ready(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Whether the first fetch is still in flight
+    pub fn is_loading(&self) -> bool {
+        matches!(self, Query::Loading)
+    }
+
+    fn from_result<E: fmt::Display>(result: Result<T, E>) -> Self {
+        match result {
+            Ok(value) => Query::Ready(value),
+            Err(e) => Query::Failed(e.to_string()),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Entries {
+    accounts: HashMap<Pubkey, Signal<Query<Option<Account>>>>,
+    balances: HashMap<Pubkey, Signal<Query<u64>>>,
+    token_accounts: HashMap<Pubkey, Signal<Query<Vec<Account>>>>,
+    /// Wallets whose token account list holds each watched token account
+    token_owners: HashMap<Pubkey, HashSet<Pubkey>>,
+    watched: HashSet<Pubkey>,
+}
+
+/// App-wide cache of accounts, balances and token accounts
+///
+/// Entries are created on first read and live as long as the app, since
+/// their signals are owned by the root scope. Cloning shares the cache.
+#[derive(Clone)]
+pub struct SolanaStore {
+    rpc_client: Arc<dyn RpcApi>,
+    watcher: Rc<ChainWatcher>,
+    entries: Rc<RefCell<Entries>>,
+    overlay: LocalOverlay,
+}
+
+impl SolanaStore {
+    /// Create a store reading through `rpc_client` and watching with `config`
+    pub fn new(rpc_client: Arc<dyn RpcApi>, config: WatchConfig) -> Self {
+        Self {
+            watcher: Rc::new(ChainWatcher::from_api(rpc_client.clone(), config)),
+            rpc_client,
+            entries: Rc::default(),
+            overlay: LocalOverlay::new(),
+        }
+    }
+
+    /// Pending optimistic writes shown over fetched accounts
+    pub fn overlay(&self) -> &LocalOverlay {
+        &self.overlay
+    }
+
+    /// Show `patch` on `pubkey` right away, until `signature` settles
+    pub fn apply_optimistic(&self, signature: &Signature, pubkey: Pubkey, patch: AccountPatch) {
+        self.overlay.apply(signature, pubkey, patch);
+        let entries = self.entries.borrow();
+        let account_signal = entries.accounts.get(&pubkey).copied();
+        let balance_signal = entries.balances.get(&pubkey).copied();
+        drop(entries);
+
+        // Patches set values, so applying them again over the shown state is safe
+        if let Some(mut signal) = account_signal {
+            signal.with_mut(|query| {
+                if let Query::Ready(Some(account)) = query {
+                    *account = self.overlay.view(account.clone());
+                }
+            });
+        }
+        if let Some(mut signal) = balance_signal {
+            signal.with_mut(|query| {
+                if let Query::Ready(lamports) = query {
+                    *lamports = self.overlay.view_lamports(&pubkey, *lamports);
+                }
+            });
+        }
+    }
+
+    /// Wait for `signature` to confirm, refetching its accounts if it fails
+    pub async fn settle(
+        &self,
+        signature: &Signature,
+        confirmer: &TransactionConfirmer,
+    ) -> Result<(), ConfirmationError> {
+        let result = confirmer.confirm(signature).await;
+        match result {
+            Ok(()) => self.overlay.confirm(signature),
+            Err(_) => {
+                for pubkey in self.overlay.rollback(signature) {
+                    self.invalidate(StoreKey::Account(pubkey));
+                    self.invalidate(StoreKey::Balance(pubkey));
+                }
+            }
+        }
+        result
+    }
+
+    /// The account at `pubkey`, fetched on first read
+    pub fn account(&self, pubkey: Pubkey) -> Signal<Query<Option<Account>>> {
+        let existing = self.entries.borrow().accounts.get(&pubkey).copied();
+        existing.unwrap_or_else(|| {
+            let signal = Signal::new_in_scope(Query::Loading, ScopeId::ROOT);
+            self.entries.borrow_mut().accounts.insert(pubkey, signal);
+            self.refresh(StoreKey::Account(pubkey));
+            self.watch(pubkey);
+            signal
+        })
+    }
+
+    /// The lamports of `pubkey`, fetched on first read
+    pub fn balance(&self, pubkey: Pubkey) -> Signal<Query<u64>> {
+        let existing = self.entries.borrow().balances.get(&pubkey).copied();
+        existing.unwrap_or_else(|| {
+            let signal = Signal::new_in_scope(Query::Loading, ScopeId::ROOT);
+            self.entries.borrow_mut().balances.insert(pubkey, signal);
+            self.refresh(StoreKey::Balance(pubkey));
+            self.watch(pubkey);
+            signal
+        })
+    }
+
+    /// The SPL Token accounts of `owner`, fetched on first read
+    ///
+    /// Each listed account is watched, but accounts opened later only appear
+    /// after [`SolanaStore::invalidate`].
+    pub fn token_accounts(&self, owner: Pubkey) -> Signal<Query<Vec<Account>>> {
+        let existing = self.entries.borrow().token_accounts.get(&owner).copied();
+        existing.unwrap_or_else(|| {
+            let signal = Signal::new_in_scope(Query::Loading, ScopeId::ROOT);
+            self.entries
+                .borrow_mut()
+                .token_accounts
+                .insert(owner, signal);
+            self.refresh(StoreKey::TokenAccounts(owner));
+            signal
+        })
+    }
+
+    /// Fetch `key` again if it is cached, keeping the old value until then
+    pub fn invalidate(&self, key: StoreKey) {
+        let entries = self.entries.borrow();
+        let cached = match key {
+            StoreKey::Account(pubkey) => entries.accounts.contains_key(&pubkey),
+            StoreKey::Balance(pubkey) => entries.balances.contains_key(&pubkey),
+            StoreKey::TokenAccounts(owner) => entries.token_accounts.contains_key(&owner),
+        };
+        drop(entries);
+        if cached {
+            self.refresh(key);
+        }
+    }
+
+    fn refresh(&self, key: StoreKey) {
+        let store = self.clone();
+        spawn_forever(async move {
+            let rpc_client = store.rpc_client.clone();
+            match key {
+                StoreKey::Account(pubkey) => {
+                    let result = rpc_client.get_account_info(&pubkey).await;
+                    let query = Query::from_result(result.map(|a| store.overlay.merge(a)));
+                    let signal = store.entries.borrow().accounts.get(&pubkey).copied();
+                    if let Some(mut signal) = signal {
+                        signal.set(query);
+                    }
+                }
+                StoreKey::Balance(pubkey) => {
+                    let result = rpc_client.get_balance(&pubkey).await;
+                    let query = Query::from_result(
+                        result.map(|lamports| store.overlay.view_lamports(&pubkey, lamports)),
+                    );
+                    let signal = store.entries.borrow().balances.get(&pubkey).copied();
+                    if let Some(mut signal) = signal {
+                        signal.set(query);
+                    }
+                }
+                StoreKey::TokenAccounts(owner) => {
+                    let filters = [
+                        Filter::data_size(TOKEN_ACCOUNT_LEN as u64),
+                        Filter::memcmp_pubkey(TOKEN_ACCOUNT_OWNER_OFFSET, &owner),
+                    ];
+                    let result = rpc_client
+                        .get_program_accounts(&TOKEN_PROGRAM_ID, &filters)
+                        .await;
+                    if let Ok(accounts) = &result {
+                        for account in accounts {
+                            store
+                                .entries
+                                .borrow_mut()
+                                .token_owners
+                                .entry(account.pubkey)
+                                .or_default()
+                                .insert(owner);
+                            store.watch(account.pubkey);
+                        }
+                    }
+                    let signal = store.entries.borrow().token_accounts.get(&owner).copied();
+                    if let Some(mut signal) = signal {
+                        signal.set(Query::from_result(result));
+                    }
+                }
+            }
+        });
+    }
+
+    /// Follow `pubkey` on chain, once however many entries depend on it
+    fn watch(&self, pubkey: Pubkey) {
+        if !self.entries.borrow_mut().watched.insert(pubkey) {
+            return;
+        }
+        let store = self.clone();
+        let changes = self.watcher.watch_account(pubkey);
+        spawn_forever(async move {
+            pin_mut!(changes);
+            // Errors are retried by the feed; entries keep their last value
+            while let Some(change) = changes.next().await {
+                if let Ok(change) = change {
+                    store.apply(pubkey, change.account);
+                }
+            }
+        });
+    }
+
+    /// Write a new state of `pubkey` into every entry derived from it
+    fn apply(&self, pubkey: Pubkey, account: Option<Account>) {
+        let account = self.overlay.merge(account);
+        let entries = self.entries.borrow();
+        let mut account_signal = entries.accounts.get(&pubkey).copied();
+        let mut balance_signal = entries.balances.get(&pubkey).copied();
+        let lists: Vec<_> = entries
+            .token_owners
+            .get(&pubkey)
+            .into_iter()
+            .flatten()
+            .filter_map(|owner| entries.token_accounts.get(owner).copied())
+            .collect();
+        drop(entries);
+
+        if let Some(signal) = &mut balance_signal {
+            signal.set(Query::Ready(account.as_ref().map_or(0, |a| a.lamports)));
+        }
+        for mut list in lists {
+            list.with_mut(|query| {
+                if let Query::Ready(accounts) = query {
+                    accounts.retain(|listed| listed.pubkey != pubkey);
+                    accounts.extend(account.clone());
+                }
+            });
+        }
+        if let Some(signal) = &mut account_signal {
+            signal.set(Query::Ready(account));
+        }
+    }
+}
+
+/// Provide a [`SolanaStore`] for the client of the enclosing `SolanaProvider`
+#[allow(non_snake_case)]
+#[component]
+pub fn SolanaStoreProvider(children: Element) -> Element {
+    let context = use_context::<super::SolanaContext>();
+    use_context_provider(move || {
+        let rpc_client: Arc<dyn RpcApi> = Arc::new((*context.client).clone());
+        SolanaStore::new(
+            rpc_client,
+            WatchConfig::for_endpoint(context.network.endpoint()),
+        )
+    });
+
+    children
+}
+
+/// The store provided by [`SolanaStoreProvider`]
+pub fn use_solana_store() -> SolanaStore {
+    use_context::<SolanaStore>()
+}
+
+/// The cached account at `pubkey`
+pub fn use_account(pubkey: Pubkey) -> Signal<Query<Option<Account>>> {
+    use_solana_store().account(pubkey)
+}
+
+/// The cached lamports of `pubkey`
+pub fn use_balance(pubkey: Pubkey) -> Signal<Query<u64>> {
+    use_solana_store().balance(pubkey)
+}
+
+/// The cached SPL Token accounts of `owner`
+pub fn use_token_accounts(owner: Pubkey) -> Signal<Query<Vec<Account>>> {
+    use_solana_store().token_accounts(owner)
+}
+
+/// The cached account at `pubkey`, decoded with `decode`
+///
+/// Decoding reruns only when the account changes. A decode error shows up
+/// as [`Query::Failed`].
+pub fn use_decoded_account<T, E>(
+    pubkey: Pubkey,
+    decode: fn(&[u8]) -> Result<T, E>,
+) -> Memo<Query<Option<T>>>
+where
+    T: PartialEq + 'static,
+    E: fmt::Display,
+{
+    let account = use_account(pubkey);
+    use_memo(move || match &*account.read() {
+        Query::Loading => Query::Loading,
+        Query::Failed(e) => Query::Failed(e.clone()),
+        Query::Ready(None) => Query::Ready(None),
+        Query::Ready(Some(account)) => Query::from_result(decode(&account.data).map(Some)),
+    })
+}
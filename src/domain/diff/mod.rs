@@ -0,0 +1,396 @@
+//! Before/after account comparisons
+//!
+//! [`AccountDiff::compare`] describes how an account changed between two
+//! reads: its lamports, owner and executable flag, the byte ranges of its data
+//! that differ and, for data decoded into a `Serialize` type, the fields whose
+//! values changed. Simulation dry runs and account watchers use it to show
+//! what a transaction did or what just happened on chain.
+
+use crate::domain::types::constants::SYSTEM_PROGRAM_ID;
+use crate::domain::types::Pubkey;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fmt;
+use thiserror::Error;
+
+/// Unchanged bytes between two differing runs that still merge into one range
+pub const MERGE_GAP: usize = 8;
+
+/// Longest byte range shown in hex by [`AccountDiff`]'s `Display`
+const HEX_PREVIEW_LEN: usize = 16;
+
+/// Errors that can occur while diffing decoded values
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum DiffError {
+    #[error("Value cannot be represented as JSON: {0}")]
+    Serialize(String),
+}
+
+/// Account state that can be compared
+pub trait AccountState {
+    fn lamports(&self) -> u64;
+    fn owner(&self) -> Pubkey;
+    fn executable(&self) -> bool;
+    fn data(&self) -> &[u8];
+}
+
+/// A value before and after
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Change<T> {
+    pub old: T,
+    pub new: T,
+}
+
+impl<T: PartialEq> Change<T> {
+    /// The change from `old` to `new`, `None` if they are equal
+    pub fn between(old: T, new: T) -> Option<Self> {
+        (old != new).then_some(Self { old, new })
+    }
+}
+
+/// Account data bytes that differ, starting at `offset`
+///
+/// `old` and `new` differ in length where the data grew or shrank.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ByteRangeChange {
+    pub offset: usize,
+    pub old: Vec<u8>,
+    pub new: Vec<u8>,
+}
+
+impl ByteRangeChange {
+    /// End of the range in the larger of the two versions
+    pub fn end(&self) -> usize {
+        self.offset + self.old.len().max(self.new.len())
+    }
+}
+
+/// A field of decoded account data whose value changed
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldChange {
+    /// Dotted path of the field, with `[i]` for array elements
+    pub path: String,
+    /// Old value, `None` if the field was added
+    pub old: Option<Value>,
+    /// New value, `None` if the field was removed
+    pub new: Option<Value>,
+}
+
+/// What changed in one account
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AccountDiff {
+    /// The account did not exist before
+    pub created: bool,
+    /// The account no longer exists
+    pub closed: bool,
+    pub lamports: Option<Change<u64>>,
+    pub owner: Option<Change<Pubkey>>,
+    pub executable: Option<Change<bool>>,
+    pub data_len: Option<Change<usize>>,
+    /// Byte ranges of the data that differ
+    pub data: Vec<ByteRangeChange>,
+    /// Changed fields, filled in by [`AccountDiff::with_fields`]
+    pub fields: Vec<FieldChange>,
+}
+
+impl AccountDiff {
+    /// Compare two versions of an account, `None` where it does not exist
+    ///
+    /// A missing account compares as the runtime sees it: no lamports, no
+    /// data and owned by the system program.
+    pub fn compare<A: AccountState + ?Sized>(old: Option<&A>, new: Option<&A>) -> Self {
+        let lamports = |account: Option<&A>| account.map_or(0, |a| a.lamports());
+        let owner = |account: Option<&A>| account.map_or(SYSTEM_PROGRAM_ID, |a| a.owner());
+        let executable = |account: Option<&A>| account.is_some_and(|a| a.executable());
+        let old_data = old.map_or(&[][..], |a| a.data());
+        let new_data = new.map_or(&[][..], |a| a.data());
+
+        Self {
+            created: old.is_none() && new.is_some(),
+            closed: old.is_some() && new.is_none(),
+            lamports: Change::between(lamports(old), lamports(new)),
+            owner: Change::between(owner(old), owner(new)),
+            executable: Change::between(executable(old), executable(new)),
+            data_len: Change::between(old_data.len(), new_data.len()),
+            data: diff_bytes(old_data, new_data),
+            fields: Vec::new(),
+        }
+    }
+
+    /// Add the field changes between `old` and `new`, the decoded data
+    pub fn with_fields<T: Serialize + ?Sized>(
+        mut self,
+        old: &T,
+        new: &T,
+    ) -> Result<Self, DiffError> {
+        self.fields = diff_fields(old, new)?;
+        Ok(self)
+    }
+
+    /// Whether nothing changed
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl fmt::Display for AccountDiff {
+    /// One change per line
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut lines = Vec::new();
+        if self.created {
+            lines.push("account created".to_string());
+        }
+        if self.closed {
+            lines.push("account closed".to_string());
+        }
+        if let Some(Change { old, new }) = self.lamports {
+            lines.push(format!("lamports: {} -> {}", old, new));
+        }
+        if let Some(Change { old, new }) = self.owner {
+            lines.push(format!("owner: {} -> {}", old, new));
+        }
+        if let Some(Change { old, new }) = self.executable {
+            lines.push(format!("executable: {} -> {}", old, new));
+        }
+        if let Some(Change { old, new }) = self.data_len {
+            lines.push(format!("data length: {} -> {}", old, new));
+        }
+        for range in &self.data {
+            lines.push(format!(
+                "data[{}..{}]: {} -> {}",
+                range.offset,
+                range.end(),
+                hex_preview(&range.old),
+                hex_preview(&range.new)
+            ));
+        }
+        for field in &self.fields {
+            let show = |value: &Option<Value>| {
+                value
+                    .as_ref()
+                    .map_or("(none)".to_string(), Value::to_string)
+            };
+            let path = if field.path.is_empty() {
+                "value"
+            } else {
+                &field.path
+            };
+            lines.push(format!(
+                "{}: {} -> {}",
+                path,
+                show(&field.old),
+                show(&field.new)
+            ));
+        }
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+/// Byte ranges where `new` differs from `old`
+///
+/// Runs separated by at most [`MERGE_GAP`] unchanged bytes are reported as
+/// one range, so a changed integer shows up whole.
+pub fn diff_bytes(old: &[u8], new: &[u8]) -> Vec<ByteRangeChange> {
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for i in 0..old.len().max(new.len()) {
+        if old.get(i) == new.get(i) {
+            continue;
+        }
+        match ranges.last_mut() {
+            Some((_, end)) if i - *end <= MERGE_GAP => *end = i + 1,
+            _ => ranges.push((i, i + 1)),
+        }
+    }
+
+    let slice = |bytes: &[u8], start: usize, end: usize| {
+        bytes
+            .get(start..end.min(bytes.len()))
+            .unwrap_or_default()
+            .to_vec()
+    };
+    ranges
+        .into_iter()
+        .map(|(start, end)| ByteRangeChange {
+            offset: start,
+            old: slice(old, start, end),
+            new: slice(new, start, end),
+        })
+        .collect()
+}
+
+/// Fields whose values differ between two serializable values
+pub fn diff_fields<T: Serialize + ?Sized>(old: &T, new: &T) -> Result<Vec<FieldChange>, DiffError> {
+    let to_value =
+        |value: &T| serde_json::to_value(value).map_err(|e| DiffError::Serialize(e.to_string()));
+    let (old, new) = (to_value(old)?, to_value(new)?);
+    let mut changes = Vec::new();
+    diff_values("", Some(&old), Some(&new), &mut changes);
+    Ok(changes)
+}
+
+fn diff_values(
+    path: &str,
+    old: Option<&Value>,
+    new: Option<&Value>,
+    changes: &mut Vec<FieldChange>,
+) {
+    match (old, new) {
+        (Some(Value::Object(old)), Some(Value::Object(new))) => {
+            let keys: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+            for key in keys {
+                let path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                diff_values(&path, old.get(key), new.get(key), changes);
+            }
+        }
+        (Some(Value::Array(old)), Some(Value::Array(new))) => {
+            for i in 0..old.len().max(new.len()) {
+                let path = format!("{}[{}]", path, i);
+                diff_values(&path, old.get(i), new.get(i), changes);
+            }
+        }
+        (old, new) if old != new => changes.push(FieldChange {
+            path: path.to_string(),
+            old: old.cloned(),
+            new: new.cloned(),
+        }),
+        _ => {}
+    }
+}
+
+fn hex_preview(bytes: &[u8]) -> String {
+    if bytes.is_empty() {
+        return "(none)".to_string();
+    }
+    if bytes.len() > HEX_PREVIEW_LEN {
+        return format!("{} bytes", bytes.len());
+    }
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    struct Raw {
+        lamports: u64,
+        owner: Pubkey,
+        data: Vec<u8>,
+    }
+
+    impl AccountState for Raw {
+        fn lamports(&self) -> u64 {
+            self.lamports
+        }
+        fn owner(&self) -> Pubkey {
+            self.owner
+        }
+        fn executable(&self) -> bool {
+            false
+        }
+        fn data(&self) -> &[u8] {
+            &self.data
+        }
+    }
+
+    #[derive(Serialize)]
+    struct Counter {
+        authority: Pubkey,
+        count: u64,
+        history: Vec<u8>,
+    }
+
+    #[test]
+    fn test_compare_accounts() {
+        let owner = Pubkey::new([4; 32]);
+        let mut data = vec![0u8; 48];
+        let old = Raw {
+            lamports: 1_000,
+            owner,
+            data: data.clone(),
+        };
+        // A u64 at 8 and a byte at 40, too far apart to merge
+        data[8..16].copy_from_slice(&258u64.to_le_bytes());
+        data[40] = 7;
+        data.push(1);
+        let new = Raw {
+            lamports: 900,
+            owner,
+            data,
+        };
+
+        let diff = AccountDiff::compare(Some(&old), Some(&new));
+        assert_eq!(
+            diff.lamports,
+            Some(Change {
+                old: 1_000,
+                new: 900
+            })
+        );
+        assert_eq!(diff.owner, None);
+        assert_eq!(diff.data_len, Some(Change { old: 48, new: 49 }));
+        assert_eq!(
+            diff.data,
+            [
+                ByteRangeChange {
+                    offset: 8,
+                    old: vec![0, 0],
+                    new: vec![2, 1]
+                },
+                ByteRangeChange {
+                    offset: 40,
+                    old: vec![0; 8],
+                    new: vec![7, 0, 0, 0, 0, 0, 0, 0, 1]
+                },
+            ]
+        );
+        assert_eq!(diff.data[1].end(), 49);
+        assert!(diff.to_string().contains("lamports: 1000 -> 900"));
+
+        let closed = AccountDiff::compare(Some(&new), None);
+        assert!(closed.closed && !closed.created);
+        assert_eq!(closed.owner.unwrap().new, SYSTEM_PROGRAM_ID);
+        assert!(AccountDiff::compare(Some(&old), Some(&old)).is_empty());
+    }
+
+    #[test]
+    fn test_field_changes() {
+        let authority = Pubkey::new([1; 32]);
+        let old = Counter {
+            authority,
+            count: 1,
+            history: vec![1],
+        };
+        let new = Counter {
+            authority,
+            count: 2,
+            history: vec![1, 2],
+        };
+
+        let changes = diff_fields(&old, &new).unwrap();
+        assert_eq!(
+            changes,
+            [
+                FieldChange {
+                    path: "count".into(),
+                    old: Some(json!(1)),
+                    new: Some(json!(2))
+                },
+                FieldChange {
+                    path: "history[1]".into(),
+                    old: None,
+                    new: Some(json!(2))
+                },
+            ]
+        );
+        assert!(diff_fields(&old, &old).unwrap().is_empty());
+
+        let diff = AccountDiff::default().with_fields(&old, &new).unwrap();
+        assert_eq!(diff.to_string(), "count: 1 -> 2\nhistory[1]: (none) -> 2");
+    }
+}
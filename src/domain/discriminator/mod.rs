@@ -0,0 +1,251 @@
+//! Anchor discriminators
+//!
+//! Anchor prefixes account data with `sha256("account:<Name>")[..8]` and
+//! instruction data with `sha256("global:<name>")[..8]`. The hashes here are
+//! `const fn`, so a type's discriminator can be a compile-time constant that
+//! is checked against fetched account data before decoding it.
+
+use borsh::BorshDeserialize;
+use thiserror::Error;
+
+/// Size of an Anchor account/instruction/event discriminator
+pub const DISCRIMINATOR_LEN: usize = 8;
+
+/// Errors that can occur while decoding discriminated account data
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum DiscriminatorError {
+    #[error("Account data is {0} bytes, too short for a discriminator")]
+    TooShort(usize),
+
+    #[error("Discriminator mismatch: expected {expected:?}, found {found:?}")]
+    Mismatch {
+        expected: [u8; DISCRIMINATOR_LEN],
+        found: [u8; DISCRIMINATOR_LEN],
+    },
+
+    #[error("Failed to decode account data: {0}")]
+    Decode(String),
+}
+
+/// An Anchor account type identified by its discriminator
+pub trait Discriminator {
+    /// The 8 bytes that prefix every account of this type
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN];
+}
+
+/// Discriminator of the Anchor account type `name`
+pub const fn account_discriminator(name: &str) -> [u8; DISCRIMINATOR_LEN] {
+    sighash("account", name)
+}
+
+/// Discriminator of the Anchor instruction `name`, given in snake_case
+pub const fn instruction_discriminator(name: &str) -> [u8; DISCRIMINATOR_LEN] {
+    sighash("global", name)
+}
+
+/// Discriminator of the Anchor event `name`
+pub const fn event_discriminator(name: &str) -> [u8; DISCRIMINATOR_LEN] {
+    sighash("event", name)
+}
+
+/// First 8 bytes of `sha256("<namespace>:<name>")`
+pub const fn sighash(namespace: &str, name: &str) -> [u8; DISCRIMINATOR_LEN] {
+    let hash = sha256(namespace.as_bytes(), name.as_bytes());
+    let mut out = [0u8; DISCRIMINATOR_LEN];
+    let mut i = 0;
+    while i < DISCRIMINATOR_LEN {
+        out[i] = hash[i];
+        i += 1;
+    }
+    out
+}
+
+/// Check that `data` starts with `T`'s discriminator and decode the rest
+///
+/// Trailing bytes are ignored, since Anchor accounts are usually allocated
+/// with room to spare.
+pub fn decode_account<T>(data: &[u8]) -> Result<T, DiscriminatorError>
+where
+    T: Discriminator + BorshDeserialize,
+{
+    check_discriminator(data, &T::DISCRIMINATOR)?;
+    let mut body = &data[DISCRIMINATOR_LEN..];
+    T::deserialize(&mut body).map_err(|e| DiscriminatorError::Decode(e.to_string()))
+}
+
+/// Check that `data` starts with `expected`
+pub fn check_discriminator(
+    data: &[u8],
+    expected: &[u8; DISCRIMINATOR_LEN],
+) -> Result<(), DiscriminatorError> {
+    let found: [u8; DISCRIMINATOR_LEN] = data
+        .get(..DISCRIMINATOR_LEN)
+        .and_then(|prefix| prefix.try_into().ok())
+        .ok_or(DiscriminatorError::TooShort(data.len()))?;
+    if &found != expected {
+        return Err(DiscriminatorError::Mismatch {
+            expected: *expected,
+            found,
+        });
+    }
+    Ok(())
+}
+
+const K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+const H0: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// SHA-256 of `namespace`, a colon and `name`, usable in const contexts
+const fn sha256(namespace: &[u8], name: &[u8]) -> [u8; 32] {
+    let len = namespace.len() + 1 + name.len();
+    // Message, the 0x80 terminator and the 64-bit length, rounded up to blocks
+    let blocks = (len + 9).div_ceil(64);
+    let bit_len = (len as u64) * 8;
+
+    let mut state = H0;
+    let mut block = 0;
+    while block < blocks {
+        let mut w = [0u32; 64];
+        let mut t = 0;
+        while t < 16 {
+            let mut word = 0u32;
+            let mut b = 0;
+            while b < 4 {
+                let i = block * 64 + t * 4 + b;
+                let byte = if i < namespace.len() {
+                    namespace[i]
+                } else if i == namespace.len() {
+                    b':'
+                } else if i < len {
+                    name[i - namespace.len() - 1]
+                } else if i == len {
+                    0x80
+                } else if i >= blocks * 64 - 8 {
+                    (bit_len >> ((blocks * 64 - 1 - i) * 8)) as u8
+                } else {
+                    0
+                };
+                word = (word << 8) | byte as u32;
+                b += 1;
+            }
+            w[t] = word;
+            t += 1;
+        }
+        while t < 64 {
+            let s0 = w[t - 15].rotate_right(7) ^ w[t - 15].rotate_right(18) ^ (w[t - 15] >> 3);
+            let s1 = w[t - 2].rotate_right(17) ^ w[t - 2].rotate_right(19) ^ (w[t - 2] >> 10);
+            w[t] = w[t - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[t - 7])
+                .wrapping_add(s1);
+            t += 1;
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state;
+        t = 0;
+        while t < 64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let temp1 = h
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(K[t])
+                .wrapping_add(w[t]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let temp2 = s0.wrapping_add(maj);
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(temp1);
+            d = c;
+            c = b;
+            b = a;
+            a = temp1.wrapping_add(temp2);
+            t += 1;
+        }
+
+        let mixed = [a, b, c, d, e, f, g, h];
+        let mut i = 0;
+        while i < 8 {
+            state[i] = state[i].wrapping_add(mixed[i]);
+            i += 1;
+        }
+        block += 1;
+    }
+
+    let mut out = [0u8; 32];
+    let mut i = 0;
+    while i < 8 {
+        let bytes = state[i].to_be_bytes();
+        out[i * 4] = bytes[0];
+        out[i * 4 + 1] = bytes[1];
+        out[i * 4 + 2] = bytes[2];
+        out[i * 4 + 3] = bytes[3];
+        i += 1;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshSerialize;
+    use sha2::{Digest, Sha256};
+
+    #[derive(Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+    struct Counter {
+        count: u64,
+    }
+
+    impl Discriminator for Counter {
+        const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = account_discriminator("Counter");
+    }
+
+    #[test]
+    fn test_matches_sha256() {
+        // Names long enough to cross one and two block boundaries
+        for name in [
+            "Counter",
+            &"x".repeat(54),
+            &"y".repeat(55),
+            &"z".repeat(130),
+        ] {
+            let expected = Sha256::digest(format!("account:{}", name).as_bytes());
+            assert_eq!(sha256(b"account", name.as_bytes()), expected.as_slice());
+        }
+        assert_eq!(
+            instruction_discriminator("initialize"),
+            [175, 175, 109, 31, 13, 152, 155, 237]
+        );
+    }
+
+    #[test]
+    fn test_decode_account() {
+        let mut data = Counter::DISCRIMINATOR.to_vec();
+        data.extend(borsh::to_vec(&Counter { count: 7 }).unwrap());
+        data.extend([0; 16]);
+        assert_eq!(decode_account::<Counter>(&data), Ok(Counter { count: 7 }));
+
+        data[0] ^= 1;
+        assert!(matches!(
+            decode_account::<Counter>(&data),
+            Err(DiscriminatorError::Mismatch { .. })
+        ));
+        assert_eq!(
+            decode_account::<Counter>(&[1, 2]),
+            Err(DiscriminatorError::TooShort(2))
+        );
+    }
+}
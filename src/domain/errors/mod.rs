@@ -0,0 +1,672 @@
+//! Transaction and instruction error decoding
+//!
+//! Solana reports failures as JSON values such as
+//! `{"InstructionError":[0,{"Custom":6000}]}`, both in RPC error payloads and in
+//! simulation results. This module turns them into typed errors with
+//! human-friendly messages, resolving Anchor framework and program-specific
+//! custom error codes where possible.
+
+use crate::domain::types::Pubkey;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Resolves program-specific custom error codes to messages
+///
+/// Implemented by the IDL registry so Anchor `#[error_code]` enums can be
+/// displayed by name instead of as raw numbers.
+pub trait CustomErrorResolver {
+    /// Resolve a custom error code raised by `program_id`
+    fn resolve_custom_error(&self, program_id: &Pubkey, code: u32) -> Option<ResolvedError>;
+}
+
+/// A custom error code resolved to its name and message
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResolvedError {
+    /// Error code
+    pub code: u32,
+    /// Error name (e.g. `ConstraintSeeds`)
+    pub name: String,
+    /// Human-readable message
+    pub message: String,
+}
+
+/// Errors raised while executing a single instruction
+#[derive(Debug, Clone, PartialEq, Eq, Error, Serialize, Deserialize)]
+pub enum InstructionError {
+    #[error("generic instruction error")]
+    GenericError,
+    #[error("invalid program argument")]
+    InvalidArgument,
+    #[error("invalid instruction data")]
+    InvalidInstructionData,
+    #[error("invalid account data for instruction")]
+    InvalidAccountData,
+    #[error("account data too small for instruction")]
+    AccountDataTooSmall,
+    #[error("insufficient funds for instruction")]
+    InsufficientFunds,
+    #[error("incorrect program id for instruction")]
+    IncorrectProgramId,
+    #[error("missing required signature for instruction")]
+    MissingRequiredSignature,
+    #[error("instruction requires an uninitialized account")]
+    AccountAlreadyInitialized,
+    #[error("instruction requires an initialized account")]
+    UninitializedAccount,
+    #[error("sum of account balances before and after instruction do not match")]
+    UnbalancedInstruction,
+    #[error("instruction illegally modified the program id of an account")]
+    ModifiedProgramId,
+    #[error("instruction spent from the balance of an account it does not own")]
+    ExternalAccountLamportSpend,
+    #[error("instruction modified data of an account it does not own")]
+    ExternalAccountDataModified,
+    #[error("instruction changed the balance of a read-only account")]
+    ReadonlyLamportChange,
+    #[error("instruction modified data of a read-only account")]
+    ReadonlyDataModified,
+    #[error("instruction contains duplicate accounts")]
+    DuplicateAccountIndex,
+    #[error("instruction changed executable bit of an account")]
+    ExecutableModified,
+    #[error("instruction modified rent epoch of an account")]
+    RentEpochModified,
+    #[error("insufficient account keys for instruction")]
+    NotEnoughAccountKeys,
+    #[error("program other than the account's owner changed the size of the account data")]
+    AccountDataSizeChanged,
+    #[error("instruction expected an executable account")]
+    AccountNotExecutable,
+    #[error("instruction tries to borrow reference for an account which is already borrowed")]
+    AccountBorrowFailed,
+    #[error("instruction left account with an outstanding borrowed reference")]
+    AccountBorrowOutstanding,
+    #[error("instruction modifications of multiply-passed account differ")]
+    DuplicateAccountOutOfSync,
+    #[error("custom program error: {0:#x}")]
+    Custom(u32),
+    #[error("program returned invalid error code")]
+    InvalidError,
+    #[error("instruction changed executable accounts data")]
+    ExecutableDataModified,
+    #[error("instruction changed the balance of an executable account")]
+    ExecutableLamportChange,
+    #[error("executable accounts must be rent exempt")]
+    ExecutableAccountNotRentExempt,
+    #[error("Unsupported program id")]
+    UnsupportedProgramId,
+    #[error("Cross-program invocation call depth too deep")]
+    CallDepth,
+    #[error("An account required by the instruction is missing")]
+    MissingAccount,
+    #[error("Cross-program invocation reentrancy not allowed for this instruction")]
+    ReentrancyNotAllowed,
+    #[error("Length of the seed is too long for address generation")]
+    MaxSeedLengthExceeded,
+    #[error("Provided seeds do not result in a valid address")]
+    InvalidSeeds,
+    #[error("Failed to reallocate account data")]
+    InvalidRealloc,
+    #[error("Computational budget exceeded")]
+    ComputationalBudgetExceeded,
+    #[error("Cross-program invocation with unauthorized signer or writable account")]
+    PrivilegeEscalation,
+    #[error("Failed to create program execution environment")]
+    ProgramEnvironmentSetupFailure,
+    #[error("Program failed to complete")]
+    ProgramFailedToComplete,
+    #[error("Program failed to compile")]
+    ProgramFailedToCompile,
+    #[error("Account is immutable")]
+    Immutable,
+    #[error("Incorrect authority provided")]
+    IncorrectAuthority,
+    #[error("Failed to serialize or deserialize account data: {0}")]
+    BorshIoError(String),
+    #[error("An account does not have enough lamports to be rent-exempt")]
+    AccountNotRentExempt,
+    #[error("Invalid account owner")]
+    InvalidAccountOwner,
+    #[error("Program arithmetic overflowed")]
+    ArithmeticOverflow,
+    #[error("Unsupported sysvar")]
+    UnsupportedSysvar,
+    #[error("Provided owner is not allowed")]
+    IllegalOwner,
+    #[error("Accounts data allocations exceeded the maximum allowed per transaction")]
+    MaxAccountsDataAllocationsExceeded,
+    #[error("Max accounts exceeded")]
+    MaxAccountsExceeded,
+    #[error("Max instruction trace length exceeded")]
+    MaxInstructionTraceLengthExceeded,
+    #[error("Builtin programs must consume compute units")]
+    BuiltinProgramsMustConsumeComputeUnits,
+    /// A variant this library does not know about, kept as raw JSON
+    #[error("unknown instruction error: {0}")]
+    #[serde(skip)]
+    Unknown(String),
+}
+
+impl InstructionError {
+    /// Decode an instruction error from its JSON representation
+    pub fn from_value(value: &serde_json::Value) -> Self {
+        serde_json::from_value(value.clone()).unwrap_or_else(|_| Self::Unknown(value.to_string()))
+    }
+
+    /// Get the custom error code, if this is a program-specific error
+    pub fn custom_code(&self) -> Option<u32> {
+        match self {
+            Self::Custom(code) => Some(*code),
+            _ => None,
+        }
+    }
+
+    /// Human-friendly description, resolving custom codes when possible
+    ///
+    /// Resolution order: the supplied resolver (e.g. a registered IDL), then the
+    /// Anchor framework error table, then the raw hexadecimal code.
+    pub fn describe(
+        &self,
+        program_id: Option<&Pubkey>,
+        resolver: Option<&dyn CustomErrorResolver>,
+    ) -> String {
+        let Some(code) = self.custom_code() else {
+            return self.to_string();
+        };
+
+        let resolved = match (program_id, resolver) {
+            (Some(program_id), Some(resolver)) => resolver.resolve_custom_error(program_id, code),
+            _ => None,
+        }
+        .or_else(|| anchor_framework_error(code));
+
+        match resolved {
+            Some(resolved) => format!(
+                "{} (custom program error {:#x}: {})",
+                resolved.message, code, resolved.name
+            ),
+            None => self.to_string(),
+        }
+    }
+}
+
+/// Errors raised while processing a transaction
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum TransactionError {
+    #[error("Account in use")]
+    AccountInUse,
+    #[error("Account loaded twice")]
+    AccountLoadedTwice,
+    #[error("Attempt to debit an account but found no record of a prior credit.")]
+    AccountNotFound,
+    #[error("Attempt to load a program that does not exist")]
+    ProgramAccountNotFound,
+    #[error("Insufficient funds for fee")]
+    InsufficientFundsForFee,
+    #[error("This account may not be used to pay transaction fees")]
+    InvalidAccountForFee,
+    #[error("This transaction has already been processed")]
+    AlreadyProcessed,
+    #[error("Blockhash not found")]
+    BlockhashNotFound,
+    #[error("Error processing Instruction {index}: {error}")]
+    InstructionError { index: u8, error: InstructionError },
+    #[error("Loader call chain is too deep")]
+    CallChainTooDeep,
+    #[error("Transaction requires a fee but has no signature present")]
+    MissingSignatureForFee,
+    #[error("Transaction contains an invalid account reference")]
+    InvalidAccountIndex,
+    #[error("Transaction did not pass signature verification")]
+    SignatureFailure,
+    #[error("This program may not be used for executing instructions")]
+    InvalidProgramForExecution,
+    #[error("Transaction failed to sanitize accounts offsets correctly")]
+    SanitizeFailure,
+    #[error("Transactions are currently disabled due to cluster maintenance")]
+    ClusterMaintenance,
+    #[error("Transaction processing left an account with an outstanding borrowed reference")]
+    AccountBorrowOutstanding,
+    #[error("Transaction would exceed max Block Cost Limit")]
+    WouldExceedMaxBlockCostLimit,
+    #[error("Transaction version is unsupported")]
+    UnsupportedVersion,
+    #[error("Transaction loads a writable account that cannot be written")]
+    InvalidWritableAccount,
+    #[error("Transaction would exceed max account limit within the block")]
+    WouldExceedMaxAccountCostLimit,
+    #[error("Transaction would exceed account data limit within the block")]
+    WouldExceedAccountDataBlockLimit,
+    #[error("Transaction locked too many accounts")]
+    TooManyAccountLocks,
+    #[error("Transaction loads an address table account that doesn't exist")]
+    AddressLookupTableNotFound,
+    #[error(
+        "Transaction results in an account ({account_index}) with insufficient funds for rent"
+    )]
+    InsufficientFundsForRent { account_index: u8 },
+    #[error("Transaction exceeded max loaded accounts data size cap")]
+    MaxLoadedAccountsDataSizeExceeded,
+    #[error("Transaction would exceed total account data limit")]
+    WouldExceedAccountDataTotalLimit,
+    #[error("Transaction contains a duplicate instruction ({0}) that is not allowed")]
+    DuplicateInstruction(u8),
+    #[error(
+        "Transaction could not fit into current block without violating the Max Vote Cost Limit"
+    )]
+    WouldExceedMaxVoteCostLimit,
+    #[error("Sum of account balances before and after transaction do not match")]
+    UnbalancedTransaction,
+    #[error("Program cache hit max limit")]
+    ProgramCacheHitMaxLimit,
+    /// A variant this library does not know about, kept as raw JSON
+    #[error("unknown transaction error: {0}")]
+    Unknown(String),
+}
+
+impl TransactionError {
+    /// Decode a transaction error from its JSON representation
+    ///
+    /// Accepts the `err` field of simulation results, signature statuses and
+    /// transaction metas, as well as RPC error `data.err` payloads.
+    pub fn from_value(value: &serde_json::Value) -> Self {
+        let unknown = || Self::Unknown(value.to_string());
+
+        if let Some(name) = value.as_str() {
+            return Self::from_unit_name(name).unwrap_or_else(unknown);
+        }
+
+        let Some((name, payload)) = value.as_object().and_then(|object| {
+            if object.len() == 1 {
+                object.iter().next()
+            } else {
+                None
+            }
+        }) else {
+            return unknown();
+        };
+
+        match name.as_str() {
+            "InstructionError" => match payload.as_array().map(Vec::as_slice) {
+                Some([index, error]) => match index.as_u64().and_then(|i| u8::try_from(i).ok()) {
+                    Some(index) => Self::InstructionError {
+                        index,
+                        error: InstructionError::from_value(error),
+                    },
+                    None => unknown(),
+                },
+                _ => unknown(),
+            },
+            "InsufficientFundsForRent" => payload
+                .get("account_index")
+                .and_then(|i| i.as_u64())
+                .and_then(|i| u8::try_from(i).ok())
+                .map(|account_index| Self::InsufficientFundsForRent { account_index })
+                .unwrap_or_else(unknown),
+            "DuplicateInstruction" => payload
+                .as_u64()
+                .and_then(|i| u8::try_from(i).ok())
+                .map(Self::DuplicateInstruction)
+                .unwrap_or_else(unknown),
+            _ => unknown(),
+        }
+    }
+
+    fn from_unit_name(name: &str) -> Option<Self> {
+        let error = match name {
+            "AccountInUse" => Self::AccountInUse,
+            "AccountLoadedTwice" => Self::AccountLoadedTwice,
+            "AccountNotFound" => Self::AccountNotFound,
+            "ProgramAccountNotFound" => Self::ProgramAccountNotFound,
+            "InsufficientFundsForFee" => Self::InsufficientFundsForFee,
+            "InvalidAccountForFee" => Self::InvalidAccountForFee,
+            "AlreadyProcessed" => Self::AlreadyProcessed,
+            "BlockhashNotFound" => Self::BlockhashNotFound,
+            "CallChainTooDeep" => Self::CallChainTooDeep,
+            "MissingSignatureForFee" => Self::MissingSignatureForFee,
+            "InvalidAccountIndex" => Self::InvalidAccountIndex,
+            "SignatureFailure" => Self::SignatureFailure,
+            "InvalidProgramForExecution" => Self::InvalidProgramForExecution,
+            "SanitizeFailure" => Self::SanitizeFailure,
+            "ClusterMaintenance" => Self::ClusterMaintenance,
+            "AccountBorrowOutstanding" => Self::AccountBorrowOutstanding,
+            "WouldExceedMaxBlockCostLimit" => Self::WouldExceedMaxBlockCostLimit,
+            "UnsupportedVersion" => Self::UnsupportedVersion,
+            "InvalidWritableAccount" => Self::InvalidWritableAccount,
+            "WouldExceedMaxAccountCostLimit" => Self::WouldExceedMaxAccountCostLimit,
+            "WouldExceedAccountDataBlockLimit" => Self::WouldExceedAccountDataBlockLimit,
+            "TooManyAccountLocks" => Self::TooManyAccountLocks,
+            "AddressLookupTableNotFound" => Self::AddressLookupTableNotFound,
+            "MaxLoadedAccountsDataSizeExceeded" => Self::MaxLoadedAccountsDataSizeExceeded,
+            "WouldExceedAccountDataTotalLimit" => Self::WouldExceedAccountDataTotalLimit,
+            "WouldExceedMaxVoteCostLimit" => Self::WouldExceedMaxVoteCostLimit,
+            "UnbalancedTransaction" => Self::UnbalancedTransaction,
+            "ProgramCacheHitMaxLimit" => Self::ProgramCacheHitMaxLimit,
+            _ => return None,
+        };
+        Some(error)
+    }
+
+    /// Get the failing instruction, if this is an instruction error
+    pub fn instruction_error(&self) -> Option<(u8, &InstructionError)> {
+        match self {
+            Self::InstructionError { index, error } => Some((*index, error)),
+            _ => None,
+        }
+    }
+
+    /// Human-friendly description, resolving custom codes when possible
+    ///
+    /// `program_ids` maps instruction indices to the invoked program, as found
+    /// in the transaction message.
+    pub fn describe(
+        &self,
+        program_ids: &[Pubkey],
+        resolver: Option<&dyn CustomErrorResolver>,
+    ) -> String {
+        match self {
+            Self::InstructionError { index, error } => format!(
+                "Error processing Instruction {}: {}",
+                index,
+                error.describe(program_ids.get(usize::from(*index)), resolver)
+            ),
+            other => other.to_string(),
+        }
+    }
+}
+
+/// Anchor framework error codes (anchor-lang `ErrorCode`)
+const ANCHOR_FRAMEWORK_ERRORS: &[(u32, &str, &str)] = &[
+    (
+        100,
+        "InstructionMissing",
+        "8 byte instruction identifier not provided",
+    ),
+    (
+        101,
+        "InstructionFallbackNotFound",
+        "Fallback functions are not supported",
+    ),
+    (
+        102,
+        "InstructionDidNotDeserialize",
+        "The program could not deserialize the given instruction",
+    ),
+    (
+        103,
+        "InstructionDidNotSerialize",
+        "The program could not serialize the given instruction",
+    ),
+    (
+        1000,
+        "IdlInstructionStub",
+        "The program was compiled without idl instructions",
+    ),
+    (
+        1001,
+        "IdlInstructionInvalidProgram",
+        "Invalid program given to the IDL instruction",
+    ),
+    (2000, "ConstraintMut", "A mut constraint was violated"),
+    (
+        2001,
+        "ConstraintHasOne",
+        "A has one constraint was violated",
+    ),
+    (2002, "ConstraintSigner", "A signer constraint was violated"),
+    (2003, "ConstraintRaw", "A raw constraint was violated"),
+    (2004, "ConstraintOwner", "An owner constraint was violated"),
+    (
+        2005,
+        "ConstraintRentExempt",
+        "A rent exemption constraint was violated",
+    ),
+    (2006, "ConstraintSeeds", "A seeds constraint was violated"),
+    (
+        2007,
+        "ConstraintExecutable",
+        "An executable constraint was violated",
+    ),
+    (
+        2009,
+        "ConstraintAssociated",
+        "An associated constraint was violated",
+    ),
+    (
+        2010,
+        "ConstraintAssociatedInit",
+        "An associated init constraint was violated",
+    ),
+    (2011, "ConstraintClose", "A close constraint was violated"),
+    (
+        2012,
+        "ConstraintAddress",
+        "An address constraint was violated",
+    ),
+    (2013, "ConstraintZero", "Expected zero account discriminant"),
+    (
+        2014,
+        "ConstraintTokenMint",
+        "A token mint constraint was violated",
+    ),
+    (
+        2015,
+        "ConstraintTokenOwner",
+        "A token owner constraint was violated",
+    ),
+    (
+        2016,
+        "ConstraintMintMintAuthority",
+        "A mint mint authority constraint was violated",
+    ),
+    (
+        2017,
+        "ConstraintMintFreezeAuthority",
+        "A mint freeze authority constraint was violated",
+    ),
+    (
+        2018,
+        "ConstraintMintDecimals",
+        "A mint decimals constraint was violated",
+    ),
+    (2019, "ConstraintSpace", "A space constraint was violated"),
+    (
+        2020,
+        "ConstraintAccountIsNone",
+        "A required account for the constraint is None",
+    ),
+    (
+        3000,
+        "AccountDiscriminatorAlreadySet",
+        "The account discriminator was already set on this account",
+    ),
+    (
+        3001,
+        "AccountDiscriminatorNotFound",
+        "No 8 byte discriminator was found on the account",
+    ),
+    (
+        3002,
+        "AccountDiscriminatorMismatch",
+        "8 byte discriminator did not match what was expected",
+    ),
+    (
+        3003,
+        "AccountDidNotDeserialize",
+        "Failed to deserialize the account",
+    ),
+    (
+        3004,
+        "AccountDidNotSerialize",
+        "Failed to serialize the account",
+    ),
+    (
+        3005,
+        "AccountNotEnoughKeys",
+        "Not enough account keys given to the instruction",
+    ),
+    (
+        3006,
+        "AccountNotMutable",
+        "The given account is not mutable",
+    ),
+    (
+        3007,
+        "AccountOwnedByWrongProgram",
+        "The given account is owned by a different program than expected",
+    ),
+    (3008, "InvalidProgramId", "Program ID was not as expected"),
+    (
+        3009,
+        "InvalidProgramExecutable",
+        "Program account is not executable",
+    ),
+    (3010, "AccountNotSigner", "The given account did not sign"),
+    (
+        3011,
+        "AccountNotSystemOwned",
+        "The given account is not owned by the system program",
+    ),
+    (
+        3012,
+        "AccountNotInitialized",
+        "The program expected this account to be already initialized",
+    ),
+    (
+        3013,
+        "AccountNotProgramData",
+        "The given account is not a program data account",
+    ),
+    (
+        3014,
+        "AccountNotAssociatedTokenAccount",
+        "The given account is not the associated token account",
+    ),
+    (
+        3015,
+        "AccountSysvarMismatch",
+        "The given public key does not match the required sysvar",
+    ),
+    (
+        3016,
+        "AccountReallocExceedsLimit",
+        "The account reallocation exceeds the MAX_PERMITTED_DATA_INCREASE limit",
+    ),
+    (
+        3017,
+        "AccountDuplicateReallocs",
+        "The account was duplicated for more than one reallocation",
+    ),
+    (
+        4100,
+        "DeclaredProgramIdMismatch",
+        "The declared program id does not match the actual program id",
+    ),
+    (
+        5000,
+        "Deprecated",
+        "The API being used is deprecated and should no longer be used",
+    ),
+];
+
+/// Look up an Anchor framework error by code
+pub fn anchor_framework_error(code: u32) -> Option<ResolvedError> {
+    ANCHOR_FRAMEWORK_ERRORS
+        .iter()
+        .find(|(candidate, _, _)| *candidate == code)
+        .map(|(code, name, message)| ResolvedError {
+            code: *code,
+            name: (*name).to_string(),
+            message: (*message).to_string(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    struct CounterErrors;
+
+    impl CustomErrorResolver for CounterErrors {
+        fn resolve_custom_error(&self, _program_id: &Pubkey, code: u32) -> Option<ResolvedError> {
+            (code == 6000).then(|| ResolvedError {
+                code,
+                name: "Overflow".to_string(),
+                message: "Counter overflowed".to_string(),
+            })
+        }
+    }
+
+    #[test]
+    fn test_decode_instruction_errors() {
+        assert_eq!(
+            InstructionError::from_value(&json!("InsufficientFunds")),
+            InstructionError::InsufficientFunds
+        );
+        assert_eq!(
+            InstructionError::from_value(&json!({ "Custom": 1 })),
+            InstructionError::Custom(1)
+        );
+        assert_eq!(
+            InstructionError::from_value(&json!({ "BorshIoError": "eof" })),
+            InstructionError::BorshIoError("eof".to_string())
+        );
+        assert!(matches!(
+            InstructionError::from_value(&json!("SomethingNew")),
+            InstructionError::Unknown(_)
+        ));
+    }
+
+    #[test]
+    fn test_decode_transaction_errors() {
+        assert_eq!(
+            TransactionError::from_value(&json!("AccountInUse")),
+            TransactionError::AccountInUse
+        );
+        assert_eq!(
+            TransactionError::from_value(&json!({ "InstructionError": [1, { "Custom": 6000 }] })),
+            TransactionError::InstructionError {
+                index: 1,
+                error: InstructionError::Custom(6000),
+            }
+        );
+        assert_eq!(
+            TransactionError::from_value(
+                &json!({ "InsufficientFundsForRent": { "account_index": 2 } })
+            ),
+            TransactionError::InsufficientFundsForRent { account_index: 2 }
+        );
+    }
+
+    #[test]
+    fn test_describe_resolves_custom_codes() {
+        let program_id = Pubkey::new([9; 32]);
+        let error = TransactionError::InstructionError {
+            index: 0,
+            error: InstructionError::Custom(6000),
+        };
+
+        assert_eq!(
+            error.describe(&[program_id], Some(&CounterErrors)),
+            "Error processing Instruction 0: Counter overflowed (custom program error 0x1770: Overflow)"
+        );
+        assert_eq!(
+            error.describe(&[program_id], None),
+            "Error processing Instruction 0: custom program error: 0x1770"
+        );
+    }
+
+    #[test]
+    fn test_describe_anchor_framework_errors() {
+        let error = InstructionError::Custom(2006);
+        assert_eq!(
+            error.describe(None, None),
+            "A seeds constraint was violated (custom program error 0x7d6: ConstraintSeeds)"
+        );
+        assert_eq!(
+            InstructionError::InsufficientFunds.describe(None, None),
+            "insufficient funds for instruction"
+        );
+    }
+}
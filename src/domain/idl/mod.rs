@@ -0,0 +1,562 @@
+//! Anchor IDL types
+//!
+//! Serde models for Anchor IDLs in both the 0.30+ format (with `address`,
+//! `metadata` and explicit discriminators) and the legacy pre-0.30 format
+//! (`name`/`version` at the top level, `isMut`/`isSigner` accounts). Also
+//! decodes the compressed on-chain IDL account written by `anchor idl init`.
+
+use crate::domain::discriminator::sighash;
+use crate::domain::types::{Pubkey, PubkeyError};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::io::Read;
+use thiserror::Error;
+
+/// Seed used by Anchor to derive the IDL account address
+pub const IDL_SEED: &str = "anchor:idl";
+
+pub use crate::domain::discriminator::DISCRIMINATOR_LEN;
+
+/// Log prefix Anchor uses for emitted events
+const PROGRAM_DATA_PREFIX: &str = "Program data: ";
+
+/// An Anchor IDL
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Idl {
+    /// Program address (0.30+)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    /// Program metadata (0.30+)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<IdlMetadata>,
+    /// Program name (legacy)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Program version (legacy)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub instructions: Vec<IdlInstruction>,
+    #[serde(default)]
+    pub accounts: Vec<IdlAccountDef>,
+    #[serde(default)]
+    pub events: Vec<IdlEventDef>,
+    #[serde(default)]
+    pub errors: Vec<IdlErrorCode>,
+    #[serde(default)]
+    pub types: Vec<IdlTypeDef>,
+}
+
+/// IDL metadata block (0.30+)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IdlMetadata {
+    pub name: String,
+    pub version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spec: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// An instruction exposed by the program
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IdlInstruction {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub docs: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub discriminator: Option<Vec<u8>>,
+    #[serde(default)]
+    pub accounts: Vec<IdlInstructionAccount>,
+    #[serde(default)]
+    pub args: Vec<IdlField>,
+}
+
+/// An account expected by an instruction
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IdlInstructionAccount {
+    pub name: String,
+    #[serde(default, alias = "isMut")]
+    pub writable: bool,
+    #[serde(default, alias = "isSigner")]
+    pub signer: bool,
+    #[serde(default, alias = "isOptional")]
+    pub optional: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pda: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub relations: Vec<String>,
+}
+
+/// A named, typed field
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IdlField {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: IdlType,
+}
+
+/// A field type
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum IdlType {
+    Bool,
+    U8,
+    I8,
+    U16,
+    I16,
+    U32,
+    I32,
+    U64,
+    I64,
+    U128,
+    I128,
+    F32,
+    F64,
+    Bytes,
+    String,
+    Pubkey,
+    Vec(Box<IdlType>),
+    Option(Box<IdlType>),
+    Array(Box<IdlType>, usize),
+    Defined(String),
+}
+
+impl IdlType {
+    fn from_value(value: &serde_json::Value) -> Result<Self, String> {
+        use serde_json::Value;
+
+        match value {
+            Value::String(name) => Ok(match name.as_str() {
+                "bool" => Self::Bool,
+                "u8" => Self::U8,
+                "i8" => Self::I8,
+                "u16" => Self::U16,
+                "i16" => Self::I16,
+                "u32" => Self::U32,
+                "i32" => Self::I32,
+                "u64" => Self::U64,
+                "i64" => Self::I64,
+                "u128" => Self::U128,
+                "i128" => Self::I128,
+                "f32" => Self::F32,
+                "f64" => Self::F64,
+                "bytes" => Self::Bytes,
+                "string" => Self::String,
+                "pubkey" | "publicKey" => Self::Pubkey,
+                other => return Err(format!("unknown IDL type: {}", other)),
+            }),
+            Value::Object(map) => {
+                if let Some(inner) = map.get("vec") {
+                    Ok(Self::Vec(Box::new(Self::from_value(inner)?)))
+                } else if let Some(inner) = map.get("option") {
+                    Ok(Self::Option(Box::new(Self::from_value(inner)?)))
+                } else if let Some(array) = map.get("array").and_then(Value::as_array) {
+                    match array.as_slice() {
+                        [inner, Value::Number(len)] => Ok(Self::Array(
+                            Box::new(Self::from_value(inner)?),
+                            len.as_u64().ok_or("invalid array length")? as usize,
+                        )),
+                        _ => Err("invalid array type".to_string()),
+                    }
+                } else if let Some(defined) = map.get("defined") {
+                    let name = defined
+                        .as_str()
+                        .or_else(|| defined.get("name").and_then(Value::as_str))
+                        .ok_or("invalid defined type")?;
+                    Ok(Self::Defined(name.to_string()))
+                } else {
+                    Err(format!("unknown IDL type: {}", value))
+                }
+            }
+            other => Err(format!("unknown IDL type: {}", other)),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for IdlType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = serde_json::Value::deserialize(deserializer)?;
+        Self::from_value(&value).map_err(serde::de::Error::custom)
+    }
+}
+
+/// An account type owned by the program
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IdlAccountDef {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub discriminator: Option<Vec<u8>>,
+    /// Inline layout (legacy format; 0.30+ puts it in `types`)
+    #[serde(default, rename = "type", skip_serializing_if = "Option::is_none")]
+    pub ty: Option<IdlTypeDefTy>,
+}
+
+/// An event emitted by the program
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IdlEventDef {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub discriminator: Option<Vec<u8>>,
+    /// Inline fields (legacy format; 0.30+ puts them in `types`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<IdlField>,
+}
+
+/// A custom error code declared with `#[error_code]`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IdlErrorCode {
+    pub code: u32,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub msg: Option<String>,
+}
+
+/// A user-defined type
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IdlTypeDef {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: IdlTypeDefTy,
+}
+
+/// Body of a user-defined type
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum IdlTypeDefTy {
+    Struct {
+        #[serde(default)]
+        fields: IdlDefinedFields,
+    },
+    Enum {
+        variants: Vec<IdlEnumVariant>,
+    },
+}
+
+/// Fields of a struct or enum variant
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum IdlDefinedFields {
+    Named(Vec<IdlField>),
+    Tuple(Vec<IdlType>),
+}
+
+impl Default for IdlDefinedFields {
+    fn default() -> Self {
+        Self::Named(Vec::new())
+    }
+}
+
+/// An enum variant
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IdlEnumVariant {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fields: Option<IdlDefinedFields>,
+}
+
+/// An event decoded from program logs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdlEvent {
+    /// Event name from the IDL
+    pub name: String,
+    /// Borsh-encoded event payload, without the discriminator
+    pub data: Vec<u8>,
+}
+
+/// Errors that can occur while decoding IDLs
+#[derive(Debug, Clone, Error)]
+pub enum IdlError {
+    #[error("IDL account data too short")]
+    AccountTooShort,
+
+    #[error("Failed to inflate IDL data: {0}")]
+    Inflate(String),
+
+    #[error("Invalid IDL JSON: {0}")]
+    Json(String),
+
+    #[error("Failed to derive IDL address: {0}")]
+    Address(#[from] PubkeyError),
+}
+
+impl Idl {
+    /// Parse an IDL from JSON
+    pub fn from_json(json: &str) -> Result<Self, IdlError> {
+        serde_json::from_str(json).map_err(|e| IdlError::Json(e.to_string()))
+    }
+
+    /// Decode the data of an on-chain IDL account
+    ///
+    /// Layout: 8-byte discriminator, 32-byte authority, u32 LE length, then
+    /// zlib-compressed JSON.
+    pub fn from_account_data(data: &[u8]) -> Result<Self, IdlError> {
+        const HEADER_LEN: usize = DISCRIMINATOR_LEN + 32;
+
+        let len_bytes = data
+            .get(HEADER_LEN..HEADER_LEN + 4)
+            .ok_or(IdlError::AccountTooShort)?;
+        let len =
+            u32::from_le_bytes([len_bytes[0], len_bytes[1], len_bytes[2], len_bytes[3]]) as usize;
+        let compressed = data
+            .get(HEADER_LEN + 4..HEADER_LEN + 4 + len)
+            .ok_or(IdlError::AccountTooShort)?;
+
+        let mut json = String::new();
+        flate2::read::ZlibDecoder::new(compressed)
+            .read_to_string(&mut json)
+            .map_err(|e| IdlError::Inflate(e.to_string()))?;
+        Self::from_json(&json)
+    }
+
+    /// Program name from either format
+    pub fn name(&self) -> Option<&str> {
+        self.metadata
+            .as_ref()
+            .map(|metadata| metadata.name.as_str())
+            .or(self.name.as_deref())
+    }
+
+    /// Program version from either format
+    pub fn version(&self) -> Option<&str> {
+        self.metadata
+            .as_ref()
+            .map(|metadata| metadata.version.as_str())
+            .or(self.version.as_deref())
+    }
+
+    /// Find an instruction by name
+    pub fn instruction(&self, name: &str) -> Option<&IdlInstruction> {
+        self.instructions.iter().find(|ix| ix.name == name)
+    }
+
+    /// Find a custom error by code
+    pub fn error(&self, code: u32) -> Option<&IdlErrorCode> {
+        self.errors.iter().find(|error| error.code == code)
+    }
+
+    /// Find a user-defined type by name
+    pub fn type_def(&self, name: &str) -> Option<&IdlTypeDef> {
+        self.types.iter().find(|ty| ty.name == name)
+    }
+
+    /// Match instruction data against the IDL's instruction discriminators
+    pub fn match_instruction(&self, data: &[u8]) -> Option<&IdlInstruction> {
+        let prefix = data.get(..DISCRIMINATOR_LEN)?;
+        self.instructions
+            .iter()
+            .find(|ix| ix.discriminator().as_slice() == prefix)
+    }
+
+    /// Decode Anchor events from transaction log messages
+    pub fn parse_events(&self, logs: &[String]) -> Vec<IdlEvent> {
+        use base64::Engine;
+
+        logs.iter()
+            .filter_map(|line| line.strip_prefix(PROGRAM_DATA_PREFIX))
+            .filter_map(|encoded| {
+                base64::engine::general_purpose::STANDARD
+                    .decode(encoded.trim())
+                    .ok()
+            })
+            .filter_map(|bytes| {
+                let prefix = bytes.get(..DISCRIMINATOR_LEN)?;
+                let event = self
+                    .events
+                    .iter()
+                    .find(|event| event.discriminator().as_slice() == prefix)?;
+                Some(IdlEvent {
+                    name: event.name.clone(),
+                    data: bytes[DISCRIMINATOR_LEN..].to_vec(),
+                })
+            })
+            .collect()
+    }
+}
+
+impl IdlInstruction {
+    /// Explicit discriminator, or the legacy `sha256("global:<name>")` one
+    pub fn discriminator(&self) -> Vec<u8> {
+        self.discriminator
+            .clone()
+            .unwrap_or_else(|| sighash("global", &to_snake_case(&self.name)).to_vec())
+    }
+}
+
+impl IdlEventDef {
+    /// Explicit discriminator, or the legacy `sha256("event:<Name>")` one
+    pub fn discriminator(&self) -> Vec<u8> {
+        self.discriminator
+            .clone()
+            .unwrap_or_else(|| sighash("event", &self.name).to_vec())
+    }
+}
+
+impl IdlAccountDef {
+    /// Explicit discriminator, or the legacy `sha256("account:<Name>")` one
+    pub fn discriminator(&self) -> Vec<u8> {
+        self.discriminator
+            .clone()
+            .unwrap_or_else(|| sighash("account", &self.name).to_vec())
+    }
+}
+
+/// Derive the address of a program's on-chain IDL account
+pub fn idl_address(program_id: &Pubkey) -> Result<Pubkey, IdlError> {
+    let (base, _) =
+        Pubkey::try_find_program_address(&[], program_id).ok_or(PubkeyError::InvalidSeeds)?;
+    Ok(Pubkey::create_with_seed(&base, IDL_SEED, program_id)?)
+}
+
+/// Legacy IDLs use camelCase instruction names; Anchor hashes the snake_case form
+fn to_snake_case(name: &str) -> String {
+    let mut snake = String::with_capacity(name.len() + 4);
+    for (i, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i > 0 {
+                snake.push('_');
+            }
+            snake.push(c.to_ascii_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    const NEW_FORMAT: &str = r#"{
+        "address": "Counter111111111111111111111111111111111111",
+        "metadata": { "name": "counter", "version": "0.1.0", "spec": "0.1.0" },
+        "instructions": [{
+            "name": "increment",
+            "discriminator": [11, 18, 104, 9, 104, 174, 59, 33],
+            "accounts": [
+                { "name": "counter", "writable": true },
+                { "name": "authority", "signer": true }
+            ],
+            "args": [{ "name": "amount", "type": "u64" }]
+        }],
+        "events": [{ "name": "Incremented", "discriminator": [1, 2, 3, 4, 5, 6, 7, 8] }],
+        "errors": [{ "code": 6000, "name": "Overflow", "msg": "Counter overflowed" }],
+        "types": [{
+            "name": "Incremented",
+            "type": { "kind": "struct", "fields": [
+                { "name": "value", "type": "u64" },
+                { "name": "owners", "type": { "vec": "pubkey" } },
+                { "name": "tag", "type": { "option": { "array": ["u8", 4] } } },
+                { "name": "config", "type": { "defined": { "name": "Config" } } }
+            ] }
+        }]
+    }"#;
+
+    const LEGACY_FORMAT: &str = r#"{
+        "version": "0.1.0",
+        "name": "counter",
+        "instructions": [{
+            "name": "initCounter",
+            "accounts": [{ "name": "counter", "isMut": true, "isSigner": false }],
+            "args": [{ "name": "owner", "type": "publicKey" }]
+        }],
+        "events": [{ "name": "Initialized", "fields": [{ "name": "owner", "type": "publicKey", "index": false }] }],
+        "types": [{ "name": "Mode", "type": { "kind": "enum", "variants": [{ "name": "On" }, { "name": "Off" }] } }]
+    }"#;
+
+    #[test]
+    fn test_parse_new_format() {
+        let idl = Idl::from_json(NEW_FORMAT).unwrap();
+        assert_eq!(idl.name(), Some("counter"));
+        assert_eq!(idl.version(), Some("0.1.0"));
+
+        let ix = idl.instruction("increment").unwrap();
+        assert!(ix.accounts[0].writable);
+        assert!(ix.accounts[1].signer);
+        assert_eq!(ix.args[0].ty, IdlType::U64);
+        assert_eq!(idl.error(6000).unwrap().name, "Overflow");
+
+        let IdlTypeDefTy::Struct {
+            fields: IdlDefinedFields::Named(fields),
+        } = &idl.type_def("Incremented").unwrap().ty
+        else {
+            panic!("expected named struct");
+        };
+        assert_eq!(fields[1].ty, IdlType::Vec(Box::new(IdlType::Pubkey)));
+        assert_eq!(
+            fields[2].ty,
+            IdlType::Option(Box::new(IdlType::Array(Box::new(IdlType::U8), 4)))
+        );
+        assert_eq!(fields[3].ty, IdlType::Defined("Config".to_string()));
+    }
+
+    #[test]
+    fn test_parse_legacy_format() {
+        let idl = Idl::from_json(LEGACY_FORMAT).unwrap();
+        assert_eq!(idl.name(), Some("counter"));
+
+        let ix = idl.instruction("initCounter").unwrap();
+        assert!(ix.accounts[0].writable);
+        assert!(!ix.accounts[0].signer);
+        assert_eq!(ix.args[0].ty, IdlType::Pubkey);
+        assert_eq!(ix.discriminator(), sighash("global", "init_counter"));
+        assert!(matches!(
+            idl.type_def("Mode").unwrap().ty,
+            IdlTypeDefTy::Enum { .. }
+        ));
+    }
+
+    #[test]
+    fn test_match_instruction_and_events() {
+        use base64::Engine;
+
+        let idl = Idl::from_json(NEW_FORMAT).unwrap();
+        let mut data = vec![11, 18, 104, 9, 104, 174, 59, 33];
+        data.extend_from_slice(&5u64.to_le_bytes());
+        assert_eq!(idl.match_instruction(&data).unwrap().name, "increment");
+        assert!(idl.match_instruction(&[0; 4]).is_none());
+
+        let payload = [1, 2, 3, 4, 5, 6, 7, 8, 42];
+        let logs = vec![
+            "Program log: Instruction: Increment".to_string(),
+            format!(
+                "Program data: {}",
+                base64::engine::general_purpose::STANDARD.encode(payload)
+            ),
+        ];
+        let events = idl.parse_events(&logs);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].name, "Incremented");
+        assert_eq!(events[0].data, vec![42]);
+    }
+
+    #[test]
+    fn test_from_account_data() {
+        let mut encoder =
+            flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(LEGACY_FORMAT.as_bytes()).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let mut data = vec![0u8; DISCRIMINATOR_LEN + 32];
+        data.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+        data.extend_from_slice(&compressed);
+
+        let idl = Idl::from_account_data(&data).unwrap();
+        assert_eq!(idl.name(), Some("counter"));
+        assert!(matches!(
+            Idl::from_account_data(&data[..10]),
+            Err(IdlError::AccountTooShort)
+        ));
+    }
+
+    #[test]
+    fn test_idl_address_is_deterministic() {
+        let program_id = Pubkey::from_base58("11111111111111111111111111111111").unwrap();
+        let address = idl_address(&program_id).unwrap();
+        assert_eq!(address, idl_address(&program_id).unwrap());
+        assert_ne!(address, program_id);
+    }
+}
@@ -0,0 +1,279 @@
+//! Account data layouts
+//!
+//! Describes where fields live inside Borsh-serialized account data so that
+//! memcmp filters and data slices can target a field by name instead of a
+//! hand-counted offset. [`account_layout!`](crate::account_layout) declares a
+//! struct and computes its layout from the Borsh sizes of its field types.
+
+use crate::domain::discriminator::DISCRIMINATOR_LEN;
+use crate::domain::types::Pubkey;
+use std::ops::Range;
+
+/// Byte layout of a Borsh-serialized account type
+///
+/// Offsets are absolute within the account data, so Anchor accounts include
+/// the 8-byte discriminator in every offset.
+pub trait AccountLayout {
+    /// Fixed-offset fields as `(name, offset)` pairs
+    const FIELDS: &'static [(&'static str, usize)];
+
+    /// Fixed-size fields as `(name, length)` pairs
+    const SIZES: &'static [(&'static str, usize)] = &[];
+
+    /// Byte offset of a field, if it has a fixed position
+    fn field_offset(field: &str) -> Option<usize> {
+        lookup(Self::FIELDS, field)
+    }
+
+    /// Bytes a field occupies, if it has a fixed position and size
+    fn field_range(field: &str) -> Option<Range<usize>> {
+        let offset = Self::field_offset(field)?;
+        Some(offset..offset + lookup(Self::SIZES, field)?)
+    }
+}
+
+fn lookup(fields: &[(&str, usize)], field: &str) -> Option<usize> {
+    fields
+        .iter()
+        .find(|(name, _)| *name == field)
+        .map(|(_, value)| *value)
+}
+
+/// Number of bytes a type always takes in Borsh, `None` if it varies
+pub trait BorshSize {
+    const SIZE: Option<usize>;
+}
+
+macro_rules! fixed_borsh_size {
+    ($($ty:ty),*) => {
+        $(impl BorshSize for $ty {
+            const SIZE: Option<usize> = Some(std::mem::size_of::<$ty>());
+        })*
+    };
+}
+
+fixed_borsh_size!(
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    f32,
+    f64,
+    bool,
+    ()
+);
+
+impl BorshSize for Pubkey {
+    const SIZE: Option<usize> = Some(32);
+}
+
+impl<T: BorshSize, const N: usize> BorshSize for [T; N] {
+    const SIZE: Option<usize> = match T::SIZE {
+        Some(size) => Some(size * N),
+        None => None,
+    };
+}
+
+impl<T> BorshSize for Option<T> {
+    const SIZE: Option<usize> = None;
+}
+
+impl<T> BorshSize for Vec<T> {
+    const SIZE: Option<usize> = None;
+}
+
+impl BorshSize for String {
+    const SIZE: Option<usize> = None;
+}
+
+/// Offsets and sizes of a struct's fields, computed at compile time
+///
+/// Used by [`account_layout!`](crate::account_layout). Every field up to and
+/// including the first variable-size one has a fixed offset; only the fields
+/// before it also have a fixed size.
+#[doc(hidden)]
+pub struct FixedFields<const N: usize> {
+    offsets: [(&'static str, usize); N],
+    sizes: [(&'static str, usize); N],
+    fixed_offsets: usize,
+    fixed_sizes: usize,
+}
+
+impl<const N: usize> FixedFields<N> {
+    pub const fn new(base: usize, fields: [(&'static str, Option<usize>); N]) -> Self {
+        let mut layout = Self {
+            offsets: [("", 0); N],
+            sizes: [("", 0); N],
+            fixed_offsets: 0,
+            fixed_sizes: 0,
+        };
+        let mut offset = base;
+        while layout.fixed_offsets < N {
+            let (name, size) = fields[layout.fixed_offsets];
+            layout.offsets[layout.fixed_offsets] = (name, offset);
+            layout.fixed_offsets += 1;
+            match size {
+                Some(size) => {
+                    layout.sizes[layout.fixed_sizes] = (name, size);
+                    layout.fixed_sizes += 1;
+                    offset += size;
+                }
+                None => break,
+            }
+        }
+        layout
+    }
+
+    pub const fn offsets(&'static self) -> &'static [(&'static str, usize)] {
+        self.offsets.split_at(self.fixed_offsets).0
+    }
+
+    pub const fn sizes(&'static self) -> &'static [(&'static str, usize)] {
+        self.sizes.split_at(self.fixed_sizes).0
+    }
+}
+
+/// Bytes before the first field of an account with `#[layout(anchor)]`
+#[doc(hidden)]
+pub const ANCHOR_LAYOUT_BASE: usize = DISCRIMINATOR_LEN;
+
+/// Declare a Borsh account struct and implement [`AccountLayout`] for it
+///
+/// Field offsets and sizes come from [`BorshSize`], so they stay correct when
+/// fields are added or reordered. Start with `#[layout(anchor)]` to account
+/// for the 8-byte Anchor discriminator. Fields after a `String`, `Vec` or
+/// `Option` have no fixed offset and cannot be looked up.
+///
+/// ```
+/// use borsh::{BorshDeserialize, BorshSerialize};
+/// use gloo_solana::{account_layout, AccountLayout, Pubkey};
+///
+/// account_layout! {
+///     #[layout(anchor)]
+///     #[derive(BorshSerialize, BorshDeserialize)]
+///     pub struct Counter {
+///         pub authority: Pubkey,
+///         pub count: u64,
+///     }
+/// }
+///
+/// assert_eq!(Counter::field_offset("count"), Some(40));
+/// assert_eq!(Counter::field_range("authority"), Some(8..40));
+/// ```
+#[macro_export]
+macro_rules! account_layout {
+    (#[layout(anchor)] $($item:tt)*) => {
+        $crate::account_layout!(@base $crate::domain::layout::ANCHOR_LAYOUT_BASE; $($item)*);
+    };
+    (@base $base:expr;
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $($(#[$field_meta:meta])* $field_vis:vis $field:ident : $ty:ty),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis struct $name {
+            $($(#[$field_meta])* $field_vis $field: $ty),*
+        }
+
+        impl $name {
+            #[doc(hidden)]
+            const ACCOUNT_LAYOUT: $crate::domain::layout::FixedFields<
+                { [$(stringify!($field)),*].len() },
+            > = $crate::domain::layout::FixedFields::new(
+                $base,
+                [$((
+                    stringify!($field),
+                    <$ty as $crate::domain::layout::BorshSize>::SIZE,
+                )),*],
+            );
+        }
+
+        impl $crate::domain::layout::AccountLayout for $name {
+            const FIELDS: &'static [(&'static str, usize)] = Self::ACCOUNT_LAYOUT.offsets();
+            const SIZES: &'static [(&'static str, usize)] = Self::ACCOUNT_LAYOUT.sizes();
+        }
+    };
+    ($($item:tt)*) => {
+        $crate::account_layout!(@base 0; $($item)*);
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshSerialize;
+
+    struct Counter;
+
+    impl AccountLayout for Counter {
+        const FIELDS: &'static [(&'static str, usize)] = &[("authority", 8), ("count", 40)];
+    }
+
+    #[test]
+    fn test_field_offset() {
+        assert_eq!(Counter::field_offset("authority"), Some(8));
+        assert_eq!(Counter::field_offset("count"), Some(40));
+        assert_eq!(Counter::field_offset("missing"), None);
+        assert_eq!(Counter::field_range("count"), None);
+    }
+
+    crate::account_layout! {
+        #[layout(anchor)]
+        #[derive(BorshSerialize)]
+        struct Profile {
+            owner: Pubkey,
+            level: u16,
+            scores: [u32; 3],
+            name: String,
+            /// Unreachable by offset: it follows a variable-size field
+            active: bool,
+        }
+    }
+
+    crate::account_layout! {
+        #[derive(BorshSerialize)]
+        struct Plain {
+            flag: bool,
+            amount: u64,
+        }
+    }
+
+    #[test]
+    fn test_account_layout_macro_matches_borsh() {
+        let profile = Profile {
+            owner: Pubkey::new([3; 32]),
+            level: 7,
+            scores: [1, 2, 3],
+            name: "surf".to_string(),
+            active: true,
+        };
+        let mut data = vec![0u8; ANCHOR_LAYOUT_BASE];
+        profile.serialize(&mut data).unwrap();
+
+        let owner = Profile::field_range("owner").unwrap();
+        assert_eq!(&data[owner], profile.owner.as_bytes());
+        let level = Profile::field_range("level").unwrap();
+        assert_eq!(&data[level], &7u16.to_le_bytes());
+        assert_eq!(Profile::field_range("scores"), Some(42..54));
+        assert_eq!(Profile::field_offset("name"), Some(54));
+        assert_eq!(Profile::field_range("name"), None);
+        assert_eq!(Profile::field_offset("active"), None);
+        assert_eq!(&data[58..62], b"surf");
+
+        let plain = borsh::to_vec(&Plain {
+            flag: true,
+            amount: 9,
+        })
+        .unwrap();
+        assert_eq!(Plain::FIELDS, &[("flag", 0), ("amount", 1)]);
+        let amount = Plain::field_range("amount").unwrap();
+        assert_eq!(&plain[amount], &9u64.to_le_bytes());
+    }
+}
@@ -0,0 +1,214 @@
+//! Program log parsing
+//!
+//! Transaction logs are flat strings written by the runtime and by programs'
+//! `msg!` calls. [`parse_logs`] follows the `invoke [n]` / `success` lines to
+//! attribute each line to the program that emitted it, and classifies it so a
+//! console can show a program's own output apart from runtime bookkeeping.
+
+use crate::domain::types::Pubkey;
+
+const LOG_PREFIX: &str = "Program log: ";
+const DATA_PREFIX: &str = "Program data: ";
+const RETURN_PREFIX: &str = "Program return: ";
+const TRUNCATED: &str = "Log truncated";
+
+/// Severity of a log line, from runtime bookkeeping to failures
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LogLevel {
+    /// Invocations, compute usage and successful returns
+    Debug,
+    /// Messages and data written by a program
+    Info,
+    /// Output the runtime dropped
+    Warn,
+    /// Failed instructions
+    Error,
+}
+
+/// What a log line records
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LogKind {
+    /// `Program <id> invoke [<depth>]`
+    Invoke,
+    /// `Program log: <message>`, written by `msg!`
+    Log,
+    /// `Program data: <base64>...`, written by `sol_log_data` and Anchor events
+    Data,
+    /// `Program return: <id> <base64>`
+    Return,
+    /// `Program <id> consumed <units> of <limit> compute units`
+    ComputeUnits { consumed: u64, limit: u64 },
+    /// `Program <id> success`
+    Success,
+    /// `Program <id> failed: <reason>`
+    Failure,
+    /// `Log truncated`
+    Truncated,
+    /// Any other runtime message
+    Other,
+}
+
+impl LogKind {
+    /// Severity of this kind of line
+    pub fn level(&self) -> LogLevel {
+        match self {
+            Self::Log | Self::Data | Self::Return => LogLevel::Info,
+            Self::Truncated => LogLevel::Warn,
+            Self::Failure => LogLevel::Error,
+            Self::Invoke | Self::ComputeUnits { .. } | Self::Success | Self::Other => {
+                LogLevel::Debug
+            }
+        }
+    }
+}
+
+/// One parsed log line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogLine {
+    /// Program executing when the line was written, if any
+    pub program: Option<Pubkey>,
+    /// Invocation depth, 1 for top-level instructions
+    pub depth: usize,
+    pub kind: LogKind,
+    /// The line without its `Program log: ` style prefix
+    pub message: String,
+}
+
+impl LogLine {
+    /// Severity of the line
+    pub fn level(&self) -> LogLevel {
+        self.kind.level()
+    }
+}
+
+/// Parse a transaction's log messages
+pub fn parse_logs(logs: &[String]) -> Vec<LogLine> {
+    let mut stack: Vec<Pubkey> = Vec::new();
+    logs.iter()
+        .map(|line| {
+            let (kind, message, program) = classify(line);
+            if kind == LogKind::Invoke {
+                if let Some(program) = program {
+                    stack.push(program);
+                }
+            }
+            let parsed = LogLine {
+                program: program.or_else(|| stack.last().copied()),
+                depth: stack.len(),
+                kind,
+                message,
+            };
+            if matches!(parsed.kind, LogKind::Success | LogKind::Failure) {
+                stack.pop();
+            }
+            parsed
+        })
+        .collect()
+}
+
+/// Kind, message and named program of one line
+fn classify(line: &str) -> (LogKind, String, Option<Pubkey>) {
+    if let Some(message) = line.strip_prefix(LOG_PREFIX) {
+        return (LogKind::Log, message.to_string(), None);
+    }
+    if let Some(data) = line.strip_prefix(DATA_PREFIX) {
+        return (LogKind::Data, data.to_string(), None);
+    }
+    if let Some(rest) = line.strip_prefix(RETURN_PREFIX) {
+        let (program, data) = rest.split_once(' ').unwrap_or((rest, ""));
+        return (
+            LogKind::Return,
+            data.to_string(),
+            Pubkey::from_base58(program).ok(),
+        );
+    }
+    if line == TRUNCATED {
+        return (LogKind::Truncated, line.to_string(), None);
+    }
+
+    let other = (LogKind::Other, line.to_string(), None);
+    let Some((program, rest)) = line
+        .strip_prefix("Program ")
+        .and_then(|rest| rest.split_once(' '))
+    else {
+        return other;
+    };
+    let Ok(program) = Pubkey::from_base58(program) else {
+        return other;
+    };
+
+    let kind = if rest.starts_with("invoke [") {
+        LogKind::Invoke
+    } else if rest == "success" {
+        LogKind::Success
+    } else if let Some(reason) = rest.strip_prefix("failed: ") {
+        return (LogKind::Failure, reason.to_string(), Some(program));
+    } else if let Some(units) = parse_compute_units(rest) {
+        units
+    } else {
+        LogKind::Other
+    };
+    (kind, line.to_string(), Some(program))
+}
+
+/// Parse `consumed <units> of <limit> compute units`
+fn parse_compute_units(rest: &str) -> Option<LogKind> {
+    let rest = rest.strip_prefix("consumed ")?;
+    let rest = rest.strip_suffix(" compute units")?;
+    let (consumed, limit) = rest.split_once(" of ")?;
+    Some(LogKind::ComputeUnits {
+        consumed: consumed.parse().ok()?,
+        limit: limit.parse().ok()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_nested_invocations() {
+        let outer = Pubkey::new([1; 32]);
+        let inner = Pubkey::new([2; 32]);
+        let logs: Vec<String> = [
+            format!("Program {} invoke [1]", outer),
+            "Program log: Instruction: Deposit".to_string(),
+            format!("Program {} invoke [2]", inner),
+            "Program log: transferring".to_string(),
+            format!("Program {} consumed 4645 of 190000 compute units", inner),
+            format!("Program {} success", inner),
+            "Program data: AQID".to_string(),
+            format!("Program {} failed: custom program error: 0x1", outer),
+        ]
+        .to_vec();
+
+        let lines = parse_logs(&logs);
+        assert_eq!(lines[1].program, Some(outer));
+        assert_eq!(lines[1].depth, 1);
+        assert_eq!(lines[1].message, "Instruction: Deposit");
+        assert_eq!(lines[1].level(), LogLevel::Info);
+        assert_eq!(lines[3].program, Some(inner));
+        assert_eq!(lines[3].depth, 2);
+        assert_eq!(
+            lines[4].kind,
+            LogKind::ComputeUnits {
+                consumed: 4645,
+                limit: 190000
+            }
+        );
+        assert_eq!(lines[6].program, Some(outer));
+        assert_eq!(lines[6].kind, LogKind::Data);
+        assert_eq!(lines[7].level(), LogLevel::Error);
+        assert_eq!(lines[7].message, "custom program error: 0x1");
+        assert_eq!(lines[7].depth, 1);
+    }
+
+    #[test]
+    fn test_unattributed_lines() {
+        let lines = parse_logs(&[TRUNCATED.to_string(), "Program is not deployed".to_string()]);
+        assert_eq!(lines[0].level(), LogLevel::Warn);
+        assert_eq!(lines[1].kind, LogKind::Other);
+        assert_eq!(lines[1].program, None);
+        assert_eq!(lines[1].depth, 0);
+    }
+}
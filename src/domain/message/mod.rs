@@ -0,0 +1,777 @@
+//! Legacy transaction message wire format
+//!
+//! Byte-exact serialization of Solana's legacy `Message`: a 3-byte header,
+//! compact-u16 prefixed account keys, the recent blockhash and compact-u16
+//! prefixed compiled instructions. Output matches `bincode` serialization of
+//! `solana_sdk::message::legacy::Message`.
+//!
+//! [`Message::compile`] builds a message from instructions the way solana-sdk
+//! does: every key appears once with the union of the privileges requested
+//! for it, the fee payer comes first, and the remaining keys are grouped into
+//! writable signers, read-only signers, writable and read-only accounts.
+//!
+//! [`Transaction`] pairs a message with its compact-u16 prefixed signatures,
+//! the format wallets and `sendTransaction` exchange as base64.
+
+use crate::domain::programs::ProgramInstruction;
+use crate::domain::types::{Hash, Pubkey, Signature};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+/// Largest value representable as a compact-u16
+pub const MAX_COMPACT_U16: usize = u16::MAX as usize;
+
+/// Counts of signer and read-only accounts at the front of the key list
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageHeader {
+    pub num_required_signatures: u8,
+    pub num_readonly_signed_accounts: u8,
+    pub num_readonly_unsigned_accounts: u8,
+}
+
+/// An instruction referencing accounts by index into the message's key list
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompiledInstruction {
+    pub program_id_index: u8,
+    pub accounts: Vec<u8>,
+    pub data: Vec<u8>,
+}
+
+/// A legacy transaction message
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Message {
+    pub header: MessageHeader,
+    pub account_keys: Vec<Pubkey>,
+    pub recent_blockhash: Hash,
+    pub instructions: Vec<CompiledInstruction>,
+}
+
+/// A key in a message together with its merged privileges
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageAccount {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+/// Errors that can occur while encoding or decoding messages
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum MessageError {
+    #[error("Length {0} does not fit in a compact-u16")]
+    LengthOverflow(usize),
+
+    #[error("Invalid compact-u16 encoding")]
+    InvalidCompactU16,
+
+    #[error("Unexpected end of message data")]
+    UnexpectedEnd,
+
+    #[error("{0} trailing bytes after message")]
+    TrailingBytes(usize),
+
+    #[error("Message references {0} accounts, at most 256 are allowed")]
+    TooManyAccounts(usize),
+
+    #[error("{0} accounts share one header group, at most 255 are allowed")]
+    HeaderOverflow(usize),
+
+    #[error("Versioned messages (version {0}) are not supported")]
+    UnsupportedVersion(u8),
+
+    #[error("Transaction has {found} signatures, its message requires {expected}")]
+    SignatureCount { expected: usize, found: usize },
+
+    #[error("{0} is not a required signer of the message")]
+    NotASigner(Pubkey),
+}
+
+/// A legacy message and the signatures of its required signers
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Transaction {
+    /// One signature per required signer, all zeroes until signed
+    pub signatures: Vec<Signature>,
+    pub message: Message,
+}
+
+/// Deduplicate the accounts of `instructions`, keeping the highest privilege
+///
+/// Program ids are read-only unless an instruction also passes them as a
+/// writable account. The payer, if any, is a writable signer and comes first;
+/// the other keys are ordered by privilege group and then by key.
+pub fn resolve_accounts(
+    instructions: &[ProgramInstruction],
+    payer: Option<&Pubkey>,
+) -> Vec<MessageAccount> {
+    // Keyed by bytes so keys come out in the same order as solana-sdk's
+    let mut privileges: BTreeMap<[u8; 32], (bool, bool)> = BTreeMap::new();
+    if let Some(payer) = payer {
+        privileges.insert(*payer.as_bytes(), (true, true));
+    }
+    for instruction in instructions {
+        privileges
+            .entry(*instruction.program_id.as_bytes())
+            .or_default();
+        for account in &instruction.accounts {
+            let entry = privileges.entry(*account.pubkey.as_bytes()).or_default();
+            entry.0 |= account.is_signer;
+            entry.1 |= account.is_writable;
+        }
+    }
+
+    let mut accounts: Vec<MessageAccount> = privileges
+        .into_iter()
+        .map(|(key, (is_signer, is_writable))| MessageAccount {
+            pubkey: Pubkey::new(key),
+            is_signer,
+            is_writable,
+        })
+        .collect();
+    // Stable sort keeps key order within each group
+    accounts.sort_by_key(|account| {
+        (
+            Some(&account.pubkey) != payer,
+            !account.is_signer,
+            !account.is_writable,
+        )
+    });
+    accounts
+}
+
+impl Message {
+    /// Compile instructions into a message paid for by `payer`
+    pub fn compile(
+        instructions: &[ProgramInstruction],
+        payer: Option<&Pubkey>,
+        recent_blockhash: Hash,
+    ) -> Result<Self, MessageError> {
+        let accounts = resolve_accounts(instructions, payer);
+        if accounts.len() > 256 {
+            return Err(MessageError::TooManyAccounts(accounts.len()));
+        }
+
+        let header_count = |matches: &dyn Fn(&MessageAccount) -> bool| {
+            let count = accounts.iter().filter(|a| matches(a)).count();
+            u8::try_from(count).map_err(|_| MessageError::HeaderOverflow(count))
+        };
+        let header = MessageHeader {
+            num_required_signatures: header_count(&|a| a.is_signer)?,
+            num_readonly_signed_accounts: header_count(&|a| a.is_signer && !a.is_writable)?,
+            num_readonly_unsigned_accounts: header_count(&|a| !a.is_signer && !a.is_writable)?,
+        };
+
+        let account_keys: Vec<Pubkey> = accounts.iter().map(|a| a.pubkey).collect();
+        let index_of = |key: &Pubkey| {
+            // Every key was added by `resolve_accounts`, and there are at most 256
+            account_keys
+                .iter()
+                .position(|k| k == key)
+                .unwrap_or_default() as u8
+        };
+        let instructions = instructions
+            .iter()
+            .map(|instruction| CompiledInstruction {
+                program_id_index: index_of(&instruction.program_id),
+                accounts: instruction
+                    .accounts
+                    .iter()
+                    .map(|account| index_of(&account.pubkey))
+                    .collect(),
+                data: instruction.data.clone(),
+            })
+            .collect();
+
+        Ok(Self {
+            header,
+            account_keys,
+            recent_blockhash,
+            instructions,
+        })
+    }
+
+    /// Every key with the privileges the header grants it
+    pub fn accounts(&self) -> Vec<MessageAccount> {
+        (0..self.account_keys.len())
+            .map(|index| MessageAccount {
+                pubkey: self.account_keys[index],
+                is_signer: self.is_signer(index),
+                is_writable: self.is_writable(index),
+            })
+            .collect()
+    }
+
+    /// Whether the key at `index` must sign
+    pub fn is_signer(&self, index: usize) -> bool {
+        index < usize::from(self.header.num_required_signatures)
+    }
+
+    /// Whether the key at `index` is writable
+    pub fn is_writable(&self, index: usize) -> bool {
+        let signers = usize::from(self.header.num_required_signatures);
+        if index < signers {
+            index < signers.saturating_sub(usize::from(self.header.num_readonly_signed_accounts))
+        } else {
+            index < self.account_keys.len()
+                && index
+                    < self
+                        .account_keys
+                        .len()
+                        .saturating_sub(usize::from(self.header.num_readonly_unsigned_accounts))
+        }
+    }
+
+    /// Serialize the message to its wire format
+    pub fn serialize(&self) -> Result<Vec<u8>, MessageError> {
+        let mut out = vec![
+            self.header.num_required_signatures,
+            self.header.num_readonly_signed_accounts,
+            self.header.num_readonly_unsigned_accounts,
+        ];
+
+        encode_length(&mut out, self.account_keys.len())?;
+        for key in &self.account_keys {
+            out.extend_from_slice(key.as_bytes());
+        }
+
+        out.extend_from_slice(self.recent_blockhash.as_bytes());
+
+        encode_length(&mut out, self.instructions.len())?;
+        for instruction in &self.instructions {
+            out.push(instruction.program_id_index);
+            encode_length(&mut out, instruction.accounts.len())?;
+            out.extend_from_slice(&instruction.accounts);
+            encode_length(&mut out, instruction.data.len())?;
+            out.extend_from_slice(&instruction.data);
+        }
+
+        Ok(out)
+    }
+
+    /// Deserialize a message from its wire format
+    pub fn deserialize(bytes: &[u8]) -> Result<Self, MessageError> {
+        let mut reader = Reader { bytes, position: 0 };
+
+        let header = MessageHeader {
+            num_required_signatures: reader.u8()?,
+            num_readonly_signed_accounts: reader.u8()?,
+            num_readonly_unsigned_accounts: reader.u8()?,
+        };
+
+        let key_count = reader.length()?;
+        let mut account_keys = Vec::with_capacity(key_count.min(bytes.len() / 32));
+        for _ in 0..key_count {
+            account_keys.push(Pubkey::new(reader.array()?));
+        }
+
+        let recent_blockhash = Hash::new(reader.array()?);
+
+        let instruction_count = reader.length()?;
+        let mut instructions = Vec::with_capacity(instruction_count.min(bytes.len()));
+        for _ in 0..instruction_count {
+            let program_id_index = reader.u8()?;
+            let account_count = reader.length()?;
+            let accounts = reader.take(account_count)?.to_vec();
+            let data_len = reader.length()?;
+            let data = reader.take(data_len)?.to_vec();
+            instructions.push(CompiledInstruction {
+                program_id_index,
+                accounts,
+                data,
+            });
+        }
+
+        let remaining = bytes.len() - reader.position;
+        if remaining > 0 {
+            return Err(MessageError::TrailingBytes(remaining));
+        }
+
+        Ok(Self {
+            header,
+            account_keys,
+            recent_blockhash,
+            instructions,
+        })
+    }
+}
+
+impl Transaction {
+    /// Wrap a message with empty signature slots for every required signer
+    pub fn new_unsigned(message: Message) -> Self {
+        let count = usize::from(message.header.num_required_signatures);
+        Self {
+            signatures: vec![Signature::new([0; 64]); count],
+            message,
+        }
+    }
+
+    /// Signers whose slot still holds an empty signature
+    pub fn missing_signers(&self) -> Vec<Pubkey> {
+        self.signatures
+            .iter()
+            .zip(&self.message.account_keys)
+            .filter(|(signature, _)| signature.as_bytes() == &[0; 64])
+            .map(|(_, key)| *key)
+            .collect()
+    }
+
+    /// Store `signature` in `signer`'s slot
+    pub fn add_signature(
+        &mut self,
+        signer: &Pubkey,
+        signature: Signature,
+    ) -> Result<(), MessageError> {
+        let index = self
+            .message
+            .account_keys
+            .iter()
+            .position(|key| key == signer)
+            .filter(|index| self.message.is_signer(*index))
+            .ok_or(MessageError::NotASigner(*signer))?;
+        let slot = self
+            .signatures
+            .get_mut(index)
+            .ok_or(MessageError::SignatureCount {
+                expected: usize::from(self.message.header.num_required_signatures),
+                found: index,
+            })?;
+        *slot = signature;
+        Ok(())
+    }
+
+    /// Serialize the transaction to its wire format
+    pub fn serialize(&self) -> Result<Vec<u8>, MessageError> {
+        let mut out = Vec::with_capacity(1 + self.signatures.len() * 64);
+        encode_length(&mut out, self.signatures.len())?;
+        for signature in &self.signatures {
+            out.extend_from_slice(signature.as_bytes());
+        }
+        out.extend(self.message.serialize()?);
+        Ok(out)
+    }
+
+    /// Deserialize a legacy transaction from its wire format
+    pub fn deserialize(bytes: &[u8]) -> Result<Self, MessageError> {
+        let mut reader = Reader { bytes, position: 0 };
+        let count = reader.length()?;
+        let mut signatures = Vec::with_capacity(count.min(bytes.len() / 64));
+        for _ in 0..count {
+            let mut signature = [0u8; 64];
+            signature.copy_from_slice(reader.take(64)?);
+            signatures.push(Signature::new(signature));
+        }
+
+        let message_bytes = &bytes[reader.position..];
+        if let Some(prefix) = message_bytes.first().filter(|prefix| *prefix & 0x80 != 0) {
+            return Err(MessageError::UnsupportedVersion(prefix & 0x7f));
+        }
+        let message = Message::deserialize(message_bytes)?;
+
+        let expected = usize::from(message.header.num_required_signatures);
+        if signatures.len() != expected {
+            return Err(MessageError::SignatureCount {
+                expected,
+                found: signatures.len(),
+            });
+        }
+        Ok(Self {
+            signatures,
+            message,
+        })
+    }
+}
+
+/// Append a compact-u16 ("short_vec") length prefix
+pub fn encode_length(out: &mut Vec<u8>, len: usize) -> Result<(), MessageError> {
+    if len > MAX_COMPACT_U16 {
+        return Err(MessageError::LengthOverflow(len));
+    }
+
+    let mut remaining = len;
+    loop {
+        let mut byte = (remaining & 0x7f) as u8;
+        remaining >>= 7;
+        if remaining == 0 {
+            out.push(byte);
+            return Ok(());
+        }
+        byte |= 0x80;
+        out.push(byte);
+    }
+}
+
+/// Decode a compact-u16 length prefix, returning the value and bytes consumed
+pub fn decode_length(bytes: &[u8]) -> Result<(usize, usize), MessageError> {
+    let mut value = 0usize;
+    for (index, byte) in bytes.iter().take(3).enumerate() {
+        let part = usize::from(byte & 0x7f);
+        value |= part << (index * 7);
+
+        if byte & 0x80 == 0 {
+            // Reject non-canonical encodings (trailing zero bytes) and overflow
+            if (index > 0 && *byte == 0) || value > MAX_COMPACT_U16 {
+                return Err(MessageError::InvalidCompactU16);
+            }
+            return Ok((value, index + 1));
+        }
+        if index == 2 {
+            return Err(MessageError::InvalidCompactU16);
+        }
+    }
+    Err(MessageError::UnexpectedEnd)
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], MessageError> {
+        let end = self
+            .position
+            .checked_add(len)
+            .ok_or(MessageError::UnexpectedEnd)?;
+        let slice = self
+            .bytes
+            .get(self.position..end)
+            .ok_or(MessageError::UnexpectedEnd)?;
+        self.position = end;
+        Ok(slice)
+    }
+
+    fn u8(&mut self) -> Result<u8, MessageError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn array(&mut self) -> Result<[u8; 32], MessageError> {
+        let mut array = [0u8; 32];
+        array.copy_from_slice(self.take(32)?);
+        Ok(array)
+    }
+
+    fn length(&mut self) -> Result<usize, MessageError> {
+        let (value, consumed) = decode_length(&self.bytes[self.position..])?;
+        self.position += consumed;
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::programs::{AccountRole, InstructionAccount};
+    use proptest::prelude::*;
+
+    /// Vectors from solana-sdk's `short_vec` tests
+    const COMPACT_U16_VECTORS: &[(usize, &[u8])] = &[
+        (0x0000, &[0x00]),
+        (0x007f, &[0x7f]),
+        (0x0080, &[0x80, 0x01]),
+        (0x00ff, &[0xff, 0x01]),
+        (0x0100, &[0x80, 0x02]),
+        (0x07ff, &[0xff, 0x0f]),
+        (0x3fff, &[0xff, 0x7f]),
+        (0x4000, &[0x80, 0x80, 0x01]),
+        (0xffff, &[0xff, 0xff, 0x03]),
+    ];
+
+    fn transfer_message() -> Message {
+        let mut data = vec![2, 0, 0, 0];
+        data.extend_from_slice(&1_000_000_000u64.to_le_bytes());
+        Message {
+            header: MessageHeader {
+                num_required_signatures: 1,
+                num_readonly_signed_accounts: 0,
+                num_readonly_unsigned_accounts: 1,
+            },
+            account_keys: vec![
+                Pubkey::new([1; 32]),
+                Pubkey::new([2; 32]),
+                Pubkey::new([0; 32]),
+            ],
+            recent_blockhash: Hash::new([3; 32]),
+            instructions: vec![CompiledInstruction {
+                program_id_index: 2,
+                accounts: vec![0, 1],
+                data,
+            }],
+        }
+    }
+
+    fn meta(pubkey: Pubkey, is_signer: bool, is_writable: bool) -> InstructionAccount {
+        InstructionAccount {
+            pubkey,
+            is_signer,
+            is_writable,
+            role: AccountRole::Readonly,
+        }
+    }
+
+    fn instruction(program_id: Pubkey, accounts: Vec<InstructionAccount>) -> ProgramInstruction {
+        ProgramInstruction {
+            program_id,
+            accounts,
+            data: vec![9],
+            instruction_id: 0,
+        }
+    }
+
+    #[test]
+    fn test_compile_matches_system_transfer() {
+        let mut data = vec![2, 0, 0, 0];
+        data.extend_from_slice(&1_000_000_000u64.to_le_bytes());
+        let transfer = ProgramInstruction {
+            data,
+            ..instruction(
+                Pubkey::new([0; 32]),
+                vec![
+                    meta(Pubkey::new([1; 32]), true, true),
+                    meta(Pubkey::new([2; 32]), false, true),
+                ],
+            )
+        };
+        let message =
+            Message::compile(&[transfer], Some(&Pubkey::new([1; 32])), Hash::new([3; 32])).unwrap();
+        assert_eq!(message, transfer_message());
+    }
+
+    #[test]
+    fn test_compile_merges_privileges() {
+        let payer = Pubkey::new([9; 32]);
+        let program = Pubkey::new([5; 32]);
+        let shared = Pubkey::new([1; 32]);
+        let signer = Pubkey::new([2; 32]);
+        let instructions = [
+            instruction(
+                program,
+                vec![meta(shared, false, false), meta(signer, true, false)],
+            ),
+            instruction(
+                program,
+                vec![meta(shared, false, true), meta(payer, false, false)],
+            ),
+        ];
+
+        let accounts = resolve_accounts(&instructions, Some(&payer));
+        assert_eq!(
+            accounts,
+            vec![
+                MessageAccount {
+                    pubkey: payer,
+                    is_signer: true,
+                    is_writable: true
+                },
+                MessageAccount {
+                    pubkey: signer,
+                    is_signer: true,
+                    is_writable: false
+                },
+                MessageAccount {
+                    pubkey: shared,
+                    is_signer: false,
+                    is_writable: true
+                },
+                MessageAccount {
+                    pubkey: program,
+                    is_signer: false,
+                    is_writable: false
+                },
+            ]
+        );
+
+        let message = Message::compile(&instructions, Some(&payer), Hash::new([3; 32])).unwrap();
+        assert_eq!(
+            message.header,
+            MessageHeader {
+                num_required_signatures: 2,
+                num_readonly_signed_accounts: 1,
+                num_readonly_unsigned_accounts: 1,
+            }
+        );
+        assert_eq!(message.accounts(), accounts);
+        assert_eq!(message.instructions[0].program_id_index, 3);
+        assert_eq!(message.instructions[0].accounts, vec![2, 1]);
+        assert_eq!(message.instructions[1].accounts, vec![2, 0]);
+
+        let too_many: Vec<_> = (0..=255u8)
+            .map(|i| {
+                let mut key = [7; 32];
+                key[0] = i;
+                meta(Pubkey::new(key), false, false)
+            })
+            .collect();
+        assert_eq!(
+            Message::compile(
+                &[instruction(program, too_many)],
+                Some(&payer),
+                Hash::new([3; 32])
+            ),
+            Err(MessageError::TooManyAccounts(258))
+        );
+        // 255 read-only accounts and the program fit, but not in one header count
+        let readonly: Vec<_> = (0..255u8)
+            .map(|i| {
+                let mut key = [7; 32];
+                key[0] = i;
+                meta(Pubkey::new(key), false, false)
+            })
+            .collect();
+        assert_eq!(
+            Message::compile(&[instruction(program, readonly)], None, Hash::new([3; 32])),
+            Err(MessageError::HeaderOverflow(256))
+        );
+    }
+
+    #[test]
+    fn test_compact_u16_vectors() {
+        for (value, encoded) in COMPACT_U16_VECTORS {
+            let mut out = Vec::new();
+            encode_length(&mut out, *value).unwrap();
+            assert_eq!(out, *encoded, "encoding {:#x}", value);
+            assert_eq!(decode_length(encoded).unwrap(), (*value, encoded.len()));
+        }
+    }
+
+    #[test]
+    fn test_compact_u16_rejects_invalid() {
+        assert_eq!(
+            decode_length(&[0x80, 0x00]),
+            Err(MessageError::InvalidCompactU16)
+        );
+        assert_eq!(
+            decode_length(&[0x80, 0x80, 0x04]),
+            Err(MessageError::InvalidCompactU16)
+        );
+        assert_eq!(
+            decode_length(&[0x80, 0x80, 0x80, 0x01]),
+            Err(MessageError::InvalidCompactU16)
+        );
+        assert_eq!(decode_length(&[0x80]), Err(MessageError::UnexpectedEnd));
+        assert!(encode_length(&mut Vec::new(), 0x10000).is_err());
+    }
+
+    #[test]
+    fn test_system_transfer_wire_format() {
+        let bytes = transfer_message().serialize().unwrap();
+
+        let mut expected = vec![1, 0, 1, 3];
+        expected.extend_from_slice(&[1; 32]);
+        expected.extend_from_slice(&[2; 32]);
+        expected.extend_from_slice(&[0; 32]);
+        expected.extend_from_slice(&[3; 32]);
+        expected.extend_from_slice(&[1, 2, 2, 0, 1, 12, 2, 0, 0, 0]);
+        expected.extend_from_slice(&[0x00, 0xca, 0x9a, 0x3b, 0, 0, 0, 0]);
+        assert_eq!(bytes, expected);
+        assert_eq!(bytes.len(), 150);
+
+        assert_eq!(Message::deserialize(&bytes).unwrap(), transfer_message());
+    }
+
+    #[test]
+    fn test_deserialize_rejects_truncated_and_trailing() {
+        let bytes = transfer_message().serialize().unwrap();
+        assert_eq!(
+            Message::deserialize(&bytes[..bytes.len() - 1]),
+            Err(MessageError::UnexpectedEnd)
+        );
+
+        let mut extended = bytes;
+        extended.push(0);
+        assert_eq!(
+            Message::deserialize(&extended),
+            Err(MessageError::TrailingBytes(1))
+        );
+    }
+
+    #[test]
+    fn test_transaction_round_trip() {
+        let mut transaction = Transaction::new_unsigned(transfer_message());
+        assert_eq!(transaction.missing_signers(), vec![Pubkey::new([1; 32])]);
+        assert_eq!(
+            transaction.add_signature(&Pubkey::new([2; 32]), Signature::new([9; 64])),
+            Err(MessageError::NotASigner(Pubkey::new([2; 32])))
+        );
+        transaction
+            .add_signature(&Pubkey::new([1; 32]), Signature::new([9; 64]))
+            .unwrap();
+        assert!(transaction.missing_signers().is_empty());
+
+        let bytes = transaction.serialize().unwrap();
+        assert_eq!(bytes.len(), 1 + 64 + 150);
+        assert_eq!(Transaction::deserialize(&bytes).unwrap(), transaction);
+
+        let mut versioned = bytes.clone();
+        versioned[65] = 0x80;
+        assert_eq!(
+            Transaction::deserialize(&versioned),
+            Err(MessageError::UnsupportedVersion(0))
+        );
+        let mut unsigned = vec![0];
+        unsigned.extend_from_slice(&bytes[65..]);
+        assert_eq!(
+            Transaction::deserialize(&unsigned),
+            Err(MessageError::SignatureCount {
+                expected: 1,
+                found: 0
+            })
+        );
+    }
+
+    fn arb_message() -> impl Strategy<Value = Message> {
+        let instruction = (
+            any::<u8>(),
+            prop::collection::vec(any::<u8>(), 0..8),
+            prop::collection::vec(any::<u8>(), 0..300),
+        )
+            .prop_map(|(program_id_index, accounts, data)| CompiledInstruction {
+                program_id_index,
+                accounts,
+                data,
+            });
+
+        (
+            any::<[u8; 3]>(),
+            prop::collection::vec(any::<[u8; 32]>(), 0..6),
+            any::<[u8; 32]>(),
+            prop::collection::vec(instruction, 0..4),
+        )
+            .prop_map(|(header, keys, blockhash, instructions)| Message {
+                header: MessageHeader {
+                    num_required_signatures: header[0],
+                    num_readonly_signed_accounts: header[1],
+                    num_readonly_unsigned_accounts: header[2],
+                },
+                account_keys: keys.into_iter().map(Pubkey::new).collect(),
+                recent_blockhash: Hash::new(blockhash),
+                instructions,
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn prop_compact_u16_roundtrip(value in 0usize..=MAX_COMPACT_U16) {
+            let mut out = Vec::new();
+            encode_length(&mut out, value).unwrap();
+            prop_assert!(out.len() <= 3);
+            prop_assert_eq!(decode_length(&out).unwrap(), (value, out.len()));
+        }
+
+        #[test]
+        fn prop_message_roundtrip(message in arb_message()) {
+            let bytes = message.serialize().unwrap();
+            prop_assert_eq!(Message::deserialize(&bytes).unwrap(), message);
+        }
+
+        #[test]
+        fn prop_deserialize_never_panics(bytes in prop::collection::vec(any::<u8>(), 0..512)) {
+            if let Ok(message) = Message::deserialize(&bytes) {
+                prop_assert_eq!(message.serialize().unwrap(), bytes);
+            }
+        }
+    }
+}
@@ -3,13 +3,43 @@
 //! This module contains the core domain types and business logic for the Solana
 //! library, following Domain-Driven Design principles.
 
+pub mod diff;
+pub mod discriminator;
+pub mod errors;
+#[cfg(feature = "anchor")]
+pub mod idl;
+pub mod layout;
+pub mod logs;
+pub mod message;
+#[cfg(feature = "oracles")]
+pub mod oracles;
 pub mod programs;
+pub mod rent;
 pub mod types;
+pub mod validation;
 
 // Re-export commonly used domain types
+pub use diff::{
+    diff_bytes, diff_fields, AccountDiff, AccountState, ByteRangeChange, Change, DiffError,
+    FieldChange,
+};
+pub use discriminator::{
+    account_discriminator, decode_account, instruction_discriminator, Discriminator,
+    DiscriminatorError,
+};
+pub use errors::{CustomErrorResolver, InstructionError, ResolvedError, TransactionError};
+#[cfg(feature = "anchor")]
+pub use idl::{Idl, IdlError, IdlEvent};
+pub use layout::{AccountLayout, BorshSize};
+pub use logs::{parse_logs, LogKind, LogLevel, LogLine};
+pub use message::{
+    resolve_accounts, CompiledInstruction, Message, MessageAccount, MessageError, MessageHeader,
+    Transaction,
+};
 pub use programs::{
     AccountMetadata, AccountRole, CreateAccountParams, CreateAccountRequest, DeploymentConfig,
     InstructionAccount, Program, ProgramAccount, ProgramDeployment, ProgramInstruction,
     ProgramStatus,
 };
 pub use types::{Hash, HashError, Pubkey, PubkeyError, Signature, SignatureError};
+pub use validation::ValidationError;
@@ -0,0 +1,373 @@
+//! Price oracle accounts
+//!
+//! Pyth and Switchboard publish prices into accounts any app can read with
+//! `getAccountInfo`. [`PythPriceUpdate`] decodes the `PriceUpdateV2` accounts
+//! of Pyth's pull oracle, including the sponsored feeds kept current by its
+//! push oracle, and [`SwitchboardFeed`] the pull feeds of Switchboard
+//! On-Demand. Both reduce to an [`OraclePrice`], a fixed-point price with its
+//! confidence and publish time; [`decode_price`] picks the decoder from the
+//! account's owner.
+
+use crate::domain::discriminator::{
+    account_discriminator, decode_account, Discriminator, DiscriminatorError, DISCRIMINATOR_LEN,
+};
+use crate::domain::types::Pubkey;
+use borsh::{BorshDeserialize, BorshSerialize};
+use std::fmt;
+use thiserror::Error;
+
+/// Pyth's pull oracle receiver, owner of posted price updates
+pub const PYTH_RECEIVER_PROGRAM_ID: Pubkey = Pubkey::new([
+    12, 183, 250, 187, 82, 247, 166, 72, 187, 91, 49, 125, 154, 1, 139, 144, 87, 203, 2, 71, 116,
+    250, 254, 1, 230, 196, 223, 152, 204, 56, 88, 129,
+]);
+
+/// Pyth's push oracle, owner of the sponsored price feed accounts
+pub const PYTH_PUSH_ORACLE_PROGRAM_ID: Pubkey = Pubkey::new([
+    12, 74, 160, 18, 142, 149, 211, 225, 98, 42, 165, 1, 197, 133, 169, 235, 7, 179, 115, 84, 193,
+    8, 234, 11, 121, 27, 69, 109, 199, 238, 163, 54,
+]);
+
+/// Switchboard On-Demand, owner of pull feed accounts
+pub const SWITCHBOARD_ON_DEMAND_PROGRAM_ID: Pubkey = Pubkey::new([
+    6, 115, 189, 70, 242, 228, 126, 4, 241, 43, 217, 47, 183, 49, 150, 142, 205, 157, 151, 87, 194,
+    116, 218, 135, 71, 111, 70, 92, 4, 12, 101, 115,
+]);
+
+/// Decimal exponent of Switchboard's 18-decimal fixed-point values
+pub const SWITCHBOARD_EXPONENT: i32 = -18;
+
+/// Errors that can occur while decoding an oracle account
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum OracleError {
+    #[error("Invalid oracle account: {0}")]
+    Discriminator(#[from] DiscriminatorError),
+
+    #[error("Oracle account is {len} bytes, expected at least {needed}")]
+    TooShort { len: usize, needed: usize },
+
+    #[error("{0} is not a known oracle program")]
+    UnknownOwner(Pubkey),
+}
+
+/// Which oracle published a price
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OracleSource {
+    Pyth,
+    Switchboard,
+}
+
+impl fmt::Display for OracleSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Pyth => "Pyth",
+            Self::Switchboard => "Switchboard",
+        })
+    }
+}
+
+/// A price read from an oracle account: `mantissa * 10^exponent`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OraclePrice {
+    pub source: OracleSource,
+    pub mantissa: i128,
+    pub exponent: i32,
+    /// Confidence interval, or standard deviation, at the same exponent
+    pub confidence: u128,
+    /// Unix timestamp the price was published at
+    pub publish_time: i64,
+    /// Slot the price was posted in
+    pub slot: u64,
+    /// Whether every signature or response the oracle requires was checked
+    pub verified: bool,
+}
+
+impl OraclePrice {
+    /// The price as a float, for display
+    pub fn value(&self) -> f64 {
+        scale(self.mantissa as f64, self.exponent)
+    }
+
+    /// The confidence interval as a float
+    pub fn confidence_value(&self) -> f64 {
+        scale(self.confidence as f64, self.exponent)
+    }
+
+    /// Worth of `amount` base units of a token with `decimals` decimals
+    pub fn value_of(&self, amount: u64, decimals: u8) -> f64 {
+        scale(amount as f64, -i32::from(decimals)) * self.value()
+    }
+
+    /// Seconds from publishing to `now`, 0 if published in the future
+    pub fn age(&self, now: i64) -> u64 {
+        u64::try_from(now.saturating_sub(self.publish_time)).unwrap_or(0)
+    }
+}
+
+fn scale(value: f64, exponent: i32) -> f64 {
+    value * 10f64.powi(exponent)
+}
+
+/// How many Wormhole guardian signatures checked a Pyth update
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub enum VerificationLevel {
+    Partial { num_signatures: u8 },
+    Full,
+}
+
+/// The price a Pyth update carries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct PriceFeedMessage {
+    pub feed_id: [u8; 32],
+    pub price: i64,
+    pub conf: u64,
+    pub exponent: i32,
+    pub publish_time: i64,
+    pub prev_publish_time: i64,
+    pub ema_price: i64,
+    pub ema_conf: u64,
+}
+
+/// A Pyth `PriceUpdateV2` account
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct PythPriceUpdate {
+    pub write_authority: Pubkey,
+    pub verification_level: VerificationLevel,
+    pub price_message: PriceFeedMessage,
+    pub posted_slot: u64,
+}
+
+impl Discriminator for PythPriceUpdate {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = account_discriminator("PriceUpdateV2");
+}
+
+impl PythPriceUpdate {
+    /// Decode the data of a price update account
+    pub fn from_account_data(data: &[u8]) -> Result<Self, OracleError> {
+        Ok(decode_account(data)?)
+    }
+
+    /// The spot price of the update
+    pub fn price(&self) -> OraclePrice {
+        let message = &self.price_message;
+        OraclePrice {
+            source: OracleSource::Pyth,
+            mantissa: i128::from(message.price),
+            exponent: message.exponent,
+            confidence: u128::from(message.conf),
+            publish_time: message.publish_time,
+            slot: self.posted_slot,
+            verified: self.verification_level == VerificationLevel::Full,
+        }
+    }
+}
+
+/// Offsets in a Switchboard `PullFeedAccountData`, discriminator included
+///
+/// The account is a `repr(C)` struct read in place, so fields sit at fixed
+/// offsets after 32 oracle submissions of 64 bytes each.
+mod switchboard_layout {
+    pub const AUTHORITY: usize = 2056;
+    pub const QUEUE: usize = 2088;
+    pub const FEED_HASH: usize = 2120;
+    pub const MIN_RESPONSES: usize = 2176;
+    pub const NAME: usize = 2180;
+    pub const LAST_UPDATE_TIMESTAMP: usize = 2216;
+    pub const RESULT_VALUE: usize = 2264;
+    pub const RESULT_STD_DEV: usize = 2280;
+    pub const RESULT_NUM_SAMPLES: usize = 2360;
+    pub const RESULT_SLOT: usize = 2368;
+    pub const MAX_STALENESS: usize = 2392;
+    /// Bytes up to and including `max_staleness`
+    pub const MIN_LEN: usize = 2396;
+}
+
+/// A Switchboard On-Demand pull feed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SwitchboardFeed {
+    pub authority: Pubkey,
+    pub queue: Pubkey,
+    pub feed_hash: [u8; 32],
+    /// Feed name, e.g. `SOL/USD`
+    pub name: String,
+    /// Responses a result needs
+    pub min_responses: u32,
+    /// Median of the latest responses, with 18 decimals
+    pub value: i128,
+    /// Standard deviation of the latest responses, with 18 decimals
+    pub std_dev: i128,
+    /// Responses the result was computed from
+    pub num_samples: u8,
+    /// Slot the result was computed in
+    pub slot: u64,
+    /// Unix timestamp of the latest update
+    pub last_update_timestamp: i64,
+    /// Slots after which the feed considers its result stale
+    pub max_staleness: u32,
+}
+
+impl Discriminator for SwitchboardFeed {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = account_discriminator("PullFeedAccountData");
+}
+
+impl SwitchboardFeed {
+    /// Decode the data of a pull feed account
+    pub fn from_account_data(data: &[u8]) -> Result<Self, OracleError> {
+        use switchboard_layout::*;
+
+        crate::domain::discriminator::check_discriminator(data, &Self::DISCRIMINATOR)?;
+        if data.len() < MIN_LEN {
+            return Err(OracleError::TooShort {
+                len: data.len(),
+                needed: MIN_LEN,
+            });
+        }
+        let bytes = |offset: usize, len: usize| &data[offset..offset + len];
+        let array = |offset: usize| -> [u8; 32] {
+            let mut out = [0; 32];
+            out.copy_from_slice(bytes(offset, 32));
+            out
+        };
+        let int = |offset: usize| {
+            let mut out = [0; 16];
+            out.copy_from_slice(bytes(offset, 16));
+            i128::from_le_bytes(out)
+        };
+        let word = |offset: usize| {
+            let mut out = [0; 8];
+            out.copy_from_slice(bytes(offset, 8));
+            out
+        };
+        let half = |offset: usize| {
+            let mut out = [0; 4];
+            out.copy_from_slice(bytes(offset, 4));
+            u32::from_le_bytes(out)
+        };
+        let name = bytes(NAME, 32);
+        let name = &name[..name.iter().position(|b| *b == 0).unwrap_or(32)];
+
+        Ok(Self {
+            authority: Pubkey::new(array(AUTHORITY)),
+            queue: Pubkey::new(array(QUEUE)),
+            feed_hash: array(FEED_HASH),
+            name: String::from_utf8_lossy(name).into_owned(),
+            min_responses: half(MIN_RESPONSES),
+            value: int(RESULT_VALUE),
+            std_dev: int(RESULT_STD_DEV),
+            num_samples: data[RESULT_NUM_SAMPLES],
+            slot: u64::from_le_bytes(word(RESULT_SLOT)),
+            last_update_timestamp: i64::from_le_bytes(word(LAST_UPDATE_TIMESTAMP)),
+            max_staleness: half(MAX_STALENESS),
+        })
+    }
+
+    /// The feed's current result
+    pub fn price(&self) -> OraclePrice {
+        OraclePrice {
+            source: OracleSource::Switchboard,
+            mantissa: self.value,
+            exponent: SWITCHBOARD_EXPONENT,
+            confidence: self.std_dev.unsigned_abs(),
+            publish_time: self.last_update_timestamp,
+            slot: self.slot,
+            verified: u32::from(self.num_samples) >= self.min_responses,
+        }
+    }
+}
+
+/// Decode the price in an account owned by `owner`
+pub fn decode_price(owner: &Pubkey, data: &[u8]) -> Result<OraclePrice, OracleError> {
+    match *owner {
+        PYTH_RECEIVER_PROGRAM_ID | PYTH_PUSH_ORACLE_PROGRAM_ID => {
+            Ok(PythPriceUpdate::from_account_data(data)?.price())
+        }
+        SWITCHBOARD_ON_DEMAND_PROGRAM_ID => Ok(SwitchboardFeed::from_account_data(data)?.price()),
+        _ => Err(OracleError::UnknownOwner(*owner)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Data of a Pyth update of `price` at exponent -8, published at `time`
+    fn pyth_update(price: i64, time: i64) -> Vec<u8> {
+        let update = PythPriceUpdate {
+            write_authority: Pubkey::new([1; 32]),
+            verification_level: VerificationLevel::Full,
+            price_message: PriceFeedMessage {
+                feed_id: [2; 32],
+                price,
+                conf: 5_000_000,
+                exponent: -8,
+                publish_time: time,
+                prev_publish_time: time - 1,
+                ema_price: price,
+                ema_conf: 5_000_000,
+            },
+            posted_slot: 300,
+        };
+        let mut data = PythPriceUpdate::DISCRIMINATOR.to_vec();
+        data.extend(borsh::to_vec(&update).unwrap());
+        data
+    }
+
+    #[test]
+    fn test_program_ids() {
+        for (id, base58) in [
+            (
+                PYTH_RECEIVER_PROGRAM_ID,
+                "rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ",
+            ),
+            (
+                PYTH_PUSH_ORACLE_PROGRAM_ID,
+                "pythWSnswVUd12oZpeFP8e9CVaEqJg25g1Vtc2biRsT",
+            ),
+            (
+                SWITCHBOARD_ON_DEMAND_PROGRAM_ID,
+                "SBondMDrcV3K4kxZR1HNVT7osZxAHVHgYXL5Ze1oMUv",
+            ),
+        ] {
+            assert_eq!(id.to_base58(), base58);
+        }
+    }
+
+    #[test]
+    fn test_decode_pyth_and_switchboard() {
+        let price = decode_price(
+            &PYTH_RECEIVER_PROGRAM_ID,
+            &pyth_update(15_230_000_000, 1_700_000_000),
+        )
+        .unwrap();
+        assert_eq!(price.source, OracleSource::Pyth);
+        assert!((price.value() - 152.3).abs() < 1e-9);
+        assert!((price.value_of(2_500_000_000, 9) - 380.75).abs() < 1e-9);
+        assert_eq!(price.age(1_700_000_030), 30);
+        assert!(price.verified);
+
+        use switchboard_layout::*;
+        let mut data = vec![0u8; MIN_LEN + 100];
+        data[..8].copy_from_slice(&SwitchboardFeed::DISCRIMINATOR);
+        data[NAME..NAME + 7].copy_from_slice(b"SOL/USD");
+        data[MIN_RESPONSES..MIN_RESPONSES + 4].copy_from_slice(&3u32.to_le_bytes());
+        let value = 152_300_000_000_000_000_000i128;
+        data[RESULT_VALUE..RESULT_VALUE + 16].copy_from_slice(&value.to_le_bytes());
+        data[RESULT_NUM_SAMPLES] = 5;
+        data[LAST_UPDATE_TIMESTAMP..LAST_UPDATE_TIMESTAMP + 8]
+            .copy_from_slice(&1_700_000_000i64.to_le_bytes());
+        let feed = SwitchboardFeed::from_account_data(&data).unwrap();
+        assert_eq!(feed.name, "SOL/USD");
+        let price = decode_price(&SWITCHBOARD_ON_DEMAND_PROGRAM_ID, &data).unwrap();
+        assert!((price.value() - 152.3).abs() < 1e-9);
+        assert!(price.verified);
+
+        assert!(matches!(
+            SwitchboardFeed::from_account_data(&data[..MIN_LEN - 1]),
+            Err(OracleError::TooShort { .. })
+        ));
+        assert!(matches!(
+            decode_price(&PYTH_RECEIVER_PROGRAM_ID, &data),
+            Err(OracleError::Discriminator(_))
+        ));
+    }
+}
@@ -0,0 +1,349 @@
+//! BPF upgradeable loader accounts and instructions
+//!
+//! Decodes the loader's `UpgradeableLoaderState` (buffer, program and
+//! programdata accounts) and builds the instructions used after initial
+//! deployment: writing to a buffer, changing a buffer's authority, upgrading a
+//! program from a buffer and closing buffers or programs to reclaim rent.
+
+use super::{AccountRole, InstructionAccount, ProgramInstruction};
+use crate::domain::types::constants::{SYSVAR_CLOCK_ID, SYSVAR_RENT_ID};
+use crate::domain::types::{Pubkey, PubkeyError};
+use thiserror::Error;
+
+pub use crate::domain::types::constants::BPF_LOADER_UPGRADEABLE_ID;
+
+/// Bytes of loader metadata at the start of a buffer account
+pub const BUFFER_METADATA_SIZE: usize = 37;
+
+/// Bytes of loader metadata at the start of a programdata account
+pub const PROGRAMDATA_METADATA_SIZE: usize = 45;
+
+/// Size of a program account
+pub const PROGRAM_ACCOUNT_SIZE: usize = 36;
+
+/// Largest chunk written by a single `Write` instruction
+pub const MAX_WRITE_CHUNK: usize = 900;
+
+const INITIALIZE_BUFFER: u32 = 0;
+const WRITE: u32 = 1;
+const UPGRADE: u32 = 3;
+const SET_AUTHORITY: u32 = 4;
+const CLOSE: u32 = 5;
+
+/// State stored in an account owned by the upgradeable loader
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UpgradeableLoaderState {
+    /// Account has not been initialized
+    Uninitialized,
+    /// A buffer holding program bytes before deployment or upgrade
+    Buffer { authority: Option<Pubkey> },
+    /// An executable program pointing at its programdata account
+    Program { programdata_address: Pubkey },
+    /// The program's bytes and upgrade authority
+    ProgramData {
+        slot: u64,
+        upgrade_authority: Option<Pubkey>,
+    },
+}
+
+/// Errors that can occur while decoding loader accounts
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum LoaderStateError {
+    #[error("Loader account data is too short")]
+    TooShort,
+
+    #[error("Unknown loader state tag {0}")]
+    UnknownTag(u32),
+
+    #[error("Invalid option tag {0}")]
+    InvalidOption(u8),
+}
+
+impl UpgradeableLoaderState {
+    /// Decode the loader state at the start of an account's data
+    pub fn from_account_data(data: &[u8]) -> Result<Self, LoaderStateError> {
+        let tag = u32::from_le_bytes(read_array(data, 0)?);
+        match tag {
+            0 => Ok(Self::Uninitialized),
+            1 => Ok(Self::Buffer {
+                authority: read_option_pubkey(data, 4)?,
+            }),
+            2 => Ok(Self::Program {
+                programdata_address: Pubkey::new(read_array(data, 4)?),
+            }),
+            3 => Ok(Self::ProgramData {
+                slot: u64::from_le_bytes(read_array(data, 4)?),
+                upgrade_authority: read_option_pubkey(data, 12)?,
+            }),
+            other => Err(LoaderStateError::UnknownTag(other)),
+        }
+    }
+
+    /// The authority of a buffer or programdata account
+    pub fn authority(&self) -> Option<Pubkey> {
+        match self {
+            Self::Buffer { authority } => *authority,
+            Self::ProgramData {
+                upgrade_authority, ..
+            } => *upgrade_authority,
+            _ => None,
+        }
+    }
+}
+
+/// Derive the programdata address of an upgradeable program
+pub fn programdata_address(program_id: &Pubkey) -> Result<Pubkey, PubkeyError> {
+    Pubkey::try_find_program_address(&[program_id.as_bytes()], &BPF_LOADER_UPGRADEABLE_ID)
+        .map(|(address, _)| address)
+        .ok_or(PubkeyError::InvalidSeeds)
+}
+
+/// Initialize a buffer account that has already been allocated
+pub fn initialize_buffer(buffer: Pubkey, authority: Pubkey) -> ProgramInstruction {
+    instruction(
+        INITIALIZE_BUFFER,
+        vec![
+            InstructionAccount::writable(buffer),
+            InstructionAccount::readonly(authority),
+        ],
+        Vec::new(),
+    )
+}
+
+/// Write bytes into a buffer at an offset
+pub fn write(buffer: Pubkey, authority: Pubkey, offset: u32, bytes: &[u8]) -> ProgramInstruction {
+    let mut data = offset.to_le_bytes().to_vec();
+    data.extend_from_slice(&(bytes.len() as u64).to_le_bytes());
+    data.extend_from_slice(bytes);
+
+    instruction(
+        WRITE,
+        vec![
+            InstructionAccount::writable(buffer),
+            InstructionAccount::signer(authority, false),
+        ],
+        data,
+    )
+}
+
+/// Write a whole program into a buffer in `MAX_WRITE_CHUNK` sized pieces
+pub fn write_chunks(buffer: Pubkey, authority: Pubkey, bytes: &[u8]) -> Vec<ProgramInstruction> {
+    bytes
+        .chunks(MAX_WRITE_CHUNK)
+        .enumerate()
+        .map(|(index, chunk)| write(buffer, authority, (index * MAX_WRITE_CHUNK) as u32, chunk))
+        .collect()
+}
+
+/// Change the authority of a buffer
+pub fn set_buffer_authority(
+    buffer: Pubkey,
+    current_authority: Pubkey,
+    new_authority: Pubkey,
+) -> ProgramInstruction {
+    instruction(
+        SET_AUTHORITY,
+        vec![
+            InstructionAccount::writable(buffer),
+            InstructionAccount::signer(current_authority, false),
+            InstructionAccount::readonly(new_authority),
+        ],
+        Vec::new(),
+    )
+}
+
+/// Replace a program's bytes with the contents of a buffer
+///
+/// The buffer's lamports, less what the programdata account needs, go to `spill`.
+pub fn upgrade(
+    program_id: Pubkey,
+    programdata: Pubkey,
+    buffer: Pubkey,
+    authority: Pubkey,
+    spill: Pubkey,
+) -> ProgramInstruction {
+    instruction(
+        UPGRADE,
+        vec![
+            InstructionAccount::writable(programdata),
+            InstructionAccount::writable(program_id),
+            InstructionAccount::writable(buffer),
+            InstructionAccount::writable(spill),
+            InstructionAccount::new(SYSVAR_RENT_ID, false, false, AccountRole::System),
+            InstructionAccount::new(SYSVAR_CLOCK_ID, false, false, AccountRole::System),
+            InstructionAccount::signer(authority, false),
+        ],
+        Vec::new(),
+    )
+}
+
+/// Close a buffer, sending its lamports to `recipient`
+pub fn close_buffer(buffer: Pubkey, authority: Pubkey, recipient: Pubkey) -> ProgramInstruction {
+    instruction(
+        CLOSE,
+        vec![
+            InstructionAccount::writable(buffer),
+            InstructionAccount::writable(recipient),
+            InstructionAccount::signer(authority, false),
+        ],
+        Vec::new(),
+    )
+}
+
+/// Close a program's programdata account, leaving the program unusable
+pub fn close_program(
+    program_id: Pubkey,
+    programdata: Pubkey,
+    authority: Pubkey,
+    recipient: Pubkey,
+) -> ProgramInstruction {
+    instruction(
+        CLOSE,
+        vec![
+            InstructionAccount::writable(programdata),
+            InstructionAccount::writable(recipient),
+            InstructionAccount::signer(authority, false),
+            InstructionAccount::writable(program_id),
+        ],
+        Vec::new(),
+    )
+}
+
+fn instruction(
+    tag: u32,
+    accounts: Vec<InstructionAccount>,
+    payload: Vec<u8>,
+) -> ProgramInstruction {
+    let mut data = tag.to_le_bytes().to_vec();
+    data.extend(payload);
+    ProgramInstruction::new(BPF_LOADER_UPGRADEABLE_ID, accounts, data, tag as u8)
+}
+
+fn read_array<const N: usize>(data: &[u8], offset: usize) -> Result<[u8; N], LoaderStateError> {
+    data.get(offset..offset + N)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(LoaderStateError::TooShort)
+}
+
+fn read_option_pubkey(data: &[u8], offset: usize) -> Result<Option<Pubkey>, LoaderStateError> {
+    match read_array::<1>(data, offset)?[0] {
+        0 => Ok(None),
+        1 => Ok(Some(Pubkey::new(read_array(data, offset + 1)?))),
+        other => Err(LoaderStateError::InvalidOption(other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_loader_id_matches_base58() {
+        assert_eq!(
+            BPF_LOADER_UPGRADEABLE_ID.to_base58(),
+            "BPFLoaderUpgradeab1e11111111111111111111111"
+        );
+        assert_eq!(
+            SYSVAR_RENT_ID.to_base58(),
+            "SysvarRent111111111111111111111111111111111"
+        );
+        assert_eq!(
+            SYSVAR_CLOCK_ID.to_base58(),
+            "SysvarC1ock11111111111111111111111111111111"
+        );
+    }
+
+    #[test]
+    fn test_decode_loader_states() {
+        let authority = Pubkey::new([9; 32]);
+
+        let mut buffer = vec![1, 0, 0, 0, 1];
+        buffer.extend_from_slice(authority.as_bytes());
+        assert_eq!(buffer.len(), BUFFER_METADATA_SIZE);
+        assert_eq!(
+            UpgradeableLoaderState::from_account_data(&buffer).unwrap(),
+            UpgradeableLoaderState::Buffer {
+                authority: Some(authority)
+            }
+        );
+
+        let mut program = vec![2, 0, 0, 0];
+        program.extend_from_slice(&[4; 32]);
+        assert_eq!(program.len(), PROGRAM_ACCOUNT_SIZE);
+        assert_eq!(
+            UpgradeableLoaderState::from_account_data(&program).unwrap(),
+            UpgradeableLoaderState::Program {
+                programdata_address: Pubkey::new([4; 32])
+            }
+        );
+
+        let mut programdata = vec![3, 0, 0, 0];
+        programdata.extend_from_slice(&42u64.to_le_bytes());
+        programdata.push(0);
+        programdata.extend_from_slice(&[0; 32]);
+        assert_eq!(programdata.len(), PROGRAMDATA_METADATA_SIZE);
+        let state = UpgradeableLoaderState::from_account_data(&programdata).unwrap();
+        assert_eq!(
+            state,
+            UpgradeableLoaderState::ProgramData {
+                slot: 42,
+                upgrade_authority: None
+            }
+        );
+        assert_eq!(state.authority(), None);
+
+        assert_eq!(
+            UpgradeableLoaderState::from_account_data(&[7, 0, 0, 0]),
+            Err(LoaderStateError::UnknownTag(7))
+        );
+        assert_eq!(
+            UpgradeableLoaderState::from_account_data(&[1, 0, 0, 0, 1, 2]),
+            Err(LoaderStateError::TooShort)
+        );
+    }
+
+    #[test]
+    fn test_write_instruction_encoding() {
+        let buffer = Pubkey::new([1; 32]);
+        let authority = Pubkey::new([2; 32]);
+        let ix = write(buffer, authority, 900, &[0xaa, 0xbb]);
+
+        assert_eq!(ix.program_id, BPF_LOADER_UPGRADEABLE_ID);
+        assert_eq!(
+            ix.data,
+            vec![1, 0, 0, 0, 0x84, 0x03, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0xaa, 0xbb]
+        );
+        assert_eq!(ix.signer_accounts().len(), 1);
+
+        let chunks = write_chunks(buffer, authority, &vec![0u8; MAX_WRITE_CHUNK * 2 + 1]);
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(
+            chunks[2].data[4..8],
+            (2 * MAX_WRITE_CHUNK as u32).to_le_bytes()
+        );
+    }
+
+    #[test]
+    fn test_upgrade_and_close_accounts() {
+        let program_id = Pubkey::new([1; 32]);
+        let programdata = programdata_address(&program_id).unwrap();
+        let authority = Pubkey::new([3; 32]);
+
+        let ix = upgrade(
+            program_id,
+            programdata,
+            Pubkey::new([4; 32]),
+            authority,
+            Pubkey::new([5; 32]),
+        );
+        assert_eq!(ix.data, vec![3, 0, 0, 0]);
+        assert_eq!(ix.accounts.len(), 7);
+        assert_eq!(ix.accounts[0].pubkey, programdata);
+        assert_eq!(ix.writable_accounts().len(), 4);
+
+        let ix = close_program(program_id, programdata, authority, authority);
+        assert_eq!(ix.data, vec![5, 0, 0, 0]);
+        assert_eq!(ix.accounts[3].pubkey, program_id);
+        assert!(ix.accounts[3].is_writable);
+    }
+}
@@ -4,10 +4,16 @@
 //! and their associated accounts, including program deployment simulation,
 //! account creation, and program interaction patterns.
 
-use crate::domain::types::{Hash, Pubkey};
+use crate::domain::types::Pubkey;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+pub mod loader;
+pub mod stake;
+pub mod system;
+#[cfg(feature = "token")]
+pub mod token;
+
 /// A Solana program with its metadata and accounts
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Program {
@@ -194,10 +200,7 @@ impl Program {
             data,
             accounts: HashMap::new(),
             status: ProgramStatus::Deploying,
-            deployed_at: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
+            deployed_at: unix_timestamp(),
         }
     }
 
@@ -216,6 +219,16 @@ impl Program {
         self.status = ProgramStatus::Deployed;
     }
 
+    /// Mark program as being upgraded
+    pub fn mark_upgrading(&mut self) {
+        self.status = ProgramStatus::Upgrading;
+    }
+
+    /// Mark program as closed
+    pub fn mark_closed(&mut self) {
+        self.status = ProgramStatus::Closed;
+    }
+
     /// Get total size of all accounts
     pub fn total_accounts_size(&self) -> usize {
         self.accounts.values().map(|acc| acc.size).sum()
@@ -245,10 +258,7 @@ impl ProgramAccount {
             lamports,
             executable,
             size,
-            created_at: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
+            created_at: unix_timestamp(),
             metadata,
         }
     }
@@ -405,6 +415,17 @@ impl InstructionAccount {
     }
 }
 
+/// Current unix timestamp in seconds, zero if the clock is before 1970
+///
+/// Goes through `web_time` because `std::time::SystemTime::now` panics on
+/// `wasm32-unknown-unknown`.
+fn unix_timestamp() -> u64 {
+    web_time::SystemTime::now()
+        .duration_since(web_time::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
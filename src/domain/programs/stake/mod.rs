@@ -0,0 +1,180 @@
+//! Stake program accounts
+//!
+//! Decodes the stake program's `StakeStateV2`: the authorities every
+//! initialized stake account carries and, once delegated, the vote account
+//! and amount it is staked with. Authority offsets are exposed through
+//! [`AccountLayout`] so stake accounts can be found by authority with memcmp
+//! filters.
+
+use crate::domain::layout::AccountLayout;
+use crate::domain::types::Pubkey;
+use thiserror::Error;
+
+pub use crate::domain::types::constants::STAKE_PROGRAM_ID;
+
+/// Size of a stake account
+pub const STAKE_ACCOUNT_SIZE: usize = 200;
+
+/// Offset of the stake authority
+pub const STAKER_OFFSET: usize = 12;
+
+/// Offset of the withdraw authority
+pub const WITHDRAWER_OFFSET: usize = 44;
+
+/// Byte layout of a stake account, for memcmp filters
+pub struct StakeLayout;
+
+impl AccountLayout for StakeLayout {
+    const FIELDS: &'static [(&'static str, usize)] = &[
+        ("rent_exempt_reserve", 4),
+        ("staker", STAKER_OFFSET),
+        ("withdrawer", WITHDRAWER_OFFSET),
+        ("voter", 124),
+        ("stake", 156),
+    ];
+    const SIZES: &'static [(&'static str, usize)] = &[
+        ("rent_exempt_reserve", 8),
+        ("staker", 32),
+        ("withdrawer", 32),
+        ("voter", 32),
+        ("stake", 8),
+    ];
+}
+
+/// Errors that can occur while decoding stake accounts
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum StakeStateError {
+    #[error("Stake account data is too short")]
+    TooShort,
+
+    #[error("Unknown stake state tag {0}")]
+    UnknownTag(u32),
+}
+
+/// Authorities and reserve shared by initialized and delegated accounts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StakeMeta {
+    pub rent_exempt_reserve: u64,
+    /// May delegate and deactivate the stake
+    pub staker: Pubkey,
+    /// May withdraw the lamports
+    pub withdrawer: Pubkey,
+}
+
+/// Where a stake account is delegated and since when
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Delegation {
+    pub voter: Pubkey,
+    /// Delegated lamports
+    pub stake: u64,
+    pub activation_epoch: u64,
+    /// `u64::MAX` until the stake is deactivated
+    pub deactivation_epoch: u64,
+}
+
+impl Delegation {
+    /// Whether deactivation was requested
+    pub fn is_deactivating(&self) -> bool {
+        self.deactivation_epoch != u64::MAX
+    }
+}
+
+/// State stored in an account owned by the stake program
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StakeState {
+    Uninitialized,
+    /// Authorities set but not delegated
+    Initialized(StakeMeta),
+    Stake(StakeMeta, Delegation),
+    /// The rewards pool of early clusters
+    RewardsPool,
+}
+
+impl StakeState {
+    /// Decode the stake state at the start of an account's data
+    pub fn from_account_data(data: &[u8]) -> Result<Self, StakeStateError> {
+        let tag = u32::from_le_bytes(read_array(data, 0)?);
+        match tag {
+            0 => Ok(Self::Uninitialized),
+            1 => Ok(Self::Initialized(read_meta(data)?)),
+            2 => Ok(Self::Stake(
+                read_meta(data)?,
+                Delegation {
+                    voter: Pubkey::new(read_array(data, 124)?),
+                    stake: u64::from_le_bytes(read_array(data, 156)?),
+                    activation_epoch: u64::from_le_bytes(read_array(data, 164)?),
+                    deactivation_epoch: u64::from_le_bytes(read_array(data, 172)?),
+                },
+            )),
+            3 => Ok(Self::RewardsPool),
+            other => Err(StakeStateError::UnknownTag(other)),
+        }
+    }
+
+    /// Authorities of an initialized or delegated account
+    pub fn meta(&self) -> Option<&StakeMeta> {
+        match self {
+            Self::Initialized(meta) | Self::Stake(meta, _) => Some(meta),
+            _ => None,
+        }
+    }
+
+    /// Delegation of a delegated account
+    pub fn delegation(&self) -> Option<&Delegation> {
+        match self {
+            Self::Stake(_, delegation) => Some(delegation),
+            _ => None,
+        }
+    }
+}
+
+fn read_meta(data: &[u8]) -> Result<StakeMeta, StakeStateError> {
+    Ok(StakeMeta {
+        rent_exempt_reserve: u64::from_le_bytes(read_array(data, 4)?),
+        staker: Pubkey::new(read_array(data, STAKER_OFFSET)?),
+        withdrawer: Pubkey::new(read_array(data, WITHDRAWER_OFFSET)?),
+    })
+}
+
+fn read_array<const N: usize>(data: &[u8], offset: usize) -> Result<[u8; N], StakeStateError> {
+    data.get(offset..offset + N)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(StakeStateError::TooShort)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_delegated_stake() {
+        let mut data = vec![0; STAKE_ACCOUNT_SIZE];
+        data[0] = 2;
+        data[4..12].copy_from_slice(&2_282_880u64.to_le_bytes());
+        data[STAKER_OFFSET..STAKER_OFFSET + 32].copy_from_slice(&[1; 32]);
+        data[WITHDRAWER_OFFSET..WITHDRAWER_OFFSET + 32].copy_from_slice(&[2; 32]);
+        data[124..156].copy_from_slice(&[3; 32]);
+        data[156..164].copy_from_slice(&5_000_000_000u64.to_le_bytes());
+        data[164..172].copy_from_slice(&600u64.to_le_bytes());
+        data[172..180].copy_from_slice(&u64::MAX.to_le_bytes());
+
+        let state = StakeState::from_account_data(&data).unwrap();
+        let meta = state.meta().unwrap();
+        assert_eq!(meta.withdrawer, Pubkey::new([2; 32]));
+        assert_eq!(meta.rent_exempt_reserve, 2_282_880);
+        let delegation = state.delegation().unwrap();
+        assert_eq!(delegation.voter, Pubkey::new([3; 32]));
+        assert_eq!(delegation.stake, 5_000_000_000);
+        assert!(!delegation.is_deactivating());
+        assert_eq!(StakeLayout::field_offset("withdrawer"), Some(44));
+
+        assert_eq!(
+            StakeState::from_account_data(&data[..100]),
+            Err(StakeStateError::TooShort)
+        );
+        assert_eq!(
+            StakeState::from_account_data(&[0; 4]),
+            Ok(StakeState::Uninitialized)
+        );
+    }
+}
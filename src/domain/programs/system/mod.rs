@@ -0,0 +1,87 @@
+//! System program instructions
+//!
+//! Builds the system program instructions that flows combine with others:
+//! SOL transfers and account creation. Instruction data is the bincode layout
+//! the runtime expects, a little-endian `u32` tag followed by the arguments.
+
+use super::{AccountRole, InstructionAccount, ProgramInstruction};
+use crate::domain::types::constants::SYSTEM_PROGRAM_ID;
+use crate::domain::types::Pubkey;
+
+const CREATE_ACCOUNT: u32 = 0;
+const TRANSFER: u32 = 2;
+
+/// Create `new_account` with `space` bytes owned by `owner`, funded by `payer`
+///
+/// Both `payer` and `new_account` must sign.
+pub fn create_account(
+    payer: Pubkey,
+    new_account: Pubkey,
+    lamports: u64,
+    space: u64,
+    owner: &Pubkey,
+) -> ProgramInstruction {
+    let mut data = lamports.to_le_bytes().to_vec();
+    data.extend_from_slice(&space.to_le_bytes());
+    data.extend_from_slice(owner.as_bytes());
+
+    instruction(
+        CREATE_ACCOUNT,
+        vec![
+            InstructionAccount::signer(payer, true),
+            InstructionAccount::signer(new_account, true),
+        ],
+        data,
+    )
+}
+
+/// Transfer `lamports` from `from` to `to`
+pub fn transfer(from: Pubkey, to: Pubkey, lamports: u64) -> ProgramInstruction {
+    instruction(
+        TRANSFER,
+        vec![
+            InstructionAccount::signer(from, true),
+            InstructionAccount::writable(to),
+        ],
+        lamports.to_le_bytes().to_vec(),
+    )
+}
+
+/// The system program as an instruction account
+pub fn program_account() -> InstructionAccount {
+    InstructionAccount::new(SYSTEM_PROGRAM_ID, false, false, AccountRole::System)
+}
+
+fn instruction(
+    tag: u32,
+    accounts: Vec<InstructionAccount>,
+    payload: Vec<u8>,
+) -> ProgramInstruction {
+    let mut data = tag.to_le_bytes().to_vec();
+    data.extend(payload);
+    ProgramInstruction::new(SYSTEM_PROGRAM_ID, accounts, data, tag as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_system_instruction_data() {
+        let from = Pubkey::new([1; 32]);
+        let to = Pubkey::new([2; 32]);
+
+        let ix = transfer(from, to, 1_000);
+        assert_eq!(ix.data, [2, 0, 0, 0, 0xe8, 0x03, 0, 0, 0, 0, 0, 0]);
+        assert!(ix.accounts[0].is_signer && ix.accounts[0].is_writable);
+        assert!(!ix.accounts[1].is_signer && ix.accounts[1].is_writable);
+
+        let owner = Pubkey::new([9; 32]);
+        let ix = create_account(from, to, 5, 165, &owner);
+        assert_eq!(ix.data.len(), 4 + 8 + 8 + 32);
+        assert_eq!(&ix.data[..4], &[0, 0, 0, 0]);
+        assert_eq!(&ix.data[12..20], &165u64.to_le_bytes());
+        assert_eq!(&ix.data[20..], owner.as_bytes());
+        assert!(ix.accounts.iter().all(|account| account.is_signer));
+    }
+}
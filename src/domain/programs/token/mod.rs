@@ -0,0 +1,222 @@
+//! SPL Token and Associated Token Account instructions
+//!
+//! Builds the token instructions that flows combine with others: creating a
+//! wallet's associated token account, checked transfers and wrapping SOL.
+//! Every builder takes the token program so Token-2022 mints work too. Mint
+//! accounts decode into a [`Mint`].
+
+use super::{system, InstructionAccount, ProgramInstruction};
+use crate::domain::types::constants::ASSOCIATED_TOKEN_PROGRAM_ID;
+use crate::domain::types::Pubkey;
+
+const CLOSE_ACCOUNT: u8 = 9;
+const TRANSFER_CHECKED: u8 = 12;
+const SYNC_NATIVE: u8 = 17;
+
+/// `CreateIdempotent` of the associated token account program
+const CREATE_IDEMPOTENT: u8 = 1;
+
+/// Size of a mint account, before any Token-2022 extensions
+pub const MINT_LEN: usize = 82;
+
+/// Size of an SPL Token account
+pub const TOKEN_ACCOUNT_LEN: usize = 165;
+
+/// Size of a Token-2022 associated token account: the base layout, the
+/// account type byte and the `ImmutableOwner` extension header
+pub const TOKEN_2022_ACCOUNT_LEN: usize = 170;
+
+/// A decoded mint account
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mint {
+    pub mint_authority: Option<Pubkey>,
+    /// Supply in base units
+    pub supply: u64,
+    pub decimals: u8,
+    pub is_initialized: bool,
+    pub freeze_authority: Option<Pubkey>,
+}
+
+impl Mint {
+    /// Decode the base mint layout, ignoring Token-2022 extensions after it
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        let data = data.get(..MINT_LEN)?;
+        Some(Self {
+            mint_authority: option_pubkey(&data[..36])?,
+            supply: u64::from_le_bytes(data[36..44].try_into().ok()?),
+            decimals: data[44],
+            is_initialized: data[45] != 0,
+            freeze_authority: option_pubkey(&data[46..82])?,
+        })
+    }
+}
+
+/// A `COption<Pubkey>`: a 4-byte tag then the key
+fn option_pubkey(data: &[u8]) -> Option<Option<Pubkey>> {
+    match data[..4] {
+        [0, 0, 0, 0] => Some(None),
+        [1, 0, 0, 0] => Some(Some(Pubkey::new(data[4..36].try_into().ok()?))),
+        _ => None,
+    }
+}
+
+/// Address of `owner`'s associated token account for `mint`
+pub fn associated_token_address(owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[owner.as_bytes(), token_program.as_bytes(), mint.as_bytes()],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0
+}
+
+/// Create `owner`'s associated token account for `mint` unless it exists
+pub fn create_associated_token_account_idempotent(
+    payer: Pubkey,
+    owner: Pubkey,
+    mint: Pubkey,
+    token_program: Pubkey,
+) -> ProgramInstruction {
+    let account = associated_token_address(&owner, &mint, &token_program);
+    ProgramInstruction::new(
+        ASSOCIATED_TOKEN_PROGRAM_ID,
+        vec![
+            InstructionAccount::signer(payer, true),
+            InstructionAccount::writable(account),
+            InstructionAccount::readonly(owner),
+            InstructionAccount::readonly(mint),
+            system::program_account(),
+            InstructionAccount::program(token_program),
+        ],
+        vec![CREATE_IDEMPOTENT],
+        CREATE_IDEMPOTENT,
+    )
+}
+
+/// Move `amount` base units of `mint` between token accounts
+///
+/// The mint's `decimals` are checked by the token program, which guards
+/// against amounts scaled for the wrong mint.
+pub fn transfer_checked(
+    token_program: Pubkey,
+    source: Pubkey,
+    mint: Pubkey,
+    destination: Pubkey,
+    authority: Pubkey,
+    amount: u64,
+    decimals: u8,
+) -> ProgramInstruction {
+    let mut data = amount.to_le_bytes().to_vec();
+    data.push(decimals);
+    instruction(
+        token_program,
+        TRANSFER_CHECKED,
+        vec![
+            InstructionAccount::writable(source),
+            InstructionAccount::readonly(mint),
+            InstructionAccount::writable(destination),
+            InstructionAccount::signer(authority, false),
+        ],
+        data,
+    )
+}
+
+/// Update a wrapped SOL account's token balance to its lamports
+pub fn sync_native(token_program: Pubkey, account: Pubkey) -> ProgramInstruction {
+    instruction(
+        token_program,
+        SYNC_NATIVE,
+        vec![InstructionAccount::writable(account)],
+        Vec::new(),
+    )
+}
+
+/// Close an empty token account, or a wrapped SOL account, sending its
+/// lamports to `destination`
+pub fn close_account(
+    token_program: Pubkey,
+    account: Pubkey,
+    destination: Pubkey,
+    owner: Pubkey,
+) -> ProgramInstruction {
+    instruction(
+        token_program,
+        CLOSE_ACCOUNT,
+        vec![
+            InstructionAccount::writable(account),
+            InstructionAccount::writable(destination),
+            InstructionAccount::signer(owner, false),
+        ],
+        Vec::new(),
+    )
+}
+
+fn instruction(
+    token_program: Pubkey,
+    tag: u8,
+    accounts: Vec<InstructionAccount>,
+    payload: Vec<u8>,
+) -> ProgramInstruction {
+    let mut data = vec![tag];
+    data.extend(payload);
+    ProgramInstruction::new(token_program, accounts, data, tag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::types::constants::{NATIVE_MINT, TOKEN_PROGRAM_ID};
+
+    #[test]
+    fn test_decode_mint() {
+        let authority = Pubkey::new([5; 32]);
+        let mut data = vec![0; MINT_LEN + 2];
+        data[..4].copy_from_slice(&1u32.to_le_bytes());
+        data[4..36].copy_from_slice(authority.as_bytes());
+        data[36..44].copy_from_slice(&1_000u64.to_le_bytes());
+        data[44] = 6;
+        data[45] = 1;
+        assert_eq!(
+            Mint::decode(&data),
+            Some(Mint {
+                mint_authority: Some(authority),
+                supply: 1_000,
+                decimals: 6,
+                is_initialized: true,
+                freeze_authority: None,
+            })
+        );
+        assert_eq!(Mint::decode(&data[..MINT_LEN - 1]), None);
+        data[46] = 7;
+        assert_eq!(Mint::decode(&data), None);
+    }
+
+    #[test]
+    fn test_associated_token_address() {
+        let owner = Pubkey::new([7; 32]);
+        let address = associated_token_address(&owner, &NATIVE_MINT, &TOKEN_PROGRAM_ID);
+        let ix =
+            create_associated_token_account_idempotent(owner, owner, NATIVE_MINT, TOKEN_PROGRAM_ID);
+        assert_eq!(ix.accounts[1].pubkey, address);
+        assert_eq!(ix.data, [1]);
+        assert_eq!(ix.accounts.len(), 6);
+    }
+
+    #[test]
+    fn test_token_instruction_data() {
+        let ix = transfer_checked(
+            TOKEN_PROGRAM_ID,
+            Pubkey::new([1; 32]),
+            Pubkey::new([2; 32]),
+            Pubkey::new([3; 32]),
+            Pubkey::new([4; 32]),
+            1_500_000,
+            6,
+        );
+        assert_eq!(ix.data, [12, 0x60, 0xe3, 0x16, 0, 0, 0, 0, 0, 6]);
+        assert!(ix.accounts[3].is_signer);
+        assert_eq!(
+            sync_native(TOKEN_PROGRAM_ID, Pubkey::new([1; 32])).data,
+            [17]
+        );
+    }
+}
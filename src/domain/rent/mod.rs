@@ -0,0 +1,34 @@
+//! Rent exemption
+//!
+//! An account stays alive only while it holds two years of rent.
+//! [`rent_exempt_minimum`] computes that balance at the rates every public
+//! cluster uses; code holding a client can ask the node instead with
+//! `getMinimumBalanceForRentExemption`.
+
+/// Bytes of account metadata charged for on top of the data
+pub const ACCOUNT_STORAGE_OVERHEAD: u64 = 128;
+
+/// Default rent rate, in lamports per byte-year
+pub const LAMPORTS_PER_BYTE_YEAR: u64 = 3480;
+
+/// Years of rent an account must hold to be exempt
+pub const EXEMPTION_THRESHOLD_YEARS: u64 = 2;
+
+/// Lamports exempting an account of `data_len` bytes from rent at default rates
+pub fn rent_exempt_minimum(data_len: usize) -> u64 {
+    (ACCOUNT_STORAGE_OVERHEAD + data_len as u64)
+        * LAMPORTS_PER_BYTE_YEAR
+        * EXEMPTION_THRESHOLD_YEARS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rent_exempt_minimum() {
+        assert_eq!(rent_exempt_minimum(0), 890_880);
+        assert_eq!(rent_exempt_minimum(82), 1_461_600);
+        assert_eq!(rent_exempt_minimum(165), 2_039_280);
+    }
+}
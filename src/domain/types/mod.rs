@@ -4,10 +4,21 @@
 //! providing WASM-compatible implementations of Solana's core data structures.
 
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::borrow::Borrow;
 use std::fmt;
 
+/// Maximum length of a single PDA seed
+pub const MAX_SEED_LEN: usize = 32;
+
+/// Maximum number of seeds for a PDA
+pub const MAX_SEEDS: usize = 16;
+
+/// Marker appended to PDA hash inputs
+const PDA_MARKER: &[u8] = b"ProgramDerivedAddress";
+
 /// A Solana public key
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Pubkey([u8; 32]);
 
 impl Serialize for Pubkey {
@@ -29,9 +40,23 @@ impl<'de> Deserialize<'de> for Pubkey {
     }
 }
 
+impl borsh::BorshSerialize for Pubkey {
+    fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        writer.write_all(&self.0)
+    }
+}
+
+impl borsh::BorshDeserialize for Pubkey {
+    fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+        let mut bytes = [0u8; 32];
+        reader.read_exact(&mut bytes)?;
+        Ok(Self(bytes))
+    }
+}
+
 impl Pubkey {
     /// Create a new pubkey from a 32-byte array
-    pub fn new(bytes: [u8; 32]) -> Self {
+    pub const fn new(bytes: [u8; 32]) -> Self {
         Self(bytes)
     }
 
@@ -60,6 +85,88 @@ impl Pubkey {
         bs58::encode(self.0).into_string()
     }
 
+    /// Whether the pubkey is a valid ed25519 curve point
+    ///
+    /// Program derived addresses are, by construction, off the curve.
+    pub fn is_on_curve(&self) -> bool {
+        curve25519_dalek::edwards::CompressedEdwardsY(self.0)
+            .decompress()
+            .is_some()
+    }
+
+    /// Derive a program address from seeds and a program id
+    pub fn create_program_address(
+        seeds: &[&[u8]],
+        program_id: &Pubkey,
+    ) -> Result<Pubkey, PubkeyError> {
+        if seeds.len() > MAX_SEEDS {
+            return Err(PubkeyError::MaxSeedLengthExceeded);
+        }
+        if seeds.iter().any(|seed| seed.len() > MAX_SEED_LEN) {
+            return Err(PubkeyError::MaxSeedLengthExceeded);
+        }
+
+        let mut hasher = Sha256::new();
+        for seed in seeds {
+            hasher.update(seed);
+        }
+        hasher.update(program_id.as_bytes());
+        hasher.update(PDA_MARKER);
+
+        let address = Pubkey(hasher.finalize().into());
+        if address.is_on_curve() {
+            return Err(PubkeyError::InvalidSeeds);
+        }
+
+        Ok(address)
+    }
+
+    /// Find a valid program address and its bump seed
+    pub fn try_find_program_address(seeds: &[&[u8]], program_id: &Pubkey) -> Option<(Pubkey, u8)> {
+        let mut bump = [u8::MAX];
+        for _ in 0..u8::MAX {
+            let mut seeds_with_bump = seeds.to_vec();
+            seeds_with_bump.push(&bump);
+            match Self::create_program_address(&seeds_with_bump, program_id) {
+                Ok(address) => return Some((address, bump[0])),
+                Err(PubkeyError::InvalidSeeds) => bump[0] -= 1,
+                Err(_) => return None,
+            }
+        }
+        None
+    }
+
+    /// Find a valid program address and its bump seed
+    ///
+    /// Panics in the practically impossible case that no bump produces an
+    /// off-curve address; use [`Pubkey::try_find_program_address`] to handle it.
+    #[allow(clippy::expect_used)]
+    pub fn find_program_address(seeds: &[&[u8]], program_id: &Pubkey) -> (Pubkey, u8) {
+        Self::try_find_program_address(seeds, program_id)
+            .expect("Unable to find a viable program address bump seed")
+    }
+
+    /// Derive an address from a base pubkey, a string seed and an owner
+    pub fn create_with_seed(
+        base: &Pubkey,
+        seed: &str,
+        owner: &Pubkey,
+    ) -> Result<Pubkey, PubkeyError> {
+        if seed.len() > MAX_SEED_LEN {
+            return Err(PubkeyError::MaxSeedLengthExceeded);
+        }
+        if owner.as_bytes().ends_with(PDA_MARKER) {
+            return Err(PubkeyError::IllegalOwner);
+        }
+
+        let mut hasher = Sha256::new();
+        hasher.update(base.as_bytes());
+        hasher.update(seed.as_bytes());
+        hasher.update(owner.as_bytes());
+
+        Ok(Pubkey(hasher.finalize().into()))
+    }
+
     /// Create a new random pubkey (placeholder - would need proper WASM-compatible RNG)
     pub fn new_unique() -> Self {
         // In a real implementation, this would use a cryptographically secure RNG
@@ -83,7 +190,7 @@ impl std::str::FromStr for Pubkey {
 }
 
 /// A Solana signature
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Signature([u8; 64]);
 
 impl Serialize for Signature {
@@ -107,7 +214,7 @@ impl<'de> Deserialize<'de> for Signature {
 
 impl Signature {
     /// Create a new signature from a 64-byte array
-    pub fn new(bytes: [u8; 64]) -> Self {
+    pub const fn new(bytes: [u8; 64]) -> Self {
         Self(bytes)
     }
 
@@ -135,6 +242,18 @@ impl Signature {
     pub fn to_base58(&self) -> String {
         bs58::encode(self.0).into_string()
     }
+
+    /// Whether this is `pubkey`'s ed25519 signature of `message`
+    ///
+    /// Uses strict verification, rejecting the malleable and small-order
+    /// forms the runtime rejects.
+    pub fn verify(&self, pubkey: &Pubkey, message: &[u8]) -> bool {
+        let Ok(key) = ed25519_dalek::VerifyingKey::from_bytes(pubkey.as_bytes()) else {
+            return false;
+        };
+        key.verify_strict(message, &ed25519_dalek::Signature::from_bytes(&self.0))
+            .is_ok()
+    }
 }
 
 impl fmt::Display for Signature {
@@ -144,7 +263,7 @@ impl fmt::Display for Signature {
 }
 
 /// A Solana hash
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Hash([u8; 32]);
 
 impl Serialize for Hash {
@@ -204,6 +323,27 @@ impl fmt::Display for Hash {
     }
 }
 
+/// Let byte newtypes be looked up by their bytes in maps and sorted slices
+///
+/// Ordering, equality and hashing all follow the bytes, as `Borrow` requires.
+macro_rules! impl_byte_borrow {
+    ($($name:ident),*) => {$(
+        impl AsRef<[u8]> for $name {
+            fn as_ref(&self) -> &[u8] {
+                &self.0
+            }
+        }
+
+        impl Borrow<[u8]> for $name {
+            fn borrow(&self) -> &[u8] {
+                &self.0
+            }
+        }
+    )*};
+}
+
+impl_byte_borrow!(Pubkey, Signature, Hash);
+
 /// Errors related to pubkey operations
 #[derive(Debug, Clone, thiserror::Error)]
 pub enum PubkeyError {
@@ -211,6 +351,12 @@ pub enum PubkeyError {
     InvalidBase58,
     #[error("Invalid pubkey length: expected 32 bytes")]
     InvalidLength,
+    #[error("Length of the seed is too long for address generation")]
+    MaxSeedLengthExceeded,
+    #[error("Provided seeds do not result in a valid address")]
+    InvalidSeeds,
+    #[error("Provided owner is not allowed")]
+    IllegalOwner,
 }
 
 /// Errors related to signature operations
@@ -243,14 +389,62 @@ pub mod constants {
 
     /// The rent sysvar ID
     pub const SYSVAR_RENT_ID: Pubkey = Pubkey([
-        6, 221, 246, 225, 215, 101, 161, 147, 217, 203, 225, 70, 206, 235, 121, 172, 28, 180, 133,
-        237, 95, 91, 55, 145, 58, 11, 40, 209, 98, 225, 236, 9,
+        6, 167, 213, 23, 25, 44, 92, 81, 33, 140, 201, 76, 61, 74, 241, 127, 88, 218, 238, 8, 155,
+        161, 253, 68, 227, 219, 217, 138, 0, 0, 0, 0,
     ]);
 
     /// The clock sysvar ID
     pub const SYSVAR_CLOCK_ID: Pubkey = Pubkey([
+        6, 167, 213, 23, 24, 199, 116, 201, 40, 86, 99, 152, 105, 29, 94, 182, 139, 94, 184, 163,
+        155, 75, 109, 92, 115, 85, 91, 33, 0, 0, 0, 0,
+    ]);
+
+    /// The SPL Token program ID
+    pub const TOKEN_PROGRAM_ID: Pubkey = Pubkey([
         6, 221, 246, 225, 215, 101, 161, 147, 217, 203, 225, 70, 206, 235, 121, 172, 28, 180, 133,
-        237, 95, 91, 55, 145, 58, 11, 40, 209, 98, 225, 236, 10,
+        237, 95, 91, 55, 145, 58, 140, 245, 133, 126, 255, 0, 169,
+    ]);
+
+    /// The SPL Token-2022 program ID
+    pub const TOKEN_2022_PROGRAM_ID: Pubkey = Pubkey([
+        6, 221, 246, 225, 238, 117, 143, 222, 24, 66, 93, 188, 228, 108, 205, 218, 182, 26, 252,
+        77, 131, 185, 11, 159, 46, 136, 80, 133, 87, 14, 59, 252,
+    ]);
+
+    /// The SPL Associated Token Account program ID
+    pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey = Pubkey([
+        140, 151, 37, 143, 78, 36, 137, 241, 187, 61, 16, 41, 20, 142, 13, 131, 11, 90, 19, 153,
+        218, 255, 16, 132, 4, 142, 123, 216, 219, 233, 248, 89,
+    ]);
+
+    /// The SPL Memo program ID (v2)
+    pub const MEMO_PROGRAM_ID: Pubkey = Pubkey([
+        5, 74, 83, 90, 153, 41, 33, 6, 77, 36, 232, 113, 96, 218, 56, 124, 124, 53, 181, 221, 188,
+        146, 187, 129, 228, 31, 168, 64, 65, 5, 68, 141,
+    ]);
+
+    /// The compute budget program ID
+    pub const COMPUTE_BUDGET_PROGRAM_ID: Pubkey = Pubkey([
+        3, 6, 70, 111, 229, 33, 23, 50, 255, 236, 173, 186, 114, 195, 155, 231, 188, 140, 229, 187,
+        197, 247, 18, 107, 44, 67, 155, 58, 64, 0, 0, 0,
+    ]);
+
+    /// The upgradeable BPF loader program ID
+    pub const BPF_LOADER_UPGRADEABLE_ID: Pubkey = Pubkey([
+        2, 168, 246, 145, 78, 136, 161, 176, 226, 16, 21, 62, 247, 99, 174, 43, 0, 194, 185, 61,
+        22, 193, 36, 210, 192, 83, 122, 16, 4, 128, 0, 0,
+    ]);
+
+    /// The stake program ID
+    pub const STAKE_PROGRAM_ID: Pubkey = Pubkey([
+        6, 161, 216, 23, 145, 55, 84, 42, 152, 52, 55, 189, 254, 42, 122, 178, 85, 127, 83, 92,
+        138, 120, 114, 43, 104, 164, 157, 192, 0, 0, 0, 0,
+    ]);
+
+    /// The wrapped SOL mint
+    pub const NATIVE_MINT: Pubkey = Pubkey([
+        6, 155, 136, 87, 254, 171, 129, 132, 251, 104, 127, 99, 70, 24, 192, 53, 218, 196, 57, 220,
+        26, 235, 59, 85, 152, 160, 240, 0, 0, 0, 0, 1,
     ]);
 }
 
@@ -258,6 +452,28 @@ pub mod constants {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_byte_order_and_borrow() {
+        use std::collections::{BTreeMap, HashSet};
+
+        let mut keys = vec![
+            Pubkey::new([2; 32]),
+            Pubkey::new([0; 32]),
+            Pubkey::new([1; 32]),
+        ];
+        keys.sort();
+        assert_eq!(keys[0], Pubkey::new([0; 32]));
+        assert_eq!(keys.binary_search(&Pubkey::new([1; 32])), Ok(1));
+        assert!(Hash::new([1; 32]) < Hash::new([2; 32]));
+        assert!(Signature::new([0; 64]) < Signature::new([1; 64]));
+
+        let balances: BTreeMap<Pubkey, u64> = keys.iter().map(|key| (*key, 5)).collect();
+        assert_eq!(balances.get(&[1u8; 32][..]), Some(&5));
+        let set: HashSet<Pubkey> = keys.into_iter().collect();
+        assert!(set.contains(&[2u8; 32][..]));
+        assert_eq!(Hash::new([3; 32]).as_ref(), &[3; 32]);
+    }
+
     #[test]
     fn test_pubkey_base58_roundtrip() {
         let pubkey = Pubkey::new([1; 32]);
@@ -266,6 +482,61 @@ mod tests {
         assert_eq!(pubkey, decoded);
     }
 
+    #[test]
+    fn test_create_program_address() {
+        let program_id =
+            Pubkey::from_base58("BPFLoaderUpgradeab1e11111111111111111111111").unwrap();
+        let public_key =
+            Pubkey::from_base58("SeedPubey1111111111111111111111111111111111").unwrap();
+
+        assert_eq!(
+            Pubkey::create_program_address(&[b"", &[1]], &program_id).unwrap(),
+            Pubkey::from_base58("BwqrghZA2htAcqq8dzP1WDAhTXYTYWj7CHxF5j7TDBAe").unwrap()
+        );
+        assert_eq!(
+            Pubkey::create_program_address(&["\u{2609}".as_bytes(), &[0]], &program_id).unwrap(),
+            Pubkey::from_base58("13yWmRpaTR4r5nAktwLqMpRNr28tnVUZw26rTvPSSB19").unwrap()
+        );
+        assert_eq!(
+            Pubkey::create_program_address(&[b"Talking", b"Squirrels"], &program_id).unwrap(),
+            Pubkey::from_base58("2fnQrngrQT4SeLcdToJAD96phoEjNL2man2kfRLCASVk").unwrap()
+        );
+        assert_eq!(
+            Pubkey::create_program_address(&[public_key.as_bytes(), &[1]], &program_id).unwrap(),
+            Pubkey::from_base58("976ymqVnfE32QFe6NfGDctSvVa36LWnvYxhU6G2232YL").unwrap()
+        );
+        assert!(matches!(
+            Pubkey::create_program_address(&[&[0u8; 33]], &program_id),
+            Err(PubkeyError::MaxSeedLengthExceeded)
+        ));
+    }
+
+    #[test]
+    fn test_find_program_address() {
+        let program_id = Pubkey::new([3; 32]);
+        let (address, bump) = Pubkey::find_program_address(&[b"counter"], &program_id);
+
+        assert!(!address.is_on_curve());
+        assert_eq!(
+            Pubkey::create_program_address(&[b"counter", &[bump]], &program_id).unwrap(),
+            address
+        );
+    }
+
+    #[test]
+    fn test_create_with_seed() {
+        let base = Pubkey::new([1; 32]);
+        let owner = Pubkey::new([2; 32]);
+
+        let derived = Pubkey::create_with_seed(&base, "seed", &owner).unwrap();
+        assert_ne!(derived, base);
+        assert_eq!(
+            derived,
+            Pubkey::create_with_seed(&base, "seed", &owner).unwrap()
+        );
+        assert!(Pubkey::create_with_seed(&base, &"x".repeat(33), &owner).is_err());
+    }
+
     #[test]
     fn test_signature_base58_roundtrip() {
         let signature = Signature::new([1; 64]);
@@ -281,4 +552,141 @@ mod tests {
         let decoded = Hash::from_base58(&base58).unwrap();
         assert_eq!(hash, decoded);
     }
+
+    #[test]
+    fn test_program_id_constants() {
+        use constants::*;
+
+        let ids = [
+            (SYSTEM_PROGRAM_ID, "11111111111111111111111111111111"),
+            (
+                SYSVAR_RENT_ID,
+                "SysvarRent111111111111111111111111111111111",
+            ),
+            (
+                SYSVAR_CLOCK_ID,
+                "SysvarC1ock11111111111111111111111111111111",
+            ),
+            (
+                TOKEN_PROGRAM_ID,
+                "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+            ),
+            (
+                TOKEN_2022_PROGRAM_ID,
+                "TokenzQdBNbLqP5VEhdkAS6EPFLC1PCnBqCXEpPxuEb",
+            ),
+            (
+                ASSOCIATED_TOKEN_PROGRAM_ID,
+                "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL",
+            ),
+            (
+                MEMO_PROGRAM_ID,
+                "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr",
+            ),
+            (
+                COMPUTE_BUDGET_PROGRAM_ID,
+                "ComputeBudget111111111111111111111111111111",
+            ),
+            (
+                BPF_LOADER_UPGRADEABLE_ID,
+                "BPFLoaderUpgradeab1e11111111111111111111111",
+            ),
+            (
+                STAKE_PROGRAM_ID,
+                "Stake11111111111111111111111111111111111111",
+            ),
+            (NATIVE_MINT, "So11111111111111111111111111111111111111112"),
+        ];
+        for (id, base58) in ids {
+            assert_eq!(id.to_base58(), base58);
+        }
+    }
+
+    #[test]
+    fn test_known_base58_vectors() {
+        assert_eq!(
+            Pubkey::from_base58("11111111111111111111111111111111").unwrap(),
+            Pubkey::new([0; 32])
+        );
+        assert_eq!(
+            Pubkey::from_base58("SysvarC1ock11111111111111111111111111111111")
+                .unwrap()
+                .as_bytes(),
+            &[
+                6, 167, 213, 23, 24, 199, 116, 201, 40, 86, 99, 152, 105, 29, 94, 182, 139, 94,
+                184, 163, 155, 75, 109, 92, 115, 85, 91, 33, 0, 0, 0, 0
+            ]
+        );
+        assert_eq!(
+            Pubkey::from_base58("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA")
+                .unwrap()
+                .as_bytes(),
+            &[
+                6, 221, 246, 225, 215, 101, 161, 147, 217, 203, 225, 70, 206, 235, 121, 172, 28,
+                180, 133, 237, 95, 91, 55, 145, 58, 140, 245, 133, 126, 255, 0, 169
+            ]
+        );
+        assert_eq!(
+            Signature::new([0; 64]).to_base58(),
+            "1".repeat(64),
+            "leading zero bytes map to '1' characters"
+        );
+        assert!(matches!(
+            Pubkey::from_base58("0OIl"),
+            Err(PubkeyError::InvalidBase58)
+        ));
+        assert!(matches!(
+            Hash::from_base58("1111"),
+            Err(HashError::InvalidLength)
+        ));
+    }
+
+    mod properties {
+        use super::*;
+        use proptest::prelude::*;
+
+        proptest! {
+            #[test]
+            fn prop_pubkey_roundtrip(bytes in any::<[u8; 32]>()) {
+                let pubkey = Pubkey::new(bytes);
+                prop_assert_eq!(Pubkey::from_base58(&pubkey.to_base58()).unwrap(), pubkey);
+                let json = serde_json::to_string(&pubkey).unwrap();
+                prop_assert_eq!(serde_json::from_str::<Pubkey>(&json).unwrap(), pubkey);
+                prop_assert_eq!(borsh::to_vec(&pubkey).unwrap(), bytes.to_vec());
+            }
+
+            #[test]
+            fn prop_signature_roundtrip(bytes in prop::collection::vec(any::<u8>(), 64)) {
+                let mut array = [0u8; 64];
+                array.copy_from_slice(&bytes);
+                let signature = Signature::new(array);
+                prop_assert_eq!(
+                    Signature::from_base58(&signature.to_base58()).unwrap(),
+                    signature.clone()
+                );
+                let json = serde_json::to_string(&signature).unwrap();
+                prop_assert_eq!(serde_json::from_str::<Signature>(&json).unwrap(), signature);
+            }
+
+            #[test]
+            fn prop_hash_roundtrip(bytes in any::<[u8; 32]>()) {
+                let hash = Hash::new(bytes);
+                prop_assert_eq!(Hash::from_base58(&hash.to_base58()).unwrap(), hash);
+            }
+
+            #[test]
+            fn prop_parse_arbitrary_strings_never_panics(input in "\\PC{0,100}") {
+                let _ = Pubkey::from_base58(&input);
+                let _ = Signature::from_base58(&input);
+                let _ = Hash::from_base58(&input);
+            }
+
+            #[test]
+            fn prop_wrong_length_rejected(bytes in prop::collection::vec(any::<u8>(), 0..80)) {
+                let encoded = bs58::encode(&bytes).into_string();
+                prop_assert_eq!(Pubkey::from_base58(&encoded).is_ok(), bytes.len() == 32);
+                prop_assert_eq!(Signature::from_base58(&encoded).is_ok(), bytes.len() == 64);
+            }
+        }
+    }
 }
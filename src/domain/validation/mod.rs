@@ -0,0 +1,224 @@
+//! Validation of user input in forms
+//!
+//! Parsing a pubkey or an amount typed into a form fails in ways users can fix:
+//! a mistyped character, too many decimals, an amount above the balance. The
+//! validators here return a [`ValidationError`] whose message can be shown
+//! next to the field as is, instead of a raw parse error.
+
+use crate::domain::types::Pubkey;
+use thiserror::Error;
+
+/// Characters of the base58 alphabet used by Solana addresses
+const BASE58_ALPHABET: &str = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Decimals of SOL, so amounts in SOL convert to lamports
+pub const SOL_DECIMALS: u8 = 9;
+
+/// Longest memo that fits in a SOL transfer signed by its payer alone
+///
+/// Extra instructions, such as compute budget ones, leave less room.
+pub const MAX_TRANSFER_MEMO_BYTES: usize = 981;
+
+/// Reasons a form input was rejected, worded for the person who typed it
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ValidationError {
+    #[error("Enter an address")]
+    EmptyAddress,
+
+    #[error("Addresses cannot contain '{0}'")]
+    InvalidCharacter(char),
+
+    #[error("This is not a valid Solana address")]
+    InvalidAddress,
+
+    #[error("Enter an amount")]
+    EmptyAmount,
+
+    #[error("Amounts cannot be negative")]
+    NegativeAmount,
+
+    #[error("Enter a number, such as 1.5")]
+    NotANumber,
+
+    #[error("Use at most {0} decimal places")]
+    TooManyDecimals(u8),
+
+    #[error("Enter an amount greater than zero")]
+    ZeroAmount,
+
+    #[error("The amount cannot exceed {max}")]
+    AmountTooLarge { max: String },
+
+    #[error("The memo is {len} bytes long, the limit is {max}")]
+    MemoTooLong { len: usize, max: usize },
+}
+
+/// Parse an address typed or pasted by a user
+///
+/// Surrounding whitespace is ignored.
+pub fn validate_pubkey_input(input: &str) -> Result<Pubkey, ValidationError> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err(ValidationError::EmptyAddress);
+    }
+    if let Some(invalid) = input.chars().find(|c| !BASE58_ALPHABET.contains(*c)) {
+        return Err(ValidationError::InvalidCharacter(invalid));
+    }
+    Pubkey::from_base58(input).map_err(|_| ValidationError::InvalidAddress)
+}
+
+/// Parse a positive decimal amount into base units
+///
+/// `decimals` is the token's precision, [`SOL_DECIMALS`] for SOL, and `max`
+/// caps the result in base units, such as a balance or a mint's supply.
+/// `"1.5"` with 9 decimals is 1 500 000 000.
+pub fn validate_lamports_amount(
+    input: &str,
+    decimals: u8,
+    max: u64,
+) -> Result<u64, ValidationError> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err(ValidationError::EmptyAmount);
+    }
+    if input.starts_with('-') {
+        return Err(ValidationError::NegativeAmount);
+    }
+
+    let (whole, fraction) = input.split_once('.').unwrap_or((input, ""));
+    let digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if !digits(whole) || !digits(fraction) || (whole.is_empty() && fraction.is_empty()) {
+        return Err(ValidationError::NotANumber);
+    }
+    if fraction.len() > decimals as usize {
+        return Err(ValidationError::TooManyDecimals(decimals));
+    }
+
+    let too_large = || ValidationError::AmountTooLarge {
+        max: format_units(max, decimals),
+    };
+    let mut units: u64 = 0;
+    let padding = decimals as usize - fraction.len();
+    for digit in whole
+        .bytes()
+        .chain(fraction.bytes())
+        .chain(std::iter::repeat_n(b'0', padding))
+    {
+        units = units
+            .checked_mul(10)
+            .and_then(|units| units.checked_add(u64::from(digit - b'0')))
+            .ok_or_else(too_large)?;
+    }
+
+    if units == 0 {
+        Err(ValidationError::ZeroAmount)
+    } else if units > max {
+        Err(too_large())
+    } else {
+        Ok(units)
+    }
+}
+
+/// Check that `memo` fits in `max_bytes` of UTF-8
+///
+/// [`MAX_TRANSFER_MEMO_BYTES`] is the room left in a plain SOL transfer.
+pub fn validate_memo_length(memo: &str, max_bytes: usize) -> Result<(), ValidationError> {
+    if memo.len() > max_bytes {
+        Err(ValidationError::MemoTooLong {
+            len: memo.len(),
+            max: max_bytes,
+        })
+    } else {
+        Ok(())
+    }
+}
+
+/// `units` of a token with `decimals` as a decimal amount without trailing zeros
+///
+/// The inverse of [`validate_lamports_amount`]: 1 500 000 with 6 decimals is
+/// `"1.5"`.
+pub fn format_units(units: u64, decimals: u8) -> String {
+    let scale = 10u128.pow(decimals as u32);
+    let whole = units as u128 / scale;
+    let fraction = units as u128 % scale;
+    if fraction == 0 {
+        return whole.to_string();
+    }
+    let fraction = format!("{:0width$}", fraction, width = decimals as usize);
+    format!("{}.{}", whole, fraction.trim_end_matches('0'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::message::{Message, Transaction};
+    use crate::domain::programs::{system, ProgramInstruction};
+    use crate::domain::types::constants::MEMO_PROGRAM_ID;
+    use crate::domain::types::Hash;
+
+    #[test]
+    fn test_validate_inputs() {
+        let system = "11111111111111111111111111111111";
+        assert_eq!(
+            validate_pubkey_input(&format!(" {} ", system)).unwrap(),
+            Pubkey::from_base58(system).unwrap()
+        );
+        assert_eq!(
+            validate_pubkey_input(""),
+            Err(ValidationError::EmptyAddress)
+        );
+        assert_eq!(
+            validate_pubkey_input("11111O11"),
+            Err(ValidationError::InvalidCharacter('O'))
+        );
+        assert_eq!(
+            validate_pubkey_input("1111"),
+            Err(ValidationError::InvalidAddress)
+        );
+
+        let max = 2_000_000_000;
+        assert_eq!(
+            validate_lamports_amount("1.5", SOL_DECIMALS, max),
+            Ok(1_500_000_000)
+        );
+        assert_eq!(validate_lamports_amount(".25", 2, max), Ok(25));
+        assert_eq!(
+            validate_lamports_amount("0.000000", SOL_DECIMALS, max),
+            Err(ValidationError::ZeroAmount)
+        );
+        assert_eq!(
+            validate_lamports_amount("1.0000000001", SOL_DECIMALS, max),
+            Err(ValidationError::TooManyDecimals(9))
+        );
+        assert_eq!(
+            validate_lamports_amount("-1", SOL_DECIMALS, max),
+            Err(ValidationError::NegativeAmount)
+        );
+        assert_eq!(
+            validate_lamports_amount("1e9", SOL_DECIMALS, max),
+            Err(ValidationError::NotANumber)
+        );
+        let too_large = validate_lamports_amount("2.1", SOL_DECIMALS, max).unwrap_err();
+        assert_eq!(too_large.to_string(), "The amount cannot exceed 2");
+        assert!(validate_lamports_amount("99999999999", SOL_DECIMALS, u64::MAX).is_err());
+    }
+
+    #[test]
+    fn test_memo_limit_fills_a_transfer() {
+        let payer = Pubkey::new([1; 32]);
+        let memo = "m".repeat(MAX_TRANSFER_MEMO_BYTES);
+        assert!(validate_memo_length(&memo, MAX_TRANSFER_MEMO_BYTES).is_ok());
+        assert_eq!(
+            validate_memo_length("héllo", 5),
+            Err(ValidationError::MemoTooLong { len: 6, max: 5 })
+        );
+
+        let instructions = [
+            system::transfer(payer, Pubkey::new([2; 32]), 1),
+            ProgramInstruction::new(MEMO_PROGRAM_ID, Vec::new(), memo.into_bytes(), 0),
+        ];
+        let message = Message::compile(&instructions, Some(&payer), Hash::new([3; 32])).unwrap();
+        let transaction = Transaction::new_unsigned(message);
+        assert_eq!(transaction.serialize().unwrap().len(), 1232);
+    }
+}
@@ -0,0 +1,381 @@
+//! Anchor workspace configuration
+//!
+//! Parses `Anchor.toml` and resolves the layout `anchor build` produces: each
+//! program's id per cluster, its deploy keypair and shared object under
+//! `target/deploy`, and its IDL under `target/idl`. The provider section maps
+//! to a [`Network`] and wallet path. Tables Anchor does not define, such as a
+//! project's own deployment settings, can be read with
+//! [`AnchorWorkspace::section`].
+
+use crate::domain::types::{Pubkey, PubkeyError};
+use crate::infrastructure::network::Network;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Name of the workspace configuration file
+pub const ANCHOR_TOML: &str = "Anchor.toml";
+
+/// Endpoint of a local validator
+pub const LOCALNET_ENDPOINT: &str = "http://127.0.0.1:8899";
+
+/// Errors that can occur while reading a workspace
+#[derive(Debug, Clone, Error)]
+pub enum WorkspaceError {
+    #[error("Failed to read {path}: {message}")]
+    Io { path: PathBuf, message: String },
+
+    #[error("Invalid Anchor.toml: {0}")]
+    Toml(String),
+
+    #[error("No Anchor.toml found in {0} or its parents")]
+    NotFound(PathBuf),
+
+    #[error("Invalid program id for {name}: {source}")]
+    InvalidProgramId { name: String, source: PubkeyError },
+
+    #[error("Invalid [{section}] section: {message}")]
+    Section { section: String, message: String },
+}
+
+/// The `[provider]` section
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Provider {
+    pub cluster: String,
+    pub wallet: String,
+}
+
+/// The `[workspace]` section
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct WorkspaceLayout {
+    #[serde(default)]
+    pub members: Vec<String>,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    #[serde(default)]
+    pub types: Option<String>,
+}
+
+/// A program entry, either an address or a table with overrides
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum ProgramEntry {
+    Address(String),
+    Detailed {
+        address: String,
+        #[serde(default)]
+        path: Option<String>,
+        #[serde(default)]
+        idl: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct AnchorToml {
+    provider: Provider,
+    #[serde(default)]
+    programs: BTreeMap<String, BTreeMap<String, ProgramEntry>>,
+    #[serde(default)]
+    workspace: WorkspaceLayout,
+    #[serde(flatten)]
+    extra: toml::Table,
+}
+
+/// A program declared for one cluster, with its build artifacts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkspaceProgram {
+    pub name: String,
+    pub program_id: Pubkey,
+    /// Keypair `anchor deploy` uses for the program address
+    pub keypair_path: PathBuf,
+    /// Compiled program
+    pub so_path: PathBuf,
+    pub idl_path: PathBuf,
+}
+
+/// A parsed Anchor workspace rooted at the directory holding `Anchor.toml`
+#[derive(Debug, Clone)]
+pub struct AnchorWorkspace {
+    root: PathBuf,
+    config: AnchorToml,
+}
+
+impl AnchorWorkspace {
+    /// Parse `Anchor.toml` contents for a workspace at `root`
+    pub fn parse(content: &str, root: impl Into<PathBuf>) -> Result<Self, WorkspaceError> {
+        let config = toml::from_str(content).map_err(|e| WorkspaceError::Toml(e.to_string()))?;
+        Ok(Self {
+            root: root.into(),
+            config,
+        })
+    }
+
+    /// Load an `Anchor.toml` file
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, WorkspaceError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|e| WorkspaceError::Io {
+            path: path.to_path_buf(),
+            message: e.to_string(),
+        })?;
+        let root = path.parent().unwrap_or_else(|| Path::new("."));
+        Self::parse(&content, root)
+    }
+
+    /// Load the workspace containing `dir`, searching parent directories
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn discover(dir: impl AsRef<Path>) -> Result<Self, WorkspaceError> {
+        let dir = dir.as_ref();
+        dir.ancestors()
+            .map(|ancestor| ancestor.join(ANCHOR_TOML))
+            .find(|candidate| candidate.is_file())
+            .ok_or_else(|| WorkspaceError::NotFound(dir.to_path_buf()))
+            .and_then(Self::load)
+    }
+
+    /// Directory holding `Anchor.toml`
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The `[provider]` section
+    pub fn provider(&self) -> &Provider {
+        &self.config.provider
+    }
+
+    /// The `[workspace]` section
+    pub fn layout(&self) -> &WorkspaceLayout {
+        &self.config.workspace
+    }
+
+    /// Network for the provider cluster
+    ///
+    /// `localnet` maps to a local validator and other unknown names are
+    /// treated as RPC URLs.
+    pub fn network(&self) -> Network {
+        match self.config.provider.cluster.to_lowercase().as_str() {
+            "localnet" | "localhost" => Network::custom(LOCALNET_ENDPOINT),
+            "devnet" => Network::Devnet,
+            "testnet" => Network::Testnet,
+            "mainnet" | "mainnet-beta" => Network::Mainnet,
+            _ => Network::custom(self.config.provider.cluster.clone()),
+        }
+    }
+
+    /// Provider wallet keypair path, with `~` expanded
+    pub fn wallet_path(&self) -> PathBuf {
+        self.resolve_path(&self.config.provider.wallet)
+    }
+
+    /// Clusters with a `[programs.<cluster>]` section
+    pub fn clusters(&self) -> Vec<&str> {
+        self.config.programs.keys().map(String::as_str).collect()
+    }
+
+    /// Programs declared for `cluster`, ordered by name
+    pub fn programs(&self, cluster: &str) -> Result<Vec<WorkspaceProgram>, WorkspaceError> {
+        self.config
+            .programs
+            .get(cluster)
+            .into_iter()
+            .flatten()
+            .map(|(name, entry)| self.resolve_program(name, entry))
+            .collect()
+    }
+
+    /// Programs declared for the provider cluster
+    pub fn provider_programs(&self) -> Result<Vec<WorkspaceProgram>, WorkspaceError> {
+        // Anchor accepts `cluster = "Localnet"` for `[programs.localnet]`
+        self.programs(&self.config.provider.cluster.to_lowercase())
+    }
+
+    /// One program declared for `cluster`
+    pub fn program(
+        &self,
+        cluster: &str,
+        name: &str,
+    ) -> Result<Option<WorkspaceProgram>, WorkspaceError> {
+        self.config
+            .programs
+            .get(cluster)
+            .and_then(|programs| programs.get(name))
+            .map(|entry| self.resolve_program(name, entry))
+            .transpose()
+    }
+
+    /// Deserialize a table Anchor itself does not define, e.g. `[surfpool]`
+    pub fn section<T: DeserializeOwned>(&self, name: &str) -> Result<Option<T>, WorkspaceError> {
+        self.config
+            .extra
+            .get(name)
+            .map(|value| {
+                value
+                    .clone()
+                    .try_into()
+                    .map_err(|e: toml::de::Error| WorkspaceError::Section {
+                        section: name.to_string(),
+                        message: e.to_string(),
+                    })
+            })
+            .transpose()
+    }
+
+    /// Read a program's IDL from its build output
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load_idl(
+        &self,
+        program: &WorkspaceProgram,
+    ) -> Result<crate::domain::idl::Idl, WorkspaceError> {
+        let json = std::fs::read_to_string(&program.idl_path).map_err(|e| WorkspaceError::Io {
+            path: program.idl_path.clone(),
+            message: e.to_string(),
+        })?;
+        crate::domain::idl::Idl::from_json(&json).map_err(|e| WorkspaceError::Io {
+            path: program.idl_path.clone(),
+            message: e.to_string(),
+        })
+    }
+
+    fn resolve_program(
+        &self,
+        name: &str,
+        entry: &ProgramEntry,
+    ) -> Result<WorkspaceProgram, WorkspaceError> {
+        let (address, path, idl) = match entry {
+            ProgramEntry::Address(address) => (address, None, None),
+            ProgramEntry::Detailed { address, path, idl } => {
+                (address, path.as_deref(), idl.as_deref())
+            }
+        };
+        let program_id =
+            Pubkey::from_base58(address).map_err(|source| WorkspaceError::InvalidProgramId {
+                name: name.to_string(),
+                source,
+            })?;
+
+        // Build artifacts are named after the crate's lib name
+        let artifact = name.replace('-', "_");
+        let deploy = self.root.join("target").join("deploy");
+        Ok(WorkspaceProgram {
+            name: name.to_string(),
+            program_id,
+            keypair_path: deploy.join(format!("{}-keypair.json", artifact)),
+            so_path: path
+                .map(|path| self.resolve_path(path))
+                .unwrap_or_else(|| deploy.join(format!("{}.so", artifact))),
+            idl_path: idl.map(|idl| self.resolve_path(idl)).unwrap_or_else(|| {
+                self.root
+                    .join("target")
+                    .join("idl")
+                    .join(format!("{}.json", artifact))
+            }),
+        })
+    }
+
+    /// Resolve a configured path against the home or workspace directory
+    /// Resolve a path from `Anchor.toml` against the workspace, expanding `~`
+    pub fn resolve_path(&self, path: &str) -> PathBuf {
+        match path.strip_prefix("~/") {
+            Some(rest) => std::env::var_os("HOME")
+                .map(PathBuf::from)
+                .unwrap_or_default()
+                .join(rest),
+            None => self.root.join(path),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ANCHOR_TOML_CONTENT: &str = r#"
+[features]
+resolution = true
+
+[programs.localnet]
+counter = "CounterProgram111111111111111111111111111111"
+token-vault = { address = "11111111111111111111111111111111", idl = "idls/vault.json" }
+
+[programs.devnet]
+counter = "CounterProgram111111111111111111111111111111"
+
+[provider]
+cluster = "Localnet"
+wallet = "keys/id.json"
+
+[workspace]
+members = ["programs/*"]
+
+[surfpool]
+endpoint = "http://127.0.0.1:8899"
+preflight = false
+"#;
+
+    #[derive(Debug, Deserialize)]
+    struct Surfpool {
+        endpoint: String,
+        preflight: bool,
+    }
+
+    #[test]
+    fn test_resolves_programs_and_artifacts() {
+        let workspace = AnchorWorkspace::parse(ANCHOR_TOML_CONTENT, "/work").unwrap();
+        assert_eq!(workspace.clusters(), vec!["devnet", "localnet"]);
+        assert_eq!(workspace.layout().members, vec!["programs/*"]);
+        assert_eq!(workspace.wallet_path(), PathBuf::from("/work/keys/id.json"));
+        assert_eq!(workspace.network().endpoint(), LOCALNET_ENDPOINT);
+
+        let programs = workspace.programs("localnet").unwrap();
+        assert_eq!(programs.len(), 2);
+        assert_eq!(programs[0].name, "counter");
+        assert_eq!(
+            programs[0].keypair_path,
+            PathBuf::from("/work/target/deploy/counter-keypair.json")
+        );
+        assert_eq!(
+            programs[0].idl_path,
+            PathBuf::from("/work/target/idl/counter.json")
+        );
+        assert_eq!(
+            programs[1].so_path,
+            PathBuf::from("/work/target/deploy/token_vault.so")
+        );
+        assert_eq!(programs[1].idl_path, PathBuf::from("/work/idls/vault.json"));
+
+        assert!(workspace
+            .program("devnet", "token-vault")
+            .unwrap()
+            .is_none());
+        assert_eq!(workspace.provider_programs().unwrap(), programs);
+    }
+
+    #[test]
+    fn test_custom_sections_and_errors() {
+        let workspace = AnchorWorkspace::parse(ANCHOR_TOML_CONTENT, "/work").unwrap();
+        let surfpool: Surfpool = workspace.section("surfpool").unwrap().unwrap();
+        assert_eq!(surfpool.endpoint, LOCALNET_ENDPOINT);
+        assert!(!surfpool.preflight);
+        assert!(workspace.section::<Surfpool>("airdrop").unwrap().is_none());
+        assert!(matches!(
+            workspace.section::<Surfpool>("features"),
+            Err(WorkspaceError::Section { .. })
+        ));
+
+        let invalid = AnchorWorkspace::parse(
+            "[provider]\ncluster = \"devnet\"\nwallet = \"id.json\"\n[programs.devnet]\nbad = \"0OIl\"\n",
+            ".",
+        )
+        .unwrap();
+        assert!(matches!(
+            invalid.programs("devnet"),
+            Err(WorkspaceError::InvalidProgramId { name, .. }) if name == "bad"
+        ));
+        assert!(matches!(
+            AnchorWorkspace::parse("[programs.devnet]", "."),
+            Err(WorkspaceError::Toml(_))
+        ));
+    }
+}
@@ -0,0 +1,290 @@
+//! Response cache for JSON-RPC reads
+//!
+//! This module provides a small cache shared by every clone of a
+//! [`SolanaRpcClient`](crate::infrastructure::rpc::SolanaRpcClient). Entries are
+//! keyed by method name and serialized params, and expire either after a
+//! wall-clock TTL or once the chain has moved on by a number of slots, which
+//! ages data the same way however fast the cluster is producing blocks.
+
+use crate::infrastructure::time::Instant;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+pub mod transactions;
+
+pub use transactions::{TransactionCache, DEFAULT_TRANSACTION_CACHE_BYTES};
+
+/// Methods whose responses must never be cached, because they mutate state or
+/// report status, history and clock values that callers poll for changes
+const UNCACHEABLE_METHODS: &[&str] = &[
+    "sendTransaction",
+    "requestAirdrop",
+    "simulateTransaction",
+    "getSignatureStatuses",
+    "getSignaturesForAddress",
+    "getLatestBlockhash",
+    "getBlockHeight",
+    "getSlot",
+    "getEpochInfo",
+];
+
+/// Default delay between slot polls for [`CachePolicy::Slots`], about one slot
+pub const DEFAULT_SLOT_POLL_INTERVAL: Duration = Duration::from_millis(400);
+
+/// When cached responses expire
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CachePolicy {
+    /// Entries expire after a wall-clock duration
+    Ttl(Duration),
+    /// Entries expire once the slot advances by `slots` past their insertion
+    ///
+    /// The client learns the slot from the context of its responses and, when
+    /// it has not seen one for `poll_interval`, by polling `getSlot`.
+    Slots { slots: u64, poll_interval: Duration },
+}
+
+impl CachePolicy {
+    /// Entries valid for `slots` slots, polling at the default interval
+    pub fn slots(slots: u64) -> Self {
+        CachePolicy::Slots {
+            slots,
+            poll_interval: DEFAULT_SLOT_POLL_INTERVAL,
+        }
+    }
+}
+
+/// Cache of JSON-RPC results
+#[derive(Debug)]
+pub struct ResponseCache {
+    policy: CachePolicy,
+    entries: Mutex<HashMap<String, CacheEntry>>,
+    clock: Mutex<SlotClock>,
+}
+
+#[derive(Debug, Clone)]
+struct CacheEntry {
+    value: serde_json::Value,
+    inserted_at: Instant,
+    /// Latest slot known when the entry was inserted
+    inserted_slot: Option<u64>,
+}
+
+/// Latest slot seen and when it was last refreshed
+#[derive(Debug, Default)]
+struct SlotClock {
+    slot: Option<u64>,
+    observed_at: Option<Instant>,
+}
+
+impl ResponseCache {
+    /// Create a new cache whose entries expire after `ttl`
+    pub fn new(ttl: Duration) -> Self {
+        Self::with_policy(CachePolicy::Ttl(ttl))
+    }
+
+    /// Create a new cache expiring entries according to `policy`
+    pub fn with_policy(policy: CachePolicy) -> Self {
+        Self {
+            policy,
+            entries: Mutex::new(HashMap::new()),
+            clock: Mutex::new(SlotClock::default()),
+        }
+    }
+
+    /// Get the expiry policy
+    pub fn policy(&self) -> CachePolicy {
+        self.policy
+    }
+
+    /// Get the configured time-to-live, `None` under a slot policy
+    pub fn ttl(&self) -> Option<Duration> {
+        match self.policy {
+            CachePolicy::Ttl(ttl) => Some(ttl),
+            CachePolicy::Slots { .. } => None,
+        }
+    }
+
+    /// Latest slot seen, if any
+    pub fn current_slot(&self) -> Option<u64> {
+        self.clock.lock().ok()?.slot
+    }
+
+    /// Record that the chain has reached `slot`
+    ///
+    /// Older slots, from a lagging node or a lower commitment, are ignored,
+    /// but still count as a fresh observation.
+    pub fn observe_slot(&self, slot: u64) {
+        if let Ok(mut clock) = self.clock.lock() {
+            clock.slot = Some(clock.slot.map_or(slot, |current| current.max(slot)));
+            clock.observed_at = Some(Instant::now());
+        }
+    }
+
+    /// Record the slot of a response carrying an RPC context
+    pub fn observe_response(&self, value: &serde_json::Value) {
+        if let Some(slot) = value
+            .pointer("/context/slot")
+            .and_then(|slot| slot.as_u64())
+        {
+            self.observe_slot(slot);
+        }
+    }
+
+    /// Whether the slot should be polled before the cache is consulted
+    ///
+    /// Always `false` under a TTL policy.
+    pub fn needs_slot_poll(&self) -> bool {
+        let CachePolicy::Slots { poll_interval, .. } = self.policy else {
+            return false;
+        };
+        self.clock.lock().is_ok_and(|clock| {
+            clock
+                .observed_at
+                .is_none_or(|at| at.elapsed() >= poll_interval)
+        })
+    }
+
+    /// Record a failed slot poll, so the next one waits a full interval
+    pub fn slot_poll_failed(&self) {
+        if let Ok(mut clock) = self.clock.lock() {
+            clock.observed_at = Some(Instant::now());
+        }
+    }
+
+    /// Whether responses for this method may be cached
+    pub fn is_cacheable(method: &str) -> bool {
+        !UNCACHEABLE_METHODS.contains(&method)
+    }
+
+    /// Build the cache key for a request
+    pub fn key(method: &str, params: &[serde_json::Value]) -> String {
+        format!(
+            "{}:{}",
+            method,
+            serde_json::to_string(params).unwrap_or_default()
+        )
+    }
+
+    /// Look up a fresh entry
+    pub fn get(&self, key: &str) -> Option<serde_json::Value> {
+        let current_slot = self.current_slot();
+        let mut entries = self.entries.lock().ok()?;
+        match entries.get(key) {
+            Some(entry) if self.is_fresh(entry, current_slot) => Some(entry.value.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Store a value
+    pub fn insert(&self, key: String, value: serde_json::Value) {
+        let inserted_slot = self.current_slot();
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.insert(
+                key,
+                CacheEntry {
+                    value,
+                    inserted_at: Instant::now(),
+                    inserted_slot,
+                },
+            );
+        }
+    }
+
+    /// Whether `entry` is still valid, given the latest slot seen
+    ///
+    /// Under a slot policy, entries stored before any slot was known are
+    /// treated as expired.
+    fn is_fresh(&self, entry: &CacheEntry, current_slot: Option<u64>) -> bool {
+        match self.policy {
+            CachePolicy::Ttl(ttl) => entry.inserted_at.elapsed() < ttl,
+            CachePolicy::Slots { slots, .. } => match (entry.inserted_slot, current_slot) {
+                (Some(inserted), Some(current)) => current.saturating_sub(inserted) < slots,
+                _ => false,
+            },
+        }
+    }
+
+    /// Remove every entry
+    pub fn clear(&self) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.clear();
+        }
+    }
+
+    /// Number of entries currently stored (including expired ones not yet evicted)
+    pub fn len(&self) -> usize {
+        self.entries
+            .lock()
+            .map(|entries| entries.len())
+            .unwrap_or(0)
+    }
+
+    /// Whether the cache holds no entries
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_cache_roundtrip() {
+        let cache = ResponseCache::new(Duration::from_secs(60));
+        let key = ResponseCache::key("getBalance", &[json!("abc")]);
+
+        assert!(cache.get(&key).is_none());
+        cache.insert(key.clone(), json!({ "value": 5 }));
+        assert_eq!(cache.get(&key), Some(json!({ "value": 5 })));
+        assert_eq!(cache.len(), 1);
+
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_cache_expiry() {
+        let cache = ResponseCache::new(Duration::from_millis(0));
+        cache.insert("k".to_string(), json!(1));
+        assert!(cache.get("k").is_none());
+    }
+
+    #[test]
+    fn test_slot_expiry() {
+        let cache = ResponseCache::with_policy(CachePolicy::Slots {
+            slots: 2,
+            poll_interval: Duration::from_secs(60),
+        });
+        assert!(cache.needs_slot_poll());
+        cache.insert("early".to_string(), json!(0));
+        cache.observe_response(&json!({ "context": { "slot": 100 }, "value": 5 }));
+        assert!(!cache.needs_slot_poll());
+        assert!(cache.get("early").is_none());
+
+        cache.insert("k".to_string(), json!(1));
+        cache.observe_slot(101);
+        assert_eq!(cache.get("k"), Some(json!(1)));
+        // A lagging node does not move the clock back
+        cache.observe_slot(90);
+        assert_eq!(cache.current_slot(), Some(101));
+        cache.observe_slot(102);
+        assert!(cache.get("k").is_none());
+        assert_eq!(cache.ttl(), None);
+        assert!(!ResponseCache::new(Duration::from_secs(1)).needs_slot_poll());
+    }
+
+    #[test]
+    fn test_uncacheable_methods() {
+        assert!(ResponseCache::is_cacheable("getAccountInfo"));
+        assert!(!ResponseCache::is_cacheable("sendTransaction"));
+        assert!(!ResponseCache::is_cacheable("getSignatureStatuses"));
+        assert!(!ResponseCache::is_cacheable("getLatestBlockhash"));
+    }
+}
@@ -0,0 +1,269 @@
+//! Cache of fetched transactions
+//!
+//! A confirmed transaction never changes, so a history view scrolled back and
+//! forth only needs to fetch each one once. [`TransactionCache`] keeps
+//! `getTransaction` results by signature, evicting the least recently used
+//! ones past a memory budget, and can write them through to a
+//! [`StorageAdapter`] that outlives the page.
+
+use crate::domain::types::Signature;
+use crate::infrastructure::rpc::ConfirmedTransaction;
+use crate::infrastructure::storage::StorageAdapter;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+/// Default memory budget of a [`TransactionCache`]
+pub const DEFAULT_TRANSACTION_CACHE_BYTES: usize = 16 * 1024 * 1024;
+
+/// Storage key prefix for persisted transactions
+const STORAGE_PREFIX: &str = "gloo_solana:tx:";
+
+/// Least recently used `getTransaction` results, bounded by their JSON size
+///
+/// Clones share the same entries. Storage failures are ignored, as the
+/// transaction can always be fetched again.
+#[derive(Clone)]
+pub struct TransactionCache {
+    entries: Arc<Mutex<Lru>>,
+    storage: Option<Arc<dyn StorageAdapter>>,
+}
+
+impl TransactionCache {
+    /// Create a cache holding at most `max_bytes` of transaction JSON
+    pub fn new(max_bytes: usize) -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(Lru::new(max_bytes))),
+            storage: None,
+        }
+    }
+
+    /// Also keep every transaction in `storage`
+    ///
+    /// Transactions evicted from memory are read back from it.
+    pub fn with_storage(mut self, storage: Arc<dyn StorageAdapter>) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    /// The cached transaction with `signature`
+    pub fn get(&self, signature: &Signature) -> Option<ConfirmedTransaction> {
+        serde_json::from_value(self.get_value(signature)?).ok()
+    }
+
+    pub fn contains(&self, signature: &Signature) -> bool {
+        self.lock().entries.contains_key(signature)
+    }
+
+    /// Drop the transaction with `signature` from memory and storage
+    pub fn remove(&self, signature: &Signature) {
+        self.lock().remove(signature);
+        if let Some(storage) = &self.storage {
+            let _ = storage.remove(&storage_key(signature));
+        }
+    }
+
+    /// Drop every transaction held in memory
+    pub fn clear(&self) {
+        let mut lru = self.lock();
+        *lru = Lru::new(lru.max_bytes);
+    }
+
+    /// Transactions held in memory
+    pub fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().entries.is_empty()
+    }
+
+    /// JSON bytes held in memory
+    pub fn size_bytes(&self) -> usize {
+        self.lock().bytes
+    }
+
+    pub fn max_bytes(&self) -> usize {
+        self.lock().max_bytes
+    }
+
+    /// The raw `getTransaction` result for `signature`
+    pub(crate) fn get_value(&self, signature: &Signature) -> Option<Value> {
+        if let Some(value) = self.lock().get(signature) {
+            return Some(value);
+        }
+        let raw = self.storage.as_ref()?.get(&storage_key(signature)).ok()??;
+        let value: Value = serde_json::from_str(&raw).ok()?;
+        self.lock()
+            .insert(signature.clone(), value.clone(), raw.len());
+        Some(value)
+    }
+
+    /// Keep a raw `getTransaction` result; `null` results are not cached
+    pub(crate) fn insert_value(&self, signature: &Signature, value: &Value) {
+        if value.is_null() {
+            return;
+        }
+        let raw = value.to_string();
+        if let Some(storage) = &self.storage {
+            let _ = storage.set(&storage_key(signature), &raw);
+        }
+        self.lock()
+            .insert(signature.clone(), value.clone(), raw.len());
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Lru> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Default for TransactionCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_TRANSACTION_CACHE_BYTES)
+    }
+}
+
+impl fmt::Debug for TransactionCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lru = self.lock();
+        f.debug_struct("TransactionCache")
+            .field("len", &lru.entries.len())
+            .field("bytes", &lru.bytes)
+            .field("max_bytes", &lru.max_bytes)
+            .field("persistent", &self.storage.is_some())
+            .finish()
+    }
+}
+
+fn storage_key(signature: &Signature) -> String {
+    format!("{}{}", STORAGE_PREFIX, signature)
+}
+
+struct LruEntry {
+    value: Value,
+    bytes: usize,
+    last_used: u64,
+}
+
+/// Entries with their last use, oldest first in `recency`
+struct Lru {
+    entries: HashMap<Signature, LruEntry>,
+    recency: BTreeMap<u64, Signature>,
+    tick: u64,
+    bytes: usize,
+    max_bytes: usize,
+}
+
+impl Lru {
+    fn new(max_bytes: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            tick: 0,
+            bytes: 0,
+            max_bytes,
+        }
+    }
+
+    fn get(&mut self, signature: &Signature) -> Option<Value> {
+        self.tick += 1;
+        let entry = self.entries.get_mut(signature)?;
+        self.recency.remove(&entry.last_used);
+        entry.last_used = self.tick;
+        self.recency.insert(self.tick, signature.clone());
+        Some(entry.value.clone())
+    }
+
+    fn insert(&mut self, signature: Signature, value: Value, bytes: usize) {
+        self.remove(&signature);
+        if bytes > self.max_bytes {
+            return;
+        }
+        while self.bytes + bytes > self.max_bytes {
+            let Some((_, oldest)) = self.recency.pop_first() else {
+                break;
+            };
+            if let Some(evicted) = self.entries.remove(&oldest) {
+                self.bytes -= evicted.bytes;
+            }
+        }
+        self.tick += 1;
+        self.recency.insert(self.tick, signature.clone());
+        self.bytes += bytes;
+        self.entries.insert(
+            signature,
+            LruEntry {
+                value,
+                bytes,
+                last_used: self.tick,
+            },
+        );
+    }
+
+    fn remove(&mut self, signature: &Signature) {
+        if let Some(entry) = self.entries.remove(signature) {
+            self.recency.remove(&entry.last_used);
+            self.bytes -= entry.bytes;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::storage::MemoryStorage;
+    use serde_json::json;
+
+    fn transaction(slot: u64) -> Value {
+        json!({
+            "slot": slot,
+            "blockTime": null,
+            "meta": null,
+            "transaction": {
+                "signatures": [],
+                "message": {
+                    "accountKeys": [],
+                    "recentBlockhash": "11111111111111111111111111111111"
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let size = transaction(1).to_string().len();
+        let cache = TransactionCache::new(size * 2);
+        let (a, b, c) = (
+            Signature::new([1; 64]),
+            Signature::new([2; 64]),
+            Signature::new([3; 64]),
+        );
+        cache.insert_value(&a, &transaction(1));
+        cache.insert_value(&b, &transaction(2));
+        cache.insert_value(&c, &Value::Null);
+        assert_eq!(cache.len(), 2);
+
+        // Reading `a` makes `b` the least recently used
+        assert_eq!(cache.get(&a).unwrap().slot, 1);
+        cache.insert_value(&c, &transaction(3));
+        assert!(cache.contains(&a) && cache.contains(&c) && !cache.contains(&b));
+        assert_eq!(cache.size_bytes(), size * 2);
+        assert!(cache.get(&b).is_none());
+    }
+
+    #[test]
+    fn test_storage_outlives_memory() {
+        let storage: Arc<dyn StorageAdapter> = Arc::new(MemoryStorage::new());
+        let signature = Signature::new([4; 64]);
+        TransactionCache::default()
+            .with_storage(storage.clone())
+            .insert_value(&signature, &transaction(7));
+
+        let reloaded = TransactionCache::default().with_storage(storage);
+        assert_eq!(reloaded.get(&signature).unwrap().slot, 7);
+        assert!(reloaded.contains(&signature));
+        reloaded.remove(&signature);
+        assert!(reloaded.get(&signature).is_none());
+    }
+}
@@ -0,0 +1,147 @@
+//! Cancellation of in-flight work
+//!
+//! A [`CancelToken`] is shared between the code that starts long-running work,
+//! such as a `getProgramAccounts` scan or a confirmation loop, and whatever
+//! decides it is no longer needed, typically a UI component unmounting.
+//! Cancelling wakes every future waiting on the token; clients built with
+//! [`SolanaRpcClient::with_cancel_token`] then abandon their requests, which
+//! drops the connection natively and aborts the fetch in browsers.
+//!
+//! [`SolanaRpcClient::with_cancel_token`]: crate::infrastructure::rpc::SolanaRpcClient::with_cancel_token
+
+use futures::future::{select, Either};
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+/// Shared flag cancelling the work that watches it
+///
+/// Clones observe the same flag. Cancelling is permanent.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    state: Arc<CancelState>,
+}
+
+#[derive(Debug, Default)]
+struct CancelState {
+    cancelled: AtomicBool,
+    next_waiter: AtomicU64,
+    /// Tasks waiting in [`Cancelled`], by waiter id
+    waiters: Mutex<HashMap<u64, Waker>>,
+}
+
+impl CancelState {
+    fn waiters(&self) -> std::sync::MutexGuard<'_, HashMap<u64, Waker>> {
+        self.waiters.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl CancelToken {
+    /// Create a token that has not been cancelled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel, waking everything waiting on this token
+    pub fn cancel(&self) {
+        if self.state.cancelled.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let waiters: Vec<Waker> = self.state.waiters().drain().map(|(_, w)| w).collect();
+        for waker in waiters {
+            waker.wake();
+        }
+    }
+
+    /// Whether [`cancel`](Self::cancel) has been called
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Resolve once the token is cancelled
+    pub fn cancelled(&self) -> Cancelled {
+        Cancelled {
+            state: self.state.clone(),
+            waiter: None,
+        }
+    }
+
+    /// Run `future` to completion unless the token is cancelled first
+    ///
+    /// Returns `None` on cancellation, after dropping `future`.
+    pub async fn run<F: Future>(&self, future: F) -> Option<F::Output> {
+        if self.is_cancelled() {
+            return None;
+        }
+        match select(Box::pin(future), self.cancelled()).await {
+            Either::Left((output, _)) => Some(output),
+            Either::Right(_) => None,
+        }
+    }
+}
+
+/// Future returned by [`CancelToken::cancelled`]
+#[derive(Debug)]
+pub struct Cancelled {
+    state: Arc<CancelState>,
+    waiter: Option<u64>,
+}
+
+impl Future for Cancelled {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.state.cancelled.load(Ordering::SeqCst) {
+            return Poll::Ready(());
+        }
+        let id = match self.waiter {
+            Some(id) => id,
+            None => {
+                let id = self.state.next_waiter.fetch_add(1, Ordering::Relaxed);
+                self.waiter = Some(id);
+                id
+            }
+        };
+        let mut waiters = self.state.waiters();
+        // Checked again under the lock, which `cancel` takes after setting it
+        if self.state.cancelled.load(Ordering::SeqCst) {
+            return Poll::Ready(());
+        }
+        waiters.insert(id, cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+impl Drop for Cancelled {
+    fn drop(&mut self) {
+        if let Some(id) = self.waiter {
+            self.state.waiters().remove(&id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_cancel_wakes_waiters() {
+        let token = CancelToken::new();
+        let remote = token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            remote.cancel();
+        });
+
+        let output = token.run(std::future::pending::<()>()).await;
+        assert_eq!(output, None);
+        assert!(token.is_cancelled());
+        assert!(token.state.waiters().is_empty());
+        assert_eq!(token.run(async { 1 }).await, None);
+        assert_eq!(CancelToken::new().run(async { 1 }).await, Some(1));
+    }
+}
@@ -0,0 +1,214 @@
+//! Cluster detection
+//!
+//! Mainnet, devnet, testnet, surfpool and local validators differ in the
+//! methods they serve: only test clusters hand out airdrops and only surfpool
+//! understands `surfnet_*` cheatcodes. [`ClusterCapabilities`] identifies the
+//! cluster behind an endpoint from its genesis hash and `getVersion`, so a
+//! call can be rejected up front with [`RpcError::Unsupported`] instead of
+//! failing with whatever error the node happens to return.
+
+use crate::domain::types::Hash;
+use crate::infrastructure::network::Network;
+use crate::infrastructure::rpc::RpcError;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Prefix of surfpool's cheatcode methods
+pub const CHEATCODE_PREFIX: &str = "surfnet_";
+
+/// The kind of cluster an endpoint belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ClusterKind {
+    Mainnet,
+    Testnet,
+    Devnet,
+    /// A surfpool simnet, possibly forking mainnet state
+    Surfpool,
+    /// A validator on this machine, e.g. `solana-test-validator`
+    Local,
+    /// A private cluster with an unrecognized genesis hash
+    Unknown,
+}
+
+impl ClusterKind {
+    /// The public cluster with `genesis_hash`, if any
+    pub fn from_genesis_hash(genesis_hash: &Hash) -> Option<Self> {
+        [
+            (Network::Mainnet, Self::Mainnet),
+            (Network::Testnet, Self::Testnet),
+            (Network::Devnet, Self::Devnet),
+        ]
+        .into_iter()
+        .find(|(network, _)| network.genesis_hash().as_ref() == Some(genesis_hash))
+        .map(|(_, kind)| kind)
+    }
+
+    /// Whether the cluster's tokens have real value
+    pub fn is_production(&self) -> bool {
+        matches!(self, Self::Mainnet)
+    }
+}
+
+impl fmt::Display for ClusterKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Mainnet => "mainnet-beta",
+            Self::Testnet => "testnet",
+            Self::Devnet => "devnet",
+            Self::Surfpool => "surfpool",
+            Self::Local => "localnet",
+            Self::Unknown => "unknown cluster",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Software version reported by `getVersion`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RpcVersion {
+    #[serde(rename = "solana-core")]
+    pub solana_core: String,
+    #[serde(rename = "feature-set", default)]
+    pub feature_set: Option<u32>,
+    /// Fields added by other node implementations
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, Value>,
+}
+
+impl RpcVersion {
+    /// Whether the node is surfpool, which adds its own version field
+    pub fn is_surfpool(&self) -> bool {
+        self.extra
+            .keys()
+            .any(|key| key.contains("surfnet") || key.contains("surfpool"))
+    }
+}
+
+/// What the cluster behind an endpoint is and which methods it serves
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClusterCapabilities {
+    pub kind: ClusterKind,
+    pub genesis_hash: Hash,
+    pub version: RpcVersion,
+}
+
+impl ClusterCapabilities {
+    /// Identify the cluster from its genesis hash, version and endpoint
+    ///
+    /// Surfpool is recognized by its version before the genesis hash is
+    /// checked, since a mainnet fork may report mainnet's genesis.
+    pub fn detect(genesis_hash: Hash, version: RpcVersion, endpoint: &str) -> Self {
+        let kind = if version.is_surfpool() {
+            ClusterKind::Surfpool
+        } else if let Some(kind) = ClusterKind::from_genesis_hash(&genesis_hash) {
+            kind
+        } else if is_local_endpoint(endpoint) {
+            ClusterKind::Local
+        } else {
+            ClusterKind::Unknown
+        };
+
+        Self {
+            kind,
+            genesis_hash,
+            version,
+        }
+    }
+
+    /// Whether `requestAirdrop` can succeed
+    pub fn supports_airdrop(&self) -> bool {
+        !self.kind.is_production()
+    }
+
+    /// Whether `surfnet_*` cheatcodes are served
+    pub fn supports_cheatcodes(&self) -> bool {
+        self.kind == ClusterKind::Surfpool
+    }
+
+    /// Whether the cluster serves `method`
+    pub fn supports(&self, method: &str) -> bool {
+        match method {
+            "requestAirdrop" => self.supports_airdrop(),
+            method if method.starts_with(CHEATCODE_PREFIX) => self.supports_cheatcodes(),
+            _ => true,
+        }
+    }
+
+    /// Fail with [`RpcError::Unsupported`] unless the cluster serves `method`
+    pub fn check(&self, method: &str) -> Result<(), RpcError> {
+        if self.supports(method) {
+            Ok(())
+        } else {
+            Err(RpcError::Unsupported {
+                method: method.to_string(),
+                cluster: self.kind,
+            })
+        }
+    }
+}
+
+/// Whether an endpoint URL points at this machine
+fn is_local_endpoint(endpoint: &str) -> bool {
+    let rest = endpoint
+        .split_once("://")
+        .map_or(endpoint, |(_, rest)| rest);
+    let authority = rest.split('/').next().unwrap_or_default();
+    let host = match authority.rsplit_once(':') {
+        Some((host, port)) if !host.is_empty() && port.bytes().all(|b| b.is_ascii_digit()) => host,
+        _ => authority,
+    };
+    matches!(host, "localhost" | "127.0.0.1" | "[::1]" | "0.0.0.0")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::network::MAINNET_GENESIS_HASH;
+    use serde_json::json;
+
+    fn version(value: Value) -> RpcVersion {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_detect_cluster() {
+        let mainnet_genesis = Hash::from_base58(MAINNET_GENESIS_HASH).unwrap();
+        let core = version(json!({ "solana-core": "2.1.0", "feature-set": 1416569292 }));
+
+        let mainnet = ClusterCapabilities::detect(
+            mainnet_genesis,
+            core.clone(),
+            "https://rpc.example.com/key",
+        );
+        assert_eq!(mainnet.kind, ClusterKind::Mainnet);
+        assert!(matches!(
+            mainnet.check("requestAirdrop"),
+            Err(RpcError::Unsupported {
+                cluster: ClusterKind::Mainnet,
+                ..
+            })
+        ));
+        assert!(mainnet.check("getBalance").is_ok());
+
+        let surfpool_version = version(json!({
+            "solana-core": "2.1.0",
+            "feature-set": 1416569292,
+            "surfnet-version": "0.9.0"
+        }));
+        let surfpool =
+            ClusterCapabilities::detect(mainnet_genesis, surfpool_version, "http://127.0.0.1:8899");
+        assert_eq!(surfpool.kind, ClusterKind::Surfpool);
+        assert!(surfpool.supports("requestAirdrop"));
+        assert!(surfpool.check("surfnet_setAccount").is_ok());
+        assert!(mainnet.check("surfnet_setAccount").is_err());
+
+        let local =
+            ClusterCapabilities::detect(Hash::new([7; 32]), core.clone(), "http://localhost:8899");
+        assert_eq!(local.kind, ClusterKind::Local);
+        let private = ClusterCapabilities::detect(Hash::new([7; 32]), core, "https://rpc.corp");
+        assert_eq!(private.kind, ClusterKind::Unknown);
+        assert!(private.supports_airdrop());
+    }
+}
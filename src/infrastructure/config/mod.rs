@@ -0,0 +1,237 @@
+//! Client configuration files
+//!
+//! A [`ClientConfig`] holds everything needed to set up a client: the
+//! cluster or endpoints, commitment, API key and other headers, rate limit
+//! and cache lifetime. It is read from JSON, or from TOML with the
+//! `config-toml` feature, so CLI tools, tests and web apps can share one
+//! file; browser builds embed it with `include_str!` and pass it to
+//! [`RpcClientBuilder::from_config`](crate::infrastructure::rpc::RpcClientBuilder::from_config).
+//!
+//! ```toml
+//! network = "devnet"
+//! commitment = "confirmed"
+//! api_key = "..."
+//! rate_limit = 10
+//! cache_ttl_ms = 2000
+//! ```
+
+use crate::infrastructure::network::{CustomNetwork, Network, SURFPOOL_HTTP_ENDPOINT};
+use crate::infrastructure::rpc::CommitmentLevel;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+use thiserror::Error;
+
+/// Header carrying [`ClientConfig::api_key`] unless `api_key_header` is set
+pub const DEFAULT_API_KEY_HEADER: &str = "x-api-key";
+
+/// Errors that can occur while loading a configuration
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ConfigError {
+    #[error("Invalid JSON configuration: {0}")]
+    Json(String),
+
+    #[error("Invalid TOML configuration: {0}")]
+    Toml(String),
+
+    #[error("Cannot read configuration: {0}")]
+    Io(String),
+
+    #[error("Unsupported configuration format: {0}")]
+    UnsupportedFormat(String),
+
+    #[error("Configuration sets neither a network nor an endpoint")]
+    MissingEndpoint,
+
+    #[error("Configuration sets both cache_ttl_ms and cache_slots")]
+    ConflictingCache,
+}
+
+/// A named cluster
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClusterName {
+    Mainnet,
+    Testnet,
+    Devnet,
+    /// A local surfpool or test validator
+    Surfpool,
+}
+
+/// Settings for building a client, as stored in a configuration file
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClientConfig {
+    /// Cluster providing the default endpoints
+    pub network: Option<ClusterName>,
+    /// HTTP endpoint, overriding the network's
+    pub endpoint: Option<String>,
+    /// Websocket endpoint, derived from `endpoint` when absent
+    pub ws_endpoint: Option<String>,
+    pub commitment: Option<CommitmentLevel>,
+    /// Provider API key, sent in `api_key_header`
+    pub api_key: Option<String>,
+    pub api_key_header: Option<String>,
+    /// Extra headers sent with every request
+    pub headers: BTreeMap<String, String>,
+    pub user_agent: Option<String>,
+    /// Most requests per second
+    pub rate_limit: Option<u32>,
+    /// Cache read responses for this many milliseconds
+    pub cache_ttl_ms: Option<u64>,
+    /// Cache read responses until the cluster moves this many slots
+    pub cache_slots: Option<u64>,
+}
+
+impl ClientConfig {
+    /// Parse a JSON configuration
+    pub fn from_json_str(text: &str) -> Result<Self, ConfigError> {
+        serde_json::from_str(text).map_err(|e| ConfigError::Json(e.to_string()))
+    }
+
+    /// Parse a TOML configuration
+    #[cfg(feature = "config-toml")]
+    pub fn from_toml_str(text: &str) -> Result<Self, ConfigError> {
+        toml::from_str(text).map_err(|e| ConfigError::Toml(e.to_string()))
+    }
+
+    /// Read a `.json` or `.toml` configuration file
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| ConfigError::Io(format!("{}: {}", path.display(), e)))?;
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json") => Self::from_json_str(&text),
+            #[cfg(feature = "config-toml")]
+            Some("toml") => Self::from_toml_str(&text),
+            _ => Err(ConfigError::UnsupportedFormat(path.display().to_string())),
+        }
+    }
+
+    /// The network the endpoints point at
+    pub fn resolve_network(&self) -> Result<Network, ConfigError> {
+        let named = self.network.map(|name| match name {
+            ClusterName::Mainnet => Network::Mainnet,
+            ClusterName::Testnet => Network::Testnet,
+            ClusterName::Devnet => Network::Devnet,
+            ClusterName::Surfpool => Network::custom(SURFPOOL_HTTP_ENDPOINT),
+        });
+        if self.endpoint.is_none() && self.ws_endpoint.is_none() {
+            return named.ok_or(ConfigError::MissingEndpoint);
+        }
+
+        let base = named.as_ref();
+        let http = match (&self.endpoint, base) {
+            (Some(endpoint), _) => endpoint.clone(),
+            (None, Some(network)) => network.endpoint().to_string(),
+            (None, None) => return Err(ConfigError::MissingEndpoint),
+        };
+        let mut custom = CustomNetwork::builder(http);
+        if let Some(ws) = &self.ws_endpoint {
+            custom = custom.ws_endpoint(ws.clone());
+        }
+        if let Some(genesis_hash) = base.and_then(Network::genesis_hash) {
+            custom = custom.genesis_hash(genesis_hash);
+        }
+        Ok(custom.build_network())
+    }
+
+    /// Headers to send, including the API key
+    pub fn all_headers(&self) -> Vec<(String, String)> {
+        let mut headers: Vec<_> = self
+            .headers
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        if let Some(key) = &self.api_key {
+            let name = self
+                .api_key_header
+                .as_deref()
+                .unwrap_or(DEFAULT_API_KEY_HEADER);
+            headers.push((name.to_string(), key.clone()));
+        }
+        headers
+    }
+
+    /// Wall-clock cache lifetime
+    pub fn cache_ttl(&self) -> Option<Duration> {
+        self.cache_ttl_ms.map(Duration::from_millis)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::rpc::RpcClientBuilder;
+
+    #[test]
+    fn test_json_config() {
+        let config = ClientConfig::from_json_str(
+            r#"{
+                "network": "mainnet",
+                "endpoint": "https://rpc.example.com",
+                "commitment": "finalized",
+                "api_key": "secret",
+                "headers": { "x-team": "wallet" },
+                "cache_ttl_ms": 1500
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(config.commitment, Some(CommitmentLevel::Finalized));
+        assert_eq!(config.cache_ttl(), Some(Duration::from_millis(1500)));
+        assert_eq!(
+            config.all_headers(),
+            [
+                ("x-team".to_string(), "wallet".to_string()),
+                ("x-api-key".to_string(), "secret".to_string())
+            ]
+        );
+
+        let network = config.resolve_network().unwrap();
+        assert_eq!(network.endpoint(), "https://rpc.example.com");
+        assert_eq!(network.ws_endpoint(), "wss://rpc.example.com");
+        assert_eq!(network.genesis_hash(), Network::Mainnet.genesis_hash());
+        let client = RpcClientBuilder::from_config(&config)
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(client.endpoint(), "https://rpc.example.com");
+
+        let conflicting = ClientConfig {
+            cache_slots: Some(2),
+            ..config
+        };
+        assert!(matches!(
+            RpcClientBuilder::from_config(&conflicting),
+            Err(ConfigError::ConflictingCache)
+        ));
+
+        assert_eq!(
+            ClientConfig::default().resolve_network(),
+            Err(ConfigError::MissingEndpoint)
+        );
+        assert!(matches!(
+            ClientConfig::from_json_str(r#"{ "netwrok": "devnet" }"#),
+            Err(ConfigError::Json(_))
+        ));
+    }
+
+    #[cfg(feature = "config-toml")]
+    #[test]
+    fn test_toml_config() {
+        let config = ClientConfig::from_toml_str(
+            r#"
+            network = "surfpool"
+            rate_limit = 20
+            cache_slots = 4
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.rate_limit, Some(20));
+        assert_eq!(
+            config.resolve_network().unwrap().endpoint(),
+            SURFPOOL_HTTP_ENDPOINT
+        );
+    }
+}
@@ -0,0 +1,161 @@
+//! Block explorer links and short display forms
+//!
+//! [`Explorer`] builds transaction, account and block URLs for the Solana
+//! Explorer, Solscan and Helius XRAY on a given [`Network`]. Explorers that
+//! cannot show a cluster, such as XRAY for testnet or anything for a local
+//! validator other than the Solana Explorer, give `None`. [`abbreviate_pubkey`]
+//! and [`abbreviate_signature`] shorten base58 strings for UIs.
+
+use crate::domain::types::{Pubkey, Signature};
+use crate::infrastructure::network::Network;
+use std::fmt;
+
+/// Characters kept at each end by [`abbreviate_pubkey`] and [`abbreviate_signature`]
+pub const ABBREVIATION_EDGE: usize = 4;
+
+/// A block explorer
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Explorer {
+    /// explorer.solana.com, which also opens custom clusters
+    #[default]
+    Solana,
+    /// solscan.io
+    Solscan,
+    /// xray.helius.xyz, mainnet and devnet only
+    Xray,
+}
+
+impl Explorer {
+    /// Every supported explorer
+    pub const ALL: [Explorer; 3] = [Explorer::Solana, Explorer::Solscan, Explorer::Xray];
+
+    /// Link to a transaction on `network`
+    pub fn transaction_url(&self, network: &Network, signature: &Signature) -> Option<String> {
+        self.url(network, "tx", &signature.to_string())
+    }
+
+    /// Link to an account on `network`
+    pub fn account_url(&self, network: &Network, pubkey: &Pubkey) -> Option<String> {
+        let path = match self {
+            Explorer::Solana => "address",
+            Explorer::Solscan | Explorer::Xray => "account",
+        };
+        self.url(network, path, &pubkey.to_string())
+    }
+
+    /// Link to the block at `slot` on `network`
+    pub fn block_url(&self, network: &Network, slot: u64) -> Option<String> {
+        self.url(network, "block", &slot.to_string())
+    }
+
+    fn url(&self, network: &Network, path: &str, id: &str) -> Option<String> {
+        let (base, query) = match self {
+            Explorer::Solana => (
+                "https://explorer.solana.com",
+                network.explorer_cluster_query(),
+            ),
+            Explorer::Solscan => (
+                "https://solscan.io",
+                match network {
+                    Network::Mainnet => String::new(),
+                    Network::Devnet => "?cluster=devnet".to_string(),
+                    Network::Testnet => "?cluster=testnet".to_string(),
+                    Network::Custom(_) => return None,
+                },
+            ),
+            Explorer::Xray => (
+                "https://xray.helius.xyz",
+                match network {
+                    Network::Mainnet => String::new(),
+                    Network::Devnet => "?network=devnet".to_string(),
+                    Network::Testnet | Network::Custom(_) => return None,
+                },
+            ),
+        };
+        Some(format!("{}/{}/{}{}", base, path, id, query))
+    }
+}
+
+impl fmt::Display for Explorer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Explorer::Solana => write!(f, "Solana Explorer"),
+            Explorer::Solscan => write!(f, "Solscan"),
+            Explorer::Xray => write!(f, "XRAY"),
+        }
+    }
+}
+
+/// Shorten `text` to its first and last `edge` characters around an ellipsis
+///
+/// Text that would not get shorter is returned unchanged.
+pub fn abbreviate(text: &str, edge: usize) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    if chars.len() <= edge * 2 + 1 {
+        return text.to_string();
+    }
+    let head: String = chars[..edge].iter().collect();
+    let tail: String = chars[chars.len() - edge..].iter().collect();
+    format!("{}…{}", head, tail)
+}
+
+/// Short form of a pubkey for display, such as `7xKX…gAsU`
+pub fn abbreviate_pubkey(pubkey: &Pubkey) -> String {
+    abbreviate(&pubkey.to_base58(), ABBREVIATION_EDGE)
+}
+
+/// Short form of a signature for display
+pub fn abbreviate_signature(signature: &Signature) -> String {
+    abbreviate(&signature.to_base58(), ABBREVIATION_EDGE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::types::constants::TOKEN_PROGRAM_ID;
+    use crate::infrastructure::network::surfpool_network;
+
+    #[test]
+    fn test_explorer_urls() {
+        let token = TOKEN_PROGRAM_ID;
+        assert_eq!(
+            Explorer::Solana.account_url(&Network::Devnet, &token).unwrap(),
+            "https://explorer.solana.com/address/TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA?cluster=devnet"
+        );
+        assert_eq!(
+            Explorer::Solscan
+                .account_url(&Network::Mainnet, &token)
+                .unwrap(),
+            "https://solscan.io/account/TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
+        );
+        assert_eq!(
+            Explorer::Xray.block_url(&Network::Devnet, 42).unwrap(),
+            "https://xray.helius.xyz/block/42?network=devnet"
+        );
+
+        let signature = Signature::new([1; 64]);
+        assert_eq!(
+            Explorer::Solana.transaction_url(&Network::Mainnet, &signature),
+            Some(Network::Mainnet.explorer_transaction_url(&signature))
+        );
+        assert!(Explorer::Solana
+            .transaction_url(&surfpool_network(), &signature)
+            .unwrap()
+            .contains("cluster=custom&customUrl=http%3A%2F%2F127.0.0.1%3A8899"));
+        assert_eq!(Explorer::Solscan.block_url(&surfpool_network(), 1), None);
+        assert_eq!(Explorer::Xray.block_url(&Network::Testnet, 1), None);
+    }
+
+    #[test]
+    fn test_abbreviate() {
+        assert_eq!(abbreviate_pubkey(&TOKEN_PROGRAM_ID), "Toke…Q5DA");
+        assert_eq!(abbreviate("123456789", 4), "123456789");
+        assert_eq!(abbreviate("1234567890", 4), "1234…7890");
+        assert_eq!(
+            abbreviate_signature(&Signature::new([0; 64]))
+                .chars()
+                .count(),
+            9
+        );
+    }
+}
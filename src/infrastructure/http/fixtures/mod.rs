@@ -0,0 +1,327 @@
+//! Recorded HTTP exchanges for deterministic tests and offline demos
+//!
+//! [`RecordingHttpClient`] wraps a real client and appends every successful
+//! exchange to a [`Recording`], which saves as a JSON file of [`Fixture`]s.
+//! [`ReplayHttpClient`] answers from such a file without touching the network.
+//! POST requests are matched on their JSON body and GET requests on their URL;
+//! identical requests get their recorded responses in order, the last one
+//! repeating once they run out, so polling loops replay as well.
+
+use super::{HttpClient, HttpError};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+
+/// Errors that can occur while saving or loading fixtures
+#[derive(Debug, Clone, Error)]
+pub enum FixtureError {
+    #[error("Fixture file error: {0}")]
+    Io(String),
+
+    #[error("Invalid fixtures: {0}")]
+    Parse(String),
+}
+
+/// HTTP method of a recorded exchange
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum FixtureMethod {
+    Get,
+    Post,
+}
+
+/// One recorded request and its response
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Fixture {
+    pub method: FixtureMethod,
+    pub url: String,
+    /// JSON body of a POST
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request: Option<Value>,
+    pub response: Value,
+}
+
+impl Fixture {
+    fn matches(&self, method: FixtureMethod, url: &str, request: Option<&Value>) -> bool {
+        self.method == method
+            && match method {
+                FixtureMethod::Get => self.url == url,
+                FixtureMethod::Post => self.request.as_ref() == request,
+            }
+    }
+}
+
+/// Fixtures recorded so far, shared by every clone
+#[derive(Debug, Clone, Default)]
+pub struct Recording {
+    fixtures: Arc<Mutex<Vec<Fixture>>>,
+}
+
+impl Recording {
+    /// Start an empty recording
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The recorded fixtures, in request order
+    pub fn fixtures(&self) -> Vec<Fixture> {
+        self.lock().clone()
+    }
+
+    /// The fixtures as pretty-printed JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&*self.lock()).unwrap_or_default()
+    }
+
+    /// Write the fixtures to a JSON file, replacing it
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), FixtureError> {
+        std::fs::write(path, self.to_json()).map_err(|e| FixtureError::Io(e.to_string()))
+    }
+
+    fn push(&self, fixture: Fixture) {
+        self.lock().push(fixture);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Fixture>> {
+        self.fixtures.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// HTTP client recording every successful exchange of `inner`
+///
+/// Failed requests are passed through without being recorded.
+#[derive(Debug, Clone)]
+pub struct RecordingHttpClient<H> {
+    inner: H,
+    recording: Recording,
+}
+
+impl<H: HttpClient> RecordingHttpClient<H> {
+    /// Record the exchanges of `inner` into `recording`
+    pub fn new(inner: H, recording: Recording) -> Self {
+        Self { inner, recording }
+    }
+
+    /// The wrapped client
+    pub fn inner(&self) -> &H {
+        &self.inner
+    }
+
+    /// The recording being written
+    pub fn recording(&self) -> &Recording {
+        &self.recording
+    }
+}
+
+impl<H: HttpClient> HttpClient for RecordingHttpClient<H> {
+    async fn post_json<'a, Req, Resp>(
+        &'a self,
+        url: &'a str,
+        body: &'a Req,
+    ) -> Result<Resp, HttpError>
+    where
+        Req: Serialize + Send + Sync,
+        Resp: for<'de> Deserialize<'de> + 'static,
+    {
+        let text = self.post_json_text(url, body).await?;
+        serde_json::from_str(&text).map_err(|e| HttpError::DeserializationError(e.to_string()))
+    }
+
+    async fn post_json_text<'a, Req>(
+        &'a self,
+        url: &'a str,
+        body: &'a Req,
+    ) -> Result<String, HttpError>
+    where
+        Req: Serialize + Send + Sync,
+    {
+        let request =
+            serde_json::to_value(body).map_err(|e| HttpError::SerializationError(e.to_string()))?;
+        let text = self.inner.post_json_text(url, body).await?;
+        let response = serde_json::from_str(&text)
+            .map_err(|e| HttpError::DeserializationError(e.to_string()))?;
+        self.recording.push(Fixture {
+            method: FixtureMethod::Post,
+            url: url.to_string(),
+            request: Some(request),
+            response,
+        });
+        Ok(text)
+    }
+
+    async fn get<'a, Resp>(&'a self, url: &'a str) -> Result<Resp, HttpError>
+    where
+        Resp: for<'de> Deserialize<'de> + 'static,
+    {
+        let response: Value = self.inner.get(url).await?;
+        self.recording.push(Fixture {
+            method: FixtureMethod::Get,
+            url: url.to_string(),
+            request: None,
+            response: response.clone(),
+        });
+        serde_json::from_value(response).map_err(|e| HttpError::DeserializationError(e.to_string()))
+    }
+}
+
+/// HTTP client answering from recorded fixtures
+///
+/// Requests without a recorded response fail with
+/// [`HttpError::FixtureMissing`]. Clones share their replay position.
+#[derive(Debug, Clone)]
+pub struct ReplayHttpClient {
+    fixtures: Arc<Vec<Fixture>>,
+    /// Times each fixture has been served
+    served: Arc<Mutex<Vec<usize>>>,
+}
+
+impl ReplayHttpClient {
+    /// Replay `fixtures`
+    pub fn new(fixtures: Vec<Fixture>) -> Self {
+        Self {
+            served: Arc::new(Mutex::new(vec![0; fixtures.len()])),
+            fixtures: Arc::new(fixtures),
+        }
+    }
+
+    /// Replay fixtures from JSON written by [`Recording::to_json`]
+    pub fn from_json(json: &str) -> Result<Self, FixtureError> {
+        serde_json::from_str(json)
+            .map(Self::new)
+            .map_err(|e| FixtureError::Parse(e.to_string()))
+    }
+
+    /// Replay a fixture file written by [`Recording::save`]
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, FixtureError> {
+        let json = std::fs::read_to_string(path).map_err(|e| FixtureError::Io(e.to_string()))?;
+        Self::from_json(&json)
+    }
+
+    /// Fixtures that have not been served yet
+    pub fn unused(&self) -> Vec<&Fixture> {
+        let served = self.served.lock().unwrap_or_else(|e| e.into_inner());
+        self.fixtures
+            .iter()
+            .zip(served.iter())
+            .filter(|(_, count)| **count == 0)
+            .map(|(fixture, _)| fixture)
+            .collect()
+    }
+
+    /// The next recorded response to a request
+    fn respond(
+        &self,
+        method: FixtureMethod,
+        url: &str,
+        request: Option<&Value>,
+    ) -> Result<Value, HttpError> {
+        let mut served = self.served.lock().unwrap_or_else(|e| e.into_inner());
+        let candidates: Vec<usize> = (0..self.fixtures.len())
+            .filter(|&index| self.fixtures[index].matches(method, url, request))
+            .collect();
+        let index = candidates
+            .iter()
+            .copied()
+            .find(|&index| served[index] == 0)
+            .or_else(|| candidates.last().copied())
+            .ok_or_else(|| {
+                HttpError::FixtureMissing(match request {
+                    Some(request) => request.to_string(),
+                    None => url.to_string(),
+                })
+            })?;
+        served[index] += 1;
+        Ok(self.fixtures[index].response.clone())
+    }
+}
+
+impl HttpClient for ReplayHttpClient {
+    async fn post_json<'a, Req, Resp>(
+        &'a self,
+        url: &'a str,
+        body: &'a Req,
+    ) -> Result<Resp, HttpError>
+    where
+        Req: Serialize + Send + Sync,
+        Resp: for<'de> Deserialize<'de> + 'static,
+    {
+        let text = self.post_json_text(url, body).await?;
+        serde_json::from_str(&text).map_err(|e| HttpError::DeserializationError(e.to_string()))
+    }
+
+    async fn post_json_text<'a, Req>(
+        &'a self,
+        url: &'a str,
+        body: &'a Req,
+    ) -> Result<String, HttpError>
+    where
+        Req: Serialize + Send + Sync,
+    {
+        let request =
+            serde_json::to_value(body).map_err(|e| HttpError::SerializationError(e.to_string()))?;
+        self.respond(FixtureMethod::Post, url, Some(&request))
+            .map(|response| response.to_string())
+    }
+
+    async fn get<'a, Resp>(&'a self, url: &'a str) -> Result<Resp, HttpError>
+    where
+        Resp: for<'de> Deserialize<'de> + 'static,
+    {
+        let response = self.respond(FixtureMethod::Get, url, None)?;
+        serde_json::from_value(response).map_err(|e| HttpError::DeserializationError(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_record_and_replay() {
+        let upstream = ReplayHttpClient::new(vec![
+            Fixture {
+                method: FixtureMethod::Post,
+                url: "http://upstream".to_string(),
+                request: Some(json!({ "method": "getSlot" })),
+                response: json!({ "result": 1 }),
+            },
+            Fixture {
+                method: FixtureMethod::Post,
+                url: "http://upstream".to_string(),
+                request: Some(json!({ "method": "getSlot" })),
+                response: json!({ "result": 2 }),
+            },
+        ]);
+        let recording = Recording::new();
+        let client = RecordingHttpClient::new(upstream, recording.clone());
+        let request = json!({ "method": "getSlot" });
+        let first: Value = client.post_json("http://local", &request).await.unwrap();
+        assert_eq!(first["result"], 1);
+        client
+            .post_json_text("http://local", &request)
+            .await
+            .unwrap();
+        assert!(client.inner().unused().is_empty());
+        assert!(client
+            .post_json_text("http://local", &json!({ "method": "getEpochInfo" }))
+            .await
+            .is_err());
+        assert_eq!(recording.fixtures().len(), 2);
+        assert_eq!(recording.fixtures()[0].url, "http://local");
+
+        // Recorded order is kept, then the last response repeats
+        let replay = ReplayHttpClient::from_json(&recording.to_json()).unwrap();
+        for expected in [1, 2, 2] {
+            let response: Value = replay.post_json("http://other", &request).await.unwrap();
+            assert_eq!(response["result"], expected);
+        }
+        let missing = replay.get::<Value>("http://other/metadata").await;
+        assert!(matches!(missing, Err(HttpError::FixtureMissing(_))));
+    }
+}
@@ -0,0 +1,194 @@
+//! Size-limited and streaming response bodies
+//!
+//! A `getProgramAccounts` scan of a large program can return hundreds of
+//! megabytes. [`read_limited`] stops downloading once a body passes a size
+//! limit, and [`parse_streaming`] feeds the body to `serde_json::from_reader`
+//! on a blocking thread as it arrives, so the raw text is never held in memory
+//! next to the parsed value.
+
+use super::HttpError;
+use serde_json::Value;
+use std::io::{self, BufReader, Read};
+use tokio::sync::mpsc;
+
+/// Chunks buffered between the download and the parser
+const CHUNK_BUFFER: usize = 16;
+
+/// Read a response body, failing once it exceeds `max_size` bytes
+pub async fn read_limited(
+    mut response: reqwest::Response,
+    max_size: usize,
+) -> Result<Vec<u8>, HttpError> {
+    check_content_length(&response, max_size)?;
+    let mut body = Vec::new();
+    while let Some(chunk) = next_chunk(&mut response).await? {
+        if body.len() + chunk.len() > max_size {
+            return Err(HttpError::ResponseTooLarge { limit: max_size });
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body)
+}
+
+/// Parse a JSON response body while it downloads
+///
+/// With `max_size`, the download is abandoned once the body exceeds it.
+pub async fn parse_streaming(
+    mut response: reqwest::Response,
+    max_size: Option<usize>,
+) -> Result<Value, HttpError> {
+    if let Some(max_size) = max_size {
+        check_content_length(&response, max_size)?;
+    }
+
+    let (sender, receiver) = mpsc::channel(CHUNK_BUFFER);
+    let parser = tokio::task::spawn_blocking(move || {
+        serde_json::from_reader::<_, Value>(BufReader::new(ChunkReader::new(receiver)))
+    });
+
+    let mut received = 0;
+    let mut download = Ok(());
+    loop {
+        let chunk = match next_chunk(&mut response).await {
+            Ok(Some(chunk)) => chunk,
+            Ok(None) => break,
+            Err(error) => {
+                download = Err(error);
+                break;
+            }
+        };
+        received += chunk.len();
+        if let Some(limit) = max_size.filter(|limit| received > *limit) {
+            download = Err(HttpError::ResponseTooLarge { limit });
+            break;
+        }
+        // The parser hung up early on invalid JSON and reports why below
+        if sender.send(chunk).await.is_err() {
+            break;
+        }
+    }
+    // Closing the channel ends the body, stopping the parser if it was cut short
+    drop(sender);
+
+    let parsed = parser
+        .await
+        .map_err(|e| HttpError::ResponseError(e.to_string()))?;
+    download?;
+    parsed.map_err(|e| HttpError::DeserializationError(e.to_string()))
+}
+
+fn check_content_length(response: &reqwest::Response, max_size: usize) -> Result<(), HttpError> {
+    match response.content_length() {
+        Some(length) if length > max_size as u64 => {
+            Err(HttpError::ResponseTooLarge { limit: max_size })
+        }
+        _ => Ok(()),
+    }
+}
+
+async fn next_chunk(response: &mut reqwest::Response) -> Result<Option<Vec<u8>>, HttpError> {
+    response
+        .chunk()
+        .await
+        .map(|chunk| chunk.map(|chunk| chunk.to_vec()))
+        .map_err(|e| HttpError::ResponseError(e.to_string()))
+}
+
+/// Blocking [`Read`] over chunks sent from the async download
+struct ChunkReader {
+    receiver: mpsc::Receiver<Vec<u8>>,
+    chunk: Vec<u8>,
+    position: usize,
+}
+
+impl ChunkReader {
+    fn new(receiver: mpsc::Receiver<Vec<u8>>) -> Self {
+        Self {
+            receiver,
+            chunk: Vec::new(),
+            position: 0,
+        }
+    }
+}
+
+impl Read for ChunkReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.chunk.len() {
+            match self.receiver.blocking_recv() {
+                Some(chunk) => {
+                    self.chunk = chunk;
+                    self.position = 0;
+                }
+                None => return Ok(0),
+            }
+        }
+        let available = &self.chunk[self.position..];
+        let len = available.len().min(buf.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        self.position += len;
+        Ok(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Answer one request with `body`, chunked so its length is not announced
+    async fn serve_chunked(body: String) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = [0u8; 4096];
+            let _ = socket.read(&mut request).await;
+            let mut response =
+                b"HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ntransfer-encoding: chunked\r\n\r\n"
+                    .to_vec();
+            for piece in body.as_bytes().chunks(1000) {
+                response.extend(format!("{:x}\r\n", piece.len()).as_bytes());
+                response.extend(piece);
+                response.extend(b"\r\n");
+            }
+            response.extend(b"0\r\n\r\n");
+            let _ = socket.write_all(&response).await;
+        });
+        format!("http://127.0.0.1:{}/", port)
+    }
+
+    async fn fetch(url: &str) -> reqwest::Response {
+        reqwest::Client::new().post(url).send().await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_streaming_parse_and_size_limit() {
+        let body = json!({ "result": vec!["account"; 2000] }).to_string();
+
+        let url = serve_chunked(body.clone()).await;
+        let value = parse_streaming(fetch(&url).await, Some(body.len()))
+            .await
+            .unwrap();
+        assert_eq!(value["result"].as_array().unwrap().len(), 2000);
+
+        let url = serve_chunked(body.clone()).await;
+        assert!(matches!(
+            parse_streaming(fetch(&url).await, Some(body.len() - 1)).await,
+            Err(HttpError::ResponseTooLarge { limit }) if limit == body.len() - 1
+        ));
+
+        let url = serve_chunked(body.clone()).await;
+        assert!(matches!(
+            read_limited(fetch(&url).await, 4096).await,
+            Err(HttpError::ResponseTooLarge { limit: 4096 })
+        ));
+
+        let url = serve_chunked("{\"result\":[1,".to_string()).await;
+        assert!(matches!(
+            parse_streaming(fetch(&url).await, None).await,
+            Err(HttpError::DeserializationError(_))
+        ));
+    }
+}
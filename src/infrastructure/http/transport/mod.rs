@@ -0,0 +1,345 @@
+//! Native connection pool tuning
+//!
+//! [`TransportConfig`] exposes the reqwest settings that matter for high
+//! request volumes: pool size, HTTP/2 prior knowledge and TCP keepalive, plus
+//! the [`ProxyConfig`] requests go through. Every
+//! [`NativeHttpClient`](super::NativeHttpClient) counts its requests and the
+//! connections it opens, so [`TransportStats`] shows how well connections are
+//! being reused.
+
+use super::HttpError;
+use hyper::client::connect::dns::Name;
+use reqwest::dns::{Addrs, Resolve, Resolving};
+use reqwest::{ClientBuilder, Response, Version};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+pub mod proxy;
+
+pub use proxy::ProxyConfig;
+
+/// Settings of the native HTTP connection pool
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TransportConfig {
+    /// Idle connections kept open per host
+    pub pool_max_idle_per_host: Option<usize>,
+    /// How long an idle connection is kept open
+    pub pool_idle_timeout: Option<Duration>,
+    /// Speak HTTP/2 without negotiating it, multiplexing every request over
+    /// one connection
+    pub http2_prior_knowledge: bool,
+    /// Interval of HTTP/2 pings keeping connections alive
+    pub http2_keep_alive_interval: Option<Duration>,
+    pub tcp_keepalive: Option<Duration>,
+    pub connect_timeout: Option<Duration>,
+    /// Timeout of a whole request
+    pub timeout: Option<Duration>,
+    /// Proxy for every request
+    ///
+    /// Without one, reqwest still honours the proxy environment variables.
+    pub proxy: Option<ProxyConfig>,
+}
+
+impl TransportConfig {
+    /// Create a configuration with reqwest's defaults
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set how many idle connections are kept per host
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.pool_max_idle_per_host = Some(max);
+        self
+    }
+
+    /// Set how long idle connections are kept
+    pub fn pool_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.pool_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Use HTTP/2 from the first byte, for endpoints known to support it
+    pub fn http2_prior_knowledge(mut self) -> Self {
+        self.http2_prior_knowledge = true;
+        self
+    }
+
+    /// Ping HTTP/2 connections every `interval`
+    pub fn http2_keep_alive_interval(mut self, interval: Duration) -> Self {
+        self.http2_keep_alive_interval = Some(interval);
+        self
+    }
+
+    /// Enable TCP keepalive probes every `interval`
+    pub fn tcp_keepalive(mut self, interval: Duration) -> Self {
+        self.tcp_keepalive = Some(interval);
+        self
+    }
+
+    /// Set the connection timeout
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Set the request timeout
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Send every request through `proxy`
+    pub fn proxy(mut self, proxy: ProxyConfig) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Use the proxy named by the environment, if any
+    ///
+    /// See [`ProxyConfig::from_env`].
+    pub fn proxy_from_env(mut self) -> Self {
+        self.proxy = ProxyConfig::from_env().or(self.proxy);
+        self
+    }
+
+    /// Apply the settings to a reqwest builder
+    pub(super) fn apply(&self, mut builder: ClientBuilder) -> Result<ClientBuilder, HttpError> {
+        if let Some(max) = self.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+        if let Some(timeout) = self.pool_idle_timeout {
+            builder = builder.pool_idle_timeout(timeout);
+        }
+        if self.http2_prior_knowledge {
+            builder = builder.http2_prior_knowledge();
+        }
+        if let Some(interval) = self.http2_keep_alive_interval {
+            builder = builder
+                .http2_keep_alive_interval(interval)
+                .http2_keep_alive_while_idle(true);
+        }
+        if let Some(interval) = self.tcp_keepalive {
+            builder = builder.tcp_keepalive(interval);
+        }
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(proxy) = &self.proxy {
+            builder = builder.proxy(proxy.to_reqwest()?);
+        }
+        Ok(builder)
+    }
+}
+
+/// Request and connection counts of a native HTTP client
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TransportStats {
+    /// Requests that received a response
+    pub requests: u64,
+    /// Responses received over HTTP/2
+    pub http2_responses: u64,
+    /// Connections opened to a host name
+    ///
+    /// Counted at DNS resolution, which happens once per new connection, so
+    /// endpoints given as IP addresses are not counted.
+    pub connections_opened: u64,
+}
+
+impl TransportStats {
+    /// Requests served over an already open connection
+    pub fn reused_connections(&self) -> u64 {
+        self.requests.saturating_sub(self.connections_opened)
+    }
+
+    /// Fraction of requests served over an already open connection
+    pub fn reuse_ratio(&self) -> f64 {
+        if self.requests == 0 {
+            0.0
+        } else {
+            self.reused_connections() as f64 / self.requests as f64
+        }
+    }
+}
+
+/// Counters shared by a client and its resolver
+#[derive(Debug, Default)]
+pub(super) struct TransportCounters {
+    requests: AtomicU64,
+    http2_responses: AtomicU64,
+    connections_opened: AtomicU64,
+}
+
+impl TransportCounters {
+    /// Install a resolver counting the connections opened by `builder`
+    pub(super) fn instrument(self: &Arc<Self>, builder: ClientBuilder) -> ClientBuilder {
+        builder.dns_resolver(Arc::new(CountingResolver {
+            counters: self.clone(),
+        }))
+    }
+
+    /// Record a received response
+    pub(super) fn record(&self, response: &Response) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        if response.version() == Version::HTTP_2 {
+            self.http2_responses.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub(super) fn snapshot(&self) -> TransportStats {
+        TransportStats {
+            requests: self.requests.load(Ordering::Relaxed),
+            http2_responses: self.http2_responses.load(Ordering::Relaxed),
+            connections_opened: self.connections_opened.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// System resolver that counts lookups
+struct CountingResolver {
+    counters: Arc<TransportCounters>,
+}
+
+impl Resolve for CountingResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        self.counters
+            .connections_opened
+            .fetch_add(1, Ordering::Relaxed);
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            let addrs = tokio::net::lookup_host((host, 0)).await?;
+            Ok(Box::new(addrs) as Addrs)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::http::{HttpClient, NativeHttpClient};
+    use serde_json::{json, Value};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    /// Answer every request on a keep-alive connection with `{"ok":true}`
+    async fn serve(socket: TcpStream) {
+        serve_logged(socket, None).await
+    }
+
+    /// [`serve`], sending each request line to `request_lines`
+    async fn serve_logged(
+        mut socket: TcpStream,
+        request_lines: Option<tokio::sync::mpsc::UnboundedSender<String>>,
+    ) {
+        let mut buffer = Vec::new();
+        let mut chunk = [0u8; 1024];
+        loop {
+            let Some(header_end) = buffer.windows(4).position(|w| w == b"\r\n\r\n") else {
+                match socket.read(&mut chunk).await {
+                    Ok(0) | Err(_) => return,
+                    Ok(n) => buffer.extend_from_slice(&chunk[..n]),
+                }
+                continue;
+            };
+            let headers = String::from_utf8_lossy(&buffer[..header_end]).to_lowercase();
+            if let Some(sender) = &request_lines {
+                let head = String::from_utf8_lossy(&buffer[..header_end]);
+                let _ = sender.send(head.lines().next().unwrap_or_default().to_string());
+            }
+            let content_length: usize = headers
+                .lines()
+                .find_map(|line| line.strip_prefix("content-length:"))
+                .and_then(|value| value.trim().parse().ok())
+                .unwrap_or(0);
+            let request_end = header_end + 4 + content_length;
+            if buffer.len() < request_end {
+                match socket.read(&mut chunk).await {
+                    Ok(0) | Err(_) => return,
+                    Ok(n) => buffer.extend_from_slice(&chunk[..n]),
+                }
+                continue;
+            }
+            buffer.drain(..request_end);
+
+            let body = r#"{"ok":true}"#;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            if socket.write_all(response.as_bytes()).await.is_err() {
+                return;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_counts_connection_reuse() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                tokio::spawn(serve(socket));
+            }
+        });
+
+        let config = TransportConfig::new()
+            .pool_max_idle_per_host(4)
+            .tcp_keepalive(Duration::from_secs(30));
+        let client = NativeHttpClient::with_transport_config(&config).unwrap();
+        let url = format!("http://localhost:{}/", port);
+        for id in 0..3 {
+            let response: Value = client.post_json(&url, &json!({ "id": id })).await.unwrap();
+            assert_eq!(response["ok"], true);
+        }
+
+        let stats = client.transport_stats();
+        assert_eq!(stats.requests, 3);
+        assert_eq!(stats.connections_opened, 1);
+        assert_eq!(stats.http2_responses, 0);
+        assert_eq!(stats.reused_connections(), 2);
+        assert!((stats.reuse_ratio() - 2.0 / 3.0).abs() < 1e-12);
+    }
+
+    #[tokio::test]
+    async fn test_requests_go_through_the_proxy() {
+        let proxy = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let proxy_url = format!("http://{}", proxy.local_addr().unwrap());
+        let (request_lines, mut seen) = tokio::sync::mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            let (socket, _) = proxy.accept().await.unwrap();
+            serve_logged(socket, Some(request_lines)).await;
+        });
+
+        let config = TransportConfig::new().proxy(ProxyConfig::new(&proxy_url).unwrap());
+        let client = NativeHttpClient::with_transport_config(&config).unwrap();
+        let response: Value = client
+            .post_json("http://rpc.gloo-solana.invalid/", &json!({ "id": 1 }))
+            .await
+            .unwrap();
+        assert_eq!(response["ok"], true);
+        assert_eq!(
+            seen.recv().await.unwrap(),
+            "POST http://rpc.gloo-solana.invalid/ HTTP/1.1"
+        );
+
+        // Hosts on the no-proxy list skip the (now closed) proxy
+        let direct = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let direct_url = format!("http://127.0.0.1:{}/", direct.local_addr().unwrap().port());
+        tokio::spawn(async move {
+            let (socket, _) = direct.accept().await.unwrap();
+            serve(socket).await;
+        });
+        let config = TransportConfig::new()
+            .proxy(ProxyConfig::new(proxy_url).unwrap().no_proxy("127.0.0.1"));
+        let client = NativeHttpClient::with_transport_config(&config).unwrap();
+        let response: Value = client
+            .post_json(&direct_url, &json!({ "id": 2 }))
+            .await
+            .unwrap();
+        assert_eq!(response["ok"], true);
+    }
+}
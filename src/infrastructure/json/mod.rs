@@ -0,0 +1,128 @@
+//! Pluggable JSON response parsing
+//!
+//! Large `getProgramAccounts` responses spend most of their time in JSON
+//! decoding. A client parses response bodies with a [`JsonParser`]:
+//! serde_json by default, SIMD-accelerated simd-json with the `simd-json`
+//! feature on native targets, or the browser's native `JSON.parse` with the
+//! `js-json` feature on wasm.
+
+use serde_json::Value;
+use std::sync::Arc;
+use thiserror::Error;
+
+/// A response body that could not be parsed
+#[derive(Debug, Clone, Error)]
+#[error("Invalid JSON: {0}")]
+pub struct JsonError(pub String);
+
+/// Turns a response body into a JSON value
+pub trait JsonParser: Send + Sync {
+    /// Parse a complete response body
+    fn parse(&self, body: String) -> Result<Value, JsonError>;
+}
+
+/// Parser backed by serde_json
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SerdeJsonParser;
+
+impl JsonParser for SerdeJsonParser {
+    fn parse(&self, body: String) -> Result<Value, JsonError> {
+        serde_json::from_str(&body).map_err(|e| JsonError(e.to_string()))
+    }
+}
+
+/// Parser backed by simd-json, which decodes the body in place
+#[cfg(all(feature = "simd-json", not(target_arch = "wasm32")))]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SimdJsonParser;
+
+#[cfg(all(feature = "simd-json", not(target_arch = "wasm32")))]
+impl JsonParser for SimdJsonParser {
+    fn parse(&self, body: String) -> Result<Value, JsonError> {
+        let mut bytes = body.into_bytes();
+        simd_json::serde::from_slice(&mut bytes).map_err(|e| JsonError(e.to_string()))
+    }
+}
+
+/// Parser backed by the browser's `JSON.parse`
+///
+/// JavaScript numbers are doubles, so bodies containing integers above 2^53,
+/// such as a `rentEpoch` of `u64::MAX`, are parsed with serde_json instead.
+#[cfg(all(feature = "js-json", target_arch = "wasm32"))]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsJsonParser;
+
+#[cfg(all(feature = "js-json", target_arch = "wasm32"))]
+impl JsonParser for JsJsonParser {
+    fn parse(&self, body: String) -> Result<Value, JsonError> {
+        if has_unsafe_integers(&body) {
+            return SerdeJsonParser.parse(body);
+        }
+        let value = js_sys::JSON::parse(&body)
+            .map_err(|e| JsonError(e.as_string().unwrap_or_else(|| format!("{:?}", e))))?;
+        serde_wasm_bindgen::from_value(value).map_err(|e| JsonError(e.to_string()))
+    }
+}
+
+/// The fastest parser enabled for the current target
+pub fn default_parser() -> Arc<dyn JsonParser> {
+    #[cfg(all(feature = "simd-json", not(target_arch = "wasm32")))]
+    {
+        Arc::new(SimdJsonParser)
+    }
+    #[cfg(all(feature = "js-json", target_arch = "wasm32"))]
+    {
+        Arc::new(JsJsonParser)
+    }
+    #[cfg(not(any(
+        all(feature = "simd-json", not(target_arch = "wasm32")),
+        all(feature = "js-json", target_arch = "wasm32")
+    )))]
+    {
+        Arc::new(SerdeJsonParser)
+    }
+}
+
+/// Whether `body` may hold an integer a double cannot represent exactly
+///
+/// Checks every run of digits; base58 and base64 strings rarely contain a
+/// long one, and a false positive only costs the faster path.
+#[cfg(any(test, all(feature = "js-json", target_arch = "wasm32")))]
+fn has_unsafe_integers(body: &str) -> bool {
+    const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+    body.split(|c: char| !c.is_ascii_digit()).any(|digits| {
+        digits.len() > 16
+            || (digits.len() == 16 && digits.parse::<u64>().map_or(true, |n| n > MAX_SAFE_INTEGER))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const BODY: &str =
+        r#"{"jsonrpc":"2.0","result":{"lamports":5,"rentEpoch":18446744073709551615},"id":1}"#;
+
+    #[test]
+    fn test_parsers_agree() {
+        let expected = json!({
+            "jsonrpc": "2.0",
+            "result": { "lamports": 5, "rentEpoch": u64::MAX },
+            "id": 1,
+        });
+        assert_eq!(SerdeJsonParser.parse(BODY.to_string()).unwrap(), expected);
+        assert_eq!(default_parser().parse(BODY.to_string()).unwrap(), expected);
+        #[cfg(all(feature = "simd-json", not(target_arch = "wasm32")))]
+        assert_eq!(SimdJsonParser.parse(BODY.to_string()).unwrap(), expected);
+
+        assert!(default_parser().parse("{\"id\":".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_detects_unsafe_integers() {
+        assert!(has_unsafe_integers(BODY));
+        assert!(!has_unsafe_integers(r#"{"slot":9007199254740991}"#));
+        assert!(has_unsafe_integers(r#"{"slot":9007199254740993}"#));
+    }
+}
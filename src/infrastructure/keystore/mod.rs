@@ -0,0 +1,414 @@
+//! Password-encrypted keypair storage
+//!
+//! A [`Keystore`] saves keypairs through a [`StorageAdapter`] (files on
+//! native, IndexedDB in the browser through `IndexedDbStorage`) encrypted with
+//! ChaCha20-Poly1305 under a key derived from a password with Argon2id. Only
+//! the public key is stored in the clear. Unlocked keypairs are held in memory
+//! until locked.
+
+use crate::domain::types::Pubkey;
+use crate::infrastructure::signer::{Keypair, KeypairError, SecretBytes, Signer};
+use crate::infrastructure::storage::{StorageAdapter, StorageError};
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::{engine::general_purpose, Engine as _};
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use thiserror::Error;
+use zeroize::Zeroizing;
+
+/// Storage key prefix for encrypted keypairs
+const STORAGE_PREFIX: &str = "gloo_solana:keystore:";
+
+/// Version of the stored entry format
+const FORMAT_VERSION: u32 = 1;
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+/// Largest Argon2 memory cost accepted, 256 MiB
+const MAX_MEMORY_KIB: u32 = 256 * 1024;
+/// Largest Argon2 pass count accepted
+const MAX_ITERATIONS: u32 = 64;
+/// Largest Argon2 lane count accepted
+const MAX_PARALLELISM: u32 = 16;
+
+/// Errors that can occur in the keystore
+#[derive(Debug, Clone, Error)]
+pub enum KeystoreError {
+    #[error("Storage error: {0}")]
+    Storage(#[from] StorageError),
+
+    #[error("Keypair error: {0}")]
+    Keypair(#[from] KeypairError),
+
+    #[error("No keypair named {0}")]
+    NotFound(String),
+
+    #[error("A keypair named {0} already exists")]
+    AlreadyExists(String),
+
+    #[error("Wrong password or corrupted keystore entry")]
+    WrongPassword,
+
+    #[error("Keypair {0} is locked")]
+    Locked(String),
+
+    #[error("Invalid keystore entry: {0}")]
+    InvalidEntry(String),
+
+    #[error("Key derivation cost exceeds the keystore limits: {0:?}")]
+    KdfTooCostly(KdfParams),
+}
+
+/// Argon2id cost parameters
+///
+/// They are stored with every entry, so raising them only affects keypairs
+/// saved afterwards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KdfParams {
+    /// Memory in KiB
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl KdfParams {
+    /// Check the costs are within the limits the keystore will pay
+    ///
+    /// Parameters are read back from storage, so a tampered entry could
+    /// otherwise demand gigabytes of memory or an endless derivation.
+    pub fn check_limits(&self) -> Result<(), KeystoreError> {
+        if self.memory_kib > MAX_MEMORY_KIB
+            || self.iterations > MAX_ITERATIONS
+            || self.parallelism > MAX_PARALLELISM
+        {
+            return Err(KeystoreError::KdfTooCostly(*self));
+        }
+        Ok(())
+    }
+}
+
+impl Default for KdfParams {
+    /// The OWASP-recommended Argon2id settings
+    fn default() -> Self {
+        Self {
+            memory_kib: Params::DEFAULT_M_COST,
+            iterations: Params::DEFAULT_T_COST,
+            parallelism: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+/// An encrypted keypair as persisted in storage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredKeypair {
+    version: u32,
+    pubkey: Pubkey,
+    kdf: KdfParams,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// Vault of password-encrypted keypairs
+#[derive(Clone)]
+pub struct Keystore {
+    storage: Arc<dyn StorageAdapter>,
+    kdf: KdfParams,
+    unlocked: Arc<RwLock<HashMap<String, Keypair>>>,
+}
+
+impl Keystore {
+    /// Create a keystore persisting to `storage`
+    pub fn new(storage: Arc<dyn StorageAdapter>) -> Self {
+        Self {
+            storage,
+            kdf: KdfParams::default(),
+            unlocked: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Set the key derivation cost used for newly saved keypairs
+    pub fn with_kdf_params(mut self, kdf: KdfParams) -> Self {
+        self.kdf = kdf;
+        self
+    }
+
+    /// Encrypt and save `keypair` under `name`, leaving it unlocked
+    pub fn save(&self, name: &str, keypair: &Keypair, password: &str) -> Result<(), KeystoreError> {
+        if self.storage.get(&storage_key(name))?.is_some() {
+            return Err(KeystoreError::AlreadyExists(name.to_string()));
+        }
+        self.write(name, keypair, password)?;
+        self.unlocked_mut()
+            .insert(name.to_string(), keypair.clone());
+        Ok(())
+    }
+
+    /// Generate, save and unlock a new keypair
+    pub fn generate(&self, name: &str, password: &str) -> Result<Pubkey, KeystoreError> {
+        let keypair = Keypair::new()?;
+        self.save(name, &keypair, password)?;
+        Ok(keypair.pubkey())
+    }
+
+    /// Decrypt the keypair saved under `name` and keep it unlocked
+    pub fn unlock(&self, name: &str, password: &str) -> Result<Keypair, KeystoreError> {
+        let stored = self.read(name)?;
+        let keypair = decrypt(&stored, password)?;
+        self.unlocked_mut()
+            .insert(name.to_string(), keypair.clone());
+        Ok(keypair)
+    }
+
+    /// Forget the decrypted keypair saved under `name`
+    pub fn lock(&self, name: &str) {
+        self.unlocked_mut().remove(name);
+    }
+
+    /// Forget every decrypted keypair
+    pub fn lock_all(&self) {
+        self.unlocked_mut().clear();
+    }
+
+    /// Whether the keypair saved under `name` is unlocked
+    pub fn is_unlocked(&self, name: &str) -> bool {
+        self.unlocked
+            .read()
+            .map(|unlocked| unlocked.contains_key(name))
+            .unwrap_or(false)
+    }
+
+    /// Get an unlocked keypair for signing
+    pub fn keypair(&self, name: &str) -> Result<Keypair, KeystoreError> {
+        self.unlocked
+            .read()
+            .ok()
+            .and_then(|unlocked| unlocked.get(name).cloned())
+            .ok_or_else(|| KeystoreError::Locked(name.to_string()))
+    }
+
+    /// Public key saved under `name`, available while locked
+    pub fn pubkey(&self, name: &str) -> Result<Pubkey, KeystoreError> {
+        Ok(self.read(name)?.pubkey)
+    }
+
+    /// Names of all saved keypairs
+    pub fn names(&self) -> Result<Vec<String>, KeystoreError> {
+        Ok(self
+            .storage
+            .keys(STORAGE_PREFIX)?
+            .into_iter()
+            .filter_map(|key| key.strip_prefix(STORAGE_PREFIX).map(str::to_string))
+            .collect())
+    }
+
+    /// Re-encrypt the keypair saved under `name` with a new password
+    pub fn change_password(
+        &self,
+        name: &str,
+        old_password: &str,
+        new_password: &str,
+    ) -> Result<(), KeystoreError> {
+        let keypair = decrypt(&self.read(name)?, old_password)?;
+        self.write(name, &keypair, new_password)
+    }
+
+    /// Delete the keypair saved under `name`
+    pub fn remove(&self, name: &str) -> Result<(), KeystoreError> {
+        self.lock(name);
+        Ok(self.storage.remove(&storage_key(name))?)
+    }
+
+    fn read(&self, name: &str) -> Result<StoredKeypair, KeystoreError> {
+        let raw = self
+            .storage
+            .get(&storage_key(name))?
+            .ok_or_else(|| KeystoreError::NotFound(name.to_string()))?;
+        let stored: StoredKeypair =
+            serde_json::from_str(&raw).map_err(|e| KeystoreError::InvalidEntry(e.to_string()))?;
+        if stored.version != FORMAT_VERSION {
+            return Err(KeystoreError::InvalidEntry(format!(
+                "unsupported version {}",
+                stored.version
+            )));
+        }
+        Ok(stored)
+    }
+
+    fn write(&self, name: &str, keypair: &Keypair, password: &str) -> Result<(), KeystoreError> {
+        let stored = encrypt(keypair, password, self.kdf)?;
+        let raw = serde_json::to_string(&stored)
+            .map_err(|e| KeystoreError::InvalidEntry(e.to_string()))?;
+        Ok(self.storage.set(&storage_key(name), &raw)?)
+    }
+
+    fn unlocked_mut(&self) -> std::sync::RwLockWriteGuard<'_, HashMap<String, Keypair>> {
+        self.unlocked
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+fn storage_key(name: &str) -> String {
+    format!("{}{}", STORAGE_PREFIX, name)
+}
+
+fn encrypt(
+    keypair: &Keypair,
+    password: &str,
+    kdf: KdfParams,
+) -> Result<StoredKeypair, KeystoreError> {
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    for buffer in [&mut salt[..], &mut nonce[..]] {
+        getrandom::getrandom(buffer).map_err(|e| KeypairError::Randomness(e.to_string()))?;
+    }
+
+    let cipher = ChaCha20Poly1305::new(&derive_key(password, &salt, kdf)?);
+    let ciphertext = cipher
+        .encrypt(
+            Nonce::from_slice(&nonce),
+            keypair.secret().expose_secret().as_slice(),
+        )
+        .map_err(|e| KeystoreError::InvalidEntry(e.to_string()))?;
+
+    Ok(StoredKeypair {
+        version: FORMAT_VERSION,
+        pubkey: keypair.pubkey(),
+        kdf,
+        salt: general_purpose::STANDARD.encode(salt),
+        nonce: general_purpose::STANDARD.encode(nonce),
+        ciphertext: general_purpose::STANDARD.encode(ciphertext),
+    })
+}
+
+fn decrypt(stored: &StoredKeypair, password: &str) -> Result<Keypair, KeystoreError> {
+    let decode = |field: &str| {
+        general_purpose::STANDARD
+            .decode(field)
+            .map_err(|e| KeystoreError::InvalidEntry(e.to_string()))
+    };
+    let salt = decode(&stored.salt)?;
+    let nonce = decode(&stored.nonce)?;
+    if nonce.len() != NONCE_LEN {
+        return Err(KeystoreError::InvalidEntry(format!(
+            "{}-byte nonce",
+            nonce.len()
+        )));
+    }
+
+    let cipher = ChaCha20Poly1305::new(&derive_key(password, &salt, stored.kdf)?);
+    let seed = Zeroizing::new(
+        cipher
+            .decrypt(
+                Nonce::from_slice(&nonce),
+                decode(&stored.ciphertext)?.as_slice(),
+            )
+            .map_err(|_| KeystoreError::WrongPassword)?,
+    );
+    let seed = SecretBytes::<32>::from_slice(&seed)
+        .ok_or_else(|| KeystoreError::InvalidEntry("secret key is not 32 bytes".to_string()))?;
+
+    let keypair = Keypair::from_seed(seed.expose_secret());
+    if keypair.pubkey() != stored.pubkey {
+        return Err(KeypairError::PublicKeyMismatch.into());
+    }
+    Ok(keypair)
+}
+
+fn derive_key(password: &str, salt: &[u8], kdf: KdfParams) -> Result<Key, KeystoreError> {
+    kdf.check_limits()?;
+    let params = Params::new(kdf.memory_kib, kdf.iterations, kdf.parallelism, Some(32))
+        .map_err(|e| KeystoreError::InvalidEntry(e.to_string()))?;
+    let mut key = Key::default();
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(password.as_bytes(), salt, &mut key)
+        .map_err(|e| KeystoreError::InvalidEntry(e.to_string()))?;
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::storage::MemoryStorage;
+
+    /// Cheap parameters so debug-build tests stay fast
+    const TEST_KDF: KdfParams = KdfParams {
+        memory_kib: 64,
+        iterations: 1,
+        parallelism: 1,
+    };
+
+    #[test]
+    fn test_save_lock_unlock() {
+        let storage = MemoryStorage::new();
+        let keystore = Keystore::new(Arc::new(storage.clone())).with_kdf_params(TEST_KDF);
+        let keypair = Keypair::from_seed(&[4; 32]);
+
+        keystore.save("main", &keypair, "hunter2").unwrap();
+        assert!(keystore.is_unlocked("main"));
+        assert!(matches!(
+            keystore.save("main", &keypair, "other"),
+            Err(KeystoreError::AlreadyExists(_))
+        ));
+
+        // The secret never reaches storage in the clear
+        let raw = storage.get("gloo_solana:keystore:main").unwrap().unwrap();
+        assert!(!raw.contains(&bs58::encode(keypair.secret().expose_secret()).into_string()));
+        assert!(raw.contains(&keypair.pubkey().to_base58()));
+
+        keystore.lock("main");
+        assert!(matches!(
+            keystore.keypair("main"),
+            Err(KeystoreError::Locked(_))
+        ));
+        assert_eq!(keystore.pubkey("main").unwrap(), keypair.pubkey());
+
+        // A fresh keystore over the same storage can unlock it
+        let reopened = Keystore::new(Arc::new(storage));
+        assert!(matches!(
+            reopened.unlock("main", "wrong"),
+            Err(KeystoreError::WrongPassword)
+        ));
+        assert_eq!(reopened.unlock("main", "hunter2").unwrap(), keypair);
+        assert_eq!(reopened.keypair("main").unwrap(), keypair);
+        assert_eq!(reopened.names().unwrap(), vec!["main"]);
+    }
+
+    #[test]
+    fn test_change_password_and_remove() {
+        let keystore = Keystore::new(Arc::new(MemoryStorage::new())).with_kdf_params(TEST_KDF);
+        let pubkey = keystore.generate("hot", "old").unwrap();
+
+        keystore.change_password("hot", "old", "new").unwrap();
+        assert!(keystore.unlock("hot", "old").is_err());
+        assert_eq!(keystore.unlock("hot", "new").unwrap().pubkey(), pubkey);
+
+        // A tampered entry cannot make unlocking allocate without bound
+        let key = storage_key("hot");
+        let raw = keystore.storage.get(&key).unwrap().unwrap();
+        let mut stored: StoredKeypair = serde_json::from_str(&raw).unwrap();
+        stored.kdf.memory_kib = u32::MAX;
+        keystore
+            .storage
+            .set(&key, &serde_json::to_string(&stored).unwrap())
+            .unwrap();
+        assert!(matches!(
+            keystore.unlock("hot", "new"),
+            Err(KeystoreError::KdfTooCostly(_))
+        ));
+
+        keystore.remove("hot").unwrap();
+        assert!(!keystore.is_unlocked("hot"));
+        assert!(matches!(
+            keystore.unlock("hot", "new"),
+            Err(KeystoreError::NotFound(_))
+        ));
+    }
+}
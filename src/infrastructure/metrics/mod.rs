@@ -0,0 +1,315 @@
+//! Per-endpoint client metrics
+//!
+//! Every [`SolanaRpcClient`](crate::infrastructure::rpc::SolanaRpcClient)
+//! counts its requests and errors and tracks exponentially weighted latency
+//! estimates (mean, p50 and p95) per JSON-RPC method. Failover logic and
+//! dashboards read a [`MetricsSnapshot`]; an optional [`MetricsSink`] receives
+//! each request as it completes.
+
+use crate::infrastructure::rpc::RpcError;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Default smoothing factor for latency estimates
+pub const DEFAULT_SMOOTHING: f64 = 0.1;
+
+/// Broad category of a failed request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    /// Transport failure or non-success HTTP status
+    Http,
+    /// JSON-RPC error object returned by the node
+    Rpc,
+    /// Response could not be decoded
+    Parse,
+    /// Request was rejected before being sent
+    Request,
+    /// Any other failure
+    Other,
+}
+
+impl ErrorKind {
+    /// Classify an RPC error
+    pub fn of(error: &RpcError) -> Self {
+        match error {
+            RpcError::Http(_) | RpcError::ResponseTooLarge { .. } => Self::Http,
+            RpcError::RpcError { .. } | RpcError::MethodNotFound(_) => Self::Rpc,
+            RpcError::ParseError(_)
+            | RpcError::InvalidSignature(_)
+            | RpcError::InvalidPubkey(_) => Self::Parse,
+            RpcError::InvalidRequest(_)
+            | RpcError::Unsupported { .. }
+            | RpcError::WrongCluster { .. } => Self::Request,
+            RpcError::InternalError(_) | RpcError::Cancelled => Self::Other,
+        }
+    }
+}
+
+/// A completed request, as reported to a [`MetricsSink`]
+#[derive(Debug, Clone)]
+pub struct RequestEvent<'a> {
+    pub method: &'a str,
+    pub latency: Duration,
+    /// Whether the response came from the client's cache
+    pub cached: bool,
+    pub error: Option<ErrorKind>,
+}
+
+/// Receives every request recorded by a client
+pub trait MetricsSink: Send + Sync {
+    fn record(&self, event: &RequestEvent<'_>);
+}
+
+impl<F> MetricsSink for F
+where
+    F: Fn(&RequestEvent<'_>) + Send + Sync,
+{
+    fn record(&self, event: &RequestEvent<'_>) {
+        self(event)
+    }
+}
+
+/// Latency and error statistics for one JSON-RPC method
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MethodStats {
+    pub requests: u64,
+    pub errors: u64,
+    pub cache_hits: u64,
+    /// Exponential moving average of latency, in milliseconds
+    pub mean_ms: f64,
+    /// Streaming estimate of the median latency, in milliseconds
+    pub p50_ms: f64,
+    /// Streaming estimate of the 95th percentile latency, in milliseconds
+    pub p95_ms: f64,
+}
+
+impl MethodStats {
+    /// Fraction of requests that failed
+    pub fn error_rate(&self) -> f64 {
+        if self.requests == 0 {
+            0.0
+        } else {
+            self.errors as f64 / self.requests as f64
+        }
+    }
+
+    fn observe(&mut self, latency_ms: f64, smoothing: f64) {
+        let samples = self.requests - self.cache_hits;
+        if samples == 1 {
+            self.mean_ms = latency_ms;
+            self.p50_ms = latency_ms;
+            self.p95_ms = latency_ms;
+            return;
+        }
+
+        self.mean_ms += smoothing * (latency_ms - self.mean_ms);
+        // Step quantile estimates by a fraction of the mean so they converge
+        // at the same rate whatever the endpoint's typical latency is
+        let step = smoothing * self.mean_ms.max(f64::EPSILON);
+        self.p50_ms = step_quantile(self.p50_ms, latency_ms, 0.5, step);
+        self.p95_ms = step_quantile(self.p95_ms, latency_ms, 0.95, step);
+    }
+}
+
+/// Point-in-time copy of a client's metrics
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MetricsSnapshot {
+    pub requests: u64,
+    pub errors: u64,
+    pub errors_by_kind: HashMap<ErrorKind, u64>,
+    pub methods: HashMap<String, MethodStats>,
+}
+
+impl MetricsSnapshot {
+    /// Statistics for one method
+    pub fn method(&self, method: &str) -> Option<&MethodStats> {
+        self.methods.get(method)
+    }
+
+    /// Fraction of all requests that failed
+    pub fn error_rate(&self) -> f64 {
+        if self.requests == 0 {
+            0.0
+        } else {
+            self.errors as f64 / self.requests as f64
+        }
+    }
+}
+
+/// Metrics collected by a client and shared by all of its clones
+pub struct ClientMetrics {
+    smoothing: f64,
+    state: Mutex<MetricsSnapshot>,
+    sink: Option<Arc<dyn MetricsSink>>,
+}
+
+impl ClientMetrics {
+    /// Create empty metrics with the default smoothing factor
+    pub fn new() -> Self {
+        Self::with_smoothing(DEFAULT_SMOOTHING)
+    }
+
+    /// Create empty metrics with a smoothing factor in `(0, 1]`
+    pub fn with_smoothing(smoothing: f64) -> Self {
+        Self {
+            smoothing: smoothing.clamp(f64::EPSILON, 1.0),
+            state: Mutex::new(MetricsSnapshot::default()),
+            sink: None,
+        }
+    }
+
+    /// Forward every recorded request to `sink`
+    pub fn with_sink(mut self, sink: Arc<dyn MetricsSink>) -> Self {
+        self.sink = Some(sink);
+        self
+    }
+
+    /// Record a completed request
+    pub fn record(&self, event: &RequestEvent<'_>) {
+        if let Ok(mut state) = self.state.lock() {
+            state.requests += 1;
+            if let Some(kind) = event.error {
+                state.errors += 1;
+                *state.errors_by_kind.entry(kind).or_default() += 1;
+            }
+
+            let stats = state.methods.entry(event.method.to_string()).or_default();
+            stats.requests += 1;
+            if event.error.is_some() {
+                stats.errors += 1;
+            }
+            if event.cached {
+                stats.cache_hits += 1;
+            } else {
+                stats.observe(event.latency.as_secs_f64() * 1000.0, self.smoothing);
+            }
+        }
+
+        if let Some(sink) = &self.sink {
+            sink.record(event);
+        }
+    }
+
+    /// Copy the current metrics
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        self.state
+            .lock()
+            .map(|state| state.clone())
+            .unwrap_or_default()
+    }
+
+    /// Clear all counters and estimates
+    pub fn reset(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            *state = MetricsSnapshot::default();
+        }
+    }
+}
+
+impl Default for ClientMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn step_quantile(estimate: f64, sample: f64, quantile: f64, step: f64) -> f64 {
+    if sample > estimate {
+        estimate + step * quantile
+    } else if sample < estimate {
+        (estimate - step * (1.0 - quantile)).max(0.0)
+    } else {
+        estimate
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn event(method: &str, millis: u64, error: Option<ErrorKind>) -> RequestEvent<'_> {
+        RequestEvent {
+            method,
+            latency: Duration::from_millis(millis),
+            cached: false,
+            error,
+        }
+    }
+
+    #[test]
+    fn test_counts_requests_and_errors_by_kind() {
+        let metrics = ClientMetrics::new();
+        metrics.record(&event("getBalance", 20, None));
+        metrics.record(&event("getBalance", 30, Some(ErrorKind::Http)));
+        metrics.record(&event("getSlot", 10, Some(ErrorKind::Rpc)));
+        metrics.record(&RequestEvent {
+            cached: true,
+            ..event("getSlot", 0, None)
+        });
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.requests, 4);
+        assert_eq!(snapshot.errors, 2);
+        assert_eq!(snapshot.errors_by_kind[&ErrorKind::Http], 1);
+        assert_eq!(snapshot.errors_by_kind[&ErrorKind::Rpc], 1);
+        assert_eq!(snapshot.error_rate(), 0.5);
+
+        let balance = snapshot.method("getBalance").unwrap();
+        assert_eq!(balance.requests, 2);
+        assert_eq!(balance.error_rate(), 0.5);
+
+        // Cache hits are counted but do not drag latency estimates to zero
+        let slot = snapshot.method("getSlot").unwrap();
+        assert_eq!(slot.cache_hits, 1);
+        assert_eq!(slot.p50_ms, 10.0);
+
+        metrics.reset();
+        assert_eq!(metrics.snapshot(), MetricsSnapshot::default());
+    }
+
+    #[test]
+    fn test_latency_quantiles_converge() {
+        let metrics = ClientMetrics::with_smoothing(0.05);
+        // 90% of requests take 100ms, 10% take 1000ms
+        for i in 0..4000 {
+            let millis = if i % 10 == 0 { 1000 } else { 100 };
+            metrics.record(&event("getAccountInfo", millis, None));
+        }
+
+        let stats = metrics.snapshot().methods["getAccountInfo"].clone();
+        assert!((stats.p50_ms - 100.0).abs() < 25.0, "p50 {}", stats.p50_ms);
+        assert!(stats.p95_ms > 500.0, "p95 {}", stats.p95_ms);
+        assert!(stats.mean_ms > 100.0 && stats.mean_ms < 400.0);
+    }
+
+    #[test]
+    fn test_sink_receives_events() {
+        let seen = Arc::new(AtomicUsize::new(0));
+        let counter = seen.clone();
+        let metrics = ClientMetrics::new().with_sink(Arc::new(move |event: &RequestEvent<'_>| {
+            assert_eq!(event.method, "getHealth");
+            counter.fetch_add(1, Ordering::SeqCst);
+        }));
+
+        metrics.record(&event("getHealth", 5, None));
+        metrics.record(&event("getHealth", 5, None));
+        assert_eq!(seen.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_error_kind_classification() {
+        assert_eq!(
+            ErrorKind::of(&RpcError::RpcError {
+                code: -32005,
+                message: "Node is behind".to_string(),
+                data: None,
+            }),
+            ErrorKind::Rpc
+        );
+        assert_eq!(
+            ErrorKind::of(&RpcError::ParseError("bad".to_string())),
+            ErrorKind::Parse
+        );
+    }
+}
@@ -0,0 +1,546 @@
+//! Mobile wallets through encrypted deep links
+//!
+//! Phones have no injected wallet extension, so a web app opened there signs
+//! through the wallet app instead. Phantom and Solflare accept universal
+//! links such as `https://phantom.app/ul/v1/connect`; the app opens the
+//! link, the wallet asks the user, then redirects back to the app with the
+//! answer in the query string. Payloads are encrypted with a NaCl box keyed
+//! by an x25519 key the app generates. The page is reloaded by the redirect,
+//! so [`DeepLinkWallet`] keeps that key and the wallet session in a
+//! [`StorageAdapter`].
+
+use crate::domain::message::Transaction;
+use crate::domain::types::{Pubkey, Signature};
+use crate::infrastructure::network::Network;
+use crate::infrastructure::storage::{StorageAdapter, StorageError};
+use crate::infrastructure::url::{percent_decode, percent_encode};
+use crypto_box::aead::Aead;
+use crypto_box::{Nonce, PublicKey, SalsaBox, SecretKey};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fmt::Write as _;
+use std::sync::Arc;
+use thiserror::Error;
+
+/// Storage key prefix for deep link sessions
+const STORAGE_PREFIX: &str = "gloo_solana:mobile_wallet:";
+
+/// Errors that can occur while talking to a wallet app
+#[derive(Debug, Clone, Error)]
+pub enum MobileWalletError {
+    #[error("Storage error: {0}")]
+    Storage(#[from] StorageError),
+
+    #[error("Randomness unavailable: {0}")]
+    Randomness(String),
+
+    #[error("The wallet's answer has no {0}")]
+    MissingParameter(&'static str),
+
+    #[error("The wallet's answer could not be decrypted")]
+    Decryption,
+
+    #[error("Invalid wallet answer: {0}")]
+    InvalidResponse(String),
+
+    #[error("The wallet rejected the request ({code}): {message}")]
+    Rejected { code: i64, message: String },
+
+    #[error("Not connected to {0}")]
+    NotConnected(MobileWallet),
+}
+
+/// Wallet apps implementing the deep link protocol
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MobileWallet {
+    Phantom,
+    Solflare,
+}
+
+impl MobileWallet {
+    /// Every supported wallet app
+    pub const ALL: [MobileWallet; 2] = [MobileWallet::Phantom, MobileWallet::Solflare];
+
+    /// Universal link prefix of the wallet's methods
+    pub fn base_url(&self) -> &'static str {
+        match self {
+            MobileWallet::Phantom => "https://phantom.app/ul/v1/",
+            MobileWallet::Solflare => "https://solflare.com/ul/v1/",
+        }
+    }
+}
+
+impl std::fmt::Display for MobileWallet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MobileWallet::Phantom => write!(f, "Phantom"),
+            MobileWallet::Solflare => write!(f, "Solflare"),
+        }
+    }
+}
+
+/// What survives the round trip through the wallet app
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredSession {
+    /// The app's x25519 secret key, base58
+    dapp_secret_key: String,
+    #[serde(default)]
+    wallet_encryption_key: Option<String>,
+    #[serde(default)]
+    session: Option<String>,
+    #[serde(default)]
+    public_key: Option<Pubkey>,
+}
+
+/// A connection to a wallet app over deep links
+///
+/// Methods come in pairs: a `*_url` building the link to open, and a
+/// `handle_*` reading the query string the wallet redirected back with.
+pub struct DeepLinkWallet {
+    wallet: MobileWallet,
+    app_url: String,
+    cluster: Option<&'static str>,
+    storage: Arc<dyn StorageAdapter>,
+    stored: StoredSession,
+}
+
+impl DeepLinkWallet {
+    /// Load the session with `wallet` from `storage`, or start a new one
+    ///
+    /// `app_url` identifies the app to the user, with its icon and name.
+    pub fn new(
+        wallet: MobileWallet,
+        app_url: impl Into<String>,
+        storage: Arc<dyn StorageAdapter>,
+    ) -> Result<Self, MobileWalletError> {
+        let stored = match storage.get(&storage_key(wallet))? {
+            Some(raw) => serde_json::from_str(&raw)
+                .map_err(|e| MobileWalletError::InvalidResponse(e.to_string()))?,
+            None => {
+                let mut secret = [0u8; 32];
+                getrandom::getrandom(&mut secret)
+                    .map_err(|e| MobileWalletError::Randomness(e.to_string()))?;
+                StoredSession {
+                    dapp_secret_key: bs58::encode(secret).into_string(),
+                    wallet_encryption_key: None,
+                    session: None,
+                    public_key: None,
+                }
+            }
+        };
+        let adapter = Self {
+            wallet,
+            app_url: app_url.into(),
+            cluster: None,
+            storage,
+            stored,
+        };
+        adapter.save()?;
+        Ok(adapter)
+    }
+
+    /// Ask the wallet to use `network`'s cluster
+    ///
+    /// Custom networks are left to the wallet's own setting.
+    pub fn network(mut self, network: &Network) -> Self {
+        self.cluster = match network {
+            Network::Mainnet => Some("mainnet-beta"),
+            Network::Testnet => Some("testnet"),
+            Network::Devnet => Some("devnet"),
+            Network::Custom(_) => None,
+        };
+        self
+    }
+
+    /// The wallet app
+    pub fn wallet(&self) -> MobileWallet {
+        self.wallet
+    }
+
+    /// The connected account
+    pub fn public_key(&self) -> Option<Pubkey> {
+        self.stored.public_key
+    }
+
+    /// Whether a session with the wallet is open
+    pub fn is_connected(&self) -> bool {
+        self.stored.session.is_some()
+    }
+
+    /// Link asking the wallet to connect, answered at `redirect_link`
+    pub fn connect_url(&self, redirect_link: &str) -> String {
+        let mut url = format!(
+            "{}connect?app_url={}&dapp_encryption_public_key={}&redirect_link={}",
+            self.wallet.base_url(),
+            percent_encode(&self.app_url),
+            self.dapp_public_key(),
+            percent_encode(redirect_link),
+        );
+        if let Some(cluster) = self.cluster {
+            let _ = write!(url, "&cluster={}", cluster);
+        }
+        url
+    }
+
+    /// Open the session from the wallet's answer to [`connect_url`](Self::connect_url)
+    pub fn handle_connect(&mut self, query: &str) -> Result<Pubkey, MobileWalletError> {
+        let params = parse_query(query)?;
+        let wallet_key = params
+            .iter()
+            .find(|(name, _)| name.ends_with("_encryption_public_key"))
+            .map(|(_, value)| value.clone())
+            .ok_or(MobileWalletError::MissingParameter("encryption public key"))?;
+        self.stored.wallet_encryption_key = Some(wallet_key);
+        let data = self.decrypt(&params)?;
+
+        let public_key = data["public_key"]
+            .as_str()
+            .and_then(|key| Pubkey::from_base58(key).ok())
+            .ok_or(MobileWalletError::MissingParameter("public_key"))?;
+        let session = data["session"]
+            .as_str()
+            .ok_or(MobileWalletError::MissingParameter("session"))?;
+        self.stored.public_key = Some(public_key);
+        self.stored.session = Some(session.to_string());
+        self.save()?;
+        Ok(public_key)
+    }
+
+    /// Link asking the wallet to sign `transaction`
+    pub fn sign_transaction_url(
+        &self,
+        transaction: &Transaction,
+        redirect_link: &str,
+    ) -> Result<String, MobileWalletError> {
+        let bytes = transaction
+            .serialize()
+            .map_err(|e| MobileWalletError::InvalidResponse(e.to_string()))?;
+        self.request_url(
+            "signTransaction",
+            json!({ "transaction": bs58::encode(bytes).into_string() }),
+            redirect_link,
+        )
+    }
+
+    /// The transaction signed by the wallet
+    pub fn handle_sign_transaction(&self, query: &str) -> Result<Transaction, MobileWalletError> {
+        let data = self.decrypt(&parse_query(query)?)?;
+        let bytes = decode_base58(&data, "transaction")?;
+        Transaction::deserialize(&bytes)
+            .map_err(|e| MobileWalletError::InvalidResponse(e.to_string()))
+    }
+
+    /// Link asking the wallet to sign `message`, shown to the user as text
+    pub fn sign_message_url(
+        &self,
+        message: &[u8],
+        redirect_link: &str,
+    ) -> Result<String, MobileWalletError> {
+        self.request_url(
+            "signMessage",
+            json!({ "message": bs58::encode(message).into_string(), "display": "utf8" }),
+            redirect_link,
+        )
+    }
+
+    /// The wallet's signature of the message
+    pub fn handle_sign_message(&self, query: &str) -> Result<Signature, MobileWalletError> {
+        let data = self.decrypt(&parse_query(query)?)?;
+        let bytes: [u8; 64] = decode_base58(&data, "signature")?
+            .try_into()
+            .map_err(|_| MobileWalletError::InvalidResponse("signature length".to_string()))?;
+        Ok(Signature::new(bytes))
+    }
+
+    /// Link ending the session in the wallet, which also forgets it here
+    pub fn disconnect_url(&mut self, redirect_link: &str) -> Result<String, MobileWalletError> {
+        let url = self.request_url("disconnect", json!({}), redirect_link)?;
+        self.stored.session = None;
+        self.stored.public_key = None;
+        self.save()?;
+        Ok(url)
+    }
+
+    /// Link calling `method` with `payload` plus the session, encrypted
+    fn request_url(
+        &self,
+        method: &str,
+        mut payload: Value,
+        redirect_link: &str,
+    ) -> Result<String, MobileWalletError> {
+        let session = self
+            .stored
+            .session
+            .as_ref()
+            .ok_or(MobileWalletError::NotConnected(self.wallet))?;
+        payload["session"] = json!(session);
+
+        let mut nonce = [0u8; 24];
+        getrandom::getrandom(&mut nonce)
+            .map_err(|e| MobileWalletError::Randomness(e.to_string()))?;
+        let encrypted = self
+            .shared_box()?
+            .encrypt(Nonce::from_slice(&nonce), payload.to_string().as_bytes())
+            .map_err(|_| MobileWalletError::Decryption)?;
+
+        Ok(format!(
+            "{}{}?dapp_encryption_public_key={}&nonce={}&redirect_link={}&payload={}",
+            self.wallet.base_url(),
+            method,
+            self.dapp_public_key(),
+            bs58::encode(nonce).into_string(),
+            percent_encode(redirect_link),
+            bs58::encode(encrypted).into_string(),
+        ))
+    }
+
+    /// Decrypt the `data` of a wallet answer, or surface its error
+    fn decrypt(&self, params: &[(String, String)]) -> Result<Value, MobileWalletError> {
+        let param = |name: &'static str| {
+            params
+                .iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.as_str())
+        };
+        if let Some(code) = param("errorCode") {
+            return Err(MobileWalletError::Rejected {
+                code: code.parse().unwrap_or_default(),
+                message: param("errorMessage").unwrap_or_default().to_string(),
+            });
+        }
+
+        let nonce =
+            bs58::decode(param("nonce").ok_or(MobileWalletError::MissingParameter("nonce"))?)
+                .into_vec()
+                .ok()
+                .filter(|nonce| nonce.len() == 24)
+                .ok_or(MobileWalletError::Decryption)?;
+        let data = bs58::decode(param("data").ok_or(MobileWalletError::MissingParameter("data"))?)
+            .into_vec()
+            .map_err(|_| MobileWalletError::Decryption)?;
+        let plaintext = self
+            .shared_box()?
+            .decrypt(Nonce::from_slice(&nonce), data.as_slice())
+            .map_err(|_| MobileWalletError::Decryption)?;
+        serde_json::from_slice(&plaintext)
+            .map_err(|e| MobileWalletError::InvalidResponse(e.to_string()))
+    }
+
+    fn shared_box(&self) -> Result<SalsaBox, MobileWalletError> {
+        let wallet_key = self
+            .stored
+            .wallet_encryption_key
+            .as_ref()
+            .ok_or(MobileWalletError::NotConnected(self.wallet))?;
+        let wallet_key = bs58::decode(wallet_key)
+            .into_vec()
+            .ok()
+            .and_then(|bytes| PublicKey::from_slice(&bytes).ok())
+            .ok_or(MobileWalletError::Decryption)?;
+        Ok(SalsaBox::new(&wallet_key, &self.secret_key()))
+    }
+
+    fn secret_key(&self) -> SecretKey {
+        let bytes = bs58::decode(&self.stored.dapp_secret_key)
+            .into_vec()
+            .unwrap_or_default();
+        SecretKey::from_slice(&bytes).unwrap_or_else(|_| SecretKey::from_bytes([0; 32]))
+    }
+
+    fn dapp_public_key(&self) -> String {
+        bs58::encode(self.secret_key().public_key().as_bytes()).into_string()
+    }
+
+    fn save(&self) -> Result<(), MobileWalletError> {
+        let raw = serde_json::to_string(&self.stored)
+            .map_err(|e| MobileWalletError::InvalidResponse(e.to_string()))?;
+        Ok(self.storage.set(&storage_key(self.wallet), &raw)?)
+    }
+}
+
+impl std::fmt::Debug for DeepLinkWallet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DeepLinkWallet")
+            .field("wallet", &self.wallet)
+            .field("public_key", &self.stored.public_key)
+            .finish_non_exhaustive()
+    }
+}
+
+fn storage_key(wallet: MobileWallet) -> String {
+    format!("{}{:?}", STORAGE_PREFIX, wallet)
+}
+
+fn decode_base58(data: &Value, field: &'static str) -> Result<Vec<u8>, MobileWalletError> {
+    let value = data[field]
+        .as_str()
+        .ok_or(MobileWalletError::MissingParameter(field))?;
+    bs58::decode(value)
+        .into_vec()
+        .map_err(|e| MobileWalletError::InvalidResponse(e.to_string()))
+}
+
+/// Name and value pairs of a query string, with or without its `?`
+fn parse_query(query: &str) -> Result<Vec<(String, String)>, MobileWalletError> {
+    let query = query.split_once('?').map_or(query, |(_, query)| query);
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            Ok((decode(name)?, decode(value)?))
+        })
+        .collect()
+}
+
+/// Percent-decode a query value, mapping a bad escape to an invalid response
+fn decode(value: &str) -> Result<String, MobileWalletError> {
+    percent_decode(value)
+        .ok_or_else(|| MobileWalletError::InvalidResponse(format!("bad escape in {}", value)))
+}
+
+/// Browser helpers for following links and reading the redirect
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+mod browser {
+    use super::MobileWalletError;
+
+    fn location() -> Result<web_sys::Location, MobileWalletError> {
+        web_sys::window()
+            .map(|window| window.location())
+            .ok_or_else(|| MobileWalletError::InvalidResponse("no browser window".to_string()))
+    }
+
+    /// Navigate to a link built by [`DeepLinkWallet`](super::DeepLinkWallet)
+    pub fn open(url: &str) -> Result<(), MobileWalletError> {
+        location()?
+            .set_href(url)
+            .map_err(|e| MobileWalletError::InvalidResponse(format!("{:?}", e)))
+    }
+
+    /// The current page's query string, where wallets put their answer
+    pub fn current_query() -> Result<String, MobileWalletError> {
+        location()?
+            .search()
+            .map_err(|e| MobileWalletError::InvalidResponse(format!("{:?}", e)))
+    }
+
+    /// Whether the page runs in a phone browser without wallet extensions
+    pub fn is_mobile_browser() -> bool {
+        web_sys::window()
+            .and_then(|window| window.navigator().user_agent().ok())
+            .is_some_and(|agent| {
+                ["Android", "iPhone", "iPad"]
+                    .iter()
+                    .any(|device| agent.contains(device))
+            })
+    }
+}
+
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+pub use browser::{current_query, is_mobile_browser, open};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::storage::MemoryStorage;
+
+    /// The wallet's side: encrypt `data` for the app and build the redirect
+    fn answer(wallet: &SecretKey, app: &DeepLinkWallet, data: Value, connect: bool) -> String {
+        let app_key = bs58::decode(app.dapp_public_key()).into_vec().unwrap();
+        let shared = SalsaBox::new(&PublicKey::from_slice(&app_key).unwrap(), wallet);
+        let nonce = [7u8; 24];
+        let encrypted = shared
+            .encrypt(Nonce::from_slice(&nonce), data.to_string().as_bytes())
+            .unwrap();
+        let mut query = format!(
+            "?nonce={}&data={}",
+            bs58::encode(nonce).into_string(),
+            bs58::encode(encrypted).into_string()
+        );
+        if connect {
+            let key = bs58::encode(wallet.public_key().as_bytes()).into_string();
+            query.push_str(&format!("&phantom_encryption_public_key={}", key));
+        }
+        query
+    }
+
+    /// What the wallet reads from a request link
+    fn request_payload(wallet: &SecretKey, app: &DeepLinkWallet, url: &str) -> Value {
+        let params = parse_query(url).unwrap();
+        let param = |name: &str| {
+            let value = &params.iter().find(|(key, _)| key == name).unwrap().1;
+            bs58::decode(value).into_vec().unwrap()
+        };
+        let app_key = PublicKey::from_slice(&param("dapp_encryption_public_key")).unwrap();
+        assert_eq!(
+            app_key.as_bytes().to_vec(),
+            bs58::decode(app.dapp_public_key()).into_vec().unwrap()
+        );
+        let plaintext = SalsaBox::new(&app_key, wallet)
+            .decrypt(
+                Nonce::from_slice(&param("nonce")),
+                param("payload").as_slice(),
+            )
+            .unwrap();
+        serde_json::from_slice(&plaintext).unwrap()
+    }
+
+    #[test]
+    fn test_connect_and_sign_message() {
+        let storage = Arc::new(MemoryStorage::new());
+        let wallet_key = SecretKey::from_bytes([9; 32]);
+        let account = Pubkey::new([4; 32]);
+        let mut app = DeepLinkWallet::new(
+            MobileWallet::Phantom,
+            "https://app.example",
+            storage.clone(),
+        )
+        .unwrap()
+        .network(&Network::Devnet);
+
+        let url = app.connect_url("https://app.example/connected");
+        assert!(
+            url.starts_with("https://phantom.app/ul/v1/connect?app_url=https%3A%2F%2Fapp.example")
+        );
+        assert!(url.ends_with("&cluster=devnet"));
+        assert!(matches!(
+            app.sign_message_url(b"hi", "https://app.example/signed"),
+            Err(MobileWalletError::NotConnected(MobileWallet::Phantom))
+        ));
+
+        let connected = answer(
+            &wallet_key,
+            &app,
+            json!({ "public_key": account.to_base58(), "session": "s1" }),
+            true,
+        );
+        assert_eq!(app.handle_connect(&connected).unwrap(), account);
+
+        // The redirect reloads the page; the session comes back from storage
+        let app =
+            DeepLinkWallet::new(MobileWallet::Phantom, "https://app.example", storage).unwrap();
+        assert_eq!(app.public_key(), Some(account));
+        let url = app
+            .sign_message_url(b"hi", "https://app.example/signed")
+            .unwrap();
+        let payload = request_payload(&wallet_key, &app, &url);
+        assert_eq!(payload["session"], "s1");
+        assert_eq!(payload["message"], bs58::encode(b"hi").into_string());
+
+        let signed = answer(
+            &wallet_key,
+            &app,
+            json!({ "signature": bs58::encode([5u8; 64]).into_string() }),
+            false,
+        );
+        assert_eq!(
+            app.handle_sign_message(&signed).unwrap(),
+            Signature::new([5; 64])
+        );
+        assert!(matches!(
+            app.handle_sign_message("?errorCode=4001&errorMessage=User%20rejected%20the%20request"),
+            Err(MobileWalletError::Rejected { code: 4001, message }) if message == "User rejected the request"
+        ));
+    }
+}
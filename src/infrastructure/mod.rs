@@ -4,14 +4,70 @@
 //! integrations, such as HTTP clients, RPC clients, and other technical
 //! concerns required to interact with the Solana network.
 
+#[cfg(feature = "anchor")]
+pub mod anchor_workspace;
+pub mod cache;
+pub mod cancel;
+pub mod cluster;
+pub mod config;
+pub mod explorer;
 pub mod http;
+pub mod json;
+#[cfg(feature = "keystore")]
+pub mod keystore;
+pub mod metrics;
+#[cfg(feature = "mobile-wallet")]
+pub mod mobile_wallet;
+pub mod network;
+pub mod pubsub;
+pub mod rate_limit;
 pub mod rpc;
+pub mod signer;
+pub mod storage;
+#[cfg(feature = "opentelemetry")]
+pub mod telemetry;
+pub mod time;
+pub mod url;
+pub mod visibility;
+pub mod wallet_standard;
 
 // Re-export commonly used infrastructure components
-pub use http::HttpError;
+#[cfg(feature = "anchor")]
+pub use anchor_workspace::{AnchorWorkspace, WorkspaceError, WorkspaceProgram};
+pub use cache::{CachePolicy, ResponseCache, TransactionCache};
+pub use cancel::CancelToken;
+pub use cluster::{ClusterCapabilities, ClusterKind, RpcVersion};
+pub use config::{ClientConfig, ConfigError};
+pub use explorer::{abbreviate_pubkey, abbreviate_signature, Explorer};
 #[cfg(target_arch = "wasm32")]
 pub use http::WasmHttpClient;
+pub use http::{HttpError, Recording, RecordingHttpClient, ReplayHttpClient};
+#[cfg(not(target_arch = "wasm32"))]
+pub use http::{ProxyConfig, TransportConfig, TransportStats};
+pub use json::{JsonError, JsonParser, SerdeJsonParser};
+#[cfg(feature = "keystore")]
+pub use keystore::{KdfParams, Keystore, KeystoreError};
+pub use metrics::{ClientMetrics, MetricsSink, MetricsSnapshot};
+#[cfg(feature = "mobile-wallet")]
+pub use mobile_wallet::{DeepLinkWallet, MobileWallet, MobileWalletError};
+pub use network::{surfpool_network, CustomNetwork, CustomNetworkBuilder, Network};
+pub use pubsub::{
+    ConnectionState, PubsubClient, PubsubConnection, PubsubError, PubsubEvent, ReconnectPolicy,
+    Subscription, SubscriptionKey,
+};
+pub use rpc::filters::{Filter, FilterBuilder, FilterError};
 pub use rpc::{
-    surfpool_network, Account, CommitmentLevel, LatestBlockhash, Network, RpcClientBuilder,
-    RpcError, SolanaRpcClient,
+    Account, CommitmentLevel, ConfirmedBlock, ConsistentReader, LatestBlockhash, NetworkRegistry,
+    RpcApi, RpcClientBuilder, RpcContextConfig, RpcError, RpcInterceptor, SolanaRpcClient,
+    WithContext,
+};
+pub use signer::{DerivationPath, Keypair, KeypairError, SecretBytes, Signer, SignerError};
+pub use storage::{MemoryStorage, StorageAdapter, StorageError};
+#[cfg(all(feature = "opentelemetry", not(target_arch = "wasm32")))]
+pub use telemetry::OtelMetricsSink;
+#[cfg(feature = "opentelemetry")]
+pub use telemetry::{BufferedTelemetry, TelemetryRecord};
+pub use wallet_standard::{
+    check_message, MockWallet, MockWalletConfig, WalletConnectionState, WalletInfo, WalletRegistry,
+    WalletSession, WalletSessionStore, WalletStandardError,
 };
@@ -0,0 +1,310 @@
+//! Network configuration for Solana clusters
+//!
+//! This module describes the clusters the library can talk to, including their
+//! HTTP JSON-RPC endpoints, websocket (PubSub) endpoints, genesis hashes and
+//! Solana Explorer links.
+
+use crate::domain::types::{Hash, Pubkey, Signature};
+use crate::infrastructure::url::percent_encode;
+
+/// Genesis hash of mainnet-beta
+pub const MAINNET_GENESIS_HASH: &str = "5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d";
+
+/// Genesis hash of testnet
+pub const TESTNET_GENESIS_HASH: &str = "4uhcVJyU9pJkvQyS88uRDiswHXSCkY3zQawwpjk2NsNY";
+
+/// Genesis hash of devnet
+pub const DEVNET_GENESIS_HASH: &str = "EtWTRABZaYq6iMfeYKouRu166VU2xqa1wcaWoxPkrZBG";
+
+/// Default surfpool (simnet) HTTP endpoint
+pub const SURFPOOL_HTTP_ENDPOINT: &str = "http://127.0.0.1:8899";
+
+/// Default surfpool (simnet) websocket endpoint
+pub const SURFPOOL_WS_ENDPOINT: &str = "ws://127.0.0.1:8900";
+
+const EXPLORER_BASE_URL: &str = "https://explorer.solana.com";
+
+/// Network configuration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Network {
+    Mainnet,
+    Testnet,
+    Devnet,
+    Custom(CustomNetwork),
+}
+
+impl std::fmt::Display for Network {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Network::Mainnet => write!(f, "Mainnet"),
+            Network::Testnet => write!(f, "Testnet"),
+            Network::Devnet => write!(f, "Devnet"),
+            Network::Custom(custom) => match &custom.name {
+                Some(name) => write!(f, "Custom({})", name),
+                None => write!(f, "Custom({})", custom.http_endpoint),
+            },
+        }
+    }
+}
+
+impl Network {
+    /// Create a custom network from an HTTP endpoint, deriving the websocket endpoint
+    pub fn custom(http_endpoint: impl Into<String>) -> Self {
+        Network::Custom(CustomNetwork::new(http_endpoint))
+    }
+
+    /// Get the RPC endpoint for this network
+    pub fn endpoint(&self) -> &str {
+        match self {
+            Network::Mainnet => "https://api.mainnet-beta.solana.com",
+            Network::Testnet => "https://api.testnet.solana.com",
+            Network::Devnet => "https://api.devnet.solana.com",
+            Network::Custom(custom) => &custom.http_endpoint,
+        }
+    }
+
+    /// Get the websocket (PubSub) endpoint for this network
+    pub fn ws_endpoint(&self) -> &str {
+        match self {
+            Network::Mainnet => "wss://api.mainnet-beta.solana.com",
+            Network::Testnet => "wss://api.testnet.solana.com",
+            Network::Devnet => "wss://api.devnet.solana.com",
+            Network::Custom(custom) => &custom.ws_endpoint,
+        }
+    }
+
+    /// Get the expected genesis hash, if known
+    pub fn genesis_hash(&self) -> Option<Hash> {
+        match self {
+            Network::Mainnet => Hash::from_base58(MAINNET_GENESIS_HASH).ok(),
+            Network::Testnet => Hash::from_base58(TESTNET_GENESIS_HASH).ok(),
+            Network::Devnet => Hash::from_base58(DEVNET_GENESIS_HASH).ok(),
+            Network::Custom(custom) => custom.genesis_hash,
+        }
+    }
+
+    /// Get the Solana Explorer URL for a transaction on this network
+    pub fn explorer_transaction_url(&self, signature: &Signature) -> String {
+        self.explorer_url(&format!("tx/{}", signature))
+    }
+
+    /// Get the Solana Explorer URL for an account on this network
+    pub fn explorer_account_url(&self, pubkey: &Pubkey) -> String {
+        self.explorer_url(&format!("address/{}", pubkey))
+    }
+
+    /// Get the Solana Explorer URL for a block on this network
+    pub fn explorer_block_url(&self, slot: u64) -> String {
+        self.explorer_url(&format!("block/{}", slot))
+    }
+
+    /// Query string selecting this cluster in the Solana Explorer
+    pub fn explorer_cluster_query(&self) -> String {
+        match self {
+            Network::Mainnet => String::new(),
+            Network::Testnet => "?cluster=testnet".to_string(),
+            Network::Devnet => "?cluster=devnet".to_string(),
+            Network::Custom(custom) => format!(
+                "?cluster=custom&customUrl={}",
+                percent_encode(&custom.http_endpoint)
+            ),
+        }
+    }
+
+    fn explorer_url(&self, path: &str) -> String {
+        format!(
+            "{}/{}{}",
+            EXPLORER_BASE_URL,
+            path,
+            self.explorer_cluster_query()
+        )
+    }
+}
+
+/// Configuration for a custom (non-public) cluster
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CustomNetwork {
+    /// Optional display name
+    pub name: Option<String>,
+    /// HTTP JSON-RPC endpoint
+    pub http_endpoint: String,
+    /// Websocket (PubSub) endpoint
+    pub ws_endpoint: String,
+    /// Expected genesis hash, if known
+    pub genesis_hash: Option<Hash>,
+}
+
+impl CustomNetwork {
+    /// Create a custom network from an HTTP endpoint, deriving the websocket endpoint
+    pub fn new(http_endpoint: impl Into<String>) -> Self {
+        Self::builder(http_endpoint).build()
+    }
+
+    /// Start building a custom network
+    pub fn builder(http_endpoint: impl Into<String>) -> CustomNetworkBuilder {
+        CustomNetworkBuilder::new(http_endpoint)
+    }
+}
+
+/// Builder for [`CustomNetwork`]
+#[derive(Debug, Clone)]
+pub struct CustomNetworkBuilder {
+    name: Option<String>,
+    http_endpoint: String,
+    ws_endpoint: Option<String>,
+    genesis_hash: Option<Hash>,
+}
+
+impl CustomNetworkBuilder {
+    /// Create a new builder for the given HTTP endpoint
+    pub fn new(http_endpoint: impl Into<String>) -> Self {
+        Self {
+            name: None,
+            http_endpoint: http_endpoint.into(),
+            ws_endpoint: None,
+            genesis_hash: None,
+        }
+    }
+
+    /// Set a display name
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Set an explicit websocket endpoint
+    pub fn ws_endpoint(mut self, ws_endpoint: impl Into<String>) -> Self {
+        self.ws_endpoint = Some(ws_endpoint.into());
+        self
+    }
+
+    /// Set the expected genesis hash
+    pub fn genesis_hash(mut self, genesis_hash: Hash) -> Self {
+        self.genesis_hash = Some(genesis_hash);
+        self
+    }
+
+    /// Build the custom network
+    pub fn build(self) -> CustomNetwork {
+        let ws_endpoint = self
+            .ws_endpoint
+            .unwrap_or_else(|| derive_ws_endpoint(&self.http_endpoint));
+
+        CustomNetwork {
+            name: self.name,
+            http_endpoint: self.http_endpoint,
+            ws_endpoint,
+            genesis_hash: self.genesis_hash,
+        }
+    }
+
+    /// Build the custom network wrapped in [`Network::Custom`]
+    pub fn build_network(self) -> Network {
+        Network::Custom(self.build())
+    }
+}
+
+/// Create a network configuration for surfpool (simnet)
+pub fn surfpool_network() -> Network {
+    CustomNetwork::builder(SURFPOOL_HTTP_ENDPOINT)
+        .name("surfpool")
+        .ws_endpoint(SURFPOOL_WS_ENDPOINT)
+        .build_network()
+}
+
+/// Derive a websocket endpoint from an HTTP endpoint
+///
+/// Follows the validator convention: the scheme switches to ws/wss and an
+/// explicit port is incremented by one (8899 -> 8900).
+fn derive_ws_endpoint(http_endpoint: &str) -> String {
+    let (scheme, rest) = if let Some(rest) = http_endpoint.strip_prefix("https://") {
+        ("wss://", rest)
+    } else if let Some(rest) = http_endpoint.strip_prefix("http://") {
+        ("ws://", rest)
+    } else {
+        return http_endpoint.to_string();
+    };
+
+    let (authority, path) = match rest.find('/') {
+        Some(index) => rest.split_at(index),
+        None => (rest, ""),
+    };
+
+    let authority = match authority.rsplit_once(':') {
+        Some((host, port)) => match port.parse::<u16>() {
+            Ok(port) => format!("{}:{}", host, port.saturating_add(1)),
+            Err(_) => authority.to_string(),
+        },
+        None => authority.to_string(),
+    };
+
+    format!("{}{}{}", scheme, authority, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ws_endpoints() {
+        assert_eq!(
+            Network::Mainnet.ws_endpoint(),
+            "wss://api.mainnet-beta.solana.com"
+        );
+        assert_eq!(Network::Devnet.ws_endpoint(), "wss://api.devnet.solana.com");
+        assert_eq!(surfpool_network().ws_endpoint(), "ws://127.0.0.1:8900");
+    }
+
+    #[test]
+    fn test_custom_network_derives_ws_endpoint() {
+        let network = Network::custom("http://localhost:8899");
+        assert_eq!(network.endpoint(), "http://localhost:8899");
+        assert_eq!(network.ws_endpoint(), "ws://localhost:8900");
+
+        let network = Network::custom("https://rpc.example.com/api-key");
+        assert_eq!(network.ws_endpoint(), "wss://rpc.example.com/api-key");
+    }
+
+    #[test]
+    fn test_custom_network_builder() {
+        let genesis = Hash::new([7; 32]);
+        let network = CustomNetwork::builder("https://rpc.example.com")
+            .name("example")
+            .ws_endpoint("wss://ws.example.com")
+            .genesis_hash(genesis)
+            .build_network();
+
+        assert_eq!(network.endpoint(), "https://rpc.example.com");
+        assert_eq!(network.ws_endpoint(), "wss://ws.example.com");
+        assert_eq!(network.genesis_hash(), Some(genesis));
+        assert_eq!(network.to_string(), "Custom(example)");
+    }
+
+    #[test]
+    fn test_genesis_hashes() {
+        assert_eq!(
+            Network::Mainnet.genesis_hash().unwrap().to_base58(),
+            MAINNET_GENESIS_HASH
+        );
+        assert!(Network::Devnet.genesis_hash().is_some());
+        assert!(Network::Testnet.genesis_hash().is_some());
+        assert!(surfpool_network().genesis_hash().is_none());
+    }
+
+    #[test]
+    fn test_explorer_urls() {
+        let pubkey = Pubkey::new([0; 32]);
+        assert_eq!(
+            Network::Mainnet.explorer_account_url(&pubkey),
+            "https://explorer.solana.com/address/11111111111111111111111111111111"
+        );
+        assert_eq!(
+            Network::Devnet.explorer_block_url(42),
+            "https://explorer.solana.com/block/42?cluster=devnet"
+        );
+        assert_eq!(
+            surfpool_network().explorer_cluster_query(),
+            "?cluster=custom&customUrl=http%3A%2F%2F127.0.0.1%3A8899"
+        );
+    }
+}
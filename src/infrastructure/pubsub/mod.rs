@@ -0,0 +1,352 @@
+//! Solana websocket (pubsub) client
+//!
+//! Subscriptions are opened over the JSON-RPC websocket endpoint, using
+//! tokio-tungstenite natively and the browser `WebSocket` API on wasm. Each
+//! [`Subscription`] owns its connection and yields the `result` payload of
+//! every notification it receives; [`PubsubConnection`] shares one connection
+//! between many subscriptions and reconnects when it drops.
+
+use crate::domain::types::Signature;
+use crate::infrastructure::rpc::{CommitmentLevel, RpcId};
+use futures::{SinkExt, StreamExt};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use thiserror::Error;
+
+pub mod multiplex;
+
+pub use multiplex::{
+    ConnectionState, PubsubConnection, PubsubEvent, ReconnectPolicy, SubscriptionKey,
+};
+
+/// Errors that can occur on a websocket subscription
+#[derive(Debug, Clone, Error)]
+pub enum PubsubError {
+    #[error("WebSocket connection failed: {0}")]
+    Connection(String),
+
+    #[error("WebSocket connection closed")]
+    Closed,
+
+    #[error("Subscription rejected: {code} - {message}")]
+    Rpc { code: i64, message: String },
+
+    #[error("Invalid pubsub message: {0}")]
+    Parse(String),
+}
+
+/// Client for the JSON-RPC websocket endpoint
+#[derive(Debug, Clone)]
+pub struct PubsubClient {
+    url: String,
+}
+
+/// An active subscription and the connection carrying it
+pub struct Subscription {
+    connection: Connection,
+    id: u64,
+    unsubscribe_method: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct PubsubMessage {
+    /// Request id of a response; notifications have none
+    #[serde(default)]
+    id: Option<RpcId>,
+    #[serde(default)]
+    result: Option<Value>,
+    #[serde(default)]
+    error: Option<PubsubErrorObject>,
+    #[serde(default)]
+    params: Option<NotificationParams>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PubsubErrorObject {
+    code: i64,
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct NotificationParams {
+    result: Value,
+    /// Server-assigned subscription id, a number on Solana nodes
+    subscription: RpcId,
+}
+
+impl PubsubClient {
+    /// Create a client for a websocket URL
+    pub fn new(url: impl Into<String>) -> Self {
+        Self { url: url.into() }
+    }
+
+    /// Create a client for the websocket endpoint paired with an HTTP endpoint
+    pub fn for_http_endpoint(endpoint: &str) -> Self {
+        Self::new(websocket_url(endpoint))
+    }
+
+    /// Get the websocket URL
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Open a subscription, e.g. `subscribe("slotSubscribe", vec![])`
+    pub async fn subscribe(
+        &self,
+        method: &str,
+        params: Vec<Value>,
+    ) -> Result<Subscription, PubsubError> {
+        let mut connection = Connection::open(&self.url).await?;
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        });
+        connection.send(request.to_string()).await?;
+
+        loop {
+            let message = connection
+                .next_message()
+                .await
+                .ok_or(PubsubError::Closed)??;
+            if let Some(error) = message.error {
+                return Err(PubsubError::Rpc {
+                    code: error.code,
+                    message: error.message,
+                });
+            }
+            if message.id == Some(RpcId::Number(1)) {
+                let id = message
+                    .result
+                    .as_ref()
+                    .and_then(Value::as_u64)
+                    .ok_or_else(|| PubsubError::Parse("missing subscription id".to_string()))?;
+                return Ok(Subscription {
+                    connection,
+                    id,
+                    unsubscribe_method: method.replace("Subscribe", "Unsubscribe"),
+                });
+            }
+        }
+    }
+
+    /// Subscribe to a transaction reaching `commitment`
+    ///
+    /// The node sends a single notification and then ends the subscription.
+    pub async fn signature_subscribe(
+        &self,
+        signature: &Signature,
+        commitment: CommitmentLevel,
+    ) -> Result<Subscription, PubsubError> {
+        self.subscribe(
+            "signatureSubscribe",
+            vec![
+                json!(signature.to_base58()),
+                json!({ "commitment": commitment }),
+            ],
+        )
+        .await
+    }
+}
+
+impl Subscription {
+    /// Server-assigned subscription id
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Wait for the next notification's `result` payload
+    ///
+    /// Returns `None` once the connection closes.
+    pub async fn next(&mut self) -> Option<Result<Value, PubsubError>> {
+        loop {
+            let message = match self.connection.next_message().await? {
+                Ok(message) => message,
+                Err(error) => return Some(Err(error)),
+            };
+            match message.params {
+                Some(params) if params.subscription == RpcId::Number(self.id) => {
+                    return Some(Ok(params.result));
+                }
+                _ => continue,
+            }
+        }
+    }
+
+    /// Cancel the subscription and close the connection
+    pub async fn unsubscribe(mut self) -> Result<(), PubsubError> {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "method": self.unsubscribe_method,
+            "params": [self.id],
+        });
+        self.connection.send(request.to_string()).await?;
+        self.connection.close().await
+    }
+}
+
+/// Derive the websocket URL paired with an HTTP RPC endpoint
+///
+/// Follows the validator convention of serving websockets one port above an
+/// explicit HTTP port, e.g. `http://127.0.0.1:8899` -> `ws://127.0.0.1:8900`.
+pub fn websocket_url(endpoint: &str) -> String {
+    let (scheme, rest) = match endpoint.split_once("://") {
+        Some(("https", rest)) => ("wss", rest),
+        Some(("http", rest)) => ("ws", rest),
+        Some((scheme, rest)) => (scheme, rest),
+        None => ("ws", endpoint),
+    };
+
+    let (authority, path) = match rest.find('/') {
+        Some(index) => rest.split_at(index),
+        None => (rest, ""),
+    };
+    let authority = match authority
+        .rsplit_once(':')
+        .and_then(|(host, port)| Some((host, port.parse::<u16>().ok()?)))
+    {
+        Some((host, port)) => format!("{}:{}", host, port.saturating_add(1)),
+        None => authority.to_string(),
+    };
+
+    format!("{}://{}{}", scheme, authority, path)
+}
+
+fn parse_message(text: &str) -> Result<PubsubMessage, PubsubError> {
+    serde_json::from_str(text).map_err(|e| PubsubError::Parse(e.to_string()))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+struct Connection {
+    stream: tokio_tungstenite::WebSocketStream<
+        tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
+    >,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Connection {
+    async fn open(url: &str) -> Result<Self, PubsubError> {
+        let (stream, _) = tokio_tungstenite::connect_async(url)
+            .await
+            .map_err(|e| PubsubError::Connection(e.to_string()))?;
+        Ok(Self { stream })
+    }
+
+    async fn send(&mut self, text: String) -> Result<(), PubsubError> {
+        self.stream
+            .send(tokio_tungstenite::tungstenite::Message::Text(text))
+            .await
+            .map_err(|e| PubsubError::Connection(e.to_string()))
+    }
+
+    async fn next_message(&mut self) -> Option<Result<PubsubMessage, PubsubError>> {
+        use tokio_tungstenite::tungstenite::Message;
+
+        loop {
+            match self.stream.next().await? {
+                Ok(Message::Text(text)) => return Some(parse_message(&text)),
+                Ok(Message::Close(_)) => return None,
+                Ok(_) => continue,
+                Err(error) => return Some(Err(PubsubError::Connection(error.to_string()))),
+            }
+        }
+    }
+
+    async fn close(&mut self) -> Result<(), PubsubError> {
+        self.stream
+            .close(None)
+            .await
+            .map_err(|e| PubsubError::Connection(e.to_string()))
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+struct Connection {
+    stream: gloo_net::websocket::futures::WebSocket,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Connection {
+    async fn open(url: &str) -> Result<Self, PubsubError> {
+        let stream = gloo_net::websocket::futures::WebSocket::open(url)
+            .map_err(|e| PubsubError::Connection(e.to_string()))?;
+        Ok(Self { stream })
+    }
+
+    async fn send(&mut self, text: String) -> Result<(), PubsubError> {
+        self.stream
+            .send(gloo_net::websocket::Message::Text(text))
+            .await
+            .map_err(|e| PubsubError::Connection(e.to_string()))
+    }
+
+    async fn next_message(&mut self) -> Option<Result<PubsubMessage, PubsubError>> {
+        use gloo_net::websocket::{Message, WebSocketError};
+
+        loop {
+            match self.stream.next().await? {
+                Ok(Message::Text(text)) => return Some(parse_message(&text)),
+                Ok(Message::Bytes(_)) => continue,
+                Err(WebSocketError::ConnectionClose(_)) => return None,
+                Err(error) => return Some(Err(PubsubError::Connection(error.to_string()))),
+            }
+        }
+    }
+
+    async fn close(&mut self) -> Result<(), PubsubError> {
+        self.stream
+            .close()
+            .await
+            .map_err(|e| PubsubError::Connection(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_websocket_url_from_http_endpoint() {
+        assert_eq!(
+            websocket_url("https://api.devnet.solana.com"),
+            "wss://api.devnet.solana.com"
+        );
+        assert_eq!(
+            websocket_url("http://127.0.0.1:8899"),
+            "ws://127.0.0.1:8900"
+        );
+        assert_eq!(
+            websocket_url("https://rpc.example.com:443/v1/key"),
+            "wss://rpc.example.com:444/v1/key"
+        );
+        assert_eq!(websocket_url("http://[::1]:8899"), "ws://[::1]:8900");
+        assert_eq!(websocket_url("http://[::1]"), "ws://[::1]");
+    }
+
+    #[test]
+    fn test_parse_notification() {
+        let message = parse_message(
+            r#"{"jsonrpc":"2.0","method":"signatureNotification","params":{"result":{"context":{"slot":5},"value":{"err":null}},"subscription":24006}}"#,
+        )
+        .unwrap();
+        let params = message.params.unwrap();
+        assert_eq!(params.subscription, RpcId::Number(24006));
+        assert_eq!(params.result["context"]["slot"], 5);
+
+        let error = parse_message(
+            r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Invalid params"},"id":1}"#,
+        )
+        .unwrap();
+        assert_eq!(error.error.unwrap().code, -32602);
+    }
+
+    #[tokio::test]
+    async fn test_connect_failure_is_reported() {
+        let result = PubsubClient::new("ws://127.0.0.1:1")
+            .subscribe("slotSubscribe", Vec::new())
+            .await;
+        assert!(matches!(result, Err(PubsubError::Connection(_))));
+    }
+}
@@ -0,0 +1,502 @@
+//! Multiplexed subscriptions with automatic reconnection
+//!
+//! [`PubsubConnection`] carries any number of subscriptions over one
+//! websocket. Responses are matched to requests by their JSON-RPC id and
+//! notifications to subscriptions by the id the node assigned, which are
+//! separate numbering spaces. When the socket drops it reconnects with
+//! exponential backoff and subscribes again, so [`SubscriptionKey`]s stay
+//! valid even though the node hands out new subscription ids.
+
+use super::{Connection, PubsubError, PubsubMessage};
+use crate::infrastructure::rpc::{RpcId, RpcRequest};
+use crate::infrastructure::time::sleep;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt;
+use std::time::Duration;
+
+/// Default delay before the first reconnection attempt
+pub const DEFAULT_INITIAL_RECONNECT_DELAY: Duration = Duration::from_millis(500);
+
+/// Default cap on the delay between reconnection attempts
+pub const DEFAULT_MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+/// How a dropped connection is retried
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReconnectPolicy {
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    /// Attempts before giving up, unlimited if `None`
+    pub max_attempts: Option<u32>,
+}
+
+impl ReconnectPolicy {
+    /// Retry forever, doubling the delay from 500ms up to 30s
+    pub fn new() -> Self {
+        Self {
+            initial_delay: DEFAULT_INITIAL_RECONNECT_DELAY,
+            max_delay: DEFAULT_MAX_RECONNECT_DELAY,
+            max_attempts: None,
+        }
+    }
+
+    /// Never reconnect
+    pub fn never() -> Self {
+        Self::new().max_attempts(0)
+    }
+
+    /// Set the delay before the first attempt
+    pub fn initial_delay(mut self, delay: Duration) -> Self {
+        self.initial_delay = delay;
+        self
+    }
+
+    /// Set the cap on the delay between attempts
+    pub fn max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// Give up after `attempts` failed attempts
+    pub fn max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = Some(attempts);
+        self
+    }
+
+    /// Delay before attempt `attempt`, counting from 1
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_delay
+            .saturating_mul(factor)
+            .min(self.max_delay)
+    }
+
+    /// Whether attempt `attempt` may be made
+    pub fn allows(&self, attempt: u32) -> bool {
+        self.max_attempts.is_none_or(|max| attempt <= max)
+    }
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Health of a [`PubsubConnection`], for status indicators
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+    Connected,
+    /// Waiting to make reconnection attempt `attempt`
+    Reconnecting {
+        attempt: u32,
+    },
+    /// Reconnection was given up or the connection was closed
+    Disconnected,
+}
+
+impl fmt::Display for ConnectionState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Connected => write!(f, "Connected"),
+            Self::Reconnecting { attempt } => write!(f, "Reconnecting (attempt {})", attempt),
+            Self::Disconnected => write!(f, "Disconnected"),
+        }
+    }
+}
+
+/// Local handle of a subscription, stable across reconnects
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SubscriptionKey(u64);
+
+/// Something that happened on a [`PubsubConnection`]
+#[derive(Debug, Clone)]
+pub enum PubsubEvent {
+    /// The node confirmed a subscription, again after every reconnect
+    Subscribed { key: SubscriptionKey, id: RpcId },
+    /// The `result` payload of a notification
+    Notification { key: SubscriptionKey, result: Value },
+    /// The node refused a subscription, which is dropped
+    Rejected {
+        key: SubscriptionKey,
+        error: PubsubError,
+    },
+    /// The connection state changed
+    State(ConnectionState),
+}
+
+/// Subscriptions over one websocket that survive reconnects
+pub struct PubsubConnection {
+    url: String,
+    policy: ReconnectPolicy,
+    router: SubscriptionRouter,
+    connection: Option<Connection>,
+    state: ConnectionState,
+    events: VecDeque<PubsubEvent>,
+}
+
+impl PubsubConnection {
+    /// Open a connection, failing if the first connection attempt does
+    pub async fn connect(
+        url: impl Into<String>,
+        policy: ReconnectPolicy,
+    ) -> Result<Self, PubsubError> {
+        let url = url.into();
+        let connection = Connection::open(&url).await?;
+        Ok(Self {
+            url,
+            policy,
+            router: SubscriptionRouter::default(),
+            connection: Some(connection),
+            state: ConnectionState::Connected,
+            events: VecDeque::new(),
+        })
+    }
+
+    /// Get the websocket URL
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Current connection state
+    pub fn state(&self) -> ConnectionState {
+        self.state
+    }
+
+    /// Subscribe, e.g. `subscribe("slotSubscribe", vec![])`
+    ///
+    /// The confirmation arrives later as [`PubsubEvent::Subscribed`]. While
+    /// reconnecting, the request is sent once the connection is back.
+    pub async fn subscribe(&mut self, method: &str, params: Vec<Value>) -> SubscriptionKey {
+        let key = self.router.subscribe(method, params);
+        self.flush().await;
+        key
+    }
+
+    /// Cancel a subscription, returning `false` if it is not active
+    ///
+    /// No more notifications are delivered for `key` once this returns.
+    pub async fn unsubscribe(&mut self, key: SubscriptionKey) -> bool {
+        let active = self.router.unsubscribe(key);
+        self.flush().await;
+        active
+    }
+
+    /// Wait for the next event
+    ///
+    /// Returns `None` once the connection is [`ConnectionState::Disconnected`]
+    /// and every event before it was delivered.
+    pub async fn next(&mut self) -> Option<PubsubEvent> {
+        loop {
+            if let Some(event) = self.events.pop_front() {
+                return Some(event);
+            }
+            let attempt = match self.state {
+                ConnectionState::Connected => None,
+                ConnectionState::Reconnecting { attempt } => Some(attempt),
+                ConnectionState::Disconnected => return None,
+            };
+            if let Some(attempt) = attempt {
+                self.reconnect(attempt).await;
+                continue;
+            }
+            let Some(connection) = &mut self.connection else {
+                self.connection_lost();
+                continue;
+            };
+
+            match connection.next_message().await {
+                Some(Ok(message)) => {
+                    if let Some(event) = self.router.handle(message) {
+                        self.events.push_back(event);
+                    }
+                    self.flush().await;
+                }
+                // A malformed frame does not mean the connection is lost
+                Some(Err(PubsubError::Parse(_))) => {}
+                _ => self.connection_lost(),
+            }
+        }
+    }
+
+    /// Close the connection without reconnecting
+    pub async fn close(mut self) -> Result<(), PubsubError> {
+        match self.connection.take() {
+            Some(mut connection) => connection.close().await,
+            None => Ok(()),
+        }
+    }
+
+    async fn reconnect(&mut self, attempt: u32) {
+        sleep(self.policy.delay(attempt)).await;
+        match Connection::open(&self.url).await {
+            Ok(connection) => {
+                self.connection = Some(connection);
+                self.router.reconnected();
+                self.set_state(ConnectionState::Connected);
+                self.flush().await;
+            }
+            Err(_) => self.schedule(attempt + 1),
+        }
+    }
+
+    fn connection_lost(&mut self) {
+        self.connection = None;
+        self.schedule(1);
+    }
+
+    fn schedule(&mut self, attempt: u32) {
+        if self.policy.allows(attempt) {
+            self.set_state(ConnectionState::Reconnecting { attempt });
+        } else {
+            self.set_state(ConnectionState::Disconnected);
+        }
+    }
+
+    fn set_state(&mut self, state: ConnectionState) {
+        if self.state != state {
+            self.state = state;
+            self.events.push_back(PubsubEvent::State(state));
+        }
+    }
+
+    /// Send queued requests; without a connection they are dropped, as every
+    /// subscription is requested again after reconnecting
+    async fn flush(&mut self) {
+        for request in self.router.take_outgoing() {
+            let Some(connection) = &mut self.connection else {
+                return;
+            };
+            if connection.send(request).await.is_err() {
+                self.connection_lost();
+                return;
+            }
+        }
+    }
+}
+
+/// A subscription as requested, with its id on the current connection
+struct Entry {
+    method: String,
+    params: Vec<Value>,
+    id: Option<RpcId>,
+    /// Unsubscribed before the node confirmed it
+    cancelled: bool,
+}
+
+enum Pending {
+    Subscribe(SubscriptionKey),
+    Unsubscribe,
+}
+
+/// Correlates requests, responses and notifications of one connection
+#[derive(Default)]
+struct SubscriptionRouter {
+    last_request: u64,
+    last_key: u64,
+    entries: BTreeMap<SubscriptionKey, Entry>,
+    pending: HashMap<RpcId, Pending>,
+    outgoing: Vec<String>,
+}
+
+impl SubscriptionRouter {
+    fn subscribe(&mut self, method: &str, params: Vec<Value>) -> SubscriptionKey {
+        self.last_key += 1;
+        let key = SubscriptionKey(self.last_key);
+        self.request(method, params.clone(), Pending::Subscribe(key));
+        self.entries.insert(
+            key,
+            Entry {
+                method: method.to_string(),
+                params,
+                id: None,
+                cancelled: false,
+            },
+        );
+        key
+    }
+
+    fn unsubscribe(&mut self, key: SubscriptionKey) -> bool {
+        let Some(entry) = self.entries.get_mut(&key) else {
+            return false;
+        };
+        if entry.cancelled {
+            return false;
+        }
+        match entry.id.clone() {
+            Some(id) => {
+                let method = unsubscribe_method(&entry.method);
+                self.entries.remove(&key);
+                self.request(&method, vec![json!(id)], Pending::Unsubscribe);
+            }
+            // Unsubscribed as soon as the confirmation arrives
+            None => entry.cancelled = true,
+        }
+        true
+    }
+
+    fn handle(&mut self, message: PubsubMessage) -> Option<PubsubEvent> {
+        if let Some(params) = message.params {
+            let key = self
+                .entries
+                .iter()
+                .find(|(_, entry)| entry.id.as_ref() == Some(&params.subscription))
+                .map(|(key, _)| *key)?;
+            return Some(PubsubEvent::Notification {
+                key,
+                result: params.result,
+            });
+        }
+
+        let Pending::Subscribe(key) = self.pending.remove(message.id.as_ref()?)? else {
+            return None;
+        };
+        if let Some(error) = message.error {
+            self.entries.remove(&key)?;
+            return Some(PubsubEvent::Rejected {
+                key,
+                error: PubsubError::Rpc {
+                    code: error.code,
+                    message: error.message,
+                },
+            });
+        }
+
+        let id: RpcId = serde_json::from_value(message.result?).ok()?;
+        let entry = self.entries.get_mut(&key)?;
+        if entry.cancelled {
+            let method = unsubscribe_method(&entry.method);
+            self.entries.remove(&key);
+            self.request(&method, vec![json!(id)], Pending::Unsubscribe);
+            return None;
+        }
+        entry.id = Some(id.clone());
+        Some(PubsubEvent::Subscribed { key, id })
+    }
+
+    /// Forget the old connection and request every subscription again
+    fn reconnected(&mut self) {
+        self.pending.clear();
+        self.outgoing.clear();
+        self.entries.retain(|_, entry| !entry.cancelled);
+
+        let requests: Vec<(SubscriptionKey, String, Vec<Value>)> = self
+            .entries
+            .iter_mut()
+            .map(|(key, entry)| {
+                entry.id = None;
+                (*key, entry.method.clone(), entry.params.clone())
+            })
+            .collect();
+        for (key, method, params) in requests {
+            self.request(&method, params, Pending::Subscribe(key));
+        }
+    }
+
+    fn take_outgoing(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.outgoing)
+    }
+
+    fn request(&mut self, method: &str, params: Vec<Value>, pending: Pending) {
+        self.last_request += 1;
+        let mut request = RpcRequest::new(method).id(self.last_request);
+        request.params = params;
+        self.pending.insert(request.id.clone(), pending);
+        self.outgoing.push(json!(request).to_string());
+    }
+}
+
+/// `accountSubscribe` -> `accountUnsubscribe`
+fn unsubscribe_method(method: &str) -> String {
+    method.replace("Subscribe", "Unsubscribe")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::parse_message;
+    use super::*;
+
+    fn handle(router: &mut SubscriptionRouter, text: &str) -> Option<PubsubEvent> {
+        router.handle(parse_message(text).unwrap())
+    }
+
+    #[test]
+    fn test_router_correlates_ids() {
+        let mut router = SubscriptionRouter::default();
+        let slots = router.subscribe("slotSubscribe", Vec::new());
+        let logs = router.subscribe("logsSubscribe", vec![json!("all")]);
+        let requests = router.take_outgoing();
+        assert!(requests[0].contains(r#""id":1"#) && requests[0].contains("slotSubscribe"));
+        assert!(requests[1].contains(r#""id":2"#));
+
+        // Request ids and subscription ids are unrelated numbers
+        let event = handle(&mut router, r#"{"jsonrpc":"2.0","result":1,"id":2}"#);
+        assert!(
+            matches!(event, Some(PubsubEvent::Subscribed { key, id: RpcId::Number(1) }) if key == logs)
+        );
+        let event = handle(&mut router, r#"{"jsonrpc":"2.0","result":"abc","id":1}"#);
+        assert!(matches!(event, Some(PubsubEvent::Subscribed { key, .. }) if key == slots));
+
+        let event = handle(
+            &mut router,
+            r#"{"jsonrpc":"2.0","method":"slotNotification","params":{"result":{"slot":9},"subscription":"abc"}}"#,
+        );
+        assert!(
+            matches!(event, Some(PubsubEvent::Notification { key, ref result }) if key == slots && result["slot"] == 9)
+        );
+        let stray = r#"{"jsonrpc":"2.0","method":"slotNotification","params":{"result":{},"subscription":77}}"#;
+        assert!(handle(&mut router, stray).is_none());
+
+        assert!(router.unsubscribe(logs));
+        assert!(!router.unsubscribe(logs));
+        assert!(router.take_outgoing()[0].contains(r#""method":"logsUnsubscribe","params":[1]"#));
+        let late = r#"{"jsonrpc":"2.0","method":"logsNotification","params":{"result":{},"subscription":1}}"#;
+        assert!(handle(&mut router, late).is_none());
+        assert!(handle(&mut router, r#"{"jsonrpc":"2.0","result":true,"id":3}"#).is_none());
+    }
+
+    #[test]
+    fn test_router_resubscribes_after_reconnect() {
+        let mut router = SubscriptionRouter::default();
+        let account = router.subscribe("accountSubscribe", vec![json!("Acc1")]);
+        let rejected = router.subscribe("blockSubscribe", vec![json!("all")]);
+        let cancelled = router.subscribe("slotSubscribe", Vec::new());
+        router.take_outgoing();
+        handle(&mut router, r#"{"jsonrpc":"2.0","result":5,"id":1}"#);
+        let event = handle(
+            &mut router,
+            r#"{"jsonrpc":"2.0","error":{"code":-32601,"message":"Method not found"},"id":2}"#,
+        );
+        assert!(matches!(event, Some(PubsubEvent::Rejected { key, .. }) if key == rejected));
+        assert!(router.unsubscribe(cancelled));
+
+        router.reconnected();
+        let requests = router.take_outgoing();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].contains(r#""id":4"#) && requests[0].contains("Acc1"));
+
+        // The old id is void until the node confirms a new one
+        let old = r#"{"jsonrpc":"2.0","method":"accountNotification","params":{"result":{},"subscription":5}}"#;
+        assert!(handle(&mut router, old).is_none());
+        let event = handle(&mut router, r#"{"jsonrpc":"2.0","result":8,"id":4}"#);
+        assert!(
+            matches!(event, Some(PubsubEvent::Subscribed { key, id: RpcId::Number(8) }) if key == account)
+        );
+    }
+
+    #[test]
+    fn test_reconnect_backoff() {
+        let policy = ReconnectPolicy::new()
+            .initial_delay(Duration::from_millis(100))
+            .max_delay(Duration::from_secs(1))
+            .max_attempts(6);
+        let delays: Vec<u64> = (1..=6)
+            .map(|attempt| policy.delay(attempt).as_millis() as u64)
+            .collect();
+        assert_eq!(delays, [100, 200, 400, 800, 1000, 1000]);
+        assert!(policy.allows(6));
+        assert!(!policy.allows(7));
+        assert!(!ReconnectPolicy::never().allows(1));
+        assert_eq!(policy.delay(u32::MAX), Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,92 @@
+//! Client-side rate limiting
+//!
+//! A token bucket shared by every clone of a
+//! [`SolanaRpcClient`](crate::infrastructure::rpc::SolanaRpcClient), so public
+//! endpoints are not hammered past their published limits.
+
+use crate::infrastructure::time::{self, Instant};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Token bucket rate limiter
+#[derive(Debug)]
+pub struct RateLimiter {
+    requests_per_second: f64,
+    burst: f64,
+    state: Mutex<BucketState>,
+}
+
+#[derive(Debug)]
+struct BucketState {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl RateLimiter {
+    /// Create a limiter allowing `requests_per_second` with a burst of the same size
+    pub fn new(requests_per_second: u32) -> Self {
+        Self::with_burst(requests_per_second, requests_per_second)
+    }
+
+    /// Create a limiter with an explicit burst size
+    pub fn with_burst(requests_per_second: u32, burst: u32) -> Self {
+        let burst = f64::from(burst.max(1));
+        Self {
+            requests_per_second: f64::from(requests_per_second.max(1)),
+            burst,
+            state: Mutex::new(BucketState {
+                tokens: burst,
+                last_refill: Instant::now(),
+            }),
+        }
+    }
+
+    /// Try to take a token, returning how long to wait if none is available
+    pub fn try_acquire(&self) -> Result<(), Duration> {
+        let mut state = match self.state.lock() {
+            Ok(state) => state,
+            Err(_) => return Ok(()),
+        };
+
+        let now = Instant::now();
+        let elapsed = now.duration_since(state.last_refill).as_secs_f64();
+        state.tokens = (state.tokens + elapsed * self.requests_per_second).min(self.burst);
+        state.last_refill = now;
+
+        if state.tokens >= 1.0 {
+            state.tokens -= 1.0;
+            Ok(())
+        } else {
+            let missing = 1.0 - state.tokens;
+            Err(Duration::from_secs_f64(missing / self.requests_per_second))
+        }
+    }
+
+    /// Wait until a token is available
+    pub async fn acquire(&self) {
+        while let Err(wait) = self.try_acquire() {
+            time::sleep(wait).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_burst_then_limited() {
+        let limiter = RateLimiter::with_burst(1, 2);
+        assert!(limiter.try_acquire().is_ok());
+        assert!(limiter.try_acquire().is_ok());
+        assert!(limiter.try_acquire().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_acquire_waits_for_refill() {
+        let limiter = RateLimiter::new(200);
+        for _ in 0..201 {
+            limiter.acquire().await;
+        }
+    }
+}
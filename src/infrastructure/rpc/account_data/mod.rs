@@ -0,0 +1,206 @@
+//! Account data encodings
+//!
+//! `getAccountInfo`, `getMultipleAccounts` and `getProgramAccounts` return
+//! account data as a `[data, encoding]` pair for binary encodings, or as a
+//! parsed object when `jsonParsed` is requested and the node knows the
+//! account's layout. [`AccountData`] accepts every form and decodes the
+//! binary ones, including zstd-compressed data, back to raw bytes.
+
+use base64::{engine::general_purpose, Engine as _};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use std::io::Read;
+use thiserror::Error;
+
+/// Encoding of binary account data
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AccountEncoding {
+    #[serde(rename = "base58")]
+    Base58,
+    #[serde(rename = "base64")]
+    Base64,
+    /// Base64 of the zstd-compressed data
+    #[serde(rename = "base64+zstd")]
+    Base64Zstd,
+    /// Parsed JSON, with a base64 fallback for unknown layouts
+    #[serde(rename = "jsonParsed")]
+    JsonParsed,
+}
+
+impl fmt::Display for AccountEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Base58 => "base58",
+            Self::Base64 => "base64",
+            Self::Base64Zstd => "base64+zstd",
+            Self::JsonParsed => "jsonParsed",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Errors decoding account data
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum AccountDataError {
+    #[error("Invalid {encoding} account data: {message}")]
+    Invalid {
+        encoding: AccountEncoding,
+        message: String,
+    },
+
+    #[error("Account data was returned parsed by {program}, not as bytes")]
+    Parsed { program: String },
+}
+
+/// Account data parsed by the node under `jsonParsed`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParsedAccount {
+    /// Name of the program whose layout was used, e.g. `spl-token`
+    pub program: String,
+    pub parsed: Value,
+    /// Size of the account data in bytes
+    pub space: u64,
+}
+
+/// Account data in any of the forms the RPC returns
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum AccountData {
+    /// `[data, encoding]`, used by every binary encoding
+    Encoded(String, AccountEncoding),
+    /// Output of `jsonParsed` for accounts the node can parse
+    Parsed(ParsedAccount),
+    /// Bare base58 string of the deprecated `binary` encoding
+    Legacy(String),
+}
+
+impl AccountData {
+    /// The raw account bytes
+    pub fn decode(&self) -> Result<Vec<u8>, AccountDataError> {
+        match self {
+            Self::Encoded(data, encoding) => decode(data, *encoding),
+            Self::Legacy(data) => decode(data, AccountEncoding::Base58),
+            Self::Parsed(account) => Err(AccountDataError::Parsed {
+                program: account.program.clone(),
+            }),
+        }
+    }
+
+    /// The node's parsed representation, if it returned one
+    pub fn parsed(&self) -> Option<&ParsedAccount> {
+        match self {
+            Self::Parsed(account) => Some(account),
+            _ => None,
+        }
+    }
+}
+
+fn decode(data: &str, encoding: AccountEncoding) -> Result<Vec<u8>, AccountDataError> {
+    let invalid = |message: String| AccountDataError::Invalid { encoding, message };
+    match encoding {
+        AccountEncoding::Base58 => bs58::decode(data)
+            .into_vec()
+            .map_err(|e| invalid(e.to_string())),
+        // Unparseable accounts fall back to base64 under jsonParsed
+        AccountEncoding::Base64 | AccountEncoding::JsonParsed => general_purpose::STANDARD
+            .decode(data)
+            .map_err(|e| invalid(e.to_string())),
+        AccountEncoding::Base64Zstd => {
+            let compressed = general_purpose::STANDARD
+                .decode(data)
+                .map_err(|e| invalid(e.to_string()))?;
+            decompress(&compressed).map_err(invalid)
+        }
+    }
+}
+
+/// Decompress a zstd frame with a pure-Rust decoder that also runs on wasm
+fn decompress(compressed: &[u8]) -> Result<Vec<u8>, String> {
+    let mut source = compressed;
+    let mut decoder = ruzstd::StreamingDecoder::new(&mut source).map_err(|e| e.to_string())?;
+    let mut data = Vec::new();
+    decoder.read_to_end(&mut data).map_err(|e| e.to_string())?;
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// The USDC mint with mainnet's authorities and an example supply
+    const USDC_MINT_BASE64: &str = "AQAAAJj+huiNm+Lqi8HMpIeLKYjCQPUrhCS/tA7Rot3LXhmbsDkznjXVHwAGAQEAAABicKqKWcWUBbRShshncubNEm6bil06OFNtN/e0FOi2Zw==";
+
+    fn usdc_mint_bytes() -> Vec<u8> {
+        general_purpose::STANDARD.decode(USDC_MINT_BASE64).unwrap()
+    }
+
+    #[test]
+    fn test_binary_encodings() {
+        let base64: AccountData =
+            serde_json::from_value(json!([USDC_MINT_BASE64, "base64"])).unwrap();
+        let bytes = base64.decode().unwrap();
+        assert_eq!(bytes.len(), 82);
+        assert_eq!(bytes[44], 6, "USDC has 6 decimals");
+
+        let zstd: AccountData = serde_json::from_value(json!([
+            "KLUv/SRSkQIAAQAAAJj+huiNm+Lqi8HMpIeLKYjCQPUrhCS/tA7Rot3LXhmbsDkznjXVHwAGAQEAAABicKqKWcWUBbRShshncubNEm6bil06OFNtN/e0FOi2Z0rZ8s8=",
+            "base64+zstd"
+        ]))
+        .unwrap();
+        assert_eq!(zstd.decode().unwrap(), usdc_mint_bytes());
+        let zeroed: AccountData =
+            serde_json::from_value(json!(["KLUv/STITQAAEAAAAQBDCmABT3XqAA==", "base64+zstd"]))
+                .unwrap();
+        assert_eq!(zeroed.decode().unwrap(), vec![0; 200]);
+
+        let base58: AccountData = serde_json::from_value(json!(["7bWpTW", "base58"])).unwrap();
+        assert_eq!(base58.decode().unwrap(), vec![1, 2, 3, 4, 5]);
+        let empty: AccountData = serde_json::from_value(json!(["", "base58"])).unwrap();
+        assert_eq!(empty.decode().unwrap(), Vec::<u8>::new());
+        let legacy: AccountData = serde_json::from_value(json!("7bWpTW")).unwrap();
+        assert_eq!(legacy.decode().unwrap(), vec![1, 2, 3, 4, 5]);
+
+        let corrupt: AccountData =
+            serde_json::from_value(json!(["KLUv/Q==", "base64+zstd"])).unwrap();
+        assert!(matches!(
+            corrupt.decode(),
+            Err(AccountDataError::Invalid {
+                encoding: AccountEncoding::Base64Zstd,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_json_parsed() {
+        let parsed: AccountData = serde_json::from_value(json!({
+            "program": "spl-token",
+            "parsed": {
+                "type": "mint",
+                "info": {
+                    "decimals": 6,
+                    "freezeAuthority": "7dGbd2QZcCKcTndnHcTL8q7SMVXAkp688NTQYwrRCrar",
+                    "isInitialized": true,
+                    "mintAuthority": "BJE5MMbqXjVwjAF7oxwPYXnTXDyspzZyt4vwenNw5ruG",
+                    "supply": "8960150542170544"
+                }
+            },
+            "space": 82
+        }))
+        .unwrap();
+        let account = parsed.parsed().unwrap();
+        assert_eq!(account.parsed["info"]["decimals"], 6);
+        assert!(matches!(
+            parsed.decode(),
+            Err(AccountDataError::Parsed { program }) if program == "spl-token"
+        ));
+
+        // Layouts the node cannot parse come back as base64
+        let fallback: AccountData =
+            serde_json::from_value(json!([USDC_MINT_BASE64, "base64"])).unwrap();
+        assert!(fallback.parsed().is_none());
+        assert_eq!(fallback.decode().unwrap(), usdc_mint_bytes());
+    }
+}
@@ -0,0 +1,340 @@
+//! Object-safe RPC interface
+//!
+//! Application services depend on [`RpcApi`] rather than on the concrete
+//! [`SolanaRpcClient`], so alternate transports (cached, batched, mocked) can
+//! be injected as `Arc<dyn RpcApi>`.
+
+use super::{
+    Account, AccountReadConfig, ConfirmedBlock, ConfirmedTransaction, EpochInfo, Filter,
+    InflationReward, LatestBlockhash, RpcError, SignatureInfo, SignatureStatus,
+    SignaturesForAddressConfig, SimulationResult, SolanaRpcClient, TokenAccountBalance,
+    UiTokenAmount, WithContext,
+};
+use crate::domain::types::{Pubkey, Signature};
+use async_trait::async_trait;
+
+/// Most accounts a node returns per `getMultipleAccounts` request
+pub const MAX_ACCOUNTS_PER_REQUEST: usize = 100;
+
+/// Most signatures a node returns per `getSignaturesForAddress` request
+pub const MAX_SIGNATURES_PER_REQUEST: usize = 1000;
+
+/// The JSON-RPC operations used by application services
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait RpcApi: Send + Sync {
+    /// Get account information
+    async fn get_account_info(&self, pubkey: &Pubkey) -> Result<Option<Account>, RpcError>;
+
+    /// Get account information at or after `min_context_slot`
+    async fn get_account_info_with_context(
+        &self,
+        pubkey: &Pubkey,
+        min_context_slot: Option<u64>,
+    ) -> Result<WithContext<Option<Account>>, RpcError>;
+
+    /// Get account information read with `config`
+    async fn get_account_info_with_config(
+        &self,
+        pubkey: &Pubkey,
+        config: &AccountReadConfig,
+    ) -> Result<WithContext<Option<Account>>, RpcError>;
+
+    /// Get account balance
+    async fn get_balance(&self, pubkey: &Pubkey) -> Result<u64, RpcError>;
+
+    /// Get account balance at or after `min_context_slot`
+    async fn get_balance_with_context(
+        &self,
+        pubkey: &Pubkey,
+        min_context_slot: Option<u64>,
+    ) -> Result<WithContext<u64>, RpcError>;
+
+    /// Get the latest blockhash
+    async fn get_latest_blockhash(&self) -> Result<LatestBlockhash, RpcError>;
+
+    /// Send a base64-encoded transaction
+    async fn send_transaction(&self, transaction: &str) -> Result<Signature, RpcError>;
+
+    /// Send a signed transaction given as its wire bytes
+    async fn send_raw_transaction(&self, transaction: &[u8]) -> Result<Signature, RpcError>;
+
+    /// Send a signed transaction encoded as base58 or base64
+    async fn send_raw_transaction_base58(&self, transaction: &str) -> Result<Signature, RpcError>;
+
+    /// Get the current block height
+    async fn get_block_height(&self) -> Result<u64, RpcError>;
+
+    /// Get the current slot
+    async fn get_slot(&self) -> Result<u64, RpcError>;
+
+    /// Get the current epoch and the position in it
+    async fn get_epoch_info(&self) -> Result<EpochInfo, RpcError>;
+
+    /// Get multiple accounts
+    async fn get_multiple_accounts(
+        &self,
+        pubkeys: &[Pubkey],
+    ) -> Result<Vec<Option<Account>>, RpcError>;
+
+    /// Get multiple accounts at or after `min_context_slot`
+    async fn get_multiple_accounts_with_context(
+        &self,
+        pubkeys: &[Pubkey],
+        min_context_slot: Option<u64>,
+    ) -> Result<WithContext<Vec<Option<Account>>>, RpcError>;
+
+    /// Get multiple accounts read with `config`
+    async fn get_multiple_accounts_with_config(
+        &self,
+        pubkeys: &[Pubkey],
+        config: &AccountReadConfig,
+    ) -> Result<WithContext<Vec<Option<Account>>>, RpcError>;
+
+    /// Get all accounts owned by a program that match every filter
+    async fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        filters: &[Filter],
+    ) -> Result<Vec<Account>, RpcError>;
+
+    /// Get signatures of transactions involving an address, newest first
+    async fn get_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        config: SignaturesForAddressConfig,
+    ) -> Result<Vec<SignatureInfo>, RpcError>;
+
+    /// Get the processing status of transactions, in the order given
+    async fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> Result<Vec<Option<SignatureStatus>>, RpcError>;
+
+    /// Get a confirmed transaction
+    async fn get_transaction(
+        &self,
+        signature: &Signature,
+    ) -> Result<Option<ConfirmedTransaction>, RpcError>;
+
+    /// Get the 20 largest token accounts of a mint
+    async fn get_token_largest_accounts(
+        &self,
+        mint: &Pubkey,
+    ) -> Result<Vec<TokenAccountBalance>, RpcError>;
+
+    /// Get the total supply of a mint
+    async fn get_token_supply(&self, mint: &Pubkey) -> Result<UiTokenAmount, RpcError>;
+
+    /// Get the block at `slot`, `None` if the slot was skipped
+    async fn get_block(&self, slot: u64) -> Result<Option<ConfirmedBlock>, RpcError>;
+
+    /// Get the production time of the block at `slot`, `None` if unknown
+    async fn get_block_time(&self, slot: u64) -> Result<Option<i64>, RpcError>;
+
+    /// Get the staking rewards credited to `addresses` for `epoch`
+    async fn get_inflation_reward(
+        &self,
+        addresses: &[Pubkey],
+        epoch: Option<u64>,
+    ) -> Result<Vec<Option<InflationReward>>, RpcError>;
+
+    /// Get the smallest delegation, in lamports, the stake program accepts
+    async fn get_stake_minimum_delegation(&self) -> Result<u64, RpcError>;
+
+    /// Get the fee for a base64-encoded message, `None` if its blockhash expired
+    async fn get_fee_for_message(&self, message: &str) -> Result<Option<u64>, RpcError>;
+
+    /// Get the lamports an account holding `data_len` bytes needs to be rent exempt
+    async fn get_minimum_balance_for_rent_exemption(
+        &self,
+        data_len: usize,
+    ) -> Result<u64, RpcError>;
+
+    /// Simulate a base64-encoded transaction without sending it
+    async fn simulate_transaction(
+        &self,
+        transaction: &str,
+    ) -> Result<WithContext<SimulationResult>, RpcError>;
+
+    /// Simulate a transaction and return the state of `addresses` after it
+    async fn simulate_transaction_with_accounts(
+        &self,
+        transaction: &str,
+        addresses: &[Pubkey],
+    ) -> Result<WithContext<SimulationResult>, RpcError>;
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl RpcApi for SolanaRpcClient {
+    async fn get_account_info(&self, pubkey: &Pubkey) -> Result<Option<Account>, RpcError> {
+        SolanaRpcClient::get_account_info(self, pubkey).await
+    }
+
+    async fn get_account_info_with_context(
+        &self,
+        pubkey: &Pubkey,
+        min_context_slot: Option<u64>,
+    ) -> Result<WithContext<Option<Account>>, RpcError> {
+        SolanaRpcClient::get_account_info_with_context(self, pubkey, min_context_slot).await
+    }
+
+    async fn get_account_info_with_config(
+        &self,
+        pubkey: &Pubkey,
+        config: &AccountReadConfig,
+    ) -> Result<WithContext<Option<Account>>, RpcError> {
+        SolanaRpcClient::get_account_info_with_config(self, pubkey, config).await
+    }
+
+    async fn get_balance(&self, pubkey: &Pubkey) -> Result<u64, RpcError> {
+        SolanaRpcClient::get_balance(self, pubkey).await
+    }
+
+    async fn get_balance_with_context(
+        &self,
+        pubkey: &Pubkey,
+        min_context_slot: Option<u64>,
+    ) -> Result<WithContext<u64>, RpcError> {
+        SolanaRpcClient::get_balance_with_context(self, pubkey, min_context_slot).await
+    }
+
+    async fn get_latest_blockhash(&self) -> Result<LatestBlockhash, RpcError> {
+        SolanaRpcClient::get_latest_blockhash(self).await
+    }
+
+    async fn send_transaction(&self, transaction: &str) -> Result<Signature, RpcError> {
+        SolanaRpcClient::send_transaction(self, transaction).await
+    }
+
+    async fn send_raw_transaction(&self, transaction: &[u8]) -> Result<Signature, RpcError> {
+        SolanaRpcClient::send_raw_transaction(self, transaction).await
+    }
+
+    async fn send_raw_transaction_base58(&self, transaction: &str) -> Result<Signature, RpcError> {
+        SolanaRpcClient::send_raw_transaction_base58(self, transaction).await
+    }
+
+    async fn get_block_height(&self) -> Result<u64, RpcError> {
+        SolanaRpcClient::get_block_height(self).await
+    }
+
+    async fn get_slot(&self) -> Result<u64, RpcError> {
+        SolanaRpcClient::get_slot(self).await
+    }
+
+    async fn get_epoch_info(&self) -> Result<EpochInfo, RpcError> {
+        SolanaRpcClient::get_epoch_info(self).await
+    }
+
+    async fn get_multiple_accounts(
+        &self,
+        pubkeys: &[Pubkey],
+    ) -> Result<Vec<Option<Account>>, RpcError> {
+        SolanaRpcClient::get_multiple_accounts(self, pubkeys).await
+    }
+
+    async fn get_multiple_accounts_with_context(
+        &self,
+        pubkeys: &[Pubkey],
+        min_context_slot: Option<u64>,
+    ) -> Result<WithContext<Vec<Option<Account>>>, RpcError> {
+        SolanaRpcClient::get_multiple_accounts_with_context(self, pubkeys, min_context_slot).await
+    }
+
+    async fn get_multiple_accounts_with_config(
+        &self,
+        pubkeys: &[Pubkey],
+        config: &AccountReadConfig,
+    ) -> Result<WithContext<Vec<Option<Account>>>, RpcError> {
+        SolanaRpcClient::get_multiple_accounts_with_config(self, pubkeys, config).await
+    }
+
+    async fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        filters: &[Filter],
+    ) -> Result<Vec<Account>, RpcError> {
+        SolanaRpcClient::get_program_accounts(self, program_id, filters).await
+    }
+
+    async fn get_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        config: SignaturesForAddressConfig,
+    ) -> Result<Vec<SignatureInfo>, RpcError> {
+        SolanaRpcClient::get_signatures_for_address(self, address, config).await
+    }
+
+    async fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> Result<Vec<Option<SignatureStatus>>, RpcError> {
+        SolanaRpcClient::get_signature_statuses(self, signatures).await
+    }
+
+    async fn get_transaction(
+        &self,
+        signature: &Signature,
+    ) -> Result<Option<ConfirmedTransaction>, RpcError> {
+        SolanaRpcClient::get_transaction(self, signature).await
+    }
+
+    async fn get_token_largest_accounts(
+        &self,
+        mint: &Pubkey,
+    ) -> Result<Vec<TokenAccountBalance>, RpcError> {
+        SolanaRpcClient::get_token_largest_accounts(self, mint).await
+    }
+
+    async fn get_token_supply(&self, mint: &Pubkey) -> Result<UiTokenAmount, RpcError> {
+        SolanaRpcClient::get_token_supply(self, mint).await
+    }
+
+    async fn get_block(&self, slot: u64) -> Result<Option<ConfirmedBlock>, RpcError> {
+        SolanaRpcClient::get_block(self, slot).await
+    }
+
+    async fn get_block_time(&self, slot: u64) -> Result<Option<i64>, RpcError> {
+        SolanaRpcClient::get_block_time(self, slot).await
+    }
+
+    async fn get_inflation_reward(
+        &self,
+        addresses: &[Pubkey],
+        epoch: Option<u64>,
+    ) -> Result<Vec<Option<InflationReward>>, RpcError> {
+        SolanaRpcClient::get_inflation_reward(self, addresses, epoch).await
+    }
+
+    async fn get_stake_minimum_delegation(&self) -> Result<u64, RpcError> {
+        SolanaRpcClient::get_stake_minimum_delegation(self).await
+    }
+
+    async fn get_fee_for_message(&self, message: &str) -> Result<Option<u64>, RpcError> {
+        SolanaRpcClient::get_fee_for_message(self, message).await
+    }
+
+    async fn get_minimum_balance_for_rent_exemption(
+        &self,
+        data_len: usize,
+    ) -> Result<u64, RpcError> {
+        SolanaRpcClient::get_minimum_balance_for_rent_exemption(self, data_len).await
+    }
+
+    async fn simulate_transaction(
+        &self,
+        transaction: &str,
+    ) -> Result<WithContext<SimulationResult>, RpcError> {
+        SolanaRpcClient::simulate_transaction(self, transaction).await
+    }
+
+    async fn simulate_transaction_with_accounts(
+        &self,
+        transaction: &str,
+        addresses: &[Pubkey],
+    ) -> Result<WithContext<SimulationResult>, RpcError> {
+        SolanaRpcClient::simulate_transaction_with_accounts(self, transaction, addresses).await
+    }
+}
@@ -0,0 +1,134 @@
+//! Surfpool cheatcodes
+//!
+//! Surfpool serves `surfnet_*` methods that write ledger state directly, which
+//! is how local tests fund wallets and create token balances without sending
+//! transactions. [`Cheatcodes`] wraps them; the client implementation checks
+//! [`ClusterCapabilities`](crate::infrastructure::cluster::ClusterCapabilities)
+//! first, so calling one against any other cluster fails with
+//! [`RpcError::Unsupported`].
+
+use super::{RpcError, RpcRequest, SolanaRpcClient};
+use crate::domain::types::Pubkey;
+use async_trait::async_trait;
+use serde_json::{json, Map, Value};
+use std::fmt::Write as _;
+
+/// Fields of an account to overwrite; `None` keeps the current value
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AccountUpdate {
+    pub lamports: Option<u64>,
+    pub data: Option<Vec<u8>>,
+    pub owner: Option<Pubkey>,
+    pub executable: Option<bool>,
+}
+
+impl AccountUpdate {
+    /// Update that only sets the balance
+    pub fn lamports(lamports: u64) -> Self {
+        Self {
+            lamports: Some(lamports),
+            ..Self::default()
+        }
+    }
+
+    /// The update as `surfnet_setAccount` expects it, with hex-encoded data
+    fn to_json(&self) -> Value {
+        let mut update = Map::new();
+        if let Some(lamports) = self.lamports {
+            update.insert("lamports".to_string(), json!(lamports));
+        }
+        if let Some(data) = &self.data {
+            update.insert("data".to_string(), json!(hex_encode(data)));
+        }
+        if let Some(owner) = &self.owner {
+            update.insert("owner".to_string(), json!(owner.to_base58()));
+        }
+        if let Some(executable) = self.executable {
+            update.insert("executable".to_string(), json!(executable));
+        }
+        Value::Object(update)
+    }
+}
+
+/// State writes served by surfpool
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait Cheatcodes: Send + Sync {
+    /// Create or overwrite an account
+    async fn set_account(&self, pubkey: &Pubkey, update: &AccountUpdate) -> Result<(), RpcError>;
+
+    /// Set `owner`'s associated token account balance of `mint`, creating it
+    /// if needed
+    async fn set_token_balance(
+        &self,
+        owner: &Pubkey,
+        mint: &Pubkey,
+        amount: u64,
+        token_program: &Pubkey,
+    ) -> Result<(), RpcError>;
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl Cheatcodes for SolanaRpcClient {
+    async fn set_account(&self, pubkey: &Pubkey, update: &AccountUpdate) -> Result<(), RpcError> {
+        let method = "surfnet_setAccount";
+        self.cluster_capabilities().await?.check(method)?;
+
+        let request = RpcRequest::new(method)
+            .param(pubkey.to_base58())
+            .param(update.to_json());
+        let _: Value = self.send(request).await?;
+        Ok(())
+    }
+
+    async fn set_token_balance(
+        &self,
+        owner: &Pubkey,
+        mint: &Pubkey,
+        amount: u64,
+        token_program: &Pubkey,
+    ) -> Result<(), RpcError> {
+        let method = "surfnet_setTokenAccount";
+        self.cluster_capabilities().await?.check(method)?;
+
+        let request = RpcRequest::new(method)
+            .param(owner.to_base58())
+            .param(mint.to_base58())
+            .param(json!({ "amount": amount }))
+            .param(token_program.to_base58());
+        let _: Value = self.send(request).await?;
+        Ok(())
+    }
+}
+
+fn hex_encode(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        let _ = write!(encoded, "{:02x}", byte);
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_account_update_json() {
+        let update = AccountUpdate {
+            data: Some(vec![0x00, 0xab, 0x10]),
+            owner: Some(Pubkey::new([0; 32])),
+            ..AccountUpdate::lamports(5)
+        };
+        assert_eq!(
+            update.to_json(),
+            json!({
+                "lamports": 5,
+                "data": "00ab10",
+                "owner": "11111111111111111111111111111111"
+            })
+        );
+        assert_eq!(AccountUpdate::default().to_json(), json!({}));
+    }
+}
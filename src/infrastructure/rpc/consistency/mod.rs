@@ -0,0 +1,197 @@
+//! Slot-consistent multi-read operations
+//!
+//! A [`ConsistentReader`] pins a group of related reads to one
+//! `minContextSlot`: the slot of the first response is passed to every later
+//! read, and reads answered by a node that has not caught up yet are retried.
+//! Composite views such as "balances + token accounts + program state" then
+//! never mix data from before and after a slot the first read already saw.
+
+use super::{Account, RpcApi, RpcError, SolanaRpcClient, WithContext};
+use crate::domain::types::Pubkey;
+use crate::infrastructure::time::sleep;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Default number of retries when a node is behind the pinned slot
+pub const DEFAULT_MAX_RETRIES: u32 = 5;
+
+/// Default delay between retries, roughly one slot
+pub const DEFAULT_RETRY_DELAY: Duration = Duration::from_millis(400);
+
+/// Reads that all observe state at or after a shared slot
+///
+/// Reads may run concurrently; the first one to complete pins the slot.
+pub struct ConsistentReader {
+    rpc_client: Arc<dyn RpcApi>,
+    min_context_slot: Mutex<Option<u64>>,
+    max_retries: u32,
+    retry_delay: Duration,
+}
+
+impl ConsistentReader {
+    /// Create a reader with no slot pinned yet
+    pub fn new(rpc_client: impl RpcApi + 'static) -> Self {
+        Self::from_api(Arc::new(rpc_client))
+    }
+
+    /// Create a reader from a shared RPC implementation
+    pub fn from_api(rpc_client: Arc<dyn RpcApi>) -> Self {
+        Self {
+            rpc_client,
+            min_context_slot: Mutex::new(None),
+            max_retries: DEFAULT_MAX_RETRIES,
+            retry_delay: DEFAULT_RETRY_DELAY,
+        }
+    }
+
+    /// Pin reads to a known slot up front instead of the first response
+    pub fn at_slot(self, slot: u64) -> Self {
+        *self.lock() = Some(slot);
+        self
+    }
+
+    /// Set how often a read is retried while the node is behind
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Set the delay between retries
+    pub fn retry_delay(mut self, retry_delay: Duration) -> Self {
+        self.retry_delay = retry_delay;
+        self
+    }
+
+    /// The slot reads are pinned to, once known
+    pub fn slot(&self) -> Option<u64> {
+        *self.lock()
+    }
+
+    /// Get account information
+    pub async fn get_account_info(&self, pubkey: &Pubkey) -> Result<Option<Account>, RpcError> {
+        self.read(|slot| self.rpc_client.get_account_info_with_context(pubkey, slot))
+            .await
+    }
+
+    /// Get account balance
+    pub async fn get_balance(&self, pubkey: &Pubkey) -> Result<u64, RpcError> {
+        self.read(|slot| self.rpc_client.get_balance_with_context(pubkey, slot))
+            .await
+    }
+
+    /// Get multiple accounts
+    pub async fn get_multiple_accounts(
+        &self,
+        pubkeys: &[Pubkey],
+    ) -> Result<Vec<Option<Account>>, RpcError> {
+        self.read(|slot| {
+            self.rpc_client
+                .get_multiple_accounts_with_context(pubkeys, slot)
+        })
+        .await
+    }
+
+    /// Run one read at the pinned slot, retrying while the node is behind
+    async fn read<T, F, Fut>(&self, call: F) -> Result<T, RpcError>
+    where
+        F: Fn(Option<u64>) -> Fut,
+        Fut: Future<Output = Result<WithContext<T>, RpcError>>,
+    {
+        let mut attempt = 0;
+        loop {
+            match call(self.slot()).await {
+                Ok(response) => {
+                    self.lock().get_or_insert(response.context.slot);
+                    return Ok(response.value);
+                }
+                Err(error)
+                    if error.is_min_context_slot_not_reached() && attempt < self.max_retries =>
+                {
+                    attempt += 1;
+                    sleep(self.retry_delay).await;
+                }
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<u64>> {
+        self.min_context_slot
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl SolanaRpcClient {
+    /// Start a group of reads pinned to a common `minContextSlot`
+    pub fn consistent_reads(&self) -> ConsistentReader {
+        ConsistentReader::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::rpc::mock::MockRpc;
+
+    /// A node at `slot` that lags behind for the first `lagging` requests
+    fn lagging_rpc(slot: u64, lagging: u32) -> Arc<MockRpc> {
+        Arc::new(
+            MockRpc::new()
+                .with_slot(slot)
+                .with_balance(Pubkey::new([1; 32]), 42)
+                .lagging(lagging),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_first_response_pins_slot() {
+        let rpc = lagging_rpc(300, 0);
+        let reader = ConsistentReader::from_api(rpc.clone());
+        assert_eq!(reader.slot(), None);
+
+        assert_eq!(reader.get_balance(&Pubkey::new([1; 32])).await.unwrap(), 42);
+        assert_eq!(reader.slot(), Some(300));
+
+        let keys = [Pubkey::new([2; 32]), Pubkey::new([3; 32])];
+        assert_eq!(reader.get_multiple_accounts(&keys).await.unwrap().len(), 2);
+        assert_eq!(
+            reader
+                .get_account_info(&keys[0])
+                .await
+                .unwrap()
+                .map(|a| a.pubkey),
+            None
+        );
+
+        assert_eq!(
+            *rpc.min_context_slots.lock().unwrap(),
+            vec![None, Some(300), Some(300)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_retries_while_node_is_behind() {
+        let rpc = lagging_rpc(500, 2);
+        let reader = ConsistentReader::from_api(rpc.clone())
+            .at_slot(450)
+            .retry_delay(Duration::from_millis(1));
+
+        assert_eq!(reader.get_balance(&Pubkey::new([1; 32])).await.unwrap(), 42);
+        assert_eq!(rpc.min_context_slots.lock().unwrap().len(), 3);
+        // A slot given up front is kept even though the node answered later
+        assert_eq!(reader.slot(), Some(450));
+
+        let rpc = lagging_rpc(500, 5);
+        let reader = ConsistentReader::from_api(rpc)
+            .at_slot(450)
+            .max_retries(1)
+            .retry_delay(Duration::from_millis(1));
+        assert!(reader
+            .get_balance(&Pubkey::new([1; 32]))
+            .await
+            .unwrap_err()
+            .is_min_context_slot_not_reached());
+    }
+}
@@ -0,0 +1,63 @@
+//! Commitment and minimum slot of a read
+//!
+//! Most read methods take the same two optional settings: the commitment to
+//! read at and the slot the node must have reached before answering.
+//! [`RpcContextConfig`] carries both to every `*_with_config` method of
+//! [`SolanaRpcClient`](super::SolanaRpcClient), which falls back to the
+//! commitment set on its builder when none is given.
+
+use super::CommitmentLevel;
+use serde::Serialize;
+
+/// Commitment and minimum context slot of a request
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcContextConfig {
+    /// Commitment to read at, the client's default when `None`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commitment: Option<CommitmentLevel>,
+    /// Refuse to answer before the node reaches this slot
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_context_slot: Option<u64>,
+}
+
+impl RpcContextConfig {
+    /// Read at the client's default commitment, from any slot
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the commitment
+    pub fn commitment(mut self, commitment: CommitmentLevel) -> Self {
+        self.commitment = Some(commitment);
+        self
+    }
+
+    /// Set the minimum context slot
+    pub fn min_context_slot(mut self, slot: u64) -> Self {
+        self.min_context_slot = Some(slot);
+        self
+    }
+
+    /// These settings, reading at `commitment` unless one is already set
+    pub fn or_commitment(mut self, commitment: Option<CommitmentLevel>) -> Self {
+        self.commitment = self.commitment.or(commitment);
+        self
+    }
+
+    /// Whether neither setting is given
+    pub fn is_empty(&self) -> bool {
+        self.commitment.is_none() && self.min_context_slot.is_none()
+    }
+
+    /// The config object to send, `None` when there is nothing to send
+    pub(crate) fn to_param(self) -> Option<serde_json::Value> {
+        (!self.is_empty()).then(|| serde_json::json!(self))
+    }
+}
+
+impl From<CommitmentLevel> for RpcContextConfig {
+    fn from(commitment: CommitmentLevel) -> Self {
+        Self::new().commitment(commitment)
+    }
+}
@@ -0,0 +1,347 @@
+//! `getProgramAccounts` filters
+//!
+//! Typed `dataSize` and `memcmp` filters with constructors for raw bytes,
+//! pubkeys, pre-encoded base58/base64 strings and Borsh-encoded values. Field
+//! offsets can be looked up by name through [`AccountLayout`] so filters stay
+//! correct when a struct gains or reorders fields.
+
+use crate::domain::layout::AccountLayout;
+use crate::domain::types::Pubkey;
+use base64::{engine::general_purpose, Engine as _};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Largest memcmp payload the RPC accepts, in bytes
+pub const MAX_MEMCMP_BYTES: usize = 128;
+
+/// A `getProgramAccounts` filter
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Filter {
+    /// Match accounts whose data is exactly this many bytes
+    DataSize(u64),
+    /// Match accounts whose data contains bytes at an offset
+    Memcmp(Memcmp),
+}
+
+/// A memcmp filter
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Memcmp {
+    pub offset: usize,
+    pub bytes: String,
+    #[serde(default)]
+    pub encoding: MemcmpEncoding,
+}
+
+/// Encoding of a memcmp filter's `bytes` field
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MemcmpEncoding {
+    #[default]
+    Base58,
+    Base64,
+}
+
+/// Errors that can occur while building filters
+#[derive(Debug, Clone, Error)]
+pub enum FilterError {
+    #[error("Unknown field '{field}' in layout {layout}")]
+    UnknownField { layout: &'static str, field: String },
+
+    #[error("Field '{field}' is {expected} bytes but the value encodes to {found}")]
+    SizeMismatch {
+        field: String,
+        expected: usize,
+        found: usize,
+    },
+
+    #[error("Failed to Borsh-encode filter value: {0}")]
+    Encoding(String),
+
+    #[error("Memcmp payload is {0} bytes; the maximum is {MAX_MEMCMP_BYTES}")]
+    TooLarge(usize),
+
+    #[error("Invalid {encoding:?} string: {message}")]
+    InvalidEncoding {
+        encoding: MemcmpEncoding,
+        message: String,
+    },
+}
+
+impl Filter {
+    /// Match accounts of an exact size
+    pub fn data_size(size: u64) -> Self {
+        Self::DataSize(size)
+    }
+
+    /// Match raw bytes at an offset
+    pub fn memcmp(offset: usize, bytes: &[u8]) -> Result<Self, FilterError> {
+        if bytes.len() > MAX_MEMCMP_BYTES {
+            return Err(FilterError::TooLarge(bytes.len()));
+        }
+        Ok(Self::Memcmp(Memcmp {
+            offset,
+            bytes: bs58::encode(bytes).into_string(),
+            encoding: MemcmpEncoding::Base58,
+        }))
+    }
+
+    /// Match a pubkey at an offset
+    pub fn memcmp_pubkey(offset: usize, pubkey: &Pubkey) -> Self {
+        Self::Memcmp(Memcmp {
+            offset,
+            bytes: pubkey.to_base58(),
+            encoding: MemcmpEncoding::Base58,
+        })
+    }
+
+    /// Match an already base58-encoded string at an offset
+    pub fn memcmp_base58(offset: usize, encoded: impl Into<String>) -> Result<Self, FilterError> {
+        let encoded = encoded.into();
+        let decoded =
+            bs58::decode(&encoded)
+                .into_vec()
+                .map_err(|e| FilterError::InvalidEncoding {
+                    encoding: MemcmpEncoding::Base58,
+                    message: e.to_string(),
+                })?;
+        if decoded.len() > MAX_MEMCMP_BYTES {
+            return Err(FilterError::TooLarge(decoded.len()));
+        }
+        Ok(Self::Memcmp(Memcmp {
+            offset,
+            bytes: encoded,
+            encoding: MemcmpEncoding::Base58,
+        }))
+    }
+
+    /// Match an already base64-encoded string at an offset
+    pub fn memcmp_base64(offset: usize, encoded: impl Into<String>) -> Result<Self, FilterError> {
+        let encoded = encoded.into();
+        let decoded = general_purpose::STANDARD.decode(&encoded).map_err(|e| {
+            FilterError::InvalidEncoding {
+                encoding: MemcmpEncoding::Base64,
+                message: e.to_string(),
+            }
+        })?;
+        if decoded.len() > MAX_MEMCMP_BYTES {
+            return Err(FilterError::TooLarge(decoded.len()));
+        }
+        Ok(Self::Memcmp(Memcmp {
+            offset,
+            bytes: encoded,
+            encoding: MemcmpEncoding::Base64,
+        }))
+    }
+
+    /// Match the Borsh encoding of `value` at an offset
+    pub fn memcmp_borsh<V: borsh::BorshSerialize>(
+        offset: usize,
+        value: &V,
+    ) -> Result<Self, FilterError> {
+        let bytes = borsh::to_vec(value).map_err(|e| FilterError::Encoding(e.to_string()))?;
+        Self::memcmp(offset, &bytes)
+    }
+
+    /// Match the Borsh encoding of `value` at the offset of `field` in layout `L`
+    ///
+    /// Fails if the layout knows the field's size and `value` encodes to a
+    /// different number of bytes.
+    pub fn field_eq<L: AccountLayout, V: borsh::BorshSerialize>(
+        field: &str,
+        value: V,
+    ) -> Result<Self, FilterError> {
+        let offset = L::field_offset(field).ok_or_else(|| unknown_field::<L>(field))?;
+        let bytes = borsh::to_vec(&value).map_err(|e| FilterError::Encoding(e.to_string()))?;
+        if let Some(range) = L::field_range(field) {
+            if range.len() != bytes.len() {
+                return Err(FilterError::SizeMismatch {
+                    field: field.to_string(),
+                    expected: range.len(),
+                    found: bytes.len(),
+                });
+            }
+        }
+        Self::memcmp(offset, &bytes)
+    }
+
+    /// The decoded bytes a memcmp filter matches
+    pub fn memcmp_bytes(&self) -> Option<Vec<u8>> {
+        match self {
+            Self::DataSize(_) => None,
+            Self::Memcmp(memcmp) => match memcmp.encoding {
+                MemcmpEncoding::Base58 => bs58::decode(&memcmp.bytes).into_vec().ok(),
+                MemcmpEncoding::Base64 => general_purpose::STANDARD.decode(&memcmp.bytes).ok(),
+            },
+        }
+    }
+
+    /// Whether account data satisfies this filter
+    pub fn matches(&self, data: &[u8]) -> bool {
+        match self {
+            Self::DataSize(size) => data.len() as u64 == *size,
+            Self::Memcmp(memcmp) => self.memcmp_bytes().is_some_and(|bytes| {
+                data.get(memcmp.offset..memcmp.offset + bytes.len()) == Some(bytes.as_slice())
+            }),
+        }
+    }
+}
+
+/// Error for a field missing from layout `L`
+pub(crate) fn unknown_field<L: AccountLayout>(field: &str) -> FilterError {
+    FilterError::UnknownField {
+        layout: std::any::type_name::<L>(),
+        field: field.to_string(),
+    }
+}
+
+/// Builder collecting several filters
+#[derive(Debug, Clone, Default)]
+pub struct FilterBuilder {
+    filters: Vec<Filter>,
+    error: Option<FilterError>,
+}
+
+impl FilterBuilder {
+    /// Create an empty filter builder
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Require an exact data size
+    pub fn data_size(mut self, size: u64) -> Self {
+        self.filters.push(Filter::data_size(size));
+        self
+    }
+
+    /// Require raw bytes at an offset
+    pub fn bytes(self, offset: usize, bytes: &[u8]) -> Self {
+        self.push(Filter::memcmp(offset, bytes))
+    }
+
+    /// Require a pubkey at an offset
+    pub fn pubkey(mut self, offset: usize, pubkey: &Pubkey) -> Self {
+        self.filters.push(Filter::memcmp_pubkey(offset, pubkey));
+        self
+    }
+
+    /// Require a base58-encoded string at an offset
+    pub fn base58(self, offset: usize, encoded: impl Into<String>) -> Self {
+        self.push(Filter::memcmp_base58(offset, encoded))
+    }
+
+    /// Require a base64-encoded string at an offset
+    pub fn base64(self, offset: usize, encoded: impl Into<String>) -> Self {
+        self.push(Filter::memcmp_base64(offset, encoded))
+    }
+
+    /// Require the Borsh encoding of a value at an offset
+    pub fn borsh<V: borsh::BorshSerialize>(self, offset: usize, value: &V) -> Self {
+        self.push(Filter::memcmp_borsh(offset, value))
+    }
+
+    /// Require a field of layout `L` to equal `value`
+    pub fn field_eq<L: AccountLayout, V: borsh::BorshSerialize>(
+        self,
+        field: &str,
+        value: V,
+    ) -> Self {
+        self.push(Filter::field_eq::<L, V>(field, value))
+    }
+
+    /// Finish building, returning the first error encountered
+    pub fn build(self) -> Result<Vec<Filter>, FilterError> {
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(self.filters),
+        }
+    }
+
+    fn push(mut self, filter: Result<Filter, FilterError>) -> Self {
+        match filter {
+            Ok(filter) => self.filters.push(filter),
+            Err(error) => {
+                self.error.get_or_insert(error);
+            }
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::programs::stake::StakeLayout;
+    use serde_json::json;
+
+    struct CounterAccount;
+
+    impl AccountLayout for CounterAccount {
+        const FIELDS: &'static [(&'static str, usize)] = &[("authority", 8), ("count", 40)];
+    }
+
+    #[test]
+    fn test_filter_serialization() {
+        let pubkey = Pubkey::new([1; 32]);
+        let filters = FilterBuilder::new()
+            .data_size(48)
+            .pubkey(8, &pubkey)
+            .base64(40, "AQ==")
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&filters).unwrap(),
+            json!([
+                { "dataSize": 48 },
+                { "memcmp": { "offset": 8, "bytes": pubkey.to_base58(), "encoding": "base58" } },
+                { "memcmp": { "offset": 40, "bytes": "AQ==", "encoding": "base64" } }
+            ])
+        );
+    }
+
+    #[test]
+    fn test_field_eq_uses_layout_offsets() {
+        let authority = Pubkey::new([7; 32]);
+        let by_field = Filter::field_eq::<CounterAccount, _>("authority", authority).unwrap();
+        assert_eq!(by_field, Filter::memcmp_pubkey(8, &authority));
+
+        let count = Filter::field_eq::<CounterAccount, _>("count", 5u64).unwrap();
+        let mut data = vec![0u8; 48];
+        data[8..40].copy_from_slice(authority.as_bytes());
+        data[40..48].copy_from_slice(&5u64.to_le_bytes());
+        assert!(by_field.matches(&data));
+        assert!(count.matches(&data));
+        assert!(!Filter::field_eq::<CounterAccount, _>("count", 6u64)
+            .unwrap()
+            .matches(&data));
+
+        assert!(matches!(
+            Filter::field_eq::<CounterAccount, _>("owner", authority),
+            Err(FilterError::UnknownField { .. })
+        ));
+
+        let staker = Pubkey::new([4; 32]);
+        assert_eq!(
+            Filter::field_eq::<StakeLayout, _>("staker", staker).unwrap(),
+            Filter::memcmp_pubkey(12, &staker)
+        );
+        assert!(matches!(
+            Filter::field_eq::<StakeLayout, _>("stake", 5u32),
+            Err(FilterError::SizeMismatch {
+                expected: 8,
+                found: 4,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_builder_reports_first_error() {
+        let result = FilterBuilder::new()
+            .bytes(0, &[0u8; MAX_MEMCMP_BYTES + 1])
+            .base58(0, "0OIl")
+            .build();
+        assert!(matches!(result, Err(FilterError::TooLarge(_))));
+    }
+}
@@ -0,0 +1,539 @@
+//! In-memory RPC node for unit tests
+//!
+//! [`MockRpc`] implements [`RpcApi`] over a map of accounts and canned
+//! signature statuses, so services can be tested without a network. Each
+//! request is recorded, and the node can be taken offline or made to lag
+//! behind `minContextSlot` requests.
+
+use super::{
+    decode_wire_transaction, Account, AccountReadConfig, ConfirmedBlock, ConfirmedTransaction,
+    EpochInfo, Filter, InflationReward, LatestBlockhash, RpcApi, RpcError, RpcResponseContext,
+    SignatureInfo, SignatureStatus, SignaturesForAddressConfig, SimulationResult,
+    TokenAccountBalance, UiTokenAmount, WithContext, MIN_CONTEXT_SLOT_NOT_REACHED,
+};
+use crate::domain::message::Message;
+use crate::domain::rent::rent_exempt_minimum;
+use crate::domain::types::{Hash, Pubkey, Signature};
+use crate::infrastructure::time::sleep;
+use base64::{engine::general_purpose, Engine as _};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Signature returned by every `send_transaction`
+pub(crate) const SENT_SIGNATURE: Signature = Signature::new([2; 64]);
+
+/// Fee `get_fee_for_message` charges per required signature
+pub(crate) const MOCK_LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+
+/// Epoch length reported by `get_epoch_info`, short so tests can cross epochs
+const MOCK_SLOTS_PER_EPOCH: u64 = 100;
+
+/// Supply, decimals and largest holders of a mock mint
+struct MockMint {
+    supply: u64,
+    decimals: u8,
+    holders: Vec<(Pubkey, u64)>,
+}
+
+/// In-memory [`RpcApi`] implementation
+pub(crate) struct MockRpc {
+    accounts: HashMap<Pubkey, Account>,
+    slot: u64,
+    block_height: u64,
+    offline: bool,
+    latency: Duration,
+    lagging: Mutex<u32>,
+    failing_sends: Mutex<u32>,
+    statuses: Mutex<HashMap<Signature, VecDeque<Option<SignatureStatus>>>>,
+    mints: HashMap<Pubkey, MockMint>,
+    transactions: HashMap<Signature, ConfirmedTransaction>,
+    blocks: HashMap<u64, ConfirmedBlock>,
+    rewards: HashMap<(Pubkey, u64), InflationReward>,
+    simulation: SimulationResult,
+    /// `minContextSlot` of every context-returning read
+    pub(crate) min_context_slots: Mutex<Vec<Option<u64>>>,
+    /// Every transaction passed to `send_transaction`
+    pub(crate) sent: Mutex<Vec<String>>,
+}
+
+impl MockRpc {
+    /// A node at slot 120 and block height 100 with no accounts
+    pub(crate) fn new() -> Self {
+        Self {
+            accounts: HashMap::new(),
+            slot: 120,
+            block_height: 100,
+            offline: false,
+            latency: Duration::ZERO,
+            lagging: Mutex::new(0),
+            failing_sends: Mutex::new(0),
+            statuses: Mutex::new(HashMap::new()),
+            mints: HashMap::new(),
+            transactions: HashMap::new(),
+            blocks: HashMap::new(),
+            rewards: HashMap::new(),
+            simulation: SimulationResult::default(),
+            min_context_slots: Mutex::new(Vec::new()),
+            sent: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Add an account
+    pub(crate) fn with_account(mut self, account: Account) -> Self {
+        self.accounts.insert(account.pubkey, account);
+        self
+    }
+
+    /// Add an empty system account holding `lamports`
+    pub(crate) fn with_balance(self, pubkey: Pubkey, lamports: u64) -> Self {
+        self.with_account(Account {
+            pubkey,
+            lamports,
+            data: Vec::new(),
+            owner: Pubkey::new([0; 32]),
+            executable: false,
+            rent_epoch: 0,
+        })
+    }
+
+    /// Set the slot reported by `getSlot` and response contexts
+    pub(crate) fn with_slot(mut self, slot: u64) -> Self {
+        self.slot = slot;
+        self
+    }
+
+    /// Fail every request
+    pub(crate) fn offline(mut self) -> Self {
+        self.offline = true;
+        self
+    }
+
+    /// Delay every `getMultipleAccounts` response by `latency`
+    pub(crate) fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// Reject the next `requests` reads that set `minContextSlot`
+    pub(crate) fn lagging(self, requests: u32) -> Self {
+        *self.lagging.lock().unwrap() = requests;
+        self
+    }
+
+    /// Reject the next `sends` calls to `send_transaction`
+    pub(crate) fn failing_sends(self, sends: u32) -> Self {
+        *self.failing_sends.lock().unwrap() = sends;
+        self
+    }
+
+    /// Answer successive status queries for `signature` with `statuses`
+    ///
+    /// The last status keeps being returned once the others are used up.
+    pub(crate) fn with_statuses(
+        self,
+        signature: Signature,
+        statuses: Vec<Option<SignatureStatus>>,
+    ) -> Self {
+        self.statuses
+            .lock()
+            .unwrap()
+            .insert(signature, statuses.into());
+        self
+    }
+
+    /// Add a mint with its supply and largest holders
+    #[cfg(feature = "token")]
+    pub(crate) fn with_mint(
+        mut self,
+        mint: Pubkey,
+        supply: u64,
+        decimals: u8,
+        holders: Vec<(Pubkey, u64)>,
+    ) -> Self {
+        self.mints.insert(
+            mint,
+            MockMint {
+                supply,
+                decimals,
+                holders,
+            },
+        );
+        self
+    }
+
+    /// Add a staking reward credited to `address`
+    pub(crate) fn with_inflation_reward(
+        mut self,
+        address: Pubkey,
+        reward: InflationReward,
+    ) -> Self {
+        self.rewards.insert((address, reward.epoch), reward);
+        self
+    }
+
+    /// Add a confirmed transaction, listed under each of its account keys
+    pub(crate) fn with_transaction(
+        mut self,
+        signature: Signature,
+        transaction: ConfirmedTransaction,
+    ) -> Self {
+        self.transactions.insert(signature, transaction);
+        self
+    }
+
+    /// Add a block at `slot`; other slots are reported as skipped
+    pub(crate) fn with_block(mut self, slot: u64, block: ConfirmedBlock) -> Self {
+        self.blocks.insert(slot, block);
+        self
+    }
+
+    /// Answer every `simulateTransaction` with `simulation`
+    pub(crate) fn with_simulation(mut self, simulation: SimulationResult) -> Self {
+        self.simulation = simulation;
+        self
+    }
+
+    fn mint(&self, mint: &Pubkey) -> Result<&MockMint, RpcError> {
+        self.check()?;
+        self.mints.get(mint).ok_or_else(|| RpcError::RpcError {
+            code: -32602,
+            message: "Invalid param: not a Token mint".to_string(),
+            data: None,
+        })
+    }
+
+    fn check(&self) -> Result<(), RpcError> {
+        if self.offline {
+            Err(RpcError::InternalError("offline".to_string()))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn respond<T>(
+        &self,
+        value: T,
+        min_context_slot: Option<u64>,
+    ) -> Result<WithContext<T>, RpcError> {
+        self.check()?;
+        self.min_context_slots
+            .lock()
+            .unwrap()
+            .push(min_context_slot);
+        let mut lagging = self.lagging.lock().unwrap();
+        if min_context_slot.is_some() && *lagging > 0 {
+            *lagging -= 1;
+            return Err(RpcError::RpcError {
+                code: MIN_CONTEXT_SLOT_NOT_REACHED,
+                message: "Minimum context slot has not been reached".to_string(),
+                data: None,
+            });
+        }
+        Ok(WithContext {
+            context: RpcResponseContext { slot: self.slot },
+            value,
+        })
+    }
+
+    fn status(&self, signature: &Signature) -> Option<SignatureStatus> {
+        let mut statuses = self.statuses.lock().unwrap();
+        let queue = statuses.get_mut(signature)?;
+        if queue.len() > 1 {
+            queue.pop_front().flatten()
+        } else {
+            queue.front().cloned().flatten()
+        }
+    }
+}
+
+/// `account` as returned under `config`'s data slice
+fn sliced(account: &Account, config: &AccountReadConfig) -> Account {
+    let mut account = account.clone();
+    if let Some(slice) = config.data_slice {
+        let start = slice.offset.min(account.data.len());
+        let end = slice
+            .offset
+            .saturating_add(slice.length)
+            .min(account.data.len());
+        account.data = account.data[start..end].to_vec();
+    }
+    account
+}
+
+#[async_trait::async_trait]
+impl RpcApi for MockRpc {
+    async fn get_account_info(&self, pubkey: &Pubkey) -> Result<Option<Account>, RpcError> {
+        Ok(self
+            .get_account_info_with_context(pubkey, None)
+            .await?
+            .value)
+    }
+
+    async fn get_account_info_with_context(
+        &self,
+        pubkey: &Pubkey,
+        min_context_slot: Option<u64>,
+    ) -> Result<WithContext<Option<Account>>, RpcError> {
+        self.respond(self.accounts.get(pubkey).cloned(), min_context_slot)
+    }
+
+    async fn get_account_info_with_config(
+        &self,
+        pubkey: &Pubkey,
+        config: &AccountReadConfig,
+    ) -> Result<WithContext<Option<Account>>, RpcError> {
+        let account = self.accounts.get(pubkey).map(|a| sliced(a, config));
+        self.respond(account, config.min_context_slot)
+    }
+
+    async fn get_balance(&self, pubkey: &Pubkey) -> Result<u64, RpcError> {
+        Ok(self.get_balance_with_context(pubkey, None).await?.value)
+    }
+
+    async fn get_balance_with_context(
+        &self,
+        pubkey: &Pubkey,
+        min_context_slot: Option<u64>,
+    ) -> Result<WithContext<u64>, RpcError> {
+        let lamports = self.accounts.get(pubkey).map_or(0, |a| a.lamports);
+        self.respond(lamports, min_context_slot)
+    }
+
+    async fn get_latest_blockhash(&self) -> Result<LatestBlockhash, RpcError> {
+        self.check()?;
+        Ok(LatestBlockhash {
+            blockhash: Hash::new([1; 32]),
+            last_valid_block_height: self.block_height + 50,
+        })
+    }
+
+    async fn send_transaction(&self, transaction: &str) -> Result<Signature, RpcError> {
+        self.check()?;
+        let mut failing = self.failing_sends.lock().unwrap();
+        if *failing > 0 {
+            *failing -= 1;
+            return Err(RpcError::InternalError("node is overloaded".to_string()));
+        }
+        drop(failing);
+        self.sent.lock().unwrap().push(transaction.to_string());
+        Ok(SENT_SIGNATURE)
+    }
+
+    async fn send_raw_transaction(&self, transaction: &[u8]) -> Result<Signature, RpcError> {
+        self.send_transaction(&general_purpose::STANDARD.encode(transaction))
+            .await
+    }
+
+    async fn send_raw_transaction_base58(&self, transaction: &str) -> Result<Signature, RpcError> {
+        let bytes = decode_wire_transaction(transaction)?;
+        self.send_raw_transaction(&bytes).await
+    }
+
+    async fn get_block_height(&self) -> Result<u64, RpcError> {
+        self.check()?;
+        Ok(self.block_height)
+    }
+
+    async fn get_slot(&self) -> Result<u64, RpcError> {
+        self.check()?;
+        Ok(self.slot)
+    }
+
+    async fn get_epoch_info(&self) -> Result<EpochInfo, RpcError> {
+        self.check()?;
+        Ok(EpochInfo {
+            epoch: self.slot / MOCK_SLOTS_PER_EPOCH,
+            absolute_slot: self.slot,
+            block_height: self.block_height,
+            slot_index: self.slot % MOCK_SLOTS_PER_EPOCH,
+            slots_in_epoch: MOCK_SLOTS_PER_EPOCH,
+            transaction_count: None,
+        })
+    }
+
+    async fn get_multiple_accounts(
+        &self,
+        pubkeys: &[Pubkey],
+    ) -> Result<Vec<Option<Account>>, RpcError> {
+        Ok(self
+            .get_multiple_accounts_with_context(pubkeys, None)
+            .await?
+            .value)
+    }
+
+    async fn get_multiple_accounts_with_context(
+        &self,
+        pubkeys: &[Pubkey],
+        min_context_slot: Option<u64>,
+    ) -> Result<WithContext<Vec<Option<Account>>>, RpcError> {
+        if !self.latency.is_zero() {
+            sleep(self.latency).await;
+        }
+        let accounts = pubkeys
+            .iter()
+            .map(|pubkey| self.accounts.get(pubkey).cloned())
+            .collect();
+        self.respond(accounts, min_context_slot)
+    }
+
+    async fn get_multiple_accounts_with_config(
+        &self,
+        pubkeys: &[Pubkey],
+        config: &AccountReadConfig,
+    ) -> Result<WithContext<Vec<Option<Account>>>, RpcError> {
+        let accounts = pubkeys
+            .iter()
+            .map(|pubkey| self.accounts.get(pubkey).map(|a| sliced(a, config)))
+            .collect();
+        self.respond(accounts, config.min_context_slot)
+    }
+
+    async fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        filters: &[Filter],
+    ) -> Result<Vec<Account>, RpcError> {
+        self.check()?;
+        Ok(self
+            .accounts
+            .values()
+            .filter(|account| account.owner == *program_id)
+            .filter(|account| filters.iter().all(|filter| filter.matches(&account.data)))
+            .cloned()
+            .collect())
+    }
+
+    async fn get_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        _config: SignaturesForAddressConfig,
+    ) -> Result<Vec<SignatureInfo>, RpcError> {
+        self.check()?;
+        let mut infos: Vec<SignatureInfo> = self
+            .transactions
+            .iter()
+            .filter(|(_, transaction)| transaction.account_keys().contains(address))
+            .map(|(signature, transaction)| SignatureInfo {
+                signature: signature.clone(),
+                slot: transaction.slot,
+                err: transaction.meta.as_ref().and_then(|meta| meta.err.clone()),
+                memo: None,
+                block_time: transaction.block_time,
+            })
+            .collect();
+        infos.sort_by_key(|info| std::cmp::Reverse(info.slot));
+        Ok(infos)
+    }
+
+    async fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> Result<Vec<Option<SignatureStatus>>, RpcError> {
+        self.check()?;
+        Ok(signatures.iter().map(|s| self.status(s)).collect())
+    }
+
+    async fn get_transaction(
+        &self,
+        signature: &Signature,
+    ) -> Result<Option<ConfirmedTransaction>, RpcError> {
+        self.check()?;
+        Ok(self.transactions.get(signature).cloned())
+    }
+
+    async fn get_token_largest_accounts(
+        &self,
+        mint: &Pubkey,
+    ) -> Result<Vec<TokenAccountBalance>, RpcError> {
+        let mint = self.mint(mint)?;
+        Ok(mint
+            .holders
+            .iter()
+            .map(|(address, amount)| TokenAccountBalance {
+                address: *address,
+                amount: token_amount(*amount, mint.decimals),
+            })
+            .collect())
+    }
+
+    async fn get_token_supply(&self, mint: &Pubkey) -> Result<UiTokenAmount, RpcError> {
+        let mint = self.mint(mint)?;
+        Ok(token_amount(mint.supply, mint.decimals))
+    }
+
+    async fn get_block(&self, slot: u64) -> Result<Option<ConfirmedBlock>, RpcError> {
+        self.check()?;
+        Ok(self.blocks.get(&slot).cloned())
+    }
+
+    async fn get_block_time(&self, slot: u64) -> Result<Option<i64>, RpcError> {
+        self.check()?;
+        Ok(self.blocks.get(&slot).and_then(|block| block.block_time))
+    }
+
+    /// Without `epoch`, the latest epoch with any reward counts as the last one
+    async fn get_inflation_reward(
+        &self,
+        addresses: &[Pubkey],
+        epoch: Option<u64>,
+    ) -> Result<Vec<Option<InflationReward>>, RpcError> {
+        self.check()?;
+        let latest = self.rewards.keys().map(|(_, epoch)| *epoch).max();
+        let Some(epoch) = epoch.or(latest) else {
+            return Ok(vec![None; addresses.len()]);
+        };
+        Ok(addresses
+            .iter()
+            .map(|address| self.rewards.get(&(*address, epoch)).cloned())
+            .collect())
+    }
+
+    /// One SOL, the minimum on clusters with the raised delegation floor
+    async fn get_stake_minimum_delegation(&self) -> Result<u64, RpcError> {
+        self.check()?;
+        Ok(1_000_000_000)
+    }
+
+    async fn get_fee_for_message(&self, message: &str) -> Result<Option<u64>, RpcError> {
+        self.check()?;
+        let bytes = general_purpose::STANDARD
+            .decode(message)
+            .map_err(|e| RpcError::InvalidRequest(e.to_string()))?;
+        let message =
+            Message::deserialize(&bytes).map_err(|e| RpcError::InvalidRequest(e.to_string()))?;
+        let signatures = u64::from(message.header.num_required_signatures);
+        Ok(Some(signatures * MOCK_LAMPORTS_PER_SIGNATURE))
+    }
+
+    async fn get_minimum_balance_for_rent_exemption(
+        &self,
+        data_len: usize,
+    ) -> Result<u64, RpcError> {
+        self.check()?;
+        Ok(rent_exempt_minimum(data_len))
+    }
+
+    async fn simulate_transaction(
+        &self,
+        _transaction: &str,
+    ) -> Result<WithContext<SimulationResult>, RpcError> {
+        self.respond(self.simulation.clone(), None)
+    }
+
+    async fn simulate_transaction_with_accounts(
+        &self,
+        transaction: &str,
+        _addresses: &[Pubkey],
+    ) -> Result<WithContext<SimulationResult>, RpcError> {
+        self.simulate_transaction(transaction).await
+    }
+}
+
+fn token_amount(amount: u64, decimals: u8) -> UiTokenAmount {
+    UiTokenAmount {
+        amount: amount.to_string(),
+        decimals,
+        ui_amount_string: None,
+    }
+}
@@ -2,23 +2,98 @@
 //!
 //! This module provides a complete implementation of the Solana JSON-RPC API
 //! using HTTP requests, designed to work in both WASM and native environments.
-
+//!
+//! Cloning a [`SolanaRpcClient`] is cheap: every clone shares the same HTTP
+//! connection pool, interceptors, response cache, rate limiter and metrics.
+
+use crate::domain::diff::AccountState;
+use crate::domain::discriminator::{decode_account, Discriminator, DiscriminatorError};
+use crate::domain::errors::TransactionError;
+use crate::domain::layout::AccountLayout;
+use crate::domain::message::MessageHeader;
 use crate::domain::types::{Hash, Pubkey, Signature};
-#[cfg(not(target_arch = "wasm32"))]
-use crate::infrastructure::http::NativeHttpClient;
+use crate::infrastructure::cache::{CachePolicy, ResponseCache, TransactionCache};
+use crate::infrastructure::cancel::CancelToken;
+use crate::infrastructure::cluster::{ClusterCapabilities, ClusterKind, RpcVersion};
+use crate::infrastructure::config::{ClientConfig, ConfigError};
 #[cfg(target_arch = "wasm32")]
 use crate::infrastructure::http::WasmHttpClient;
-use crate::infrastructure::http::{HttpClient, HttpError};
+use crate::infrastructure::http::{
+    HttpClient, HttpError, Recording, RecordingHttpClient, ReplayHttpClient, USER_AGENT_HEADER,
+};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::infrastructure::http::{NativeHttpClient, ProxyConfig, TransportConfig, TransportStats};
+use crate::infrastructure::json::{default_parser, JsonParser};
+use crate::infrastructure::metrics::{ClientMetrics, ErrorKind, MetricsSink, RequestEvent};
+pub use crate::infrastructure::network::{surfpool_network, CustomNetwork, Network};
+use crate::infrastructure::rate_limit::RateLimiter;
+use crate::infrastructure::time::Instant;
+use base64::{engine::general_purpose, Engine as _};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use thiserror::Error;
 
+pub mod account_data;
+pub mod api;
+pub mod cheatcodes;
+pub mod consistency;
+pub mod context;
+pub mod filters;
+#[cfg(test)]
+pub(crate) mod mock;
+pub mod registry;
+pub mod scheduler;
+pub mod types;
+pub mod verify;
+
+pub use account_data::{AccountData, AccountDataError, AccountEncoding, ParsedAccount};
+pub use api::{RpcApi, MAX_ACCOUNTS_PER_REQUEST, MAX_SIGNATURES_PER_REQUEST};
+pub use cheatcodes::{AccountUpdate, Cheatcodes};
+pub use consistency::ConsistentReader;
+pub use context::RpcContextConfig;
+pub use filters::{Filter, FilterBuilder, FilterError, Memcmp, MemcmpEncoding};
+pub use registry::NetworkRegistry;
+pub use scheduler::ReadScheduler;
+pub use types::{
+    RpcErrorObject, RpcId, RpcMessage, RpcRequest, RpcResponse, RpcResponseContext, WithContext,
+};
+pub use verify::{verify_transaction_signatures, SignatureVerificationError};
+
 /// Solana JSON-RPC client
 #[derive(Clone)]
 pub struct SolanaRpcClient {
+    inner: Arc<ClientInner>,
+    /// Abandons this handle's requests once cancelled
+    cancel: Option<CancelToken>,
+}
+
+/// State shared by every clone of a [`SolanaRpcClient`]
+struct ClientInner {
     http_client: HttpClientEnum,
     endpoint: String,
+    config: RpcClientConfig,
+    interceptors: Vec<Arc<dyn RpcInterceptor>>,
+    cache: Option<ResponseCache>,
+    rate_limiter: Option<RateLimiter>,
+    metrics: ClientMetrics,
+    json_parser: Arc<dyn JsonParser>,
+    cluster: Mutex<Option<ClusterCapabilities>>,
+    /// Genesis hash fetched to check the expected cluster
+    genesis_hash: Mutex<Option<Hash>>,
+}
+
+/// Hook invoked around every JSON-RPC call made by a client
+///
+/// Interceptors are shared by all clones of the client they were registered on.
+pub trait RpcInterceptor: Send + Sync {
+    /// Called before a request is sent (or served from cache)
+    fn before_request(&self, _method: &str, _params: &[serde_json::Value]) {}
+
+    /// Called once a request has completed
+    fn after_response(&self, _method: &str, _result: Result<&serde_json::Value, &RpcError>) {}
 }
 
 /// Enum to hold different HTTP client implementations
@@ -28,64 +103,102 @@ pub enum HttpClientEnum {
     Wasm(WasmHttpClient),
     #[cfg(not(target_arch = "wasm32"))]
     Native(NativeHttpClient),
+    /// The platform client, recording every exchange
+    #[cfg(target_arch = "wasm32")]
+    Recording(RecordingHttpClient<WasmHttpClient>),
+    /// The platform client, recording every exchange
+    #[cfg(not(target_arch = "wasm32"))]
+    Recording(RecordingHttpClient<NativeHttpClient>),
+    /// Recorded responses instead of the network
+    Replay(ReplayHttpClient),
 }
 
 #[cfg(target_arch = "wasm32")]
 impl HttpClient for HttpClientEnum {
-    fn post_json<'a, Req, Resp>(
+    async fn post_json<'a, Req, Resp>(
         &'a self,
         url: &'a str,
         body: &'a Req,
-    ) -> impl Future<Output = Result<Resp, HttpError>> + 'a
+    ) -> Result<Resp, HttpError>
     where
         Req: Serialize + Send + Sync,
         Resp: for<'de> Deserialize<'de> + 'static,
     {
-        async move {
-            match self {
-                HttpClientEnum::Wasm(client) => client.post_json(url, body).await,
-            }
+        match self {
+            HttpClientEnum::Wasm(client) => client.post_json(url, body).await,
+            HttpClientEnum::Recording(client) => client.post_json(url, body).await,
+            HttpClientEnum::Replay(client) => client.post_json(url, body).await,
         }
     }
 
-    fn get<'a, Resp>(&'a self, url: &'a str) -> impl Future<Output = Result<Resp, HttpError>> + 'a
+    async fn post_json_text<'a, Req>(
+        &'a self,
+        url: &'a str,
+        body: &'a Req,
+    ) -> Result<String, HttpError>
+    where
+        Req: Serialize + Send + Sync,
+    {
+        match self {
+            HttpClientEnum::Wasm(client) => client.post_json_text(url, body).await,
+            HttpClientEnum::Recording(client) => client.post_json_text(url, body).await,
+            HttpClientEnum::Replay(client) => client.post_json_text(url, body).await,
+        }
+    }
+
+    async fn get<'a, Resp>(&'a self, url: &'a str) -> Result<Resp, HttpError>
     where
         Resp: for<'de> Deserialize<'de> + 'static,
     {
-        async move {
-            match self {
-                HttpClientEnum::Wasm(client) => client.get(url).await,
-            }
+        match self {
+            HttpClientEnum::Wasm(client) => client.get(url).await,
+            HttpClientEnum::Recording(client) => client.get(url).await,
+            HttpClientEnum::Replay(client) => client.get(url).await,
         }
     }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
 impl HttpClient for HttpClientEnum {
-    fn post_json<'a, Req, Resp>(
+    async fn post_json<'a, Req, Resp>(
         &'a self,
         url: &'a str,
         body: &'a Req,
-    ) -> impl Future<Output = Result<Resp, HttpError>> + 'a
+    ) -> Result<Resp, HttpError>
     where
         Req: Serialize + Send + Sync,
         Resp: for<'de> Deserialize<'de> + 'static,
     {
-        async move {
-            match self {
-                HttpClientEnum::Native(client) => client.post_json(url, body).await,
-            }
+        match self {
+            HttpClientEnum::Native(client) => client.post_json(url, body).await,
+            HttpClientEnum::Recording(client) => client.post_json(url, body).await,
+            HttpClientEnum::Replay(client) => client.post_json(url, body).await,
+        }
+    }
+
+    async fn post_json_text<'a, Req>(
+        &'a self,
+        url: &'a str,
+        body: &'a Req,
+    ) -> Result<String, HttpError>
+    where
+        Req: Serialize + Send + Sync,
+    {
+        match self {
+            HttpClientEnum::Native(client) => client.post_json_text(url, body).await,
+            HttpClientEnum::Recording(client) => client.post_json_text(url, body).await,
+            HttpClientEnum::Replay(client) => client.post_json_text(url, body).await,
         }
     }
 
-    fn get<'a, Resp>(&'a self, url: &'a str) -> impl Future<Output = Result<Resp, HttpError>> + 'a
+    async fn get<'a, Resp>(&'a self, url: &'a str) -> Result<Resp, HttpError>
     where
         Resp: for<'de> Deserialize<'de> + 'static,
     {
-        async move {
-            match self {
-                HttpClientEnum::Native(client) => client.get(url).await,
-            }
+        match self {
+            HttpClientEnum::Native(client) => client.get(url).await,
+            HttpClientEnum::Recording(client) => client.get(url).await,
+            HttpClientEnum::Replay(client) => client.get(url).await,
         }
     }
 }
@@ -94,96 +207,218 @@ impl SolanaRpcClient {
     /// Create a new RPC client with the given endpoint
     #[cfg(target_arch = "wasm32")]
     pub fn new(endpoint: impl Into<String>, http_client: WasmHttpClient) -> Self {
-        Self {
-            http_client: HttpClientEnum::Wasm(http_client),
-            endpoint: endpoint.into(),
-        }
+        Self::from_parts(
+            HttpClientEnum::Wasm(http_client),
+            endpoint.into(),
+            RpcClientConfig::default(),
+        )
     }
 
     /// Create a new RPC client with the given endpoint
     #[cfg(not(target_arch = "wasm32"))]
     pub fn new(endpoint: impl Into<String>, http_client: NativeHttpClient) -> Self {
-        Self {
-            http_client: HttpClientEnum::Native(http_client),
-            endpoint: endpoint.into(),
-        }
+        Self::from_parts(
+            HttpClientEnum::Native(http_client),
+            endpoint.into(),
+            RpcClientConfig::default(),
+        )
     }
 
     /// Create a new RPC client with default HTTP client
-    pub fn with_endpoint(endpoint: impl Into<String>) -> Self {
-        #[cfg(target_arch = "wasm32")]
-        {
-            Self::new(endpoint, WasmHttpClient::new())
-        }
-        #[cfg(not(target_arch = "wasm32"))]
-        {
-            Self::new(endpoint, NativeHttpClient::new())
+    ///
+    /// Fails if the native HTTP client cannot be created.
+    pub fn with_endpoint(endpoint: impl Into<String>) -> Result<Self, RpcError> {
+        Ok(Self::from_parts(
+            default_http_client()?,
+            endpoint.into(),
+            RpcClientConfig::default(),
+        ))
+    }
+
+    fn from_parts(http_client: HttpClientEnum, endpoint: String, config: RpcClientConfig) -> Self {
+        let cache = config.cache_policy.map(ResponseCache::with_policy);
+        let rate_limiter = config.requests_per_second.map(RateLimiter::new);
+        let interceptors = config.interceptors.clone();
+        let metrics = match &config.metrics_sink {
+            Some(sink) => ClientMetrics::new().with_sink(sink.clone()),
+            None => ClientMetrics::new(),
+        };
+        let json_parser = config.json_parser.clone().unwrap_or_else(default_parser);
+
+        Self {
+            inner: Arc::new(ClientInner {
+                http_client,
+                endpoint,
+                config,
+                interceptors,
+                cache,
+                rate_limiter,
+                metrics,
+                json_parser,
+                cluster: Mutex::new(None),
+                genesis_hash: Mutex::new(None),
+            }),
+            cancel: None,
         }
     }
 
     /// Get the RPC endpoint URL
     pub fn endpoint(&self) -> &str {
-        &self.endpoint
+        &self.inner.endpoint
+    }
+
+    /// Get the default commitment level configured on the builder
+    pub fn commitment(&self) -> Option<CommitmentLevel> {
+        self.inner.config.commitment
+    }
+
+    /// The cluster the endpoint must belong to, if checked
+    pub fn expected_cluster(&self) -> Option<&Network> {
+        self.inner.config.expected_cluster.as_ref()
+    }
+
+    /// Request counts, error counts and latency estimates for this endpoint
+    pub fn metrics(&self) -> &ClientMetrics {
+        &self.inner.metrics
+    }
+
+    /// Request and connection reuse counts of the underlying HTTP client
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn transport_stats(&self) -> TransportStats {
+        match &self.inner.http_client {
+            HttpClientEnum::Native(client) => client.transport_stats(),
+            HttpClientEnum::Recording(client) => client.inner().transport_stats(),
+            HttpClientEnum::Replay(_) => TransportStats::default(),
+        }
+    }
+
+    /// Get the shared response cache, if caching is enabled
+    pub fn cache(&self) -> Option<&ResponseCache> {
+        self.inner.cache.as_ref()
+    }
+
+    /// Get the cache of fetched transactions, if one is set
+    pub fn transaction_cache(&self) -> Option<&TransactionCache> {
+        self.inner.config.transaction_cache.as_ref()
+    }
+
+    /// A clone whose requests fail with [`RpcError::Cancelled`] once `token` is
+    /// cancelled
+    ///
+    /// Requests in flight at that moment are abandoned: native clients drop
+    /// the connection and browsers abort the fetch. Other clones, which share
+    /// the connection pool and cache, are unaffected.
+    pub fn with_cancel_token(&self, token: CancelToken) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            cancel: Some(token),
+        }
+    }
+
+    /// The token cancelling this handle's requests, if any
+    pub fn cancel_token(&self) -> Option<&CancelToken> {
+        self.cancel.as_ref()
+    }
+
+    /// Whether two clients share the same underlying state
+    pub fn shares_state_with(&self, other: &SolanaRpcClient) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
     }
 
     /// Get account information
     pub async fn get_account_info(&self, pubkey: &Pubkey) -> Result<Option<Account>, RpcError> {
+        Ok(self
+            .get_account_info_with_context(pubkey, None)
+            .await?
+            .value)
+    }
+
+    /// Get account information with the slot it was read at
+    ///
+    /// When `min_context_slot` is set the node refuses to answer until it has
+    /// reached that slot.
+    pub async fn get_account_info_with_context(
+        &self,
+        pubkey: &Pubkey,
+        min_context_slot: Option<u64>,
+    ) -> Result<WithContext<Option<Account>>, RpcError> {
+        let config = AccountReadConfig {
+            min_context_slot,
+            ..AccountReadConfig::default()
+        };
+        self.get_account_info_with_config(pubkey, &config).await
+    }
+
+    /// Get account information read with `config`
+    ///
+    /// With a data slice, the account's `data` holds only that range.
+    pub async fn get_account_info_with_config(
+        &self,
+        pubkey: &Pubkey,
+        config: &AccountReadConfig,
+    ) -> Result<WithContext<Option<Account>>, RpcError> {
         let request = RpcRequest::new("getAccountInfo")
             .param(pubkey.to_base58())
-            .param(json!({
-                "encoding": "base64"
-            }));
+            .param(self.read_params(config));
 
-        let response: RpcResponse<Option<AccountInfo>> = self
-            .http_client
-            .post_json(&self.endpoint, &request)
-            .await
-            .map_err(RpcError::Http)?;
-
-        Ok(response.result.map(|info| {
-            let data = info.data.decode_data().unwrap_or_default();
-            Account {
-                pubkey: *pubkey,
-                lamports: info.lamports,
-                data,
-                owner: info.owner,
-                executable: info.executable,
-                rent_epoch: info.rent_epoch,
-            }
-        }))
+        let result: WithContext<Option<AccountInfo>> = self.send(request).await?;
+
+        let context = result.context;
+        let value = result
+            .value
+            .map(|info| info.into_account(*pubkey))
+            .transpose()?;
+        Ok(WithContext { context, value })
     }
 
     /// Get account balance
     pub async fn get_balance(&self, pubkey: &Pubkey) -> Result<u64, RpcError> {
-        let request = RpcRequest::new("getBalance").param(pubkey.to_base58());
+        Ok(self.get_balance_with_context(pubkey, None).await?.value)
+    }
 
-        let response: RpcResponse<BalanceInfo> = match &self.http_client {
-            #[cfg(target_arch = "wasm32")]
-            HttpClientEnum::Wasm(client) => client
-                .post_json(&self.endpoint, &request)
-                .await
-                .map_err(|e| RpcError::HttpError(e))?,
-            #[cfg(not(target_arch = "wasm32"))]
-            HttpClientEnum::Native(client) => client
-                .post_json(&self.endpoint, &request)
-                .await
-                .map_err(RpcError::Http)?,
+    /// Get account balance with the slot it was read at
+    pub async fn get_balance_with_context(
+        &self,
+        pubkey: &Pubkey,
+        min_context_slot: Option<u64>,
+    ) -> Result<WithContext<u64>, RpcError> {
+        let config = RpcContextConfig {
+            min_context_slot,
+            ..RpcContextConfig::default()
         };
+        self.get_balance_with_config(pubkey, &config).await
+    }
+
+    /// Get account balance read with `config`
+    pub async fn get_balance_with_config(
+        &self,
+        pubkey: &Pubkey,
+        config: &RpcContextConfig,
+    ) -> Result<WithContext<u64>, RpcError> {
+        let request = self.with_context(
+            RpcRequest::new("getBalance").param(pubkey.to_base58()),
+            config,
+        );
 
-        Ok(response.result.value)
+        self.send(request).await
     }
 
     /// Get the latest blockhash
     pub async fn get_latest_blockhash(&self) -> Result<LatestBlockhash, RpcError> {
-        let request = RpcRequest::new("getLatestBlockhash");
+        Ok(self
+            .get_latest_blockhash_with_config(&RpcContextConfig::default())
+            .await?
+            .value)
+    }
 
-        let response: RpcResponse<LatestBlockhashInfo> = self
-            .http_client
-            .post_json(&self.endpoint, &request)
-            .await
-            .map_err(RpcError::Http)?;
+    /// Get the latest blockhash read with `config`
+    pub async fn get_latest_blockhash_with_config(
+        &self,
+        config: &RpcContextConfig,
+    ) -> Result<WithContext<LatestBlockhash>, RpcError> {
+        let request = self.with_context(RpcRequest::new("getLatestBlockhash"), config);
 
-        Ok(response.result.value)
+        self.send(request).await
     }
 
     /// Send a transaction
@@ -194,230 +429,1343 @@ impl SolanaRpcClient {
                 "encoding": "base64"
             }));
 
-        let response: RpcResponse<String> = self
-            .http_client
-            .post_json(&self.endpoint, &request)
+        let result: String = self.send(request).await?;
+
+        Signature::from_base58(&result).map_err(RpcError::InvalidSignature)
+    }
+
+    /// Send a signed transaction given as its wire bytes
+    ///
+    /// This is the form wallet adapters return from `signTransaction`.
+    pub async fn send_raw_transaction(&self, transaction: &[u8]) -> Result<Signature, RpcError> {
+        self.send_transaction(&general_purpose::STANDARD.encode(transaction))
             .await
-            .map_err(RpcError::Http)?;
+    }
 
-        Signature::from_base58(&response.result).map_err(RpcError::InvalidSignature)
+    /// Send a signed transaction encoded as base58 or base64
+    ///
+    /// The encoding is detected by [`decode_wire_transaction`].
+    pub async fn send_raw_transaction_base58(
+        &self,
+        transaction: &str,
+    ) -> Result<Signature, RpcError> {
+        let bytes = decode_wire_transaction(transaction)?;
+        self.send_raw_transaction(&bytes).await
     }
 
-    /// Get block height
-    /// Get the current block height
-    pub async fn get_block_height(&self) -> Result<u64, RpcError> {
-        let request = RpcRequest::new("getBlockHeight");
+    /// Simulate a base64-encoded transaction without sending it
+    ///
+    /// Signatures are not verified and the blockhash is replaced with a
+    /// recent one, so unsigned transactions can be simulated too.
+    pub async fn simulate_transaction(
+        &self,
+        transaction: &str,
+    ) -> Result<WithContext<SimulationResult>, RpcError> {
+        self.simulate_transaction_with_accounts(transaction, &[])
+            .await
+    }
 
-        let response: RpcResponse<u64> = match &self.http_client {
-            #[cfg(target_arch = "wasm32")]
-            HttpClientEnum::Wasm(client) => client
-                .post_json(&self.endpoint, &request)
-                .await
-                .map_err(|e| RpcError::HttpError(e))?,
-            #[cfg(not(target_arch = "wasm32"))]
-            HttpClientEnum::Native(client) => client
-                .post_json(&self.endpoint, &request)
-                .await
-                .map_err(RpcError::Http)?,
-        };
+    /// Simulate a transaction and return the state of `addresses` after it
+    ///
+    /// The accounts are returned in [`SimulationResult::accounts`], in the
+    /// order of `addresses`.
+    pub async fn simulate_transaction_with_accounts(
+        &self,
+        transaction: &str,
+        addresses: &[Pubkey],
+    ) -> Result<WithContext<SimulationResult>, RpcError> {
+        let mut config = json!({
+            "encoding": "base64",
+            "sigVerify": false,
+            "replaceRecentBlockhash": true,
+            "innerInstructions": true
+        });
+        if !addresses.is_empty() {
+            config["accounts"] = json!({
+                "encoding": "base64",
+                "addresses": addresses.iter().map(Pubkey::to_base58).collect::<Vec<_>>()
+            });
+        }
+        let request = RpcRequest::new("simulateTransaction")
+            .param(transaction)
+            .param(config);
+
+        #[derive(Deserialize)]
+        struct Simulation {
+            #[serde(flatten)]
+            result: SimulationResult,
+            #[serde(default)]
+            accounts: Option<Vec<Option<AccountInfo>>>,
+        }
 
-        Ok(response.result)
+        let response: WithContext<Simulation> = self.send(request).await?;
+        let mut result = response.value.result;
+        // A failed simulation reports no accounts
+        if let Some(accounts) = response.value.accounts {
+            result.accounts = addresses
+                .iter()
+                .zip(accounts)
+                .map(|(pubkey, info)| info.map(|info| info.into_account(*pubkey)).transpose())
+                .collect::<Result<_, _>>()?;
+        }
+        Ok(WithContext {
+            context: response.context,
+            value: result,
+        })
     }
 
-    /// Get multiple accounts
-    pub async fn get_multiple_accounts(
+    /// Request an airdrop of `lamports`, refused up front on mainnet
+    pub async fn request_airdrop(
         &self,
-        pubkeys: &[Pubkey],
-    ) -> Result<Vec<Option<Account>>, RpcError> {
-        let pubkey_strings: Vec<String> = pubkeys.iter().map(|pk| pk.to_base58()).collect();
+        pubkey: &Pubkey,
+        lamports: u64,
+    ) -> Result<Signature, RpcError> {
+        self.cluster_capabilities().await?.check("requestAirdrop")?;
 
-        let request = RpcRequest::new("getMultipleAccounts")
-            .param(pubkey_strings)
-            .param(json!({
-                "encoding": "base64"
-            }));
+        let request = RpcRequest::new("requestAirdrop")
+            .param(pubkey.to_base58())
+            .param(lamports);
 
-        let response: RpcResponse<Vec<Option<AccountInfo>>> = self
-            .http_client
-            .post_json(&self.endpoint, &request)
-            .await
-            .map_err(RpcError::Http)?;
+        let result: String = self.send(request).await?;
 
-        Ok(response
-            .result
-            .into_iter()
-            .enumerate()
-            .map(|(i, info)| {
-                info.map(|info| {
-                    let data = info.data.decode_data().unwrap_or_default();
-                    Account {
-                        pubkey: pubkeys[i],
-                        lamports: info.lamports,
-                        data,
-                        owner: info.owner,
-                        executable: info.executable,
-                        rent_epoch: info.rent_epoch,
-                    }
-                })
-            })
-            .collect())
+        Signature::from_base58(&result).map_err(RpcError::InvalidSignature)
     }
-}
 
-/// JSON-RPC request structure
-#[derive(Debug, Clone, Serialize)]
-struct RpcRequest {
-    jsonrpc: String,
-    id: u64,
-    method: String,
-    params: Vec<serde_json::Value>,
-}
+    /// Get the software version of the node
+    pub async fn get_version(&self) -> Result<RpcVersion, RpcError> {
+        self.send(RpcRequest::new("getVersion")).await
+    }
 
-impl RpcRequest {
-    fn new(method: impl Into<String>) -> Self {
-        Self {
-            jsonrpc: "2.0".to_string(),
-            id: 1,
-            method: method.into(),
-            params: Vec::new(),
-        }
+    /// Get the genesis hash identifying the cluster
+    pub async fn get_genesis_hash(&self) -> Result<Hash, RpcError> {
+        self.send(RpcRequest::new("getGenesisHash")).await
     }
 
-    fn param(mut self, param: impl Serialize) -> Self {
-        self.params.push(serde_json::to_value(param).unwrap());
-        self
+    /// Identify the cluster behind the endpoint and the methods it serves
+    ///
+    /// Detected on first use and shared by every clone of the client.
+    pub async fn cluster_capabilities(&self) -> Result<ClusterCapabilities, RpcError> {
+        if let Some(capabilities) = self.cached_cluster() {
+            return Ok(capabilities);
+        }
+
+        let genesis_hash = self.get_genesis_hash().await?;
+        let version = self.get_version().await?;
+        let capabilities = ClusterCapabilities::detect(genesis_hash, version, self.endpoint());
+        if let Ok(mut cluster) = self.inner.cluster.lock() {
+            *cluster = Some(capabilities.clone());
+        }
+        Ok(capabilities)
     }
-}
 
-/// JSON-RPC response structure
-#[derive(Debug, Clone, Deserialize)]
-struct RpcResponse<T> {
-    result: T,
-}
+    /// Fail unless the endpoint belongs to the expected cluster, if any
+    ///
+    /// The genesis hash is fetched once and shared by every clone of the
+    /// client. It is requested directly, as going through [`send`](Self::send)
+    /// would check the cluster again.
+    async fn verify_cluster(&self) -> Result<(), RpcError> {
+        let Some(expected) = &self.inner.config.expected_cluster else {
+            return Ok(());
+        };
+        let Some(expected_hash) = expected.genesis_hash() else {
+            return Ok(());
+        };
 
-/// Account information from RPC
-#[derive(Debug, Clone, Deserialize)]
-struct AccountInfo {
-    lamports: u64,
-    data: AccountData,
-    owner: Pubkey,
-    executable: bool,
-    rent_epoch: u64,
-}
+        let known = *self
+            .inner
+            .genesis_hash
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let genesis_hash = match known {
+            Some(genesis_hash) => genesis_hash,
+            None => {
+                let value = self.execute(&RpcRequest::new("getGenesisHash")).await?;
+                let genesis_hash: Hash = serde_json::from_value(value)
+                    .map_err(|e| RpcError::ParseError(e.to_string()))?;
+                *self
+                    .inner
+                    .genesis_hash
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner()) = Some(genesis_hash);
+                genesis_hash
+            }
+        };
 
-/// Account data structure
-#[derive(Debug, Clone, Deserialize)]
-struct AccountData {
-    #[serde(rename = "data")]
-    data: String, // Base64 encoded data
-}
+        if genesis_hash != expected_hash {
+            return Err(RpcError::WrongCluster {
+                expected: expected.to_string(),
+                genesis_hash,
+            });
+        }
+        Ok(())
+    }
 
-impl AccountData {
-    /// Decode the base64 data
-    fn decode_data(&self) -> Result<Vec<u8>, base64::DecodeError> {
-        use base64::{engine::general_purpose, Engine as _};
-        general_purpose::STANDARD.decode(&self.data)
+    fn cached_cluster(&self) -> Option<ClusterCapabilities> {
+        self.inner
+            .cluster
+            .lock()
+            .ok()
+            .and_then(|cluster| cluster.clone())
     }
-}
 
-/// Balance information from RPC
-#[derive(Debug, Clone, Deserialize)]
-struct BalanceInfo {
-    value: u64,
-}
+    /// Get the current block height
+    pub async fn get_block_height(&self) -> Result<u64, RpcError> {
+        self.get_block_height_with_config(&RpcContextConfig::default())
+            .await
+    }
 
-/// Latest blockhash information from RPC
-#[derive(Debug, Clone, Deserialize)]
-struct LatestBlockhashInfo {
-    value: LatestBlockhash,
-}
+    /// Get the block height at the commitment of `config`
+    pub async fn get_block_height_with_config(
+        &self,
+        config: &RpcContextConfig,
+    ) -> Result<u64, RpcError> {
+        let request = self.with_context(RpcRequest::new("getBlockHeight"), config);
 
-/// Latest blockhash structure
-#[derive(Debug, Clone, Deserialize)]
-pub struct LatestBlockhash {
-    pub blockhash: Hash,
-    #[serde(rename = "lastValidBlockHeight")]
-    pub last_valid_block_height: u64,
-}
+        self.send(request).await
+    }
 
-/// Account structure
-#[derive(Debug, Clone)]
-pub struct Account {
-    pub pubkey: Pubkey,
-    pub lamports: u64,
-    pub data: Vec<u8>,
-    pub owner: Pubkey,
-    pub executable: bool,
-    pub rent_epoch: u64,
-}
+    /// Get the slot the node has processed up to
+    pub async fn get_slot(&self) -> Result<u64, RpcError> {
+        self.get_slot_with_config(&RpcContextConfig::default())
+            .await
+    }
 
-/// RPC error types
-#[derive(Debug, Clone, Error)]
-pub enum RpcError {
-    #[error("HTTP error: {0}")]
-    Http(#[from] HttpError),
+    /// Get the slot reached at the commitment of `config`
+    pub async fn get_slot_with_config(&self, config: &RpcContextConfig) -> Result<u64, RpcError> {
+        let request = self.with_context(RpcRequest::new("getSlot"), config);
 
-    #[error("Invalid signature: {0}")]
-    InvalidSignature(#[from] crate::domain::types::SignatureError),
+        self.send(request).await
+    }
 
-    #[error("Invalid public key: {0}")]
-    InvalidPubkey(#[from] crate::domain::types::PubkeyError),
+    /// Get the current epoch and how far into it the cluster is
+    pub async fn get_epoch_info(&self) -> Result<EpochInfo, RpcError> {
+        let request = self.with_context(
+            RpcRequest::new("getEpochInfo"),
+            &RpcContextConfig::default(),
+        );
 
-    #[error("RPC error: {code} - {message}")]
-    RpcError { code: i64, message: String },
+        self.send(request).await
+    }
 
-    #[error("Parse error: {0}")]
-    ParseError(String),
+    /// Get multiple accounts
+    pub async fn get_multiple_accounts(
+        &self,
+        pubkeys: &[Pubkey],
+    ) -> Result<Vec<Option<Account>>, RpcError> {
+        Ok(self
+            .get_multiple_accounts_with_context(pubkeys, None)
+            .await?
+            .value)
+    }
 
-    #[error("Invalid request: {0}")]
-    InvalidRequest(String),
+    /// Get multiple accounts with the slot they were read at
+    pub async fn get_multiple_accounts_with_context(
+        &self,
+        pubkeys: &[Pubkey],
+        min_context_slot: Option<u64>,
+    ) -> Result<WithContext<Vec<Option<Account>>>, RpcError> {
+        let config = AccountReadConfig {
+            min_context_slot,
+            ..AccountReadConfig::default()
+        };
+        self.get_multiple_accounts_with_config(pubkeys, &config)
+            .await
+    }
 
-    #[error("Method not found: {0}")]
-    MethodNotFound(String),
+    /// Get multiple accounts read with `config`, as of one slot
+    ///
+    /// Every account is read at the returned context's slot, so
+    /// [`WithContext::split`] can hand each one its own context for caching
+    /// next to single reads.
+    pub async fn get_multiple_accounts_with_config(
+        &self,
+        pubkeys: &[Pubkey],
+        config: &AccountReadConfig,
+    ) -> Result<WithContext<Vec<Option<Account>>>, RpcError> {
+        let pubkey_strings: Vec<String> = pubkeys.iter().map(|pk| pk.to_base58()).collect();
 
-    #[error("Internal error: {0}")]
-    InternalError(String),
-}
+        let request = RpcRequest::new("getMultipleAccounts")
+            .param(pubkey_strings)
+            .param(self.read_params(config));
 
-/// Network configuration
-#[derive(Debug, Clone, PartialEq)]
-pub enum Network {
-    Mainnet,
-    Testnet,
-    Devnet,
-    Custom(String),
-}
+        let result: WithContext<Vec<Option<AccountInfo>>> = self.send(request).await?;
 
-impl std::fmt::Display for Network {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Network::Mainnet => write!(f, "Mainnet"),
-            Network::Testnet => write!(f, "Testnet"),
-            Network::Devnet => write!(f, "Devnet"),
-            Network::Custom(url) => write!(f, "Custom({})", url),
-        }
+        let value = result
+            .value
+            .into_iter()
+            .zip(pubkeys)
+            .map(|(info, pubkey)| info.map(|info| info.into_account(*pubkey)).transpose())
+            .collect::<Result<_, _>>()?;
+        Ok(WithContext {
+            context: result.context,
+            value,
+        })
     }
-}
 
-impl Network {
-    /// Get the RPC endpoint for this network
-    pub fn endpoint(&self) -> &str {
-        match self {
-            Network::Mainnet => "https://api.mainnet-beta.solana.com",
-            Network::Testnet => "https://api.testnet.solana.com",
-            Network::Devnet => "https://api.devnet.solana.com",
-            Network::Custom(url) => url,
-        }
+    /// Get all accounts owned by a program that match every filter
+    pub async fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        filters: &[Filter],
+    ) -> Result<Vec<Account>, RpcError> {
+        self.get_program_accounts_with_config(program_id, filters, &AccountReadConfig::default())
+            .await
     }
-}
 
-/// Create a network configuration for surfpool (simnet)
-pub fn surfpool_network() -> Network {
-    Network::Custom("http://127.0.0.1:8899".to_string())
-}
+    /// Get the accounts of a program that match every filter, read with `config`
+    pub async fn get_program_accounts_with_config(
+        &self,
+        program_id: &Pubkey,
+        filters: &[Filter],
+        config: &AccountReadConfig,
+    ) -> Result<Vec<Account>, RpcError> {
+        let mut params = self.read_params(config);
+        if !filters.is_empty() {
+            params["filters"] = json!(filters);
+        }
 
-/// RPC client builder
-pub struct RpcClientBuilder {
+        let request = RpcRequest::new("getProgramAccounts")
+            .param(program_id.to_base58())
+            .param(params);
+
+        let result: Vec<KeyedAccountInfo> = self.send(request).await?;
+
+        result
+            .into_iter()
+            .map(|keyed| keyed.account.into_account(keyed.pubkey))
+            .collect()
+    }
+
+    /// Get signatures of transactions involving an address, newest first
+    pub async fn get_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        config: SignaturesForAddressConfig,
+    ) -> Result<Vec<SignatureInfo>, RpcError> {
+        let request = RpcRequest::new("getSignaturesForAddress")
+            .param(address.to_base58())
+            .try_param(config)?;
+
+        self.send(request).await
+    }
+
+    /// Get the processing status of transactions, in the order given
+    pub async fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> Result<Vec<Option<SignatureStatus>>, RpcError> {
+        let signatures: Vec<String> = signatures.iter().map(|sig| sig.to_base58()).collect();
+        let request = RpcRequest::new("getSignatureStatuses").param(signatures);
+
+        let result: WithContext<Vec<Option<SignatureStatus>>> = self.send(request).await?;
+
+        Ok(result.value)
+    }
+
+    /// Get the 20 largest token accounts of a mint
+    pub async fn get_token_largest_accounts(
+        &self,
+        mint: &Pubkey,
+    ) -> Result<Vec<TokenAccountBalance>, RpcError> {
+        Ok(self
+            .get_token_largest_accounts_with_config(mint, &RpcContextConfig::default())
+            .await?
+            .value)
+    }
+
+    /// Get the 20 largest token accounts of a mint at the commitment of `config`
+    pub async fn get_token_largest_accounts_with_config(
+        &self,
+        mint: &Pubkey,
+        config: &RpcContextConfig,
+    ) -> Result<WithContext<Vec<TokenAccountBalance>>, RpcError> {
+        let request = self.with_context(
+            RpcRequest::new("getTokenLargestAccounts").param(mint.to_base58()),
+            config,
+        );
+
+        self.send(request).await
+    }
+
+    /// Get the total supply of a mint
+    pub async fn get_token_supply(&self, mint: &Pubkey) -> Result<UiTokenAmount, RpcError> {
+        Ok(self
+            .get_token_supply_with_config(mint, &RpcContextConfig::default())
+            .await?
+            .value)
+    }
+
+    /// Get the total supply of a mint at the commitment of `config`
+    pub async fn get_token_supply_with_config(
+        &self,
+        mint: &Pubkey,
+        config: &RpcContextConfig,
+    ) -> Result<WithContext<UiTokenAmount>, RpcError> {
+        let request = self.with_context(
+            RpcRequest::new("getTokenSupply").param(mint.to_base58()),
+            config,
+        );
+
+        self.send(request).await
+    }
+
+    /// Get a confirmed transaction
+    pub async fn get_transaction(
+        &self,
+        signature: &Signature,
+    ) -> Result<Option<ConfirmedTransaction>, RpcError> {
+        let request = RpcRequest::new("getTransaction")
+            .param(signature.to_base58())
+            .param(json!({
+                "encoding": "json",
+                "maxSupportedTransactionVersion": 0
+            }));
+
+        let Some(cache) = &self.inner.config.transaction_cache else {
+            return self.send(request).await;
+        };
+        let value = match cache.get_value(signature) {
+            Some(value) => value,
+            None => {
+                let value: serde_json::Value = self.send(request).await?;
+                cache.insert_value(signature, &value);
+                value
+            }
+        };
+        serde_json::from_value(value).map_err(|e| RpcError::ParseError(e.to_string()))
+    }
+
+    /// Get the header and transaction signatures of the block at `slot`
+    ///
+    /// Returns `None` for skipped slots and blocks the node no longer has.
+    pub async fn get_block(&self, slot: u64) -> Result<Option<ConfirmedBlock>, RpcError> {
+        let request = RpcRequest::new("getBlock").param(slot).param(json!({
+            "encoding": "json",
+            "transactionDetails": "signatures",
+            "rewards": false,
+            "maxSupportedTransactionVersion": 0
+        }));
+
+        match self.send(request).await {
+            Err(RpcError::RpcError { code, .. }) if BLOCK_UNAVAILABLE_CODES.contains(&code) => {
+                Ok(None)
+            }
+            result => result,
+        }
+    }
+
+    /// Get the estimated production time of the block at `slot`
+    ///
+    /// Returns `None` for skipped slots and blocks without a recorded time.
+    pub async fn get_block_time(&self, slot: u64) -> Result<Option<i64>, RpcError> {
+        let request = RpcRequest::new("getBlockTime").param(slot);
+
+        match self.send(request).await {
+            Err(RpcError::RpcError { code, .. }) if BLOCK_UNAVAILABLE_CODES.contains(&code) => {
+                Ok(None)
+            }
+            result => result,
+        }
+    }
+
+    /// Get the staking rewards credited to `addresses` for `epoch`
+    ///
+    /// Without `epoch`, the last completed epoch is used. Addresses that
+    /// earned nothing that epoch are `None`.
+    pub async fn get_inflation_reward(
+        &self,
+        addresses: &[Pubkey],
+        epoch: Option<u64>,
+    ) -> Result<Vec<Option<InflationReward>>, RpcError> {
+        self.get_inflation_reward_with_config(addresses, epoch, &RpcContextConfig::default())
+            .await
+    }
+
+    /// Get the staking rewards credited to `addresses` for `epoch`, read with `config`
+    pub async fn get_inflation_reward_with_config(
+        &self,
+        addresses: &[Pubkey],
+        epoch: Option<u64>,
+        config: &RpcContextConfig,
+    ) -> Result<Vec<Option<InflationReward>>, RpcError> {
+        let addresses: Vec<String> = addresses.iter().map(Pubkey::to_base58).collect();
+        let mut request = RpcRequest::new("getInflationReward").param(addresses);
+        let mut params = self.context_params(config).unwrap_or_else(|| json!({}));
+        if let Some(epoch) = epoch {
+            params["epoch"] = json!(epoch);
+        }
+        if params.as_object().is_some_and(|params| !params.is_empty()) {
+            request = request.param(params);
+        }
+
+        self.send(request).await
+    }
+
+    /// Get the smallest delegation, in lamports, the stake program accepts
+    pub async fn get_stake_minimum_delegation(&self) -> Result<u64, RpcError> {
+        let request = self.with_context(
+            RpcRequest::new("getStakeMinimumDelegation"),
+            &RpcContextConfig::default(),
+        );
+
+        let result: WithContext<u64> = self.send(request).await?;
+
+        Ok(result.value)
+    }
+
+    /// Get the fee the cluster charges for a base64-encoded message
+    ///
+    /// `None` when the node no longer knows the message's blockhash.
+    pub async fn get_fee_for_message(&self, message: &str) -> Result<Option<u64>, RpcError> {
+        let request = self.with_context(
+            RpcRequest::new("getFeeForMessage").param(message),
+            &RpcContextConfig::default(),
+        );
+
+        let result: WithContext<Option<u64>> = self.send(request).await?;
+
+        Ok(result.value)
+    }
+
+    /// Get the lamports an account holding `data_len` bytes needs to be rent exempt
+    pub async fn get_minimum_balance_for_rent_exemption(
+        &self,
+        data_len: usize,
+    ) -> Result<u64, RpcError> {
+        let request = self.with_context(
+            RpcRequest::new("getMinimumBalanceForRentExemption").param(data_len),
+            &RpcContextConfig::default(),
+        );
+
+        self.send(request).await
+    }
+
+    /// `config` as a params object, reading at the client's commitment unless it sets one
+    fn context_params(&self, config: &RpcContextConfig) -> Option<serde_json::Value> {
+        config.or_commitment(self.commitment()).to_param()
+    }
+
+    /// `request` with the config object of `config` appended, if any
+    fn with_context(&self, request: RpcRequest, config: &RpcContextConfig) -> RpcRequest {
+        match self.context_params(config) {
+            Some(params) => request.param(params),
+            None => request,
+        }
+    }
+
+    /// Config object of an account read, at the client's commitment unless it sets one
+    fn read_params(&self, config: &AccountReadConfig) -> serde_json::Value {
+        let config = AccountReadConfig {
+            commitment: config.commitment.or(self.commitment()),
+            ..*config
+        };
+        read_config(self.account_encoding(), &config)
+    }
+
+    /// Encoding requested for account data
+    fn account_encoding(&self) -> AccountEncoding {
+        if self.inner.config.zstd_account_data {
+            AccountEncoding::Base64Zstd
+        } else {
+            AccountEncoding::Base64
+        }
+    }
+
+    /// Send a request through interceptors, cache and rate limiter
+    async fn send<T>(&self, request: RpcRequest) -> Result<T, RpcError>
+    where
+        T: DeserializeOwned,
+    {
+        let inner = &self.inner;
+
+        if self.cancel.as_ref().is_some_and(CancelToken::is_cancelled) {
+            return Err(RpcError::Cancelled);
+        }
+
+        if request.method != "getGenesisHash" {
+            self.verify_cluster().await?;
+        }
+
+        for interceptor in &inner.interceptors {
+            interceptor.before_request(&request.method, &request.params);
+        }
+
+        let cache_key = inner
+            .cache
+            .as_ref()
+            .filter(|_| ResponseCache::is_cacheable(&request.method))
+            .map(|_| ResponseCache::key(&request.method, &request.params));
+
+        if let (Some(cache), Some(_)) = (&inner.cache, &cache_key) {
+            if cache.needs_slot_poll() {
+                match self.execute(&RpcRequest::new("getSlot")).await {
+                    Ok(slot) => match slot.as_u64() {
+                        Some(slot) => cache.observe_slot(slot),
+                        None => cache.slot_poll_failed(),
+                    },
+                    Err(_) => cache.slot_poll_failed(),
+                }
+            }
+        }
+
+        let cached = match (&inner.cache, &cache_key) {
+            (Some(cache), Some(key)) => cache.get(key),
+            _ => None,
+        };
+
+        let result = match cached {
+            Some(value) => {
+                inner.metrics.record(&RequestEvent {
+                    method: &request.method,
+                    latency: Duration::ZERO,
+                    cached: true,
+                    error: None,
+                });
+                Ok(value)
+            }
+            None => {
+                if let Some(rate_limiter) = &inner.rate_limiter {
+                    rate_limiter.acquire().await;
+                }
+
+                let started = Instant::now();
+                let result = self.execute(&request).await;
+                inner.metrics.record(&RequestEvent {
+                    method: &request.method,
+                    latency: started.elapsed(),
+                    cached: false,
+                    error: result.as_ref().err().map(ErrorKind::of),
+                });
+
+                if let (Some(cache), Ok(value)) = (&inner.cache, &result) {
+                    cache.observe_response(value);
+                    // A null result, such as a transaction not found yet, may
+                    // be filled in by the next poll
+                    if let Some(key) = cache_key.filter(|_| !value.is_null()) {
+                        cache.insert(key, value.clone());
+                    }
+                }
+
+                result
+            }
+        };
+
+        for interceptor in &inner.interceptors {
+            interceptor.after_response(&request.method, result.as_ref());
+        }
+
+        serde_json::from_value(result?).map_err(|e| RpcError::ParseError(e.to_string()))
+    }
+
+    /// Perform the HTTP round trip and unwrap the JSON-RPC envelope
+    async fn execute(&self, request: &RpcRequest) -> Result<serde_json::Value, RpcError> {
+        let fetched = match &self.cancel {
+            Some(token) => token
+                .run(self.fetch(request))
+                .await
+                .unwrap_or(Err(HttpError::Cancelled)),
+            None => self.fetch(request).await,
+        };
+        let value = fetched.map_err(|error| match error {
+            HttpError::ResponseTooLarge { limit } => RpcError::ResponseTooLarge { limit },
+            HttpError::Cancelled => RpcError::Cancelled,
+            error => RpcError::Http(error),
+        })?;
+        let response: RpcResponse<serde_json::Value> = serde_json::from_value(value)
+            .map_err(|e| RpcError::Http(HttpError::DeserializationError(e.to_string())))?;
+
+        Ok(response.into_result()?.unwrap_or(serde_json::Value::Null))
+    }
+
+    /// POST `request` and parse the response body, within the size limit
+    async fn fetch(&self, request: &RpcRequest) -> Result<serde_json::Value, HttpError> {
+        let inner = &self.inner;
+        let max_size = inner.config.max_response_size;
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            if let HttpClientEnum::Native(client) = &inner.http_client {
+                if inner.config.stream_responses {
+                    return client
+                        .post_json_streaming(&inner.endpoint, request, max_size)
+                        .await;
+                }
+                if let Some(max_size) = max_size {
+                    let body = client
+                        .post_json_text_limited(&inner.endpoint, request, max_size)
+                        .await?;
+                    return inner
+                        .json_parser
+                        .parse(body)
+                        .map_err(|e| HttpError::DeserializationError(e.to_string()));
+                }
+            }
+        }
+
+        let body = match (&self.cancel, &inner.http_client) {
+            // Dropping a browser fetch does not stop it, so abort it explicitly
+            #[cfg(target_arch = "wasm32")]
+            (Some(token), HttpClientEnum::Wasm(client)) => {
+                client
+                    .post_json_text_cancellable(&inner.endpoint, request, token)
+                    .await?
+            }
+            _ => {
+                inner
+                    .http_client
+                    .post_json_text(&inner.endpoint, request)
+                    .await?
+            }
+        };
+        // The body has already been downloaded or replayed, but it is not parsed
+        if let Some(limit) = max_size.filter(|limit| body.len() > *limit) {
+            return Err(HttpError::ResponseTooLarge { limit });
+        }
+        inner
+            .json_parser
+            .parse(body)
+            .map_err(|e| HttpError::DeserializationError(e.to_string()))
+    }
+}
+
+/// Config object for account reads
+fn read_config(encoding: AccountEncoding, config: &AccountReadConfig) -> serde_json::Value {
+    let mut params = json!({
+        "encoding": encoding
+    });
+    if let Some(commitment) = config.commitment {
+        params["commitment"] = json!(commitment);
+    }
+    if let Some(slice) = config.data_slice {
+        params["dataSlice"] = json!(slice);
+    }
+    if let Some(slot) = config.min_context_slot {
+        params["minContextSlot"] = json!(slot);
+    }
+    params
+}
+
+fn default_http_client() -> Result<HttpClientEnum, HttpError> {
+    #[cfg(target_arch = "wasm32")]
+    {
+        Ok(HttpClientEnum::Wasm(WasmHttpClient::new()))
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        Ok(HttpClientEnum::Native(NativeHttpClient::new()?))
+    }
+}
+
+/// Account information from RPC
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AccountInfo {
+    lamports: u64,
+    data: AccountData,
+    owner: Pubkey,
+    executable: bool,
+    rent_epoch: u64,
+}
+
+impl AccountInfo {
+    fn into_account(self, pubkey: Pubkey) -> Result<Account, RpcError> {
+        let data = self
+            .data
+            .decode()
+            .map_err(|e| RpcError::ParseError(format!("Account {}: {}", pubkey, e)))?;
+        Ok(Account {
+            pubkey,
+            lamports: self.lamports,
+            data,
+            owner: self.owner,
+            executable: self.executable,
+            rent_epoch: self.rent_epoch,
+        })
+    }
+}
+
+/// Decode an account in the `getAccountInfo` result shape, as carried by
+/// `accountNotification`s
+pub(crate) fn parse_account(
+    pubkey: Pubkey,
+    value: serde_json::Value,
+) -> Result<Option<Account>, RpcError> {
+    let info: Option<AccountInfo> =
+        serde_json::from_value(value).map_err(|e| RpcError::ParseError(e.to_string()))?;
+    info.map(|info| info.into_account(pubkey)).transpose()
+}
+
+/// Account returned by `getProgramAccounts`
+#[derive(Debug, Clone, Deserialize)]
+struct KeyedAccountInfo {
+    pubkey: Pubkey,
+    account: AccountInfo,
+}
+
+/// Latest blockhash structure
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LatestBlockhash {
+    pub blockhash: Hash,
+    #[serde(rename = "lastValidBlockHeight")]
+    pub last_valid_block_height: u64,
+}
+
+/// Account structure
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Account {
+    pub pubkey: Pubkey,
+    pub lamports: u64,
+    pub data: Vec<u8>,
+    pub owner: Pubkey,
+    pub executable: bool,
+    pub rent_epoch: u64,
+}
+
+impl Account {
+    /// Decode the data as Anchor account type `T`, checking its discriminator
+    pub fn decode<T>(&self) -> Result<T, DiscriminatorError>
+    where
+        T: Discriminator + borsh::BorshDeserialize,
+    {
+        decode_account(&self.data)
+    }
+}
+
+impl AccountState for Account {
+    fn lamports(&self) -> u64 {
+        self.lamports
+    }
+
+    fn owner(&self) -> Pubkey {
+        self.owner
+    }
+
+    fn executable(&self) -> bool {
+        self.executable
+    }
+
+    fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+/// Range of account data to return instead of the whole account
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct DataSlice {
+    pub offset: usize,
+    pub length: usize,
+}
+
+/// Options for `getAccountInfo` and `getMultipleAccounts`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AccountReadConfig {
+    /// Commitment to read at, the node's default when `None`
+    pub commitment: Option<CommitmentLevel>,
+    /// Return only this range of each account's data
+    pub data_slice: Option<DataSlice>,
+    /// Refuse to answer before the node reaches this slot
+    pub min_context_slot: Option<u64>,
+}
+
+impl AccountReadConfig {
+    /// Read whole accounts at the node's default commitment
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the commitment
+    pub fn commitment(mut self, commitment: CommitmentLevel) -> Self {
+        self.commitment = Some(commitment);
+        self
+    }
+
+    /// Return `length` bytes of data from `offset`
+    pub fn data_slice(mut self, offset: usize, length: usize) -> Self {
+        self.data_slice = Some(DataSlice { offset, length });
+        self
+    }
+
+    /// Return only `field` of layout `L` from each account
+    pub fn field_slice<L: AccountLayout>(self, field: &str) -> Result<Self, FilterError> {
+        let range = L::field_range(field).ok_or_else(|| filters::unknown_field::<L>(field))?;
+        Ok(self.data_slice(range.start, range.len()))
+    }
+
+    /// Set the minimum context slot
+    pub fn min_context_slot(mut self, slot: u64) -> Self {
+        self.min_context_slot = Some(slot);
+        self
+    }
+
+    /// The commitment and minimum context slot of these options
+    pub fn context(&self) -> RpcContextConfig {
+        RpcContextConfig {
+            commitment: self.commitment,
+            min_context_slot: self.min_context_slot,
+        }
+    }
+}
+
+impl From<RpcContextConfig> for AccountReadConfig {
+    fn from(context: RpcContextConfig) -> Self {
+        Self {
+            commitment: context.commitment,
+            min_context_slot: context.min_context_slot,
+            data_slice: None,
+        }
+    }
+}
+
+/// Options for `getSignaturesForAddress`
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignaturesForAddressConfig {
+    /// Start searching backwards from this signature
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub before: Option<Signature>,
+    /// Stop searching at this signature
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub until: Option<Signature>,
+    /// Maximum number of signatures to return (up to 1000)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+}
+
+/// Entry returned by `getSignaturesForAddress`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignatureInfo {
+    pub signature: Signature,
+    pub slot: u64,
+    #[serde(default)]
+    pub err: Option<serde_json::Value>,
+    #[serde(default)]
+    pub memo: Option<String>,
+    #[serde(default)]
+    pub block_time: Option<i64>,
+}
+
+/// Processing status of a transaction from `getSignatureStatuses`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignatureStatus {
+    pub slot: u64,
+    /// Blocks since the transaction was processed; `None` once rooted
+    #[serde(default)]
+    pub confirmations: Option<u64>,
+    #[serde(default)]
+    pub err: Option<serde_json::Value>,
+    #[serde(default)]
+    pub confirmation_status: Option<CommitmentLevel>,
+}
+
+impl SignatureStatus {
+    /// Whether the transaction has reached at least `commitment`
+    pub fn satisfies(&self, commitment: CommitmentLevel) -> bool {
+        match self.confirmation_status {
+            Some(status) => status >= commitment,
+            // Nodes that predate `confirmationStatus` report rooted
+            // transactions with no confirmation count
+            None => self.confirmations.is_none() || commitment == CommitmentLevel::Processed,
+        }
+    }
+
+    /// Decode the transaction error, if it failed
+    pub fn error(&self) -> Option<TransactionError> {
+        self.err
+            .as_ref()
+            .filter(|err| !err.is_null())
+            .map(TransactionError::from_value)
+    }
+}
+
+/// Transaction returned by `getTransaction`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfirmedTransaction {
+    pub slot: u64,
+    #[serde(default)]
+    pub block_time: Option<i64>,
+    #[serde(default)]
+    pub meta: Option<TransactionMeta>,
+    pub transaction: UiTransaction,
+}
+
+impl ConfirmedTransaction {
+    /// Account keys in index order, including addresses loaded from lookup tables
+    pub fn account_keys(&self) -> Vec<Pubkey> {
+        let mut keys = self.transaction.message.account_keys.clone();
+        if let Some(loaded) = self
+            .meta
+            .as_ref()
+            .and_then(|meta| meta.loaded_addresses.as_ref())
+        {
+            keys.extend(loaded.writable.iter().copied());
+            keys.extend(loaded.readonly.iter().copied());
+        }
+        keys
+    }
+
+    /// The transaction error, if it failed
+    pub fn error(&self) -> Option<TransactionError> {
+        self.meta
+            .as_ref()
+            .and_then(|meta| meta.err.as_ref())
+            .filter(|err| !err.is_null())
+            .map(TransactionError::from_value)
+    }
+}
+
+/// Block returned by `getBlock` with signature-only transaction details
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfirmedBlock {
+    pub blockhash: Hash,
+    pub previous_blockhash: Hash,
+    pub parent_slot: u64,
+    #[serde(default)]
+    pub block_time: Option<i64>,
+    #[serde(default)]
+    pub block_height: Option<u64>,
+    #[serde(default)]
+    pub signatures: Vec<Signature>,
+}
+
+/// Status metadata of a confirmed transaction
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionMeta {
+    #[serde(default)]
+    pub err: Option<serde_json::Value>,
+    pub fee: u64,
+    pub pre_balances: Vec<u64>,
+    pub post_balances: Vec<u64>,
+    #[serde(default)]
+    pub pre_token_balances: Option<Vec<TokenBalance>>,
+    #[serde(default)]
+    pub post_token_balances: Option<Vec<TokenBalance>>,
+    #[serde(default)]
+    pub log_messages: Option<Vec<String>>,
+    #[serde(default)]
+    pub loaded_addresses: Option<LoadedAddresses>,
+    #[serde(default)]
+    pub compute_units_consumed: Option<u64>,
+}
+
+/// Outcome of `simulateTransaction`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulationResult {
+    #[serde(default)]
+    pub err: Option<serde_json::Value>,
+    #[serde(default)]
+    pub logs: Option<Vec<String>>,
+    /// Compute units the transaction consumed
+    #[serde(default)]
+    pub units_consumed: Option<u64>,
+    /// Instructions invoked by each instruction, `None` if the node does not
+    /// report them
+    #[serde(default)]
+    pub inner_instructions: Option<Vec<InnerInstructions>>,
+    /// State of the requested accounts after the transaction, empty if it
+    /// failed or none were requested
+    #[serde(skip)]
+    pub accounts: Vec<Option<Account>>,
+}
+
+impl SimulationResult {
+    /// The error the transaction failed with, if it did
+    pub fn error(&self) -> Option<TransactionError> {
+        self.err
+            .as_ref()
+            .filter(|err| !err.is_null())
+            .map(TransactionError::from_value)
+    }
+}
+
+/// Addresses loaded from address lookup tables
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct LoadedAddresses {
+    pub writable: Vec<Pubkey>,
+    pub readonly: Vec<Pubkey>,
+}
+
+/// SPL token balance of one account before or after a transaction
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenBalance {
+    pub account_index: usize,
+    pub mint: Pubkey,
+    #[serde(default)]
+    pub owner: Option<Pubkey>,
+    pub ui_token_amount: UiTokenAmount,
+}
+
+/// Token amount with its decimals
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UiTokenAmount {
+    pub amount: String,
+    pub decimals: u8,
+    #[serde(default)]
+    pub ui_amount_string: Option<String>,
+}
+
+impl UiTokenAmount {
+    /// The amount in base units
+    pub fn raw_amount(&self) -> Result<u64, RpcError> {
+        self.amount
+            .parse()
+            .map_err(|_| RpcError::ParseError(format!("Invalid token amount: {}", self.amount)))
+    }
+
+    /// The amount scaled by the mint's decimals
+    pub fn ui_amount(&self) -> Result<f64, RpcError> {
+        Ok(self.raw_amount()? as f64 / 10f64.powi(self.decimals as i32))
+    }
+}
+
+/// Position in the current epoch, from `getEpochInfo`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EpochInfo {
+    pub epoch: u64,
+    /// Slot the node has reached
+    pub absolute_slot: u64,
+    pub block_height: u64,
+    /// Slots of the epoch already past
+    pub slot_index: u64,
+    pub slots_in_epoch: u64,
+    #[serde(default)]
+    pub transaction_count: Option<u64>,
+}
+
+impl EpochInfo {
+    /// Slots left until the next epoch starts
+    pub fn slots_remaining(&self) -> u64 {
+        self.slots_in_epoch.saturating_sub(self.slot_index)
+    }
+
+    /// Fraction of the epoch completed, from 0 to 1
+    pub fn progress(&self) -> f64 {
+        match self.slots_in_epoch {
+            0 => 0.0,
+            slots => (self.slot_index as f64 / slots as f64).min(1.0),
+        }
+    }
+}
+
+/// Staking reward credited at the start of an epoch, from `getInflationReward`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InflationReward {
+    /// Epoch the reward was earned in
+    pub epoch: u64,
+    /// Slot the reward was credited at
+    pub effective_slot: u64,
+    /// Reward in lamports
+    pub amount: u64,
+    /// Balance after the reward
+    pub post_balance: u64,
+    /// Vote account commission when the reward was credited
+    #[serde(default)]
+    pub commission: Option<u8>,
+}
+
+impl InflationReward {
+    /// Balance the reward was earned on
+    pub fn pre_balance(&self) -> u64 {
+        self.post_balance.saturating_sub(self.amount)
+    }
+
+    /// Reward as a fraction of the balance it was earned on
+    pub fn rate(&self) -> f64 {
+        match self.pre_balance() {
+            0 => 0.0,
+            balance => self.amount as f64 / balance as f64,
+        }
+    }
+}
+
+/// Token account balance returned by `getTokenLargestAccounts`
+#[derive(Debug, Clone, Deserialize)]
+pub struct TokenAccountBalance {
+    pub address: Pubkey,
+    #[serde(flatten)]
+    pub amount: UiTokenAmount,
+}
+
+/// JSON-encoded transaction
+#[derive(Debug, Clone, Deserialize)]
+pub struct UiTransaction {
+    pub signatures: Vec<Signature>,
+    pub message: UiMessage,
+}
+
+/// JSON-encoded transaction message
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UiMessage {
+    #[serde(default)]
+    pub header: Option<MessageHeader>,
+    pub account_keys: Vec<Pubkey>,
+    pub recent_blockhash: Hash,
+    #[serde(default)]
+    pub instructions: Vec<UiCompiledInstruction>,
+    /// Lookup tables of a version 0 message, absent for legacy messages
+    #[serde(default)]
+    pub address_table_lookups: Option<Vec<UiAddressTableLookup>>,
+}
+
+/// Address lookup table referenced by a version 0 message
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UiAddressTableLookup {
+    pub account_key: Pubkey,
+    pub writable_indexes: Vec<u8>,
+    pub readonly_indexes: Vec<u8>,
+}
+
+/// JSON-encoded compiled instruction
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UiCompiledInstruction {
+    pub program_id_index: u8,
+    pub accounts: Vec<u8>,
+    /// Base58-encoded instruction data
+    pub data: String,
+    /// Invocation depth, 1 for top-level instructions, if reported
+    #[serde(default)]
+    pub stack_height: Option<u32>,
+}
+
+/// Instructions invoked through cross-program calls by one instruction
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InnerInstructions {
+    /// Index of the top-level instruction that made the calls
+    pub index: u8,
+    pub instructions: Vec<UiCompiledInstruction>,
+}
+
+/// RPC error types
+#[derive(Debug, Clone, Error)]
+pub enum RpcError {
+    #[error("HTTP error: {0}")]
+    Http(#[from] HttpError),
+
+    #[error("Invalid signature: {0}")]
+    InvalidSignature(#[from] crate::domain::types::SignatureError),
+
+    #[error("Invalid public key: {0}")]
+    InvalidPubkey(#[from] crate::domain::types::PubkeyError),
+
+    #[error("RPC error: {code} - {message}")]
+    RpcError {
+        code: i64,
+        message: String,
+        data: Option<serde_json::Value>,
+    },
+
+    #[error("Parse error: {0}")]
+    ParseError(String),
+
+    #[error("Invalid request: {0}")]
+    InvalidRequest(String),
+
+    #[error("Method not found: {0}")]
+    MethodNotFound(String),
+
+    #[error("Internal error: {0}")]
+    InternalError(String),
+
+    #[error("Response exceeds the {limit}-byte size limit")]
+    ResponseTooLarge { limit: usize },
+
+    #[error("{method} is not available on {cluster}")]
+    Unsupported {
+        method: String,
+        cluster: ClusterKind,
+    },
+
+    #[error("Request was cancelled")]
+    Cancelled,
+
+    #[error("Endpoint is not on {expected}: its genesis hash is {genesis_hash}")]
+    WrongCluster {
+        /// Name of the expected network
+        expected: String,
+        genesis_hash: Hash,
+    },
+}
+
+/// Decode a serialized transaction given in base58 or base64
+///
+/// Base58 never uses `0`, `O`, `I`, `l`, `+`, `/` or `=`, and a base64
+/// transaction is all but certain to contain one of them, so a string made
+/// only of base58 characters is read as base58.
+pub fn decode_wire_transaction(encoded: &str) -> Result<Vec<u8>, RpcError> {
+    let encoded = encoded.trim();
+    if encoded.is_empty() {
+        return Err(RpcError::InvalidRequest("Empty transaction".to_string()));
+    }
+    match bs58::decode(encoded).into_vec() {
+        Ok(bytes) => Ok(bytes),
+        Err(_) => general_purpose::STANDARD.decode(encoded).map_err(|e| {
+            RpcError::InvalidRequest(format!("Transaction is neither base58 nor base64: {}", e))
+        }),
+    }
+}
+
+/// JSON-RPC error code returned when a node is behind `minContextSlot`
+pub const MIN_CONTEXT_SLOT_NOT_REACHED: i64 = -32016;
+
+/// `getBlock` and `getBlockTime` error codes for skipped, cleaned up and missing blocks
+const BLOCK_UNAVAILABLE_CODES: [i64; 3] = [-32004, -32007, -32009];
+
+impl RpcError {
+    /// Whether the node had not yet reached the requested `minContextSlot`
+    pub fn is_min_context_slot_not_reached(&self) -> bool {
+        matches!(
+            self,
+            RpcError::RpcError { code, .. } if *code == MIN_CONTEXT_SLOT_NOT_REACHED
+        )
+    }
+
+    /// Decode the transaction error carried by a preflight/simulation failure
+    pub fn transaction_error(&self) -> Option<TransactionError> {
+        match self {
+            RpcError::RpcError {
+                data: Some(data), ..
+            } => data
+                .get("err")
+                .filter(|err| !err.is_null())
+                .map(TransactionError::from_value),
+            _ => None,
+        }
+    }
+
+    /// Program logs attached to a preflight/simulation failure
+    pub fn logs(&self) -> Vec<String> {
+        match self {
+            RpcError::RpcError {
+                data: Some(data), ..
+            } => data
+                .get("logs")
+                .and_then(|logs| logs.as_array())
+                .map(|logs| {
+                    logs.iter()
+                        .filter_map(|line| line.as_str().map(str::to_string))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// RPC client builder
+pub struct RpcClientBuilder {
     endpoint: String,
     config: RpcClientConfig,
 }
@@ -431,32 +1779,244 @@ impl RpcClientBuilder {
         }
     }
 
-    /// Set commitment level
+    /// Create a builder for the endpoint and settings of a configuration file
+    pub fn from_config(config: &ClientConfig) -> Result<Self, ConfigError> {
+        let network = config.resolve_network()?;
+        let mut builder = Self::new(network.endpoint());
+        if let Some(commitment) = config.commitment {
+            builder = builder.commitment(commitment);
+        }
+        for (name, value) in config.all_headers() {
+            builder = builder.header(name, value);
+        }
+        if let Some(user_agent) = &config.user_agent {
+            builder = builder.user_agent(user_agent.clone());
+        }
+        if let Some(requests_per_second) = config.rate_limit {
+            builder = builder.rate_limit(requests_per_second);
+        }
+        match (config.cache_ttl(), config.cache_slots) {
+            (Some(_), Some(_)) => return Err(ConfigError::ConflictingCache),
+            (Some(ttl), None) => builder = builder.cache_ttl(ttl),
+            (None, Some(slots)) => builder = builder.cache_for_slots(slots),
+            (None, None) => {}
+        }
+        Ok(builder)
+    }
+
+    /// Set the commitment of every read that does not choose its own
+    ///
+    /// Applies to the methods taking an [`RpcContextConfig`] or an
+    /// [`AccountReadConfig`], and to their shorthands.
     pub fn commitment(mut self, commitment: CommitmentLevel) -> Self {
         self.config.commitment = Some(commitment);
         self
     }
 
-    /// Build the RPC client
-    pub fn build(self) -> SolanaRpcClient {
-        SolanaRpcClient::with_endpoint(self.endpoint)
+    /// Refuse to talk to an endpoint outside `network`
+    ///
+    /// The endpoint's genesis hash is compared with the network's on first
+    /// use, and every request fails with [`RpcError::WrongCluster`] if they
+    /// differ. Custom networks without a genesis hash are not checked.
+    pub fn expect_cluster(mut self, network: Network) -> Self {
+        self.config.expected_cluster = Some(network);
+        self
+    }
+
+    /// Register an interceptor invoked around every request
+    pub fn interceptor(mut self, interceptor: impl RpcInterceptor + 'static) -> Self {
+        self.config.interceptors.push(Arc::new(interceptor));
+        self
+    }
+
+    /// Cache read responses for the given duration
+    pub fn cache_ttl(self, ttl: Duration) -> Self {
+        self.cache_policy(CachePolicy::Ttl(ttl))
+    }
+
+    /// Cache read responses until the chain advances by `slots` slots
+    pub fn cache_for_slots(self, slots: u64) -> Self {
+        self.cache_policy(CachePolicy::slots(slots))
+    }
+
+    /// Cache read responses, expiring them according to `policy`
+    pub fn cache_policy(mut self, policy: CachePolicy) -> Self {
+        self.config.cache_policy = Some(policy);
+        self
+    }
+
+    /// Keep `getTransaction` results in `cache`
+    ///
+    /// Confirmed transactions are immutable, so they are reused until evicted
+    /// whatever the [`cache_policy`](Self::cache_policy). Share one cache
+    /// between clients of the same cluster only.
+    pub fn transaction_cache(mut self, cache: TransactionCache) -> Self {
+        self.config.transaction_cache = Some(cache);
+        self
+    }
+
+    /// Limit outgoing requests to the given rate
+    pub fn rate_limit(mut self, requests_per_second: u32) -> Self {
+        self.config.requests_per_second = Some(requests_per_second);
+        self
+    }
+
+    /// Forward every completed request to a metrics sink
+    pub fn metrics_sink(mut self, sink: impl MetricsSink + 'static) -> Self {
+        self.config.metrics_sink = Some(Arc::new(sink));
+        self
+    }
+
+    /// Parse responses with `parser` instead of the default for this target
+    pub fn json_parser(mut self, parser: impl JsonParser + 'static) -> Self {
+        self.config.json_parser = Some(Arc::new(parser));
+        self
+    }
+
+    /// Request account data as zstd-compressed base64
+    ///
+    /// Compression costs a little CPU to decode but shrinks large accounts and
+    /// `getProgramAccounts` scans severalfold, which matters most in browsers.
+    /// Small accounts can come out slightly larger.
+    pub fn zstd_account_data(mut self) -> Self {
+        self.config.zstd_account_data = true;
+        self
+    }
+
+    /// Fail with [`RpcError::ResponseTooLarge`] on bodies over `bytes`
+    ///
+    /// Native clients stop downloading at the limit; browsers download the
+    /// whole body but skip parsing it.
+    pub fn max_response_size(mut self, bytes: usize) -> Self {
+        self.config.max_response_size = Some(bytes);
+        self
+    }
+
+    /// Parse responses while they download instead of buffering the body
+    ///
+    /// Peak memory for huge `getProgramAccounts` responses drops to roughly
+    /// the parsed value alone. Parsing uses serde_json on tokio's blocking
+    /// pool, bypassing [`json_parser`](Self::json_parser).
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn stream_responses(mut self) -> Self {
+        self.config.stream_responses = true;
+        self
+    }
+
+    /// Tune the native connection pool, e.g. for indexers sending many requests
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn transport_config(mut self, mut transport: TransportConfig) -> Self {
+        if transport.proxy.is_none() {
+            transport.proxy = self.config.transport.take().and_then(|old| old.proxy);
+        }
+        self.config.transport = Some(transport);
+        self
+    }
+
+    /// Send every request through `proxy`
+    ///
+    /// Kept when [`transport_config`](Self::transport_config) is called
+    /// afterwards with no proxy of its own.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn proxy(mut self, proxy: ProxyConfig) -> Self {
+        self.config
+            .transport
+            .get_or_insert_with(TransportConfig::default)
+            .proxy = Some(proxy);
+        self
+    }
+
+    /// Send a header with every request, e.g. a provider's API key
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.config.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Identify the application instead of
+    /// [`DEFAULT_USER_AGENT`](crate::infrastructure::http::DEFAULT_USER_AGENT)
+    ///
+    /// Browsers send it as the `solana-client` header.
+    pub fn user_agent(self, user_agent: impl Into<String>) -> Self {
+        self.header(USER_AGENT_HEADER, user_agent)
+    }
+
+    /// Append every request and its response to `recording`
+    ///
+    /// Save the recording afterwards to replay the session with
+    /// [`replay`](Self::replay).
+    pub fn record(mut self, recording: Recording) -> Self {
+        self.config.fixtures = Some(Fixtures::Record(recording));
+        self
+    }
+
+    /// Answer from recorded fixtures instead of sending requests
+    pub fn replay(mut self, replay: ReplayHttpClient) -> Self {
+        self.config.fixtures = Some(Fixtures::Replay(replay));
+        self
+    }
+
+    /// Build the RPC client, failing if the native transport settings
+    /// (timeouts, TLS, proxy) cannot be applied
+    pub fn build(self) -> Result<SolanaRpcClient, RpcError> {
+        let headers = self.config.headers.clone();
+        #[cfg(not(target_arch = "wasm32"))]
+        let platform_client = NativeHttpClient::with_config(
+            &self.config.transport.clone().unwrap_or_default(),
+            headers,
+        )?;
+        #[cfg(target_arch = "wasm32")]
+        let platform_client = WasmHttpClient::with_headers(headers);
+
+        let http_client = match self.config.fixtures.clone() {
+            Some(Fixtures::Record(recording)) => {
+                HttpClientEnum::Recording(RecordingHttpClient::new(platform_client, recording))
+            }
+            Some(Fixtures::Replay(replay)) => HttpClientEnum::Replay(replay),
+            #[cfg(not(target_arch = "wasm32"))]
+            None => HttpClientEnum::Native(platform_client),
+            #[cfg(target_arch = "wasm32")]
+            None => HttpClientEnum::Wasm(platform_client),
+        };
+
+        Ok(SolanaRpcClient::from_parts(
+            http_client,
+            self.endpoint,
+            self.config,
+        ))
     }
 }
 
 /// RPC client configuration
-#[derive(Debug, Clone)]
+#[derive(Clone, Default)]
 struct RpcClientConfig {
     commitment: Option<CommitmentLevel>,
+    expected_cluster: Option<Network>,
+    interceptors: Vec<Arc<dyn RpcInterceptor>>,
+    cache_policy: Option<CachePolicy>,
+    transaction_cache: Option<TransactionCache>,
+    requests_per_second: Option<u32>,
+    metrics_sink: Option<Arc<dyn MetricsSink>>,
+    json_parser: Option<Arc<dyn JsonParser>>,
+    zstd_account_data: bool,
+    max_response_size: Option<usize>,
+    /// Headers sent with every request
+    headers: Vec<(String, String)>,
+    #[cfg(not(target_arch = "wasm32"))]
+    stream_responses: bool,
+    #[cfg(not(target_arch = "wasm32"))]
+    transport: Option<TransportConfig>,
+    fixtures: Option<Fixtures>,
 }
 
-impl Default for RpcClientConfig {
-    fn default() -> Self {
-        Self { commitment: None }
-    }
+/// How a client uses recorded fixtures
+#[derive(Clone)]
+enum Fixtures {
+    Record(Recording),
+    Replay(ReplayHttpClient),
 }
 
-/// Commitment levels
-#[derive(Debug, Clone, Copy, Serialize)]
+/// Commitment levels, ordered from least to most final
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum CommitmentLevel {
     Processed,
@@ -467,6 +2027,27 @@ pub enum CommitmentLevel {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_decode_wire_transaction() {
+        let bytes = vec![1, 0, 0, 255, 62, 63, 42];
+        let base58 = bs58::encode(&bytes).into_string();
+        let base64 = general_purpose::STANDARD.encode(&bytes);
+        assert_eq!(base64, "AQAA/z4/Kg==");
+
+        assert_eq!(decode_wire_transaction(&base58).unwrap(), bytes);
+        assert_eq!(decode_wire_transaction(&base64).unwrap(), bytes);
+        assert_eq!(
+            decode_wire_transaction(&format!(" {}\n", base58)).unwrap(),
+            bytes
+        );
+        assert!(matches!(
+            decode_wire_transaction("not a transaction!"),
+            Err(RpcError::InvalidRequest(_))
+        ));
+        assert!(decode_wire_transaction("").is_err());
+    }
 
     #[test]
     fn test_network_endpoints() {
@@ -492,13 +2073,611 @@ mod tests {
         assert!(json.contains("11111111111111111111111111111111"));
     }
 
+    #[test]
+    fn test_context_responses_and_min_context_slot() {
+        let response: WithContext<u64> = serde_json::from_value(json!({
+            "context": { "apiVersion": "1.18.22", "slot": 271_000_000u64 },
+            "value": 5_000
+        }))
+        .unwrap();
+        assert_eq!(response.context.slot, 271_000_000);
+        assert_eq!(response.map(|lamports| lamports * 2).value, 10_000);
+
+        assert_eq!(
+            read_config(AccountEncoding::Base64, &AccountReadConfig::new()),
+            json!({ "encoding": "base64" })
+        );
+        assert_eq!(
+            read_config(
+                AccountEncoding::Base64Zstd,
+                &AccountReadConfig::new().min_context_slot(7)
+            ),
+            json!({ "encoding": "base64+zstd", "minContextSlot": 7 })
+        );
+        let config = AccountReadConfig::new()
+            .commitment(CommitmentLevel::Confirmed)
+            .data_slice(8, 32);
+        assert_eq!(
+            read_config(AccountEncoding::Base64, &config),
+            json!({
+                "encoding": "base64",
+                "commitment": "confirmed",
+                "dataSlice": { "offset": 8, "length": 32 }
+            })
+        );
+        let staker = AccountReadConfig::new()
+            .field_slice::<crate::domain::programs::stake::StakeLayout>("staker")
+            .unwrap();
+        assert_eq!(
+            staker.data_slice,
+            Some(DataSlice {
+                offset: 12,
+                length: 32
+            })
+        );
+
+        let behind = RpcError::RpcError {
+            code: -32016,
+            message: "Minimum context slot has not been reached".to_string(),
+            data: Some(json!({ "contextSlot": 6 })),
+        };
+        assert!(behind.is_min_context_slot_not_reached());
+        assert!(!RpcError::ParseError("x".to_string()).is_min_context_slot_not_reached());
+    }
+
+    #[test]
+    fn test_account_info_data_shapes() {
+        // getAccountInfo for the USDC mint, in the shape mainnet returns
+        let usdc = Pubkey::from_base58("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v").unwrap();
+        let response: WithContext<Option<AccountInfo>> = serde_json::from_value(json!({
+            "context": { "apiVersion": "2.0.15", "slot": 301_592_107u64 },
+            "value": {
+                "data": [
+                    "AQAAAJj+huiNm+Lqi8HMpIeLKYjCQPUrhCS/tA7Rot3LXhmbsDkznjXVHwAGAQEAAABicKqKWcWUBbRShshncubNEm6bil06OFNtN/e0FOi2Zw==",
+                    "base64"
+                ],
+                "executable": false,
+                "lamports": 388_127_047_454u64,
+                "owner": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+                "rentEpoch": 18_446_744_073_709_551_615u64,
+                "space": 82
+            }
+        }))
+        .unwrap();
+        let account = response.value.unwrap().into_account(usdc).unwrap();
+        assert_eq!(account.data.len(), 82);
+        assert_eq!(
+            account.owner,
+            crate::domain::types::constants::TOKEN_PROGRAM_ID
+        );
+        assert_eq!(account.rent_epoch, u64::MAX);
+
+        let keyed: Vec<KeyedAccountInfo> = serde_json::from_value(json!([{
+            "pubkey": usdc.to_base58(),
+            "account": {
+                "data": { "program": "spl-token", "parsed": { "type": "mint" }, "space": 82 },
+                "executable": false,
+                "lamports": 1_461_600,
+                "owner": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+                "rentEpoch": 0
+            }
+        }]))
+        .unwrap();
+        let error = keyed[0].account.clone().into_account(usdc).unwrap_err();
+        assert!(error.to_string().contains("parsed by spl-token"));
+    }
+
+    #[test]
+    fn test_signature_status_commitment() {
+        let statuses: WithContext<Vec<Option<SignatureStatus>>> =
+            serde_json::from_value(json!({
+                "context": { "slot": 82 },
+                "value": [
+                    { "slot": 72, "confirmations": 10, "err": null, "status": { "Ok": null }, "confirmationStatus": "confirmed" },
+                    { "slot": 48, "confirmations": null, "err": { "InstructionError": [0, { "Custom": 1 }] }, "confirmationStatus": "finalized" },
+                    null
+                ]
+            }))
+            .unwrap();
+
+        let confirmed = statuses.value[0].as_ref().unwrap();
+        assert!(confirmed.satisfies(CommitmentLevel::Processed));
+        assert!(confirmed.satisfies(CommitmentLevel::Confirmed));
+        assert!(!confirmed.satisfies(CommitmentLevel::Finalized));
+        assert!(confirmed.error().is_none());
+
+        let failed = statuses.value[1].as_ref().unwrap();
+        assert!(failed.satisfies(CommitmentLevel::Finalized));
+        assert!(failed.error().is_some());
+        assert!(statuses.value[2].is_none());
+    }
+
+    #[test]
+    fn test_token_largest_accounts_response() {
+        let largest: WithContext<Vec<TokenAccountBalance>> = serde_json::from_value(json!({
+            "context": { "slot": 1114 },
+            "value": [{
+                "address": "FYjHNoFtSQ5uijKrZFyYAxvEr87hsKXkXcxkcmkBAf4r",
+                "amount": "771",
+                "decimals": 2,
+                "uiAmount": 7.71,
+                "uiAmountString": "7.71"
+            }]
+        }))
+        .unwrap();
+
+        let balance = &largest.value[0].amount;
+        assert_eq!(balance.raw_amount().unwrap(), 771);
+        assert_eq!(balance.ui_amount().unwrap(), 7.71);
+        assert_eq!(balance.ui_amount_string.as_deref(), Some("7.71"));
+    }
+
     #[test]
     fn test_rpc_client_builder() {
         let client = RpcClientBuilder::new("http://localhost:8899")
             .commitment(CommitmentLevel::Confirmed)
-            .build();
+            .build()
+            .unwrap();
 
         // Test that the client was created successfully
         assert_eq!(client.endpoint(), "http://localhost:8899");
     }
+
+    #[test]
+    fn test_clones_share_state() {
+        let client = RpcClientBuilder::new("http://localhost:8899")
+            .cache_ttl(Duration::from_secs(30))
+            .build()
+            .unwrap();
+        let clone = client.clone();
+
+        assert!(client.shares_state_with(&clone));
+        assert!(
+            !client.shares_state_with(&SolanaRpcClient::with_endpoint(client.endpoint()).unwrap())
+        );
+
+        client
+            .cache()
+            .unwrap()
+            .insert("getSlot:[]".to_string(), json!(5));
+        assert_eq!(clone.cache().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_rpc_error_exposes_transaction_error() {
+        let response: RpcResponse<serde_json::Value> = serde_json::from_value(json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "error": {
+                "code": -32002,
+                "message": "Transaction simulation failed: Error processing Instruction 0: custom program error: 0x1",
+                "data": {
+                    "err": { "InstructionError": [0, { "Custom": 1 }] },
+                    "logs": ["Program log: boom"]
+                }
+            }
+        }))
+        .unwrap();
+        let error = RpcError::from(response.into_result().unwrap_err());
+
+        assert_eq!(
+            error.transaction_error(),
+            Some(TransactionError::InstructionError {
+                index: 0,
+                error: crate::domain::errors::InstructionError::Custom(1),
+            })
+        );
+        assert_eq!(error.logs(), vec!["Program log: boom".to_string()]);
+    }
+
+    #[derive(Default)]
+    struct CountingInterceptor {
+        requests: Arc<AtomicUsize>,
+        responses: Arc<AtomicUsize>,
+    }
+
+    impl RpcInterceptor for CountingInterceptor {
+        fn before_request(&self, _method: &str, _params: &[serde_json::Value]) {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+        }
+
+        fn after_response(&self, _method: &str, _result: Result<&serde_json::Value, &RpcError>) {
+            self.responses.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cached_response_skips_transport() {
+        let interceptor = CountingInterceptor::default();
+        let requests = interceptor.requests.clone();
+        let responses = interceptor.responses.clone();
+
+        // Unroutable endpoint: any request that reaches the transport fails
+        let client = RpcClientBuilder::new("http://127.0.0.1:1")
+            .cache_ttl(Duration::from_secs(30))
+            .interceptor(interceptor)
+            .build()
+            .unwrap();
+
+        let pubkey = Pubkey::new([4; 32]);
+        let request = RpcRequest::new("getBalance").param(pubkey.to_base58());
+        client.cache().unwrap().insert(
+            ResponseCache::key(&request.method, &request.params),
+            json!({ "context": { "slot": 1 }, "value": 42 }),
+        );
+
+        let balance = client.get_balance(&pubkey).await.unwrap();
+        assert_eq!(balance, 42);
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+        assert_eq!(responses.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_cache_skips_status_polls() {
+        use crate::infrastructure::http::{fixtures::FixtureMethod, Fixture};
+
+        let signature = Signature::new([7; 64]);
+        let request = RpcRequest::new("getSignatureStatuses").param(vec![signature.to_base58()]);
+        let fixture = |status: serde_json::Value| Fixture {
+            method: FixtureMethod::Post,
+            url: "http://127.0.0.1:8899".to_string(),
+            request: Some(serde_json::to_value(&request).unwrap()),
+            response: serde_json::to_value(RpcResponse::success(
+                1,
+                json!({ "context": { "slot": 10 }, "value": [status] }),
+            ))
+            .unwrap(),
+        };
+        let replay = ReplayHttpClient::new(vec![
+            fixture(json!({ "slot": 10, "confirmationStatus": "processed" })),
+            fixture(json!({ "slot": 10, "confirmationStatus": "confirmed" })),
+        ]);
+        let client = RpcClientBuilder::new("http://127.0.0.1:1")
+            .cache_ttl(Duration::from_secs(30))
+            .replay(replay)
+            .build()
+            .unwrap();
+
+        let status = |statuses: Vec<Option<SignatureStatus>>| {
+            statuses[0].as_ref().unwrap().confirmation_status
+        };
+        let first = client
+            .get_signature_statuses(std::slice::from_ref(&signature))
+            .await
+            .unwrap();
+        assert_eq!(status(first), Some(CommitmentLevel::Processed));
+        let second = client
+            .get_signature_statuses(std::slice::from_ref(&signature))
+            .await
+            .unwrap();
+        assert_eq!(status(second), Some(CommitmentLevel::Confirmed));
+        assert!(client.cache().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cache_skips_history_polls() {
+        use crate::infrastructure::http::{fixtures::FixtureMethod, Fixture};
+
+        let address = Pubkey::new([5; 32]);
+        let signature = Signature::new([8; 64]);
+        let history = RpcRequest::new("getSignaturesForAddress")
+            .param(address.to_base58())
+            .try_param(SignaturesForAddressConfig::default())
+            .unwrap();
+        let lookup = RpcRequest::new("getTransaction")
+            .param(signature.to_base58())
+            .param(json!({ "encoding": "json", "maxSupportedTransactionVersion": 0 }));
+        let fixture = |request: &RpcRequest, result: serde_json::Value| Fixture {
+            method: FixtureMethod::Post,
+            url: "http://127.0.0.1:8899".to_string(),
+            request: Some(serde_json::to_value(request).unwrap()),
+            response: serde_json::to_value(RpcResponse::success(1, result)).unwrap(),
+        };
+        let replay = ReplayHttpClient::new(vec![
+            fixture(&history, json!([])),
+            fixture(
+                &history,
+                json!([{ "signature": signature.to_base58(), "slot": 11 }]),
+            ),
+            fixture(&lookup, serde_json::Value::Null),
+            fixture(&lookup, serde_json::Value::Null),
+        ]);
+        let client = RpcClientBuilder::new("http://127.0.0.1:1")
+            .cache_ttl(Duration::from_secs(30))
+            .replay(replay)
+            .build()
+            .unwrap();
+
+        let poll = || client.get_signatures_for_address(&address, Default::default());
+        assert!(poll().await.unwrap().is_empty());
+        let second = poll().await.unwrap();
+        assert_eq!(second.len(), 1);
+        assert_eq!(second[0].signature, signature);
+
+        assert!(client.get_transaction(&signature).await.unwrap().is_none());
+        assert!(client.get_transaction(&signature).await.unwrap().is_none());
+        assert!(client.cache().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_transaction_cache_skips_transport() {
+        let interceptor = CountingInterceptor::default();
+        let requests = interceptor.requests.clone();
+        let cache = TransactionCache::default();
+        let signature = Signature::new([5; 64]);
+        cache.insert_value(
+            &signature,
+            &json!({
+                "slot": 12,
+                "transaction": {
+                    "signatures": [signature.to_base58()],
+                    "message": {
+                        "accountKeys": [],
+                        "recentBlockhash": "11111111111111111111111111111111"
+                    }
+                }
+            }),
+        );
+
+        // Unroutable endpoint: only cached transactions can be returned
+        let client = RpcClientBuilder::new("http://127.0.0.1:1")
+            .transaction_cache(cache.clone())
+            .interceptor(interceptor)
+            .build()
+            .unwrap();
+        let transaction = client.get_transaction(&signature).await.unwrap().unwrap();
+        assert_eq!(transaction.slot, 12);
+        assert_eq!(requests.load(Ordering::SeqCst), 0);
+        assert!(client
+            .get_transaction(&Signature::new([6; 64]))
+            .await
+            .is_err());
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+        assert_eq!(client.transaction_cache().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_zstd_account_data() {
+        // Unroutable endpoint: the response is served from the cache
+        let client = RpcClientBuilder::new("http://127.0.0.1:1")
+            .cache_ttl(Duration::from_secs(30))
+            .zstd_account_data()
+            .build()
+            .unwrap();
+        let pubkey = Pubkey::new([3; 32]);
+        let request = RpcRequest::new("getAccountInfo")
+            .param(pubkey.to_base58())
+            .param(json!({ "encoding": "base64+zstd" }));
+        client.cache().unwrap().insert(
+            ResponseCache::key(&request.method, &request.params),
+            json!({
+                "context": { "slot": 9 },
+                "value": {
+                    "data": ["KLUv/STITQAAEAAAAQBDCmABT3XqAA==", "base64+zstd"],
+                    "executable": false,
+                    "lamports": 2_282_880,
+                    "owner": "11111111111111111111111111111111",
+                    "rentEpoch": 0,
+                    "space": 200
+                }
+            }),
+        );
+
+        let account = client.get_account_info(&pubkey).await.unwrap().unwrap();
+        assert_eq!(account.data, vec![0; 200]);
+    }
+
+    #[tokio::test]
+    async fn test_metrics_record_requests_and_errors() {
+        let sunk = Arc::new(AtomicUsize::new(0));
+        let counter = sunk.clone();
+        let client = RpcClientBuilder::new("http://127.0.0.1:1")
+            .cache_ttl(Duration::from_secs(30))
+            .metrics_sink(move |_: &RequestEvent<'_>| {
+                counter.fetch_add(1, Ordering::SeqCst);
+            })
+            .build()
+            .unwrap();
+
+        let pubkey = Pubkey::new([4; 32]);
+        let request = RpcRequest::new("getBalance").param(pubkey.to_base58());
+        client.cache().unwrap().insert(
+            ResponseCache::key(&request.method, &request.params),
+            json!({ "context": { "slot": 1 }, "value": 42 }),
+        );
+        client.get_balance(&pubkey).await.unwrap();
+        assert!(client.get_slot().await.is_err());
+
+        let snapshot = client.clone().metrics().snapshot();
+        assert_eq!(snapshot.requests, 2);
+        assert_eq!(snapshot.errors_by_kind[&ErrorKind::Http], 1);
+        assert_eq!(snapshot.method("getBalance").unwrap().cache_hits, 1);
+        assert_eq!(snapshot.method("getSlot").unwrap().errors, 1);
+        assert_eq!(sunk.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_requests() {
+        // A node that accepts the connection but never answers
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let _connection = listener.accept().await;
+            std::future::pending::<()>().await;
+        });
+
+        let token = CancelToken::new();
+        let client = RpcClientBuilder::new(endpoint)
+            .build()
+            .unwrap()
+            .with_cancel_token(token.clone());
+        let remote = token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            remote.cancel();
+        });
+        assert!(matches!(client.get_slot().await, Err(RpcError::Cancelled)));
+        assert!(matches!(
+            client.get_block_height().await,
+            Err(RpcError::Cancelled)
+        ));
+        assert_eq!(client.metrics().snapshot().requests, 1);
+    }
+
+    #[tokio::test]
+    async fn test_replayed_fixtures() {
+        use crate::infrastructure::http::{fixtures::FixtureMethod, Fixture};
+
+        let fixture = Fixture {
+            method: FixtureMethod::Post,
+            url: "http://127.0.0.1:8899".to_string(),
+            request: Some(serde_json::to_value(RpcRequest::new("getBlockHeight")).unwrap()),
+            response: serde_json::to_value(RpcResponse::success(1, 42)).unwrap(),
+        };
+        // Unroutable endpoint: only recorded requests succeed
+        let client = RpcClientBuilder::new("http://127.0.0.1:1")
+            .replay(ReplayHttpClient::new(vec![fixture]))
+            .build()
+            .unwrap();
+        assert_eq!(client.get_block_height().await.unwrap(), 42);
+        assert!(matches!(
+            client.get_slot().await,
+            Err(RpcError::Http(HttpError::FixtureMissing(_)))
+        ));
+
+        let recording = Recording::new();
+        let client = RpcClientBuilder::new("http://127.0.0.1:1")
+            .record(recording.clone())
+            .build()
+            .unwrap();
+        assert!(client.get_slot().await.is_err());
+        assert!(recording.fixtures().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_context_config_defaults_to_client_commitment() {
+        use crate::infrastructure::http::{fixtures::FixtureMethod, Fixture};
+
+        let pubkey = Pubkey::new([3; 32]);
+        let fixture = |request: RpcRequest, result: serde_json::Value| Fixture {
+            method: FixtureMethod::Post,
+            url: "http://127.0.0.1:8899".to_string(),
+            request: Some(serde_json::to_value(request).unwrap()),
+            response: serde_json::to_value(RpcResponse::success(1, result)).unwrap(),
+        };
+        let replay = ReplayHttpClient::new(vec![
+            fixture(
+                RpcRequest::new("getBalance")
+                    .param(pubkey.to_base58())
+                    .param(json!({ "commitment": "finalized" })),
+                json!({ "context": { "slot": 9 }, "value": 7 }),
+            ),
+            fixture(
+                RpcRequest::new("getSlot")
+                    .param(json!({ "commitment": "processed", "minContextSlot": 5 })),
+                json!(11),
+            ),
+        ]);
+        // Unroutable endpoint: only requests with the expected config succeed
+        let client = RpcClientBuilder::new("http://127.0.0.1:1")
+            .commitment(CommitmentLevel::Finalized)
+            .replay(replay)
+            .build()
+            .unwrap();
+
+        assert_eq!(client.get_balance(&pubkey).await.unwrap(), 7);
+        let config = RpcContextConfig::from(CommitmentLevel::Processed).min_context_slot(5);
+        assert_eq!(client.get_slot_with_config(&config).await.unwrap(), 11);
+        assert!(client.get_slot().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_expect_cluster_checks_genesis_hash() {
+        use crate::infrastructure::http::{fixtures::FixtureMethod, Fixture};
+        use crate::infrastructure::network::DEVNET_GENESIS_HASH;
+
+        let fixture = |method: &str, result: serde_json::Value| Fixture {
+            method: FixtureMethod::Post,
+            url: "http://127.0.0.1:8899".to_string(),
+            request: Some(serde_json::to_value(RpcRequest::new(method)).unwrap()),
+            response: serde_json::to_value(RpcResponse::success(1, result)).unwrap(),
+        };
+        let replay = ReplayHttpClient::new(vec![
+            fixture("getGenesisHash", json!(DEVNET_GENESIS_HASH)),
+            fixture("getBlockHeight", json!(42)),
+        ]);
+
+        let mainnet = RpcClientBuilder::new("http://127.0.0.1:1")
+            .expect_cluster(Network::Mainnet)
+            .replay(replay.clone())
+            .build()
+            .unwrap();
+        match mainnet.get_block_height().await {
+            Err(RpcError::WrongCluster {
+                expected,
+                genesis_hash,
+            }) => {
+                assert_eq!(expected, "Mainnet");
+                assert_eq!(genesis_hash.to_base58(), DEVNET_GENESIS_HASH);
+            }
+            other => panic!("expected a cluster mismatch, got {:?}", other),
+        }
+        // The genesis hash itself can still be read
+        assert!(mainnet.get_genesis_hash().await.is_ok());
+
+        let devnet = RpcClientBuilder::new("http://127.0.0.1:1")
+            .expect_cluster(Network::Devnet)
+            .replay(replay)
+            .build()
+            .unwrap();
+        assert_eq!(devnet.get_block_height().await.unwrap(), 42);
+        assert_eq!(devnet.expected_cluster(), Some(&Network::Devnet));
+    }
+
+    #[tokio::test]
+    async fn test_multiple_accounts_with_config() {
+        use crate::infrastructure::http::{fixtures::FixtureMethod, Fixture};
+
+        let keys = [Pubkey::new([1; 32]), Pubkey::new([2; 32])];
+        let config = AccountReadConfig::new()
+            .commitment(CommitmentLevel::Finalized)
+            .data_slice(0, 2)
+            .min_context_slot(40);
+        let request = RpcRequest::new("getMultipleAccounts")
+            .param(vec![keys[0].to_base58(), keys[1].to_base58()])
+            .param(read_config(AccountEncoding::Base64, &config));
+        let fixture = Fixture {
+            method: FixtureMethod::Post,
+            url: "http://127.0.0.1:8899".to_string(),
+            request: Some(serde_json::to_value(request).unwrap()),
+            response: json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": {
+                    "context": { "slot": 41 },
+                    "value": [{
+                        "data": ["AQI=", "base64"],
+                        "executable": false,
+                        "lamports": 5,
+                        "owner": "11111111111111111111111111111111",
+                        "rentEpoch": 0
+                    }, null]
+                }
+            }),
+        };
+        let client = RpcClientBuilder::new("http://127.0.0.1:1")
+            .replay(ReplayHttpClient::new(vec![fixture]))
+            .build()
+            .unwrap();
+
+        let accounts = client
+            .get_multiple_accounts_with_config(&keys, &config)
+            .await
+            .unwrap()
+            .split();
+        assert_eq!(accounts.len(), 2);
+        assert!(accounts.iter().all(|account| account.context.slot == 41));
+        assert_eq!(accounts[0].value.as_ref().unwrap().data, [1, 2]);
+        assert_eq!(accounts[1].value, None);
+    }
 }
@@ -0,0 +1,153 @@
+//! Clients for several networks at once
+//!
+//! Cross-cluster apps keep a client per network: a wallet operating on devnet
+//! still reads prices from mainnet, and a deploy tool compares a program on
+//! every cluster. [`NetworkRegistry`] holds one client per [`Network`], looks
+//! them up by network and runs a request on all of them concurrently.
+
+use super::{Network, RpcApi, RpcClientBuilder, RpcError};
+use futures::future::join_all;
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+
+/// One RPC client per network
+#[derive(Clone, Default)]
+pub struct NetworkRegistry {
+    clients: Vec<(Network, Arc<dyn RpcApi>)>,
+}
+
+impl NetworkRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a client for `network` using its default endpoint
+    pub fn with_network(self, network: Network) -> Result<Self, RpcError> {
+        let client = RpcClientBuilder::new(network.endpoint()).build()?;
+        Ok(self.with_client(network, client))
+    }
+
+    /// Add `client` for `network`, replacing any client it had
+    pub fn with_client(self, network: Network, client: impl RpcApi + 'static) -> Self {
+        self.with_api(network, Arc::new(client))
+    }
+
+    /// Add a shared RPC implementation for `network`
+    pub fn with_api(mut self, network: Network, client: Arc<dyn RpcApi>) -> Self {
+        self.insert(network, client);
+        self
+    }
+
+    /// Add or replace the client for `network`
+    pub fn insert(&mut self, network: Network, client: Arc<dyn RpcApi>) {
+        match self.clients.iter_mut().find(|(known, _)| *known == network) {
+            Some(entry) => entry.1 = client,
+            None => self.clients.push((network, client)),
+        }
+    }
+
+    /// The client for `network`
+    pub fn get(&self, network: &Network) -> Option<Arc<dyn RpcApi>> {
+        self.clients
+            .iter()
+            .find(|(known, _)| known == network)
+            .map(|(_, client)| client.clone())
+    }
+
+    /// Whether a client is registered for `network`
+    pub fn contains(&self, network: &Network) -> bool {
+        self.clients.iter().any(|(known, _)| known == network)
+    }
+
+    /// Registered networks, in the order they were added
+    pub fn networks(&self) -> impl Iterator<Item = &Network> {
+        self.clients.iter().map(|(network, _)| network)
+    }
+
+    pub fn len(&self) -> usize {
+        self.clients.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.clients.is_empty()
+    }
+
+    /// Run `request` against every network concurrently
+    ///
+    /// Results come back in registration order, each with its network.
+    pub async fn broadcast<T, F, Fut>(&self, request: F) -> Vec<(Network, T)>
+    where
+        F: Fn(Arc<dyn RpcApi>) -> Fut,
+        Fut: Future<Output = T>,
+    {
+        let results = join_all(
+            self.clients
+                .iter()
+                .map(|(_, client)| request(client.clone())),
+        )
+        .await;
+        self.networks().cloned().zip(results).collect()
+    }
+}
+
+impl fmt::Debug for NetworkRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.networks()).finish()
+    }
+}
+
+/// Registries are equal when they hold the same clients for the same networks
+impl PartialEq for NetworkRegistry {
+    fn eq(&self, other: &Self) -> bool {
+        self.clients.len() == other.clients.len()
+            && self
+                .clients
+                .iter()
+                .zip(&other.clients)
+                .all(|((a, client_a), (b, client_b))| a == b && Arc::ptr_eq(client_a, client_b))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::types::Pubkey;
+    use crate::infrastructure::rpc::mock::MockRpc;
+
+    #[tokio::test]
+    async fn test_registry_routes_and_broadcasts() {
+        let wallet = Pubkey::new([1; 32]);
+        let local = Network::custom("http://127.0.0.1:8899");
+        let registry = NetworkRegistry::new()
+            .with_client(Network::Mainnet, MockRpc::new().with_balance(wallet, 7))
+            .with_client(Network::Devnet, MockRpc::new().with_balance(wallet, 2))
+            .with_network(local.clone())
+            .unwrap();
+        assert_eq!(registry.len(), 3);
+        assert!(registry.contains(&local));
+        assert!(registry.get(&Network::Testnet).is_none());
+
+        let mainnet = registry.get(&Network::Mainnet).unwrap();
+        assert_eq!(mainnet.get_balance(&wallet).await.unwrap(), 7);
+
+        let devnets = NetworkRegistry::new()
+            .with_api(Network::Devnet, registry.get(&Network::Devnet).unwrap())
+            .with_client(Network::Mainnet, MockRpc::new().with_balance(wallet, 9));
+        let balances = devnets
+            .broadcast(|client| async move { client.get_balance(&wallet).await })
+            .await;
+        let balances: Vec<_> = balances
+            .into_iter()
+            .map(|(network, balance)| (network, balance.unwrap()))
+            .collect();
+        assert_eq!(balances, [(Network::Devnet, 2), (Network::Mainnet, 9)]);
+
+        let mut replaced = devnets.clone();
+        assert_eq!(replaced, devnets);
+        replaced.insert(Network::Mainnet, Arc::new(MockRpc::new()));
+        assert_eq!(replaced.len(), 2);
+        assert_ne!(replaced, devnets);
+    }
+}
@@ -0,0 +1,428 @@
+//! Batched account reads for UI-driven views
+//!
+//! A dashboard with hundreds of widgets issues hundreds of single-account
+//! reads per render. A [`ReadScheduler`] holds the account reads issued within
+//! a short window, 16ms by default so one frame's reads share a request, and
+//! serves them with as few `getMultipleAccounts` calls as possible, resolving
+//! each read with its own account. Only a few batches are in flight at once:
+//! reads issued while a slow node is still answering keep collecting into the
+//! next batch instead of adding requests.
+
+use super::{
+    Account, AccountReadConfig, ConfirmedBlock, ConfirmedTransaction, EpochInfo, Filter,
+    InflationReward, LatestBlockhash, RpcApi, RpcError, SignatureInfo, SignatureStatus,
+    SignaturesForAddressConfig, SimulationResult, TokenAccountBalance, UiTokenAmount, WithContext,
+    MAX_ACCOUNTS_PER_REQUEST,
+};
+use crate::domain::types::{Pubkey, Signature};
+use crate::infrastructure::time::sleep;
+use async_trait::async_trait;
+use futures::channel::oneshot;
+use futures::future::{join_all, select, Either};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+/// How long reads are collected before a batch is sent
+pub const DEFAULT_BATCH_WINDOW: Duration = Duration::from_millis(16);
+
+/// Batches sent at once before further reads wait for one to finish
+pub const DEFAULT_MAX_IN_FLIGHT: usize = 4;
+
+type Reply = oneshot::Sender<Result<Option<Account>, RpcError>>;
+
+/// Reads waiting for a batch, and how many batches are in flight
+#[derive(Default)]
+struct Queue {
+    pending: HashMap<Pubkey, Vec<Reply>>,
+    in_flight: usize,
+}
+
+/// [`RpcApi`] that merges concurrent account reads into batched requests
+///
+/// `get_account_info`, `get_balance` and `get_multiple_accounts` go through
+/// the batches, with reads of the same account sharing one key. Everything
+/// else, including reads pinned to a slot or with a config, is passed
+/// straight to the wrapped client.
+pub struct ReadScheduler {
+    rpc_client: Arc<dyn RpcApi>,
+    window: Duration,
+    max_in_flight: usize,
+    queue: Mutex<Queue>,
+}
+
+impl ReadScheduler {
+    /// Create a scheduler with the default window and in-flight limit
+    pub fn new(rpc_client: impl RpcApi + 'static) -> Self {
+        Self::from_api(Arc::new(rpc_client))
+    }
+
+    /// Create a scheduler from a shared RPC implementation
+    pub fn from_api(rpc_client: Arc<dyn RpcApi>) -> Self {
+        Self {
+            rpc_client,
+            window: DEFAULT_BATCH_WINDOW,
+            max_in_flight: DEFAULT_MAX_IN_FLIGHT,
+            queue: Mutex::new(Queue::default()),
+        }
+    }
+
+    /// Set how long reads are collected before a batch is sent
+    pub fn window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Set how many batches may be in flight at once
+    pub fn max_in_flight(mut self, max_in_flight: usize) -> Self {
+        self.max_in_flight = max_in_flight.max(1);
+        self
+    }
+
+    /// Number of reads waiting for a batch
+    pub fn pending(&self) -> usize {
+        self.lock().pending.values().map(Vec::len).sum()
+    }
+
+    /// Read `pubkey` in the next batch
+    pub async fn read(&self, pubkey: &Pubkey) -> Result<Option<Account>, RpcError> {
+        let (reply, mut receiver) = oneshot::channel();
+        self.lock().pending.entry(*pubkey).or_default().push(reply);
+
+        // Whichever waiter's window ends first sends the batch; the others are
+        // answered through their channel, or send the next batch themselves
+        loop {
+            match select(receiver, Box::pin(sleep(self.window))).await {
+                Either::Left((result, _)) => return result.unwrap_or(Err(RpcError::Cancelled)),
+                Either::Right((_, waiting)) => {
+                    receiver = waiting;
+                    self.flush().await;
+                }
+            }
+        }
+    }
+
+    /// Send up to one request's worth of pending reads, unless the in-flight
+    /// limit is reached
+    async fn flush(&self) {
+        let batch: Vec<(Pubkey, Vec<Reply>)> = {
+            let mut queue = self.lock();
+            if queue.pending.is_empty() || queue.in_flight >= self.max_in_flight {
+                return;
+            }
+            let keys: Vec<Pubkey> = queue
+                .pending
+                .keys()
+                .take(MAX_ACCOUNTS_PER_REQUEST)
+                .copied()
+                .collect();
+            queue.in_flight += 1;
+            keys.iter()
+                .filter_map(|key| queue.pending.remove_entry(key))
+                .collect()
+        };
+        let pubkeys: Vec<Pubkey> = batch.iter().map(|(pubkey, _)| *pubkey).collect();
+        let mut in_flight = InFlight {
+            queue: &self.queue,
+            batch,
+        };
+
+        let result = self.rpc_client.get_multiple_accounts(&pubkeys).await;
+        let batch = std::mem::take(&mut in_flight.batch);
+        match result {
+            Ok(accounts) => {
+                for ((_, replies), account) in batch.into_iter().zip(accounts) {
+                    for reply in replies {
+                        let _ = reply.send(Ok(account.clone()));
+                    }
+                }
+            }
+            Err(e) => {
+                for reply in batch.into_iter().flat_map(|(_, replies)| replies) {
+                    let _ = reply.send(Err(e.clone()));
+                }
+            }
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Queue> {
+        self.queue.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// A batch being sent, holding its in-flight slot
+///
+/// If the read sending it is dropped before the answer arrives, the reads
+/// still waiting go back to the queue for the next batch, and the slot is
+/// released.
+struct InFlight<'a> {
+    queue: &'a Mutex<Queue>,
+    /// Reads not yet answered
+    batch: Vec<(Pubkey, Vec<Reply>)>,
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        let mut queue = self.queue.lock().unwrap_or_else(|e| e.into_inner());
+        queue.in_flight = queue.in_flight.saturating_sub(1);
+        for (pubkey, mut replies) in self.batch.drain(..) {
+            replies.retain(|reply| !reply.is_canceled());
+            if !replies.is_empty() {
+                queue.pending.entry(pubkey).or_default().extend(replies);
+            }
+        }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl RpcApi for ReadScheduler {
+    async fn get_account_info(&self, pubkey: &Pubkey) -> Result<Option<Account>, RpcError> {
+        self.read(pubkey).await
+    }
+
+    async fn get_account_info_with_context(
+        &self,
+        pubkey: &Pubkey,
+        min_context_slot: Option<u64>,
+    ) -> Result<WithContext<Option<Account>>, RpcError> {
+        self.rpc_client
+            .get_account_info_with_context(pubkey, min_context_slot)
+            .await
+    }
+
+    async fn get_account_info_with_config(
+        &self,
+        pubkey: &Pubkey,
+        config: &AccountReadConfig,
+    ) -> Result<WithContext<Option<Account>>, RpcError> {
+        self.rpc_client
+            .get_account_info_with_config(pubkey, config)
+            .await
+    }
+
+    async fn get_balance(&self, pubkey: &Pubkey) -> Result<u64, RpcError> {
+        Ok(self
+            .read(pubkey)
+            .await?
+            .map_or(0, |account| account.lamports))
+    }
+
+    async fn get_balance_with_context(
+        &self,
+        pubkey: &Pubkey,
+        min_context_slot: Option<u64>,
+    ) -> Result<WithContext<u64>, RpcError> {
+        self.rpc_client
+            .get_balance_with_context(pubkey, min_context_slot)
+            .await
+    }
+
+    async fn get_latest_blockhash(&self) -> Result<LatestBlockhash, RpcError> {
+        self.rpc_client.get_latest_blockhash().await
+    }
+
+    async fn send_transaction(&self, transaction: &str) -> Result<Signature, RpcError> {
+        self.rpc_client.send_transaction(transaction).await
+    }
+
+    async fn send_raw_transaction(&self, transaction: &[u8]) -> Result<Signature, RpcError> {
+        self.rpc_client.send_raw_transaction(transaction).await
+    }
+
+    async fn send_raw_transaction_base58(&self, transaction: &str) -> Result<Signature, RpcError> {
+        self.rpc_client
+            .send_raw_transaction_base58(transaction)
+            .await
+    }
+
+    async fn get_block_height(&self) -> Result<u64, RpcError> {
+        self.rpc_client.get_block_height().await
+    }
+
+    async fn get_slot(&self) -> Result<u64, RpcError> {
+        self.rpc_client.get_slot().await
+    }
+
+    async fn get_epoch_info(&self) -> Result<EpochInfo, RpcError> {
+        self.rpc_client.get_epoch_info().await
+    }
+
+    async fn get_multiple_accounts(
+        &self,
+        pubkeys: &[Pubkey],
+    ) -> Result<Vec<Option<Account>>, RpcError> {
+        join_all(pubkeys.iter().map(|pubkey| self.read(pubkey)))
+            .await
+            .into_iter()
+            .collect()
+    }
+
+    async fn get_multiple_accounts_with_context(
+        &self,
+        pubkeys: &[Pubkey],
+        min_context_slot: Option<u64>,
+    ) -> Result<WithContext<Vec<Option<Account>>>, RpcError> {
+        self.rpc_client
+            .get_multiple_accounts_with_context(pubkeys, min_context_slot)
+            .await
+    }
+
+    async fn get_multiple_accounts_with_config(
+        &self,
+        pubkeys: &[Pubkey],
+        config: &AccountReadConfig,
+    ) -> Result<WithContext<Vec<Option<Account>>>, RpcError> {
+        self.rpc_client
+            .get_multiple_accounts_with_config(pubkeys, config)
+            .await
+    }
+
+    async fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        filters: &[Filter],
+    ) -> Result<Vec<Account>, RpcError> {
+        self.rpc_client
+            .get_program_accounts(program_id, filters)
+            .await
+    }
+
+    async fn get_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        config: SignaturesForAddressConfig,
+    ) -> Result<Vec<SignatureInfo>, RpcError> {
+        self.rpc_client
+            .get_signatures_for_address(address, config)
+            .await
+    }
+
+    async fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> Result<Vec<Option<SignatureStatus>>, RpcError> {
+        self.rpc_client.get_signature_statuses(signatures).await
+    }
+
+    async fn get_transaction(
+        &self,
+        signature: &Signature,
+    ) -> Result<Option<ConfirmedTransaction>, RpcError> {
+        self.rpc_client.get_transaction(signature).await
+    }
+
+    async fn get_token_largest_accounts(
+        &self,
+        mint: &Pubkey,
+    ) -> Result<Vec<TokenAccountBalance>, RpcError> {
+        self.rpc_client.get_token_largest_accounts(mint).await
+    }
+
+    async fn get_token_supply(&self, mint: &Pubkey) -> Result<UiTokenAmount, RpcError> {
+        self.rpc_client.get_token_supply(mint).await
+    }
+
+    async fn get_block(&self, slot: u64) -> Result<Option<ConfirmedBlock>, RpcError> {
+        self.rpc_client.get_block(slot).await
+    }
+
+    async fn get_block_time(&self, slot: u64) -> Result<Option<i64>, RpcError> {
+        self.rpc_client.get_block_time(slot).await
+    }
+
+    async fn get_inflation_reward(
+        &self,
+        addresses: &[Pubkey],
+        epoch: Option<u64>,
+    ) -> Result<Vec<Option<InflationReward>>, RpcError> {
+        self.rpc_client.get_inflation_reward(addresses, epoch).await
+    }
+
+    async fn get_stake_minimum_delegation(&self) -> Result<u64, RpcError> {
+        self.rpc_client.get_stake_minimum_delegation().await
+    }
+
+    async fn get_fee_for_message(&self, message: &str) -> Result<Option<u64>, RpcError> {
+        self.rpc_client.get_fee_for_message(message).await
+    }
+
+    async fn get_minimum_balance_for_rent_exemption(
+        &self,
+        data_len: usize,
+    ) -> Result<u64, RpcError> {
+        self.rpc_client
+            .get_minimum_balance_for_rent_exemption(data_len)
+            .await
+    }
+
+    async fn simulate_transaction(
+        &self,
+        transaction: &str,
+    ) -> Result<WithContext<SimulationResult>, RpcError> {
+        self.rpc_client.simulate_transaction(transaction).await
+    }
+
+    async fn simulate_transaction_with_accounts(
+        &self,
+        transaction: &str,
+        addresses: &[Pubkey],
+    ) -> Result<WithContext<SimulationResult>, RpcError> {
+        self.rpc_client
+            .simulate_transaction_with_accounts(transaction, addresses)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::rpc::mock::MockRpc;
+
+    #[tokio::test]
+    async fn test_concurrent_reads_share_batches() {
+        let mut rpc = MockRpc::new();
+        for seed in 0..150u8 {
+            rpc = rpc.with_balance(Pubkey::new([seed; 32]), u64::from(seed) + 1);
+        }
+        let rpc = Arc::new(rpc);
+        let scheduler = ReadScheduler::from_api(rpc.clone());
+
+        // 150 accounts, one of them read twice, plus one that does not exist
+        let mut keys: Vec<Pubkey> = (0..150u8).map(|seed| Pubkey::new([seed; 32])).collect();
+        keys.push(Pubkey::new([7; 32]));
+        keys.push(Pubkey::new([200; 32]));
+        let balances = join_all(keys.iter().map(|key| scheduler.get_balance(key))).await;
+
+        assert_eq!(balances[0].as_ref().unwrap(), &1);
+        assert_eq!(balances[149].as_ref().unwrap(), &150);
+        assert_eq!(balances[150].as_ref().unwrap(), &8);
+        assert_eq!(balances[151].as_ref().unwrap(), &0);
+        // 151 distinct keys fit in two getMultipleAccounts requests
+        assert_eq!(rpc.min_context_slots.lock().unwrap().len(), 2);
+        assert_eq!(scheduler.pending(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_dropped_flush_requeues_other_reads() {
+        let (first, second) = (Pubkey::new([1; 32]), Pubkey::new([2; 32]));
+        let rpc = MockRpc::new()
+            .with_balance(first, 1)
+            .with_balance(second, 2)
+            .with_latency(Duration::from_millis(100));
+        let scheduler = ReadScheduler::new(rpc).window(Duration::from_millis(5));
+
+        // The first read's window ends first, so it sends the batch holding
+        // both keys, then is dropped while the node is still answering
+        let (dropped, balance) = tokio::join!(
+            tokio::time::timeout(Duration::from_millis(40), scheduler.get_balance(&first)),
+            async {
+                sleep(Duration::from_millis(2)).await;
+                scheduler.get_balance(&second).await
+            },
+        );
+        assert!(dropped.is_err());
+        assert_eq!(balance.unwrap(), 2);
+        assert_eq!(scheduler.pending(), 0);
+    }
+}
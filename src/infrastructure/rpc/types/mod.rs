@@ -0,0 +1,292 @@
+//! JSON-RPC 2.0 protocol types
+//!
+//! The request, response and error envelopes [`SolanaRpcClient`] speaks,
+//! published so custom calls, proxies and test servers can reuse them instead
+//! of redefining the protocol. Every type serializes and deserializes, and
+//! [`RpcMessage`] covers both single messages and batched arrays.
+//!
+//! [`SolanaRpcClient`]: super::SolanaRpcClient
+
+use super::RpcError;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+
+/// The protocol version every message carries
+pub const JSONRPC_VERSION: &str = "2.0";
+
+/// Request id, echoed back in the matching response
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RpcId {
+    Number(u64),
+    String(String),
+    /// Sent by servers that could not read the request's id
+    #[default]
+    Null,
+}
+
+impl From<u64> for RpcId {
+    fn from(id: u64) -> Self {
+        Self::Number(id)
+    }
+}
+
+impl From<String> for RpcId {
+    fn from(id: String) -> Self {
+        Self::String(id)
+    }
+}
+
+impl From<&str> for RpcId {
+    fn from(id: &str) -> Self {
+        Self::String(id.to_string())
+    }
+}
+
+/// JSON-RPC request with positional params
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RpcRequest {
+    pub jsonrpc: String,
+    pub id: RpcId,
+    pub method: String,
+    #[serde(default)]
+    pub params: Vec<Value>,
+}
+
+impl RpcRequest {
+    /// Create a request with id 1 and no params
+    pub fn new(method: impl Into<String>) -> Self {
+        Self {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            id: RpcId::Number(1),
+            method: method.into(),
+            params: Vec::new(),
+        }
+    }
+
+    /// Set the request id
+    pub fn id(mut self, id: impl Into<RpcId>) -> Self {
+        self.id = id.into();
+        self
+    }
+
+    /// Append a positional param
+    pub fn param(mut self, param: impl Into<Value>) -> Self {
+        self.params.push(param.into());
+        self
+    }
+
+    /// Append a positional param of any serializable type
+    ///
+    /// Fails if `param` cannot be represented as JSON, e.g. a map with
+    /// non-string keys.
+    pub fn try_param(self, param: impl Serialize) -> Result<Self, RpcError> {
+        let param =
+            serde_json::to_value(param).map_err(|e| RpcError::InvalidRequest(e.to_string()))?;
+        Ok(self.param(param))
+    }
+}
+
+/// JSON-RPC response carrying either a result or an error
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RpcResponse<T> {
+    #[serde(default = "default_version")]
+    pub jsonrpc: String,
+    #[serde(default)]
+    pub id: RpcId,
+    #[serde(default = "Option::default", skip_serializing_if = "Option::is_none")]
+    pub result: Option<T>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<RpcErrorObject>,
+}
+
+impl<T> RpcResponse<T> {
+    /// Create a successful response
+    pub fn success(id: impl Into<RpcId>, result: T) -> Self {
+        Self {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            id: id.into(),
+            result: Some(result),
+            error: None,
+        }
+    }
+
+    /// Create an error response
+    pub fn failure(id: impl Into<RpcId>, error: RpcErrorObject) -> Self {
+        Self {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            id: id.into(),
+            result: None,
+            error: Some(error),
+        }
+    }
+
+    /// The result, or the error object if the call failed
+    ///
+    /// A response without either yields `Ok(None)`, as for methods returning
+    /// `null`.
+    pub fn into_result(self) -> Result<Option<T>, RpcErrorObject> {
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(self.result),
+        }
+    }
+}
+
+fn default_version() -> String {
+    JSONRPC_VERSION.to_string()
+}
+
+/// JSON-RPC error object
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RpcErrorObject {
+    pub code: i64,
+    pub message: String,
+    /// Extra detail, e.g. the transaction error and logs of a failed preflight
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+impl RpcErrorObject {
+    /// Create an error object without data
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+}
+
+impl fmt::Display for RpcErrorObject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (code {})", self.message, self.code)
+    }
+}
+
+impl From<RpcErrorObject> for RpcError {
+    fn from(error: RpcErrorObject) -> Self {
+        Self::RpcError {
+            code: error.code,
+            message: error.message,
+            data: error.data,
+        }
+    }
+}
+
+/// A single message or a batch, as sent in one HTTP body
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RpcMessage<T> {
+    Batch(Vec<T>),
+    Single(T),
+}
+
+impl<T> RpcMessage<T> {
+    /// The messages, in order
+    pub fn into_vec(self) -> Vec<T> {
+        match self {
+            Self::Batch(messages) => messages,
+            Self::Single(message) => vec![message],
+        }
+    }
+}
+
+/// Slot metadata attached to context-wrapped RPC responses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcResponseContext {
+    /// Slot at which the node evaluated the request
+    pub slot: u64,
+}
+
+/// A response value together with the context it was read at
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WithContext<T> {
+    pub context: RpcResponseContext,
+    pub value: T,
+}
+
+impl<T> WithContext<T> {
+    /// Transform the value while keeping its context
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> WithContext<U> {
+        WithContext {
+            context: self.context,
+            value: f(self.value),
+        }
+    }
+}
+
+impl<T> WithContext<Vec<T>> {
+    /// Pair each item of a batched read with the context it was read at
+    pub fn split(self) -> Vec<WithContext<T>> {
+        let context = self.context;
+        self.value
+            .into_iter()
+            .map(|value| WithContext { context, value })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_request_round_trip() {
+        let request = RpcRequest::new("getBalance")
+            .id("abc")
+            .param("11111111111111111111111111111111");
+        let value = serde_json::to_value(&request).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "jsonrpc": "2.0",
+                "id": "abc",
+                "method": "getBalance",
+                "params": ["11111111111111111111111111111111"]
+            })
+        );
+        assert_eq!(
+            serde_json::from_value::<RpcRequest>(value).unwrap(),
+            request
+        );
+
+        let batch = RpcMessage::Batch(vec![
+            RpcRequest::new("getSlot"),
+            RpcRequest::new("getBlockHeight").id(2),
+        ]);
+        let encoded = serde_json::to_string(&batch).unwrap();
+        let decoded: RpcMessage<RpcRequest> = serde_json::from_str(&encoded).unwrap();
+        assert_eq!(decoded.into_vec()[1].id, RpcId::Number(2));
+    }
+
+    #[test]
+    fn test_batched_responses() {
+        let responses: RpcMessage<RpcResponse<u64>> = serde_json::from_value(json!([
+            { "jsonrpc": "2.0", "id": 1, "result": 5 },
+            {
+                "jsonrpc": "2.0",
+                "id": null,
+                "error": { "code": -32600, "message": "Invalid request" }
+            }
+        ]))
+        .unwrap();
+        let mut responses = responses.into_vec().into_iter();
+
+        assert_eq!(responses.next().unwrap().into_result(), Ok(Some(5)));
+        let failed = responses.next().unwrap();
+        assert_eq!(failed.id, RpcId::Null);
+        let error = failed.into_result().unwrap_err();
+        assert_eq!(error, RpcErrorObject::new(-32600, "Invalid request"));
+        assert!(matches!(
+            RpcError::from(error),
+            RpcError::RpcError { code: -32600, .. }
+        ));
+
+        let encoded = serde_json::to_value(RpcResponse::success(3, 7u64)).unwrap();
+        assert_eq!(encoded, json!({ "jsonrpc": "2.0", "id": 3, "result": 7 }));
+    }
+}
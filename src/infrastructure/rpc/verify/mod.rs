@@ -0,0 +1,235 @@
+//! Signature verification of fetched transactions
+//!
+//! `getTransaction` returns a transaction's signatures alongside its message
+//! as JSON. [`verify_transaction_signatures`] rebuilds the exact message bytes
+//! that were signed, legacy or version 0, and checks every ed25519 signature
+//! locally, so an explorer can show a transaction without trusting the node
+//! that served it.
+
+use super::{ConfirmedTransaction, UiTransaction};
+use crate::domain::message::{encode_length, CompiledInstruction, Message, MessageError};
+use crate::domain::types::Pubkey;
+use thiserror::Error;
+
+/// Prefix byte of a version 0 message
+const VERSION_0_PREFIX: u8 = 0x80;
+
+/// Reasons a fetched transaction fails verification
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum SignatureVerificationError {
+    #[error("Transaction has no message header; fetch it with the json encoding")]
+    MissingHeader,
+
+    #[error("Instruction {index} has invalid base58 data")]
+    InstructionData { index: usize },
+
+    #[error(transparent)]
+    Message(#[from] MessageError),
+
+    #[error("Signature {index} was not made by {signer}")]
+    InvalidSignature { index: usize, signer: Pubkey },
+}
+
+/// Check every signature of `transaction` against its message
+///
+/// Succeeds only if the transaction carries one valid signature per required
+/// signer of its message.
+pub fn verify_transaction_signatures(
+    transaction: &ConfirmedTransaction,
+) -> Result<(), SignatureVerificationError> {
+    let transaction = &transaction.transaction;
+    let message = transaction.message_bytes()?;
+    let header = transaction
+        .message
+        .header
+        .ok_or(SignatureVerificationError::MissingHeader)?;
+
+    let expected = usize::from(header.num_required_signatures);
+    if transaction.signatures.len() != expected || transaction.message.account_keys.len() < expected
+    {
+        return Err(MessageError::SignatureCount {
+            expected,
+            found: transaction.signatures.len(),
+        }
+        .into());
+    }
+
+    for (index, (signature, signer)) in transaction
+        .signatures
+        .iter()
+        .zip(&transaction.message.account_keys)
+        .enumerate()
+    {
+        if !signature.verify(signer, &message) {
+            return Err(SignatureVerificationError::InvalidSignature {
+                index,
+                signer: *signer,
+            });
+        }
+    }
+    Ok(())
+}
+
+impl UiTransaction {
+    /// The wire-format message the signers signed
+    pub fn message_bytes(&self) -> Result<Vec<u8>, SignatureVerificationError> {
+        let ui = &self.message;
+        let instructions = ui
+            .instructions
+            .iter()
+            .enumerate()
+            .map(|(index, instruction)| {
+                let data = bs58::decode(&instruction.data)
+                    .into_vec()
+                    .map_err(|_| SignatureVerificationError::InstructionData { index })?;
+                Ok(CompiledInstruction {
+                    program_id_index: instruction.program_id_index,
+                    accounts: instruction.accounts.clone(),
+                    data,
+                })
+            })
+            .collect::<Result<Vec<_>, SignatureVerificationError>>()?;
+        let message = Message {
+            header: ui.header.ok_or(SignatureVerificationError::MissingHeader)?,
+            account_keys: ui.account_keys.clone(),
+            recent_blockhash: ui.recent_blockhash,
+            instructions,
+        };
+
+        let Some(lookups) = &ui.address_table_lookups else {
+            return Ok(message.serialize()?);
+        };
+        // A version 0 message is the legacy layout behind a version prefix,
+        // followed by its lookup tables
+        let mut bytes = vec![VERSION_0_PREFIX];
+        bytes.extend(message.serialize()?);
+        encode_length(&mut bytes, lookups.len())?;
+        for lookup in lookups {
+            bytes.extend_from_slice(lookup.account_key.as_bytes());
+            encode_length(&mut bytes, lookup.writable_indexes.len())?;
+            bytes.extend_from_slice(&lookup.writable_indexes);
+            encode_length(&mut bytes, lookup.readonly_indexes.len())?;
+            bytes.extend_from_slice(&lookup.readonly_indexes);
+        }
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::message::MessageHeader;
+    use crate::domain::programs::{InstructionAccount, ProgramInstruction};
+    use crate::domain::types::{Hash, Signature};
+    use crate::infrastructure::signer::{Keypair, Signer};
+    use serde_json::{json, Value};
+
+    fn confirmed(signatures: &[Signature], message: &Message, lookups: Option<Value>) -> Value {
+        let instructions: Vec<Value> = message
+            .instructions
+            .iter()
+            .map(|instruction| {
+                json!({
+                    "programIdIndex": instruction.program_id_index,
+                    "accounts": instruction.accounts,
+                    "data": bs58::encode(&instruction.data).into_string(),
+                    "stackHeight": null
+                })
+            })
+            .collect();
+        let mut ui_message = json!({
+            "header": message.header,
+            "accountKeys": message.account_keys,
+            "recentBlockhash": message.recent_blockhash,
+            "instructions": instructions
+        });
+        if let Some(lookups) = lookups {
+            ui_message["addressTableLookups"] = lookups;
+        }
+        json!({
+            "slot": 42,
+            "transaction": { "signatures": signatures, "message": ui_message }
+        })
+    }
+
+    #[test]
+    fn test_verify_legacy_transaction() {
+        let payer = Keypair::from_seed(&[1; 32]);
+        let authority = Keypair::from_seed(&[2; 32]);
+        let instruction = ProgramInstruction::new(
+            Pubkey::new([9; 32]),
+            vec![
+                InstructionAccount::signer(authority.pubkey(), false),
+                InstructionAccount::writable(Pubkey::new([5; 32])),
+            ],
+            vec![1, 2, 3],
+            0,
+        );
+        let message =
+            Message::compile(&[instruction], Some(&payer.pubkey()), Hash::new([7; 32])).unwrap();
+        let bytes = message.serialize().unwrap();
+        let signatures = [payer.sign_bytes(&bytes), authority.sign_bytes(&bytes)];
+
+        let transaction: ConfirmedTransaction =
+            serde_json::from_value(confirmed(&signatures, &message, None)).unwrap();
+        assert_eq!(transaction.transaction.message_bytes().unwrap(), bytes);
+        assert_eq!(verify_transaction_signatures(&transaction), Ok(()));
+
+        // A node that swaps in another signature is caught
+        let forged = [signatures[0].clone(), payer.sign_bytes(&bytes)];
+        let transaction: ConfirmedTransaction =
+            serde_json::from_value(confirmed(&forged, &message, None)).unwrap();
+        assert_eq!(
+            verify_transaction_signatures(&transaction),
+            Err(SignatureVerificationError::InvalidSignature {
+                index: 1,
+                signer: authority.pubkey()
+            })
+        );
+
+        let missing: ConfirmedTransaction =
+            serde_json::from_value(confirmed(&signatures[..1], &message, None)).unwrap();
+        assert!(matches!(
+            verify_transaction_signatures(&missing),
+            Err(SignatureVerificationError::Message(
+                MessageError::SignatureCount {
+                    expected: 2,
+                    found: 1
+                }
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_version_0_message_bytes() {
+        let payer = Keypair::from_seed(&[3; 32]);
+        let message = Message {
+            header: MessageHeader {
+                num_required_signatures: 1,
+                num_readonly_signed_accounts: 0,
+                num_readonly_unsigned_accounts: 0,
+            },
+            account_keys: vec![payer.pubkey()],
+            recent_blockhash: Hash::new([4; 32]),
+            instructions: Vec::new(),
+        };
+        let table = Pubkey::new([6; 32]);
+        let lookups = json!([{
+            "accountKey": table,
+            "writableIndexes": [1],
+            "readonlyIndexes": [2, 3]
+        }]);
+
+        let mut expected = vec![VERSION_0_PREFIX];
+        expected.extend(message.serialize().unwrap());
+        expected.push(1);
+        expected.extend_from_slice(table.as_bytes());
+        expected.extend_from_slice(&[1, 1, 2, 2, 3]);
+        let signature = payer.sign_bytes(&expected);
+
+        let transaction: ConfirmedTransaction =
+            serde_json::from_value(confirmed(&[signature], &message, Some(lookups))).unwrap();
+        assert_eq!(transaction.transaction.message_bytes().unwrap(), expected);
+        assert_eq!(verify_transaction_signatures(&transaction), Ok(()));
+    }
+}
@@ -0,0 +1,161 @@
+//! Local ed25519 keypairs
+//!
+//! A [`Keypair`] holds a secret key in memory and signs without any device or
+//! wallet round trip. Keypairs use the 64-byte `secret || public` layout of
+//! `solana-keygen` files and wallet exports.
+
+use super::{SecretBytes, Signer, SignerError};
+use crate::domain::types::{Pubkey, Signature};
+use async_trait::async_trait;
+use ed25519_dalek::{Signer as _, SigningKey};
+use std::fmt;
+use thiserror::Error;
+use zeroize::Zeroizing;
+
+/// Length of a serialized keypair
+pub const KEYPAIR_LENGTH: usize = 64;
+
+/// Errors that can occur while loading a keypair
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum KeypairError {
+    #[error("Invalid keypair length: expected 64 bytes, got {0}")]
+    InvalidLength(usize),
+
+    #[error("Public key does not match the secret key")]
+    PublicKeyMismatch,
+
+    #[error("Invalid base58 keypair: {0}")]
+    InvalidBase58(String),
+
+    #[error("System randomness unavailable: {0}")]
+    Randomness(String),
+}
+
+/// An ed25519 keypair held in memory
+#[derive(Clone)]
+pub struct Keypair {
+    signing_key: SigningKey,
+}
+
+impl Keypair {
+    /// Generate a keypair from system randomness
+    pub fn new() -> Result<Self, KeypairError> {
+        let mut seed = [0u8; 32];
+        getrandom::getrandom(&mut seed).map_err(|e| KeypairError::Randomness(e.to_string()))?;
+        Ok(Self::from_seed(&seed))
+    }
+
+    /// Derive the keypair for a 32-byte secret seed
+    pub fn from_seed(seed: &[u8; 32]) -> Self {
+        Self {
+            signing_key: SigningKey::from_bytes(seed),
+        }
+    }
+
+    /// Load a 64-byte `secret || public` keypair, checking that the halves match
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, KeypairError> {
+        let bytes: &[u8; KEYPAIR_LENGTH] = bytes
+            .try_into()
+            .map_err(|_| KeypairError::InvalidLength(bytes.len()))?;
+        SigningKey::from_keypair_bytes(bytes)
+            .map(|signing_key| Self { signing_key })
+            .map_err(|_| KeypairError::PublicKeyMismatch)
+    }
+
+    /// Load a base58-encoded keypair, as exported by browser wallets
+    pub fn from_base58(encoded: &str) -> Result<Self, KeypairError> {
+        let bytes = Zeroizing::new(
+            bs58::decode(encoded)
+                .into_vec()
+                .map_err(|e| KeypairError::InvalidBase58(e.to_string()))?,
+        );
+        Self::from_bytes(&bytes)
+    }
+
+    /// Serialize as 64 `secret || public` bytes
+    pub fn to_bytes(&self) -> SecretBytes<KEYPAIR_LENGTH> {
+        SecretBytes::new(self.signing_key.to_keypair_bytes())
+    }
+
+    /// Serialize as base58
+    ///
+    /// The string holds the secret key in the clear, so wipe it once written
+    /// out, e.g. by wrapping it in [`zeroize::Zeroizing`].
+    pub fn to_base58(&self) -> String {
+        bs58::encode(self.to_bytes().expose_secret()).into_string()
+    }
+
+    /// The 32-byte secret seed
+    pub fn secret(&self) -> SecretBytes<32> {
+        SecretBytes::new(self.signing_key.to_bytes())
+    }
+
+    /// Sign bytes without going through the async [`Signer`] interface
+    pub fn sign_bytes(&self, message: &[u8]) -> Signature {
+        Signature::new(self.signing_key.sign(message).to_bytes())
+    }
+}
+
+impl PartialEq for Keypair {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_bytes().expose_secret() == other.to_bytes().expose_secret()
+    }
+}
+
+impl Eq for Keypair {}
+
+impl fmt::Debug for Keypair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Keypair")
+            .field("pubkey", &Signer::pubkey(self))
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl Signer for Keypair {
+    fn pubkey(&self) -> Pubkey {
+        Pubkey::new(self.signing_key.verifying_key().to_bytes())
+    }
+
+    async fn sign_message(&self, message: &[u8]) -> Result<Signature, SignerError> {
+        Ok(self.sign_bytes(message))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_keypair_round_trip_and_signing() {
+        // RFC 8032 test vector 1
+        let seed: [u8; 32] = [
+            0x9d, 0x61, 0xb1, 0x9d, 0xef, 0xfd, 0x5a, 0x60, 0xba, 0x84, 0x4a, 0xf4, 0x92, 0xec,
+            0x2c, 0xc4, 0x44, 0x49, 0xc5, 0x69, 0x7b, 0x32, 0x69, 0x19, 0x70, 0x3b, 0xac, 0x03,
+            0x1c, 0xae, 0x7f, 0x60,
+        ];
+        let keypair = Keypair::from_seed(&seed);
+        assert_eq!(keypair.pubkey().as_bytes()[..4], [0xd7, 0x5a, 0x98, 0x01]);
+
+        let signature = keypair.sign_message(b"").await.unwrap();
+        assert_eq!(signature.as_bytes()[..4], [0xe5, 0x56, 0x43, 0x00]);
+
+        let restored = Keypair::from_base58(&keypair.to_base58()).unwrap();
+        assert_eq!(restored, keypair);
+        assert_eq!(restored.secret().expose_secret(), &seed);
+
+        let mut tampered = *keypair.to_bytes().expose_secret();
+        tampered[40] ^= 1;
+        assert_eq!(
+            Keypair::from_bytes(&tampered),
+            Err(KeypairError::PublicKeyMismatch)
+        );
+        assert_eq!(
+            Keypair::from_bytes(&[0; 32]),
+            Err(KeypairError::InvalidLength(32))
+        );
+        assert_ne!(Keypair::new().unwrap(), Keypair::new().unwrap());
+    }
+}
@@ -0,0 +1,267 @@
+//! Ledger hardware wallet signer
+//!
+//! Talks to the Solana app on a Ledger device over USB HID. Messages longer
+//! than one APDU are streamed in chunks, and the device asks the user to
+//! approve every signature on its screen.
+
+use super::{DerivationPath, Signer, SignerError};
+use crate::domain::types::{Pubkey, Signature};
+use async_trait::async_trait;
+use ledger_transport::{APDUCommand, Exchange};
+use ledger_transport_hid::hidapi::HidApi;
+use ledger_transport_hid::TransportNativeHID;
+use std::fmt::Display;
+
+const CLA: u8 = 0xe0;
+const INS_GET_PUBKEY: u8 = 0x05;
+const INS_SIGN_MESSAGE: u8 = 0x06;
+const P1_NON_CONFIRM: u8 = 0x00;
+const P1_CONFIRM: u8 = 0x01;
+const P2_EXTEND: u8 = 0x01;
+const P2_MORE: u8 = 0x02;
+
+/// Largest APDU payload the Solana app accepts
+const MAX_CHUNK_SIZE: usize = 255;
+
+const SW_OK: u16 = 0x9000;
+const SW_USER_REJECTED: u16 = 0x6985;
+
+/// Signs with a key held by the Solana app on a Ledger device
+pub struct LedgerSigner<E = TransportNativeHID> {
+    transport: E,
+    derivation_path: DerivationPath,
+    pubkey: Pubkey,
+}
+
+impl LedgerSigner {
+    /// Connect to the first Ledger found and load the key at `derivation_path`
+    pub async fn connect(derivation_path: DerivationPath) -> Result<Self, SignerError> {
+        let api = HidApi::new().map_err(|e| SignerError::Device(e.to_string()))?;
+        let transport =
+            TransportNativeHID::new(&api).map_err(|e| SignerError::Device(e.to_string()))?;
+        Self::with_transport(transport, derivation_path).await
+    }
+}
+
+impl<E> LedgerSigner<E>
+where
+    E: Exchange + Send + Sync,
+    E::Error: Display,
+{
+    /// Load the key at `derivation_path` over an open transport
+    pub async fn with_transport(
+        transport: E,
+        derivation_path: DerivationPath,
+    ) -> Result<Self, SignerError> {
+        let response = exchange(
+            &transport,
+            INS_GET_PUBKEY,
+            P1_NON_CONFIRM,
+            0,
+            serialize_path(&derivation_path),
+        )
+        .await?;
+        let bytes: [u8; 32] = response.as_slice().try_into().map_err(|_| {
+            SignerError::InvalidResponse(format!("{}-byte public key", response.len()))
+        })?;
+
+        Ok(Self {
+            transport,
+            derivation_path,
+            pubkey: Pubkey::new(bytes),
+        })
+    }
+
+    /// Get the derivation path of the signing key
+    pub fn derivation_path(&self) -> DerivationPath {
+        self.derivation_path
+    }
+
+    /// Show the address on the device so the user can check it
+    pub async fn display_address(&self) -> Result<(), SignerError> {
+        exchange(
+            &self.transport,
+            INS_GET_PUBKEY,
+            P1_CONFIRM,
+            0,
+            serialize_path(&self.derivation_path),
+        )
+        .await
+        .map(|_| ())
+    }
+}
+
+#[async_trait]
+impl<E> Signer for LedgerSigner<E>
+where
+    E: Exchange + Send + Sync,
+    E::Error: Display,
+{
+    fn pubkey(&self) -> Pubkey {
+        self.pubkey
+    }
+
+    async fn sign_message(&self, message: &[u8]) -> Result<Signature, SignerError> {
+        let mut response = Vec::new();
+        for (p2, payload) in sign_payloads(&self.derivation_path, message) {
+            response = exchange(&self.transport, INS_SIGN_MESSAGE, P1_CONFIRM, p2, payload).await?;
+        }
+
+        let bytes: [u8; 64] = response.as_slice().try_into().map_err(|_| {
+            SignerError::InvalidResponse(format!("{}-byte signature", response.len()))
+        })?;
+        Ok(Signature::new(bytes))
+    }
+}
+
+async fn exchange<E>(
+    transport: &E,
+    ins: u8,
+    p1: u8,
+    p2: u8,
+    data: Vec<u8>,
+) -> Result<Vec<u8>, SignerError>
+where
+    E: Exchange + Send + Sync,
+    E::Error: Display,
+{
+    let command = APDUCommand {
+        cla: CLA,
+        ins,
+        p1,
+        p2,
+        data,
+    };
+    let answer = transport
+        .exchange(&command)
+        .await
+        .map_err(|e| SignerError::Device(e.to_string()))?;
+
+    match answer.retcode() {
+        SW_OK => Ok(answer.data().to_vec()),
+        SW_USER_REJECTED => Err(SignerError::Rejected),
+        code => Err(SignerError::Device(format!(
+            "Solana app returned status {:#06x}",
+            code
+        ))),
+    }
+}
+
+/// Path length followed by each big-endian component
+fn serialize_path(path: &DerivationPath) -> Vec<u8> {
+    let indices = path.indices();
+    let mut out = Vec::with_capacity(1 + indices.len() * 4);
+    out.push(indices.len() as u8);
+    for index in indices {
+        out.extend_from_slice(&index.to_be_bytes());
+    }
+    out
+}
+
+/// Split a sign request into APDU payloads tagged with their P2 flags
+///
+/// The first payload carries the signer count and derivation path; later
+/// ones extend it, and every payload but the last sets `P2_MORE`.
+fn sign_payloads(path: &DerivationPath, message: &[u8]) -> Vec<(u8, Vec<u8>)> {
+    let mut first = vec![1];
+    first.extend(serialize_path(path));
+    let (head, rest) = message.split_at(message.len().min(MAX_CHUNK_SIZE - first.len()));
+    first.extend_from_slice(head);
+
+    let mut payloads = vec![(0, first)];
+    payloads.extend(
+        rest.chunks(MAX_CHUNK_SIZE)
+            .map(|chunk| (P2_EXTEND | P2_MORE, chunk.to_vec())),
+    );
+    if payloads.len() > 1 {
+        payloads[0].0 = P2_MORE;
+    }
+    if let Some(last) = payloads.last_mut() {
+        last.0 &= !P2_MORE;
+    }
+    payloads
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ledger_transport::APDUAnswer;
+    use std::ops::Deref;
+    use std::sync::Mutex;
+
+    /// Instruction, P1, P2 and payload of a sent APDU
+    type Command = (u8, u8, u8, Vec<u8>);
+
+    /// A device that answers each APDU with a canned response
+    struct MockDevice {
+        commands: Mutex<Vec<Command>>,
+        status: u16,
+    }
+
+    #[async_trait]
+    impl Exchange for MockDevice {
+        type Error = String;
+        type AnswerType = Vec<u8>;
+
+        async fn exchange<I>(&self, command: &APDUCommand<I>) -> Result<APDUAnswer<Vec<u8>>, String>
+        where
+            I: Deref<Target = [u8]> + Send + Sync,
+        {
+            self.commands.lock().unwrap().push((
+                command.ins,
+                command.p1,
+                command.p2,
+                command.data.to_vec(),
+            ));
+            let mut answer = match command.ins {
+                INS_GET_PUBKEY => vec![7; 32],
+                _ => vec![9; 64],
+            };
+            answer.extend_from_slice(&self.status.to_be_bytes());
+            APDUAnswer::from_answer(answer).map_err(|_| "short answer".to_string())
+        }
+    }
+
+    fn device(status: u16) -> MockDevice {
+        MockDevice {
+            commands: Mutex::new(Vec::new()),
+            status,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_signs_long_message_in_chunks() {
+        let signer = LedgerSigner::with_transport(device(SW_OK), DerivationPath::new(0, 0))
+            .await
+            .unwrap();
+        assert_eq!(signer.pubkey(), Pubkey::new([7; 32]));
+
+        let message = vec![5; 600];
+        let signature = signer.sign_message(&message).await.unwrap();
+        assert_eq!(signature, Signature::new([9; 64]));
+
+        let commands = signer.transport.commands.lock().unwrap();
+        let path = serialize_path(&DerivationPath::new(0, 0));
+        assert_eq!(commands[0], (INS_GET_PUBKEY, P1_NON_CONFIRM, 0, path));
+
+        let flags: Vec<u8> = commands[1..].iter().map(|c| c.2).collect();
+        assert_eq!(flags, vec![P2_MORE, P2_EXTEND | P2_MORE, P2_EXTEND]);
+        // Signer count and a 4-component path precede the first chunk
+        assert_eq!(commands[1].3.len(), MAX_CHUNK_SIZE);
+        assert_eq!(&commands[1].3[..2], &[1, 4]);
+        let sent: usize = commands[1..].iter().map(|c| c.3.len()).sum();
+        assert_eq!(sent, 1 + 17 + message.len());
+    }
+
+    #[tokio::test]
+    async fn test_short_message_and_rejection() {
+        assert_eq!(
+            sign_payloads(&DerivationPath::default(), &[1, 2, 3]),
+            vec![(0, vec![1, 2, 0x80, 0, 0, 44, 0x80, 0, 1, 0xf5, 1, 2, 3])]
+        );
+
+        let result =
+            LedgerSigner::with_transport(device(SW_USER_REJECTED), Default::default()).await;
+        assert!(matches!(result, Err(SignerError::Rejected)));
+    }
+}
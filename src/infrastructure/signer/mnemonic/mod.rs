@@ -0,0 +1,180 @@
+//! BIP39 mnemonics and hierarchical key derivation
+//!
+//! Wallets back keys up as a 12 or 24 word BIP39 phrase and derive each
+//! account along `m/44'/501'/account'/0'` with SLIP-0010, the ed25519 variant
+//! of BIP32. Deriving the same way here recovers the accounts Phantom and
+//! `solana-keygen` show for a phrase, for test wallets and import flows.
+
+use super::{DerivationPath, Keypair, SecretBytes, HARDENED};
+use bip39::Mnemonic;
+use hmac::{Hmac, Mac};
+use sha2::Sha512;
+use thiserror::Error;
+use zeroize::Zeroizing;
+
+/// Word counts BIP39 allows
+pub const MNEMONIC_WORD_COUNTS: [usize; 5] = [12, 15, 18, 21, 24];
+
+/// HMAC key of the SLIP-0010 ed25519 master key
+const ED25519_SEED_KEY: &[u8] = b"ed25519 seed";
+
+/// Errors that can occur while handling a mnemonic
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum MnemonicError {
+    #[error("Invalid mnemonic: {0}")]
+    InvalidMnemonic(String),
+
+    #[error("Unsupported word count {0}; expected 12, 15, 18, 21 or 24")]
+    InvalidWordCount(usize),
+
+    #[error("System randomness unavailable: {0}")]
+    Randomness(String),
+
+    #[error("Key derivation failed: {0}")]
+    Derivation(String),
+}
+
+/// Generate a new English mnemonic of `word_count` words
+pub fn generate_mnemonic(word_count: usize) -> Result<String, MnemonicError> {
+    if !MNEMONIC_WORD_COUNTS.contains(&word_count) {
+        return Err(MnemonicError::InvalidWordCount(word_count));
+    }
+    // Every three words encode 32 bits of entropy
+    let mut entropy = Zeroizing::new(vec![0u8; word_count / 3 * 4]);
+    getrandom::getrandom(&mut entropy).map_err(|e| MnemonicError::Randomness(e.to_string()))?;
+    Mnemonic::from_entropy(&entropy)
+        .map(|mnemonic| mnemonic.to_string())
+        .map_err(|e| MnemonicError::InvalidMnemonic(e.to_string()))
+}
+
+/// The 64-byte BIP39 seed of an English `phrase`, checking its checksum
+pub fn mnemonic_to_seed(phrase: &str, passphrase: &str) -> Result<SecretBytes<64>, MnemonicError> {
+    let mnemonic =
+        Mnemonic::parse(phrase).map_err(|e| MnemonicError::InvalidMnemonic(e.to_string()))?;
+    Ok(SecretBytes::new(mnemonic.to_seed(passphrase)))
+}
+
+/// Derive the ed25519 secret at hardened `indices` below `seed` (SLIP-0010)
+///
+/// Every intermediate key and chain code is wiped as soon as the next level
+/// replaces it.
+fn derive_secret(seed: &[u8], indices: &[u32]) -> Result<Zeroizing<[u8; 32]>, MnemonicError> {
+    let master = hmac_sha512(ED25519_SEED_KEY, &[seed])?;
+    let (mut key, mut chain_code) = split(&master);
+    for index in indices {
+        // ed25519 only defines hardened children
+        let index = (index | HARDENED).to_be_bytes();
+        let child = hmac_sha512(&*chain_code, &[&[0], &*key, &index])?;
+        (key, chain_code) = split(&child);
+    }
+    Ok(key)
+}
+
+fn hmac_sha512(key: &[u8], data: &[&[u8]]) -> Result<Zeroizing<[u8; 64]>, MnemonicError> {
+    let mut mac = Hmac::<Sha512>::new_from_slice(key)
+        .map_err(|e| MnemonicError::Derivation(e.to_string()))?;
+    for chunk in data {
+        mac.update(chunk);
+    }
+    Ok(Zeroizing::new(mac.finalize().into_bytes().into()))
+}
+
+fn split(bytes: &[u8; 64]) -> (Zeroizing<[u8; 32]>, Zeroizing<[u8; 32]>) {
+    let mut key = Zeroizing::new([0u8; 32]);
+    let mut chain_code = Zeroizing::new([0u8; 32]);
+    key.copy_from_slice(&bytes[..32]);
+    chain_code.copy_from_slice(&bytes[32..]);
+    (key, chain_code)
+}
+
+impl Keypair {
+    /// Derive the keypair at `path` from a BIP39 seed
+    pub fn from_seed_and_derivation_path(
+        seed: &[u8],
+        path: &DerivationPath,
+    ) -> Result<Self, MnemonicError> {
+        let secret = derive_secret(seed, &path.indices())?;
+        Ok(Self::from_seed(&secret))
+    }
+
+    /// Recover the keypair at `path` from a mnemonic
+    ///
+    /// Phantom and most browser wallets put account `n` at
+    /// `DerivationPath::new(n, 0)`.
+    pub fn from_mnemonic(
+        phrase: &str,
+        passphrase: &str,
+        path: &DerivationPath,
+    ) -> Result<Self, MnemonicError> {
+        let seed = mnemonic_to_seed(phrase, passphrase)?;
+        Self::from_seed_and_derivation_path(seed.expose_secret(), path)
+    }
+
+    /// Recover the keypair `solana-keygen new` prints for a mnemonic
+    ///
+    /// Without `--derivation-path`, `solana-keygen` uses the first 32 bytes
+    /// of the BIP39 seed directly instead of deriving a child key.
+    pub fn from_mnemonic_seed(phrase: &str, passphrase: &str) -> Result<Self, MnemonicError> {
+        let seed = mnemonic_to_seed(phrase, passphrase)?;
+        let secret = SecretBytes::<32>::from_slice(&seed.expose_secret()[..32])
+            .ok_or_else(|| MnemonicError::Derivation("BIP39 seed too short".to_string()))?;
+        Ok(Self::from_seed(secret.expose_secret()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::signer::Signer;
+
+    const PHRASE: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    #[test]
+    fn test_slip10_vector() {
+        // SLIP-0010 ed25519 test vector 1
+        let seed: Vec<u8> = (0u8..16).collect();
+        assert_eq!(
+            hex(&*derive_secret(&seed, &[]).unwrap()),
+            "2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7"
+        );
+        assert_eq!(
+            hex(&*derive_secret(&seed, &[0]).unwrap()),
+            "68e0fe46dfb67e368c75379acec591dad19df3cde26e63b93a8e704f1dade7a3"
+        );
+    }
+
+    #[test]
+    fn test_mnemonic_keypairs() {
+        // BIP39 reference vector
+        assert_eq!(
+            hex(mnemonic_to_seed(PHRASE, "TREZOR").unwrap().expose_secret()),
+            "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04"
+        );
+        assert!(matches!(
+            mnemonic_to_seed(&PHRASE.replace("about", "abandon"), ""),
+            Err(MnemonicError::InvalidMnemonic(_))
+        ));
+
+        let first = Keypair::from_mnemonic(PHRASE, "", &DerivationPath::new(0, 0)).unwrap();
+        let second = Keypair::from_mnemonic(PHRASE, "", &DerivationPath::new(1, 0)).unwrap();
+        // The first account Phantom shows for this phrase
+        assert_eq!(
+            first.pubkey().to_string(),
+            "HAgk14JpMQLgt6rVgv7cBQFJWFto5Dqxi472uT3DKpqk"
+        );
+        assert_ne!(first, second);
+        assert_ne!(Keypair::from_mnemonic_seed(PHRASE, "").unwrap(), first);
+
+        let phrase = generate_mnemonic(24).unwrap();
+        assert_eq!(phrase.split_whitespace().count(), 24);
+        assert!(mnemonic_to_seed(&phrase, "").is_ok());
+        assert_eq!(
+            generate_mnemonic(13),
+            Err(MnemonicError::InvalidWordCount(13))
+        );
+    }
+}
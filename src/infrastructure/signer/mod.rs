@@ -0,0 +1,134 @@
+//! Transaction signing backends
+//!
+//! [`Signer`] abstracts over anything that can produce an ed25519 signature for
+//! a serialized message, so transaction code is shared between browser
+//! wallets, local [`Keypair`]s and, on native targets, hardware wallets.
+
+pub mod keypair;
+#[cfg(all(feature = "ledger", not(target_arch = "wasm32")))]
+pub mod ledger;
+#[cfg(feature = "mnemonic")]
+pub mod mnemonic;
+pub mod secret;
+#[cfg(all(feature = "native", not(target_arch = "wasm32")))]
+pub mod vanity;
+
+pub use keypair::{Keypair, KeypairError};
+#[cfg(all(feature = "ledger", not(target_arch = "wasm32")))]
+pub use ledger::LedgerSigner;
+#[cfg(feature = "mnemonic")]
+pub use mnemonic::{generate_mnemonic, mnemonic_to_seed, MnemonicError};
+pub use secret::SecretBytes;
+#[cfg(all(feature = "native", not(target_arch = "wasm32")))]
+pub use vanity::{VanityError, VanityGrinder, VanityMatch, VanityPattern};
+
+use crate::domain::message::{Message, MessageError};
+use crate::domain::types::{Pubkey, Signature};
+use async_trait::async_trait;
+use std::fmt;
+use thiserror::Error;
+
+/// Hardened derivation offset
+const HARDENED: u32 = 0x8000_0000;
+
+/// BIP44 purpose and Solana coin type
+const SOLANA_PATH_PREFIX: [u32; 2] = [44, 501];
+
+/// Errors that can occur while signing
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum SignerError {
+    #[error("Signing device error: {0}")]
+    Device(String),
+
+    #[error("Signing request was rejected by the user")]
+    Rejected,
+
+    #[error("Invalid signer response: {0}")]
+    InvalidResponse(String),
+
+    #[error("Message encoding failed: {0}")]
+    Message(#[from] MessageError),
+}
+
+/// Something that can sign messages for one public key
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait Signer: Send + Sync {
+    /// The public key signatures verify against
+    fn pubkey(&self) -> Pubkey;
+
+    /// Sign raw message bytes
+    async fn sign_message(&self, message: &[u8]) -> Result<Signature, SignerError>;
+
+    /// Sign a transaction message
+    async fn sign(&self, message: &Message) -> Result<Signature, SignerError> {
+        self.sign_message(&message.serialize()?).await
+    }
+}
+
+/// A Solana BIP44 path, `m/44'/501'[/account'[/change']]`
+///
+/// Every component is hardened, as ed25519 derivation requires.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DerivationPath {
+    pub account: Option<u32>,
+    pub change: Option<u32>,
+}
+
+impl DerivationPath {
+    /// The path `m/44'/501'/account'`
+    pub fn account(account: u32) -> Self {
+        Self {
+            account: Some(account),
+            change: None,
+        }
+    }
+
+    /// The path `m/44'/501'/account'/change'`
+    pub fn new(account: u32, change: u32) -> Self {
+        Self {
+            account: Some(account),
+            change: Some(change),
+        }
+    }
+
+    /// Path components with the hardened bit set
+    pub fn indices(&self) -> Vec<u32> {
+        let tail = match (self.account, self.change) {
+            (Some(account), Some(change)) => vec![account, change],
+            (Some(account), None) => vec![account],
+            (None, _) => Vec::new(),
+        };
+        SOLANA_PATH_PREFIX
+            .iter()
+            .chain(&tail)
+            .map(|index| index | HARDENED)
+            .collect()
+    }
+}
+
+impl fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "m")?;
+        for index in self.indices() {
+            write!(f, "/{}'", index & !HARDENED)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_derivation_path() {
+        assert_eq!(DerivationPath::default().to_string(), "m/44'/501'");
+        assert_eq!(DerivationPath::account(2).to_string(), "m/44'/501'/2'");
+        assert_eq!(DerivationPath::new(0, 0).to_string(), "m/44'/501'/0'/0'");
+        assert_eq!(
+            DerivationPath::new(1, 0).indices(),
+            vec![0x8000_002c, 0x8000_01f5, 0x8000_0001, 0x8000_0000]
+        );
+    }
+}
@@ -0,0 +1,94 @@
+//! Secret key material
+//!
+//! Keys end up in logs when an app derives `Debug` on a struct holding one or
+//! formats an error with it. [`SecretBytes`] holds private key bytes, prints
+//! only their length and wipes them from memory when dropped, so the bytes
+//! are only ever seen through an explicit [`SecretBytes::expose_secret`].
+
+use std::fmt;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// `N` bytes of private key material, redacted when printed and zeroed on drop
+#[derive(Clone)]
+pub struct SecretBytes<const N: usize>([u8; N]);
+
+impl<const N: usize> SecretBytes<N> {
+    /// Wrap `bytes`
+    ///
+    /// Arrays are `Copy`, so this only wipes its own copy: pass a temporary,
+    /// or zeroize the caller's array once it is wrapped.
+    pub fn new(bytes: [u8; N]) -> Self {
+        bytes.into()
+    }
+
+    /// Copy the secret out of `bytes`, `None` unless it is exactly `N` bytes
+    pub fn from_slice(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != N {
+            return None;
+        }
+        let mut secret = Self([0; N]);
+        secret.0.copy_from_slice(bytes);
+        Some(secret)
+    }
+
+    /// The secret bytes; keep the borrow short and never log it
+    pub fn expose_secret(&self) -> &[u8; N] {
+        &self.0
+    }
+
+    /// Number of secret bytes, `N`
+    pub fn len(&self) -> usize {
+        N
+    }
+
+    /// Whether the secret holds no bytes
+    pub fn is_empty(&self) -> bool {
+        N == 0
+    }
+}
+
+impl<const N: usize> From<[u8; N]> for SecretBytes<N> {
+    fn from(mut bytes: [u8; N]) -> Self {
+        let secret = Self(bytes);
+        bytes.zeroize();
+        secret
+    }
+}
+
+impl<const N: usize> Drop for SecretBytes<N> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<const N: usize> ZeroizeOnDrop for SecretBytes<N> {}
+
+impl<const N: usize> fmt::Debug for SecretBytes<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SecretBytes<{}>([REDACTED])", N)
+    }
+}
+
+impl<const N: usize> fmt::Display for SecretBytes<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[REDACTED]")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secret_bytes_are_redacted() {
+        let secret = SecretBytes::from([7u8; 32]);
+        assert_eq!(format!("{:?}", secret), "SecretBytes<32>([REDACTED])");
+        assert_eq!(secret.to_string(), "[REDACTED]");
+        assert_eq!(secret.expose_secret(), &[7; 32]);
+        assert_eq!(secret.len(), 32);
+
+        assert!(SecretBytes::<32>::from_slice(&[1; 31]).is_none());
+        let copied = SecretBytes::<4>::from_slice(&[1, 2, 3, 4]).unwrap();
+        assert_eq!(copied.expose_secret(), &[1, 2, 3, 4]);
+    }
+}
@@ -0,0 +1,258 @@
+//! Vanity address grinding
+//!
+//! Branded program ids and mints start or end with a recognizable word.
+//! [`VanityGrinder`] generates keypairs on every core until one's base58
+//! pubkey matches a [`VanityPattern`]. Each extra character multiplies the
+//! expected work by up to 58, so patterns beyond five or six characters take
+//! hours; [`VanityGrinder::max_attempts`] bounds the search.
+
+use super::keypair::{Keypair, KeypairError};
+use super::Signer;
+use crate::domain::types::Pubkey;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use thiserror::Error;
+use zeroize::Zeroizing;
+
+/// Characters base58 pubkeys are written with
+const BASE58_ALPHABET: &str = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Attempts a thread makes between checks of the shared counters
+const ATTEMPTS_PER_CHECK: u64 = 256;
+
+/// Errors that can occur while grinding
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum VanityError {
+    #[error("The pattern needs a prefix or a suffix")]
+    EmptyPattern,
+
+    #[error("'{0}' never appears in a base58 pubkey")]
+    InvalidCharacter(char),
+
+    #[error("No match after {attempts} attempts")]
+    NotFound { attempts: u64 },
+
+    #[error("Keypair generation failed: {0}")]
+    Keypair(#[from] KeypairError),
+
+    #[error("A grinding thread panicked")]
+    WorkerPanicked,
+}
+
+/// What a vanity pubkey starts and ends with
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VanityPattern {
+    prefix: String,
+    suffix: String,
+    ignore_case: bool,
+}
+
+impl VanityPattern {
+    /// A pattern matching every pubkey until a prefix or suffix is set
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Require the pubkey to start with `prefix`
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Require the pubkey to end with `suffix`
+    pub fn suffix(mut self, suffix: impl Into<String>) -> Self {
+        self.suffix = suffix.into();
+        self
+    }
+
+    /// Compare letters without regard to case, which makes matches far quicker
+    pub fn ignore_case(mut self, ignore_case: bool) -> Self {
+        self.ignore_case = ignore_case;
+        self
+    }
+
+    /// Check the pattern can match a pubkey at all
+    pub fn validate(&self) -> Result<(), VanityError> {
+        if self.prefix.is_empty() && self.suffix.is_empty() {
+            return Err(VanityError::EmptyPattern);
+        }
+        let allowed = |c: char| {
+            BASE58_ALPHABET.contains(c)
+                || (self.ignore_case
+                    && (BASE58_ALPHABET.contains(c.to_ascii_lowercase())
+                        || BASE58_ALPHABET.contains(c.to_ascii_uppercase())))
+        };
+        match self
+            .prefix
+            .chars()
+            .chain(self.suffix.chars())
+            .find(|c| !allowed(*c))
+        {
+            Some(c) => Err(VanityError::InvalidCharacter(c)),
+            None => Ok(()),
+        }
+    }
+
+    /// Whether `pubkey` matches
+    pub fn matches(&self, pubkey: &Pubkey) -> bool {
+        let address = pubkey.to_base58();
+        if self.ignore_case {
+            let address = address.to_ascii_lowercase();
+            address.starts_with(&self.prefix.to_ascii_lowercase())
+                && address.ends_with(&self.suffix.to_ascii_lowercase())
+        } else {
+            address.starts_with(&self.prefix) && address.ends_with(&self.suffix)
+        }
+    }
+}
+
+/// A keypair found by [`VanityGrinder::grind`]
+#[derive(Debug, Clone)]
+pub struct VanityMatch {
+    pub keypair: Keypair,
+    /// Keypairs generated across all threads before the match
+    pub attempts: u64,
+}
+
+/// Searches for a keypair whose pubkey matches a pattern, on several threads
+#[derive(Debug, Clone)]
+pub struct VanityGrinder {
+    pattern: VanityPattern,
+    threads: usize,
+    max_attempts: Option<u64>,
+}
+
+impl VanityGrinder {
+    /// Grind for `pattern` on every available core, without an attempt limit
+    pub fn new(pattern: VanityPattern) -> Self {
+        Self {
+            pattern,
+            threads: thread::available_parallelism().map_or(1, NonZeroUsize::get),
+            max_attempts: None,
+        }
+    }
+
+    /// Set how many threads grind in parallel
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Give up after about `max_attempts` keypairs
+    pub fn max_attempts(mut self, max_attempts: u64) -> Self {
+        self.max_attempts = Some(max_attempts);
+        self
+    }
+
+    /// Generate keypairs until one matches, blocking the calling thread
+    ///
+    /// Each thread draws one random seed and XORs a counter into it for each
+    /// attempt, so the system randomness is read once per thread rather than
+    /// per attempt while every seed stays random.
+    pub fn grind(&self) -> Result<VanityMatch, VanityError> {
+        self.pattern.validate()?;
+        let limit = self.max_attempts.unwrap_or(u64::MAX);
+        let attempts = AtomicU64::new(0);
+        let done = AtomicBool::new(false);
+        let found: Mutex<Option<Keypair>> = Mutex::new(None);
+
+        let results: Vec<Result<(), VanityError>> = thread::scope(|scope| {
+            let workers: Vec<_> = (0..self.threads)
+                .map(|_| {
+                    scope.spawn(|| -> Result<(), VanityError> {
+                        let _stop = StopOnExit(&done);
+                        let base = Zeroizing::new(*Keypair::new()?.secret().expose_secret());
+                        let mut seed = base.clone();
+                        let mut counter = 0u64;
+                        while !done.load(Ordering::Relaxed) {
+                            for _ in 0..ATTEMPTS_PER_CHECK {
+                                counter = counter.wrapping_add(1);
+                                for (byte, (base, count)) in
+                                    seed.iter_mut().zip(base.iter().zip(counter.to_le_bytes()))
+                                {
+                                    *byte = base ^ count;
+                                }
+                                let keypair = Keypair::from_seed(&seed);
+                                if self.pattern.matches(&keypair.pubkey()) {
+                                    done.store(true, Ordering::Relaxed);
+                                    found
+                                        .lock()
+                                        .unwrap_or_else(|e| e.into_inner())
+                                        .get_or_insert(keypair);
+                                    break;
+                                }
+                            }
+                            let total = attempts.fetch_add(ATTEMPTS_PER_CHECK, Ordering::Relaxed);
+                            if total.saturating_add(ATTEMPTS_PER_CHECK) >= limit {
+                                done.store(true, Ordering::Relaxed);
+                            }
+                        }
+                        Ok(())
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .map(|worker| worker.join().unwrap_or(Err(VanityError::WorkerPanicked)))
+                .collect()
+        });
+        results.into_iter().collect::<Result<(), _>>()?;
+
+        let attempts = attempts.into_inner();
+        match found.into_inner().unwrap_or_else(|e| e.into_inner()) {
+            Some(keypair) => Ok(VanityMatch { keypair, attempts }),
+            None => Err(VanityError::NotFound { attempts }),
+        }
+    }
+}
+
+/// Stops the other threads when a thread finishes, including by panicking
+struct StopOnExit<'a>(&'a AtomicBool);
+
+impl Drop for StopOnExit<'_> {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grind_finds_a_matching_keypair() {
+        assert_eq!(
+            VanityPattern::new().validate(),
+            Err(VanityError::EmptyPattern)
+        );
+        assert_eq!(
+            VanityPattern::new().prefix("S0L").validate(),
+            Err(VanityError::InvalidCharacter('0'))
+        );
+        assert_eq!(
+            VanityPattern::new()
+                .prefix("Il")
+                .ignore_case(true)
+                .validate(),
+            Ok(())
+        );
+
+        let pattern = VanityPattern::new().suffix("a");
+        let found = VanityGrinder::new(pattern.clone())
+            .threads(2)
+            .grind()
+            .unwrap();
+        assert!(found.keypair.pubkey().to_base58().ends_with('a'));
+        assert!(pattern.matches(&found.keypair.pubkey()));
+
+        let hopeless = VanityGrinder::new(VanityPattern::new().prefix("zzzzzzzz"))
+            .threads(2)
+            .max_attempts(1_000);
+        assert!(matches!(
+            hopeless.grind(),
+            Err(VanityError::NotFound { attempts }) if attempts >= 1_000
+        ));
+    }
+}
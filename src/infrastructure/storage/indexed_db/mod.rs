@@ -0,0 +1,155 @@
+//! Browser IndexedDB storage
+//!
+//! IndexedDB is asynchronous while [`StorageAdapter`] is not, so
+//! [`IndexedDbStorage::open`] loads every entry into memory once. Reads are
+//! then answered from memory, and writes update memory at once and are
+//! persisted in the background in the order they were made.
+
+use super::{MemoryStorage, StorageAdapter, StorageError};
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+use web_sys::{IdbDatabase, IdbObjectStore, IdbOpenDbRequest, IdbRequest, IdbTransactionMode};
+
+/// Object store holding the entries of every database opened here
+const STORE: &str = "entries";
+
+/// Schema version of the databases
+const VERSION: u32 = 1;
+
+/// Storage persisted to an IndexedDB database
+#[derive(Debug, Clone)]
+pub struct IndexedDbStorage {
+    database: String,
+    entries: MemoryStorage,
+}
+
+impl IndexedDbStorage {
+    /// Open the database named `database`, creating it if needed, and load
+    /// its entries
+    pub async fn open(database: &str) -> Result<Self, StorageError> {
+        let db = open_database(database).await?;
+        let store = object_store(&db, IdbTransactionMode::Readonly)?;
+        let keys = settle(&store.get_all_keys().map_err(io("list keys"))?).await?;
+        let values = settle(&store.get_all().map_err(io("read entries"))?).await?;
+        db.close();
+
+        let entries = MemoryStorage::new();
+        let keys = js_sys::Array::from(&keys);
+        let values = js_sys::Array::from(&values);
+        for (key, value) in keys.iter().zip(values.iter()) {
+            if let (Some(key), Some(value)) = (key.as_string(), value.as_string()) {
+                entries.set(&key, &value)?;
+            }
+        }
+
+        Ok(Self {
+            database: database.to_string(),
+            entries,
+        })
+    }
+
+    /// Write `value` to the database, or delete the key when it is `None`
+    fn persist(&self, key: &str, value: Option<&str>) {
+        let database = self.database.clone();
+        let key = key.to_string();
+        let value = value.map(str::to_string);
+        spawn_local(async move {
+            if let Err(e) = write(&database, &key, value.as_deref()).await {
+                web_sys::console::warn_1(&JsValue::from_str(&format!(
+                    "IndexedDB write of {} failed: {}",
+                    key, e
+                )));
+            }
+        });
+    }
+}
+
+impl StorageAdapter for IndexedDbStorage {
+    fn get(&self, key: &str) -> Result<Option<String>, StorageError> {
+        self.entries.get(key)
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), StorageError> {
+        self.entries.set(key, value)?;
+        self.persist(key, Some(value));
+        Ok(())
+    }
+
+    fn remove(&self, key: &str) -> Result<(), StorageError> {
+        self.entries.remove(key)?;
+        self.persist(key, None);
+        Ok(())
+    }
+
+    fn keys(&self, prefix: &str) -> Result<Vec<String>, StorageError> {
+        self.entries.keys(prefix)
+    }
+}
+
+async fn write(database: &str, key: &str, value: Option<&str>) -> Result<(), StorageError> {
+    let db = open_database(database).await?;
+    let store = object_store(&db, IdbTransactionMode::Readwrite)?;
+    let key = JsValue::from_str(key);
+    let request = match value {
+        Some(value) => store.put_with_key(&JsValue::from_str(value), &key),
+        None => store.delete(&key),
+    }
+    .map_err(io("write"))?;
+    let result = settle(&request).await;
+    db.close();
+    result.map(|_| ())
+}
+
+async fn open_database(name: &str) -> Result<IdbDatabase, StorageError> {
+    let factory = web_sys::window()
+        .ok_or_else(|| StorageError::Unavailable("no window".to_string()))?
+        .indexed_db()
+        .map_err(|_| StorageError::Unavailable("IndexedDB access denied".to_string()))?
+        .ok_or_else(|| StorageError::Unavailable("IndexedDB missing".to_string()))?;
+    let request = factory.open_with_u32(name, VERSION).map_err(io("open"))?;
+
+    let on_upgrade = Closure::<dyn FnMut(web_sys::Event)>::new(|event: web_sys::Event| {
+        let db = event
+            .target()
+            .and_then(|target| target.dyn_into::<IdbOpenDbRequest>().ok())
+            .and_then(|request| request.result().ok())
+            .and_then(|result| result.dyn_into::<IdbDatabase>().ok());
+        if let Some(db) = db {
+            // Only fails if the store exists, which a fresh database rules out
+            let _ = db.create_object_store(STORE);
+        }
+    });
+    request.set_onupgradeneeded(Some(on_upgrade.as_ref().unchecked_ref()));
+    let db = settle(&request).await;
+    request.set_onupgradeneeded(None);
+
+    db?.dyn_into::<IdbDatabase>()
+        .map_err(|_| StorageError::Io("open did not return a database".to_string()))
+}
+
+fn object_store(
+    db: &IdbDatabase,
+    mode: IdbTransactionMode,
+) -> Result<IdbObjectStore, StorageError> {
+    db.transaction_with_str_and_mode(STORE, mode)
+        .and_then(|transaction| transaction.object_store(STORE))
+        .map_err(io("start a transaction"))
+}
+
+/// Wait for `request` to finish and return its result
+async fn settle(request: &IdbRequest) -> Result<JsValue, StorageError> {
+    let promise = js_sys::Promise::new(&mut |resolve, reject| {
+        request.set_onsuccess(Some(&resolve));
+        request.set_onerror(Some(&reject));
+    });
+    let outcome = JsFuture::from(promise).await;
+    request.set_onsuccess(None);
+    request.set_onerror(None);
+    outcome.map_err(io("complete a request"))?;
+    request.result().map_err(io("read a result"))
+}
+
+fn io(action: &'static str) -> impl Fn(JsValue) -> StorageError {
+    move |_| StorageError::Io(format!("IndexedDB failed to {}", action))
+}
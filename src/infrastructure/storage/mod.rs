@@ -0,0 +1,286 @@
+//! Key-value storage adapters
+//!
+//! Services that need to persist small pieces of state (IDLs, keystores,
+//! pending transactions, wallet sessions) go through the [`StorageAdapter`]
+//! trait so the same code runs against browser `localStorage` or IndexedDB,
+//! files on disk, or plain memory in tests.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+pub mod indexed_db;
+
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+pub use indexed_db::IndexedDbStorage;
+
+/// String key-value storage used by services to persist state
+pub trait StorageAdapter: Send + Sync {
+    /// Read a value
+    fn get(&self, key: &str) -> Result<Option<String>, StorageError>;
+
+    /// Write a value, replacing any previous one
+    fn set(&self, key: &str, value: &str) -> Result<(), StorageError>;
+
+    /// Remove a value (no-op if absent)
+    fn remove(&self, key: &str) -> Result<(), StorageError>;
+
+    /// List keys starting with `prefix`
+    fn keys(&self, prefix: &str) -> Result<Vec<String>, StorageError>;
+}
+
+/// Errors that can occur while accessing storage
+#[derive(Debug, Clone, Error)]
+pub enum StorageError {
+    #[error("Storage unavailable: {0}")]
+    Unavailable(String),
+
+    #[error("Storage I/O error: {0}")]
+    Io(String),
+
+    #[error("Invalid storage key: {0}")]
+    InvalidKey(String),
+}
+
+/// In-memory storage, shared between clones
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStorage {
+    entries: Arc<Mutex<HashMap<String, String>>>,
+}
+
+impl MemoryStorage {
+    /// Create an empty in-memory storage
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl StorageAdapter for MemoryStorage {
+    fn get(&self, key: &str) -> Result<Option<String>, StorageError> {
+        let entries = self
+            .entries
+            .lock()
+            .map_err(|e| StorageError::Unavailable(e.to_string()))?;
+        Ok(entries.get(key).cloned())
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), StorageError> {
+        let mut entries = self
+            .entries
+            .lock()
+            .map_err(|e| StorageError::Unavailable(e.to_string()))?;
+        entries.insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+
+    fn remove(&self, key: &str) -> Result<(), StorageError> {
+        let mut entries = self
+            .entries
+            .lock()
+            .map_err(|e| StorageError::Unavailable(e.to_string()))?;
+        entries.remove(key);
+        Ok(())
+    }
+
+    fn keys(&self, prefix: &str) -> Result<Vec<String>, StorageError> {
+        let entries = self
+            .entries
+            .lock()
+            .map_err(|e| StorageError::Unavailable(e.to_string()))?;
+        let mut keys: Vec<String> = entries
+            .keys()
+            .filter(|key| key.starts_with(prefix))
+            .cloned()
+            .collect();
+        keys.sort();
+        Ok(keys)
+    }
+}
+
+/// File-backed storage storing one file per key in a directory
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone)]
+pub struct FileStorage {
+    directory: std::path::PathBuf,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl FileStorage {
+    /// Create a file storage rooted at `directory` (created on first write)
+    pub fn new(directory: impl Into<std::path::PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+        }
+    }
+
+    fn path_for(&self, key: &str) -> Result<std::path::PathBuf, StorageError> {
+        let valid = !key.is_empty()
+            && key
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, ':' | '-' | '_' | '.'))
+            && !key.starts_with('.');
+        if !valid {
+            return Err(StorageError::InvalidKey(key.to_string()));
+        }
+        // ':' is not portable in file names
+        Ok(self.directory.join(key.replace(':', "__")))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl StorageAdapter for FileStorage {
+    fn get(&self, key: &str) -> Result<Option<String>, StorageError> {
+        match std::fs::read_to_string(self.path_for(key)?) {
+            Ok(value) => Ok(Some(value)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(StorageError::Io(e.to_string())),
+        }
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), StorageError> {
+        let path = self.path_for(key)?;
+        std::fs::create_dir_all(&self.directory).map_err(|e| StorageError::Io(e.to_string()))?;
+        std::fs::write(path, value).map_err(|e| StorageError::Io(e.to_string()))
+    }
+
+    fn remove(&self, key: &str) -> Result<(), StorageError> {
+        match std::fs::remove_file(self.path_for(key)?) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(StorageError::Io(e.to_string())),
+        }
+    }
+
+    fn keys(&self, prefix: &str) -> Result<Vec<String>, StorageError> {
+        let entries = match std::fs::read_dir(&self.directory) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(StorageError::Io(e.to_string())),
+        };
+
+        let mut keys: Vec<String> = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .map(|name| name.replace("__", ":"))
+            .filter(|key| key.starts_with(prefix))
+            .collect();
+        keys.sort();
+        Ok(keys)
+    }
+}
+
+/// Browser `localStorage` storage
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+#[derive(Debug, Clone, Default)]
+pub struct LocalStorage;
+
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+impl LocalStorage {
+    /// Create a handle to the window's `localStorage`
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn storage() -> Result<web_sys::Storage, StorageError> {
+        web_sys::window()
+            .ok_or_else(|| StorageError::Unavailable("no window".to_string()))?
+            .local_storage()
+            .map_err(|_| StorageError::Unavailable("localStorage access denied".to_string()))?
+            .ok_or_else(|| StorageError::Unavailable("localStorage missing".to_string()))
+    }
+}
+
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+impl StorageAdapter for LocalStorage {
+    fn get(&self, key: &str) -> Result<Option<String>, StorageError> {
+        Self::storage()?
+            .get_item(key)
+            .map_err(|_| StorageError::Io(format!("failed to read {}", key)))
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), StorageError> {
+        Self::storage()?
+            .set_item(key, value)
+            .map_err(|_| StorageError::Io(format!("failed to write {}", key)))
+    }
+
+    fn remove(&self, key: &str) -> Result<(), StorageError> {
+        Self::storage()?
+            .remove_item(key)
+            .map_err(|_| StorageError::Io(format!("failed to remove {}", key)))
+    }
+
+    fn keys(&self, prefix: &str) -> Result<Vec<String>, StorageError> {
+        let storage = Self::storage()?;
+        let length = storage
+            .length()
+            .map_err(|_| StorageError::Io("failed to list keys".to_string()))?;
+
+        let mut keys: Vec<String> = (0..length)
+            .filter_map(|index| storage.key(index).ok().flatten())
+            .filter(|key| key.starts_with(prefix))
+            .collect();
+        keys.sort();
+        Ok(keys)
+    }
+}
+
+/// The default persistent storage for the current platform
+///
+/// `localStorage` in the browser (with the `wasm` feature, memory otherwise),
+/// a `.gloo_solana` directory in the working directory natively.
+pub fn default_storage() -> Arc<dyn StorageAdapter> {
+    #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+    {
+        Arc::new(LocalStorage::new())
+    }
+    #[cfg(all(target_arch = "wasm32", not(feature = "wasm")))]
+    {
+        Arc::new(MemoryStorage::new())
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        Arc::new(FileStorage::new(".gloo_solana"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exercise(storage: &dyn StorageAdapter) {
+        assert_eq!(storage.get("app:a").unwrap(), None);
+        storage.set("app:a", "1").unwrap();
+        storage.set("app:b", "2").unwrap();
+        storage.set("other", "3").unwrap();
+
+        assert_eq!(storage.get("app:a").unwrap(), Some("1".to_string()));
+        assert_eq!(storage.keys("app:").unwrap(), vec!["app:a", "app:b"]);
+
+        storage.remove("app:a").unwrap();
+        storage.remove("app:a").unwrap();
+        assert_eq!(storage.get("app:a").unwrap(), None);
+    }
+
+    #[test]
+    fn test_memory_storage() {
+        let storage = MemoryStorage::new();
+        exercise(&storage);
+
+        // Clones share entries
+        let clone = storage.clone();
+        assert_eq!(clone.get("app:b").unwrap(), Some("2".to_string()));
+    }
+
+    #[test]
+    fn test_file_storage() {
+        let directory =
+            std::env::temp_dir().join(format!("gloo_solana_storage_test_{}", std::process::id()));
+        let storage = FileStorage::new(&directory);
+        exercise(&storage);
+        assert!(storage.set("../escape", "x").is_err());
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,416 @@
+//! OpenTelemetry export of client metrics and spans
+//!
+//! Both sinks plug into [`RpcClientBuilder::metrics_sink`](crate::infrastructure::rpc::RpcClientBuilder::metrics_sink).
+//! On native targets [`OtelMetricsSink`] records a counter, a latency
+//! histogram and a client span per request through the `opentelemetry` API,
+//! so any installed SDK pipeline (for example `opentelemetry-otlp` over HTTP)
+//! exports them. The SDK's exporters do not run in the browser, so
+//! [`BufferedTelemetry`] keeps completed requests in memory and posts them as
+//! OTLP/HTTP JSON when the app calls [`BufferedTelemetry::flush`].
+
+use crate::infrastructure::http::{HttpClient, HttpError};
+use crate::infrastructure::metrics::{ErrorKind, MetricsSink, RequestEvent};
+use crate::infrastructure::time::{SystemTime, UNIX_EPOCH};
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Instrumentation scope name used for meters, tracers and OTLP payloads
+pub const SCOPE_NAME: &str = "gloo_solana";
+
+/// Default number of requests [`BufferedTelemetry`] keeps before dropping
+pub const DEFAULT_BUFFER_CAPACITY: usize = 1_000;
+
+impl ErrorKind {
+    /// Value of the `error.type` attribute
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Http => "http",
+            Self::Rpc => "rpc",
+            Self::Parse => "parse",
+            Self::Request => "request",
+            Self::Other => "other",
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use otel::OtelMetricsSink;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod otel {
+    use super::SCOPE_NAME;
+    use crate::infrastructure::metrics::{MetricsSink, RequestEvent};
+    use opentelemetry::metrics::{Counter, Histogram, Meter};
+    use opentelemetry::trace::{Span, SpanKind, Status, Tracer};
+    use opentelemetry::{global, KeyValue};
+    use std::time::SystemTime;
+
+    /// Metrics sink recording requests through the `opentelemetry` API
+    ///
+    /// Emits `rpc.client.requests`, `rpc.client.errors`,
+    /// `rpc.client.duration` (seconds) and one client span per request,
+    /// back-dated by its latency.
+    pub struct OtelMetricsSink<T: Tracer = global::BoxedTracer> {
+        requests: Counter<u64>,
+        errors: Counter<u64>,
+        duration: Histogram<f64>,
+        tracer: Option<T>,
+    }
+
+    impl OtelMetricsSink {
+        /// Record through the globally installed meter and tracer providers
+        pub fn global() -> Self {
+            Self::new(&global::meter(SCOPE_NAME)).with_tracer(global::tracer(SCOPE_NAME))
+        }
+
+        /// Record metrics with `meter` and no spans
+        pub fn new(meter: &Meter) -> Self {
+            Self {
+                requests: meter
+                    .u64_counter("rpc.client.requests")
+                    .with_description("JSON-RPC requests sent")
+                    .build(),
+                errors: meter
+                    .u64_counter("rpc.client.errors")
+                    .with_description("JSON-RPC requests that failed")
+                    .build(),
+                duration: meter
+                    .f64_histogram("rpc.client.duration")
+                    .with_description("JSON-RPC request latency")
+                    .with_unit("s")
+                    .build(),
+                tracer: None,
+            }
+        }
+    }
+
+    impl<T: Tracer> OtelMetricsSink<T> {
+        /// Also start a span for every request
+        pub fn with_tracer<U: Tracer>(self, tracer: U) -> OtelMetricsSink<U> {
+            OtelMetricsSink {
+                requests: self.requests,
+                errors: self.errors,
+                duration: self.duration,
+                tracer: Some(tracer),
+            }
+        }
+    }
+
+    impl<T> MetricsSink for OtelMetricsSink<T>
+    where
+        T: Tracer + Send + Sync,
+    {
+        fn record(&self, event: &RequestEvent<'_>) {
+            let mut attributes = vec![
+                KeyValue::new("rpc.system", "jsonrpc"),
+                KeyValue::new("rpc.method", event.method.to_string()),
+                KeyValue::new("cached", event.cached),
+            ];
+            if let Some(kind) = event.error {
+                attributes.push(KeyValue::new("error.type", kind.as_str()));
+                self.errors.add(1, &attributes);
+            }
+            self.requests.add(1, &attributes);
+            self.duration
+                .record(event.latency.as_secs_f64(), &attributes);
+
+            if let Some(tracer) = &self.tracer {
+                let end = SystemTime::now();
+                let mut span = tracer
+                    .span_builder(event.method.to_string())
+                    .with_kind(SpanKind::Client)
+                    .with_start_time(end - event.latency)
+                    .with_attributes(attributes)
+                    .start(tracer);
+                if let Some(kind) = event.error {
+                    span.set_status(Status::error(kind.as_str()));
+                }
+                span.end_with_timestamp(end);
+            }
+        }
+    }
+}
+
+/// A completed request held by [`BufferedTelemetry`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TelemetryRecord {
+    /// Span id, unique within the buffer's trace
+    pub span_id: u64,
+    pub method: String,
+    /// When the request started, in nanoseconds since the unix epoch
+    pub start_unix_nanos: u64,
+    pub latency: Duration,
+    pub cached: bool,
+    pub error: Option<ErrorKind>,
+}
+
+impl TelemetryRecord {
+    fn end_unix_nanos(&self) -> u64 {
+        self.start_unix_nanos + self.latency.as_nanos() as u64
+    }
+}
+
+#[derive(Debug, Default)]
+struct Buffer {
+    records: VecDeque<TelemetryRecord>,
+    dropped: u64,
+    next_span_id: u64,
+}
+
+/// In-memory telemetry buffer flushed as OTLP/HTTP JSON, for wasm apps
+///
+/// Once `capacity` requests are waiting the oldest are dropped and counted
+/// in [`dropped`](Self::dropped). Clones share their buffer, so keep one to
+/// flush and hand another to the client.
+#[derive(Debug, Clone)]
+pub struct BufferedTelemetry {
+    endpoint: String,
+    service_name: String,
+    capacity: usize,
+    trace_id: String,
+    buffer: Arc<Mutex<Buffer>>,
+}
+
+impl BufferedTelemetry {
+    /// Buffer requests for the OTLP/HTTP collector at `endpoint`
+    ///
+    /// `endpoint` is the collector's base URL; traces are posted to
+    /// `{endpoint}/v1/traces`.
+    pub fn new(endpoint: impl Into<String>) -> Self {
+        let mut trace_id = [0u8; 16];
+        // An all-zero trace id is invalid, fall back to the clock
+        if getrandom::getrandom(&mut trace_id).is_err() {
+            trace_id[..8].copy_from_slice(&now_unix_nanos().to_be_bytes());
+        }
+        Self {
+            endpoint: endpoint.into().trim_end_matches('/').to_string(),
+            service_name: SCOPE_NAME.to_string(),
+            capacity: DEFAULT_BUFFER_CAPACITY,
+            trace_id: hex(&trace_id),
+            buffer: Arc::new(Mutex::new(Buffer::default())),
+        }
+    }
+
+    /// Report spans under `service.name`
+    pub fn service_name(mut self, name: impl Into<String>) -> Self {
+        self.service_name = name.into();
+        self
+    }
+
+    /// Keep at most `capacity` requests between flushes
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// Number of buffered requests
+    pub fn len(&self) -> usize {
+        self.lock().records.len()
+    }
+
+    /// Whether nothing is buffered
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Requests dropped because the buffer was full
+    pub fn dropped(&self) -> u64 {
+        self.lock().dropped
+    }
+
+    /// Copy of the buffered requests, oldest first
+    pub fn records(&self) -> Vec<TelemetryRecord> {
+        self.lock().records.iter().cloned().collect()
+    }
+
+    /// Take every buffered request
+    pub fn drain(&self) -> Vec<TelemetryRecord> {
+        self.lock().records.drain(..).collect()
+    }
+
+    /// The buffer, even if a thread panicked while holding it
+    fn lock(&self) -> std::sync::MutexGuard<'_, Buffer> {
+        self.buffer.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Encode `records` as an OTLP `ExportTraceServiceRequest` in JSON
+    pub fn to_otlp_json(&self, records: &[TelemetryRecord]) -> Value {
+        let spans: Vec<Value> = records
+            .iter()
+            .map(|record| self.span_json(record))
+            .collect();
+        json!({
+            "resourceSpans": [{
+                "resource": {
+                    "attributes": [attribute("service.name", json!({ "stringValue": self.service_name }))]
+                },
+                "scopeSpans": [{
+                    "scope": { "name": SCOPE_NAME, "version": env!("CARGO_PKG_VERSION") },
+                    "spans": spans
+                }]
+            }]
+        })
+    }
+
+    /// Post every buffered request to the collector and return how many were sent
+    ///
+    /// Records are put back at the front of the buffer if the post fails.
+    pub async fn flush(&self, http: &impl HttpClient) -> Result<usize, HttpError> {
+        let records = self.drain();
+        if records.is_empty() {
+            return Ok(0);
+        }
+        let body = self.to_otlp_json(&records);
+        let url = format!("{}/v1/traces", self.endpoint);
+        match http.post_json_text(&url, &body).await {
+            Ok(_) => Ok(records.len()),
+            Err(e) => {
+                let mut buffer = self.lock();
+                for record in records.into_iter().rev() {
+                    buffer.records.push_front(record);
+                }
+                while buffer.records.len() > self.capacity {
+                    buffer.records.pop_front();
+                    buffer.dropped += 1;
+                }
+                Err(e)
+            }
+        }
+    }
+
+    fn span_json(&self, record: &TelemetryRecord) -> Value {
+        let mut attributes = vec![
+            attribute("rpc.system", json!({ "stringValue": "jsonrpc" })),
+            attribute("rpc.method", json!({ "stringValue": record.method })),
+            attribute("cached", json!({ "boolValue": record.cached })),
+        ];
+        let status = match record.error {
+            Some(kind) => {
+                attributes.push(attribute(
+                    "error.type",
+                    json!({ "stringValue": kind.as_str() }),
+                ));
+                json!({ "code": 2, "message": kind.as_str() })
+            }
+            None => json!({ "code": 0 }),
+        };
+        json!({
+            "traceId": self.trace_id,
+            "spanId": hex(&record.span_id.to_be_bytes()),
+            "name": record.method,
+            // SPAN_KIND_CLIENT
+            "kind": 3,
+            "startTimeUnixNano": record.start_unix_nanos.to_string(),
+            "endTimeUnixNano": record.end_unix_nanos().to_string(),
+            "attributes": attributes,
+            "status": status
+        })
+    }
+}
+
+impl MetricsSink for BufferedTelemetry {
+    fn record(&self, event: &RequestEvent<'_>) {
+        let mut buffer = self.lock();
+        // Span ids start at one, zero is invalid
+        buffer.next_span_id += 1;
+        let record = TelemetryRecord {
+            span_id: buffer.next_span_id,
+            method: event.method.to_string(),
+            start_unix_nanos: now_unix_nanos().saturating_sub(event.latency.as_nanos() as u64),
+            latency: event.latency,
+            cached: event.cached,
+            error: event.error,
+        };
+        if buffer.records.len() >= self.capacity {
+            buffer.records.pop_front();
+            buffer.dropped += 1;
+        }
+        buffer.records.push_back(record);
+    }
+}
+
+fn attribute(key: &str, value: Value) -> Value {
+    json!({ "key": key, "value": value })
+}
+
+fn now_unix_nanos() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos() as u64)
+        .unwrap_or_default()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::http::fixtures::{Fixture, FixtureMethod};
+    use crate::infrastructure::http::ReplayHttpClient;
+
+    fn event(method: &str, error: Option<ErrorKind>) -> RequestEvent<'_> {
+        RequestEvent {
+            method,
+            latency: Duration::from_millis(30),
+            cached: false,
+            error,
+        }
+    }
+
+    #[test]
+    fn test_buffer_encodes_otlp_spans() {
+        let telemetry = BufferedTelemetry::new("http://collector:4318/").capacity(2);
+        telemetry.record(&event("getSlot", None));
+        telemetry.record(&event("getBalance", Some(ErrorKind::Rpc)));
+        telemetry.record(&event("getAccountInfo", None));
+        assert_eq!((telemetry.len(), telemetry.dropped()), (2, 1));
+
+        let records = telemetry.drain();
+        assert!(telemetry.is_empty());
+        assert_eq!(
+            records[0].end_unix_nanos() - records[0].start_unix_nanos,
+            30_000_000
+        );
+        let body = telemetry.to_otlp_json(&records);
+        let spans = &body["resourceSpans"][0]["scopeSpans"][0]["spans"];
+        assert_eq!(spans[0]["name"], "getBalance");
+        assert_eq!(spans[0]["status"]["code"], 2);
+        assert_eq!(spans[0]["traceId"].as_str().unwrap().len(), 32);
+        assert_ne!(spans[0]["spanId"], spans[1]["spanId"]);
+        assert_eq!(
+            spans[1]["attributes"][1]["value"]["stringValue"],
+            "getAccountInfo"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_flush_keeps_records_on_failure() {
+        let telemetry = BufferedTelemetry::new("http://collector:4318");
+        telemetry.record(&event("getSlot", None));
+
+        let offline = ReplayHttpClient::new(vec![]);
+        assert!(telemetry.flush(&offline).await.is_err());
+        assert_eq!(telemetry.len(), 1);
+
+        let collector = ReplayHttpClient::new(vec![Fixture {
+            method: FixtureMethod::Post,
+            url: "http://collector:4318/v1/traces".to_string(),
+            request: Some(telemetry.to_otlp_json(&telemetry.records())),
+            response: json!({}),
+        }]);
+        assert_eq!(telemetry.flush(&collector).await.unwrap(), 1);
+        assert!(telemetry.is_empty());
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_otel_sink_records_through_noop_providers() {
+        let sink = OtelMetricsSink::global();
+        sink.record(&event("getSlot", Some(ErrorKind::Http)));
+        sink.record(&event("getSlot", None));
+    }
+}
@@ -0,0 +1,166 @@
+//! Time utilities for WASM and native environments
+//!
+//! `std::time::Instant` and `SystemTime::now` panic on `wasm32-unknown-unknown`,
+//! so the library goes through these helpers whenever it needs a clock or a delay.
+
+use std::time::Duration;
+
+pub use web_time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Nominal slot time the cluster targets
+pub const SLOT_DURATION: Duration = Duration::from_millis(400);
+
+/// Seconds at which [`format_relative_to`] switches to a date
+const RELATIVE_LIMIT: u64 = 30 * 86_400;
+
+/// Sleep for the given duration without blocking the executor
+#[cfg(target_arch = "wasm32")]
+pub async fn sleep(duration: Duration) {
+    gloo_timers::future::sleep(duration).await;
+}
+
+/// Sleep for the given duration without blocking the executor
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn sleep(duration: Duration) {
+    tokio::time::sleep(duration).await;
+}
+
+/// Current unix timestamp in seconds
+pub fn unix_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+/// Format a unix timestamp as `YYYY-MM-DD HH:MM:SS UTC`
+pub fn format_utc(timestamp: i64) -> String {
+    let days = timestamp.div_euclid(86_400);
+    let seconds = timestamp.rem_euclid(86_400);
+
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        seconds / 3_600,
+        seconds % 3_600 / 60,
+        seconds % 60
+    )
+}
+
+/// Approximate wall-clock time of `slots` slots at the nominal slot time
+///
+/// Real slots run somewhat slower; use the clock service's measured
+/// `slot_duration` where accuracy matters.
+pub fn slots_to_approx_duration(slots: u64) -> Duration {
+    SLOT_DURATION.saturating_mul(slots.min(u32::MAX as u64) as u32)
+}
+
+/// Format a duration by its two largest units, e.g. `2h 5m` or `40s`
+pub fn format_duration(duration: Duration) -> String {
+    const UNITS: [(u64, &str); 4] = [(86_400, "d"), (3_600, "h"), (60, "m"), (1, "s")];
+
+    let mut seconds = duration.as_secs();
+    let parts: Vec<String> = UNITS
+        .iter()
+        .filter_map(|&(size, unit)| {
+            let count = seconds / size;
+            seconds %= size;
+            (count > 0).then(|| format!("{}{}", count, unit))
+        })
+        .take(2)
+        .collect();
+    if parts.is_empty() {
+        "0s".to_string()
+    } else {
+        parts.join(" ")
+    }
+}
+
+/// Describe a unix timestamp relative to now, e.g. `5 minutes ago`
+pub fn format_timestamp_relative(timestamp: i64) -> String {
+    format_relative_to(timestamp, unix_timestamp() as i64)
+}
+
+/// Describe `timestamp` relative to `now`, e.g. `in 2 hours`
+///
+/// Times more than 30 days away are shown as a UTC date instead.
+pub fn format_relative_to(timestamp: i64, now: i64) -> String {
+    let distance = timestamp.abs_diff(now);
+    if distance < 5 {
+        return "just now".to_string();
+    }
+    if distance > RELATIVE_LIMIT {
+        return format_utc(timestamp);
+    }
+
+    let (count, unit) = match distance {
+        0..=59 => (distance, "second"),
+        60..=3_599 => (distance / 60, "minute"),
+        3_600..=86_399 => (distance / 3_600, "hour"),
+        _ => (distance / 86_400, "day"),
+    };
+    let plural = if count == 1 { "" } else { "s" };
+    if timestamp < now {
+        format!("{} {}{} ago", count, unit, plural)
+    } else {
+        format!("in {} {}{}", count, unit, plural)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unix_timestamp() {
+        assert!(unix_timestamp() > 1_600_000_000);
+    }
+
+    #[test]
+    fn test_format_utc() {
+        assert_eq!(format_utc(0), "1970-01-01 00:00:00 UTC");
+        assert_eq!(format_utc(1_700_000_000), "2023-11-14 22:13:20 UTC");
+        assert_eq!(format_utc(951_782_400), "2000-02-29 00:00:00 UTC");
+        assert_eq!(format_utc(-1), "1969-12-31 23:59:59 UTC");
+    }
+
+    #[test]
+    fn test_durations_and_relative_times() {
+        assert_eq!(slots_to_approx_duration(150), Duration::from_secs(60));
+        assert_eq!(format_duration(Duration::from_secs(0)), "0s");
+        assert_eq!(format_duration(Duration::from_secs(7_530)), "2h 5m");
+        assert_eq!(format_duration(Duration::from_secs(90_061)), "1d 1h");
+
+        let now = 1_700_000_000;
+        assert_eq!(format_relative_to(now - 2, now), "just now");
+        assert_eq!(format_relative_to(now - 45, now), "45 seconds ago");
+        assert_eq!(format_relative_to(now - 60, now), "1 minute ago");
+        assert_eq!(format_relative_to(now + 7_200, now), "in 2 hours");
+        assert_eq!(format_relative_to(0, now), "1970-01-01 00:00:00 UTC");
+    }
+
+    #[tokio::test]
+    async fn test_sleep() {
+        let start = Instant::now();
+        sleep(Duration::from_millis(5)).await;
+        assert!(start.elapsed() >= Duration::from_millis(5));
+    }
+}
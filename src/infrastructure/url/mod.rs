@@ -0,0 +1,60 @@
+//! Percent-encoding for URL query values
+//!
+//! Explorer links, Solana Pay URLs and wallet deep links all carry values in
+//! query strings. Everything but the RFC 3986 unreserved characters is
+//! escaped, which is safe in any component.
+
+use std::fmt::Write;
+
+/// Escape every byte of `value` that is not an unreserved character
+pub fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b'~') {
+            encoded.push(byte as char);
+        } else {
+            let _ = write!(encoded, "%{:02X}", byte);
+        }
+    }
+    encoded
+}
+
+/// Undo [`percent_encode`], also reading `+` as a space as forms write it
+///
+/// `None` for a malformed escape or bytes that are not UTF-8.
+pub fn percent_decode(value: &str) -> Option<String> {
+    let mut decoded = Vec::with_capacity(value.len());
+    let mut bytes = value.bytes();
+    while let Some(byte) = bytes.next() {
+        match byte {
+            b'%' => {
+                let hex = [bytes.next()?, bytes.next()?];
+                let hex = std::str::from_utf8(&hex).ok()?;
+                decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            }
+            b'+' => decoded.push(b' '),
+            byte => decoded.push(byte),
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percent_round_trip() {
+        let value = "https://rpc.example.com/?key=a b&c=ü~";
+        let encoded = percent_encode(value);
+        assert_eq!(
+            encoded,
+            "https%3A%2F%2Frpc.example.com%2F%3Fkey%3Da%20b%26c%3D%C3%BC~"
+        );
+        assert_eq!(percent_decode(&encoded).as_deref(), Some(value));
+        assert_eq!(percent_decode("a+b").as_deref(), Some("a b"));
+        assert_eq!(percent_decode("%4"), None);
+        assert_eq!(percent_decode("%zz"), None);
+        assert_eq!(percent_decode("%FF"), None);
+    }
+}
@@ -0,0 +1,84 @@
+//! Page visibility
+//!
+//! Polling loops in a browser keep spending RPC quota while their tab sits in
+//! the background. [`sleep_visible`] waits out a refresh interval and then,
+//! if the page is hidden, keeps waiting until it is shown again, so the next
+//! refresh runs as soon as the user comes back. It reads the Page Visibility
+//! API with the `wasm` feature on wasm32; everywhere else the page always
+//! counts as visible and it is a plain sleep.
+
+use crate::infrastructure::time::sleep;
+use std::time::Duration;
+
+/// Whether the page is currently shown
+pub fn is_page_visible() -> bool {
+    imp::is_page_visible()
+}
+
+/// Return once the page is visible, immediately if it already is
+pub async fn wait_until_visible() {
+    imp::wait_until_visible().await
+}
+
+/// Sleep for `interval`, then until the page is visible
+pub async fn sleep_visible(interval: Duration) {
+    sleep(interval).await;
+    wait_until_visible().await;
+}
+
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+mod imp {
+    use wasm_bindgen_futures::JsFuture;
+    use web_sys::{AddEventListenerOptions, Document, VisibilityState};
+
+    const VISIBILITY_CHANGE_EVENT: &str = "visibilitychange";
+
+    fn document() -> Option<Document> {
+        web_sys::window()?.document()
+    }
+
+    pub fn is_page_visible() -> bool {
+        document().is_none_or(|document| document.visibility_state() != VisibilityState::Hidden)
+    }
+
+    pub async fn wait_until_visible() {
+        let Some(document) = document() else {
+            return;
+        };
+        while document.visibility_state() == VisibilityState::Hidden {
+            let changed = js_sys::Promise::new(&mut |resolve, _reject| {
+                let options = AddEventListenerOptions::new();
+                options.set_once(true);
+                let _ = document.add_event_listener_with_callback_and_add_event_listener_options(
+                    VISIBILITY_CHANGE_EVENT,
+                    &resolve,
+                    &options,
+                );
+            });
+            let _ = JsFuture::from(changed).await;
+        }
+    }
+}
+
+#[cfg(not(all(target_arch = "wasm32", feature = "wasm")))]
+mod imp {
+    pub fn is_page_visible() -> bool {
+        true
+    }
+
+    pub async fn wait_until_visible() {}
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::infrastructure::time::Instant;
+
+    #[tokio::test]
+    async fn test_native_page_is_always_visible() {
+        assert!(is_page_visible());
+        let start = Instant::now();
+        sleep_visible(Duration::from_millis(5)).await;
+        assert!(start.elapsed() >= Duration::from_millis(5));
+    }
+}
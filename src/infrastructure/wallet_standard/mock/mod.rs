@@ -0,0 +1,481 @@
+//! Scriptable wallet for end-to-end tests and demos
+//!
+//! [`MockWallet`] signs with an in-memory [`Keypair`] and answers each request
+//! as its [`MockWalletConfig`] says instead of showing a prompt, so sign-and-send
+//! flows run headless in CI. In the browser, `register` announces it through
+//! the Wallet Standard events like an extension would, and the app's
+//! discovery, `WalletPicker` and wallet connection pick it up unchanged. The
+//! config is plain serde, so a demo page can read it from a query string.
+
+use super::{check_message, features, WalletInfo, WalletStandardError};
+use crate::domain::message::Transaction;
+use crate::domain::types::{Pubkey, Signature};
+use crate::infrastructure::signer::{Keypair, Signer};
+use serde::{Deserialize, Serialize};
+use std::sync::{Mutex, MutexGuard};
+
+/// How the mock wallet answers one kind of request
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Approval {
+    #[default]
+    Approve,
+    Reject,
+}
+
+/// What a [`MockWallet`] announces and how it answers requests
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct MockWalletConfig {
+    pub name: String,
+    pub chains: Vec<String>,
+    pub connect: Approval,
+    pub sign_message: Approval,
+    pub sign_transaction: Approval,
+    /// Whether a silent connect succeeds before any prompted one, as for a
+    /// site the user approved in an earlier session
+    pub trusted: bool,
+}
+
+impl Default for MockWalletConfig {
+    fn default() -> Self {
+        Self {
+            name: "Mock Wallet".to_string(),
+            chains: ["mainnet", "devnet", "testnet", "localnet"]
+                .iter()
+                .map(|cluster| format!("solana:{}", cluster))
+                .collect(),
+            connect: Approval::Approve,
+            sign_message: Approval::Approve,
+            sign_transaction: Approval::Approve,
+            trusted: false,
+        }
+    }
+}
+
+impl MockWalletConfig {
+    /// A wallet approving every request
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the name the wallet registers under
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Set how connect requests are answered
+    pub fn connect(mut self, approval: Approval) -> Self {
+        self.connect = approval;
+        self
+    }
+
+    /// Set how message signing requests are answered
+    pub fn sign_message(mut self, approval: Approval) -> Self {
+        self.sign_message = approval;
+        self
+    }
+
+    /// Set how transaction signing requests are answered
+    pub fn sign_transaction(mut self, approval: Approval) -> Self {
+        self.sign_transaction = approval;
+        self
+    }
+
+    /// Set whether silent connects succeed from the start
+    pub fn trusted(mut self, trusted: bool) -> Self {
+        self.trusted = trusted;
+        self
+    }
+}
+
+/// A request the mock wallet received, for assertions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MockRequest {
+    Connect { silent: bool },
+    Disconnect,
+    SignMessage(Vec<u8>),
+    SignTransaction(Transaction),
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    connected: bool,
+    /// A prompted connect was approved, so silent ones succeed from now on
+    approved: bool,
+    requests: Vec<MockRequest>,
+}
+
+/// A wallet that signs with an in-memory keypair, approving or rejecting
+/// requests as configured
+#[derive(Debug)]
+pub struct MockWallet {
+    keypair: Keypair,
+    config: MockWalletConfig,
+    state: Mutex<MockState>,
+}
+
+impl MockWallet {
+    /// Create a wallet holding `keypair`
+    pub fn new(keypair: Keypair, config: MockWalletConfig) -> Self {
+        Self {
+            keypair,
+            config,
+            state: Mutex::new(MockState::default()),
+        }
+    }
+
+    /// The wallet's only account
+    pub fn pubkey(&self) -> Pubkey {
+        self.keypair.pubkey()
+    }
+
+    /// What the wallet announces when it registers
+    pub fn info(&self) -> WalletInfo {
+        WalletInfo {
+            name: self.config.name.clone(),
+            icon: MOCK_WALLET_ICON.to_string(),
+            version: "1.0.0".to_string(),
+            chains: self.config.chains.clone(),
+            features: [
+                features::CONNECT,
+                features::DISCONNECT,
+                features::SIGN_MESSAGE,
+                features::SIGN_TRANSACTION,
+            ]
+            .iter()
+            .map(|feature| feature.to_string())
+            .collect(),
+        }
+    }
+
+    /// Whether the app is connected
+    pub fn is_connected(&self) -> bool {
+        self.lock().connected
+    }
+
+    /// Requests received so far, oldest first
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.lock().requests.clone()
+    }
+
+    /// Answer `standard:connect`, returning the account
+    pub fn connect(&self, silent: bool) -> Result<Vec<Pubkey>, WalletStandardError> {
+        let mut state = self.lock();
+        state.requests.push(MockRequest::Connect { silent });
+        let approved = if silent {
+            self.config.trusted || state.approved
+        } else {
+            self.config.connect == Approval::Approve
+        };
+        if !approved {
+            return Err(WalletStandardError::Rejected);
+        }
+        state.connected = true;
+        state.approved = true;
+        Ok(vec![self.pubkey()])
+    }
+
+    /// Answer `standard:disconnect`
+    pub fn disconnect(&self) {
+        let mut state = self.lock();
+        state.requests.push(MockRequest::Disconnect);
+        state.connected = false;
+    }
+
+    /// Answer `solana:signMessage`, refusing bytes that decode as a transaction
+    pub fn sign_message(&self, message: &[u8]) -> Result<Signature, WalletStandardError> {
+        self.approve(
+            MockRequest::SignMessage(message.to_vec()),
+            self.config.sign_message,
+        )?;
+        check_message(message)?;
+        Ok(self.keypair.sign_bytes(message))
+    }
+
+    /// Answer `solana:signTransaction`, returning the signed transaction's bytes
+    pub fn sign_transaction(&self, transaction: &[u8]) -> Result<Vec<u8>, WalletStandardError> {
+        let mut transaction = Transaction::deserialize(transaction)?;
+        self.approve(
+            MockRequest::SignTransaction(transaction.clone()),
+            self.config.sign_transaction,
+        )?;
+        let signature = self.keypair.sign_bytes(&transaction.message.serialize()?);
+        transaction.add_signature(&self.pubkey(), signature)?;
+        Ok(transaction.serialize()?)
+    }
+
+    /// Record `request` and check it may be answered
+    fn approve(&self, request: MockRequest, approval: Approval) -> Result<(), WalletStandardError> {
+        let mut state = self.lock();
+        state.requests.push(request);
+        if !state.connected {
+            return Err(WalletStandardError::NotConnected);
+        }
+        match approval {
+            Approval::Approve => Ok(()),
+            Approval::Reject => Err(WalletStandardError::Rejected),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// A plain square, so pickers have an image to show
+const MOCK_WALLET_ICON: &str = "data:image/svg+xml;base64,PHN2ZyB4bWxucz0iaHR0cDovL3d3dy53My5vcmcvMjAwMC9zdmciIHZpZXdCb3g9IjAgMCAxIDEiPjxyZWN0IHdpZHRoPSIxIiBoZWlnaHQ9IjEiIGZpbGw9IiM5OTQ1ZmYiLz48L3N2Zz4=";
+
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+pub use browser::register;
+
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+mod browser {
+    use super::MockWallet;
+    use crate::infrastructure::wallet_standard::{
+        features, WalletStandardError, APP_READY_EVENT, REGISTER_WALLET_EVENT,
+    };
+    use js_sys::{Array, Function, Object, Promise, Reflect, Uint8Array};
+    use std::rc::Rc;
+    use wasm_bindgen::prelude::*;
+    use wasm_bindgen::JsCast;
+
+    type Method = Closure<dyn FnMut(JsValue) -> JsValue>;
+
+    /// Announce `wallet` to the page through the Wallet Standard events
+    ///
+    /// Apps already listening receive it at once; apps that start later
+    /// collect it through `wallet-standard:app-ready`. The wallet stays
+    /// registered for the lifetime of the page.
+    pub fn register(wallet: Rc<MockWallet>) -> Result<(), WalletStandardError> {
+        let window = web_sys::window().ok_or(WalletStandardError::NoWindow)?;
+        let object = wallet_object(wallet)?;
+
+        let register = Closure::<dyn FnMut(JsValue)>::new(move |api: JsValue| {
+            if let Ok(register) = Reflect::get(&api, &"register".into()) {
+                if let Ok(register) = register.dyn_into::<Function>() {
+                    let _ = register.call1(&api, &object);
+                }
+            }
+        });
+        let on_ready = {
+            let register: Function = register.as_ref().clone().unchecked_into();
+            Closure::<dyn FnMut(web_sys::Event)>::new(move |event: web_sys::Event| {
+                if let Ok(event) = event.dyn_into::<web_sys::CustomEvent>() {
+                    let _ = register.call1(&JsValue::UNDEFINED, &event.detail());
+                }
+            })
+        };
+        window
+            .add_event_listener_with_callback(APP_READY_EVENT, on_ready.as_ref().unchecked_ref())
+            .map_err(js_error)?;
+
+        let init = web_sys::CustomEventInit::new();
+        init.set_detail(register.as_ref());
+        let event = web_sys::CustomEvent::new_with_event_init_dict(REGISTER_WALLET_EVENT, &init)
+            .map_err(js_error)?;
+        window.dispatch_event(&event).map_err(js_error)?;
+
+        register.forget();
+        on_ready.forget();
+        Ok(())
+    }
+
+    /// The Wallet Standard object answering through `wallet`
+    fn wallet_object(wallet: Rc<MockWallet>) -> Result<JsValue, WalletStandardError> {
+        let info = wallet.info();
+        let object: JsValue = Object::new().into();
+        set(&object, "version", &info.version.into())?;
+        set(&object, "name", &info.name.into())?;
+        set(&object, "icon", &info.icon.into())?;
+        let chains: Array = info
+            .chains
+            .iter()
+            .map(|chain| JsValue::from_str(chain))
+            .collect();
+        set(&object, "chains", &chains)?;
+        set(&object, "accounts", &Array::new())?;
+
+        let connect = {
+            let (wallet, object) = (wallet.clone(), object.clone());
+            method(move |input| {
+                let silent = Reflect::get(&input, &"silent".into())
+                    .ok()
+                    .and_then(|silent| silent.as_bool())
+                    .unwrap_or(false);
+                wallet.connect(silent)?;
+                let accounts = Array::of1(&account(&wallet)?);
+                set(&object, "accounts", &accounts)?;
+                let output: JsValue = Object::new().into();
+                set(&output, "accounts", &accounts)?;
+                Ok(output)
+            })
+        };
+        let disconnect = {
+            let (wallet, object) = (wallet.clone(), object.clone());
+            method(move |_| {
+                wallet.disconnect();
+                set(&object, "accounts", &Array::new())?;
+                Ok(JsValue::UNDEFINED)
+            })
+        };
+        let sign_message = {
+            let wallet = wallet.clone();
+            method(move |input| {
+                let message = bytes(&input, "message")?;
+                let signature = wallet.sign_message(&message)?;
+                let output: JsValue = Object::new().into();
+                set(&output, "signedMessage", &Uint8Array::from(&message[..]))?;
+                set(
+                    &output,
+                    "signature",
+                    &Uint8Array::from(&signature.as_bytes()[..]),
+                )?;
+                Ok(Array::of1(&output).into())
+            })
+        };
+        let sign_transaction = method(move |input| {
+            let signed = wallet.sign_transaction(&bytes(&input, "transaction")?)?;
+            let output: JsValue = Object::new().into();
+            set(&output, "signedTransaction", &Uint8Array::from(&signed[..]))?;
+            Ok(Array::of1(&output).into())
+        });
+
+        let implementations: JsValue = Object::new().into();
+        for (feature, name, method) in [
+            (features::CONNECT, "connect", connect),
+            (features::DISCONNECT, "disconnect", disconnect),
+            (features::SIGN_MESSAGE, "signMessage", sign_message),
+            (
+                features::SIGN_TRANSACTION,
+                "signTransaction",
+                sign_transaction,
+            ),
+        ] {
+            let implementation: JsValue = Object::new().into();
+            set(&implementation, "version", &"1.0.0".into())?;
+            set(&implementation, name, method.as_ref())?;
+            if feature == features::SIGN_TRANSACTION {
+                set(
+                    &implementation,
+                    "supportedTransactionVersions",
+                    &Array::of1(&"legacy".into()),
+                )?;
+            }
+            set(&implementations, feature, &implementation)?;
+            method.forget();
+        }
+        set(&object, "features", &implementations)?;
+        Ok(object)
+    }
+
+    /// Wrap `answer` as a feature method returning a promise, rejected like a
+    /// wallet rejects when the user declines
+    fn method(
+        answer: impl Fn(JsValue) -> Result<JsValue, WalletStandardError> + 'static,
+    ) -> Method {
+        Closure::new(move |input: JsValue| {
+            let promise = match answer(input) {
+                Ok(output) => Promise::resolve(&output),
+                Err(e) => Promise::reject(&js_sys::Error::new(&e.to_string())),
+            };
+            promise.into()
+        })
+    }
+
+    /// The `WalletAccount` object of the wallet's keypair
+    fn account(wallet: &MockWallet) -> Result<JsValue, WalletStandardError> {
+        let info = wallet.info();
+        let account: JsValue = Object::new().into();
+        let pubkey = wallet.pubkey();
+        set(&account, "address", &pubkey.to_base58().into())?;
+        set(
+            &account,
+            "publicKey",
+            &Uint8Array::from(&pubkey.as_bytes()[..]),
+        )?;
+        let chains: Array = info
+            .chains
+            .iter()
+            .map(|chain| JsValue::from_str(chain))
+            .collect();
+        set(&account, "chains", &chains)?;
+        let features: Array = info.features.iter().map(|f| JsValue::from_str(f)).collect();
+        set(&account, "features", &features)?;
+        Ok(account)
+    }
+
+    fn bytes(input: &JsValue, field: &str) -> Result<Vec<u8>, WalletStandardError> {
+        let value = Reflect::get(input, &field.into()).map_err(js_error)?;
+        value
+            .dyn_into::<Uint8Array>()
+            .map(|bytes| bytes.to_vec())
+            .map_err(|_| WalletStandardError::Js(format!("{} is not a Uint8Array", field)))
+    }
+
+    fn set(target: &JsValue, key: &str, value: &JsValue) -> Result<(), WalletStandardError> {
+        Reflect::set(target, &key.into(), value)
+            .map(drop)
+            .map_err(js_error)
+    }
+
+    fn js_error(error: JsValue) -> WalletStandardError {
+        WalletStandardError::Js(format!("{:?}", error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::flows::TransactionBuilder;
+    use crate::domain::programs::system;
+    use crate::domain::types::Hash;
+
+    #[test]
+    fn test_mock_wallet_follows_its_config() {
+        let keypair = Keypair::from_seed(&[4; 32]);
+        let transaction = TransactionBuilder::new(keypair.pubkey())
+            .instruction(system::transfer(keypair.pubkey(), Pubkey::new([5; 32]), 1))
+            .build(Hash::new([6; 32]))
+            .unwrap();
+        let bytes = transaction.serialize().unwrap();
+
+        let wallet = MockWallet::new(keypair.clone(), MockWalletConfig::new());
+        assert!(matches!(
+            wallet.sign_transaction(&bytes),
+            Err(WalletStandardError::NotConnected)
+        ));
+        assert!(matches!(
+            wallet.connect(true),
+            Err(WalletStandardError::Rejected)
+        ));
+        assert_eq!(wallet.connect(false).unwrap(), vec![keypair.pubkey()]);
+        assert!(wallet.connect(true).is_ok());
+
+        let signed = Transaction::deserialize(&wallet.sign_transaction(&bytes).unwrap()).unwrap();
+        let expected = keypair.sign_bytes(&transaction.message.serialize().unwrap());
+        assert_eq!(signed.signatures, vec![expected]);
+        assert!(wallet.sign_message(b"Sign in").is_ok());
+        assert!(matches!(
+            wallet.sign_message(&bytes),
+            Err(WalletStandardError::MessageIsTransaction)
+        ));
+
+        let config: MockWalletConfig =
+            serde_json::from_str(r#"{ "signTransaction": "reject", "trusted": true }"#).unwrap();
+        let rejecting = MockWallet::new(keypair, config);
+        assert!(rejecting.connect(true).is_ok());
+        assert!(matches!(
+            rejecting.sign_transaction(&bytes),
+            Err(WalletStandardError::Rejected)
+        ));
+        assert_eq!(
+            rejecting.requests(),
+            vec![
+                MockRequest::Connect { silent: true },
+                MockRequest::SignTransaction(transaction)
+            ]
+        );
+    }
+}
@@ -0,0 +1,532 @@
+//! Wallet Standard discovery
+//!
+//! Browser wallets implementing the Wallet Standard announce themselves with
+//! `wallet-standard:register-wallet` events rather than a `window.<name>`
+//! global, so every injected wallet can be listed, not just Phantom.
+//! [`WalletRegistry`] keeps what they announce, and on wasm `discover` wires
+//! it to the events and dispatches `wallet-standard:app-ready` so wallets
+//! injected before the app started register as well.
+
+use crate::domain::message::{Message, MessageError, Transaction};
+use crate::domain::types::Pubkey;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+pub mod mock;
+pub mod session;
+
+pub use mock::{Approval, MockRequest, MockWallet, MockWalletConfig};
+pub use session::{WalletConnectionState, WalletSession, WalletSessionStore};
+
+/// Event wallets dispatch to register with the app
+pub const REGISTER_WALLET_EVENT: &str = "wallet-standard:register-wallet";
+
+/// Event the app dispatches to collect wallets that loaded first
+pub const APP_READY_EVENT: &str = "wallet-standard:app-ready";
+
+/// Feature names defined by the standard and its Solana extension
+pub mod features {
+    pub const CONNECT: &str = "standard:connect";
+    pub const DISCONNECT: &str = "standard:disconnect";
+    pub const EVENTS: &str = "standard:events";
+    pub const SIGN_TRANSACTION: &str = "solana:signTransaction";
+    pub const SIGN_AND_SEND_TRANSACTION: &str = "solana:signAndSendTransaction";
+    pub const SIGN_MESSAGE: &str = "solana:signMessage";
+}
+
+/// Errors raised while discovering wallets
+#[derive(Debug, Clone, Error)]
+pub enum WalletStandardError {
+    #[error("No browser window available")]
+    NoWindow,
+
+    #[error("JavaScript error: {0}")]
+    Js(String),
+
+    #[error("The wallet does not implement {0}")]
+    MissingFeature(&'static str),
+
+    #[error("No wallet is connected")]
+    NotConnected,
+
+    #[error("The user rejected the request")]
+    Rejected,
+
+    #[error("The wallet has no account {0}")]
+    UnknownAccount(Pubkey),
+
+    #[error("The message decodes as a transaction and is not signed as text")]
+    MessageIsTransaction,
+
+    #[error("Transaction encoding failed: {0}")]
+    Message(#[from] MessageError),
+
+    #[error("The wallet returned a different transaction than it was asked to sign")]
+    TransactionChanged,
+}
+
+/// First byte of a version 0 transaction message
+const VERSION_0_PREFIX: u8 = 0x80;
+
+/// Refuse a message that is also a valid transaction or transaction message
+///
+/// A signature over such bytes authorizes the transaction, so a site asking
+/// to "sign in" with them could spend from the account. Wallets check this
+/// too, but not all of them. Bytes starting with the version 0 prefix are
+/// refused as well; no UTF-8 text starts with that byte.
+pub fn check_message(message: &[u8]) -> Result<(), WalletStandardError> {
+    let versioned = message.first() == Some(&VERSION_0_PREFIX);
+    if versioned
+        || Message::deserialize(message).is_ok()
+        || Transaction::deserialize(message).is_ok()
+    {
+        return Err(WalletStandardError::MessageIsTransaction);
+    }
+    Ok(())
+}
+
+/// What a registered wallet announced about itself
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalletInfo {
+    pub name: String,
+    /// Data URI of the wallet's icon
+    pub icon: String,
+    /// Wallet Standard version the wallet implements
+    pub version: String,
+    /// Chains such as `solana:mainnet`
+    pub chains: Vec<String>,
+    /// Feature names such as `solana:signTransaction`
+    pub features: Vec<String>,
+}
+
+impl WalletInfo {
+    /// Whether the wallet implements `feature`
+    pub fn supports(&self, feature: &str) -> bool {
+        self.features.iter().any(|f| f == feature)
+    }
+
+    /// Whether the wallet can connect and sign Solana transactions
+    pub fn is_solana_wallet(&self) -> bool {
+        self.chains.iter().any(|chain| chain.starts_with("solana:"))
+            && self.supports(features::CONNECT)
+            && (self.supports(features::SIGN_TRANSACTION)
+                || self.supports(features::SIGN_AND_SEND_TRANSACTION))
+    }
+}
+
+/// Registered wallets in registration order, each with a handle to the
+/// wallet object (the JS object on wasm)
+#[derive(Debug, Clone)]
+pub struct WalletRegistry<W = ()> {
+    wallets: Vec<(WalletInfo, W)>,
+}
+
+impl<W> Default for WalletRegistry<W> {
+    fn default() -> Self {
+        Self {
+            wallets: Vec::new(),
+        }
+    }
+}
+
+impl<W> WalletRegistry<W> {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a wallet, returning `false` if one with its name already was
+    pub fn register(&mut self, info: WalletInfo, wallet: W) -> bool {
+        if self
+            .wallets
+            .iter()
+            .any(|(known, _)| known.name == info.name)
+        {
+            return false;
+        }
+        self.wallets.push((info, wallet));
+        true
+    }
+
+    /// Every registered wallet
+    pub fn wallets(&self) -> impl Iterator<Item = &WalletInfo> {
+        self.wallets.iter().map(|(info, _)| info)
+    }
+
+    /// Wallets able to sign Solana transactions
+    pub fn solana_wallets(&self) -> impl Iterator<Item = &WalletInfo> {
+        self.wallets().filter(|info| info.is_solana_wallet())
+    }
+
+    /// The handle of the wallet called `name`
+    pub fn get(&self, name: &str) -> Option<&W> {
+        self.wallets
+            .iter()
+            .find(|(info, _)| info.name == name)
+            .map(|(_, wallet)| wallet)
+    }
+
+    /// Number of registered wallets
+    pub fn len(&self) -> usize {
+        self.wallets.len()
+    }
+
+    /// Whether no wallet has registered
+    pub fn is_empty(&self) -> bool {
+        self.wallets.is_empty()
+    }
+}
+
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+pub use browser::{connect, disconnect, discover, sign_message, sign_transaction, WalletDiscovery};
+
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+mod browser {
+    use super::{
+        features, WalletInfo, WalletRegistry, WalletStandardError, APP_READY_EVENT,
+        REGISTER_WALLET_EVENT,
+    };
+    use crate::domain::types::{Pubkey, Signature};
+    use js_sys::{Array, Function, Object, Promise, Reflect, Uint8Array};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use wasm_bindgen::prelude::*;
+    use wasm_bindgen::JsCast;
+    use wasm_bindgen_futures::JsFuture;
+
+    type Registry = Rc<RefCell<WalletRegistry<JsValue>>>;
+
+    /// A running discovery; stops listening for wallets when dropped
+    pub struct WalletDiscovery {
+        registry: Registry,
+        listener: Closure<dyn FnMut(web_sys::Event)>,
+        _register: Closure<dyn FnMut(JsValue) -> JsValue>,
+    }
+
+    impl WalletDiscovery {
+        /// Wallets registered so far
+        pub fn wallets(&self) -> Vec<WalletInfo> {
+            self.registry.borrow().wallets().cloned().collect()
+        }
+
+        /// The JS object of the wallet called `name`, for connecting and signing
+        pub fn wallet_object(&self, name: &str) -> Option<JsValue> {
+            self.registry.borrow().get(name).cloned()
+        }
+    }
+
+    impl Drop for WalletDiscovery {
+        fn drop(&mut self) {
+            if let Some(window) = web_sys::window() {
+                let _ = window.remove_event_listener_with_callback(
+                    REGISTER_WALLET_EVENT,
+                    self.listener.as_ref().unchecked_ref(),
+                );
+            }
+        }
+    }
+
+    /// Listen for Wallet Standard wallets, calling `on_register` for each new one
+    ///
+    /// Wallets that loaded before this call are collected through the
+    /// `wallet-standard:app-ready` event.
+    pub fn discover(
+        on_register: impl FnMut(&WalletInfo) + 'static,
+    ) -> Result<WalletDiscovery, WalletStandardError> {
+        let window = web_sys::window().ok_or(WalletStandardError::NoWindow)?;
+        let registry: Registry = Rc::default();
+        let on_register = Rc::new(RefCell::new(on_register));
+
+        let register = {
+            let registry = registry.clone();
+            Closure::<dyn FnMut(JsValue) -> JsValue>::new(move |wallet: JsValue| {
+                if let Some(info) = wallet_info(&wallet) {
+                    if registry.borrow_mut().register(info.clone(), wallet) {
+                        (on_register.borrow_mut())(&info);
+                    }
+                }
+                // `register` returns an unregister callback; wallets are kept
+                // for the lifetime of the discovery
+                Function::new_no_args("").into()
+            })
+        };
+        let api = Object::new();
+        Reflect::set(&api, &"register".into(), register.as_ref()).map_err(js_error)?;
+
+        let listener = {
+            let api = api.clone();
+            Closure::<dyn FnMut(web_sys::Event)>::new(move |event: web_sys::Event| {
+                let Ok(event) = event.dyn_into::<web_sys::CustomEvent>() else {
+                    return;
+                };
+                if let Ok(callback) = event.detail().dyn_into::<Function>() {
+                    let _ = callback.call1(&JsValue::UNDEFINED, &api);
+                }
+            })
+        };
+        window
+            .add_event_listener_with_callback(
+                REGISTER_WALLET_EVENT,
+                listener.as_ref().unchecked_ref(),
+            )
+            .map_err(js_error)?;
+
+        let init = web_sys::CustomEventInit::new();
+        init.set_detail(&api);
+        let ready = web_sys::CustomEvent::new_with_event_init_dict(APP_READY_EVENT, &init)
+            .map_err(js_error)?;
+        window.dispatch_event(&ready).map_err(js_error)?;
+
+        Ok(WalletDiscovery {
+            registry,
+            listener,
+            _register: register,
+        })
+    }
+
+    /// Ask `wallet` for its accounts through `standard:connect`
+    ///
+    /// With `silent`, a wallet the user has not approved the app with fails
+    /// instead of prompting, which is how a remembered session is restored.
+    pub async fn connect(
+        wallet: &JsValue,
+        silent: bool,
+    ) -> Result<Vec<Pubkey>, WalletStandardError> {
+        let input = Object::new();
+        Reflect::set(&input, &"silent".into(), &JsValue::from_bool(silent)).map_err(js_error)?;
+        let output = call_feature(wallet, features::CONNECT, "connect", &input).await?;
+
+        let accounts = Reflect::get(&output, &"accounts".into()).map_err(js_error)?;
+        Ok(Array::from(&accounts)
+            .iter()
+            .filter_map(|account| Reflect::get(&account, &"address".into()).ok())
+            .filter_map(|address| Pubkey::from_base58(&address.as_string()?).ok())
+            .collect())
+    }
+
+    /// End the app's connection through `standard:disconnect`, if supported
+    pub async fn disconnect(wallet: &JsValue) -> Result<(), WalletStandardError> {
+        match call_feature(
+            wallet,
+            features::DISCONNECT,
+            "disconnect",
+            &JsValue::UNDEFINED,
+        )
+        .await
+        {
+            Err(WalletStandardError::MissingFeature(_)) => Ok(()),
+            result => result.map(drop),
+        }
+    }
+
+    /// Sign `message` as `account` through `solana:signMessage`
+    ///
+    /// Messages that decode as transactions are refused, see [`check_message`].
+    ///
+    /// [`check_message`]: super::check_message
+    pub async fn sign_message(
+        wallet: &JsValue,
+        account: &Pubkey,
+        message: &[u8],
+    ) -> Result<Signature, WalletStandardError> {
+        super::check_message(message)?;
+        let input = Object::new();
+        let account_object = wallet_account(wallet, account)?;
+        Reflect::set(&input, &"account".into(), &account_object).map_err(js_error)?;
+        let bytes = Uint8Array::from(message);
+        Reflect::set(&input, &"message".into(), &bytes).map_err(js_error)?;
+        let output = call_feature(wallet, features::SIGN_MESSAGE, "signMessage", &input).await?;
+
+        let signature = first_output_bytes(&output, "signature")?;
+        let signature: [u8; 64] = signature
+            .try_into()
+            .map_err(|_| WalletStandardError::Js("signature is not 64 bytes".to_string()))?;
+        Ok(Signature::new(signature))
+    }
+
+    /// Have `account` sign the serialized `transaction` through
+    /// `solana:signTransaction`, returning the signed transaction's bytes
+    ///
+    /// The wallet may add signatures or, if the app allows it, change the
+    /// transaction, so the result should be decoded and checked again.
+    pub async fn sign_transaction(
+        wallet: &JsValue,
+        account: &Pubkey,
+        transaction: &[u8],
+    ) -> Result<Vec<u8>, WalletStandardError> {
+        let input = Object::new();
+        let account_object = wallet_account(wallet, account)?;
+        Reflect::set(&input, &"account".into(), &account_object).map_err(js_error)?;
+        let bytes = Uint8Array::from(transaction);
+        Reflect::set(&input, &"transaction".into(), &bytes).map_err(js_error)?;
+        let output = call_feature(
+            wallet,
+            features::SIGN_TRANSACTION,
+            "signTransaction",
+            &input,
+        )
+        .await?;
+        first_output_bytes(&output, "signedTransaction")
+    }
+
+    /// The `WalletAccount` object of `account`, which signing features take
+    fn wallet_account(wallet: &JsValue, account: &Pubkey) -> Result<JsValue, WalletStandardError> {
+        let accounts = Reflect::get(wallet, &"accounts".into()).map_err(js_error)?;
+        let address = account.to_base58();
+        Array::from(&accounts)
+            .iter()
+            .find(|object| {
+                Reflect::get(object, &"address".into())
+                    .ok()
+                    .and_then(|value| value.as_string())
+                    .is_some_and(|value| value == address)
+            })
+            .ok_or(WalletStandardError::UnknownAccount(*account))
+    }
+
+    /// Bytes of `field` in the first output of a signing feature
+    fn first_output_bytes(output: &JsValue, field: &str) -> Result<Vec<u8>, WalletStandardError> {
+        let first = Array::from(output).get(0);
+        let bytes = Reflect::get(&first, &field.into()).map_err(js_error)?;
+        if !bytes.is_instance_of::<Uint8Array>() {
+            return Err(WalletStandardError::Js(format!(
+                "no {} in the output",
+                field
+            )));
+        }
+        Ok(Uint8Array::from(bytes).to_vec())
+    }
+
+    /// Call `method` of `feature` on `wallet` and await the promise it returns
+    async fn call_feature(
+        wallet: &JsValue,
+        feature: &'static str,
+        method: &str,
+        input: &JsValue,
+    ) -> Result<JsValue, WalletStandardError> {
+        let features = Reflect::get(wallet, &"features".into()).map_err(js_error)?;
+        let implementation = Reflect::get(&features, &feature.into())
+            .ok()
+            .filter(|implementation| implementation.is_object())
+            .ok_or(WalletStandardError::MissingFeature(feature))?;
+        let function = Reflect::get(&implementation, &method.into())
+            .ok()
+            .and_then(|function| function.dyn_into::<Function>().ok())
+            .ok_or(WalletStandardError::MissingFeature(feature))?;
+        let promise = function
+            .call1(&implementation, input)
+            .map_err(js_error)?
+            .dyn_into::<Promise>()
+            .map_err(js_error)?;
+        JsFuture::from(promise).await.map_err(js_error)
+    }
+
+    /// Read the announced properties of a wallet object
+    fn wallet_info(wallet: &JsValue) -> Option<WalletInfo> {
+        let string = |key: &str| {
+            Reflect::get(wallet, &key.into())
+                .ok()
+                .and_then(|value| value.as_string())
+        };
+        let chains = Reflect::get(wallet, &"chains".into())
+            .ok()
+            .filter(Array::is_array)
+            .map(|chains| {
+                Array::from(&chains)
+                    .iter()
+                    .filter_map(|chain| chain.as_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let features = Reflect::get(wallet, &"features".into())
+            .ok()
+            .and_then(|features| features.dyn_into::<Object>().ok())
+            .map(|features| {
+                Object::keys(&features)
+                    .iter()
+                    .filter_map(|feature| feature.as_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Some(WalletInfo {
+            name: string("name")?,
+            icon: string("icon").unwrap_or_default(),
+            version: string("version").unwrap_or_default(),
+            chains,
+            features,
+        })
+    }
+
+    fn js_error(error: JsValue) -> WalletStandardError {
+        WalletStandardError::Js(format!("{:?}", error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wallet(name: &str, chains: &[&str], wallet_features: &[&str]) -> WalletInfo {
+        WalletInfo {
+            name: name.to_string(),
+            icon: "data:image/svg+xml;base64,PHN2Zy8+".to_string(),
+            version: "1.0.0".to_string(),
+            chains: chains.iter().map(|chain| chain.to_string()).collect(),
+            features: wallet_features.iter().map(|f| f.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_registry_filters_solana_wallets() {
+        let mut registry = WalletRegistry::new();
+        let solana = wallet(
+            "Backpack",
+            &["solana:mainnet", "solana:devnet"],
+            &[
+                features::CONNECT,
+                features::SIGN_TRANSACTION,
+                features::SIGN_MESSAGE,
+            ],
+        );
+        assert!(registry.register(solana.clone(), 1));
+        assert!(!registry.register(solana.clone(), 2), "names are unique");
+        assert!(registry.register(wallet("EthOnly", &["eip155:1"], &[features::CONNECT]), 3));
+        assert!(registry.register(
+            wallet("ReadOnly", &["solana:mainnet"], &[features::CONNECT]),
+            4
+        ));
+
+        assert_eq!(registry.len(), 3);
+        assert_eq!(registry.get("Backpack"), Some(&1));
+        let names: Vec<&str> = registry
+            .solana_wallets()
+            .map(|info| info.name.as_str())
+            .collect();
+        assert_eq!(names, ["Backpack"]);
+        assert!(solana.supports(features::SIGN_MESSAGE));
+    }
+
+    #[test]
+    fn test_check_message_refuses_transactions() {
+        use crate::application::flows::TransactionBuilder;
+        use crate::domain::programs::system;
+        use crate::domain::types::Hash;
+
+        assert!(check_message(b"Sign in to example.com\nNonce: 8f3a").is_ok());
+        let payer = Pubkey::new([1; 32]);
+        let transaction = TransactionBuilder::new(payer)
+            .instruction(system::transfer(payer, Pubkey::new([2; 32]), 1))
+            .build(Hash::new([3; 32]))
+            .unwrap();
+        for bytes in [
+            transaction.message.serialize().unwrap(),
+            transaction.serialize().unwrap(),
+        ] {
+            assert!(matches!(
+                check_message(&bytes),
+                Err(WalletStandardError::MessageIsTransaction)
+            ));
+        }
+    }
+}
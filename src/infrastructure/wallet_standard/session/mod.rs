@@ -0,0 +1,142 @@
+//! Remembered wallet connections
+//!
+//! Browser wallets forget nothing between reloads, but the app does: without
+//! help every reload shows "Connect wallet" until the user clicks again.
+//! [`WalletSessionStore`] keeps the last connected wallet and account in a
+//! [`StorageAdapter`], so at startup the app can ask that wallet to reconnect
+//! silently (the Wallet Standard counterpart of Phantom's `onlyIfTrusted`)
+//! and show [`WalletConnectionState::Restoring`] meanwhile instead of a
+//! disconnected state.
+
+use crate::domain::types::Pubkey;
+use crate::infrastructure::storage::{StorageAdapter, StorageError};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+
+/// Storage key of the remembered session
+const STORAGE_KEY: &str = "gloo_solana:wallet_session";
+
+/// The wallet and account the app was last connected with
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WalletSession {
+    /// Name the wallet registered under, e.g. `Phantom`
+    pub wallet: String,
+    pub public_key: Pubkey,
+}
+
+/// Where the app stands with the user's wallet, for connect buttons
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WalletConnectionState {
+    /// Silently reconnecting a remembered session at startup
+    Restoring(WalletSession),
+    /// Waiting for the user to approve a connection
+    Connecting {
+        wallet: String,
+    },
+    Connected(WalletSession),
+    Disconnected,
+}
+
+impl WalletConnectionState {
+    /// The state to start in: restoring `session` if one is remembered
+    pub fn initial(session: Option<WalletSession>) -> Self {
+        session.map_or(Self::Disconnected, Self::Restoring)
+    }
+
+    /// The account to show, also while a remembered session is restored
+    pub fn public_key(&self) -> Option<Pubkey> {
+        match self {
+            Self::Restoring(session) | Self::Connected(session) => Some(session.public_key),
+            _ => None,
+        }
+    }
+
+    /// Whether the wallet is connected and can sign
+    pub fn is_connected(&self) -> bool {
+        matches!(self, Self::Connected(_))
+    }
+}
+
+impl fmt::Display for WalletConnectionState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Restoring(session) => write!(f, "Reconnecting to {}", session.wallet),
+            Self::Connecting { wallet } => write!(f, "Connecting to {}", wallet),
+            Self::Connected(session) => write!(f, "Connected to {}", session.wallet),
+            Self::Disconnected => write!(f, "Disconnected"),
+        }
+    }
+}
+
+/// Keeps the last [`WalletSession`] across reloads
+#[derive(Clone)]
+pub struct WalletSessionStore {
+    storage: Arc<dyn StorageAdapter>,
+}
+
+impl WalletSessionStore {
+    /// Create a store persisting to `storage`
+    pub fn new(storage: Arc<dyn StorageAdapter>) -> Self {
+        Self { storage }
+    }
+
+    /// The remembered session, `None` if there is none or it is unreadable
+    pub fn load(&self) -> Result<Option<WalletSession>, StorageError> {
+        let Some(raw) = self.storage.get(STORAGE_KEY)? else {
+            return Ok(None);
+        };
+        Ok(serde_json::from_str(&raw).ok())
+    }
+
+    /// Remember `session` for the next start
+    pub fn save(&self, session: &WalletSession) -> Result<(), StorageError> {
+        let raw = serde_json::to_string(session).map_err(|e| StorageError::Io(e.to_string()))?;
+        self.storage.set(STORAGE_KEY, &raw)
+    }
+
+    /// Forget the session, e.g. when the user disconnects
+    pub fn clear(&self) -> Result<(), StorageError> {
+        self.storage.remove(STORAGE_KEY)
+    }
+}
+
+impl fmt::Debug for WalletSessionStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WalletSessionStore").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::storage::MemoryStorage;
+
+    #[test]
+    fn test_session_survives_a_reload() {
+        let storage = Arc::new(MemoryStorage::new());
+        let store = WalletSessionStore::new(storage.clone());
+        assert_eq!(store.load().unwrap(), None);
+        assert_eq!(
+            WalletConnectionState::initial(None),
+            WalletConnectionState::Disconnected
+        );
+
+        let session = WalletSession {
+            wallet: "Backpack".to_string(),
+            public_key: Pubkey::new([3; 32]),
+        };
+        store.save(&session).unwrap();
+        let reloaded = WalletSessionStore::new(storage.clone()).load().unwrap();
+        let state = WalletConnectionState::initial(reloaded);
+        assert_eq!(state, WalletConnectionState::Restoring(session));
+        assert_eq!(state.public_key(), Some(Pubkey::new([3; 32])));
+        assert!(!state.is_connected());
+
+        storage.set(STORAGE_KEY, "not json").unwrap();
+        assert_eq!(store.load().unwrap(), None);
+        store.clear().unwrap();
+        assert_eq!(storage.get(STORAGE_KEY).unwrap(), None);
+    }
+}
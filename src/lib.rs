@@ -12,16 +12,39 @@
 //! - DDD architecture with SOLID principles
 //! - Dioxus integration support
 //!
+//! # Cargo Features
+//!
+//! - `native` (default): reqwest transport and tokio timers for non-wasm targets
+//! - `anchor` (default): Anchor IDL decoding, the IDL registry and `Anchor.toml` workspaces
+//! - `token` (default): SPL Token helpers
+//! - `wasm`: browser extras such as `localStorage` persistence
+//! - `dioxus`: Dioxus hooks and components
+//! - `fullstack`: fetch component data during SSR and hydrate it on the client
+//! - `keystore`: password-encrypted keypair storage
+//! - `mobile-wallet`: sign with Phantom or Solflare apps through deep links
+//! - `config-toml`: read [`ClientConfig`] files written in TOML
+//! - `opentelemetry`: export request metrics and spans through OpenTelemetry
+//! - `jupiter`: quote and build token swaps through the Jupiter aggregator
+//! - `oracles`: decode Pyth and Switchboard prices and value balances in USD
+//! - `ledger`: sign with a Ledger hardware wallet over USB HID (native only)
+//! - `simd-json`: parse RPC responses with simd-json (native only)
+//! - `js-json`: parse RPC responses with the browser's `JSON.parse` (wasm only)
+//!
+//! A minimal browser build uses `--no-default-features` on `wasm32`.
+//!
+//! [`prelude`] re-exports the types most apps need.
+//!
 //! # Quick Start
 //!
 //! ```rust,no_run
-//! use gloo_solana::{RpcClientBuilder, surfpool_network, CommitmentLevel, constants::SYSTEM_PROGRAM_ID};
+//! use gloo_solana::prelude::*;
+//! use gloo_solana::constants::SYSTEM_PROGRAM_ID;
 //!
 //! #[tokio::main]
 //! async fn main() -> Result<(), Box<dyn std::error::Error>> {
 //!     let client = RpcClientBuilder::new(surfpool_network().endpoint())
 //!         .commitment(CommitmentLevel::Confirmed)
-//!         .build();
+//!         .build()?;
 //!
 //!     let balance = client.get_balance(&SYSTEM_PROGRAM_ID).await?;
 //!     println!("System program balance: {} lamports", balance);
@@ -30,20 +53,141 @@
 //! }
 //! ```
 
+// A panic takes down the whole WASM app, so failures are returned as errors
+#![deny(clippy::unwrap_used, clippy::expect_used)]
+
+#[cfg(all(not(target_arch = "wasm32"), not(feature = "native")))]
+compile_error!("gloo_solana needs the `native` feature on non-wasm32 targets");
+
 pub mod application;
 pub mod domain;
 pub mod infrastructure;
+pub mod prelude;
 
 // Re-export commonly used types
+pub use application::confirmation::{
+    ConfirmationConfig, ConfirmationError, ConfirmationStrategy, SignatureProgress,
+    TransactionConfirmer,
+};
+pub use application::decoders::{
+    DecodedAccount, DecodedInstruction, DecoderRegistry, FnDecoder, InspectedInstruction,
+    ProgramDecoder, SystemDecoder,
+};
+#[cfg(feature = "oracles")]
+pub use application::decoders::{PythDecoder, SwitchboardDecoder};
+pub use application::estimate::{
+    analyze_transaction, dry_run, estimate_transaction, AccountAccess, AccountEffect, DryRun,
+    EstimateError, EstimateWarning, TransactionAnalysis, TransactionEstimate,
+};
+pub use application::flows::{
+    anchor_instruction, create_and_initialize_anchor_account, TransactionBuilder,
+};
+#[cfg(feature = "token")]
+pub use application::flows::{create_ata_and_transfer, wrap_sol_and_swap};
+#[cfg(feature = "jupiter")]
+pub use application::integrations::jupiter::{
+    JupiterClient, JupiterError, Quote, QuoteRequest, SwapMode, SwapTransaction,
+};
+pub use application::offline::{EnvelopeError, TransactionEnvelope, ENVELOPE_VERSION};
+pub use application::overlay::{AccountPatch, LocalOverlay};
+pub use application::sender::{
+    idempotency_key, PendingTransaction, PendingTransactions, SendConfig, SendError,
+    SentTransaction, TransactionSender,
+};
+pub use application::services::assertion::{AssertionError, AssertionService};
+#[cfg(feature = "token")]
+pub use application::services::audit::{
+    AccountAuditService, AuditFinding, AuditReport, AuditedAccount,
+};
+pub use application::services::balance_history::{
+    BalanceHistory, BalanceHistoryService, BalancePoint,
+};
+pub use application::services::clock::{
+    ClockEstimate, ClockService, EpochStatus, SlotTimeEstimator,
+};
+pub use application::services::data_account::{
+    DataAccountError, DataAccountService, WriteWindow, WrittenChunk,
+};
+pub use application::services::fixtures::{DevFixtures, FixtureConfig, FixtureMint};
+pub use application::services::fork::{ForkError, ForkReport, SurfpoolForkService};
+#[cfg(feature = "oracles")]
+pub use application::services::price::{PriceError, PriceService};
+pub use application::services::rewards::{RewardsHistory, RewardsService};
+pub use application::services::snapshot::{SnapshotError, SnapshotService, StateSnapshot};
+pub use application::services::stake::{StakeAccount, StakeService};
+#[cfg(feature = "token")]
+pub use application::services::token::{
+    HolderConcentration, MintInfo, MintInfoError, MintInfoService, PlannedStep, TokenAnalytics,
+    TokenTransferError, TokenTransferService, TransferConfig, TransferPlan,
+};
+pub use application::services::transfer::{SolTransfer, SolTransferConfig, SolTransferError};
+pub use application::solana_pay::{
+    SolanaPayService, SolanaPayUrl, TransactionRequest, TransactionRequestClient, TransferRequest,
+};
+#[cfg(feature = "svm-lite")]
+pub use application::svm::{AccountExecutor, ExecutionError, ExecutorError};
+pub use application::watch::{
+    AccountChange, BlockUpdate, ChainWatcher, IndexUpdate, LogEvent, LogFilter, ProgramIndex,
+    SlotUpdate, WatchConfig,
+};
+pub use domain::diff::{AccountDiff, AccountState};
+pub use domain::discriminator::{Discriminator, DiscriminatorError};
+pub use domain::errors::{InstructionError, TransactionError};
+pub use domain::layout::{AccountLayout, BorshSize};
+pub use domain::logs::{LogLevel, LogLine};
+#[cfg(feature = "oracles")]
+pub use domain::oracles::{
+    OracleError, OraclePrice, OracleSource, PythPriceUpdate, SwitchboardFeed,
+};
 pub use domain::types::constants;
 pub use domain::types::{Hash, HashError, Pubkey, PubkeyError, Signature, SignatureError};
-pub use infrastructure::http::HttpError;
+pub use domain::validation::{
+    format_units, validate_lamports_amount, validate_memo_length, validate_pubkey_input,
+    ValidationError,
+};
+#[cfg(feature = "anchor")]
+pub use infrastructure::anchor_workspace::AnchorWorkspace;
+pub use infrastructure::cache::{CachePolicy, TransactionCache};
+pub use infrastructure::cancel::CancelToken;
+pub use infrastructure::cluster::{ClusterCapabilities, ClusterKind};
+pub use infrastructure::config;
+pub use infrastructure::config::{ClientConfig, ConfigError};
+pub use infrastructure::explorer::{abbreviate_pubkey, abbreviate_signature, Explorer};
 #[cfg(target_arch = "wasm32")]
 pub use infrastructure::http::WasmHttpClient;
+pub use infrastructure::http::{
+    HttpError, Recording, RecordingHttpClient, ReplayHttpClient, DEFAULT_USER_AGENT,
+};
+#[cfg(not(target_arch = "wasm32"))]
+pub use infrastructure::http::{ProxyConfig, TransportConfig};
+#[cfg(feature = "keystore")]
+pub use infrastructure::keystore::Keystore;
+pub use infrastructure::metrics::{ClientMetrics, MetricsSnapshot};
+#[cfg(feature = "mobile-wallet")]
+pub use infrastructure::mobile_wallet::{DeepLinkWallet, MobileWallet, MobileWalletError};
+pub use infrastructure::network::{surfpool_network, CustomNetwork, CustomNetworkBuilder, Network};
+pub use infrastructure::rpc::filters::{Filter, FilterBuilder};
 pub use infrastructure::rpc::{
-    surfpool_network, Account, CommitmentLevel, LatestBlockhash, Network, RpcClientBuilder,
-    RpcError, SolanaRpcClient,
+    verify_transaction_signatures, Account, AccountReadConfig, Cheatcodes, CommitmentLevel,
+    ConsistentReader, EpochInfo, InflationReward, LatestBlockhash, NetworkRegistry, ReadScheduler,
+    RpcApi, RpcClientBuilder, RpcContextConfig, RpcError, RpcInterceptor,
+    SignatureVerificationError, SolanaRpcClient, WithContext,
+};
+#[cfg(all(feature = "ledger", not(target_arch = "wasm32")))]
+pub use infrastructure::signer::LedgerSigner;
+#[cfg(feature = "mnemonic")]
+pub use infrastructure::signer::{generate_mnemonic, MnemonicError};
+pub use infrastructure::signer::{DerivationPath, Keypair, SecretBytes, Signer, SignerError};
+#[cfg(all(feature = "native", not(target_arch = "wasm32")))]
+pub use infrastructure::signer::{VanityError, VanityGrinder, VanityMatch, VanityPattern};
+#[cfg(all(feature = "opentelemetry", not(target_arch = "wasm32")))]
+pub use infrastructure::telemetry::OtelMetricsSink;
+#[cfg(feature = "opentelemetry")]
+pub use infrastructure::telemetry::{BufferedTelemetry, TelemetryRecord};
+pub use infrastructure::time::{
+    format_duration, format_timestamp_relative, format_utc, slots_to_approx_duration,
 };
+pub use infrastructure::visibility;
 
 #[cfg(feature = "dioxus")]
 pub mod dioxus_integration;
@@ -55,29 +199,31 @@ pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 pub const DEFAULT_COMMITMENT: CommitmentLevel = CommitmentLevel::Confirmed;
 
 /// Create a new RPC client for the specified network
-pub fn create_client(network: Network) -> SolanaRpcClient {
+///
+/// Fails if the native HTTP client cannot be created.
+pub fn create_client(network: Network) -> Result<SolanaRpcClient, RpcError> {
     RpcClientBuilder::new(network.endpoint())
         .commitment(DEFAULT_COMMITMENT)
         .build()
 }
 
 /// Create a new RPC client for surfpool (simnet)
-pub fn create_surfpool_client() -> SolanaRpcClient {
+pub fn create_surfpool_client() -> Result<SolanaRpcClient, RpcError> {
     create_client(surfpool_network())
 }
 
 /// Create a new RPC client for mainnet
-pub fn create_mainnet_client() -> SolanaRpcClient {
+pub fn create_mainnet_client() -> Result<SolanaRpcClient, RpcError> {
     create_client(Network::Mainnet)
 }
 
 /// Create a new RPC client for devnet
-pub fn create_devnet_client() -> SolanaRpcClient {
+pub fn create_devnet_client() -> Result<SolanaRpcClient, RpcError> {
     create_client(Network::Devnet)
 }
 
 /// Create a new RPC client for testnet
-pub fn create_testnet_client() -> SolanaRpcClient {
+pub fn create_testnet_client() -> Result<SolanaRpcClient, RpcError> {
     create_client(Network::Testnet)
 }
 
@@ -92,10 +238,10 @@ mod tests {
 
     #[test]
     fn test_create_clients() {
-        let _surf_client = create_surfpool_client();
-        let _mainnet_client = create_mainnet_client();
-        let _devnet_client = create_devnet_client();
-        let _testnet_client = create_testnet_client();
+        let _surf_client = create_surfpool_client().unwrap();
+        let _mainnet_client = create_mainnet_client().unwrap();
+        let _devnet_client = create_devnet_client().unwrap();
+        let _testnet_client = create_testnet_client().unwrap();
     }
 
     #[test]
@@ -0,0 +1,55 @@
+//! Common imports in one place
+//!
+//! Most apps reach for the same handful of types: a client and its network,
+//! keys and signatures, a signer, a transaction builder and the services that
+//! send, confirm and decode. `use gloo_solana::prelude::*;` brings those in
+//! without spelling out module paths; everything else stays at the crate root
+//! or under its layer.
+//!
+//! ```rust,no_run
+//! use gloo_solana::prelude::*;
+//!
+//! # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = RpcClientBuilder::new(Network::Devnet.endpoint())
+//!     .commitment(CommitmentLevel::Confirmed)
+//!     .build()?;
+//! let payer = Keypair::new()?;
+//! let recipient = Pubkey::new([1; 32]);
+//!
+//! let blockhash = client.get_latest_blockhash().await?;
+//! let transaction = TransactionBuilder::new(payer.pubkey())
+//!     .instruction(system::transfer(payer.pubkey(), recipient, 1_000))
+//!     .build(blockhash.blockhash)?;
+//! # Ok(())
+//! # }
+//! ```
+
+pub use crate::account_layout;
+pub use crate::application::confirmation::{
+    ConfirmationConfig, ConfirmationError, ConfirmationStrategy, TransactionConfirmer,
+};
+pub use crate::application::decoders::{DecoderRegistry, ProgramDecoder};
+pub use crate::application::flows::{anchor_instruction, TransactionBuilder};
+pub use crate::application::sender::{SendConfig, SendError, TransactionSender};
+#[cfg(feature = "anchor")]
+pub use crate::application::services::idl::IdlRegistry;
+#[cfg(feature = "token")]
+pub use crate::application::services::token::{
+    MintInfoService, TokenTransferError, TokenTransferService, TransferConfig,
+};
+pub use crate::domain::discriminator::Discriminator;
+pub use crate::domain::layout::AccountLayout;
+pub use crate::domain::programs::system;
+#[cfg(feature = "token")]
+pub use crate::domain::programs::token;
+pub use crate::domain::types::constants;
+pub use crate::domain::types::{Hash, Pubkey, Signature};
+#[cfg(feature = "anchor")]
+pub use crate::infrastructure::anchor_workspace::AnchorWorkspace;
+pub use crate::infrastructure::network::{surfpool_network, CustomNetwork, Network};
+pub use crate::infrastructure::rpc::filters::{Filter, FilterBuilder};
+pub use crate::infrastructure::rpc::{
+    Account, AccountReadConfig, CommitmentLevel, LatestBlockhash, NetworkRegistry, RpcApi,
+    RpcClientBuilder, RpcContextConfig, RpcError, SolanaRpcClient,
+};
+pub use crate::infrastructure::signer::{Keypair, Signer, SignerError};
@@ -0,0 +1,162 @@
+//! Tests shared by the native and `wasm32-unknown-unknown` builds
+//!
+//! RPC calls are answered by a [`ReplayHttpClient`] instead of `fetch`, so the
+//! suite needs no network and runs headless. Run it in a browser with
+//! `wasm-pack test --headless --chrome -- --test wasm`, or natively with
+//! `cargo test --test wasm`.
+
+use gloo_solana::infrastructure::http::fixtures::{Fixture, FixtureMethod};
+use gloo_solana::infrastructure::rpc::types::{RpcMessage, RpcRequest, RpcResponse};
+use gloo_solana::infrastructure::storage::{MemoryStorage, StorageAdapter};
+use gloo_solana::infrastructure::time::{format_relative_to, unix_timestamp, Instant};
+use gloo_solana::{
+    HttpError, Pubkey, ReplayHttpClient, RpcClientBuilder, RpcError, SolanaRpcClient,
+};
+use serde_json::{json, Value};
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen_test::wasm_bindgen_test;
+
+/// A recorded answer to `request`
+fn fixture(request: RpcRequest, result: Value) -> Fixture {
+    Fixture {
+        method: FixtureMethod::Post,
+        url: "http://localhost:8899".to_string(),
+        request: Some(serde_json::to_value(request).unwrap()),
+        response: serde_json::to_value(RpcResponse::success(1, result)).unwrap(),
+    }
+}
+
+/// A client answering only `fixtures`
+fn client(fixtures: Vec<Fixture>) -> SolanaRpcClient {
+    RpcClientBuilder::new("http://localhost:8899")
+        .replay(ReplayHttpClient::new(fixtures))
+        .build()
+        .unwrap()
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+#[cfg_attr(not(target_arch = "wasm32"), tokio::test)]
+async fn rpc_client_reads_through_test_double() {
+    let owner = Pubkey::new([7; 32]);
+    let account = Pubkey::new([8; 32]);
+    let client = client(vec![
+        fixture(
+            RpcRequest::new("getBalance").param(owner.to_base58()),
+            json!({ "context": { "slot": 5 }, "value": 42 }),
+        ),
+        fixture(
+            RpcRequest::new("getAccountInfo")
+                .param(account.to_base58())
+                .param(json!({ "encoding": "base64" })),
+            json!({
+                "context": { "slot": 5 },
+                "value": {
+                    "lamports": 1_000,
+                    "owner": owner.to_base58(),
+                    "data": ["AQID", "base64"],
+                    "executable": false,
+                    "rentEpoch": 0,
+                    "space": 3
+                }
+            }),
+        ),
+    ]);
+
+    assert_eq!(client.get_balance(&owner).await.unwrap(), 42);
+    let info = client.get_account_info(&account).await.unwrap().unwrap();
+    assert_eq!((info.owner, info.data), (owner, vec![1, 2, 3]));
+    assert!(matches!(
+        client.get_slot().await,
+        Err(RpcError::Http(HttpError::FixtureMissing(_)))
+    ));
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+#[cfg_attr(not(target_arch = "wasm32"), test)]
+fn serde_types_round_trip() {
+    let pubkey = Pubkey::new([3; 32]);
+    let encoded = serde_json::to_string(&pubkey).unwrap();
+    assert_eq!(serde_json::from_str::<Pubkey>(&encoded).unwrap(), pubkey);
+
+    let batch = RpcMessage::Batch(vec![
+        RpcRequest::new("getSlot"),
+        RpcRequest::new("getBlockHeight").id(2),
+    ]);
+    let decoded: RpcMessage<RpcRequest> =
+        serde_json::from_str(&serde_json::to_string(&batch).unwrap()).unwrap();
+    assert_eq!(decoded, batch);
+}
+
+/// `std::time` panics on wasm32; the crate's clock must not
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+#[cfg_attr(not(target_arch = "wasm32"), test)]
+fn clock_and_storage_work_without_std_time() {
+    let start = Instant::now();
+    assert!(unix_timestamp() > 1_600_000_000);
+    assert!(start.elapsed().as_secs() < 60);
+    assert_eq!(format_relative_to(100, 220), "2 minutes ago");
+
+    let storage = MemoryStorage::new();
+    storage.set("gloo_solana:test:a", "1").unwrap();
+    assert_eq!(
+        storage.keys("gloo_solana:test:").unwrap(),
+        ["gloo_solana:test:a"]
+    );
+}
+
+#[cfg(feature = "dioxus")]
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+#[cfg_attr(not(target_arch = "wasm32"), test)]
+fn balance_totals_wait_for_loading_queries() {
+    use gloo_solana::dioxus_integration::balances::total_balance;
+    use gloo_solana::dioxus_integration::store::Query;
+    use std::collections::HashMap;
+
+    let mut balances = HashMap::new();
+    balances.insert(Pubkey::new([1; 32]), Query::Ready(5));
+    balances.insert(Pubkey::new([2; 32]), Query::Loading);
+    assert_eq!(total_balance(&balances), (5, true));
+    balances.insert(Pubkey::new([2; 32]), Query::Failed("offline".to_string()));
+    assert_eq!(total_balance(&balances), (5, false));
+}
+
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+#[wasm_bindgen_test]
+async fn mock_wallet_signs_through_the_wallet_standard() {
+    use gloo_solana::domain::message::Transaction;
+    use gloo_solana::domain::programs::system;
+    use gloo_solana::infrastructure::wallet_standard::{
+        connect, discover, mock, sign_transaction, MockWallet, MockWalletConfig,
+    };
+    use gloo_solana::{Hash, Keypair, Signer, TransactionBuilder};
+    use std::rc::Rc;
+
+    let keypair = Keypair::from_seed(&[9; 32]);
+    let wallet = Rc::new(MockWallet::new(
+        keypair.clone(),
+        MockWalletConfig::new().name("E2E Wallet"),
+    ));
+    mock::register(wallet.clone()).unwrap();
+    let discovery = discover(|_| {}).unwrap();
+    let object = discovery.wallet_object("E2E Wallet").unwrap();
+
+    assert_eq!(
+        connect(&object, false).await.unwrap(),
+        vec![keypair.pubkey()]
+    );
+    let transaction = TransactionBuilder::new(keypair.pubkey())
+        .instruction(system::transfer(keypair.pubkey(), Pubkey::new([1; 32]), 1))
+        .build(Hash::new([2; 32]))
+        .unwrap();
+    let signed = sign_transaction(
+        &object,
+        &keypair.pubkey(),
+        &transaction.serialize().unwrap(),
+    )
+    .await
+    .unwrap();
+    let signed = Transaction::deserialize(&signed).unwrap();
+    assert_eq!(signed.message, transaction.message);
+    assert!(wallet.is_connected());
+}
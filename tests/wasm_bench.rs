@@ -0,0 +1,82 @@
+//! Browser micro-benchmarks of the client hot paths
+//!
+//! Criterion does not run on `wasm32-unknown-unknown`, so these time a fixed
+//! number of iterations and log the mean to the console. Run with
+//! `wasm-pack test --headless --chrome --release -- --test wasm_bench`.
+
+#![cfg(target_arch = "wasm32")]
+
+use base64::{engine::general_purpose, Engine as _};
+use gloo_solana::application::flows::TransactionBuilder;
+use gloo_solana::domain::message::Transaction;
+use gloo_solana::domain::programs::system;
+use gloo_solana::infrastructure::json::{JsonParser, SerdeJsonParser};
+use gloo_solana::infrastructure::time::Instant;
+use gloo_solana::{Hash, Pubkey};
+use serde_json::json;
+use wasm_bindgen_test::{console_log, wasm_bindgen_test, wasm_bindgen_test_configure};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+/// Run `f` `iterations` times after a short warm-up and log the mean time
+fn bench<T>(name: &str, iterations: u32, mut f: impl FnMut() -> T) {
+    for _ in 0..iterations / 10 {
+        std::hint::black_box(f());
+    }
+    let start = Instant::now();
+    for _ in 0..iterations {
+        std::hint::black_box(f());
+    }
+    let mean = start.elapsed() / iterations;
+    console_log!("{}: {:?}/iter over {} iterations", name, mean, iterations);
+}
+
+#[wasm_bindgen_test]
+fn bench_base58() {
+    let pubkey = Pubkey::new([7; 32]);
+    let encoded = pubkey.to_base58();
+    bench("pubkey_encode", 10_000, || pubkey.to_base58());
+    bench("pubkey_decode", 10_000, || {
+        Pubkey::from_base58(&encoded).unwrap()
+    });
+}
+
+#[wasm_bindgen_test]
+fn bench_message_round_trip() {
+    let payer = Pubkey::new([1; 32]);
+    let transaction = (0..20u8)
+        .fold(TransactionBuilder::new(payer), |builder, i| {
+            builder.instruction(system::transfer(payer, Pubkey::new([i + 2; 32]), 5))
+        })
+        .build(Hash::new([3; 32]))
+        .unwrap();
+    let bytes = transaction.serialize().unwrap();
+    bench("serialize_20_transfers", 2_000, || {
+        transaction.serialize().unwrap()
+    });
+    bench("deserialize_20_transfers", 2_000, || {
+        Transaction::deserialize(&bytes).unwrap()
+    });
+}
+
+#[wasm_bindgen_test]
+fn bench_parse_program_accounts() {
+    let accounts: Vec<_> = (0..1_000)
+        .map(|i| {
+            json!({
+                "pubkey": bs58::encode([(i % 256) as u8; 32]).into_string(),
+                "account": {
+                    "lamports": 2_039_280u64,
+                    "owner": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+                    "data": [general_purpose::STANDARD.encode([(i % 251) as u8; 165]), "base64"],
+                    "executable": false,
+                    "rentEpoch": 0,
+                }
+            })
+        })
+        .collect();
+    let body = json!({ "jsonrpc": "2.0", "result": accounts, "id": 1 }).to_string();
+    bench("parse_1000_program_accounts", 20, || {
+        SerdeJsonParser.parse(body.clone()).unwrap()
+    });
+}